@@ -0,0 +1,110 @@
+//!
+//! Benchmarks for parsing and verifying compact JWTs, as done by high-throughput resource servers
+//! and relying parties.
+//!
+//! In order to run the benchmarks call:
+//!
+//! ```sh
+//! cargo bench --bench jwt
+//! ```
+//!
+
+use chrono::{TimeZone, Utc};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use openidconnect::core::{
+    CoreHmacKey, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKeySet,
+    CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm,
+};
+use openidconnect::{
+    Audience, ClientId, ClientSecret, EmptyAdditionalClaims, EndUserEmail, IssuerUrl,
+    JsonWebTokenHeader, Nonce, StandardClaims, SubjectIdentifier,
+};
+
+type CoreJsonWebTokenHeader = JsonWebTokenHeader<
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+fn signed_id_token() -> String {
+    let claims = CoreIdTokenClaims::new(
+        IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+        vec![Audience::new("s6BhdRkqt3".to_string())],
+        Utc.timestamp(1311281970, 0),
+        Utc.timestamp(1311280970, 0),
+        StandardClaims::new(SubjectIdentifier::new("24400320".to_string()))
+            .set_email(Some(EndUserEmail::new("janedoe@example.com".to_string()))),
+        EmptyAdditionalClaims {},
+    );
+    let id_token = CoreIdToken::new(
+        claims,
+        &CoreHmacKey::new("secret"),
+        CoreJwsSigningAlgorithm::HmacSha256,
+        None,
+        None,
+    )
+    .unwrap();
+    serde_json::to_value(&id_token)
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+// Previous approach: call sites wrapped a copy of the token in a `serde_json::Value` and
+// deserialized from it, which copied the token again, collected its parts into a `Vec`, and
+// reassembled the signing input from them.
+fn parse_via_json_value(
+    id_token: &str,
+) -> (CoreJsonWebTokenHeader, CoreIdTokenClaims, Vec<u8>, String) {
+    let value = serde_json::Value::String(id_token.to_string());
+    let raw_token = value.as_str().unwrap().to_string();
+    let parts = raw_token.split('.').collect::<Vec<_>>();
+    assert_eq!(parts.len(), 3);
+
+    let decode = |part: &str| base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap();
+    let header = serde_json::from_slice(&decode(parts[0])).unwrap();
+    let claims = serde_json::from_slice(&decode(parts[1])).unwrap();
+    let signature = decode(parts[2]);
+    let signing_input = format!("{}.{}", parts[0], parts[1]);
+
+    (header, claims, signature, signing_input)
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let id_token = signed_id_token();
+
+    let mut group = c.benchmark_group("parse_id_token");
+    group.bench_function("serde_json_value", |b| {
+        b.iter(|| parse_via_json_value(black_box(&id_token)))
+    });
+    group.bench_function("from_str", |b| {
+        b.iter(|| black_box(&id_token).parse::<CoreIdToken>().unwrap())
+    });
+    group.finish();
+}
+
+fn bench_parse_and_verify(c: &mut Criterion) {
+    let id_token = signed_id_token();
+    let verifier = CoreIdTokenVerifier::new_confidential_client(
+        ClientId::new("s6BhdRkqt3".to_string()),
+        ClientSecret::new("secret".to_string()),
+        IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+        CoreJsonWebKeySet::new(vec![]),
+    )
+    .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+    .set_time_fn(|| Utc.timestamp(1311280971, 0));
+
+    c.bench_function("parse_and_verify_id_token", |b| {
+        b.iter(|| {
+            black_box(&id_token)
+                .parse::<CoreIdToken>()
+                .unwrap()
+                .into_claims(&verifier, |_: Option<&Nonce>| Ok(()))
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_parse, bench_parse_and_verify);
+criterion_main!(benches);
@@ -15,22 +15,22 @@
 //!
 
 use std::env;
-use std::io::{BufRead, BufReader, Write};
-use std::net::TcpListener;
+use std::net::SocketAddr;
 use std::process::exit;
 
 use failure::Fail;
-use url::Url;
 
 use http_client::h1::H1Client;
 use http_client::HttpClient;
 use openidconnect::core::{
-    CoreClient, CoreIdTokenClaims, CoreIdTokenVerifier, CoreProviderMetadata, CoreResponseType,
+    CoreAuthErrorResponseType, CoreClient, CoreIdTokenClaims, CoreIdTokenVerifier,
+    CoreProviderMetadata, CoreResponseType,
 };
 use openidconnect::http_types::{Request, Response};
+use openidconnect::native::RedirectListener;
 use openidconnect::{
-    AccessTokenHash, AuthenticationFlow, AuthorizationCode, ClientId, ClientSecret,
-    CodeTokenRequest, CsrfToken, IssuerUrl, Nonce, PkceCodeChallenge, RedirectUrl, Scope,
+    AccessTokenHash, AuthenticationFlow, ClientId, ClientSecret, CodeTokenRequest, CsrfToken,
+    IssuerUrl, Nonce, PkceCodeChallenge, Scope,
 };
 use openidconnect::{OAuth2TokenResponse, TokenResponse};
 
@@ -84,13 +84,23 @@ fn main() {
             .expect("Invalid issuer URL");
 
         // Fetch Auth0's OpenID Connect discovery document.
-        let provider_metadata = CoreProviderMetadata::discover(issuer_url, async_http_client)
-            .await
+        let provider_metadata =
+            CoreProviderMetadata::discover_async(issuer_url, &async_http_client)
+                .await
+                .unwrap_or_else(|err| {
+                    handle_error(&err, "Failed to discover OpenID Provider");
+                    unreachable!();
+                });
+        println!("{:?}", provider_metadata);
+
+        // This example receives the authorization response on a loopback listener. Auth0 requires
+        // the redirect URI to exactly match a registered value, so the listener binds to a fixed
+        // port, and http://127.0.0.1:8080/ must be registered as an allowed callback URL.
+        let listener = RedirectListener::bind_to(SocketAddr::from(([127, 0, 0, 1], 8080)))
             .unwrap_or_else(|err| {
-                handle_error(&err, "Failed to discover OpenID Provider");
+                handle_error(&err, "Failed to bind redirect listener");
                 unreachable!();
             });
-        println!("{:?}", provider_metadata);
 
         // Set up the config for the Auth0 OAuth2 process.
         let client = CoreClient::from_provider_metadata(
@@ -98,11 +108,7 @@ fn main() {
             auth0_client_id,
             Some(auth0_client_secret),
         )
-        // This example will be running its own server at localhost:8080.
-        // See below for the server implementation.
-        .set_redirect_uri(
-            RedirectUrl::new("http://localhost:8080".to_string()).expect("Invalid redirect URL"),
-        );
+        .set_redirect_uri(listener.redirect_url().clone());
 
         // Generate the authorization URL to which we'll redirect the user.
         let (authorize_url, csrf_state, nonce) = client
@@ -121,89 +127,45 @@ fn main() {
             authorize_url.to_string()
         );
 
-        // A very naive implementation of the redirect server.
-        let listener = TcpListener::bind("127.0.0.1:8080").unwrap();
-        for stream in listener.incoming() {
-            if let Ok(mut stream) = stream {
-                let code;
-                let state;
-                {
-                    let mut reader = BufReader::new(&stream);
-
-                    let mut request_line = String::new();
-                    reader.read_line(&mut request_line).unwrap();
-
-                    let redirect_url = request_line.split_whitespace().nth(1).unwrap();
-                    let url = Url::parse(&("http://localhost".to_string() + redirect_url)).unwrap();
-
-                    let code_pair = url
-                        .query_pairs()
-                        .find(|pair| {
-                            let &(ref key, _) = pair;
-                            key == "code"
-                        })
-                        .unwrap();
-
-                    let (_, value) = code_pair;
-                    code = AuthorizationCode::new(value.into_owned());
-
-                    let state_pair = url
-                        .query_pairs()
-                        .find(|pair| {
-                            let &(ref key, _) = pair;
-                            key == "state"
-                        })
-                        .unwrap();
-
-                    let (_, value) = state_pair;
-                    state = CsrfToken::new(value.into_owned());
-                }
-
-                let message = "Go back to your terminal :)";
-                let response = format!(
-                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
-                    message.len(),
-                    message
-                );
-                stream.write_all(response.as_bytes()).unwrap();
-
-                println!("Auth0 returned the following code:\n{}\n", code.secret());
-                println!(
-                    "Auth0 returned the following state:\n{} (expected `{}`)\n",
-                    state.secret(),
-                    csrf_state.secret()
-                );
-
-                // Exchange the code with a token.
-                let token_response = client
-                    .exchange_code(code)
-                    .request(async_http_client)
-                    .await
-                    .unwrap();
-
-                println!("{:?}", token_response);
-
-                println!(
-                    "Auth0 returned access token:\n{:?}\n",
-                    token_response.id_token()
-                );
-                println!("Auth0 returned scopes: {:?}", token_response.scopes());
-
-                let id_token_verifier: CoreIdTokenVerifier = client.id_token_verifier();
-                let id_token_claims: &CoreIdTokenClaims = token_response
-                    .extra_fields()
-                    .id_token()
-                    .expect("Server did not return an ID token")
-                    .claims(&id_token_verifier, &nonce)
-                    .unwrap_or_else(|err| {
-                        handle_error(&err, "Failed to verify ID token");
-                        unreachable!();
-                    });
-                println!("Auth0 returned ID token: {:?}", id_token_claims);
-
-                // The server will terminate itself after collecting the first code.
-                break;
-            }
-        }
+        // Verifies the CSRF state and surfaces any error returned by Auth0.
+        let response = listener
+            .wait_for_response::<CoreAuthErrorResponseType>(&csrf_state)
+            .unwrap_or_else(|err| {
+                handle_error(&err, "Failed to receive authorization response");
+                unreachable!();
+            });
+        let code = response
+            .code()
+            .cloned()
+            .expect("Auth0 did not return an authorization code");
+
+        println!("Auth0 returned the following code:\n{}\n", code.secret());
+
+        // Exchange the code with a token.
+        let token_response = client
+            .exchange_code(code)
+            .request(async_http_client)
+            .await
+            .unwrap();
+
+        println!("{:?}", token_response);
+
+        println!(
+            "Auth0 returned access token:\n{:?}\n",
+            token_response.id_token()
+        );
+        println!("Auth0 returned scopes: {:?}", token_response.scopes());
+
+        let id_token_verifier: CoreIdTokenVerifier = client.id_token_verifier();
+        let id_token_claims: &CoreIdTokenClaims = token_response
+            .extra_fields()
+            .id_token()
+            .expect("Server did not return an ID token")
+            .claims(&id_token_verifier, &nonce)
+            .unwrap_or_else(|err| {
+                handle_error(&err, "Failed to verify ID token");
+                unreachable!();
+            });
+        println!("Auth0 returned ID token: {:?}", id_token_claims);
     });
 }
@@ -0,0 +1,296 @@
+#![warn(missing_docs)]
+
+//!
+//! Derive macros for the [`openidconnect`](https://docs.rs/openidconnect) crate.
+//!
+//! This crate is re-exported by `openidconnect` when its `derive` feature is enabled and should
+//! not be depended on directly.
+//!
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Field, Fields, Generics,
+    Ident, Lit, Meta, NestedMeta, Visibility,
+};
+
+// Shared with the openidconnect crate, which parses these claims before flattening any additional
+// claims. An additional claim with one of these names would never be populated and would be
+// serialized as a duplicate key.
+mod standard_claims;
+use standard_claims::is_standard_claim;
+
+const NOT_AN_OBJECT: &str = "additional claims must serialize as a JSON object; use a struct with \
+                             named fields (e.g., `struct Claims {}`) instead";
+
+const DENIES_UNKNOWN_FIELDS: &str = "additional claims can't use `#[serde(deny_unknown_fields)]`, \
+                                     since they're flattened alongside the standard claims";
+
+// Name of the private type whose serde implementations are generated for the derived type (see
+// https://serde.rs/remote-derive.html).
+const REMOTE_TYPE: &str = "__AdditionalClaimsRemote";
+
+///
+/// Implements the `AdditionalClaims` marker trait along with `serde::Deserialize` and
+/// `serde::Serialize`.
+///
+/// The serde implementations honor `#[serde(...)]` attributes as if the type derived them
+/// directly, so the type must not also derive `Deserialize` or `Serialize`. Since additional
+/// claims are flattened alongside the standard claims, the following are rejected at compile
+/// time:
+///  * fields whose claim name (after applying any `#[serde(rename)]` or
+///    `#[serde(rename_all)]` attributes) collides with a standard or ID token claim,
+///  * `#[serde(deny_unknown_fields)]`, which would reject every standard claim, and
+///  * unit structs and tuple structs with more than one field, which don't serialize as JSON
+///    objects. Empty structs should be declared with braces (e.g., `struct Claims {}`).
+///
+#[proc_macro_derive(AdditionalClaims, attributes(serde))]
+pub fn derive_additional_claims(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    if serde_flag(&input.attrs, "deny_unknown_fields") {
+        return Err(Error::new_spanned(&input.ident, DENIES_UNKNOWN_FIELDS));
+    }
+    match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => check_claim_names(&input.attrs, &fields.named)?,
+            // Newtype structs (e.g., wrapping a map) serialize as their inner value.
+            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {}
+            _ => return Err(Error::new_spanned(&input.ident, NOT_AN_OBJECT)),
+        },
+        Data::Enum(_) => {}
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, NOT_AN_OBJECT)),
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let remote = quote!(#name #ty_generics).to_string();
+    let remote_type = remote_type(input);
+    let remote_ident = &remote_type.ident;
+
+    let mut de_generics = input.generics.clone();
+    de_generics.params.insert(0, parse_quote!('de));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    let serde = quote!(::openidconnect::__private::serde);
+    let serialize_where_clause =
+        where_clause_with_bound(&input.generics, quote!(#serde::Serialize));
+    let deserialize_where_clause =
+        where_clause_with_bound(&input.generics, quote!(#serde::Deserialize<'de>));
+
+    // The serde implementations are generated for a private copy of the type with its serde
+    // attributes, which lets serde_derive handle those attributes while the derived type's
+    // implementations remain in the caller's crate.
+    Ok(quote! {
+        const _: () = {
+            #[derive(
+                ::openidconnect::__private::Deserialize,
+                ::openidconnect::__private::Serialize,
+            )]
+            #[serde(crate = "::openidconnect::__private::serde", remote = #remote)]
+            #remote_type
+
+            impl #impl_generics #serde::Serialize for #name #ty_generics #serialize_where_clause {
+                fn serialize<__S>(
+                    &self,
+                    serializer: __S,
+                ) -> ::std::result::Result<__S::Ok, __S::Error>
+                where
+                    __S: #serde::Serializer,
+                {
+                    #remote_ident::serialize(self, serializer)
+                }
+            }
+
+            impl #de_impl_generics #serde::Deserialize<'de> for #name #ty_generics
+                #deserialize_where_clause
+            {
+                fn deserialize<__D>(deserializer: __D) -> ::std::result::Result<Self, __D::Error>
+                where
+                    __D: #serde::Deserializer<'de>,
+                {
+                    #remote_ident::deserialize(deserializer)
+                }
+            }
+
+            impl #impl_generics ::openidconnect::AdditionalClaims for #name #ty_generics
+                #where_clause
+            {
+            }
+        };
+    })
+}
+
+// Returns a private copy of the type that retains only its serde attributes.
+fn remote_type(input: &DeriveInput) -> DeriveInput {
+    let mut remote_type = input.clone();
+    remote_type.ident = Ident::new(REMOTE_TYPE, Span::call_site());
+    remote_type.vis = Visibility::Inherited;
+    retain_serde_attrs(&mut remote_type.attrs);
+    match remote_type.data {
+        Data::Struct(ref mut data) => retain_field_serde_attrs(&mut data.fields),
+        Data::Enum(ref mut data) => {
+            for variant in data.variants.iter_mut() {
+                retain_serde_attrs(&mut variant.attrs);
+                retain_field_serde_attrs(&mut variant.fields);
+            }
+        }
+        Data::Union(_) => unreachable!("unions are rejected"),
+    }
+    remote_type
+}
+
+fn retain_serde_attrs(attrs: &mut Vec<Attribute>) {
+    attrs.retain(|attr| attr.path.is_ident("serde"));
+}
+
+fn retain_field_serde_attrs(fields: &mut Fields) {
+    for field in fields.iter_mut() {
+        field.vis = Visibility::Inherited;
+        retain_serde_attrs(&mut field.attrs);
+    }
+}
+
+// Returns the type's where clause with the specified bound added to each type parameter.
+fn where_clause_with_bound(generics: &Generics, bound: TokenStream2) -> TokenStream2 {
+    let predicates = generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| where_clause.predicates.iter());
+    let type_params = generics.type_params().map(|param| &param.ident);
+    quote! {
+        where
+            #(#predicates,)*
+            #(#type_params: #bound,)*
+    }
+}
+
+fn check_claim_names<'a, I>(struct_attrs: &[Attribute], fields: I) -> Result<(), Error>
+where
+    I: IntoIterator<Item = &'a Field>,
+{
+    let rename_all = serde_values(struct_attrs, "rename_all");
+    for field in fields {
+        if serde_flag(&field.attrs, "flatten") || serde_flag(&field.attrs, "skip") {
+            continue;
+        }
+
+        let mut claim_names = serde_values(&field.attrs, "rename");
+        if claim_names.is_empty() {
+            let field_name = field
+                .ident
+                .as_ref()
+                .expect("named fields have identifiers")
+                .unraw()
+                .to_string();
+            if rename_all.is_empty() {
+                claim_names.push(field_name);
+            } else {
+                claim_names.extend(
+                    rename_all
+                        .iter()
+                        .map(|rule| apply_rename_rule(&field_name, rule)),
+                );
+            }
+        }
+
+        if let Some(claim_name) = claim_names.iter().find(|name| is_standard_claim(name)) {
+            return Err(Error::new_spanned(
+                field,
+                format!(
+                    "additional claim `{}` collides with a standard claim",
+                    claim_name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Returns whether a `#[serde(...)]` attribute contains the specified flag (e.g., `flatten`).
+fn serde_flag(attrs: &[Attribute], flag: &str) -> bool {
+    serde_meta(attrs).any(|meta| match meta {
+        NestedMeta::Meta(Meta::Path(ref path)) => path.is_ident(flag),
+        _ => false,
+    })
+}
+
+// Returns the values of a `#[serde(key = "...")]` attribute. Both the serialized and deserialized
+// names are returned when specified separately (e.g., `#[serde(key(serialize = "..."))]`).
+fn serde_values(attrs: &[Attribute], key: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    for meta in serde_meta(attrs) {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(ref name_value)) if name_value.path.is_ident(key) => {
+                if let Lit::Str(ref value) = name_value.lit {
+                    values.push(value.value());
+                }
+            }
+            NestedMeta::Meta(Meta::List(ref list)) if list.path.is_ident(key) => {
+                for nested in &list.nested {
+                    if let NestedMeta::Meta(Meta::NameValue(ref name_value)) = nested {
+                        if let Lit::Str(ref value) = name_value.lit {
+                            values.push(value.value());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    values
+}
+
+// Attributes that fail to parse are ignored here, since serde reports a more helpful error.
+fn serde_meta<'a>(attrs: &'a [Attribute]) -> impl Iterator<Item = NestedMeta> + 'a {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested.into_iter()),
+            _ => None,
+        })
+        .flatten()
+}
+
+// Mirrors serde's `rename_all` rules, which assume snake_case field names.
+fn apply_rename_rule(field_name: &str, rule: &str) -> String {
+    let pascal_case = || {
+        field_name
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect::<String>()
+    };
+
+    match rule {
+        "lowercase" => field_name.to_ascii_lowercase(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field_name.to_ascii_uppercase(),
+        "PascalCase" => pascal_case(),
+        "camelCase" => {
+            let pascal = pascal_case();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        }
+        "kebab-case" => field_name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field_name.replace('_', "-").to_ascii_uppercase(),
+        _ => field_name.to_string(),
+    }
+}
@@ -0,0 +1 @@
+../../src/standard_claims.rs
\ No newline at end of file
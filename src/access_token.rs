@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use oauth2::{AccessToken, ClientId};
+
+use crate::jwt::{
+    JsonWebToken, JsonWebTokenAccess, JsonWebTokenError, JsonWebTokenHeader,
+    JsonWebTokenJsonPayloadSerde,
+};
+use crate::scope::ScopeSet;
+use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt};
+use crate::{
+    AccessTokenVerifier, AdditionalClaims, Audience, AudiencesClaim, ClaimsVerificationError,
+    IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, PrivateSigningKey, SubjectIdentifier,
+};
+
+///
+/// Access token issued as a signed JWT, as defined in
+/// [RFC 9068](https://tools.ietf.org/html/rfc9068).
+///
+/// Resource servers use this type to verify bearer tokens presented by clients without contacting
+/// the authorization server. Access tokens that aren't JWTs (i.e., opaque tokens) must instead be
+/// validated using the provider's token introspection endpoint.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JwtAccessToken<
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(
+    #[serde(bound = "AC: AdditionalClaims")]
+    JsonWebToken<JE, JS, JT, AccessTokenClaims<AC>, JsonWebTokenJsonPayloadSerde>,
+);
+impl<AC, JE, JS, JT> JwtAccessToken<AC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes a new access token containing the specified claims, signed with the specified
+    /// key and signing algorithm.
+    ///
+    pub fn new<JU, K, S>(
+        claims: AccessTokenClaims<AC>,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        Ok(Self(JsonWebToken::new(claims, signing_key, &alg)?))
+    }
+
+    ///
+    /// Parses a bearer access token as a JWT without verifying it.
+    ///
+    pub fn from_access_token(access_token: &AccessToken) -> Result<Self, serde_json::Error> {
+        JsonWebToken::from_compact(access_token.secret()).map(Self)
+    }
+
+    ///
+    /// Returns the JOSE header of the access token without verifying the token's signature.
+    ///
+    /// Resource servers that accept tokens from multiple issuers may use the header's `kid` to
+    /// select the appropriate [`AccessTokenVerifier`].
+    ///
+    /// # Security Warning
+    ///
+    /// The returned header is untrusted and may have been forged or tampered with.
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+
+    ///
+    /// Verifies and returns the access token claims.
+    ///
+    pub fn claims<JU, K>(
+        self,
+        verifier: &AccessTokenVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<AccessTokenClaims<AC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_claims(self.0)
+    }
+}
+
+///
+/// Claims of a [`JwtAccessToken`].
+///
+/// RFC 9068 requires several claims (e.g., `sub`, `client_id`, and `jti`) that some providers
+/// omit from their access tokens, so only the `iss`, `aud`, and `exp` claims are required here.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    #[serde(rename = "iss")]
+    issuer: IssuerUrl,
+    #[serde(rename = "aud", deserialize_with = "deserialize_string_or_vec")]
+    audiences: Vec<Audience>,
+    #[serde(rename = "exp", with = "serde_utc_seconds")]
+    expiration: DateTime<Utc>,
+    #[serde(
+        default,
+        rename = "iat",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    issue_time: Option<DateTime<Utc>>,
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<ClientId>,
+    #[serde(rename = "scope", skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[serde(rename = "jti", skip_serializing_if = "Option::is_none")]
+    token_id: Option<String>,
+
+    #[serde(bound = "AC: AdditionalClaims")]
+    #[serde(flatten)]
+    additional_claims: AC,
+}
+impl<AC> AccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    ///
+    /// Initializes new access token claims.
+    ///
+    pub fn new(
+        issuer: IssuerUrl,
+        audiences: Vec<Audience>,
+        expiration: DateTime<Utc>,
+        additional_claims: AC,
+    ) -> Self {
+        Self {
+            issuer,
+            audiences,
+            expiration,
+            issue_time: None,
+            subject: None,
+            client_id: None,
+            scope: None,
+            token_id: None,
+            additional_claims,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[IssuerUrl] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_expiration -> expiration[DateTime<Utc>] ["exp"],
+            set_issue_time -> issue_time[Option<DateTime<Utc>>] ["iat"],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_client_id -> client_id[Option<ClientId>],
+            set_scope -> scope[Option<String>],
+            set_token_id -> token_id[Option<String>] ["jti"],
+        }
+    ];
+
+    ///
+    /// Returns the scopes granted to the access token, parsed from the `scope` claim.
+    ///
+    pub fn scopes(&self) -> ScopeSet {
+        self.scope
+            .as_ref()
+            .map(|scope| ScopeSet::from_space_delimited(scope))
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Returns additional access token claims.
+    ///
+    pub fn additional_claims(&self) -> &AC {
+        &self.additional_claims
+    }
+}
+impl<AC> AudiencesClaim for AccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(AccessTokenClaims::audiences(self))
+    }
+}
+impl<'a, AC> AudiencesClaim for &'a AccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(AccessTokenClaims::audiences(self))
+    }
+}
+impl<AC> IssuerClaim for AccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(AccessTokenClaims::issuer(self))
+    }
+}
+impl<'a, AC> IssuerClaim for &'a AccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(AccessTokenClaims::issuer(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::AccessToken;
+
+    use crate::core::{
+        CoreAccessTokenClaims, CoreAccessTokenVerifier, CoreJsonWebKeySet, CoreJwsSigningAlgorithm,
+        CoreJwtAccessToken, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::{
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, FixedClock, IssuerUrl,
+        JsonWebTokenAlgorithm, PrivateSigningKey, Scope, StandardScopes,
+    };
+
+    #[test]
+    fn test_jwt_access_token() {
+        let claims = CoreAccessTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("https://api.example.com".to_string())],
+            Utc.timestamp(1311281970, 0),
+            EmptyAdditionalClaims {},
+        )
+        .set_scope(Some("openid email".to_string()));
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let access_token = AccessToken::new(
+            serde_json::to_value(
+                CoreJwtAccessToken::new(
+                    claims.clone(),
+                    &signing_key,
+                    CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                )
+                .unwrap(),
+            )
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string(),
+        );
+
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let verifier = |audience: &str| {
+            CoreAccessTokenVerifier::new(
+                Audience::new(audience.to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            )
+            .set_clock(clock.clone())
+        };
+        assert_eq!(
+            CoreJwtAccessToken::from_access_token(&access_token)
+                .unwrap()
+                .unverified_header()
+                .alg,
+            JsonWebTokenAlgorithm::Signature(
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                PhantomData
+            )
+        );
+
+        let verify = |verifier: &CoreAccessTokenVerifier| {
+            CoreJwtAccessToken::from_access_token(&access_token)
+                .unwrap()
+                .claims(verifier)
+        };
+
+        let verified_claims = verify(&verifier("https://api.example.com")).unwrap();
+        assert_eq!(verified_claims, claims);
+        assert!(verified_claims.scopes().contains(Scope::EMAIL));
+
+        match verify(&verifier("https://other.example.com")) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        clock.set(Utc.timestamp(1311282000, 0));
+        match verify(&verifier("https://api.example.com")) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        verify(&verifier("https://api.example.com").set_clock_skew(Duration::from_secs(60)))
+            .expect("expiration should be within the allowed clock skew");
+
+        assert!(
+            CoreJwtAccessToken::from_access_token(&AccessToken::new("opaque".to_string())).is_err()
+        );
+    }
+}
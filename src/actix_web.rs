@@ -0,0 +1,361 @@
+use std::marker::PhantomData;
+
+use actix_web::body::{BoxBody, EitherBody};
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header::{HeaderMap, COOKIE, LOCATION, SET_COOKIE};
+use actix_web::http::StatusCode;
+use actix_web::{web, Error, FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+use futures::future::{ready, LocalBoxFuture, Ready};
+
+use crate::web::cookie_value;
+use crate::{AdditionalClaims, GenderClaim};
+
+pub use crate::web::{
+    OidcCallback, OidcConfigError, OidcLoginError, OidcLoginRedirect, OidcRejection, OidcState,
+    VerifiedClaims,
+};
+
+impl ResponseError for OidcRejection {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OidcRejection::Login(_) => StatusCode::BAD_REQUEST,
+            OidcRejection::Unauthenticated => StatusCode::UNAUTHORIZED,
+            OidcRejection::SessionStore(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            OidcRejection::SessionStore(_) => {
+                HttpResponse::build(self.status_code()).body("Session store error")
+            }
+            _ => HttpResponse::build(self.status_code()).body(self.to_string()),
+        }
+    }
+}
+
+impl Responder for OidcLoginRedirect {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut response = HttpResponse::SeeOther();
+        response.insert_header((LOCATION, self.location()));
+        for cookie in self.cookies() {
+            response.append_header((SET_COOKIE, cookie.as_str()));
+        }
+        response.finish()
+    }
+}
+
+///
+/// Completes the login when the Authorization Endpoint redirects the user agent to the client's
+/// redirect URI. Requires the [`OidcState`] to be registered as `web::Data<OidcState<AC, GC>>`.
+///
+impl<AC, GC> FromRequest for OidcCallback<AC, GC>
+where
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let oidc = match oidc_state::<AC, GC>(req) {
+            Ok(oidc) => oidc,
+            Err(err) => return Box::pin(ready(Err(err))),
+        };
+        let login_cookie = request_cookie(req.headers(), &oidc.login_cookie_name());
+        let query = Some(req.query_string().to_string()).filter(|query| !query.is_empty());
+        Box::pin(async move {
+            oidc.callback(query.as_deref(), login_cookie)
+                .await
+                .map_err(Error::from)
+        })
+    }
+}
+
+///
+/// Loads the verified ID token claims of the current session. Requires the [`OidcState`] to be
+/// registered as `web::Data<OidcState<AC, GC>>`.
+///
+impl<AC, GC> FromRequest for VerifiedClaims<AC, GC>
+where
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+{
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(oidc_state::<AC, GC>(req).and_then(|oidc| {
+            oidc.verified_claims(request_cookie(req.headers(), oidc.session_cookie_name()))
+                .map_err(Error::from)
+        }))
+    }
+}
+
+///
+/// Middleware that redirects requests without an authenticated session to a login route.
+///
+/// Requests whose session has verified ID token claims (see [`VerifiedClaims`]) are passed to the
+/// wrapped service. All other requests are redirected (`303 See Other`) to the specified login
+/// path, whose handler would typically return [`OidcState::login`]. The [`OidcState`] must be
+/// registered as `web::Data<OidcState<AC, GC>>`.
+///
+#[derive(Clone, Debug)]
+pub struct RequireLogin<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    login_path: String,
+    _phantom: PhantomData<(AC, GC)>,
+}
+impl<AC, GC> RequireLogin<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Instantiates the middleware, redirecting unauthenticated requests to `login_path`.
+    ///
+    pub fn new(login_path: String) -> Self {
+        Self {
+            login_path,
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<S, B, AC, GC> Transform<S, ServiceRequest> for RequireLogin<AC, GC>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireLoginMiddleware<S, AC, GC>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireLoginMiddleware {
+            login_path: self.login_path.clone(),
+            service,
+            _phantom: PhantomData,
+        }))
+    }
+}
+
+///
+/// Service created by the [`RequireLogin`] middleware.
+///
+#[derive(Debug)]
+pub struct RequireLoginMiddleware<S, AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    login_path: String,
+    service: S,
+    _phantom: PhantomData<(AC, GC)>,
+}
+impl<S, B, AC, GC> Service<ServiceRequest> for RequireLoginMiddleware<S, AC, GC>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let claims = oidc_state::<AC, GC>(req.request()).and_then(|oidc| {
+            match oidc.verified_claims(request_cookie(req.headers(), oidc.session_cookie_name())) {
+                Ok(claims) => Ok(Some(claims)),
+                Err(OidcRejection::Unauthenticated) => Ok(None),
+                Err(err) => Err(Error::from(err)),
+            }
+        });
+        match claims {
+            Ok(Some(_)) => {
+                let response = self.service.call(req);
+                Box::pin(async move { response.await.map(ServiceResponse::map_into_left_body) })
+            }
+            Ok(None) => {
+                let response = HttpResponse::SeeOther()
+                    .insert_header((LOCATION, self.login_path.as_str()))
+                    .finish()
+                    .map_into_right_body();
+                Box::pin(ready(Ok(req.into_response(response))))
+            }
+            Err(err) => Box::pin(ready(Err(err))),
+        }
+    }
+}
+
+fn oidc_state<AC, GC>(req: &HttpRequest) -> Result<OidcState<AC, GC>, Error>
+where
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+{
+    req.app_data::<web::Data<OidcState<AC, GC>>>()
+        .map(|oidc| oidc.get_ref().clone())
+        .ok_or_else(|| ErrorInternalServerError("OidcState is not registered as app data"))
+}
+
+fn request_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    cookie_value(
+        headers
+            .get_all(COOKIE)
+            .filter_map(|value| value.to_str().ok()),
+        name,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use actix_web::dev::Payload;
+    use actix_web::http::header::{COOKIE, LOCATION, SET_COOKIE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::{web, Error, FromRequest, HttpRequest, Responder};
+    use futures::executor::block_on;
+
+    use crate::core::CoreGenderClaim;
+    use crate::web::tests::{request_cookie, TestLogin, TEST_SUBJECT};
+    use crate::{EmptyAdditionalClaims, SubjectIdentifier};
+
+    use super::{OidcCallback, OidcLoginRedirect, VerifiedClaims};
+
+    type TestCallback = OidcCallback<EmptyAdditionalClaims, CoreGenderClaim>;
+    type TestVerifiedClaims = VerifiedClaims<EmptyAdditionalClaims, CoreGenderClaim>;
+
+    fn request(login: &TestLogin, query: &str, cookie: Option<&str>) -> HttpRequest {
+        let mut request = TestRequest::default()
+            .uri(&format!("/cb?{}", query))
+            .app_data(web::Data::new(login.state.clone()));
+        if let Some(cookie) = cookie {
+            request = request.insert_header((COOKIE, cookie));
+        }
+        request.to_http_request()
+    }
+
+    // Returns the status code of the response to which the extractor's error is converted.
+    fn error_status<T>(result: Result<T, Error>) -> StatusCode
+    where
+        T: Debug,
+    {
+        match result {
+            Ok(value) => panic!("unexpected result: {:?}", value),
+            Err(err) => err.as_response_error().status_code(),
+        }
+    }
+
+    #[test]
+    fn test_extractors() {
+        let login = TestLogin::new();
+        let (query, login_cookie) = login.begin();
+
+        let callback = block_on(TestCallback::from_request(
+            &request(&login, &query, Some(&login_cookie)),
+            &mut Payload::None,
+        ))
+        .unwrap();
+        assert_eq!(
+            *callback.claims().subject(),
+            SubjectIdentifier::new(TEST_SUBJECT.to_string())
+        );
+
+        let session_cookie = request_cookie(&callback.redirect("/".to_string()).cookies()[0]);
+        let claims = block_on(TestVerifiedClaims::from_request(
+            &request(&login, "", Some(&session_cookie)),
+            &mut Payload::None,
+        ))
+        .unwrap();
+        assert_eq!(claims.0, *callback.claims());
+
+        // Each login may only be completed once.
+        assert_eq!(
+            error_status(block_on(TestCallback::from_request(
+                &request(&login, &query, Some(&login_cookie)),
+                &mut Payload::None,
+            ))),
+            StatusCode::BAD_REQUEST
+        );
+
+        // The extractors require the state to be registered as app data.
+        assert_eq!(
+            error_status(block_on(TestVerifiedClaims::from_request(
+                &TestRequest::default()
+                    .insert_header((COOKIE, session_cookie.as_str()))
+                    .to_http_request(),
+                &mut Payload::None,
+            ))),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_extractors_missing_cookie() {
+        let login = TestLogin::new();
+        let (query, _) = login.begin();
+
+        assert_eq!(
+            error_status(block_on(TestCallback::from_request(
+                &request(&login, &query, None),
+                &mut Payload::None,
+            ))),
+            StatusCode::BAD_REQUEST
+        );
+
+        for cookie in &[None, Some("oidc_session=unknown")] {
+            assert_eq!(
+                error_status(block_on(TestVerifiedClaims::from_request(
+                    &request(&login, "", *cookie),
+                    &mut Payload::None,
+                ))),
+                StatusCode::UNAUTHORIZED
+            );
+        }
+    }
+
+    #[test]
+    fn test_callback_extractor_wrong_state() {
+        let login = TestLogin::new();
+        let (_, login_cookie) = login.begin();
+        // The state parameter belongs to a login started by a different user agent.
+        let (other_query, _) = login.begin();
+
+        assert_eq!(
+            error_status(block_on(TestCallback::from_request(
+                &request(&login, &other_query, Some(&login_cookie)),
+                &mut Payload::None,
+            ))),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_login_redirect_response() {
+        let request = TestRequest::default().to_http_request();
+        let response = OidcLoginRedirect::new("https://example.com/authorize".to_string())
+            .add_cookie("a=1".to_string())
+            .add_cookie("b=2; Max-Age=0".to_string())
+            .respond_to(&request);
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "https://example.com/authorize"
+        );
+        assert_eq!(response.headers().get_all(SET_COOKIE).count(), 2);
+    }
+}
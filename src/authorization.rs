@@ -0,0 +1,596 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use failure::Fail;
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, ErrorResponseType, StandardErrorResponse};
+use url::{form_urlencoded, Url};
+
+use crate::crypto::constant_time_eq;
+use crate::{
+    AdditionalClaims, GenderClaim, IdToken, IssuerUrl, JsonWebKeyType,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, SessionState,
+};
+
+///
+/// Parameters returned by the Authorization Endpoint to the client's redirect URI.
+///
+/// Depending on the [`AuthenticationFlow`](crate::AuthenticationFlow), the response contains an
+/// authorization code (Authorization Code Flow), an ID token and optional access token (Implicit
+/// Flow), or a combination of these (Hybrid Flow).
+///
+#[derive(Clone, Debug)]
+pub struct AuthorizationResponse {
+    access_token: Option<AccessToken>,
+    code: Option<AuthorizationCode>,
+    expires_in: Option<Duration>,
+    id_token: Option<String>,
+    issuer: Option<String>,
+    session_state: Option<SessionState>,
+    state: Option<CsrfToken>,
+    token_type: Option<String>,
+}
+impl AuthorizationResponse {
+    ///
+    /// Parses an `application/x-www-form-urlencoded` request body POSTed to the client's redirect
+    /// URI when using the
+    /// [Form Post Response Mode](https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html).
+    ///
+    /// The `state` parameter is not validated; callers must compare [`AuthorizationResponse::state`]
+    /// to the CSRF token returned by [`AuthorizationRequest::url`](crate::AuthorizationRequest::url).
+    ///
+    pub fn from_form_post<T>(body: &[u8]) -> Result<Self, AuthorizationResponseError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        Self::from_params(parse_params(form_urlencoded::parse(body))?)
+    }
+
+    ///
+    /// Parses the URL to which the Authorization Endpoint redirected the user agent, verifying that
+    /// its `state` parameter matches `expected_state`.
+    ///
+    /// Parameters are read from the URL's query string or, if the query string is empty, from its
+    /// fragment (as returned by the Implicit and Hybrid Flows). The `state` parameter is verified
+    /// before any error returned by the Authorization Endpoint is surfaced, since an unverified
+    /// error response may have been forged by an attacker.
+    ///
+    /// The `iss` parameter is not validated; use
+    /// [`AuthorizationResponse::from_redirect_url_with_issuer`] to also verify the issuer.
+    ///
+    pub fn from_redirect_url<T>(
+        redirect_url: &Url,
+        expected_state: &CsrfToken,
+    ) -> Result<Self, AuthorizationResponseError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        Self::from_params(redirect_url_verified_params(redirect_url, expected_state)?)
+    }
+
+    ///
+    /// Parses the URL to which the Authorization Endpoint redirected the user agent, verifying that
+    /// its `state` parameter matches `expected_state` and that its `iss` parameter (if any)
+    /// matches `expected_issuer`.
+    ///
+    /// Verifying the `iss` parameter defined by [RFC 9207](https://tools.ietf.org/html/rfc9207)
+    /// mitigates mix-up attacks against clients that interact with multiple providers. If
+    /// `require_issuer` is `true` (e.g., because the provider advertises
+    /// `authorization_response_iss_parameter_supported`), responses without an `iss` parameter are
+    /// also rejected. As with the `state` parameter, the issuer is verified before any error
+    /// returned by the Authorization Endpoint is surfaced.
+    ///
+    pub fn from_redirect_url_with_issuer<T>(
+        redirect_url: &Url,
+        expected_state: &CsrfToken,
+        expected_issuer: &IssuerUrl,
+        require_issuer: bool,
+    ) -> Result<Self, AuthorizationResponseError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        let params = redirect_url_verified_params(redirect_url, expected_state)?;
+
+        // Section 2.4 of RFC 9207 requires a simple string comparison.
+        match params.get("iss") {
+            Some(issuer) if issuer == expected_issuer.as_str() => {}
+            None if !require_issuer => {}
+            _ => return Err(AuthorizationResponseError::IssuerMismatch),
+        }
+
+        Self::from_params(params)
+    }
+
+    fn from_params<T>(
+        mut params: HashMap<String, String>,
+    ) -> Result<Self, AuthorizationResponseError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        if let Some(error) = params.remove("error") {
+            let error =
+                serde_json::from_value::<T>(serde_json::Value::String(error)).map_err(|err| {
+                    AuthorizationResponseError::Parse(format!("invalid `error` parameter: {}", err))
+                })?;
+            return Err(AuthorizationResponseError::ServerResponse(
+                StandardErrorResponse::new(
+                    error,
+                    params.remove("error_description"),
+                    params.remove("error_uri"),
+                ),
+            ));
+        }
+
+        let expires_in = params
+            .remove("expires_in")
+            .map(|expires_in| {
+                expires_in
+                    .parse::<u64>()
+                    .map(Duration::from_secs)
+                    .map_err(|_| {
+                        AuthorizationResponseError::Parse(format!(
+                            "invalid `expires_in` parameter: {}",
+                            expires_in
+                        ))
+                    })
+            })
+            .transpose()?;
+
+        let response = Self {
+            access_token: params.remove("access_token").map(AccessToken::new),
+            code: params.remove("code").map(AuthorizationCode::new),
+            expires_in,
+            id_token: params.remove("id_token"),
+            issuer: params.remove("iss"),
+            session_state: params.remove("session_state").map(SessionState::new),
+            state: params.remove("state").map(CsrfToken::new),
+            token_type: params.remove("token_type"),
+        };
+        if response.code.is_none() && response.id_token.is_none() && response.access_token.is_none()
+        {
+            return Err(AuthorizationResponseError::Parse(
+                "response contains no `code`, `id_token`, or `access_token` parameter".to_string(),
+            ));
+        }
+        Ok(response)
+    }
+
+    ///
+    /// Returns the OAuth2 access token returned by the Implicit or Hybrid Flow, if any.
+    ///
+    pub fn access_token(&self) -> Option<&AccessToken> {
+        self.access_token.as_ref()
+    }
+
+    ///
+    /// Returns the authorization code returned by the Authorization Code or Hybrid Flow, if any.
+    ///
+    /// The code should be exchanged for tokens via
+    /// [`Client::exchange_code`](crate::Client::exchange_code).
+    ///
+    pub fn code(&self) -> Option<&AuthorizationCode> {
+        self.code.as_ref()
+    }
+
+    ///
+    /// Returns the lifetime of the access token, if specified.
+    ///
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.expires_in
+    }
+
+    ///
+    /// Returns the serialized ID token returned by the Implicit or Hybrid Flow, if any.
+    ///
+    pub fn id_token_raw(&self) -> Option<&str> {
+        self.id_token.as_ref().map(String::as_str)
+    }
+
+    ///
+    /// Parses the ID token returned by the Implicit or Hybrid Flow, if any.
+    ///
+    /// The ID token must subsequently be verified via [`IdToken::claims`].
+    ///
+    pub fn id_token<AC, GC, JE, JS, JT>(
+        &self,
+    ) -> Option<Result<IdToken<AC, GC, JE, JS, JT>, serde_json::Error>>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+    {
+        self.id_token.as_ref().map(|id_token| id_token.parse())
+    }
+
+    ///
+    /// Returns the `iss` parameter defined by [RFC 9207](https://tools.ietf.org/html/rfc9207), if
+    /// any.
+    ///
+    pub fn issuer(&self) -> Option<&str> {
+        self.issuer.as_ref().map(String::as_str)
+    }
+
+    ///
+    /// Returns the `session_state` parameter defined by
+    /// [OpenID Connect Session Management](
+    /// https://openid.net/specs/openid-connect-session-1_0.html#CreatingUpdatingSessions), if any.
+    ///
+    pub fn session_state(&self) -> Option<&SessionState> {
+        self.session_state.as_ref()
+    }
+
+    ///
+    /// Returns the `state` parameter, if any.
+    ///
+    pub fn state(&self) -> Option<&CsrfToken> {
+        self.state.as_ref()
+    }
+
+    ///
+    /// Returns the type of the access token returned by the Implicit or Hybrid Flow, if any.
+    ///
+    pub fn token_type(&self) -> Option<&str> {
+        self.token_type.as_ref().map(String::as_str)
+    }
+}
+
+///
+/// Returns the `state` parameter of the URL to which the Authorization Endpoint redirected the
+/// user agent, without otherwise parsing or validating the response.
+///
+/// This allows the expected CSRF token to be looked up before calling
+/// [`AuthorizationResponse::from_redirect_url`].
+///
+pub(crate) fn redirect_url_state(redirect_url: &Url) -> Option<CsrfToken> {
+    redirect_url_params(redirect_url).and_then(|params| {
+        form_urlencoded::parse(params.as_bytes())
+            .find(|(name, _)| name == "state")
+            .map(|(_, state)| CsrfToken::new(state.into_owned()))
+    })
+}
+
+// Parameters are read from the query string or, if the query string is empty, from the fragment.
+fn redirect_url_params(redirect_url: &Url) -> Option<&str> {
+    match (redirect_url.query(), redirect_url.fragment()) {
+        (Some(query), _) if !query.is_empty() => Some(query),
+        (_, fragment) => fragment,
+    }
+}
+
+fn redirect_url_verified_params<T>(
+    redirect_url: &Url,
+    expected_state: &CsrfToken,
+) -> Result<HashMap<String, String>, AuthorizationResponseError<T>>
+where
+    T: ErrorResponseType + 'static,
+{
+    let params = match redirect_url_params(redirect_url) {
+        Some(params) => parse_params(form_urlencoded::parse(params.as_bytes()))?,
+        None => HashMap::new(),
+    };
+
+    match params.get("state") {
+        Some(state) if constant_time_eq(state.as_bytes(), expected_state.secret().as_bytes()) => {}
+        _ => return Err(AuthorizationResponseError::StateMismatch),
+    }
+    Ok(params)
+}
+
+fn parse_params<'a, I, T>(
+    pairs: I,
+) -> Result<HashMap<String, String>, AuthorizationResponseError<T>>
+where
+    I: Iterator<Item = (Cow<'a, str>, Cow<'a, str>)>,
+    T: ErrorResponseType + 'static,
+{
+    let mut params = HashMap::new();
+    for (name, value) in pairs {
+        // Section 3.1 of RFC 6749 prohibits repeating request and response parameters.
+        if params
+            .insert(name.clone().into_owned(), value.into_owned())
+            .is_some()
+        {
+            return Err(AuthorizationResponseError::Parse(format!(
+                "duplicate `{}` parameter",
+                name
+            )));
+        }
+    }
+    Ok(params)
+}
+
+///
+/// Error parsing a response from the Authorization Endpoint.
+///
+/// The error code returned by the Authorization Endpoint is parsed as `T`, which is typically
+/// [`CoreAuthErrorResponseType`](crate::core::CoreAuthErrorResponseType). This allows failures
+/// such as those resulting from silent authentication requests (i.e., `prompt=none`) to be
+/// pattern matched.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum AuthorizationResponseError<T>
+where
+    T: ErrorResponseType + 'static,
+{
+    ///
+    /// The response is malformed.
+    ///
+    #[fail(display = "Failed to parse authorization response: {}", _0)]
+    Parse(String),
+    ///
+    /// The Authorization Endpoint returned an error.
+    ///
+    #[fail(display = "Server returned error")]
+    ServerResponse(StandardErrorResponse<T>),
+    ///
+    /// The `state` parameter is missing or does not match the expected CSRF token.
+    ///
+    #[fail(display = "State parameter is missing or does not match the expected value")]
+    StateMismatch,
+    ///
+    /// The `iss` parameter does not match the expected issuer, or is missing but required.
+    ///
+    #[fail(display = "Issuer parameter is missing or does not match the expected value")]
+    IssuerMismatch,
+    ///
+    /// The response was returned in the URL fragment, which the client doesn't allow (see
+    /// [`Client::oauth21_strict`](crate::Client::oauth21_strict)).
+    ///
+    #[fail(display = "Authorization response must not be returned in the URL fragment")]
+    FragmentNotAllowed,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use oauth2::CsrfToken;
+    use url::Url;
+
+    use crate::core::CoreAuthErrorResponseType;
+    use crate::{IssuerUrl, SessionState};
+
+    use super::{AuthorizationResponse, AuthorizationResponseError};
+
+    #[test]
+    fn test_form_post_code() {
+        let response = AuthorizationResponse::from_form_post::<CoreAuthErrorResponseType>(
+            b"code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj",
+        )
+        .unwrap();
+        assert_eq!("SplxlOBeZQQYbYS6WxSbIA", response.code().unwrap().secret());
+        assert_eq!("af0ifjsldkj", response.state().unwrap().secret());
+        assert!(response.id_token_raw().is_none());
+        assert!(response.access_token().is_none());
+        assert!(response.session_state().is_none());
+    }
+
+    #[test]
+    fn test_form_post_hybrid() {
+        let response = AuthorizationResponse::from_form_post::<CoreAuthErrorResponseType>(
+            b"code=SplxlOBeZQQYbYS6WxSbIA&id_token=eyJhbGciOiJSUzI1NiJ9.e30.c2ln&\
+              access_token=jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y&token_type=Bearer&\
+              expires_in=3600&state=af0ifjsldkj",
+        )
+        .unwrap();
+        assert_eq!("SplxlOBeZQQYbYS6WxSbIA", response.code().unwrap().secret());
+        assert_eq!(
+            Some("eyJhbGciOiJSUzI1NiJ9.e30.c2ln"),
+            response.id_token_raw()
+        );
+        assert_eq!(
+            "jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y",
+            response.access_token().unwrap().secret()
+        );
+        assert_eq!(Some("Bearer"), response.token_type());
+        assert_eq!(Some(Duration::from_secs(3600)), response.expires_in());
+    }
+
+    #[test]
+    fn test_form_post_error() {
+        match AuthorizationResponse::from_form_post::<CoreAuthErrorResponseType>(
+            b"error=access_denied&error_description=User+denied+access&state=af0ifjsldkj",
+        ) {
+            Err(AuthorizationResponseError::ServerResponse(err)) => {
+                assert_eq!(CoreAuthErrorResponseType::AccessDenied, *err.error());
+                assert_eq!(
+                    Some(&"User denied access".to_string()),
+                    err.error_description()
+                );
+                assert_eq!(None, err.error_uri());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_form_post_invalid() {
+        match AuthorizationResponse::from_form_post::<CoreAuthErrorResponseType>(
+            b"state=af0ifjsldkj",
+        ) {
+            Err(AuthorizationResponseError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match AuthorizationResponse::from_form_post::<CoreAuthErrorResponseType>(
+            b"code=foo&code=bar",
+        ) {
+            Err(AuthorizationResponseError::Parse(msg)) => {
+                assert_eq!("duplicate `code` parameter", msg)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_redirect_url_code() {
+        let response = AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+            &Url::parse(
+                "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj",
+            )
+            .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+        )
+        .unwrap();
+        assert_eq!("SplxlOBeZQQYbYS6WxSbIA", response.code().unwrap().secret());
+    }
+
+    #[test]
+    fn test_redirect_url_session_state() {
+        let response = AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+            &Url::parse(
+                "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj&\
+                 session_state=5e1f4a0c6a2b.salt",
+            )
+            .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            Some(&SessionState::new("5e1f4a0c6a2b.salt".to_string())),
+            response.session_state()
+        );
+    }
+
+    #[test]
+    fn test_redirect_url_fragment() {
+        let response = AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+            &Url::parse(
+                "https://client.example.org/cb#id_token=eyJhbGciOiJSUzI1NiJ9.e30.c2ln&\
+                 state=af0ifjsldkj",
+            )
+            .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            Some("eyJhbGciOiJSUzI1NiJ9.e30.c2ln"),
+            response.id_token_raw()
+        );
+    }
+
+    #[test]
+    fn test_redirect_url_error() {
+        match AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+            &Url::parse(
+                "https://client.example.org/cb?error=invalid_scope&\
+                 error_uri=https%3A%2F%2Fexample%2Ferrors&state=af0ifjsldkj",
+            )
+            .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+        ) {
+            Err(AuthorizationResponseError::ServerResponse(err)) => {
+                assert_eq!(CoreAuthErrorResponseType::InvalidScope, *err.error());
+                assert_eq!(None, err.error_description());
+                assert_eq!(Some(&"https://example/errors".to_string()), err.error_uri());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_redirect_url_state_mismatch() {
+        for url in &[
+            "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=other",
+            "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA",
+            // The state must be verified before surfacing server errors.
+            "https://client.example.org/cb?error=access_denied&state=other",
+        ] {
+            match AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+                &Url::parse(url).unwrap(),
+                &CsrfToken::new("af0ifjsldkj".to_string()),
+            ) {
+                Err(AuthorizationResponseError::StateMismatch) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_redirect_url_silent_auth_error() {
+        let result = AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+            &Url::parse("https://client.example.org/cb?error=login_required&state=af0ifjsldkj")
+                .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+        );
+        match result {
+            Err(AuthorizationResponseError::ServerResponse(ref err)) => match err.error() {
+                CoreAuthErrorResponseType::LoginRequired => {}
+                other => panic!("unexpected error: {:?}", other),
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match AuthorizationResponse::from_redirect_url::<CoreAuthErrorResponseType>(
+            &Url::parse("https://client.example.org/cb?error=custom_error&state=af0ifjsldkj")
+                .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+        ) {
+            Err(AuthorizationResponseError::ServerResponse(err)) => assert_eq!(
+                CoreAuthErrorResponseType::Extension("custom_error".to_string()),
+                *err.error()
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_redirect_url_issuer() {
+        let issuer = IssuerUrl::new("https://server.example.com".to_string()).unwrap();
+        let response =
+            AuthorizationResponse::from_redirect_url_with_issuer::<CoreAuthErrorResponseType>(
+                &Url::parse(
+                    "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj&\
+                     iss=https%3A%2F%2Fserver.example.com",
+                )
+                .unwrap(),
+                &CsrfToken::new("af0ifjsldkj".to_string()),
+                &issuer,
+                true,
+            )
+            .unwrap();
+        assert_eq!(Some("https://server.example.com"), response.issuer());
+
+        // The `iss` parameter is optional unless required.
+        AuthorizationResponse::from_redirect_url_with_issuer::<CoreAuthErrorResponseType>(
+            &Url::parse(
+                "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj",
+            )
+            .unwrap(),
+            &CsrfToken::new("af0ifjsldkj".to_string()),
+            &issuer,
+            false,
+        )
+        .unwrap();
+
+        for (url, require_issuer) in &[
+            (
+                "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj",
+                true,
+            ),
+            (
+                "https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj&\
+                 iss=https%3A%2F%2Fattacker.example.com",
+                false,
+            ),
+            // The issuer must be verified before surfacing server errors.
+            (
+                "https://client.example.org/cb?error=access_denied&state=af0ifjsldkj&\
+                 iss=https%3A%2F%2Fattacker.example.com",
+                false,
+            ),
+        ] {
+            match AuthorizationResponse::from_redirect_url_with_issuer::<CoreAuthErrorResponseType>(
+                &Url::parse(url).unwrap(),
+                &CsrfToken::new("af0ifjsldkj".to_string()),
+                &issuer,
+                *require_issuer,
+            ) {
+                Err(AuthorizationResponseError::IssuerMismatch) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+}
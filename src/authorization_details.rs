@@ -0,0 +1,175 @@
+use oauth2::{
+    CodeTokenRequest, ErrorResponse, RefreshTokenRequest, TokenResponse as OAuth2TokenResponse,
+    TokenType,
+};
+
+///
+/// Authorization detail object as defined by
+/// [Rich Authorization Requests (RFC 9396)](https://tools.ietf.org/html/rfc9396#section-2).
+///
+/// Authorization details allow clients to request fine-grained permissions (e.g., a specific
+/// payment or access to particular resources) that can't be conveyed by scopes. They may be added
+/// to authorization requests via the `add_authorization_detail` method of
+/// [`AuthorizationRequest`](crate::AuthorizationRequest) and to token requests via
+/// [`TokenRequestAuthorizationDetails::set_authorization_details`]. Authorization details granted
+/// by the authorization
+/// server are returned in token responses and may be accessed via
+/// [`IdTokenFields::authorization_details`](crate::IdTokenFields::authorization_details).
+///
+/// Fields specific to the authorization details type are preserved and may be accessed via
+/// [`AuthorizationDetail::fields`].
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuthorizationDetail {
+    #[serde(rename = "type")]
+    detail_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locations: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    datatypes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    privileges: Option<Vec<String>>,
+    #[serde(flatten)]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+impl AuthorizationDetail {
+    ///
+    /// Instantiates a new authorization detail object of the specified type.
+    ///
+    pub fn new(detail_type: String) -> Self {
+        Self {
+            detail_type,
+            locations: None,
+            actions: None,
+            datatypes: None,
+            identifier: None,
+            privileges: None,
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["authorization detail value"] {
+            set_detail_type -> detail_type[String],
+            set_locations -> locations[Option<Vec<String>>],
+            set_actions -> actions[Option<Vec<String>>],
+            set_datatypes -> datatypes[Option<Vec<String>>],
+            set_identifier -> identifier[Option<String>],
+            set_privileges -> privileges[Option<Vec<String>>],
+        }
+    ];
+
+    ///
+    /// Adds a field specific to the authorization details type.
+    ///
+    pub fn add_field(mut self, name: String, value: serde_json::Value) -> Self {
+        self.fields.insert(name, value);
+        self
+    }
+
+    ///
+    /// Returns the fields specific to the authorization details type.
+    ///
+    pub fn fields(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.fields
+    }
+
+    ///
+    /// Serializes the specified authorization details as the value of the `authorization_details`
+    /// request parameter.
+    ///
+    /// This is useful for adding authorization details to requests other than authorization
+    /// requests and the token requests supported by [`TokenRequestAuthorizationDetails`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type-specific fields fail to serialize as JSON.
+    ///
+    pub fn to_request_param(authorization_details: &[Self]) -> String {
+        serde_json::to_string(authorization_details)
+            .expect("failed to serialize authorization details")
+    }
+}
+
+///
+/// Extension trait for sending authorization details (see
+/// [Section 6 of RFC 9396](https://tools.ietf.org/html/rfc9396#section-6)) in token requests.
+///
+/// This trait is implemented for [`CodeTokenRequest`] and [`RefreshTokenRequest`], and must be in
+/// scope to call [`TokenRequestAuthorizationDetails::set_authorization_details`].
+///
+pub trait TokenRequestAuthorizationDetails: Sized {
+    ///
+    /// Sets the `authorization_details` parameter of the token request, which requests a subset
+    /// of the authorization details granted to the client.
+    ///
+    /// This method should be called at most once per request.
+    ///
+    fn set_authorization_details(self, authorization_details: &[AuthorizationDetail]) -> Self;
+}
+impl<'a, TE, TR, TT> TokenRequestAuthorizationDetails for CodeTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: OAuth2TokenResponse<TT>,
+    TT: TokenType,
+{
+    fn set_authorization_details(self, authorization_details: &[AuthorizationDetail]) -> Self {
+        self.add_extra_param(
+            "authorization_details",
+            AuthorizationDetail::to_request_param(authorization_details),
+        )
+    }
+}
+impl<'a, TE, TR, TT> TokenRequestAuthorizationDetails for RefreshTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: OAuth2TokenResponse<TT>,
+    TT: TokenType,
+{
+    fn set_authorization_details(self, authorization_details: &[AuthorizationDetail]) -> Self {
+        self.add_extra_param(
+            "authorization_details",
+            AuthorizationDetail::to_request_param(authorization_details),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthorizationDetail;
+
+    #[test]
+    fn test_authorization_details() {
+        let json = "[{\
+            \"type\":\"payment_initiation\",\
+            \"locations\":[\"https://example.com/payments\"],\
+            \"actions\":[\"initiate\",\"status\"],\
+            \"instructedAmount\":{\"amount\":\"123.50\",\"currency\":\"EUR\"}\
+        }]";
+        let authorization_detail = AuthorizationDetail::new("payment_initiation".to_string())
+            .set_locations(Some(vec!["https://example.com/payments".to_string()]))
+            .set_actions(Some(vec!["initiate".to_string(), "status".to_string()]))
+            .add_field(
+                "instructedAmount".to_string(),
+                serde_json::json!({"amount": "123.50", "currency": "EUR"}),
+            );
+        let authorization_details = vec![authorization_detail];
+        assert_eq!(
+            AuthorizationDetail::to_request_param(&authorization_details),
+            json
+        );
+
+        let deserialized = serde_json::from_str::<Vec<AuthorizationDetail>>(json).unwrap();
+        assert_eq!(deserialized, authorization_details);
+        assert_eq!(deserialized[0].detail_type(), "payment_initiation");
+        assert_eq!(deserialized[0].identifier(), None);
+        assert_eq!(
+            deserialized[0].fields().get("instructedAmount").unwrap()["currency"],
+            "EUR"
+        );
+    }
+}
@@ -0,0 +1,441 @@
+use std::marker::PhantomData;
+
+use failure::Fail;
+#[cfg(feature = "net")]
+use futures::executor::block_on;
+use oauth2::{AuthUrl, PkceCodeChallengeMethod, Scope, TokenUrl};
+
+use crate::discovery::AdditionalProviderMetadata;
+#[cfg(feature = "net")]
+use crate::discovery::{discovery_request, discovery_response_body, DiscoveryError};
+#[cfg(feature = "net")]
+use crate::http_client::{
+    AsyncHttpClient, BlockingHttpClient, HttpClient, InstrumentedHttpClient, RequestKind,
+    RequestOptions,
+};
+use crate::types::{
+    ClientAuthMethod, DeviceAuthorizationUrl, GrantType, IntrospectionUrl, IssuerUrl, JsonWebKey,
+    JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse, JwsSigningAlgorithm,
+    LanguageTag, OpPolicyUrl, OpTosUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
+    RevocationUrl, ServiceDocUrl,
+};
+
+#[cfg(feature = "net")]
+const AUTHORIZATION_SERVER_CONFIG_PATH: &str = "/.well-known/oauth-authorization-server";
+
+///
+/// OAuth 2.0 Authorization Server Metadata as defined in
+/// [RFC 8414](https://tools.ietf.org/html/rfc8414#section-2).
+///
+/// Some authorization servers only publish this metadata rather than an OpenID Connect Discovery
+/// document (see [`ProviderMetadata`](crate::ProviderMetadata)). A [`Client`] may be initialized
+/// from this metadata via its `from_authorization_server_metadata` constructor for use with the
+/// token, introspection, and revocation endpoints. Since this metadata doesn't include OpenID
+/// Connect fields such as `userinfo_endpoint`, such clients don't support user info requests.
+///
+/// The `device_authorization_endpoint` field defined by
+/// [RFC 8628](https://tools.ietf.org/html/rfc8628#section-4) and the
+/// `authorization_response_iss_parameter_supported` field defined by
+/// [RFC 9207](https://tools.ietf.org/html/rfc9207#section-3) are also supported.
+///
+/// Fields that are neither modeled by this struct nor by the [`AdditionalProviderMetadata`] are
+/// preserved and may be accessed via [`AuthorizationServerMetadata::unknown_fields`].
+///
+/// [`Client`]: crate::Client
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[allow(clippy::type_complexity)]
+pub struct AuthorizationServerMetadata<A, CA, G, JS, JT, JU, K, RM, RT>
+where
+    A: AdditionalProviderMetadata,
+    CA: ClientAuthMethod,
+    G: GrantType,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RT: ResponseType,
+{
+    issuer: IssuerUrl,
+    authorization_endpoint: AuthUrl,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_endpoint: Option<TokenUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jwks_uri: Option<JsonWebKeySetUrl>,
+    #[serde(default = "JsonWebKeySet::default", skip)]
+    jwks: JsonWebKeySet<JS, JT, JU, K>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registration_endpoint: Option<RegistrationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scopes_supported: Option<Vec<Scope>>,
+    #[serde(bound(deserialize = "RT: ResponseType"))]
+    response_types_supported: Vec<ResponseTypes<RT>>,
+    #[serde(
+        bound(deserialize = "RM: ResponseMode"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    response_modes_supported: Option<Vec<RM>>,
+    #[serde(
+        bound(deserialize = "G: GrantType"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    grant_types_supported: Option<Vec<G>>,
+    #[serde(
+        bound(deserialize = "CA: ClientAuthMethod"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    token_endpoint_auth_methods_supported: Option<Vec<CA>>,
+    #[serde(
+        bound(deserialize = "JS: JwsSigningAlgorithm<JT>"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    token_endpoint_auth_signing_alg_values_supported: Option<Vec<JS>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_documentation: Option<ServiceDocUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ui_locales_supported: Option<Vec<LanguageTag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    op_policy_uri: Option<OpPolicyUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    op_tos_uri: Option<OpTosUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_endpoint: Option<RevocationUrl>,
+    #[serde(
+        bound(deserialize = "CA: ClientAuthMethod"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    revocation_endpoint_auth_methods_supported: Option<Vec<CA>>,
+    #[serde(
+        bound(deserialize = "JS: JwsSigningAlgorithm<JT>"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    revocation_endpoint_auth_signing_alg_values_supported: Option<Vec<JS>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    introspection_endpoint: Option<IntrospectionUrl>,
+    #[serde(
+        bound(deserialize = "CA: ClientAuthMethod"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    introspection_endpoint_auth_methods_supported: Option<Vec<CA>>,
+    #[serde(
+        bound(deserialize = "JS: JwsSigningAlgorithm<JT>"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    introspection_endpoint_auth_signing_alg_values_supported: Option<Vec<JS>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_challenge_methods_supported: Option<Vec<PkceCodeChallengeMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_authorization_endpoint: Option<DeviceAuthorizationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authorization_response_iss_parameter_supported: Option<bool>,
+
+    #[serde(bound(deserialize = "A: AdditionalProviderMetadata"), flatten)]
+    additional_metadata: A,
+
+    // This must follow `additional_metadata` so that fields deserialized by the additional metadata
+    // type aren't also captured here.
+    #[serde(flatten)]
+    unknown_fields: serde_json::Map<String, serde_json::Value>,
+
+    #[serde(skip)]
+    _phantom_jt: PhantomData<JT>,
+}
+impl<A, CA, G, JS, JT, JU, K, RM, RT> AuthorizationServerMetadata<A, CA, G, JS, JT, JU, K, RM, RT>
+where
+    A: AdditionalProviderMetadata,
+    CA: ClientAuthMethod,
+    G: GrantType,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RT: ResponseType,
+{
+    ///
+    /// Instantiates new authorization server metadata.
+    ///
+    pub fn new(
+        issuer: IssuerUrl,
+        authorization_endpoint: AuthUrl,
+        response_types_supported: Vec<ResponseTypes<RT>>,
+        additional_metadata: A,
+    ) -> Self {
+        Self {
+            issuer,
+            authorization_endpoint,
+            token_endpoint: None,
+            jwks_uri: None,
+            jwks: JsonWebKeySet::new(Vec::new()),
+            registration_endpoint: None,
+            scopes_supported: None,
+            response_types_supported,
+            response_modes_supported: None,
+            grant_types_supported: None,
+            token_endpoint_auth_methods_supported: None,
+            token_endpoint_auth_signing_alg_values_supported: None,
+            service_documentation: None,
+            ui_locales_supported: None,
+            op_policy_uri: None,
+            op_tos_uri: None,
+            revocation_endpoint: None,
+            revocation_endpoint_auth_methods_supported: None,
+            revocation_endpoint_auth_signing_alg_values_supported: None,
+            introspection_endpoint: None,
+            introspection_endpoint_auth_methods_supported: None,
+            introspection_endpoint_auth_signing_alg_values_supported: None,
+            code_challenge_methods_supported: None,
+            device_authorization_endpoint: None,
+            authorization_response_iss_parameter_supported: None,
+            additional_metadata,
+            unknown_fields: serde_json::Map::new(),
+            _phantom_jt: PhantomData,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["authorization server metadata value"] {
+            set_issuer -> issuer[IssuerUrl],
+            set_authorization_endpoint -> authorization_endpoint[AuthUrl],
+            set_token_endpoint -> token_endpoint[Option<TokenUrl>],
+            set_jwks_uri -> jwks_uri[Option<JsonWebKeySetUrl>],
+            set_jwks -> jwks[JsonWebKeySet<JS, JT, JU, K>],
+            set_registration_endpoint -> registration_endpoint[Option<RegistrationUrl>],
+            set_scopes_supported -> scopes_supported[Option<Vec<Scope>>],
+            set_response_types_supported -> response_types_supported[Vec<ResponseTypes<RT>>],
+            set_response_modes_supported -> response_modes_supported[Option<Vec<RM>>],
+            set_grant_types_supported -> grant_types_supported[Option<Vec<G>>],
+            set_token_endpoint_auth_methods_supported
+                -> token_endpoint_auth_methods_supported[Option<Vec<CA>>],
+            set_token_endpoint_auth_signing_alg_values_supported
+                -> token_endpoint_auth_signing_alg_values_supported[Option<Vec<JS>>],
+            set_service_documentation -> service_documentation[Option<ServiceDocUrl>],
+            set_ui_locales_supported -> ui_locales_supported[Option<Vec<LanguageTag>>],
+            set_op_policy_uri -> op_policy_uri[Option<OpPolicyUrl>],
+            set_op_tos_uri -> op_tos_uri[Option<OpTosUrl>],
+            set_revocation_endpoint -> revocation_endpoint[Option<RevocationUrl>],
+            set_revocation_endpoint_auth_methods_supported
+                -> revocation_endpoint_auth_methods_supported[Option<Vec<CA>>],
+            set_revocation_endpoint_auth_signing_alg_values_supported
+                -> revocation_endpoint_auth_signing_alg_values_supported[Option<Vec<JS>>],
+            set_introspection_endpoint -> introspection_endpoint[Option<IntrospectionUrl>],
+            set_introspection_endpoint_auth_methods_supported
+                -> introspection_endpoint_auth_methods_supported[Option<Vec<CA>>],
+            set_introspection_endpoint_auth_signing_alg_values_supported
+                -> introspection_endpoint_auth_signing_alg_values_supported[Option<Vec<JS>>],
+            set_code_challenge_methods_supported
+                -> code_challenge_methods_supported[Option<Vec<PkceCodeChallengeMethod>>],
+            set_device_authorization_endpoint
+                -> device_authorization_endpoint[Option<DeviceAuthorizationUrl>],
+            set_authorization_response_iss_parameter_supported
+                -> authorization_response_iss_parameter_supported[Option<bool>],
+        }
+    ];
+
+    ///
+    /// Asynchronously fetches the authorization server metadata and associated JSON Web Key Set
+    /// (if any) from the authorization server.
+    ///
+    /// The metadata is fetched from the `/.well-known/oauth-authorization-server` URL derived from
+    /// the issuer URL as described in
+    /// [Section 3 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-3). To fetch the
+    /// metadata from a different URL, use [`AuthorizationServerMetadata::discover_from_url`].
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_async<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let discovery_url = Self::discovery_url(&issuer_url);
+        Self::discover_from_url(issuer_url, discovery_url, http_client).await
+    }
+
+    ///
+    /// Asynchronously fetches the authorization server metadata from the specified URL and the
+    /// associated JSON Web Key Set (if any) from the authorization server.
+    ///
+    /// The `issuer` in the returned metadata must still match `issuer_url`.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_from_url<C>(
+        issuer_url: IssuerUrl,
+        discovery_url: url::Url,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let request_options = RequestOptions::default();
+        let mut discovery_request = discovery_request(discovery_url);
+        request_options.apply(&mut discovery_request);
+        let http_response = InstrumentedHttpClient::new(RequestKind::Discovery, http_client)
+            .call(discovery_request)
+            .await
+            .map_err(DiscoveryError::Request)?;
+        let body = discovery_response_body(http_response).await?;
+        let metadata = serde_json::from_slice::<Self>(&body).map_err(DiscoveryError::Parse)?;
+
+        if metadata.issuer() != &issuer_url {
+            return Err(DiscoveryError::Validation(format!(
+                "unexpected issuer URI `{}` (expected `{}`)",
+                metadata.issuer().url(),
+                issuer_url.url()
+            )));
+        }
+
+        // Unlike OpenID Connect Providers, authorization servers aren't required to publish a JSON
+        // Web Key Set.
+        let jwks = match metadata.jwks_uri() {
+            Some(jwks_uri) => {
+                JsonWebKeySet::fetch_with_options(jwks_uri, http_client, &request_options).await?
+            }
+            None => return Ok(metadata),
+        };
+        Ok(Self { jwks, ..metadata })
+    }
+
+    ///
+    /// Synchronously fetches the authorization server metadata and associated JSON Web Key Set (if
+    /// any) from the authorization server.
+    ///
+    /// See [`AuthorizationServerMetadata::discover_async`] for details.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_blocking<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_async(
+            issuer_url,
+            &BlockingHttpClient(http_client),
+        ))
+    }
+
+    // The well-known path is inserted between the host and the path component of the issuer.
+    #[cfg(feature = "net")]
+    fn discovery_url(issuer_url: &IssuerUrl) -> url::Url {
+        let mut discovery_url = issuer_url.url().clone();
+        let issuer_path = issuer_url.url().path().trim_end_matches('/');
+        discovery_url.set_path(&format!(
+            "{}{}",
+            AUTHORIZATION_SERVER_CONFIG_PATH, issuer_path
+        ));
+        discovery_url
+    }
+
+    ///
+    /// Returns additional authorization server metadata fields.
+    ///
+    pub fn additional_metadata(&self) -> &A {
+        &self.additional_metadata
+    }
+    ///
+    /// Returns mutable additional authorization server metadata fields.
+    ///
+    pub fn additional_metadata_mut(&mut self) -> &mut A {
+        &mut self.additional_metadata
+    }
+    ///
+    /// Returns authorization server metadata fields that are neither modeled by this struct nor by
+    /// the additional metadata type.
+    ///
+    pub fn unknown_fields(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.unknown_fields
+    }
+    ///
+    /// Returns mutable authorization server metadata fields that are neither modeled by this
+    /// struct nor by the additional metadata type.
+    ///
+    pub fn unknown_fields_mut(&mut self) -> &mut serde_json::Map<String, serde_json::Value> {
+        &mut self.unknown_fields
+    }
+}
+
+#[cfg(all(test, feature = "net"))]
+mod tests {
+    use std::io;
+
+    use oauth2::{ClientId, TokenUrl};
+
+    use crate::core::{CoreAuthorizationServerMetadata, CoreClient, CoreClientAuthMethod};
+    use crate::http_types::{Request, Response, StatusCode};
+    use crate::{IntrospectionUrl, IssuerUrl, RevocationUrl};
+
+    #[test]
+    fn test_discover_authorization_server_metadata() {
+        let http_client = |request: Request| {
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            match request.url().as_str() {
+                "https://example.com/.well-known/oauth-authorization-server/tenant" => {
+                    response.set_body(
+                        "{
+                            \"issuer\": \"https://example.com/tenant\",
+                            \"authorization_endpoint\": \"https://example.com/tenant/authorize\",
+                            \"token_endpoint\": \"https://example.com/tenant/token\",
+                            \"response_types_supported\": [\"code\"],
+                            \"token_endpoint_auth_methods_supported\": [\"private_key_jwt\"],
+                            \"introspection_endpoint\": \"https://example.com/tenant/introspect\",
+                            \"revocation_endpoint\": \"https://example.com/tenant/revoke\",
+                            \"tls_client_certificate_bound_access_tokens\": true
+                        }",
+                    );
+                }
+                _ => response = Response::new(StatusCode::NotFound),
+            }
+            Ok::<_, io::Error>(response)
+        };
+
+        let metadata = CoreAuthorizationServerMetadata::discover_blocking(
+            IssuerUrl::new("https://example.com/tenant".to_string()).unwrap(),
+            &http_client,
+        )
+        .unwrap();
+        assert_eq!(
+            metadata.token_endpoint(),
+            Some(&TokenUrl::new("https://example.com/tenant/token".to_string()).unwrap())
+        );
+        assert_eq!(
+            metadata.token_endpoint_auth_methods_supported(),
+            Some(&vec![CoreClientAuthMethod::PrivateKeyJwt])
+        );
+        assert_eq!(None, metadata.jwks_uri());
+        assert!(metadata.jwks().keys().is_empty());
+        assert_eq!(
+            metadata
+                .unknown_fields()
+                .get("tls_client_certificate_bound_access_tokens"),
+            Some(&serde_json::Value::Bool(true))
+        );
+
+        let client = CoreClient::from_authorization_server_metadata(
+            metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert_eq!(
+            client.introspection_url(),
+            Some(
+                &IntrospectionUrl::new("https://example.com/tenant/introspect".to_string())
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            client.revocation_url(),
+            Some(&RevocationUrl::new("https://example.com/tenant/revoke".to_string()).unwrap())
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header::{COOKIE, LOCATION, SET_COOKIE};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+
+use crate::web::cookie_value;
+use crate::{AdditionalClaims, GenderClaim};
+
+pub use crate::web::{
+    OidcCallback, OidcConfigError, OidcLoginError, OidcLoginRedirect, OidcRejection, OidcState,
+    VerifiedClaims,
+};
+
+impl IntoResponse for OidcRejection {
+    fn into_response(self) -> Response {
+        match self {
+            OidcRejection::Login(_) => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
+            OidcRejection::Unauthenticated => {
+                (StatusCode::UNAUTHORIZED, self.to_string()).into_response()
+            }
+            OidcRejection::SessionStore(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Session store error").into_response()
+            }
+        }
+    }
+}
+
+impl IntoResponse for OidcLoginRedirect {
+    fn into_response(self) -> Response {
+        let mut response = StatusCode::SEE_OTHER.into_response();
+        let headers = response.headers_mut();
+        match HeaderValue::from_str(self.location()) {
+            Ok(location) => {
+                headers.insert(LOCATION, location);
+            }
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+        for cookie in self.cookies() {
+            match HeaderValue::from_str(cookie) {
+                Ok(cookie) => {
+                    headers.append(SET_COOKIE, cookie);
+                }
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            }
+        }
+        response
+    }
+}
+
+///
+/// Completes the login when the Authorization Endpoint redirects the user agent to the client's
+/// redirect URI. Requires the application state to provide an [`OidcState`] via [`FromRef`].
+///
+#[async_trait]
+impl<AC, GC, S> FromRequestParts<S> for OidcCallback<AC, GC>
+where
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+    OidcState<AC, GC>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = OidcRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let oidc = OidcState::from_ref(state);
+        let login_cookie = request_cookie(&parts.headers, &oidc.login_cookie_name());
+        oidc.callback(parts.uri.query(), login_cookie).await
+    }
+}
+
+///
+/// Loads the verified ID token claims of the current session. Requires the application state to
+/// provide an [`OidcState`] via [`FromRef`].
+///
+#[async_trait]
+impl<AC, GC, S> FromRequestParts<S> for VerifiedClaims<AC, GC>
+where
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+    OidcState<AC, GC>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = OidcRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let oidc = OidcState::from_ref(state);
+        oidc.verified_claims(request_cookie(&parts.headers, oidc.session_cookie_name()))
+    }
+}
+
+fn request_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    cookie_value(
+        headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok()),
+        name,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::FromRequestParts;
+    use axum::http::header::{COOKIE, LOCATION, SET_COOKIE};
+    use axum::http::request::Parts;
+    use axum::http::{Request, StatusCode};
+    use axum::response::IntoResponse;
+    use futures::executor::block_on;
+
+    use crate::core::CoreGenderClaim;
+    use crate::web::tests::{request_cookie, TestLogin, TEST_SUBJECT};
+    use crate::{EmptyAdditionalClaims, SubjectIdentifier};
+
+    use super::{OidcCallback, OidcLoginError, OidcLoginRedirect, OidcRejection, VerifiedClaims};
+
+    type TestCallback = OidcCallback<EmptyAdditionalClaims, CoreGenderClaim>;
+    type TestVerifiedClaims = VerifiedClaims<EmptyAdditionalClaims, CoreGenderClaim>;
+
+    fn request_parts(query: &str, cookie: Option<&str>) -> Parts {
+        let mut request = Request::builder().uri(format!("https://client/cb?{}", query));
+        if let Some(cookie) = cookie {
+            request = request.header(COOKIE, cookie);
+        }
+        request.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn test_extractors() {
+        let login = TestLogin::new();
+        let (query, login_cookie) = login.begin();
+
+        let callback: TestCallback = block_on(TestCallback::from_request_parts(
+            &mut request_parts(&query, Some(&login_cookie)),
+            &login.state,
+        ))
+        .unwrap();
+        assert_eq!(
+            *callback.claims().subject(),
+            SubjectIdentifier::new(TEST_SUBJECT.to_string())
+        );
+
+        let session_cookie = request_cookie(&callback.redirect("/".to_string()).cookies()[0]);
+        let claims: TestVerifiedClaims = block_on(TestVerifiedClaims::from_request_parts(
+            &mut request_parts("", Some(&session_cookie)),
+            &login.state,
+        ))
+        .unwrap();
+        assert_eq!(claims.0, *callback.claims());
+
+        // Each login may only be completed once.
+        match block_on(TestCallback::from_request_parts(
+            &mut request_parts(&query, Some(&login_cookie)),
+            &login.state,
+        )) {
+            Err(OidcRejection::Login(OidcLoginError::LoginManager(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_extractors_missing_cookie() {
+        let login = TestLogin::new();
+        let (query, _) = login.begin();
+
+        match block_on(TestCallback::from_request_parts(
+            &mut request_parts(&query, None),
+            &login.state,
+        )) {
+            Err(OidcRejection::Login(OidcLoginError::MissingLoginCookie)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        for cookie in &[None, Some("oidc_session=unknown")] {
+            match block_on(TestVerifiedClaims::from_request_parts(
+                &mut request_parts("", *cookie),
+                &login.state,
+            )) {
+                Err(OidcRejection::Unauthenticated) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_callback_extractor_wrong_state() {
+        let login = TestLogin::new();
+        let (_, login_cookie) = login.begin();
+        // The state parameter belongs to a login started by a different user agent.
+        let (other_query, _) = login.begin();
+
+        match block_on(TestCallback::from_request_parts(
+            &mut request_parts(&other_query, Some(&login_cookie)),
+            &login.state,
+        )) {
+            Err(OidcRejection::Login(OidcLoginError::StateMismatch)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_login_redirect_response() {
+        let response = OidcLoginRedirect::new("https://example.com/authorize".to_string())
+            .add_cookie("a=1".to_string())
+            .add_cookie("b=2; Max-Age=0".to_string())
+            .into_response();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "https://example.com/authorize"
+        );
+        assert_eq!(response.headers().get_all(SET_COOKIE).iter().count(), 2);
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::fmt::{Debug, Display, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
 use std::str;
 
@@ -8,14 +8,15 @@ use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
 
 use crate::helpers::FlattenFilter;
+use crate::standard_claims::is_standard_claim;
 use crate::types::helpers::{seconds_to_utc, split_language_tag_key, utc_to_seconds};
-use crate::types::{LocalizedClaim, Seconds};
+use crate::types::{LenientSeconds, LocalizedClaim, Seconds};
 use crate::{
-    AddressCountry, AddressLocality, AddressPostalCode, AddressRegion, EndUserBirthday,
-    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
-    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
-    EndUserUsername, EndUserWebsiteUrl, FormattedAddress, LanguageTag, StreetAddress,
-    SubjectIdentifier,
+    AddressCountry, AddressLocality, AddressPostalCode, AddressRegion, ClaimName,
+    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
+    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
+    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, LanguageTag,
+    StreetAddress, SubjectIdentifier,
 };
 
 ///
@@ -32,6 +33,14 @@ pub trait AdditionalClaims: Debug + DeserializeOwned + Serialize + 'static {}
 pub struct EmptyAdditionalClaims {}
 impl AdditionalClaims for EmptyAdditionalClaims {}
 
+///
+/// Additional claims captured as a raw JSON object.
+///
+/// This implementation retains every claim not otherwise defined by OpenID Connect Core, which is
+/// useful for forwarding provider-specific claims without declaring a dedicated struct.
+///
+impl AdditionalClaims for serde_json::Map<String, serde_json::Value> {}
+
 ///
 /// Address claims.
 ///
@@ -78,11 +87,193 @@ pub struct AddressClaim {
     pub country: Option<AddressCountry>,
 }
 
+///
+/// Request for an individual claim via the
+/// [`claims` request parameter](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter).
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct IndividualClaimRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    essential: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values: Option<Vec<serde_json::Value>>,
+}
+impl IndividualClaimRequest {
+    ///
+    /// Instantiates a new individual claim request with no constraints.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim request constraint"] {
+            set_essential -> essential[Option<bool>],
+            set_value -> value[Option<serde_json::Value>],
+            set_values -> values[Option<Vec<serde_json::Value>>],
+        }
+    ];
+}
+
+///
+/// Typed representation of the
+/// [`claims` request parameter](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter),
+/// which requests specific claims to be returned in the ID token and/or from the user info
+/// endpoint.
+///
+/// Claims are serialized in the order in which they were added. A claim requested without an
+/// [`IndividualClaimRequest`] is serialized as `null`, requesting the claim in the default manner.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimsRequest<CN>
+where
+    CN: ClaimName,
+{
+    id_token: Vec<(CN, Option<IndividualClaimRequest>)>,
+    userinfo: Vec<(CN, Option<IndividualClaimRequest>)>,
+}
+impl<CN> ClaimsRequest<CN>
+where
+    CN: ClaimName,
+{
+    ///
+    /// Instantiates an empty claims request.
+    ///
+    pub fn new() -> Self {
+        Self {
+            id_token: Vec::new(),
+            userinfo: Vec::new(),
+        }
+    }
+
+    ///
+    /// Requests a claim to be returned in the ID token.
+    ///
+    pub fn add_id_token_claim(
+        mut self,
+        claim_name: CN,
+        claim_request: Option<IndividualClaimRequest>,
+    ) -> Self {
+        self.id_token.push((claim_name, claim_request));
+        self
+    }
+
+    ///
+    /// Requests a claim to be returned from the user info endpoint.
+    ///
+    pub fn add_userinfo_claim(
+        mut self,
+        claim_name: CN,
+        claim_request: Option<IndividualClaimRequest>,
+    ) -> Self {
+        self.userinfo.push((claim_name, claim_request));
+        self
+    }
+
+    ///
+    /// Returns the claims requested to be returned in the ID token.
+    ///
+    pub fn id_token_claims(&self) -> &[(CN, Option<IndividualClaimRequest>)] {
+        &self.id_token
+    }
+
+    ///
+    /// Returns the claims requested to be returned from the user info endpoint.
+    ///
+    pub fn userinfo_claims(&self) -> &[(CN, Option<IndividualClaimRequest>)] {
+        &self.userinfo
+    }
+
+    ///
+    /// Returns `true` if no claims have been requested.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.id_token.is_empty() && self.userinfo.is_empty()
+    }
+}
+impl<CN> Default for ClaimsRequest<CN>
+where
+    CN: ClaimName,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<CN> Serialize for ClaimsRequest<CN>
+where
+    CN: ClaimName,
+{
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        struct ClaimRequests<'a, CN>(&'a [(CN, Option<IndividualClaimRequest>)]);
+        impl<'a, CN> Serialize for ClaimRequests<'a, CN>
+        where
+            CN: ClaimName,
+        {
+            fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+            where
+                SE: Serializer,
+            {
+                let mut map = serializer.serialize_map(Some(self.0.len()))?;
+                for (claim_name, claim_request) in self.0 {
+                    map.serialize_entry(claim_name, claim_request)?;
+                }
+                map.end()
+            }
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+        if !self.userinfo.is_empty() {
+            map.serialize_entry("userinfo", &ClaimRequests(&self.userinfo))?;
+        }
+        if !self.id_token.is_empty() {
+            map.serialize_entry("id_token", &ClaimRequests(&self.id_token))?;
+        }
+        map.end()
+    }
+}
+
 ///
 /// Gender claim.
 ///
 pub trait GenderClaim: Clone + Debug + DeserializeOwned + Serialize + 'static {}
 
+// Invokes the specified field macro with the setter, field name, and type of each optional
+// Standard Claim, so that the getters, setters, and builder methods share a single field list.
+macro_rules! standard_claims_fields {
+    ($macro:ident![$($args:tt)+]) => {
+        $macro![
+            $($args)+ {
+                set_name -> name[Option<LocalizedClaim<EndUserName>>],
+                set_given_name -> given_name[Option<LocalizedClaim<EndUserGivenName>>],
+                set_family_name ->
+                    family_name[Option<LocalizedClaim<EndUserFamilyName>>],
+                set_middle_name ->
+                    middle_name[Option<LocalizedClaim<EndUserMiddleName>>],
+                set_nickname -> nickname[Option<LocalizedClaim<EndUserNickname>>],
+                set_preferred_username -> preferred_username[Option<EndUserUsername>],
+                set_profile -> profile[Option<LocalizedClaim<EndUserProfileUrl>>],
+                set_picture -> picture[Option<LocalizedClaim<EndUserPictureUrl>>],
+                set_website -> website[Option<LocalizedClaim<EndUserWebsiteUrl>>],
+                set_email -> email[Option<EndUserEmail>],
+                set_email_verified -> email_verified[Option<bool>],
+                set_gender -> gender[Option<GC>],
+                set_birthday -> birthday[Option<EndUserBirthday>],
+                set_zoneinfo -> zoneinfo[Option<EndUserTimezone>],
+                set_locale -> locale[Option<LanguageTag>],
+                set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
+                set_phone_number_verified -> phone_number_verified[Option<bool>],
+                set_address -> address[Option<AddressClaim>],
+                set_updated_at -> updated_at[Option<DateTime<Utc>>],
+            }
+        ];
+    };
+}
+
 ///
 /// Standard Claims defined by OpenID Connect Core.
 ///
@@ -110,7 +301,7 @@ where
     pub(crate) phone_number: Option<EndUserPhoneNumber>,
     pub(crate) phone_number_verified: Option<bool>,
     pub(crate) address: Option<AddressClaim>,
-    pub(crate) updated_at: Option<String>,
+    pub(crate) updated_at: Option<DateTime<Utc>>,
 }
 impl<GC> StandardClaims<GC>
 where
@@ -146,6 +337,16 @@ where
         }
     }
 
+    ///
+    /// Returns a builder for a set of Standard Claims with the specified Subject (`sub`) claim.
+    ///
+    /// This is a more concise alternative to [`StandardClaims::new`] followed by calls to the
+    /// `set_*` methods when constructing claims on the OpenID Connect Provider side.
+    ///
+    pub fn builder(subject: SubjectIdentifier) -> StandardClaimsBuilder<GC> {
+        StandardClaimsBuilder(Self::new(subject))
+    }
+
     ///
     /// Returns the Subject (`sub`) claim.
     ///
@@ -161,32 +362,32 @@ where
         self
     }
 
-    field_getters_setters![
-        pub self [self] ["claim"] {
-            set_name -> name[Option<LocalizedClaim<EndUserName>>],
-            set_given_name -> given_name[Option<LocalizedClaim<EndUserGivenName>>],
-            set_family_name ->
-                family_name[Option<LocalizedClaim<EndUserFamilyName>>],
-            set_middle_name ->
-                middle_name[Option<LocalizedClaim<EndUserMiddleName>>],
-            set_nickname -> nickname[Option<LocalizedClaim<EndUserNickname>>],
-            set_preferred_username -> preferred_username[Option<EndUserUsername>],
-            set_profile -> profile[Option<LocalizedClaim<EndUserProfileUrl>>],
-            set_picture -> picture[Option<LocalizedClaim<EndUserPictureUrl>>],
-            set_website -> website[Option<LocalizedClaim<EndUserWebsiteUrl>>],
-            set_email -> email[Option<EndUserEmail>],
-            set_email_verified -> email_verified[Option<bool>],
-            set_gender -> gender[Option<GC>],
-            set_birthday -> birthday[Option<EndUserBirthday>],
-            set_zoneinfo -> zoneinfo[Option<EndUserTimezone>],
-            set_locale -> locale[Option<LanguageTag>],
-            set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
-            set_phone_number_verified -> phone_number_verified[Option<bool>],
-            set_address -> address[Option<AddressClaim>],
-            set_updated_at -> updated_at[Option<String>],
-        }
-    ];
+    standard_claims_fields![field_getters_setters![pub self [self] ["claim"]]];
 }
+
+///
+/// Builder for [`StandardClaims`].
+///
+/// See [`StandardClaims::builder`].
+///
+#[derive(Clone, Debug)]
+pub struct StandardClaimsBuilder<GC>(StandardClaims<GC>)
+where
+    GC: GenderClaim;
+impl<GC> StandardClaimsBuilder<GC>
+where
+    GC: GenderClaim,
+{
+    standard_claims_fields![field_builders![pub self [self.0] ["claim"]]];
+
+    ///
+    /// Returns the Standard Claims.
+    ///
+    pub fn build(self) -> StandardClaims<GC> {
+        self.0
+    }
+}
+
 impl<GC> FlattenFilter for StandardClaims<GC>
 where
     GC: GenderClaim,
@@ -266,7 +467,7 @@ where
                         [Option(phone_number)]
                         [Option(phone_number_verified)]
                         [Option(address)]
-                        [Option(updated_at)]
+                        [Option(DateTime(Seconds(updated_at)))]
                     }
                 }
             }
@@ -304,7 +505,122 @@ where
                 [Option(phone_number)]
                 [Option(phone_number_verified)]
                 [Option(address)]
-                [Option(updated_at)]
+                [Option(DateTime(Seconds(updated_at)))]
+            }
+        }
+    }
+}
+
+// Boolean claims that some providers incorrectly encode as the strings "true" or "false".
+const LENIENT_BOOLEAN_CLAIMS: &[&str] = &["email_verified", "phone_number_verified"];
+
+// Timestamp claims that some providers incorrectly encode as numeric strings.
+const LENIENT_TIMESTAMP_CLAIMS: &[&str] = &["exp", "iat", "nbf", "auth_time", "updated_at"];
+
+// Parses a JSON object containing claims, tolerating common provider bugs. Each coercion is
+// recorded in `warnings`.
+pub(crate) fn parse_claims_lenient<T>(
+    json: &[u8],
+    warnings: &mut Vec<ClaimWarning>,
+) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned,
+{
+    let mut claims = match serde_json::from_slice(json)? {
+        serde_json::Value::Object(claims) => claims,
+        other => return serde_json::from_value(other),
+    };
+
+    // Only standard claims are modified, since additional claims may legitimately be null.
+    let standard_claims = claims
+        .keys()
+        .filter(|claim| is_standard_claim(claim))
+        .cloned()
+        .collect::<Vec<_>>();
+    for claim in standard_claims {
+        let converted_value = match claims.get(&claim) {
+            Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(value)) if claim == "aud" => Some(
+                serde_json::Value::Array(vec![serde_json::Value::String(value.clone())]),
+            ),
+            Some(serde_json::Value::String(value))
+                if LENIENT_BOOLEAN_CLAIMS.contains(&claim.as_str()) =>
+            {
+                match value.as_str() {
+                    "true" => Some(serde_json::Value::Bool(true)),
+                    "false" => Some(serde_json::Value::Bool(false)),
+                    _ => continue,
+                }
+            }
+            Some(serde_json::Value::String(value))
+                if LENIENT_TIMESTAMP_CLAIMS.contains(&claim.as_str()) =>
+            {
+                let value = value.trim();
+                match value
+                    .parse::<i64>()
+                    .map(serde_json::Number::from)
+                    .ok()
+                    .or_else(|| value.parse().ok().and_then(serde_json::Number::from_f64))
+                {
+                    Some(number) => Some(serde_json::Value::Number(number)),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        if let Some(value) = converted_value {
+            warnings.push(ClaimWarning::ConvertedClaim(claim.clone(), value.clone()));
+            claims.insert(claim, value);
+        } else {
+            claims.remove(&claim);
+            warnings.push(ClaimWarning::NullClaim(claim));
+        }
+    }
+
+    serde_json::from_value(serde_json::Value::Object(claims))
+}
+
+///
+/// Provider bug tolerated when parsing claims leniently (e.g., using
+/// [`IdToken::from_str_lenient`](crate::IdToken::from_str_lenient) or
+/// [`UserInfoClaims::from_json_lenient`](crate::UserInfoClaims::from_json_lenient)).
+///
+/// Lenient parsing only modifies the claims defined by OpenID Connect Core, as follows:
+///  * Claims with `null` values are treated as though they were missing.
+///  * A single string `aud` claim is converted to an array containing that string.
+///  * The strings `"true"` and `"false"` in the `email_verified` and `phone_number_verified`
+///    claims are converted to booleans.
+///  * Numeric strings in the `exp`, `iat`, `nbf`, `auth_time`, and `updated_at` claims are
+///    converted to numbers.
+///
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ClaimWarning {
+    ///
+    /// The claim was `null` and was treated as though it were missing.
+    ///
+    NullClaim(String),
+    ///
+    /// The claim contained the wrong JSON type and was converted to the specified value.
+    ///
+    ConvertedClaim(String, serde_json::Value),
+}
+impl ClaimWarning {
+    ///
+    /// Returns the name of the claim to which this warning applies.
+    ///
+    pub fn claim(&self) -> &str {
+        match self {
+            ClaimWarning::NullClaim(claim) | ClaimWarning::ConvertedClaim(claim, _) => claim,
+        }
+    }
+}
+impl Display for ClaimWarning {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        match self {
+            ClaimWarning::NullClaim(claim) => write!(f, "ignored null claim `{}`", claim),
+            ClaimWarning::ConvertedClaim(claim, value) => {
+                write!(f, "converted claim `{}` to `{}`", claim, value)
             }
         }
     }
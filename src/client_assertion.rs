@@ -0,0 +1,156 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use oauth2::{ClientId, CsrfToken, TokenUrl};
+
+use crate::jwt::{JsonWebToken, JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::types::helpers::serde_utc_seconds;
+use crate::{
+    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
+    PrivateSigningKey, SigningError, TokenEndpointAuthMethod,
+};
+
+///
+/// Value of the `client_assertion_type` parameter for JWT client assertions (see
+/// [Section 2.2 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.2)).
+///
+pub(crate) const CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+// Assertions are signed immediately before each token request, so they needn't be valid for long.
+const CLIENT_ASSERTION_LIFETIME_SECS: i64 = 300;
+
+///
+/// Claims of a client assertion, as defined in
+/// [Section 9](https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication) of
+/// OpenID Connect Core.
+///
+#[derive(Debug, Deserialize, Serialize)]
+struct ClientAssertionClaims {
+    iss: ClientId,
+    sub: ClientId,
+    aud: String,
+    jti: String,
+    #[serde(with = "serde_utc_seconds")]
+    iat: DateTime<Utc>,
+    #[serde(with = "serde_utc_seconds")]
+    exp: DateTime<Utc>,
+}
+
+///
+/// Key used by a [`Client`](crate::Client) to sign client assertions for the `private_key_jwt`
+/// and `client_secret_jwt` token endpoint authentication methods.
+///
+pub(crate) struct ClientAssertionKey<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    signing_key: Arc<dyn PrivateSigningKey<JS, JT, JU, K> + Send + Sync>,
+    alg: JS,
+}
+impl<JS, JT, JU, K> ClientAssertionKey<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    pub(crate) fn new<S>(signing_key: S, alg: JS) -> Self
+    where
+        S: PrivateSigningKey<JS, JT, JU, K> + Send + Sync + 'static,
+    {
+        Self {
+            signing_key: Arc::new(signing_key),
+            alg,
+        }
+    }
+
+    ///
+    /// Returns the authentication method implied by the signing algorithm: HMAC algorithms use
+    /// the client secret as the key (`client_secret_jwt`), while the others use a private key
+    /// registered with the provider (`private_key_jwt`).
+    ///
+    pub(crate) fn auth_method(&self) -> TokenEndpointAuthMethod {
+        if self.alg.uses_shared_secret() {
+            TokenEndpointAuthMethod::ClientSecretJwt
+        } else {
+            TokenEndpointAuthMethod::PrivateKeyJwt
+        }
+    }
+
+    ///
+    /// Signs a new client assertion for the specified token endpoint.
+    ///
+    pub(crate) fn sign_assertion<JE>(
+        &self,
+        client_id: &ClientId,
+        token_url: &TokenUrl,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        let issued_at = Utc::now();
+        let claims = ClientAssertionClaims {
+            iss: client_id.clone(),
+            sub: client_id.clone(),
+            aud: token_url.url().to_string(),
+            jti: CsrfToken::new_random().secret().clone(),
+            iat: issued_at,
+            exp: issued_at + Duration::seconds(CLIENT_ASSERTION_LIFETIME_SECS),
+        };
+        let assertion = JsonWebToken::<JE, JS, JT, _, JsonWebTokenJsonPayloadSerde>::new(
+            claims, self, &self.alg,
+        )?;
+        match serde_json::to_value(&assertion).map_err(JsonWebTokenError::SerializationError)? {
+            serde_json::Value::String(assertion) => Ok(assertion),
+            _ => unreachable!("JWTs serialize to strings"),
+        }
+    }
+}
+impl<JS, JT, JU, K> Clone for ClientAssertionKey<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            signing_key: self.signing_key.clone(),
+            alg: self.alg.clone(),
+        }
+    }
+}
+impl<JS, JT, JU, K> Debug for ClientAssertionKey<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("ClientAssertionKey")
+            .field("signing_key", &"[redacted]")
+            .field("alg", &self.alg)
+            .finish()
+    }
+}
+impl<JS, JT, JU, K> PrivateSigningKey<JS, JT, JU, K> for ClientAssertionKey<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn sign(&self, signature_alg: &JS, message: &[u8]) -> Result<Vec<u8>, SigningError> {
+        self.signing_key.sign(signature_alg, message)
+    }
+
+    fn as_verification_key(&self) -> K {
+        self.signing_key.as_verification_key()
+    }
+}
@@ -0,0 +1,439 @@
+use std::env;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::ParseBoolError;
+
+use failure::Fail;
+use oauth2::{ClientId, ClientSecret, RedirectUrl, Scope};
+
+use crate::{IssuerUrl, ScopeSet, TokenEndpointAuthMethod, TokenEndpointAuthMethodError};
+
+///
+/// Declarative client configuration, which may be loaded from a configuration file (e.g., YAML
+/// or TOML) using any `serde` format.
+///
+/// Clients are constructed from a configuration and the provider metadata of the configured
+/// issuer via [`Client::from_config`](crate::Client::from_config). For example, the following TOML
+/// configures a confidential client whose secret is read from an environment variable:
+///
+/// ```toml
+/// issuer = "https://accounts.example.com"
+/// client_id = "my-client"
+/// redirect_uris = ["https://app.example.com/callback"]
+/// token_endpoint_auth_method = "client_secret_post"
+/// scopes = ["email", "profile"]
+/// require_pkce = true
+///
+/// [client_secret]
+/// env = "OIDC_CLIENT_SECRET"
+/// ```
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ClientConfig {
+    ///
+    /// Issuer identifier of the OpenID Connect Provider.
+    ///
+    pub issuer: IssuerUrl,
+    ///
+    /// Client identifier issued by the OpenID Connect Provider.
+    ///
+    pub client_id: ClientId,
+    ///
+    /// Source of the client secret, or `None` for public clients.
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<ClientSecretRef>,
+    ///
+    /// Redirect URIs registered with the OpenID Connect Provider. The first redirect URI is used
+    /// by default, and others may be selected per request via
+    /// [`AuthorizationRequest::set_redirect_url`](crate::AuthorizationRequest::set_redirect_url).
+    ///
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirect_uris: Vec<RedirectUrl>,
+    ///
+    /// Method used to authenticate to the token endpoint, or `None` to negotiate it from the
+    /// provider metadata (see [`TokenEndpointAuthMethod::negotiate`]).
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint_auth_method: Option<TokenEndpointAuthMethod>,
+    ///
+    /// Scopes to request in addition to `openid` (see [`ClientConfig::scope_set`]).
+    ///
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<Scope>,
+    ///
+    /// Whether authorization requests must use PKCE (see
+    /// [`Client::set_require_pkce`](crate::Client::set_require_pkce)).
+    ///
+    #[serde(default)]
+    pub require_pkce: bool,
+}
+impl ClientConfig {
+    ///
+    /// Instantiates a configuration for a public client with no redirect URIs or scopes.
+    ///
+    pub fn new(issuer: IssuerUrl, client_id: ClientId) -> Self {
+        Self {
+            issuer,
+            client_id,
+            client_secret: None,
+            redirect_uris: Vec::new(),
+            token_endpoint_auth_method: None,
+            scopes: Vec::new(),
+            require_pkce: false,
+        }
+    }
+
+    ///
+    /// Loads a configuration from environment variables whose names begin with `prefix` followed
+    /// by an underscore, as is common for twelve-factor applications. For example,
+    /// `ClientConfig::from_env("OIDC")` reads the following variables:
+    ///
+    ///  * `OIDC_ISSUER` (required): issuer identifier of the OpenID Connect Provider.
+    ///  * `OIDC_CLIENT_ID` (required): client identifier.
+    ///  * `OIDC_CLIENT_SECRET`: client secret. The secret itself isn't stored in the
+    ///    configuration; it's referenced via [`ClientSecretRef::Env`].
+    ///  * `OIDC_CLIENT_SECRET_FILE`: path of a file containing the client secret, which is used
+    ///    if `OIDC_CLIENT_SECRET` is unset.
+    ///  * `OIDC_REDIRECT_URL`: whitespace-separated list of redirect URIs.
+    ///  * `OIDC_TOKEN_ENDPOINT_AUTH_METHOD`: token endpoint auth method (e.g.,
+    ///    `client_secret_basic`).
+    ///  * `OIDC_SCOPES`: whitespace-separated list of scopes.
+    ///  * `OIDC_REQUIRE_PKCE`: `true` or `false`.
+    ///
+    /// Empty variables are treated as unset.
+    ///
+    pub fn from_env(prefix: &str) -> Result<Self, ClientConfigError> {
+        let var = |name: &str| {
+            let key = format!("{}_{}", prefix, name);
+            match env::var(&key) {
+                Ok(ref value) if value.trim().is_empty() => Ok((key, None)),
+                Ok(value) => Ok((key, Some(value))),
+                Err(env::VarError::NotPresent) => Ok((key, None)),
+                Err(env::VarError::NotUnicode(_)) => Err(ClientConfigError::InvalidEnvVar(
+                    key,
+                    "value is not valid Unicode".to_string(),
+                )),
+            }
+        };
+        let required = |name: &str| -> Result<String, ClientConfigError> {
+            match var(name)? {
+                (_, Some(value)) => Ok(value),
+                (key, None) => Err(ClientConfigError::MissingEnvVar(key)),
+            }
+        };
+
+        let issuer_key = format!("{}_ISSUER", prefix);
+        let issuer = IssuerUrl::new(required("ISSUER")?)
+            .map_err(|err| ClientConfigError::InvalidEnvVar(issuer_key, err.to_string()))?;
+        let mut config = Self::new(issuer, ClientId::new(required("CLIENT_ID")?));
+
+        config.client_secret = match (var("CLIENT_SECRET")?, var("CLIENT_SECRET_FILE")?) {
+            ((key, Some(_)), _) => Some(ClientSecretRef::Env(key)),
+            (_, (_, Some(path))) => Some(ClientSecretRef::File(path.into())),
+            _ => None,
+        };
+
+        if let (key, Some(urls)) = var("REDIRECT_URL")? {
+            config.redirect_uris = urls
+                .split_whitespace()
+                .map(|url| RedirectUrl::new(url.to_string()))
+                .collect::<Result<_, _>>()
+                .map_err(|err| ClientConfigError::InvalidEnvVar(key, err.to_string()))?;
+        }
+
+        if let (key, Some(method)) = var("TOKEN_ENDPOINT_AUTH_METHOD")? {
+            config.token_endpoint_auth_method = Some(
+                serde_json::from_value(serde_json::Value::String(method.trim().to_string()))
+                    .map_err(|err| ClientConfigError::InvalidEnvVar(key, err.to_string()))?,
+            );
+        }
+
+        if let (_, Some(scopes)) = var("SCOPES")? {
+            config.scopes = scopes
+                .split_whitespace()
+                .map(|scope| Scope::new(scope.to_string()))
+                .collect();
+        }
+
+        if let (key, Some(require_pkce)) = var("REQUIRE_PKCE")? {
+            config.require_pkce = require_pkce.trim().parse().map_err(|err: ParseBoolError| {
+                ClientConfigError::InvalidEnvVar(key, err.to_string())
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    ///
+    /// Returns the configured scopes, which may be added to authorization requests via
+    /// [`AuthorizationRequest::add_scopes`](crate::AuthorizationRequest::add_scopes).
+    ///
+    pub fn scope_set(&self) -> ScopeSet {
+        self.scopes.iter().cloned().collect()
+    }
+}
+
+///
+/// Source of a client secret referenced by a [`ClientConfig`].
+///
+/// Referencing the secret by environment variable or file allows the rest of the configuration to
+/// be stored alongside the application without exposing the secret.
+///
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ClientSecretRef {
+    ///
+    /// Name of an environment variable containing the secret.
+    ///
+    Env(String),
+    ///
+    /// Path of a file containing the secret. Leading and trailing whitespace (e.g., a trailing
+    /// newline) is ignored.
+    ///
+    File(PathBuf),
+    ///
+    /// The secret itself.
+    ///
+    Value(String),
+}
+impl ClientSecretRef {
+    ///
+    /// Returns the referenced client secret.
+    ///
+    pub fn resolve(&self) -> Result<ClientSecret, ClientConfigError> {
+        match self {
+            ClientSecretRef::Env(name) => env::var(name)
+                .map(ClientSecret::new)
+                .map_err(|_| ClientConfigError::MissingEnvVar(name.clone())),
+            ClientSecretRef::File(path) => fs::read_to_string(path)
+                .map(|secret| ClientSecret::new(secret.trim().to_string()))
+                .map_err(|err| ClientConfigError::SecretFile(path.display().to_string(), err)),
+            ClientSecretRef::Value(secret) => Ok(ClientSecret::new(secret.clone())),
+        }
+    }
+}
+impl Debug for ClientSecretRef {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        match self {
+            ClientSecretRef::Env(name) => f.debug_tuple("Env").field(name).finish(),
+            ClientSecretRef::File(path) => f.debug_tuple("File").field(path).finish(),
+            ClientSecretRef::Value(_) => f.debug_tuple("Value").field(&"[redacted]").finish(),
+        }
+    }
+}
+
+///
+/// Error constructing a client from a [`ClientConfig`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum ClientConfigError {
+    ///
+    /// The provider metadata's issuer doesn't match the configured issuer. This variant holds the
+    /// issuer listed in the provider metadata.
+    ///
+    #[fail(display = "Provider metadata is for unexpected issuer `{}`", _0)]
+    IssuerMismatch(String),
+    ///
+    /// A required environment variable is unset. This variant is returned by
+    /// [`ClientConfig::from_env`] and by [`ClientSecretRef::resolve`] when the variable referenced
+    /// by [`ClientSecretRef::Env`] is unset or isn't valid Unicode.
+    ///
+    #[fail(display = "Environment variable `{}` is unset", _0)]
+    MissingEnvVar(String),
+    ///
+    /// An environment variable read by [`ClientConfig::from_env`] has an invalid value. This
+    /// variant holds the name of the variable and a description of the problem.
+    ///
+    #[fail(display = "Invalid value for environment variable `{}`: {}", _0, _1)]
+    InvalidEnvVar(String, String),
+    ///
+    /// The file referenced by [`ClientSecretRef::File`] couldn't be read.
+    ///
+    #[fail(display = "Failed to read client secret file `{}`", _0)]
+    SecretFile(String, #[cause] io::Error),
+    ///
+    /// No token endpoint authentication method is configured, and the provider doesn't support any
+    /// method for which the client has credentials.
+    ///
+    #[fail(display = "Failed to negotiate token endpoint authentication method")]
+    TokenEndpointAuthMethod(#[cause] TokenEndpointAuthMethodError),
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::{ClientId, RedirectUrl, Scope};
+
+    use crate::core::{
+        CoreClient, CoreJwsSigningAlgorithm, CoreProviderMetadata, CoreResponseType,
+        CoreSubjectIdentifierType,
+    };
+    use crate::{
+        AuthUrl, EmptyAdditionalProviderMetadata, IssuerUrl, JsonWebKeySetUrl, ResponseTypes,
+        ScopeSet, TokenEndpointAuthMethod,
+    };
+
+    use super::{ClientConfig, ClientConfigError, ClientSecretRef};
+
+    fn provider_metadata(issuer: &str) -> CoreProviderMetadata {
+        CoreProviderMetadata::new(
+            IssuerUrl::new(issuer.to_string()).unwrap(),
+            AuthUrl::new(format!("{}/authorize", issuer)).unwrap(),
+            JsonWebKeySetUrl::new(format!("{}/jwks", issuer)).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+    }
+
+    #[test]
+    fn test_client_config() {
+        let config: ClientConfig = serde_json::from_str(
+            "{
+                \"issuer\": \"https://example\",
+                \"client_id\": \"aaa\",
+                \"client_secret\": {\"value\": \"bbb\"},
+                \"redirect_uris\": [\"https://app/cb\", \"https://app/cb2\"],
+                \"token_endpoint_auth_method\": \"client_secret_post\",
+                \"scopes\": [\"email\", \"profile\"],
+                \"require_pkce\": true
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            Some(TokenEndpointAuthMethod::ClientSecretPost),
+            config.token_endpoint_auth_method
+        );
+        assert_eq!(
+            ScopeSet::from_space_delimited("email profile"),
+            config.scope_set()
+        );
+        assert!(!format!("{:?}", config).contains("bbb"));
+        assert_eq!(
+            config,
+            serde_json::from_value(serde_json::to_value(&config).unwrap()).unwrap()
+        );
+
+        let client =
+            CoreClient::from_config(&config, provider_metadata("https://example")).unwrap();
+        assert_eq!(
+            Some(&RedirectUrl::new("https://app/cb".to_string()).unwrap()),
+            client.redirect_url.as_ref()
+        );
+        assert_eq!(
+            TokenEndpointAuthMethod::ClientSecretPost,
+            client.token_endpoint_auth_method()
+        );
+        assert_eq!("bbb", client.client_secret.as_ref().unwrap().secret());
+        assert!(client.require_pkce);
+
+        match CoreClient::from_config(&config, provider_metadata("https://other")) {
+            Err(ClientConfigError::IssuerMismatch(issuer)) => assert_eq!("https://other", issuer),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_client_config_minimal() {
+        let config: ClientConfig =
+            serde_json::from_str("{\"issuer\": \"https://example\", \"client_id\": \"aaa\"}")
+                .unwrap();
+        assert_eq!(
+            ClientConfig::new(
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                ClientId::new("aaa".to_string()),
+            ),
+            config
+        );
+        assert_eq!(
+            "{\"issuer\":\"https://example\",\"client_id\":\"aaa\",\"require_pkce\":false}",
+            serde_json::to_string(&config).unwrap()
+        );
+
+        let client =
+            CoreClient::from_config(&config, provider_metadata("https://example")).unwrap();
+        assert!(client.client_secret.is_none());
+        assert_eq!(
+            TokenEndpointAuthMethod::None,
+            client.token_endpoint_auth_method()
+        );
+
+        let scopes = vec![Scope::new("email".to_string())];
+        assert_eq!(1, ClientConfig { scopes, ..config }.scope_set().len());
+    }
+
+    #[test]
+    fn test_client_config_from_env() {
+        let set = |name: &str, value: &str| {
+            std::env::set_var(format!("OPENIDCONNECT_TEST_FROM_ENV_{}", name), value)
+        };
+
+        match ClientConfig::from_env("OPENIDCONNECT_TEST_FROM_ENV") {
+            Err(ClientConfigError::MissingEnvVar(name)) => {
+                assert_eq!("OPENIDCONNECT_TEST_FROM_ENV_ISSUER", name)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        set("ISSUER", "https://example");
+        set("CLIENT_ID", "aaa");
+        set("CLIENT_SECRET", "bbb");
+        set("CLIENT_SECRET_FILE", "/unused");
+        set("REDIRECT_URL", "https://app/cb https://app/cb2");
+        set("TOKEN_ENDPOINT_AUTH_METHOD", "client_secret_post");
+        set("SCOPES", "email profile");
+        set("REQUIRE_PKCE", "true");
+        let config = ClientConfig::from_env("OPENIDCONNECT_TEST_FROM_ENV").unwrap();
+        assert_eq!(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            config.issuer
+        );
+        assert_eq!(
+            Some(ClientSecretRef::Env(
+                "OPENIDCONNECT_TEST_FROM_ENV_CLIENT_SECRET".to_string()
+            )),
+            config.client_secret
+        );
+        assert_eq!(
+            "bbb",
+            config.client_secret.unwrap().resolve().unwrap().secret()
+        );
+        assert_eq!(2, config.redirect_uris.len());
+        assert_eq!(
+            Some(TokenEndpointAuthMethod::ClientSecretPost),
+            config.token_endpoint_auth_method
+        );
+        assert_eq!(
+            ScopeSet::from_space_delimited("email profile"),
+            config.scope_set()
+        );
+        assert!(config.require_pkce);
+
+        set("REQUIRE_PKCE", "yes");
+        match ClientConfig::from_env("OPENIDCONNECT_TEST_FROM_ENV") {
+            Err(ClientConfigError::InvalidEnvVar(name, _)) => {
+                assert_eq!("OPENIDCONNECT_TEST_FROM_ENV_REQUIRE_PKCE", name)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_client_secret_ref() {
+        match ClientSecretRef::Env("OPENIDCONNECT_TEST_UNSET_SECRET".to_string()).resolve() {
+            Err(ClientConfigError::MissingEnvVar(name)) => {
+                assert_eq!("OPENIDCONNECT_TEST_UNSET_SECRET", name)
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        match ClientSecretRef::File("/nonexistent/openidconnect-secret".into()).resolve() {
+            Err(ClientConfigError::SecretFile(..)) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use url::{Origin, Url};
+
+#[cfg(feature = "net")]
+use crate::http_client::RequestUrl;
+#[cfg(feature = "net")]
+use crate::http_types::Response;
+use crate::IssuerUrl;
+
+const DEFAULT_MAX_DRIFT_MINUTES: i64 = 5;
+
+///
+/// Source of the current time.
+///
+/// Verifiers and other time-dependent types (e.g., [`IdTokenVerifier`](crate::IdTokenVerifier) and
+/// [`TokenManager`](crate::TokenManager)) obtain the current time from a `Clock` rather than from
+/// the system clock directly, which allows expiration-related behavior to be tested
+/// deterministically using a [`FixedClock`].
+///
+/// This trait is implemented for any `Fn() -> DateTime<Utc>`.
+///
+pub trait Clock {
+    ///
+    /// Returns the current time.
+    ///
+    fn now(&self) -> DateTime<Utc>;
+}
+impl<F> Clock for F
+where
+    F: Fn() -> DateTime<Utc>,
+{
+    fn now(&self) -> DateTime<Utc> {
+        (self)()
+    }
+}
+
+///
+/// [`Clock`] that returns the current system time. This is the default clock.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+///
+/// [`Clock`] that returns a fixed time, which may be changed explicitly. Intended for tests.
+///
+/// Clones share the same time, so a clone passed to a verifier observes subsequent calls to
+/// [`FixedClock::set`] and [`FixedClock::advance`].
+///
+#[derive(Clone, Debug)]
+pub struct FixedClock(Arc<Mutex<DateTime<Utc>>>);
+impl FixedClock {
+    ///
+    /// Instantiates a clock fixed at the specified time.
+    ///
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FixedClock(Arc::new(Mutex::new(now)))
+    }
+
+    ///
+    /// Sets the current time.
+    ///
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.lock() = now;
+    }
+
+    ///
+    /// Advances the current time by the specified duration.
+    ///
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.lock();
+        *now = *now + duration;
+    }
+
+    // The guarded value is a plain timestamp, so a poisoned lock is safe to reuse.
+    fn lock(&self) -> std::sync::MutexGuard<DateTime<Utc>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+}
+
+///
+/// [`Clock`] that compensates for drift between the local clock and the OpenID Connect Provider's
+/// clock.
+///
+/// Devices with inaccurate clocks (e.g., embedded devices and kiosks) may otherwise reject freshly
+/// issued ID tokens as not yet valid or already expired. This clock estimates the provider's
+/// current time from the `Date` header of the most recent HTTP response observed (see
+/// [`ServerClock::response_interceptor`]) and applies the resulting offset to the local clock.
+/// The estimate is only accurate to within about a second plus the network latency, which is
+/// typically well within the verifiers' allowed clock skew.
+///
+/// Since the offset shifts the time against which token expiration is checked, only responses
+/// from the provider's own endpoints are observed: those sharing an origin with the issuer or
+/// with an endpoint added via [`ServerClock::add_trusted_endpoint`]. The offset is also capped at
+/// a maximum drift (five minutes by default; see [`ServerClock::set_max_drift`]).
+///
+/// Clones share the same offset, so a clone passed to a verifier (e.g., using
+/// [`IdTokenVerifier::set_clock`](crate::IdTokenVerifier::set_clock)) observes subsequent
+/// responses.
+///
+#[derive(Clone)]
+pub struct ServerClock {
+    local: Arc<dyn Clock + Send + Sync>,
+    max_drift: Duration,
+    offset: Arc<Mutex<Duration>>,
+    trusted_origins: Vec<Origin>,
+}
+impl ServerClock {
+    ///
+    /// Instantiates a clock that applies the offset observed from the specified issuer's
+    /// responses to the system clock.
+    ///
+    pub fn new(issuer: &IssuerUrl) -> Self {
+        Self::from_clock(issuer, SystemClock)
+    }
+
+    ///
+    /// Instantiates a clock that applies the offset observed from the specified issuer's
+    /// responses to the specified local clock.
+    ///
+    pub fn from_clock<C>(issuer: &IssuerUrl, local: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        ServerClock {
+            local: Arc::new(local),
+            max_drift: Duration::minutes(DEFAULT_MAX_DRIFT_MINUTES),
+            offset: Arc::new(Mutex::new(Duration::zero())),
+            trusted_origins: vec![issuer.url().origin()],
+        }
+    }
+
+    ///
+    /// Observes responses from the origin of the specified provider endpoint (e.g., a token
+    /// endpoint hosted separately from the issuer).
+    ///
+    pub fn add_trusted_endpoint(mut self, endpoint: &Url) -> Self {
+        self.trusted_origins.push(endpoint.origin());
+        self
+    }
+
+    ///
+    /// Sets the maximum magnitude of the offset. Larger observed offsets are capped at this value.
+    ///
+    pub fn set_max_drift(mut self, max_drift: Duration) -> Self {
+        self.max_drift = max_drift;
+        self
+    }
+
+    ///
+    /// Returns the offset of the provider's clock relative to the local clock, which is zero until
+    /// a response has been observed.
+    ///
+    pub fn offset(&self) -> Duration {
+        *self.lock()
+    }
+
+    ///
+    /// Records the provider's current time, updating the offset (subject to the maximum drift).
+    ///
+    pub fn observe(&self, server_time: DateTime<Utc>) {
+        let offset = server_time.signed_duration_since(self.local.now());
+        *self.lock() = if offset > self.max_drift {
+            self.max_drift
+        } else if offset < -self.max_drift {
+            -self.max_drift
+        } else {
+            offset
+        };
+    }
+
+    ///
+    /// Updates the offset from the `Date` header of the specified HTTP response, if present and
+    /// valid.
+    ///
+    /// The response is ignored unless its [`RequestUrl`] is attached (as for responses passed to
+    /// [`ServerClock::response_interceptor`]) and shares an origin with the issuer or a trusted
+    /// endpoint. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn observe_response(&self, response: &Response) {
+        let trusted = RequestUrl::from_response(response)
+            .map_or(false, |url| self.trusted_origins.contains(&url.origin()));
+        if !trusted {
+            return;
+        }
+
+        // HTTP dates use the IMF-fixdate format, which is a subset of the RFC 2822 format (see
+        // https://tools.ietf.org/html/rfc7231#section-7.1.1.1).
+        if let Some(server_time) = response
+            .header("Date")
+            .and_then(|values| DateTime::parse_from_rfc2822(values.last().as_str().trim()).ok())
+        {
+            self.observe(server_time.with_timezone(&Utc));
+        }
+    }
+
+    ///
+    /// Returns a response interceptor that updates the offset from each response's `Date` header.
+    ///
+    /// The interceptor may be added to a [`Client`](crate::Client) using
+    /// [`Client::add_response_interceptor`](crate::Client::add_response_interceptor) or to
+    /// an [`Interceptors`](crate::Interceptors) chain wrapping the HTTP client used for discovery
+    /// and token requests. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn response_interceptor(&self) -> impl Fn(&mut Response) + Send + Sync + 'static {
+        let clock = self.clone();
+        move |response: &mut Response| clock.observe_response(response)
+    }
+
+    // The guarded value is a plain duration, so a poisoned lock is safe to reuse.
+    fn lock(&self) -> std::sync::MutexGuard<Duration> {
+        self.offset.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+impl Clock for ServerClock {
+    fn now(&self) -> DateTime<Utc> {
+        let now = self.local.now();
+        now.checked_add_signed(self.offset()).unwrap_or(now)
+    }
+}
+impl Debug for ServerClock {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("ServerClock")
+            .field("max_drift", &self.max_drift)
+            .field("offset", &self.offset())
+            .field("trusted_origins", &self.trusted_origins)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    #[cfg(feature = "net")]
+    use futures::executor::block_on;
+    #[cfg(feature = "net")]
+    use futures::future::ready;
+
+    #[cfg(feature = "net")]
+    use crate::http_types::{Method, Request, Response, StatusCode, Url};
+    use crate::IssuerUrl;
+    #[cfg(feature = "net")]
+    use crate::{AsyncHttpClient, Interceptors};
+
+    use super::{Clock, FixedClock, ServerClock, SystemClock};
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let shared = clock.clone();
+        assert_eq!(Utc.timestamp(1311280970, 0), shared.now());
+
+        clock.advance(Duration::seconds(60));
+        assert_eq!(Utc.timestamp(1311281030, 0), shared.now());
+
+        clock.set(Utc.timestamp(0, 0));
+        assert_eq!(Utc.timestamp(0, 0), shared.now());
+    }
+
+    #[test]
+    fn test_fn_clock() {
+        let clock = || Utc.timestamp(1311280970, 0);
+        assert_eq!(Utc.timestamp(1311280970, 0), Clock::now(&clock));
+        assert!(SystemClock.now() > Utc.timestamp(1311280970, 0));
+    }
+
+    #[cfg(feature = "net")]
+    fn send(clock: &ServerClock, url: &str, date: Option<&str>) {
+        let date = date.map(str::to_string);
+        let http_client = move |_: Request| {
+            let mut response = Response::new(StatusCode::Ok);
+            if let Some(ref date) = date {
+                response.insert_header("Date", date.as_str());
+            }
+            ready(Ok::<_, std::io::Error>(response))
+        };
+        let interceptors =
+            Interceptors::new().add_response_interceptor(clock.response_interceptor());
+        let request = Request::new(Method::Get, Url::parse(url).unwrap());
+        block_on(interceptors.wrap(&http_client).call(request)).unwrap();
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_server_clock() {
+        let local = FixedClock::new(Utc.timestamp(1445412180, 0));
+        let clock = ServerClock::from_clock(
+            &IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            local.clone(),
+        )
+        .add_trusted_endpoint(&Url::parse("https://token.example.com/token").unwrap());
+        assert_eq!(Utc.timestamp(1445412180, 0), clock.now());
+
+        // The provider's clock is five minutes ahead of the local clock.
+        send(
+            &clock,
+            "https://example.com/.well-known/openid-configuration",
+            Some("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(Duration::minutes(5), clock.offset());
+        assert_eq!(Utc.timestamp(1445412480, 0), clock.now());
+
+        local.advance(Duration::seconds(60));
+        assert_eq!(Utc.timestamp(1445412540, 0), clock.now());
+
+        // Responses without a valid `Date` header leave the offset unchanged.
+        send(&clock, "https://example.com/jwks", None);
+        send(&clock, "https://example.com/jwks", Some("invalid"));
+        assert_eq!(Duration::minutes(5), clock.offset());
+
+        // Responses from other servers, or that weren't passed through an interceptor chain, are
+        // ignored.
+        send(
+            &clock,
+            "https://attacker.example.com/",
+            Some("Wed, 21 Oct 2015 07:20:00 GMT"),
+        );
+        let mut response = Response::new(StatusCode::Ok);
+        response.insert_header("Date", "Wed, 21 Oct 2015 07:20:00 GMT");
+        clock.observe_response(&response);
+        assert_eq!(Duration::minutes(5), clock.offset());
+
+        // Trusted endpoints are observed.
+        send(
+            &clock,
+            "https://token.example.com/token",
+            Some("Wed, 21 Oct 2015 07:27:00 GMT"),
+        );
+        assert_eq!(Duration::minutes(3), clock.offset());
+    }
+
+    #[test]
+    fn test_server_clock_max_drift() {
+        let local = FixedClock::new(Utc.timestamp(1445412180, 0));
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let clock = ServerClock::from_clock(&issuer, local.clone());
+        clock.observe(Utc.timestamp(1445412180 + 3600, 0));
+        assert_eq!(Duration::minutes(5), clock.offset());
+        clock.observe(Utc.timestamp(1445412180 - 3600, 0));
+        assert_eq!(Duration::minutes(-5), clock.offset());
+
+        let clock = ServerClock::from_clock(&issuer, local).set_max_drift(Duration::hours(2));
+        clock.observe(Utc.timestamp(1445412180 + 3600, 0));
+        assert_eq!(Duration::hours(1), clock.offset());
+    }
+}
@@ -1,48 +1,43 @@
-use ring::hmac;
-use ring::rand::SecureRandom;
-use ring::signature as ring_signature;
-
 use crate::types::Base64UrlEncodedBytes;
-use crate::{JsonWebKey, SignatureVerificationError, SigningError};
+use crate::{
+    CryptoBackend, DefaultCryptoBackend, HashAlgorithm, JsonWebKey, RsaPadding,
+    SignatureVerificationError, SigningError,
+};
 
 use super::{CoreJsonWebKey, CoreJsonWebKeyType};
 
 use std::ops::Deref;
 
-pub fn sign_hmac(
-    key: &[u8],
-    hmac_alg: hmac::Algorithm,
-    msg: &[u8],
-) -> hmac::Tag {
-    let signing_key = hmac::Key::new(hmac_alg, key);
-    hmac::sign(&signing_key, msg)
+pub fn sign_hmac(key: &[u8], hash_alg: HashAlgorithm, msg: &[u8]) -> Vec<u8> {
+    DefaultCryptoBackend::hmac_sign(hash_alg, key, msg)
 }
 
 pub fn verify_hmac(
     key: &CoreJsonWebKey,
-    hmac_alg: hmac::Algorithm,
+    hash_alg: HashAlgorithm,
     msg: &[u8],
     signature: &[u8],
 ) -> Result<(), SignatureVerificationError> {
     let k = key.k.as_ref().ok_or_else(|| {
         SignatureVerificationError::InvalidKey("Symmetric key `k` is missing".to_string())
     })?;
-    let verification_key = hmac::Key::new(hmac_alg, k);
-    hmac::verify(&verification_key, msg, signature)
-        .map_err(|_| SignatureVerificationError::CryptoError("bad HMAC".to_string()))
+    if DefaultCryptoBackend::hmac_verify(hash_alg, k, msg, signature) {
+        Ok(())
+    } else {
+        Err(SignatureVerificationError::CryptoError(
+            "bad HMAC".to_string(),
+        ))
+    }
 }
 
 pub fn sign_rsa(
-    key: &ring_signature::RsaKeyPair,
-    padding_alg: &'static dyn ring_signature::RsaEncoding,
-    rng: &dyn SecureRandom,
+    key: &<DefaultCryptoBackend as CryptoBackend>::RsaPrivateKey,
+    padding: RsaPadding,
+    hash_alg: HashAlgorithm,
+    rng: &<DefaultCryptoBackend as CryptoBackend>::SecureRandom,
     msg: &[u8],
 ) -> Result<Vec<u8>, SigningError> {
-    let sig_len = key.public_modulus_len();
-    let mut sig = vec![0; sig_len];
-    key.sign(padding_alg, rng, msg, &mut sig)
-        .map_err(|_| SigningError::CryptoError)?;
-    Ok(sig)
+    DefaultCryptoBackend::rsa_sign(key, padding, hash_alg, rng, msg)
 }
 
 fn rsa_public_key(
@@ -63,14 +58,11 @@ fn rsa_public_key(
 
 pub fn verify_rsa_signature(
     key: &CoreJsonWebKey,
-    params: &ring_signature::RsaParameters,
+    padding: RsaPadding,
+    hash_alg: HashAlgorithm,
     msg: &[u8],
     signature: &[u8],
 ) -> Result<(), SignatureVerificationError> {
     let (n, e) = rsa_public_key(&key).map_err(SignatureVerificationError::InvalidKey)?;
-    let public_key = ring_signature::RsaPublicKeyComponents { n: n.deref(), e: e.deref() };
-
-    public_key
-        .verify(params, msg, signature)
-        .map_err(|_| SignatureVerificationError::CryptoError("bad signature".to_string()))
+    DefaultCryptoBackend::rsa_verify(padding, hash_alg, n.deref(), e.deref(), msg, signature)
 }
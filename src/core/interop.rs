@@ -0,0 +1,160 @@
+use std::convert::TryFrom;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{CoreJsonWebKey, CoreJsonWebKeySet};
+
+// Each of these crates represents keys using the JSON Web Key format defined in RFC 7517, so keys
+// are converted via their JSON representation rather than by mapping each parameter by hand. Key
+// parameters that aren't supported by `CoreJsonWebKey` (e.g., RSA private key parameters) are
+// discarded.
+fn convert<T, U>(value: &T) -> Result<U, serde_json::Error>
+where
+    T: Serialize,
+    U: DeserializeOwned,
+{
+    serde_json::from_value(serde_json::to_value(value)?)
+}
+
+///
+/// Converts a key loaded using the `jsonwebtoken` crate. Requires "jsonwebtoken" feature.
+///
+#[cfg(feature = "jsonwebtoken")]
+impl TryFrom<&jsonwebtoken::jwk::Jwk> for CoreJsonWebKey {
+    type Error = serde_json::Error;
+
+    fn try_from(jwk: &jsonwebtoken::jwk::Jwk) -> Result<Self, Self::Error> {
+        convert(jwk)
+    }
+}
+
+///
+/// Converts a key for use with the `jsonwebtoken` crate. Requires "jsonwebtoken" feature.
+///
+#[cfg(feature = "jsonwebtoken")]
+impl TryFrom<&CoreJsonWebKey> for jsonwebtoken::jwk::Jwk {
+    type Error = serde_json::Error;
+
+    fn try_from(key: &CoreJsonWebKey) -> Result<Self, Self::Error> {
+        convert(key)
+    }
+}
+
+///
+/// Converts a key for verifying signatures using the `jsonwebtoken` crate. Requires "jsonwebtoken"
+/// feature.
+///
+#[cfg(feature = "jsonwebtoken")]
+impl TryFrom<&CoreJsonWebKey> for jsonwebtoken::DecodingKey {
+    type Error = jsonwebtoken::errors::Error;
+
+    fn try_from(key: &CoreJsonWebKey) -> Result<Self, Self::Error> {
+        jsonwebtoken::DecodingKey::from_jwk(&jsonwebtoken::jwk::Jwk::try_from(key)?)
+    }
+}
+
+///
+/// Converts a key set loaded using the `jsonwebtoken` crate. Requires "jsonwebtoken" feature.
+///
+#[cfg(feature = "jsonwebtoken")]
+impl TryFrom<&jsonwebtoken::jwk::JwkSet> for CoreJsonWebKeySet {
+    type Error = serde_json::Error;
+
+    fn try_from(jwk_set: &jsonwebtoken::jwk::JwkSet) -> Result<Self, Self::Error> {
+        convert(jwk_set)
+    }
+}
+
+///
+/// Converts a key set for use with the `jsonwebtoken` crate. Requires "jsonwebtoken" feature.
+///
+#[cfg(feature = "jsonwebtoken")]
+impl TryFrom<&CoreJsonWebKeySet> for jsonwebtoken::jwk::JwkSet {
+    type Error = serde_json::Error;
+
+    fn try_from(key_set: &CoreJsonWebKeySet) -> Result<Self, Self::Error> {
+        convert(key_set)
+    }
+}
+
+///
+/// Converts a key loaded using the `jsonwebkey` crate. Requires "jsonwebkey" feature.
+///
+#[cfg(feature = "jsonwebkey")]
+impl TryFrom<&jsonwebkey::JsonWebKey> for CoreJsonWebKey {
+    type Error = serde_json::Error;
+
+    fn try_from(jwk: &jsonwebkey::JsonWebKey) -> Result<Self, Self::Error> {
+        convert(jwk)
+    }
+}
+
+///
+/// Converts a key for use with the `jsonwebkey` crate. Requires "jsonwebkey" feature.
+///
+#[cfg(feature = "jsonwebkey")]
+impl TryFrom<&CoreJsonWebKey> for jsonwebkey::JsonWebKey {
+    type Error = serde_json::Error;
+
+    fn try_from(key: &CoreJsonWebKey) -> Result<Self, Self::Error> {
+        convert(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::core::CoreJsonWebKey;
+    #[cfg(feature = "jsonwebtoken")]
+    use crate::core::{CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey};
+    #[cfg(feature = "jsonwebtoken")]
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::jwt::tests::TEST_RSA_PUB_KEY;
+    #[cfg(feature = "jsonwebtoken")]
+    use crate::PrivateSigningKey;
+
+    #[cfg(feature = "jsonwebtoken")]
+    #[test]
+    fn test_jsonwebtoken_conversions() {
+        let key: CoreJsonWebKey = serde_json::from_str(TEST_RSA_PUB_KEY).unwrap();
+        let jwk = jsonwebtoken::jwk::Jwk::try_from(&key).unwrap();
+        assert_eq!(
+            key.kid.as_ref().map(|kid| kid.as_str()),
+            jwk.common.key_id.as_deref()
+        );
+        assert_eq!(CoreJsonWebKey::try_from(&jwk).unwrap(), key);
+
+        // Signatures produced by this crate verify using the converted key.
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let message = b"hello jsonwebtoken";
+        let signature = base64::encode_config(
+            signing_key
+                .sign(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, message)
+                .unwrap(),
+            base64::URL_SAFE_NO_PAD,
+        );
+        let decoding_key =
+            jsonwebtoken::DecodingKey::try_from(&signing_key.as_verification_key()).unwrap();
+        assert!(jsonwebtoken::crypto::verify(
+            &signature,
+            message,
+            &decoding_key,
+            jsonwebtoken::Algorithm::RS256,
+        )
+        .unwrap());
+    }
+
+    #[cfg(feature = "jsonwebkey")]
+    #[test]
+    fn test_jsonwebkey_conversions() {
+        let key: CoreJsonWebKey = serde_json::from_str(TEST_RSA_PUB_KEY).unwrap();
+        let jwk = jsonwebkey::JsonWebKey::try_from(&key).unwrap();
+        assert_eq!(
+            key.kid.as_ref().map(|kid| kid.as_str()),
+            jwk.key_id.as_deref()
+        );
+        assert_eq!(CoreJsonWebKey::try_from(&jwk).unwrap(), key);
+    }
+}
@@ -1,15 +1,12 @@
 use base64;
 use oauth2::helpers::variant_name;
-use ring::rand;
-use ring::signature as ring_signature;
-use ring::signature::KeyPair;
-use ring::hmac;
 
 use crate::types::helpers::deserialize_option_or_none;
 use crate::types::Base64UrlEncodedBytes;
 use crate::{
-    JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JwsSigningAlgorithm,
-    PrivateSigningKey, SignatureVerificationError, SigningError,
+    CryptoBackend, DefaultCryptoBackend, HashAlgorithm, JsonWebKey, JsonWebKeyId, JsonWebKeyType,
+    JsonWebKeyUse, JwsSigningAlgorithm, PrivateSigningKey, RsaPadding, SignatureVerificationError,
+    SigningError,
 };
 
 use super::{crypto, CoreJwsSigningAlgorithm};
@@ -24,6 +21,11 @@ use super::{crypto, CoreJwsSigningAlgorithm};
 ///
 /// Public or symmetric key expressed as a JSON Web Key.
 ///
+/// Keys already loaded using the `jsonwebtoken` or `jsonwebkey` crates may be converted to and
+/// from this type via `TryFrom` when the corresponding feature is enabled. Since this type only
+/// represents RSA public keys and symmetric keys, any other key parameters (e.g., elliptic curve
+/// coordinates or RSA private key components) are discarded by these conversions.
+///
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CoreJsonWebKey {
     pub(crate) kty: CoreJsonWebKeyType,
@@ -31,6 +33,14 @@ pub struct CoreJsonWebKey {
     pub(crate) use_: Option<CoreJsonWebKeyUse>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) kid: Option<JsonWebKeyId>,
+    // Algorithms not supported by this crate (e.g., key management algorithms such as A128KW) are
+    // treated as though the parameter were omitted.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) alg: Option<CoreJwsSigningAlgorithm>,
 
     // From RFC 7517, Section 4: "Additional members can be present in the JWK; if not understood
     // by implementations encountering them, they MUST be ignored.  Member names used for
@@ -69,6 +79,7 @@ impl CoreJsonWebKey {
             kty: CoreJsonWebKeyType::RSA,
             use_: Some(CoreJsonWebKeyUse::Signature),
             kid,
+            alg: None,
             n: Some(Base64UrlEncodedBytes::new(n)),
             e: Some(Base64UrlEncodedBytes::new(e)),
             k: None,
@@ -85,12 +96,16 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
     fn key_use(&self) -> Option<&CoreJsonWebKeyUse> {
         self.use_.as_ref()
     }
+    fn signing_alg(&self) -> Option<&CoreJwsSigningAlgorithm> {
+        self.alg.as_ref()
+    }
 
     fn new_symmetric(key: Vec<u8>) -> Self {
         Self {
             kty: CoreJsonWebKeyType::Symmetric,
             use_: None,
             kid: None,
+            alg: None,
             n: None,
             e: None,
             k: Some(Base64UrlEncodedBytes::new(key)),
@@ -120,48 +135,54 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
         match *signature_alg {
             CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256 => crypto::verify_rsa_signature(
                 self,
-                &ring_signature::RSA_PKCS1_2048_8192_SHA256,
+                RsaPadding::Pkcs1V15,
+                HashAlgorithm::Sha256,
                 message,
                 signature,
             ),
             CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384 => crypto::verify_rsa_signature(
                 self,
-                &ring_signature::RSA_PKCS1_2048_8192_SHA384,
+                RsaPadding::Pkcs1V15,
+                HashAlgorithm::Sha384,
                 message,
                 signature,
             ),
             CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512 => crypto::verify_rsa_signature(
                 self,
-                &ring_signature::RSA_PKCS1_2048_8192_SHA512,
+                RsaPadding::Pkcs1V15,
+                HashAlgorithm::Sha512,
                 message,
                 signature,
             ),
             CoreJwsSigningAlgorithm::RsaSsaPssSha256 => crypto::verify_rsa_signature(
                 self,
-                &ring_signature::RSA_PSS_2048_8192_SHA256,
+                RsaPadding::Pss,
+                HashAlgorithm::Sha256,
                 message,
                 signature,
             ),
             CoreJwsSigningAlgorithm::RsaSsaPssSha384 => crypto::verify_rsa_signature(
                 self,
-                &ring_signature::RSA_PSS_2048_8192_SHA384,
+                RsaPadding::Pss,
+                HashAlgorithm::Sha384,
                 message,
                 signature,
             ),
             CoreJwsSigningAlgorithm::RsaSsaPssSha512 => crypto::verify_rsa_signature(
                 self,
-                &ring_signature::RSA_PSS_2048_8192_SHA512,
+                RsaPadding::Pss,
+                HashAlgorithm::Sha512,
                 message,
                 signature,
             ),
             CoreJwsSigningAlgorithm::HmacSha256 => {
-                crypto::verify_hmac(self, hmac::HMAC_SHA256, message, signature)
+                crypto::verify_hmac(self, HashAlgorithm::Sha256, message, signature)
             }
             CoreJwsSigningAlgorithm::HmacSha384 => {
-                crypto::verify_hmac(self, hmac::HMAC_SHA384, message, signature)
+                crypto::verify_hmac(self, HashAlgorithm::Sha384, message, signature)
             }
             CoreJwsSigningAlgorithm::HmacSha512 => {
-                crypto::verify_hmac(self, hmac::HMAC_SHA512, message, signature)
+                crypto::verify_hmac(self, HashAlgorithm::Sha512, message, signature)
             }
             ref other => Err(SignatureVerificationError::UnsupportedAlg(
                 variant_name(other).to_string(),
@@ -206,21 +227,17 @@ impl
         signature_alg: &CoreJwsSigningAlgorithm,
         message: &[u8],
     ) -> Result<Vec<u8>, SigningError> {
-        let hmac_alg = match *signature_alg {
-            CoreJwsSigningAlgorithm::HmacSha256 => hmac::HMAC_SHA256,
-            CoreJwsSigningAlgorithm::HmacSha384 => hmac::HMAC_SHA384,
-            CoreJwsSigningAlgorithm::HmacSha512 => hmac::HMAC_SHA512,
+        let hash_alg = match *signature_alg {
+            CoreJwsSigningAlgorithm::HmacSha256 => HashAlgorithm::Sha256,
+            CoreJwsSigningAlgorithm::HmacSha384 => HashAlgorithm::Sha384,
+            CoreJwsSigningAlgorithm::HmacSha512 => HashAlgorithm::Sha512,
             ref other => {
                 return Err(SigningError::UnsupportedAlg(
                     variant_name(other).to_string(),
                 ))
             }
         };
-        Ok(
-            crypto::sign_hmac(self.secret.as_ref(), hmac_alg, message)
-                .as_ref()
-                .into(),
-        )
+        Ok(crypto::sign_hmac(self.secret.as_ref(), hash_alg, message))
     }
 
     fn as_verification_key(&self) -> CoreJsonWebKey {
@@ -238,8 +255,8 @@ const RSA_FOOTER: &str = "-----END RSA PRIVATE KEY-----";
 /// them.
 ///
 pub struct CoreRsaPrivateSigningKey {
-    key_pair: ring_signature::RsaKeyPair,
-    rng: Box<dyn rand::SecureRandom>,
+    key_pair: <DefaultCryptoBackend as CryptoBackend>::RsaPrivateKey,
+    rng: Box<<DefaultCryptoBackend as CryptoBackend>::SecureRandom>,
     kid: Option<JsonWebKeyId>,
 }
 impl CoreRsaPrivateSigningKey {
@@ -247,12 +264,12 @@ impl CoreRsaPrivateSigningKey {
     /// Converts an RSA private key (in PEM format) to a JWK representing its public key.
     ///
     pub fn from_pem(pem: &str, kid: Option<JsonWebKeyId>) -> Result<Self, String> {
-        Self::from_pem_internal(pem, Box::new(rand::SystemRandom::new()), kid)
+        Self::from_pem_internal(pem, DefaultCryptoBackend::system_random(), kid)
     }
 
     pub(crate) fn from_pem_internal(
         pem: &str,
-        rng: Box<dyn rand::SecureRandom>,
+        rng: Box<<DefaultCryptoBackend as CryptoBackend>::SecureRandom>,
         kid: Option<JsonWebKeyId>,
     ) -> Result<Self, String> {
         let trimmed_pem = pem.trim();
@@ -267,8 +284,7 @@ impl CoreRsaPrivateSigningKey {
         let der = base64::decode_config(base64_pem, config)
             .map_err(|_| "Failed to decode RSA private key body as base64".to_string())?;
 
-        let key_pair = ring_signature::RsaKeyPair::from_der(&der)
-            .map_err(|err| err.description_().to_string())?;
+        let key_pair = DefaultCryptoBackend::rsa_private_key_from_der(&der)?;
         Ok(Self { key_pair, rng, kid })
     }
 
@@ -307,13 +323,19 @@ impl
         signature_alg: &CoreJwsSigningAlgorithm,
         msg: &[u8],
     ) -> Result<Vec<u8>, SigningError> {
-        let padding_alg: &dyn ring_signature::RsaEncoding = match *signature_alg {
-            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256 => &ring_signature::RSA_PKCS1_SHA256,
-            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384 => &ring_signature::RSA_PKCS1_SHA384,
-            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512 => &ring_signature::RSA_PKCS1_SHA512,
-            CoreJwsSigningAlgorithm::RsaSsaPssSha256 => &ring_signature::RSA_PSS_SHA256,
-            CoreJwsSigningAlgorithm::RsaSsaPssSha384 => &ring_signature::RSA_PSS_SHA384,
-            CoreJwsSigningAlgorithm::RsaSsaPssSha512 => &ring_signature::RSA_PSS_SHA512,
+        let (padding, hash_alg) = match *signature_alg {
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256 => {
+                (RsaPadding::Pkcs1V15, HashAlgorithm::Sha256)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384 => {
+                (RsaPadding::Pkcs1V15, HashAlgorithm::Sha384)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512 => {
+                (RsaPadding::Pkcs1V15, HashAlgorithm::Sha512)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPssSha256 => (RsaPadding::Pss, HashAlgorithm::Sha256),
+            CoreJwsSigningAlgorithm::RsaSsaPssSha384 => (RsaPadding::Pss, HashAlgorithm::Sha384),
+            CoreJwsSigningAlgorithm::RsaSsaPssSha512 => (RsaPadding::Pss, HashAlgorithm::Sha512),
             ref other => {
                 return Err(SigningError::UnsupportedAlg(
                     variant_name(other).to_string(),
@@ -321,27 +343,18 @@ impl
             }
         };
 
-        crypto::sign_rsa(&self.key_pair, padding_alg, self.rng.as_ref(), msg)
+        crypto::sign_rsa(&self.key_pair, padding, hash_alg, self.rng.as_ref(), msg)
     }
 
     fn as_verification_key(&self) -> CoreJsonWebKey {
-        let public_key = self.key_pair.public_key();
+        let (n, e) = DefaultCryptoBackend::rsa_public_key(&self.key_pair);
         CoreJsonWebKey {
             kty: CoreJsonWebKeyType::RSA,
             use_: Some(CoreJsonWebKeyUse::Signature),
             kid: self.kid.clone(),
-            n: Some(Base64UrlEncodedBytes::new(
-                public_key
-                    .modulus()
-                    .big_endian_without_leading_zero()
-                    .into(),
-            )),
-            e: Some(Base64UrlEncodedBytes::new(
-                public_key
-                    .exponent()
-                    .big_endian_without_leading_zero()
-                    .into(),
-            )),
+            alg: None,
+            n: Some(Base64UrlEncodedBytes::new(n)),
+            e: Some(Base64UrlEncodedBytes::new(e)),
             k: None,
         }
     }
@@ -409,6 +422,7 @@ impl JsonWebKeyUse for CoreJsonWebKeyUse {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "ring")]
     use ring::test::rand::FixedByteRandom;
     use serde_json;
 
@@ -429,6 +443,7 @@ mod tests {
             \"kty\": \"RSA\",
             \"use\": \"sig\",
             \"kid\": \"2011-04-29\",
+            \"alg\": \"RS256\",
             \"n\": \"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhD\
                      R1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6C\
                      f0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1\
@@ -441,6 +456,7 @@ mod tests {
         assert_eq!(key.kty, CoreJsonWebKeyType::RSA);
         assert_eq!(key.use_, Some(CoreJsonWebKeyUse::Signature));
         assert_eq!(key.kid, Some(JsonWebKeyId::new("2011-04-29".to_string())));
+        assert_eq!(key.alg, Some(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256));
         assert_eq!(
             key.n,
             Some(Base64UrlEncodedBytes::new(vec![
@@ -477,6 +493,7 @@ mod tests {
         assert_eq!(key.kty, CoreJsonWebKeyType::Symmetric);
         assert_eq!(key.use_, None);
         assert_eq!(key.kid, None);
+        assert_eq!(key.alg, None);
         assert_eq!(key.n, None);
         assert_eq!(key.e, None);
         assert_eq!(
@@ -887,6 +904,8 @@ mod tests {
         public_key.verify_signature(alg, message, &sig).unwrap();
     }
 
+    // The expected RSA-PSS signatures depend on `ring`'s handling of the fixed salt bytes.
+    #[cfg(feature = "ring")]
     #[test]
     fn test_rsa_signing() {
         let private_key = CoreRsaPrivateSigningKey::from_pem_internal(
@@ -16,13 +16,21 @@ use crate::registration::{
     EmptyAdditionalClientMetadata, EmptyAdditionalClientRegistrationResponse,
     RegisterErrorResponseType,
 };
+#[cfg(feature = "net")]
+use crate::resource_server::{BearerTokenClaims, ResourceServerValidator};
 use crate::{
-    ApplicationType, AuthDisplay, AuthPrompt, ClaimName, ClaimType, Client, ClientAuthMethod,
-    EmptyAdditionalClaims, EmptyAdditionalProviderMetadata, GenderClaim, GrantType, IdToken,
-    IdTokenClaims, IdTokenFields, IdTokenVerifier, JsonWebKeySet, JweContentEncryptionAlgorithm,
-    JweKeyManagementAlgorithm, JwsSigningAlgorithm, ProviderMetadata, ResponseMode, ResponseType,
-    SubjectIdentifierType, UserInfoClaims, UserInfoJsonWebToken, UserInfoVerifier,
+    AccessTokenClaims, AccessTokenVerifier, ApplicationType, AuthDisplay, AuthPrompt,
+    AuthorizationResponseError, AuthorizationServerMetadata, ClaimName, ClaimType, ClaimsRequest,
+    Client, ClientAuthMethod, CryptoBackend, DefaultCryptoBackend, DynAccessTokenVerifier,
+    DynIdTokenVerifier, EmptyAdditionalClaims, EmptyAdditionalProviderMetadata, GenderClaim,
+    GrantType, HashAlgorithm, IdToken, IdTokenClaims, IdTokenFields, IdTokenVerifier,
+    JsonWebKeySet, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    JwtAccessToken, LogoutToken, LogoutTokenVerifier, ProviderMetadata, ResponseMode, ResponseType,
+    SecurityEventToken, SecurityEventTokenVerifier, StoredToken, SubjectIdentifierType,
+    TimestampedTokenResponse, UserInfoClaims, UserInfoJsonWebToken, UserInfoVerifier,
 };
+#[cfg(feature = "net")]
+use crate::{IssuerRegistry, LoginFlow, LoginManager, TokenManager};
 
 use super::AuthenticationFlow;
 
@@ -35,11 +43,48 @@ mod crypto;
 // Private purely for organizational reasons; exported publicly above.
 mod jwk;
 
+// Conversions to and from the key types of other JWT/JWK crates.
+#[cfg(any(feature = "jsonwebkey", feature = "jsonwebtoken"))]
+mod interop;
+
+///
+/// OpenID Connect Core JWT access token claims.
+///
+pub type CoreAccessTokenClaims = AccessTokenClaims<EmptyAdditionalClaims>;
+
+///
+/// OpenID Connect Core JWT access token verifier.
+///
+pub type CoreAccessTokenVerifier<'a> = AccessTokenVerifier<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
 ///
 /// OpenID Connect Core authentication flows.
 ///
 pub type CoreAuthenticationFlow = AuthenticationFlow<CoreResponseType>;
 
+///
+/// OpenID Connect Core bearer access token claims. Requires "net" feature.
+///
+#[cfg(feature = "net")]
+pub type CoreBearerTokenClaims = BearerTokenClaims<EmptyAdditionalClaims>;
+
+///
+/// OpenID Connect Core authorization response error.
+///
+pub type CoreAuthorizationResponseError = AuthorizationResponseError<CoreAuthErrorResponseType>;
+
+///
+/// OpenID Connect Core claims request parameter.
+///
+pub type CoreClaimsRequest = ClaimsRequest<CoreClaimName>;
+
 ///
 /// OpenID Connect Core client.
 ///
@@ -115,6 +160,17 @@ pub type CoreClientRegistrationResponse = ClientRegistrationResponse<
     CoreSubjectIdentifierType,
 >;
 
+///
+/// Type-erased OpenID Connect Core access token verifier.
+///
+pub type CoreDynAccessTokenVerifier<'a> = DynAccessTokenVerifier<'a, EmptyAdditionalClaims>;
+
+///
+/// Type-erased OpenID Connect Core ID token verifier.
+///
+pub type CoreDynIdTokenVerifier<'a> =
+    DynIdTokenVerifier<'a, EmptyAdditionalClaims, CoreGenderClaim>;
+
 ///
 /// OpenID Connect Core ID token.
 ///
@@ -154,11 +210,161 @@ pub type CoreIdTokenVerifier<'a> = IdTokenVerifier<
     CoreJsonWebKey,
 >;
 
+///
+/// OpenID Connect Core JWT access token.
+///
+pub type CoreJwtAccessToken = JwtAccessToken<
+    EmptyAdditionalClaims,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// OpenID Connect Core resource server validator. Requires "net" feature.
+///
+#[cfg(feature = "net")]
+pub type CoreResourceServerValidator<C> = ResourceServerValidator<
+    EmptyAdditionalClaims,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    C,
+>;
+
+///
+/// OpenID Connect Core back-channel logout token.
+///
+pub type CoreLogoutToken =
+    LogoutToken<CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreJsonWebKeyType>;
+
+///
+/// OpenID Connect Core back-channel logout token verifier.
+///
+pub type CoreLogoutTokenVerifier<'a> = LogoutTokenVerifier<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Core Security Event Token.
+///
+pub type CoreSecurityEventToken = SecurityEventToken<
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// OpenID Connect Core Security Event Token verifier.
+///
+pub type CoreSecurityEventTokenVerifier<'a> = SecurityEventTokenVerifier<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Core stored token state.
+///
+pub type CoreStoredToken = StoredToken<EmptyAdditionalClaims, CoreGenderClaim>;
+
+///
+/// OpenID Connect Core token manager. Requires "net" feature.
+///
+#[cfg(feature = "net")]
+pub type CoreTokenManager = TokenManager<
+    EmptyAdditionalClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    CoreTokenResponse,
+    CoreTokenType,
+>;
+
+///
+/// OpenID Connect Core issuer registry. Requires "net" feature.
+///
+#[cfg(feature = "net")]
+pub type CoreIssuerRegistry = IssuerRegistry<
+    EmptyAdditionalClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    CoreTokenResponse,
+    CoreTokenType,
+>;
+
+///
+/// OpenID Connect Core login flow. Requires "net" feature.
+///
+#[cfg(feature = "net")]
+pub type CoreLoginFlow<'a> = LoginFlow<
+    'a,
+    EmptyAdditionalClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    CoreTokenResponse,
+    CoreTokenType,
+>;
+
+///
+/// OpenID Connect Core login manager. Requires "net" feature.
+///
+#[cfg(feature = "net")]
+pub type CoreLoginManager = LoginManager<
+    EmptyAdditionalClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    CoreTokenResponse,
+    CoreTokenType,
+>;
+
 ///
 /// OpenID Connect Core token response.
 ///
 pub type CoreTokenResponse = StandardTokenResponse<CoreIdTokenFields, CoreTokenType>;
 
+///
+/// OpenID Connect Core token response annotated with the time at which it was received.
+///
+pub type CoreTimestampedTokenResponse = TimestampedTokenResponse<CoreTokenResponse>;
+
 ///
 /// OpenID Connect Core JSON Web Key Set.
 ///
@@ -186,6 +392,21 @@ pub type CoreProviderMetadata = ProviderMetadata<
     CoreSubjectIdentifierType,
 >;
 
+///
+/// OAuth 2.0 authorization server metadata using the OpenID Connect Core types.
+///
+pub type CoreAuthorizationServerMetadata = AuthorizationServerMetadata<
+    EmptyAdditionalProviderMetadata,
+    CoreClientAuthMethod,
+    CoreGrantType,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreResponseMode,
+    CoreResponseType,
+>;
+
 ///
 /// OpenID Connect Core user info claims.
 ///
@@ -337,7 +558,10 @@ impl Display for CoreAuthDisplay {
 /// consent.
 ///
 /// These values are defined in
-/// [Section 3.1.2.1](http://openid.net/specs/openid-connect-core-1_0.html#AuthRequest).
+/// [Section 3.1.2.1](http://openid.net/specs/openid-connect-core-1_0.html#AuthRequest), except for
+/// `create`, which is defined by
+/// [Initiating User Registration via OpenID Connect](
+/// https://openid.net/specs/openid-connect-prompt-create-1_0.html#section-4).
 ///
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum CoreAuthPrompt {
@@ -371,6 +595,16 @@ pub enum CoreAuthPrompt {
     ///
     SelectAccount,
     ///
+    /// The Client desires the End-User to be shown the user account creation user interface rather
+    /// than the login flow. Providers that support this value advertise it in the
+    /// `prompt_values_supported` provider metadata field (see
+    /// [`ProviderMetadata::prompt_values_supported`]).
+    ///
+    /// [`ProviderMetadata::prompt_values_supported`]:
+    /// crate::ProviderMetadata::prompt_values_supported
+    ///
+    Create,
+    ///
     /// An extension not defined by the OpenID Connect Core spec.
     ///
     Extension(String),
@@ -384,6 +618,7 @@ impl CoreAuthPrompt {
             "login" => CoreAuthPrompt::Login,
             "consent" => CoreAuthPrompt::Consent,
             "select_account" => CoreAuthPrompt::SelectAccount,
+            "create" => CoreAuthPrompt::Create,
             ext => CoreAuthPrompt::Extension(ext.to_string()),
         }
     }
@@ -395,6 +630,7 @@ impl AsRef<str> for CoreAuthPrompt {
             CoreAuthPrompt::Login => "login",
             CoreAuthPrompt::Consent => "consent",
             CoreAuthPrompt::SelectAccount => "select_account",
+            CoreAuthPrompt::Create => "create",
             CoreAuthPrompt::Extension(ref ext) => ext.as_str(),
         }
     }
@@ -874,26 +1110,26 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
     }
 
     fn hash_bytes(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
-        use ring::digest::{digest, SHA256, SHA384, SHA512};
-        Ok(match *self {
+        let hash_alg = match *self {
             CoreJwsSigningAlgorithm::HmacSha256
             | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
             | CoreJwsSigningAlgorithm::RsaSsaPssSha256
-            | CoreJwsSigningAlgorithm::EcdsaP256Sha256 => digest(&SHA256, bytes).as_ref().to_vec(),
+            | CoreJwsSigningAlgorithm::EcdsaP256Sha256 => HashAlgorithm::Sha256,
             CoreJwsSigningAlgorithm::HmacSha384
             | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384
             | CoreJwsSigningAlgorithm::RsaSsaPssSha384
-            | CoreJwsSigningAlgorithm::EcdsaP384Sha384 => digest(&SHA384, bytes).as_ref().to_vec(),
+            | CoreJwsSigningAlgorithm::EcdsaP384Sha384 => HashAlgorithm::Sha384,
             CoreJwsSigningAlgorithm::HmacSha512
             | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512
             | CoreJwsSigningAlgorithm::RsaSsaPssSha512
-            | CoreJwsSigningAlgorithm::EcdsaP521Sha512 => digest(&SHA512, bytes).as_ref().to_vec(),
+            | CoreJwsSigningAlgorithm::EcdsaP521Sha512 => HashAlgorithm::Sha512,
             CoreJwsSigningAlgorithm::None => {
                 return Err(
                     "signature algorithm `none` has no corresponding hash algorithm".to_string(),
                 );
             }
-        })
+        };
+        Ok(DefaultCryptoBackend::digest(hash_alg, bytes))
     }
 
     fn rsa_sha_256() -> Self {
@@ -1032,7 +1268,7 @@ impl AsRef<str> for CoreAuthErrorResponseType {
             CoreAuthErrorResponseType::ConsentRequired => "consent_required",
             CoreAuthErrorResponseType::InteractionRequired => "interaction_required",
             CoreAuthErrorResponseType::InvalidRequest => "invalid_request",
-            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_obbject",
+            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_object",
             CoreAuthErrorResponseType::InvalidRequestUri => "invalid_request_uri",
             CoreAuthErrorResponseType::InvalidScope => "invalid_scope",
             CoreAuthErrorResponseType::LoginRequired => "login_required",
@@ -1047,6 +1283,12 @@ impl AsRef<str> for CoreAuthErrorResponseType {
         }
     }
 }
+impl ErrorResponseType for CoreAuthErrorResponseType {}
+impl Display for CoreAuthErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
 
 ///
 /// OpenID Connect Core registration error response type.
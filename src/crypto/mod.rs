@@ -0,0 +1,278 @@
+use crate::{SignatureVerificationError, SigningError};
+
+#[cfg(feature = "ring")]
+pub use self::ring_backend::RingCryptoBackend;
+#[cfg(feature = "rustcrypto")]
+pub use self::rustcrypto_backend::RustCryptoBackend;
+
+#[cfg(feature = "ring")]
+mod ring_backend;
+#[cfg(feature = "rustcrypto")]
+mod rustcrypto_backend;
+
+///
+/// Cryptography backend selected by the enabled feature flags.
+///
+/// This is [`RingCryptoBackend`] when the "ring" feature is enabled, and [`RustCryptoBackend`]
+/// when only the "rustcrypto" feature is enabled.
+///
+#[cfg(feature = "ring")]
+pub type DefaultCryptoBackend = RingCryptoBackend;
+///
+/// Cryptography backend selected by the enabled feature flags.
+///
+/// This is [`RingCryptoBackend`] when the "ring" feature is enabled, and [`RustCryptoBackend`]
+/// when only the "rustcrypto" feature is enabled.
+///
+#[cfg(all(feature = "rustcrypto", not(feature = "ring")))]
+pub type DefaultCryptoBackend = RustCryptoBackend;
+
+///
+/// Hash algorithm used for message digests, HMACs, and RSA signatures.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    ///
+    /// SHA-256.
+    ///
+    Sha256,
+    ///
+    /// SHA-384.
+    ///
+    Sha384,
+    ///
+    /// SHA-512.
+    ///
+    Sha512,
+}
+
+///
+/// Padding scheme used for RSA signatures.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RsaPadding {
+    ///
+    /// RSASSA-PKCS1-v1_5.
+    ///
+    Pkcs1V15,
+    ///
+    /// RSASSA-PSS, using a salt as long as the hash algorithm's output.
+    ///
+    Pss,
+}
+
+///
+/// Implementation of the cryptographic primitives used to sign and verify JSON Web Tokens.
+///
+/// The [`core`](crate::core) types use the [`DefaultCryptoBackend`] selected by the "ring" and
+/// "rustcrypto" feature flags. Environments that forbid `ring`'s C and assembly code (or that
+/// require a FIPS-validated build of a particular library) may disable the default "ring" feature
+/// and enable "rustcrypto" instead.
+///
+pub trait CryptoBackend: 'static {
+    ///
+    /// RSA private key used for signing messages.
+    ///
+    type RsaPrivateKey;
+    ///
+    /// Cryptographically secure random number generator used for signing messages (e.g., to
+    /// generate RSA-PSS salts).
+    ///
+    type SecureRandom: ?Sized;
+
+    ///
+    /// Returns the system's cryptographically secure random number generator.
+    ///
+    fn system_random() -> Box<Self::SecureRandom>;
+
+    ///
+    /// Returns the digest of the specified message.
+    ///
+    fn digest(hash_alg: HashAlgorithm, message: &[u8]) -> Vec<u8>;
+
+    ///
+    /// Returns whether the two byte strings are equal, in time that depends only on their lengths.
+    ///
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool;
+
+    ///
+    /// Returns the HMAC of the specified message.
+    ///
+    fn hmac_sign(hash_alg: HashAlgorithm, key: &[u8], message: &[u8]) -> Vec<u8>;
+
+    ///
+    /// Returns whether `signature` is the HMAC of the specified message.
+    ///
+    fn hmac_verify(hash_alg: HashAlgorithm, key: &[u8], message: &[u8], signature: &[u8]) -> bool;
+
+    ///
+    /// Encrypts the specified message using AES-256-GCM with a 32-byte key and a 12-byte nonce,
+    /// returning the ciphertext followed by the authentication tag.
+    ///
+    /// The same nonce must never be used twice with the same key.
+    ///
+    fn aes_gcm_seal(key: &[u8], nonce: &[u8], message: &[u8]) -> Result<Vec<u8>, String>;
+
+    ///
+    /// Decrypts a ciphertext (followed by its authentication tag) produced by
+    /// [`CryptoBackend::aes_gcm_seal`], returning an error if the key or nonce is invalid or if the
+    /// ciphertext fails authentication.
+    ///
+    fn aes_gcm_open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String>;
+
+    ///
+    /// Verifies an RSA signature using the public key with the specified big-endian modulus (`n`)
+    /// and exponent (`e`).
+    ///
+    fn rsa_verify(
+        padding: RsaPadding,
+        hash_alg: HashAlgorithm,
+        n: &[u8],
+        e: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignatureVerificationError>;
+
+    ///
+    /// Parses a DER-encoded RSA private key (see
+    /// [RFC 8017](https://tools.ietf.org/html/rfc8017#appendix-A.1.2)).
+    ///
+    fn rsa_private_key_from_der(der: &[u8]) -> Result<Self::RsaPrivateKey, String>;
+
+    ///
+    /// Returns the big-endian modulus (`n`) and exponent (`e`) of the specified RSA private key's
+    /// public key, without leading zeros.
+    ///
+    fn rsa_public_key(key: &Self::RsaPrivateKey) -> (Vec<u8>, Vec<u8>);
+
+    ///
+    /// Signs the specified message using an RSA private key.
+    ///
+    fn rsa_sign(
+        key: &Self::RsaPrivateKey,
+        padding: RsaPadding,
+        hash_alg: HashAlgorithm,
+        rng: &Self::SecureRandom,
+        message: &[u8],
+    ) -> Result<Vec<u8>, SigningError>;
+}
+
+pub(crate) fn sha256(message: &[u8]) -> Vec<u8> {
+    DefaultCryptoBackend::digest(HashAlgorithm::Sha256, message)
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    DefaultCryptoBackend::constant_time_eq(a, b)
+}
+
+#[cfg(all(test, feature = "ring", feature = "rustcrypto"))]
+mod tests {
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+
+    use super::{CryptoBackend, HashAlgorithm, RingCryptoBackend, RsaPadding, RustCryptoBackend};
+
+    #[test]
+    fn test_backends_interoperate() {
+        let der = base64::decode(
+            TEST_RSA_PRIV_KEY
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with("-----"))
+                .collect::<String>(),
+        )
+        .unwrap();
+        let ring_key = RingCryptoBackend::rsa_private_key_from_der(&der).unwrap();
+        let rustcrypto_key = RustCryptoBackend::rsa_private_key_from_der(&der).unwrap();
+        let (n, e) = RingCryptoBackend::rsa_public_key(&ring_key);
+        assert_eq!(
+            (n.clone(), e.clone()),
+            RustCryptoBackend::rsa_public_key(&rustcrypto_key)
+        );
+
+        let message = b"hello backends";
+        for hash_alg in &[
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Sha384,
+            HashAlgorithm::Sha512,
+        ] {
+            assert_eq!(
+                RingCryptoBackend::digest(*hash_alg, message),
+                RustCryptoBackend::digest(*hash_alg, message)
+            );
+
+            let mac = RingCryptoBackend::hmac_sign(*hash_alg, b"secret", message);
+            assert_eq!(
+                mac,
+                RustCryptoBackend::hmac_sign(*hash_alg, b"secret", message)
+            );
+            assert!(RustCryptoBackend::hmac_verify(
+                *hash_alg, b"secret", message, &mac
+            ));
+            assert!(!RustCryptoBackend::hmac_verify(
+                *hash_alg, b"other", message, &mac
+            ));
+
+            for padding in &[RsaPadding::Pkcs1V15, RsaPadding::Pss] {
+                let ring_sig = RingCryptoBackend::rsa_sign(
+                    &ring_key,
+                    *padding,
+                    *hash_alg,
+                    RingCryptoBackend::system_random().as_ref(),
+                    message,
+                )
+                .unwrap();
+                RustCryptoBackend::rsa_verify(*padding, *hash_alg, &n, &e, message, &ring_sig)
+                    .unwrap();
+
+                let rustcrypto_sig = RustCryptoBackend::rsa_sign(
+                    &rustcrypto_key,
+                    *padding,
+                    *hash_alg,
+                    RustCryptoBackend::system_random().as_ref(),
+                    message,
+                )
+                .unwrap();
+                RingCryptoBackend::rsa_verify(
+                    *padding,
+                    *hash_alg,
+                    &n,
+                    &e,
+                    message,
+                    &rustcrypto_sig,
+                )
+                .unwrap();
+                RingCryptoBackend::rsa_verify(
+                    *padding,
+                    *hash_alg,
+                    &n,
+                    &e,
+                    b"other",
+                    &rustcrypto_sig,
+                )
+                .unwrap_err();
+            }
+        }
+
+        let key = [7u8; 32];
+        let nonce = [9u8; 12];
+        let ciphertext = RingCryptoBackend::aes_gcm_seal(&key, &nonce, message).unwrap();
+        assert_eq!(
+            ciphertext,
+            RustCryptoBackend::aes_gcm_seal(&key, &nonce, message).unwrap()
+        );
+        assert_eq!(
+            RustCryptoBackend::aes_gcm_open(&key, &nonce, &ciphertext).unwrap(),
+            message.to_vec()
+        );
+        RustCryptoBackend::aes_gcm_open(&[8u8; 32], &nonce, &ciphertext).unwrap_err();
+        RingCryptoBackend::aes_gcm_open(&key, &nonce, &ciphertext[1..]).unwrap_err();
+        RingCryptoBackend::aes_gcm_seal(&key[1..], &nonce, message).unwrap_err();
+        RustCryptoBackend::aes_gcm_seal(&key, &nonce[1..], message).unwrap_err();
+
+        assert!(RustCryptoBackend::constant_time_eq(b"abc", b"abc"));
+        assert!(!RustCryptoBackend::constant_time_eq(b"abc", b"abd"));
+        assert!(!RustCryptoBackend::constant_time_eq(b"abc", b"ab"));
+    }
+}
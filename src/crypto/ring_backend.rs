@@ -0,0 +1,159 @@
+use ring::aead;
+use ring::constant_time::verify_slices_are_equal;
+use ring::digest;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature as ring_signature;
+use ring::signature::KeyPair;
+
+use crate::{SignatureVerificationError, SigningError};
+
+use super::{CryptoBackend, HashAlgorithm, RsaPadding};
+
+///
+/// Cryptography backend implemented using [`ring`](https://docs.rs/ring). Requires "ring" feature.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct RingCryptoBackend;
+impl RingCryptoBackend {
+    fn digest_alg(hash_alg: HashAlgorithm) -> &'static digest::Algorithm {
+        match hash_alg {
+            HashAlgorithm::Sha256 => &digest::SHA256,
+            HashAlgorithm::Sha384 => &digest::SHA384,
+            HashAlgorithm::Sha512 => &digest::SHA512,
+        }
+    }
+
+    fn hmac_key(hash_alg: HashAlgorithm, key: &[u8]) -> hmac::Key {
+        let hmac_alg = match hash_alg {
+            HashAlgorithm::Sha256 => hmac::HMAC_SHA256,
+            HashAlgorithm::Sha384 => hmac::HMAC_SHA384,
+            HashAlgorithm::Sha512 => hmac::HMAC_SHA512,
+        };
+        hmac::Key::new(hmac_alg, key)
+    }
+
+    fn aes_gcm_key(key: &[u8], nonce: &[u8]) -> Result<(aead::LessSafeKey, aead::Nonce), String> {
+        let key = aead::UnboundKey::new(&aead::AES_256_GCM, key)
+            .map_err(|_| "AES-256-GCM key must be 32 bytes".to_string())?;
+        let nonce = aead::Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| "AES-GCM nonce must be 12 bytes".to_string())?;
+        Ok((aead::LessSafeKey::new(key), nonce))
+    }
+}
+impl CryptoBackend for RingCryptoBackend {
+    type RsaPrivateKey = ring_signature::RsaKeyPair;
+    type SecureRandom = dyn SecureRandom;
+
+    fn system_random() -> Box<dyn SecureRandom> {
+        Box::new(SystemRandom::new())
+    }
+
+    fn digest(hash_alg: HashAlgorithm, message: &[u8]) -> Vec<u8> {
+        digest::digest(Self::digest_alg(hash_alg), message)
+            .as_ref()
+            .to_vec()
+    }
+
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        verify_slices_are_equal(a, b).is_ok()
+    }
+
+    fn hmac_sign(hash_alg: HashAlgorithm, key: &[u8], message: &[u8]) -> Vec<u8> {
+        hmac::sign(&Self::hmac_key(hash_alg, key), message)
+            .as_ref()
+            .to_vec()
+    }
+
+    fn hmac_verify(hash_alg: HashAlgorithm, key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        hmac::verify(&Self::hmac_key(hash_alg, key), message, signature).is_ok()
+    }
+
+    fn aes_gcm_seal(key: &[u8], nonce: &[u8], message: &[u8]) -> Result<Vec<u8>, String> {
+        let (key, nonce) = Self::aes_gcm_key(key, nonce)?;
+        let mut in_out = message.to_vec();
+        key.seal_in_place_append_tag(nonce, aead::Aad::empty(), &mut in_out)
+            .map_err(|_| "failed to encrypt message".to_string())?;
+        Ok(in_out)
+    }
+
+    fn aes_gcm_open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let (key, nonce) = Self::aes_gcm_key(key, nonce)?;
+        let mut in_out = ciphertext.to_vec();
+        let message_len = key
+            .open_in_place(nonce, aead::Aad::empty(), &mut in_out)
+            .map_err(|_| "failed to decrypt message".to_string())?
+            .len();
+        in_out.truncate(message_len);
+        Ok(in_out)
+    }
+
+    fn rsa_verify(
+        padding: RsaPadding,
+        hash_alg: HashAlgorithm,
+        n: &[u8],
+        e: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignatureVerificationError> {
+        let params: &ring_signature::RsaParameters = match (padding, hash_alg) {
+            (RsaPadding::Pkcs1V15, HashAlgorithm::Sha256) => {
+                &ring_signature::RSA_PKCS1_2048_8192_SHA256
+            }
+            (RsaPadding::Pkcs1V15, HashAlgorithm::Sha384) => {
+                &ring_signature::RSA_PKCS1_2048_8192_SHA384
+            }
+            (RsaPadding::Pkcs1V15, HashAlgorithm::Sha512) => {
+                &ring_signature::RSA_PKCS1_2048_8192_SHA512
+            }
+            (RsaPadding::Pss, HashAlgorithm::Sha256) => &ring_signature::RSA_PSS_2048_8192_SHA256,
+            (RsaPadding::Pss, HashAlgorithm::Sha384) => &ring_signature::RSA_PSS_2048_8192_SHA384,
+            (RsaPadding::Pss, HashAlgorithm::Sha512) => &ring_signature::RSA_PSS_2048_8192_SHA512,
+        };
+        let public_key = ring_signature::RsaPublicKeyComponents { n, e };
+
+        public_key
+            .verify(params, message, signature)
+            .map_err(|_| SignatureVerificationError::CryptoError("bad signature".to_string()))
+    }
+
+    fn rsa_private_key_from_der(der: &[u8]) -> Result<Self::RsaPrivateKey, String> {
+        ring_signature::RsaKeyPair::from_der(der).map_err(|err| err.description_().to_string())
+    }
+
+    fn rsa_public_key(key: &Self::RsaPrivateKey) -> (Vec<u8>, Vec<u8>) {
+        let public_key = key.public_key();
+        (
+            public_key
+                .modulus()
+                .big_endian_without_leading_zero()
+                .to_vec(),
+            public_key
+                .exponent()
+                .big_endian_without_leading_zero()
+                .to_vec(),
+        )
+    }
+
+    fn rsa_sign(
+        key: &Self::RsaPrivateKey,
+        padding: RsaPadding,
+        hash_alg: HashAlgorithm,
+        rng: &Self::SecureRandom,
+        message: &[u8],
+    ) -> Result<Vec<u8>, SigningError> {
+        let padding_alg: &'static dyn ring_signature::RsaEncoding = match (padding, hash_alg) {
+            (RsaPadding::Pkcs1V15, HashAlgorithm::Sha256) => &ring_signature::RSA_PKCS1_SHA256,
+            (RsaPadding::Pkcs1V15, HashAlgorithm::Sha384) => &ring_signature::RSA_PKCS1_SHA384,
+            (RsaPadding::Pkcs1V15, HashAlgorithm::Sha512) => &ring_signature::RSA_PKCS1_SHA512,
+            (RsaPadding::Pss, HashAlgorithm::Sha256) => &ring_signature::RSA_PSS_SHA256,
+            (RsaPadding::Pss, HashAlgorithm::Sha384) => &ring_signature::RSA_PSS_SHA384,
+            (RsaPadding::Pss, HashAlgorithm::Sha512) => &ring_signature::RSA_PSS_SHA512,
+        };
+
+        let mut sig = vec![0; key.public_modulus_len()];
+        key.sign(padding_alg, rng, message, &mut sig)
+            .map_err(|_| SigningError::CryptoError)?;
+        Ok(sig)
+    }
+}
@@ -0,0 +1,163 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac};
+use rand_core::OsRng;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pkcs1v15Sign, Pss, RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use subtle::ConstantTimeEq;
+
+use crate::{SignatureVerificationError, SigningError};
+
+use super::{CryptoBackend, HashAlgorithm, RsaPadding};
+
+// Length of AES-GCM nonces, in bytes.
+const AES_GCM_NONCE_LEN: usize = 12;
+
+// Accept the same range of RSA modulus sizes as the `ring` backend.
+const RSA_MIN_MODULUS_BITS: usize = 2048;
+const RSA_MAX_MODULUS_BITS: usize = 8192;
+
+// Evaluates `$body` with `$hash` bound to the RustCrypto digest type corresponding to `$hash_alg`.
+macro_rules! with_hash {
+    ($hash_alg:expr, $hash:ident => $body:expr) => {
+        match $hash_alg {
+            HashAlgorithm::Sha256 => {
+                type $hash = Sha256;
+                $body
+            }
+            HashAlgorithm::Sha384 => {
+                type $hash = Sha384;
+                $body
+            }
+            HashAlgorithm::Sha512 => {
+                type $hash = Sha512;
+                $body
+            }
+        }
+    };
+}
+
+///
+/// Cryptography backend implemented using the pure-Rust [RustCrypto](https://github.com/RustCrypto)
+/// crates. Requires "rustcrypto" feature.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct RustCryptoBackend;
+impl CryptoBackend for RustCryptoBackend {
+    type RsaPrivateKey = RsaPrivateKey;
+    type SecureRandom = OsRng;
+
+    fn system_random() -> Box<OsRng> {
+        Box::new(OsRng)
+    }
+
+    fn digest(hash_alg: HashAlgorithm, message: &[u8]) -> Vec<u8> {
+        with_hash!(hash_alg, H => H::digest(message).to_vec())
+    }
+
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        a.ct_eq(b).into()
+    }
+
+    fn hmac_sign(hash_alg: HashAlgorithm, key: &[u8], message: &[u8]) -> Vec<u8> {
+        with_hash!(hash_alg, H => {
+            let mut mac =
+                Hmac::<H>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        })
+    }
+
+    fn hmac_verify(hash_alg: HashAlgorithm, key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        with_hash!(hash_alg, H => {
+            let mut mac =
+                Hmac::<H>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.verify_slice(signature).is_ok()
+        })
+    }
+
+    fn aes_gcm_seal(key: &[u8], nonce: &[u8], message: &[u8]) -> Result<Vec<u8>, String> {
+        aes_gcm_cipher(key, nonce)?
+            .encrypt(Nonce::from_slice(nonce), message)
+            .map_err(|_| "failed to encrypt message".to_string())
+    }
+
+    fn aes_gcm_open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        aes_gcm_cipher(key, nonce)?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "failed to decrypt message".to_string())
+    }
+
+    fn rsa_verify(
+        padding: RsaPadding,
+        hash_alg: HashAlgorithm,
+        n: &[u8],
+        e: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignatureVerificationError> {
+        let public_key = RsaPublicKey::new_with_max_size(
+            BigUint::from_bytes_be(n),
+            BigUint::from_bytes_be(e),
+            RSA_MAX_MODULUS_BITS,
+        )
+        .map_err(|err| SignatureVerificationError::InvalidKey(err.to_string()))?;
+        if public_key.size() * 8 < RSA_MIN_MODULUS_BITS {
+            return Err(SignatureVerificationError::InvalidKey(format!(
+                "RSA modulus must be at least {} bits",
+                RSA_MIN_MODULUS_BITS
+            )));
+        }
+
+        with_hash!(hash_alg, H => {
+            let hashed = H::digest(message);
+            match padding {
+                RsaPadding::Pkcs1V15 => {
+                    public_key.verify(Pkcs1v15Sign::new::<H>(), &hashed, signature)
+                }
+                RsaPadding::Pss => public_key.verify(Pss::new::<H>(), &hashed, signature),
+            }
+        })
+        .map_err(|_| SignatureVerificationError::CryptoError("bad signature".to_string()))
+    }
+
+    fn rsa_private_key_from_der(der: &[u8]) -> Result<Self::RsaPrivateKey, String> {
+        RsaPrivateKey::from_pkcs1_der(der).map_err(|err| err.to_string())
+    }
+
+    fn rsa_public_key(key: &Self::RsaPrivateKey) -> (Vec<u8>, Vec<u8>) {
+        (key.n().to_bytes_be(), key.e().to_bytes_be())
+    }
+
+    fn rsa_sign(
+        key: &Self::RsaPrivateKey,
+        padding: RsaPadding,
+        hash_alg: HashAlgorithm,
+        rng: &OsRng,
+        message: &[u8],
+    ) -> Result<Vec<u8>, SigningError> {
+        let mut rng = *rng;
+        with_hash!(hash_alg, H => {
+            let hashed = H::digest(message);
+            match padding {
+                RsaPadding::Pkcs1V15 => {
+                    key.sign_with_rng(&mut rng, Pkcs1v15Sign::new::<H>(), &hashed)
+                }
+                RsaPadding::Pss => key.sign_with_rng(&mut rng, Pss::new::<H>(), &hashed),
+            }
+        })
+        .map_err(|_| SigningError::CryptoError)
+    }
+}
+
+// Returns the cipher for the specified key after checking the nonce length, since
+// `Nonce::from_slice` panics if the length is incorrect.
+fn aes_gcm_cipher(key: &[u8], nonce: &[u8]) -> Result<Aes256Gcm, String> {
+    if nonce.len() != AES_GCM_NONCE_LEN {
+        return Err("AES-GCM nonce must be 12 bytes".to_string());
+    }
+    Aes256Gcm::new_from_slice(key).map_err(|_| "AES-256-GCM key must be 32 bytes".to_string())
+}
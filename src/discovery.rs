@@ -1,25 +1,105 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
 
 use failure::Fail;
+#[cfg(feature = "net")]
+use futures::executor::block_on;
+#[cfg(feature = "net")]
 use futures::Future;
-use oauth2::{AuthUrl, Scope, TokenUrl};
+use oauth2::{AuthUrl, PkceCodeChallengeMethod, Scope, TokenUrl};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
 use url;
 
+use crate::error::{ClassifiedError, ErrorKind};
+#[cfg(feature = "net")]
+use crate::http_client::{
+    AsyncHttpClient, BlockingHttpClient, HttpClient, HttpClientError, InstrumentedHttpClient,
+    RequestKind, RequestOptions, UnexpectedResponse,
+};
+#[cfg(feature = "net")]
 use crate::http_types::headers::ACCEPT;
-use crate::http_types::{Body, Method, Request, Response, StatusCode};
+#[cfg(feature = "net")]
+use crate::http_types::{Method, Request, Response, StatusCode};
+#[cfg(feature = "net")]
 use crate::http_utils::{check_content_type, MIME_TYPE_JSON};
 use crate::types::{
-    AuthDisplay, AuthenticationContextClass, ClaimName, ClaimType, ClientAuthMethod, GrantType,
+    AuthDisplay, AuthenticationContextClass, CheckSessionIframeUrl, ClaimName, ClaimType,
+    ClientAuthMethod, DeviceAuthorizationUrl, EndSessionUrl, GrantType, IntrospectionUrl,
     IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse,
     JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
     OpPolicyUrl, OpTosUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
-    ServiceDocUrl, SubjectIdentifierType,
+    RevocationUrl, ServiceDocUrl, SubjectIdentifierType,
 };
-use crate::{UserInfoUrl, CONFIG_URL_SUFFIX};
+#[cfg(feature = "net")]
+use crate::CONFIG_URL_SUFFIX;
+use crate::{UserInfoUrl, PKCE_METHOD_S256};
+
+// Fields modeled by `ProviderMetadata`. Only these fields are modified when parsing provider
+// metadata leniently, so this list must be kept in sync with the struct definition below.
+#[cfg(feature = "net")]
+const STANDARD_FIELDS: &[&str] = &[
+    "issuer",
+    "authorization_endpoint",
+    "token_endpoint",
+    "userinfo_endpoint",
+    "jwks_uri",
+    "registration_endpoint",
+    "scopes_supported",
+    "response_types_supported",
+    "response_modes_supported",
+    "grant_types_supported",
+    "acr_values_supported",
+    "subject_types_supported",
+    "id_token_signing_alg_values_supported",
+    "id_token_encryption_alg_values_supported",
+    "id_token_encryption_enc_values_supported",
+    "userinfo_signing_alg_values_supported",
+    "userinfo_encryption_alg_values_supported",
+    "userinfo_encryption_enc_values_supported",
+    "request_object_signing_alg_values_supported",
+    "request_object_encryption_alg_values_supported",
+    "request_object_encryption_enc_values_supported",
+    "token_endpoint_auth_methods_supported",
+    "token_endpoint_auth_signing_alg_values_supported",
+    "display_values_supported",
+    "claim_types_supported",
+    "claims_supported",
+    "service_documentation",
+    "claims_locales_supported",
+    "ui_locales_supported",
+    "claims_parameter_supported",
+    "request_parameter_supported",
+    "request_uri_parameter_supported",
+    "require_request_uri_registration",
+    "op_policy_uri",
+    "op_tos_uri",
+    "code_challenge_methods_supported",
+    "device_authorization_endpoint",
+    "introspection_endpoint",
+    "revocation_endpoint",
+    "end_session_endpoint",
+    "check_session_iframe",
+    "frontchannel_logout_supported",
+    "frontchannel_logout_session_supported",
+    "backchannel_logout_supported",
+    "backchannel_logout_session_supported",
+    "authorization_response_iss_parameter_supported",
+    "prompt_values_supported",
+];
+
+// Fields required by `ProviderMetadata`, which are never ignored when parsing provider metadata
+// leniently.
+#[cfg(feature = "net")]
+const REQUIRED_FIELDS: &[&str] = &[
+    "issuer",
+    "authorization_endpoint",
+    "jwks_uri",
+    "response_types_supported",
+    "subject_types_supported",
+    "id_token_signing_alg_values_supported",
+];
 
 ///
 /// Trait for adding extra fields to [`ProviderMetadata`].
@@ -39,6 +119,32 @@ impl AdditionalProviderMetadata for EmptyAdditionalProviderMetadata {}
 /// Provider metadata returned by [OpenID Connect Discovery](
 /// https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata).
 ///
+/// The `code_challenge_methods_supported`, `introspection_endpoint`, and `revocation_endpoint`
+/// fields defined by [RFC 8414](https://tools.ietf.org/html/rfc8414#section-2) are also
+/// supported, since they're commonly included by OpenID Connect Providers to advertise support for
+/// PKCE, token introspection, and token revocation. The `device_authorization_endpoint` field
+/// defined by [RFC 8628](https://tools.ietf.org/html/rfc8628#section-4) is supported for the same
+/// reason.
+///
+/// The logout-related fields defined by
+/// [OpenID Connect RP-Initiated Logout](
+/// https://openid.net/specs/openid-connect-rpinitiated-1_0.html#OPMetadata),
+/// [Front-Channel Logout](
+/// https://openid.net/specs/openid-connect-frontchannel-1_0.html#OPLogout), and
+/// [Back-Channel Logout](
+/// https://openid.net/specs/openid-connect-backchannel-1_0.html#BCSupport) are also supported, as
+/// is the `authorization_response_iss_parameter_supported` field defined by
+/// [RFC 9207](https://tools.ietf.org/html/rfc9207#section-3) and the `prompt_values_supported`
+/// field defined by
+/// [Initiating User Registration via OpenID Connect](
+/// https://openid.net/specs/openid-connect-prompt-create-1_0.html#section-4.2). Since provider
+/// metadata isn't parameterized by the [`AuthPrompt`](crate::AuthPrompt) type, prompt values are
+/// represented as strings.
+///
+/// Fields that are neither modeled by this struct nor by the [`AdditionalProviderMetadata`] are
+/// preserved and may be accessed via [`ProviderMetadata::unknown_fields`]. This allows
+/// provider-specific extensions to be read without defining a custom additional metadata type.
+///
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[allow(clippy::type_complexity)]
 pub struct ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
@@ -173,10 +279,39 @@ where
     op_policy_uri: Option<OpPolicyUrl>,
     #[serde(skip_serializing_if = "Option::is_none")]
     op_tos_uri: Option<OpTosUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_challenge_methods_supported: Option<Vec<PkceCodeChallengeMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_authorization_endpoint: Option<DeviceAuthorizationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    introspection_endpoint: Option<IntrospectionUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_endpoint: Option<RevocationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_session_endpoint: Option<EndSessionUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check_session_iframe: Option<CheckSessionIframeUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frontchannel_logout_supported: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frontchannel_logout_session_supported: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backchannel_logout_supported: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backchannel_logout_session_supported: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authorization_response_iss_parameter_supported: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt_values_supported: Option<Vec<String>>,
 
     #[serde(bound(deserialize = "A: AdditionalProviderMetadata"), flatten)]
     additional_metadata: A,
 
+    // This must follow `additional_metadata` so that fields deserialized by the additional metadata
+    // type aren't also captured here.
+    #[serde(flatten)]
+    unknown_fields: serde_json::Map<String, serde_json::Value>,
+
     #[serde(skip)]
     _phantom_jt: PhantomData<JT>,
 }
@@ -248,7 +383,20 @@ where
             require_request_uri_registration: None,
             op_policy_uri: None,
             op_tos_uri: None,
+            code_challenge_methods_supported: None,
+            device_authorization_endpoint: None,
+            introspection_endpoint: None,
+            revocation_endpoint: None,
+            end_session_endpoint: None,
+            check_session_iframe: None,
+            frontchannel_logout_supported: None,
+            frontchannel_logout_session_supported: None,
+            backchannel_logout_supported: None,
+            backchannel_logout_session_supported: None,
+            authorization_response_iss_parameter_supported: None,
+            prompt_values_supported: None,
             additional_metadata,
+            unknown_fields: serde_json::Map::new(),
             _phantom_jt: PhantomData,
         }
     }
@@ -303,13 +451,37 @@ where
             set_require_request_uri_registration -> require_request_uri_registration[Option<bool>],
             set_op_policy_uri -> op_policy_uri[Option<OpPolicyUrl>],
             set_op_tos_uri -> op_tos_uri[Option<OpTosUrl>],
+            set_code_challenge_methods_supported
+                -> code_challenge_methods_supported[Option<Vec<PkceCodeChallengeMethod>>],
+            set_device_authorization_endpoint
+                -> device_authorization_endpoint[Option<DeviceAuthorizationUrl>],
+            set_introspection_endpoint -> introspection_endpoint[Option<IntrospectionUrl>],
+            set_revocation_endpoint -> revocation_endpoint[Option<RevocationUrl>],
+            set_end_session_endpoint -> end_session_endpoint[Option<EndSessionUrl>],
+            set_check_session_iframe -> check_session_iframe[Option<CheckSessionIframeUrl>],
+            set_frontchannel_logout_supported -> frontchannel_logout_supported[Option<bool>],
+            set_frontchannel_logout_session_supported
+                -> frontchannel_logout_session_supported[Option<bool>],
+            set_backchannel_logout_supported -> backchannel_logout_supported[Option<bool>],
+            set_backchannel_logout_session_supported
+                -> backchannel_logout_session_supported[Option<bool>],
+            set_authorization_response_iss_parameter_supported
+                -> authorization_response_iss_parameter_supported[Option<bool>],
+            set_prompt_values_supported -> prompt_values_supported[Option<Vec<String>>],
         }
     ];
 
     ///
     /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
-    /// from the OpenID Connect Provider.
+    /// from the OpenID Connect Provider using an HTTP client passed by value.
+    ///
+    /// Requires "net" feature.
     ///
+    #[cfg(feature = "net")]
+    #[deprecated(
+        since = "2.0.0",
+        note = "use `discover_async`, which borrows an `AsyncHttpClient` so that it may be reused"
+    )]
     pub async fn discover<F, HC, RE>(
         issuer_url: IssuerUrl,
         http_client: HC,
@@ -319,142 +491,1173 @@ where
         HC: Fn(Request) -> F + 'static,
         RE: Fail,
     {
-        let discovery_url = issuer_url
-            .join(CONFIG_URL_SUFFIX)
-            .map_err(DiscoveryError::UrlParse)?;
+        Self::discover_async(issuer_url, &http_client).await
+    }
 
-        let provider_metadata = match http_client(Self::discovery_request(discovery_url)).await {
-            Err(err) => Err(DiscoveryError::Request(err)),
-            Ok(http_response) => Self::discovery_response(&issuer_url, http_response).await,
-        }?;
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_async<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        Self::discover_with_options(issuer_url, http_client, &RequestOptions::default()).await
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider using the specified [`DiscoveryOptions`].
+    ///
+    /// Returns the provider metadata along with any [`DiscoveryWarning`]s encountered while
+    /// parsing the Discovery document, which is always empty unless
+    /// [`DiscoveryOptions::set_lenient`] is enabled.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_with<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+        options: &DiscoveryOptions,
+    ) -> Result<(Self, Vec<DiscoveryWarning>), DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        Self::discover_impl(issuer_url, http_client, options, None).await
+    }
 
-        JsonWebKeySet::fetch(provider_metadata.jwks_uri(), http_client)
+    ///
+    /// Synchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider using the specified [`DiscoveryOptions`].
+    ///
+    /// See [`ProviderMetadata::discover_with`] for details.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_with_blocking<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+        options: &DiscoveryOptions,
+    ) -> Result<(Self, Vec<DiscoveryWarning>), DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_with(
+            issuer_url,
+            &BlockingHttpClient(http_client),
+            options,
+        ))
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, applying the specified options to each HTTP request.
+    ///
+    /// This is equivalent to [`ProviderMetadata::discover_with`] using
+    /// [`DiscoveryOptions::set_request_options`].
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_with_options<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+        request_options: &RequestOptions,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let options = DiscoveryOptions::new().set_request_options(request_options.clone());
+        Self::discover_with(issuer_url, http_client, &options)
+            .await
+            .map(|(provider_metadata, _)| provider_metadata)
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, enforcing the specified policy on the endpoint URLs listed
+    /// in the Discovery document.
+    ///
+    /// This is equivalent to [`ProviderMetadata::discover_with`] using
+    /// [`DiscoveryOptions::set_request_options`] and [`DiscoveryOptions::set_endpoint_policy`].
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_with_endpoint_policy<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+        request_options: &RequestOptions,
+        endpoint_policy: &EndpointSecurityPolicy,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let options = DiscoveryOptions::new()
+            .set_request_options(request_options.clone())
+            .set_endpoint_policy(endpoint_policy.clone());
+        Self::discover_with(issuer_url, http_client, &options)
+            .await
+            .map(|(provider_metadata, _)| provider_metadata)
+    }
+
+    ///
+    /// Asynchronously fetches the provider metadata from the specified URL and the associated JSON
+    /// Web Key Set from the OpenID Connect Provider.
+    ///
+    /// This is equivalent to [`ProviderMetadata::discover_with`] using
+    /// [`DiscoveryOptions::set_discovery_url`].
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_from_url<C>(
+        issuer_url: IssuerUrl,
+        discovery_url: url::Url,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let options = DiscoveryOptions::new().set_discovery_url(Some(discovery_url));
+        Self::discover_with(issuer_url, http_client, &options)
             .await
-            .map(|jwks| Self {
+            .map(|(provider_metadata, _)| provider_metadata)
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, tolerating common spec violations in the Discovery
+    /// document.
+    ///
+    /// This is equivalent to [`ProviderMetadata::discover_with`] using
+    /// [`DiscoveryOptions::set_lenient`].
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_lenient<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+    ) -> Result<(Self, Vec<DiscoveryWarning>), DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let options = DiscoveryOptions::new().set_lenient(true);
+        Self::discover_with(issuer_url, http_client, &options).await
+    }
+
+    ///
+    /// Synchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_blocking<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_async(
+            issuer_url,
+            &BlockingHttpClient(http_client),
+        ))
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document from the OpenID Connect
+    /// Provider, using the specified JSON Web Key Set instead of fetching it from the provider.
+    ///
+    /// This supports pinning the provider's signing keys (e.g., keys distributed out of band or
+    /// cached from a previous discovery), and avoids a request to the `jwks_uri` endpoint. The
+    /// returned metadata retains the specified key set (see [`ProviderMetadata::jwks`]), so it may
+    /// be passed directly to
+    /// [`Client::from_provider_metadata`](crate::Client::from_provider_metadata).
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn discover_with_jwks<C>(
+        issuer_url: IssuerUrl,
+        jwks: JsonWebKeySet<JS, JT, JU, K>,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        Self::discover_impl(
+            issuer_url,
+            http_client,
+            &DiscoveryOptions::default(),
+            Some(jwks),
+        )
+        .await
+        .map(|(provider_metadata, _)| provider_metadata)
+    }
+
+    ///
+    /// Synchronously fetches the OpenID Connect Discovery document from the OpenID Connect
+    /// Provider, using the specified JSON Web Key Set instead of fetching it from the provider.
+    ///
+    /// See [`ProviderMetadata::discover_with_jwks`] for details.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_with_jwks_blocking<C>(
+        issuer_url: IssuerUrl,
+        jwks: JsonWebKeySet<JS, JT, JU, K>,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_with_jwks(
+            issuer_url,
+            jwks,
+            &BlockingHttpClient(http_client),
+        ))
+    }
+
+    ///
+    /// Synchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, enforcing the specified policy on the endpoint URLs listed
+    /// in the Discovery document.
+    ///
+    /// See [`ProviderMetadata::discover_with_endpoint_policy`] for details.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_with_endpoint_policy_blocking<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+        request_options: &RequestOptions,
+        endpoint_policy: &EndpointSecurityPolicy,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_with_endpoint_policy(
+            issuer_url,
+            &BlockingHttpClient(http_client),
+            request_options,
+            endpoint_policy,
+        ))
+    }
+
+    ///
+    /// Synchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, tolerating common spec violations in the Discovery
+    /// document.
+    ///
+    /// See [`ProviderMetadata::discover_lenient`] for details.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_lenient_blocking<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+    ) -> Result<(Self, Vec<DiscoveryWarning>), DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_lenient(
+            issuer_url,
+            &BlockingHttpClient(http_client),
+        ))
+    }
+
+    ///
+    /// Synchronously fetches the provider metadata from the specified URL and the associated JSON
+    /// Web Key Set from the OpenID Connect Provider.
+    ///
+    /// See [`ProviderMetadata::discover_from_url`] for details.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn discover_from_url_blocking<C>(
+        issuer_url: IssuerUrl,
+        discovery_url: url::Url,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::discover_from_url(
+            issuer_url,
+            discovery_url,
+            &BlockingHttpClient(http_client),
+        ))
+    }
+
+    #[cfg(feature = "net")]
+    async fn discover_impl<C>(
+        issuer_url: IssuerUrl,
+        http_client: &C,
+        options: &DiscoveryOptions,
+        jwks: Option<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Result<(Self, Vec<DiscoveryWarning>), DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let discovery_url = match options.discovery_url {
+            Some(ref discovery_url) => discovery_url.clone(),
+            None => issuer_url
+                .join(CONFIG_URL_SUFFIX)
+                .map_err(DiscoveryError::UrlParse)?,
+        };
+        let mut discovery_request = discovery_request(discovery_url);
+        options.request_options.apply(&mut discovery_request);
+        let http_response = InstrumentedHttpClient::new(RequestKind::Discovery, http_client)
+            .call(discovery_request)
+            .await
+            .map_err(DiscoveryError::Request)?;
+        let mut warnings = Vec::new();
+        let provider_metadata = Self::discovery_response(
+            &issuer_url,
+            http_response,
+            if options.lenient {
+                Some(&mut warnings)
+            } else {
+                None
+            },
+        )
+        .await?;
+
+        // Reject insecure endpoints before fetching the JWK set, which would otherwise be
+        // retrieved over the insecure connection.
+        if let Some((field, url)) = provider_metadata.insecure_endpoint(&options.endpoint_policy) {
+            return Err(DiscoveryError::Validation(format!(
+                "insecure `{}` URI `{}`",
+                field, url
+            )));
+        }
+
+        let jwks = match jwks {
+            Some(jwks) => jwks,
+            None => {
+                JsonWebKeySet::fetch_with_options(
+                    provider_metadata.jwks_uri(),
+                    http_client,
+                    &options.request_options,
+                )
+                .await?
+            }
+        };
+        Ok((
+            Self {
                 jwks,
                 ..provider_metadata
+            },
+            warnings,
+        ))
+    }
+
+    #[cfg(feature = "net")]
+    async fn discovery_response<RE>(
+        issuer_url: &IssuerUrl,
+        discovery_response: Response,
+        warnings: Option<&mut Vec<DiscoveryWarning>>,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        RE: Fail,
+    {
+        let body = discovery_response_body(discovery_response).await?;
+
+        let provider_metadata = match warnings {
+            Some(warnings) => Self::parse_lenient(&body, warnings),
+            None => serde_json::from_slice::<Self>(&body),
+        }
+        .map_err(DiscoveryError::Parse)?;
+
+        if provider_metadata.issuer() != issuer_url {
+            Err(DiscoveryError::Validation(format!(
+                "unexpected issuer URI `{}` (expected `{}`)",
+                provider_metadata.issuer().url(),
+                issuer_url.url()
+            )))
+        } else {
+            Ok(provider_metadata)
+        }
+    }
+
+    #[cfg(feature = "net")]
+    fn parse_lenient(
+        body: &[u8],
+        warnings: &mut Vec<DiscoveryWarning>,
+    ) -> Result<Self, serde_json::Error> {
+        let mut fields = match serde_json::from_slice(body)? {
+            serde_json::Value::Object(fields) => fields,
+            other => return serde_json::from_value(other),
+        };
+
+        let standard_fields = fields
+            .keys()
+            .filter(|field| STANDARD_FIELDS.contains(&field.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        for field in standard_fields {
+            if fields.get(&field) == Some(&serde_json::Value::Null) {
+                fields.remove(&field);
+                warnings.push(DiscoveryWarning::NullField(field));
+                continue;
+            }
+            let converted_value = match fields.get(&field) {
+                Some(serde_json::Value::String(value)) if value.is_empty() => None,
+                Some(serde_json::Value::String(value))
+                    if field.ends_with("_supported")
+                        || field == "require_request_uri_registration" =>
+                {
+                    Some(match value.as_str() {
+                        "true" => serde_json::Value::Bool(true),
+                        "false" => serde_json::Value::Bool(false),
+                        _ => serde_json::Value::Array(
+                            value
+                                .split_whitespace()
+                                .map(|item| serde_json::Value::String(item.to_string()))
+                                .collect(),
+                        ),
+                    })
+                }
+                _ => continue,
+            };
+            if let Some(value) = converted_value {
+                warnings.push(DiscoveryWarning::ConvertedField(
+                    field.clone(),
+                    value.clone(),
+                ));
+                fields.insert(field, value);
+            } else {
+                fields.remove(&field);
+                warnings.push(DiscoveryWarning::EmptyField(field));
+            }
+        }
+
+        for (field, default) in &[
+            ("subject_types_supported", "public"),
+            ("id_token_signing_alg_values_supported", "RS256"),
+        ] {
+            if !fields.contains_key(*field) {
+                let value =
+                    serde_json::Value::Array(vec![serde_json::Value::String(default.to_string())]);
+                warnings.push(DiscoveryWarning::MissingField(
+                    field.to_string(),
+                    value.clone(),
+                ));
+                fields.insert(field.to_string(), value);
+            }
+        }
+
+        // Each optional field is validated individually so that an invalid field can be ignored
+        // without also ignoring the remaining fields. If the other fields are invalid on their
+        // own, the resulting error is returned below instead.
+        let (optional_fields, mut accepted_fields): (
+            serde_json::Map<String, serde_json::Value>,
+            serde_json::Map<String, serde_json::Value>,
+        ) = fields.into_iter().partition(|(field, _)| {
+            STANDARD_FIELDS.contains(&field.as_str()) && !REQUIRED_FIELDS.contains(&field.as_str())
+        });
+        let parse = |fields: &serde_json::Map<String, serde_json::Value>| {
+            serde_json::from_value::<Self>(serde_json::Value::Object(fields.clone()))
+        };
+        if parse(&accepted_fields).is_ok() {
+            for (field, value) in optional_fields {
+                let mut candidate_fields = accepted_fields.clone();
+                candidate_fields.insert(field.clone(), value);
+                match parse(&candidate_fields) {
+                    Ok(_) => accepted_fields = candidate_fields,
+                    Err(err) => {
+                        warnings.push(DiscoveryWarning::InvalidField(field, err.to_string()))
+                    }
+                }
+            }
+        } else {
+            accepted_fields.extend(optional_fields);
+        }
+        serde_json::from_value(serde_json::Value::Object(accepted_fields))
+    }
+
+    ///
+    /// Returns additional provider metadata fields.
+    ///
+    pub fn additional_metadata(&self) -> &A {
+        &self.additional_metadata
+    }
+    ///
+    /// Returns mutable additional provider metadata fields.
+    ///
+    pub fn additional_metadata_mut(&mut self) -> &mut A {
+        &mut self.additional_metadata
+    }
+    ///
+    /// Returns provider metadata fields that are neither modeled by this struct nor by the
+    /// additional metadata type (e.g., provider-specific extensions).
+    ///
+    pub fn unknown_fields(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.unknown_fields
+    }
+    ///
+    /// Returns mutable provider metadata fields that are neither modeled by this struct nor by
+    /// the additional metadata type.
+    ///
+    pub fn unknown_fields_mut(&mut self) -> &mut serde_json::Map<String, serde_json::Value> {
+        &mut self.unknown_fields
+    }
+
+    ///
+    /// Checks this provider metadata for violations of the
+    /// [OpenID Connect Discovery](https://openid.net/specs/openid-connect-discovery-1_0.html) spec
+    /// and for missing recommended fields.
+    ///
+    /// This is intended for vetting a provider before deploying a client against it. Violations
+    /// don't necessarily prevent this crate from interoperating with the provider.
+    ///
+    pub fn validate(&self) -> ComplianceReport {
+        let mut report = ComplianceReport::default();
+
+        let issuer_url = self.issuer.url();
+        if issuer_url.scheme() != "https" {
+            report.violation("issuer", "issuer must use the https scheme");
+        }
+        if issuer_url.query().is_some() || issuer_url.fragment().is_some() {
+            report.violation(
+                "issuer",
+                "issuer must not contain a query or fragment component",
+            );
+        }
+        for (field, url) in &[
+            (
+                "authorization_endpoint",
+                Some(self.authorization_endpoint.url()),
+            ),
+            (
+                "token_endpoint",
+                self.token_endpoint.as_ref().map(TokenUrl::url),
+            ),
+            (
+                "userinfo_endpoint",
+                self.userinfo_endpoint.as_ref().map(UserInfoUrl::url),
+            ),
+            ("jwks_uri", Some(self.jwks_uri.url())),
+        ] {
+            if url.map_or(false, |url| url.scheme() != "https") {
+                report.violation(*field, "endpoint must use the https scheme");
+            }
+        }
+
+        if self.response_types_supported.is_empty() {
+            report.violation(
+                "response_types_supported",
+                "no response types are supported",
+            );
+        }
+        let supports_code_flow = self.response_types_supported.iter().any(|response_types| {
+            response_types
+                .iter()
+                .any(|response_type| response_type.as_ref() == "code")
+        });
+        if supports_code_flow && self.token_endpoint.is_none() {
+            report.violation(
+                "token_endpoint",
+                "token endpoint is required unless only the implicit flow is supported",
+            );
+        }
+        if self.subject_types_supported.is_empty() {
+            report.violation("subject_types_supported", "no subject types are supported");
+        }
+        if !self
+            .id_token_signing_alg_values_supported
+            .contains(&JS::rsa_sha_256())
+        {
+            report.violation(
+                "id_token_signing_alg_values_supported",
+                "RS256 must be supported",
+            );
+        }
+        if self
+            .token_endpoint_auth_signing_alg_values_supported
+            .as_ref()
+            .map_or(false, |algs| {
+                algs.iter().any(|alg| alg.key_type().is_none())
             })
+        {
+            report.violation(
+                "token_endpoint_auth_signing_alg_values_supported",
+                "the none algorithm must not be supported",
+            );
+        }
+        match self.scopes_supported {
+            Some(ref scopes) if !scopes.iter().any(|scope| scope.as_str() == "openid") => {
+                report.violation("scopes_supported", "openid scope must be supported")
+            }
+            Some(_) => {}
+            None => report.recommendation("scopes_supported", "supported scopes should be listed"),
+        }
+
+        if self.userinfo_endpoint.is_none() {
+            report.recommendation("userinfo_endpoint", "user info endpoint should be provided");
+        }
+        if self.registration_endpoint.is_none() {
+            report.recommendation(
+                "registration_endpoint",
+                "dynamic client registration endpoint should be provided",
+            );
+        }
+        if self.claims_supported.is_none() {
+            report.recommendation("claims_supported", "supported claims should be listed");
+        }
+        let supports_pkce_s256 = match self.code_challenge_methods_supported {
+            Some(ref methods) => methods
+                .iter()
+                .any(|method| method.as_str() == PKCE_METHOD_S256),
+            None => false,
+        };
+        if !supports_pkce_s256 {
+            report.recommendation(
+                "code_challenge_methods_supported",
+                "PKCE support with the S256 code challenge method should be advertised",
+            );
+        }
+
+        report
+    }
+
+    #[cfg(feature = "net")]
+    fn insecure_endpoint(
+        &self,
+        endpoint_policy: &EndpointSecurityPolicy,
+    ) -> Option<(&'static str, &url::Url)> {
+        let endpoints = vec![
+            (
+                "authorization_endpoint",
+                Some(self.authorization_endpoint.url()),
+            ),
+            (
+                "token_endpoint",
+                self.token_endpoint.as_ref().map(TokenUrl::url),
+            ),
+            (
+                "userinfo_endpoint",
+                self.userinfo_endpoint.as_ref().map(UserInfoUrl::url),
+            ),
+            ("jwks_uri", Some(self.jwks_uri.url())),
+            (
+                "registration_endpoint",
+                self.registration_endpoint
+                    .as_ref()
+                    .map(RegistrationUrl::url),
+            ),
+            (
+                "device_authorization_endpoint",
+                self.device_authorization_endpoint
+                    .as_ref()
+                    .map(DeviceAuthorizationUrl::url),
+            ),
+            (
+                "introspection_endpoint",
+                self.introspection_endpoint
+                    .as_ref()
+                    .map(IntrospectionUrl::url),
+            ),
+            (
+                "revocation_endpoint",
+                self.revocation_endpoint.as_ref().map(RevocationUrl::url),
+            ),
+            (
+                "end_session_endpoint",
+                self.end_session_endpoint.as_ref().map(EndSessionUrl::url),
+            ),
+            (
+                "check_session_iframe",
+                self.check_session_iframe
+                    .as_ref()
+                    .map(CheckSessionIframeUrl::url),
+            ),
+        ];
+        endpoints
+            .into_iter()
+            .filter_map(|(field, url)| url.map(|url| (field, url)))
+            .find(|(_, url)| !endpoint_policy.is_allowed(url))
+    }
+}
+
+#[cfg(feature = "net")]
+pub(crate) fn discovery_request(discovery_url: url::Url) -> Request {
+    let mut req = Request::new(Method::Get, discovery_url);
+    req.insert_header(ACCEPT, MIME_TYPE_JSON);
+    req.set_body(Vec::new());
+    req
+}
+
+// Returns the body of a successful metadata response.
+#[cfg(feature = "net")]
+pub(crate) async fn discovery_response_body<RE>(
+    mut discovery_response: Response,
+) -> Result<Vec<u8>, DiscoveryError<RE>>
+where
+    RE: Fail,
+{
+    if discovery_response.status() != StatusCode::Ok {
+        return Err(DiscoveryError::Response(
+            UnexpectedResponse::from_response(&mut discovery_response).await,
+            format!("HTTP status code {}", discovery_response.status()),
+        ));
+    }
+
+    if let Err(err_msg) = check_content_type(&discovery_response, MIME_TYPE_JSON) {
+        return Err(DiscoveryError::Response(
+            UnexpectedResponse::from_response(&mut discovery_response).await,
+            err_msg,
+        ));
+    }
+
+    discovery_response
+        .body_bytes()
+        .await
+        .map_err(|_| DiscoveryError::Other("Body Error".into()))
+}
+
+///
+/// Error retrieving provider metadata.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum DiscoveryError<RE>
+where
+    RE: Fail,
+{
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+    ///
+    /// Failed to parse server response.
+    ///
+    #[fail(display = "Failed to parse server response")]
+    Parse(#[cause] serde_json::Error),
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[fail(display = "Request failed")]
+    Request(#[cause] RE),
+    ///
+    /// Server returned an invalid response. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    #[fail(display = "Server returned invalid response: {} ({})", _1, _0)]
+    Response(UnexpectedResponse, String),
+    ///
+    /// Failed to parse discovery URL from issuer URL.
+    ///
+    #[fail(display = "Failed to parse URL")]
+    UrlParse(#[cause] url::ParseError),
+    ///
+    /// Failed to validate provider metadata.
+    ///
+    #[fail(display = "Validation error: {}", _0)]
+    Validation(String),
+}
+impl<RE> ClassifiedError for DiscoveryError<RE>
+where
+    RE: Fail,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            DiscoveryError::Other(_) | DiscoveryError::UrlParse(_) => ErrorKind::Other,
+            DiscoveryError::Parse(_) => ErrorKind::InvalidResponse,
+            DiscoveryError::Request(_) => ErrorKind::Request,
+            #[cfg(feature = "net")]
+            DiscoveryError::Response(response, _) => ErrorKind::from_status(response.status()),
+            DiscoveryError::Validation(_) => ErrorKind::Verification,
+        }
+    }
+}
+#[cfg(feature = "net")]
+impl<RE> DiscoveryError<HttpClientError<RE>>
+where
+    RE: Fail,
+{
+    // Unwraps the errors of an HTTP client passed by value to a deprecated method (see
+    // `FnOnceHttpClient`).
+    pub(crate) fn into_by_value_error(self) -> DiscoveryError<RE> {
+        match self {
+            DiscoveryError::Other(message) => DiscoveryError::Other(message),
+            DiscoveryError::Parse(err) => DiscoveryError::Parse(err),
+            DiscoveryError::Request(HttpClientError::Request(err)) => DiscoveryError::Request(err),
+            DiscoveryError::Request(err) => DiscoveryError::Other(err.to_string()),
+            DiscoveryError::Response(response, message) => {
+                DiscoveryError::Response(response, message)
+            }
+            DiscoveryError::UrlParse(err) => DiscoveryError::UrlParse(err),
+            DiscoveryError::Validation(message) => DiscoveryError::Validation(message),
+        }
+    }
+}
+
+///
+/// Policy for the endpoint URLs listed in a Discovery document.
+///
+/// By default, discovery fails if any endpoint (e.g., the token, user info, or JWK set endpoint)
+/// doesn't use the `https` scheme, which prevents a malicious or misconfigured Discovery document
+/// from silently downgrading requests containing credentials or tokens to plain HTTP. Plain HTTP
+/// may be allowed for specific hosts used during development via
+/// [`EndpointSecurityPolicy::add_insecure_host`].
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EndpointSecurityPolicy {
+    allow_insecure: bool,
+    insecure_hosts: Vec<String>,
+}
+impl EndpointSecurityPolicy {
+    ///
+    /// Instantiates the default policy, which requires all endpoints to use HTTPS.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Allows endpoints on the specified host (e.g., `localhost`) to use plain HTTP. Hosts are
+    /// compared case-insensitively, and the port is ignored.
+    ///
+    pub fn add_insecure_host(mut self, host: &str) -> Self {
+        self.insecure_hosts.push(host.to_lowercase());
+        self
+    }
+
+    ///
+    /// Specifies whether endpoints on any host may use plain HTTP. This disables the policy
+    /// entirely and should only be used for testing.
+    ///
+    pub fn set_allow_insecure(mut self, allow_insecure: bool) -> Self {
+        self.allow_insecure = allow_insecure;
+        self
+    }
+
+    ///
+    /// Returns `true` if the policy allows the specified endpoint URL.
+    ///
+    pub fn is_allowed(&self, url: &url::Url) -> bool {
+        if self.allow_insecure || url.scheme() == "https" {
+            return true;
+        }
+        url.scheme() == "http"
+            && url.host_str().map_or(false, |host| {
+                self.insecure_hosts
+                    .iter()
+                    .any(|insecure_host| host.eq_ignore_ascii_case(insecure_host))
+            })
+    }
+}
+
+///
+/// Options for fetching and validating an OpenID Connect Discovery document (see
+/// [`ProviderMetadata::discover_with`]).
+///
+/// By default, the Discovery document is fetched from the standard
+/// `.well-known/openid-configuration` URL relative to the issuer, parsed strictly, and validated
+/// against the default [`EndpointSecurityPolicy`].
+///
+/// Requires "net" feature.
+///
+#[cfg(feature = "net")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiscoveryOptions {
+    discovery_url: Option<url::Url>,
+    lenient: bool,
+    request_options: RequestOptions,
+    endpoint_policy: EndpointSecurityPolicy,
+}
+#[cfg(feature = "net")]
+impl DiscoveryOptions {
+    ///
+    /// Instantiates the default discovery options.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Specifies the URL from which to fetch the provider metadata instead of the standard
+    /// Discovery URL.
+    ///
+    /// This supports providers that publish their metadata at a nonstandard location (e.g., Azure
+    /// AD B2C policy-specific URLs or the
+    /// [RFC 8414](https://tools.ietf.org/html/rfc8414#section-3) `oauth-authorization-server`
+    /// well-known URL). To discover the metadata using a nonstandard suffix, pass the result of
+    /// joining the suffix to the issuer URL (see [`IssuerUrl::join`]). The `issuer` in the
+    /// returned metadata must still match the issuer URL.
+    ///
+    pub fn set_discovery_url(mut self, discovery_url: Option<url::Url>) -> Self {
+        self.discovery_url = discovery_url;
+        self
+    }
+
+    ///
+    /// Specifies whether to tolerate common spec violations in the Discovery document.
+    ///
+    /// Some providers publish Discovery documents that are usable despite violating the spec. If
+    /// enabled, the following violations are tolerated instead of failing discovery, and a
+    /// [`DiscoveryWarning`] describing each violation encountered is returned:
+    ///  * Fields with empty string or `null` values are ignored.
+    ///  * A missing `subject_types_supported` or `id_token_signing_alg_values_supported` field is
+    ///    assumed to be `["public"]` or `["RS256"]`, respectively.
+    ///  * A `*_supported` field containing a string instead of an array (or boolean) is converted
+    ///    by splitting the string on whitespace (or parsing `true` or `false`). The
+    ///    `require_request_uri_registration` field is converted from `true` or `false` likewise.
+    ///  * Any other optional field with an invalid value is ignored.
+    ///
+    /// The issuer is still validated strictly, and fields defined by the additional metadata type
+    /// aren't modified.
+    ///
+    pub fn set_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    ///
+    /// Specifies the options to apply to each HTTP request (i.e., the Discovery document and
+    /// JSON Web Key Set requests).
+    ///
+    pub fn set_request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
+    ///
+    /// Specifies the policy to enforce on the endpoint URLs listed in the Discovery document.
+    ///
+    /// The default [`EndpointSecurityPolicy`] rejects endpoints that don't use HTTPS. A custom
+    /// policy may allow plain HTTP endpoints on specific hosts (e.g., a provider running locally
+    /// during development).
+    ///
+    pub fn set_endpoint_policy(mut self, endpoint_policy: EndpointSecurityPolicy) -> Self {
+        self.endpoint_policy = endpoint_policy;
+        self
+    }
+}
+
+///
+/// Severity of a [`ComplianceIssue`].
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ComplianceSeverity {
+    ///
+    /// The provider metadata violates a requirement of the spec.
+    ///
+    Violation,
+    ///
+    /// The provider metadata omits a recommended field or capability.
+    ///
+    Recommendation,
+}
+
+///
+/// Issue found by [`ProviderMetadata::validate`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplianceIssue {
+    description: &'static str,
+    field: &'static str,
+    severity: ComplianceSeverity,
+}
+impl ComplianceIssue {
+    ///
+    /// Returns the severity of this issue.
+    ///
+    pub fn severity(&self) -> ComplianceSeverity {
+        self.severity
     }
 
-    fn discovery_request(discovery_url: url::Url) -> Request {
-        let mut req = Request::new(Method::Get, discovery_url);
-        req.insert_header(ACCEPT, MIME_TYPE_JSON);
-        req.set_body(Vec::new());
-        req
+    ///
+    /// Returns the name of the provider metadata field to which this issue applies.
+    ///
+    pub fn field(&self) -> &str {
+        self.field
     }
 
-    async fn discovery_response<RE>(
-        issuer_url: &IssuerUrl,
-        mut discovery_response: Response,
-    ) -> Result<Self, DiscoveryError<RE>>
-    where
-        RE: Fail,
-    {
-        if discovery_response.status() != StatusCode::Ok {
-            return Err(DiscoveryError::Response(
-                discovery_response.status(),
-                discovery_response.take_body(),
-                format!("HTTP status code {}", discovery_response.status()),
-            ));
-        }
-
-        check_content_type(&discovery_response, MIME_TYPE_JSON).map_err(|err_msg| {
-            DiscoveryError::Response(
-                discovery_response.status(),
-                discovery_response.take_body(),
-                err_msg,
-            )
-        })?;
-
-        let body = match discovery_response.body_bytes().await {
-            Ok(body) => body,
-            Err(_) => return Err(DiscoveryError::Other("Body Error".into())),
-        };
+    ///
+    /// Returns a human-readable description of this issue.
+    ///
+    pub fn description(&self) -> &str {
+        self.description
+    }
+}
+impl Display for ComplianceIssue {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        write!(f, "{}: {}", self.field, self.description)
+    }
+}
 
-        let provider_metadata =
-            serde_json::from_slice::<Self>(&body).map_err(DiscoveryError::Parse)?;
+///
+/// Report returned by [`ProviderMetadata::validate`].
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ComplianceReport {
+    issues: Vec<ComplianceIssue>,
+}
+impl ComplianceReport {
+    ///
+    /// Returns all issues found, in the order they were checked.
+    ///
+    pub fn issues(&self) -> &[ComplianceIssue] {
+        &self.issues
+    }
 
-        if provider_metadata.issuer() != issuer_url {
-            Err(DiscoveryError::Validation(format!(
-                "unexpected issuer URI `{}` (expected `{}`)",
-                provider_metadata.issuer().url(),
-                issuer_url.url()
-            )))
-        } else {
-            Ok(provider_metadata)
-        }
+    ///
+    /// Returns the issues that violate a requirement of the spec.
+    ///
+    pub fn violations(&self) -> impl Iterator<Item = &ComplianceIssue> {
+        self.issues_with_severity(ComplianceSeverity::Violation)
     }
 
     ///
-    /// Returns additional provider metadata fields.
+    /// Returns the issues that omit a recommended field or capability.
     ///
-    pub fn additional_metadata(&self) -> &A {
-        &self.additional_metadata
+    pub fn recommendations(&self) -> impl Iterator<Item = &ComplianceIssue> {
+        self.issues_with_severity(ComplianceSeverity::Recommendation)
     }
+
     ///
-    /// Returns mutable additional provider metadata fields.
+    /// Returns `true` if no spec violations were found. Missing recommended fields don't affect
+    /// compliance.
     ///
-    pub fn additional_metadata_mut(&mut self) -> &mut A {
-        &mut self.additional_metadata
+    pub fn is_compliant(&self) -> bool {
+        self.violations().next().is_none()
+    }
+
+    fn issues_with_severity(
+        &self,
+        severity: ComplianceSeverity,
+    ) -> impl Iterator<Item = &ComplianceIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+
+    fn violation(&mut self, field: &'static str, description: &'static str) {
+        self.issues.push(ComplianceIssue {
+            description,
+            field,
+            severity: ComplianceSeverity::Violation,
+        });
+    }
+
+    fn recommendation(&mut self, field: &'static str, description: &'static str) {
+        self.issues.push(ComplianceIssue {
+            description,
+            field,
+            severity: ComplianceSeverity::Recommendation,
+        });
     }
 }
 
 ///
-/// Error retrieving provider metadata.
+/// Spec violation in a Discovery document tolerated by [`ProviderMetadata::discover_lenient`].
 ///
-#[derive(Debug, Fail)]
+#[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
-pub enum DiscoveryError<RE>
-where
-    RE: Fail,
-{
+pub enum DiscoveryWarning {
     ///
-    /// An unexpected error occurred.
+    /// The field contained an empty string and was ignored.
     ///
-    #[fail(display = "Other error: {}", _0)]
-    Other(String),
+    EmptyField(String),
     ///
-    /// Failed to parse server response.
+    /// The field was `null` and was ignored.
     ///
-    #[fail(display = "Failed to parse server response")]
-    Parse(#[cause] serde_json::Error),
+    NullField(String),
     ///
-    /// An error occurred while sending the request or receiving the response (e.g., network
-    /// connectivity failed).
+    /// The required field was missing, and the specified default value was assumed.
     ///
-    #[fail(display = "Request failed")]
-    Request(#[cause] RE),
+    MissingField(String, serde_json::Value),
     ///
-    /// Server returned an invalid response.
+    /// The field contained the wrong JSON type and was converted to the specified value.
     ///
-    #[fail(display = "Server returned invalid response: {}", _2)]
-    Response(StatusCode, Body, String),
+    ConvertedField(String, serde_json::Value),
     ///
-    /// Failed to parse discovery URL from issuer URL.
+    /// The field contained an invalid value and was ignored. The second element describes the
+    /// parse error.
     ///
-    #[fail(display = "Failed to parse URL")]
-    UrlParse(#[cause] url::ParseError),
+    InvalidField(String, String),
+}
+impl DiscoveryWarning {
     ///
-    /// Failed to validate provider metadata.
+    /// Returns the name of the field to which this warning applies.
     ///
-    #[fail(display = "Validation error: {}", _0)]
-    Validation(String),
+    pub fn field(&self) -> &str {
+        match self {
+            DiscoveryWarning::EmptyField(field)
+            | DiscoveryWarning::NullField(field)
+            | DiscoveryWarning::MissingField(field, _)
+            | DiscoveryWarning::ConvertedField(field, _)
+            | DiscoveryWarning::InvalidField(field, _) => field,
+        }
+    }
+}
+impl Display for DiscoveryWarning {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        match self {
+            DiscoveryWarning::EmptyField(field) => {
+                write!(f, "ignored empty field `{}`", field)
+            }
+            DiscoveryWarning::NullField(field) => write!(f, "ignored null field `{}`", field),
+            DiscoveryWarning::MissingField(field, value) => {
+                write!(f, "missing required field `{}`; assumed `{}`", field, value)
+            }
+            DiscoveryWarning::ConvertedField(field, value) => {
+                write!(f, "converted field `{}` to `{}`", field, value)
+            }
+            DiscoveryWarning::InvalidField(field, err) => {
+                write!(f, "ignored invalid field `{}`: {}", field, err)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use oauth2::{AuthUrl, Scope, TokenUrl};
+    #[cfg(feature = "net")]
+    use std::io;
+
+    use oauth2::{AuthUrl, PkceCodeChallengeMethod, Scope, TokenUrl};
 
+    #[cfg(feature = "net")]
+    use crate::core::CoreJsonWebKeySet;
     use crate::core::{
         CoreAuthDisplay, CoreClaimName, CoreClaimType, CoreClientAuthMethod, CoreGrantType,
         CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
         CoreProviderMetadata, CoreResponseMode, CoreResponseType, CoreSubjectIdentifierType,
     };
+    #[cfg(feature = "net")]
+    use crate::http_types::{Request, Response, StatusCode};
 
     use super::{
-        AuthenticationContextClass, IssuerUrl, JsonWebKeySetUrl, LanguageTag, OpPolicyUrl,
-        OpTosUrl, RegistrationUrl, ResponseTypes, ServiceDocUrl, UserInfoUrl,
+        AuthenticationContextClass, CheckSessionIframeUrl, DeviceAuthorizationUrl,
+        EmptyAdditionalProviderMetadata, EndSessionUrl, EndpointSecurityPolicy, IntrospectionUrl,
+        IssuerUrl, JsonWebKeySetUrl, LanguageTag, OpPolicyUrl, OpTosUrl, RegistrationUrl,
+        ResponseTypes, RevocationUrl, ServiceDocUrl, UserInfoUrl,
     };
+    #[cfg(feature = "net")]
+    use super::{DiscoveryError, DiscoveryOptions, DiscoveryWarning, RequestOptions};
+    #[cfg(feature = "net")]
+    use crate::jwt::tests::TEST_RSA_PUB_KEY;
 
     #[test]
     fn test_discovery_deserialization() {
@@ -689,7 +1892,7 @@ mod tests {
             CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap192,
             CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap256,
         ];
-        let new_provider_metadata = CoreProviderMetadata::new(
+        let mut new_provider_metadata = CoreProviderMetadata::new(
             IssuerUrl::new(
                 "https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code"
                     .to_string(),
@@ -845,13 +2048,27 @@ mod tests {
         ]))
         .set_acr_values_supported(Some(vec![AuthenticationContextClass::new(
             "PASSWORD".to_string(),
-        )]));
+        )]))
+        .set_end_session_endpoint(Some(
+            EndSessionUrl::new(
+                "https://rp.certification.openid.net:8080/openidconnect-rs/\
+                 rp-response_type-code/end_session"
+                    .to_string(),
+            )
+            .unwrap(),
+        ));
+
+        // Fields not modeled by `ProviderMetadata` are preserved.
+        new_provider_metadata.unknown_fields_mut().insert(
+            "version".to_string(),
+            serde_json::Value::String("3.0".to_string()),
+        );
 
         let provider_metadata: CoreProviderMetadata = serde_json::from_str(&json_response).unwrap();
         assert_eq!(provider_metadata, new_provider_metadata);
 
         let serialized = serde_json::to_string(&provider_metadata).unwrap();
-        assert_eq!(serialized, format!("{{{}}}", json_response_standard));
+        assert_eq!(serialized, json_response);
 
         assert_eq!(
             IssuerUrl::new(
@@ -1174,10 +2391,24 @@ mod tests {
         );
         assert_eq!(None, provider_metadata.op_policy_uri());
         assert_eq!(None, provider_metadata.op_tos_uri());
+        assert_eq!(None, provider_metadata.code_challenge_methods_supported());
+        assert_eq!(
+            Some(
+                &EndSessionUrl::new(
+                    "https://rp.certification.openid.net:8080/openidconnect-rs/\
+                     rp-response_type-code/end_session"
+                        .to_string()
+                )
+                .unwrap()
+            ),
+            provider_metadata.end_session_endpoint()
+        );
+        assert_eq!(None, provider_metadata.check_session_iframe());
+        assert_eq!(None, provider_metadata.frontchannel_logout_supported());
+        assert_eq!(None, provider_metadata.backchannel_logout_supported());
 
-        // Note: the following fields provided by the response above are not part of the OpenID
-        // Connect Discovery 1.0 spec:
-        // - end_session_endpoint
+        // Note: the following field provided by the response above is not part of the OpenID
+        // Connect Discovery 1.0 spec or any of the supported extensions:
         // - version
 
         let serialized_json = serde_json::to_string(&provider_metadata).unwrap();
@@ -1232,7 +2463,26 @@ mod tests {
            \"yue-HK\"
         ],
         \"op_policy_uri\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/op_policy\",
-        \"op_tos_uri\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/op_tos\"
+        \"op_tos_uri\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/op_tos\",
+        \"code_challenge_methods_supported\" : [
+           \"S256\",
+           \"plain\"
+        ],
+        \"device_authorization_endpoint\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/device_authorization\",
+        \"introspection_endpoint\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/introspect\",
+        \"revocation_endpoint\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/revoke\",
+        \"check_session_iframe\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/check_session\",
+        \"frontchannel_logout_supported\" : true,
+        \"frontchannel_logout_session_supported\" : true,
+        \"backchannel_logout_supported\" : true,
+        \"backchannel_logout_session_supported\" : false,
+        \"authorization_response_iss_parameter_supported\" : true,
+        \"prompt_values_supported\" : [
+           \"none\",
+           \"login\",
+           \"consent\",
+           \"create\"
+        ]
     }";
 
         let provider_metadata: CoreProviderMetadata = serde_json::from_str(json_response).unwrap();
@@ -1411,6 +2661,85 @@ mod tests {
             ),
             provider_metadata.op_tos_uri()
         );
+        assert_eq!(
+            Some(&vec![
+                PkceCodeChallengeMethod::new("S256".to_string()),
+                PkceCodeChallengeMethod::new("plain".to_string()),
+            ]),
+            provider_metadata.code_challenge_methods_supported()
+        );
+        assert_eq!(
+            Some(
+                &DeviceAuthorizationUrl::new(
+                    "https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code\
+                     /device_authorization"
+                        .to_string()
+                )
+                .unwrap()
+            ),
+            provider_metadata.device_authorization_endpoint()
+        );
+        assert_eq!(
+            Some(
+                &IntrospectionUrl::new(
+                    "https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code\
+                     /introspect"
+                        .to_string()
+                )
+                .unwrap()
+            ),
+            provider_metadata.introspection_endpoint()
+        );
+        assert_eq!(
+            Some(
+                &RevocationUrl::new(
+                    "https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code\
+                     /revoke"
+                        .to_string()
+                )
+                .unwrap()
+            ),
+            provider_metadata.revocation_endpoint()
+        );
+        assert_eq!(None, provider_metadata.end_session_endpoint());
+        assert_eq!(
+            Some(
+                &CheckSessionIframeUrl::new(
+                    "https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code\
+                     /check_session"
+                        .to_string()
+                )
+                .unwrap()
+            ),
+            provider_metadata.check_session_iframe()
+        );
+        assert_eq!(
+            Some(true),
+            provider_metadata.frontchannel_logout_supported()
+        );
+        assert_eq!(
+            Some(true),
+            provider_metadata.frontchannel_logout_session_supported()
+        );
+        assert_eq!(Some(true), provider_metadata.backchannel_logout_supported());
+        assert_eq!(
+            Some(false),
+            provider_metadata.backchannel_logout_session_supported()
+        );
+        assert_eq!(
+            Some(true),
+            provider_metadata.authorization_response_iss_parameter_supported()
+        );
+        assert_eq!(
+            Some(&vec![
+                "none".to_string(),
+                "login".to_string(),
+                "consent".to_string(),
+                "create".to_string(),
+            ]),
+            provider_metadata.prompt_values_supported()
+        );
+        assert!(provider_metadata.unknown_fields().is_empty());
 
         let serialized_json = serde_json::to_string(&provider_metadata).unwrap();
 
@@ -1418,4 +2747,417 @@ mod tests {
             serde_json::from_str(&serialized_json).unwrap();
         assert_eq!(provider_metadata, redeserialized_metadata);
     }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_discover_lenient() {
+        let http_client = |request: Request| {
+            let body = if request.url().path() == "/jwks" {
+                "{\"keys\":[]}"
+            } else {
+                "{
+                    \"issuer\": \"https://example.com/\",
+                    \"authorization_endpoint\": \"https://example.com/authorize\",
+                    \"token_endpoint\": \"https://example.com/token\",
+                    \"jwks_uri\": \"https://example.com/jwks\",
+                    \"registration_endpoint\": \"\",
+                    \"response_types_supported\": [\"code\"],
+                    \"id_token_signing_alg_values_supported\": \"RS256 ES256\",
+                    \"scopes_supported\": \"openid email\",
+                    \"claims_parameter_supported\": \"true\",
+                    \"require_request_uri_registration\": \"false\",
+                    \"service_documentation\": null,
+                    \"grant_types_supported\": [1, 2],
+                    \"op_tos_uri\": 42,
+                    \"custom_field\": \"\"
+                }"
+            };
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(body);
+            Ok::<_, io::Error>(response)
+        };
+        let issuer_url = IssuerUrl::new("https://example.com/".to_string()).unwrap();
+
+        match CoreProviderMetadata::discover_blocking(issuer_url.clone(), &http_client) {
+            Err(DiscoveryError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let (provider_metadata, warnings) =
+            CoreProviderMetadata::discover_lenient_blocking(issuer_url, &http_client).unwrap();
+        assert_eq!(
+            provider_metadata.token_endpoint(),
+            Some(&TokenUrl::new("https://example.com/token".to_string()).unwrap())
+        );
+        assert_eq!(None, provider_metadata.registration_endpoint());
+        assert_eq!(
+            vec![CoreSubjectIdentifierType::Public],
+            *provider_metadata.subject_types_supported()
+        );
+        assert_eq!(
+            vec![
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+            ],
+            *provider_metadata.id_token_signing_alg_values_supported()
+        );
+        assert_eq!(
+            Some(&vec![
+                Scope::new("openid".to_string()),
+                Scope::new("email".to_string()),
+            ]),
+            provider_metadata.scopes_supported()
+        );
+        assert_eq!(Some(true), provider_metadata.claims_parameter_supported());
+        assert_eq!(None, provider_metadata.grant_types_supported());
+        assert_eq!(None, provider_metadata.op_tos_uri());
+        // Fields that aren't modeled by `ProviderMetadata` are preserved as-is.
+        assert_eq!(
+            Some(&serde_json::Value::String(String::new())),
+            provider_metadata.unknown_fields().get("custom_field")
+        );
+
+        assert_eq!(
+            Some(false),
+            provider_metadata.require_request_uri_registration()
+        );
+        assert_eq!(None, provider_metadata.service_documentation());
+
+        assert_eq!(warnings.len(), 9, "{:?}", warnings);
+        for expected_warning in &[
+            DiscoveryWarning::EmptyField("registration_endpoint".to_string()),
+            DiscoveryWarning::MissingField(
+                "subject_types_supported".to_string(),
+                serde_json::json!(["public"]),
+            ),
+            DiscoveryWarning::ConvertedField(
+                "id_token_signing_alg_values_supported".to_string(),
+                serde_json::json!(["RS256", "ES256"]),
+            ),
+            DiscoveryWarning::ConvertedField(
+                "scopes_supported".to_string(),
+                serde_json::json!(["openid", "email"]),
+            ),
+            DiscoveryWarning::ConvertedField(
+                "claims_parameter_supported".to_string(),
+                serde_json::json!(true),
+            ),
+            DiscoveryWarning::ConvertedField(
+                "require_request_uri_registration".to_string(),
+                serde_json::json!(false),
+            ),
+            DiscoveryWarning::NullField("service_documentation".to_string()),
+        ] {
+            assert!(warnings.contains(expected_warning), "{:?}", warnings);
+        }
+        for field in &["grant_types_supported", "op_tos_uri"] {
+            assert!(warnings.iter().any(|warning| match warning {
+                DiscoveryWarning::InvalidField(invalid_field, _) => invalid_field == field,
+                _ => false,
+            }));
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let new_provider_metadata = |issuer: &str| {
+            CoreProviderMetadata::new(
+                IssuerUrl::new(issuer.to_string()).unwrap(),
+                AuthUrl::new(format!("{}/authorize", issuer)).unwrap(),
+                JsonWebKeySetUrl::new(format!("{}/jwks", issuer)).unwrap(),
+                vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+                vec![CoreSubjectIdentifierType::Public],
+                vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+                EmptyAdditionalProviderMetadata {},
+            )
+        };
+
+        let provider_metadata = new_provider_metadata("https://example.com")
+            .set_token_endpoint(Some(
+                TokenUrl::new("https://example.com/token".to_string()).unwrap(),
+            ))
+            .set_userinfo_endpoint(Some(
+                UserInfoUrl::new("https://example.com/userinfo".to_string()).unwrap(),
+            ))
+            .set_registration_endpoint(Some(
+                RegistrationUrl::new("https://example.com/register".to_string()).unwrap(),
+            ))
+            .set_scopes_supported(Some(vec![Scope::new("openid".to_string())]))
+            .set_claims_supported(Some(vec![CoreClaimName::new("sub".to_string())]))
+            .set_code_challenge_methods_supported(Some(vec![PkceCodeChallengeMethod::new(
+                "S256".to_string(),
+            )]));
+        let report = provider_metadata.validate();
+        assert!(report.is_compliant());
+        assert!(report.issues().is_empty(), "{:?}", report);
+
+        let report = new_provider_metadata("http://example.com")
+            .set_id_token_signing_alg_values_supported(vec![
+                CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+            ])
+            .set_token_endpoint_auth_signing_alg_values_supported(Some(vec![
+                CoreJwsSigningAlgorithm::None,
+            ]))
+            .set_scopes_supported(Some(vec![Scope::new("email".to_string())]))
+            .validate();
+        assert!(!report.is_compliant());
+        assert_eq!(
+            report
+                .violations()
+                .map(|issue| issue.field())
+                .collect::<Vec<_>>(),
+            vec![
+                "issuer",
+                "authorization_endpoint",
+                "jwks_uri",
+                "token_endpoint",
+                "id_token_signing_alg_values_supported",
+                "token_endpoint_auth_signing_alg_values_supported",
+                "scopes_supported",
+            ]
+        );
+        assert_eq!(
+            report
+                .recommendations()
+                .map(|issue| issue.field())
+                .collect::<Vec<_>>(),
+            vec![
+                "userinfo_endpoint",
+                "registration_endpoint",
+                "claims_supported",
+                "code_challenge_methods_supported",
+            ]
+        );
+        assert_eq!(
+            report.violations().next().unwrap().to_string(),
+            "issuer: issuer must use the https scheme"
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_discover_with_jwks() {
+        let http_client = |request: Request| {
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            match request.url().path() {
+                "/.well-known/openid-configuration" => response.set_body(
+                    "{
+                        \"issuer\": \"https://example.com\",
+                        \"authorization_endpoint\": \"https://example.com/authorize\",
+                        \"jwks_uri\": \"https://example.com/jwks\",
+                        \"response_types_supported\": [\"code\"],
+                        \"subject_types_supported\": [\"public\"],
+                        \"id_token_signing_alg_values_supported\": [\"RS256\"]
+                    }",
+                ),
+                "/jwks" => panic!("pinned key set should not be fetched"),
+                _ => response = Response::new(StatusCode::NotFound),
+            }
+            Ok::<_, io::Error>(response)
+        };
+
+        let jwks: CoreJsonWebKeySet =
+            serde_json::from_str(&format!("{{\"keys\":[{}]}}", TEST_RSA_PUB_KEY)).unwrap();
+        let provider_metadata = CoreProviderMetadata::discover_with_jwks_blocking(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            jwks.clone(),
+            &http_client,
+        )
+        .unwrap();
+        assert_eq!(1, provider_metadata.jwks().keys().len());
+        assert_eq!(&jwks, provider_metadata.jwks());
+    }
+
+    #[test]
+    fn test_endpoint_security_policy() {
+        let url = |url: &str| url::Url::parse(url).unwrap();
+
+        let policy = EndpointSecurityPolicy::new();
+        assert!(policy.is_allowed(&url("https://example.com/token")));
+        assert!(!policy.is_allowed(&url("http://example.com/token")));
+        assert!(!policy.is_allowed(&url("http://localhost:8080/token")));
+
+        let policy = policy.add_insecure_host("LocalHost");
+        assert!(policy.is_allowed(&url("http://localhost:8080/token")));
+        assert!(!policy.is_allowed(&url("http://example.com/token")));
+        assert!(!policy.is_allowed(&url("ftp://localhost/token")));
+
+        let policy = EndpointSecurityPolicy::new().set_allow_insecure(true);
+        assert!(policy.is_allowed(&url("http://example.com/token")));
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_discover_insecure_endpoint() {
+        let http_client = |request: Request| {
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            match request.url().as_str() {
+                "https://example.com/.well-known/openid-configuration" => response.set_body(
+                    "{
+                        \"issuer\": \"https://example.com\",
+                        \"authorization_endpoint\": \"https://example.com/authorize\",
+                        \"token_endpoint\": \"http://localhost:8080/token\",
+                        \"jwks_uri\": \"https://example.com/jwks\",
+                        \"response_types_supported\": [\"code\"],
+                        \"subject_types_supported\": [\"public\"],
+                        \"id_token_signing_alg_values_supported\": [\"RS256\"]
+                    }",
+                ),
+                "https://example.com/jwks" => response.set_body("{\"keys\":[]}"),
+                _ => response = Response::new(StatusCode::NotFound),
+            }
+            Ok::<_, io::Error>(response)
+        };
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+
+        match CoreProviderMetadata::discover_blocking(issuer_url.clone(), &http_client) {
+            Err(DiscoveryError::Validation(msg)) => assert_eq!(
+                "insecure `token_endpoint` URI `http://localhost:8080/token`",
+                msg
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let provider_metadata = CoreProviderMetadata::discover_with_endpoint_policy_blocking(
+            issuer_url,
+            &http_client,
+            &RequestOptions::default(),
+            &EndpointSecurityPolicy::new().add_insecure_host("localhost"),
+        )
+        .unwrap();
+        assert_eq!(
+            "http://localhost:8080/token",
+            provider_metadata.token_endpoint().unwrap().url().as_str()
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_discover_from_url() {
+        let http_client = |request: Request| {
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            match request.url().as_str() {
+                "https://example.com/tenant/.well-known/oauth-authorization-server?p=policy" => {
+                    response.set_body(
+                        "{
+                            \"issuer\": \"https://example.com/tenant\",
+                            \"authorization_endpoint\": \"https://example.com/authorize\",
+                            \"jwks_uri\": \"https://example.com/jwks\",
+                            \"response_types_supported\": [\"code\"],
+                            \"subject_types_supported\": [\"public\"],
+                            \"id_token_signing_alg_values_supported\": [\"RS256\"]
+                        }",
+                    );
+                }
+                "https://example.com/jwks" => response.set_body("{\"keys\":[]}"),
+                _ => response = Response::new(StatusCode::NotFound),
+            }
+            Ok::<_, io::Error>(response)
+        };
+        let discovery_url = url::Url::parse(
+            "https://example.com/tenant/.well-known/oauth-authorization-server?p=policy",
+        )
+        .unwrap();
+
+        let provider_metadata = CoreProviderMetadata::discover_from_url_blocking(
+            IssuerUrl::new("https://example.com/tenant".to_string()).unwrap(),
+            discovery_url.clone(),
+            &http_client,
+        )
+        .unwrap();
+        assert_eq!(
+            provider_metadata.issuer(),
+            &IssuerUrl::new("https://example.com/tenant".to_string()).unwrap()
+        );
+
+        // The issuer is still validated against the metadata.
+        match CoreProviderMetadata::discover_from_url_blocking(
+            IssuerUrl::new("https://example.com/other".to_string()).unwrap(),
+            discovery_url,
+            &http_client,
+        ) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_discover_with() {
+        let http_client = |request: Request| {
+            // The request options apply to both the Discovery document and JWK set requests.
+            assert_eq!("example", request.header("X-Tenant").unwrap().as_str());
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            match request.url().as_str() {
+                "https://example.com/tenant/metadata" => response.set_body(
+                    "{
+                        \"issuer\": \"https://example.com/tenant\",
+                        \"authorization_endpoint\": \"https://example.com/authorize\",
+                        \"token_endpoint\": \"http://localhost:8080/token\",
+                        \"registration_endpoint\": \"\",
+                        \"jwks_uri\": \"https://example.com/jwks\",
+                        \"response_types_supported\": [\"code\"],
+                        \"subject_types_supported\": [\"public\"],
+                        \"id_token_signing_alg_values_supported\": [\"RS256\"]
+                    }",
+                ),
+                "https://example.com/jwks" => response.set_body("{\"keys\":[]}"),
+                _ => response = Response::new(StatusCode::NotFound),
+            }
+            Ok::<_, io::Error>(response)
+        };
+        let issuer_url = IssuerUrl::new("https://example.com/tenant".to_string()).unwrap();
+        let options = DiscoveryOptions::new()
+            .set_discovery_url(Some(
+                url::Url::parse("https://example.com/tenant/metadata").unwrap(),
+            ))
+            .set_request_options(RequestOptions::new().add_header("X-Tenant", "example"));
+
+        // The default policy and strict parsing are still enforced.
+        match CoreProviderMetadata::discover_with_blocking(
+            issuer_url.clone(),
+            &http_client,
+            &options.clone().set_lenient(true),
+        ) {
+            Err(DiscoveryError::Validation(msg)) => assert_eq!(
+                "insecure `token_endpoint` URI `http://localhost:8080/token`",
+                msg
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let options = options
+            .set_endpoint_policy(EndpointSecurityPolicy::new().add_insecure_host("localhost"));
+        match CoreProviderMetadata::discover_with_blocking(
+            issuer_url.clone(),
+            &http_client,
+            &options,
+        ) {
+            Err(DiscoveryError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let (provider_metadata, warnings) = CoreProviderMetadata::discover_with_blocking(
+            issuer_url.clone(),
+            &http_client,
+            &options.set_lenient(true),
+        )
+        .unwrap();
+        assert_eq!(provider_metadata.issuer(), &issuer_url);
+        assert_eq!(
+            "http://localhost:8080/token",
+            provider_metadata.token_endpoint().unwrap().url().as_str()
+        );
+        assert_eq!(None, provider_metadata.registration_endpoint());
+        assert_eq!(
+            warnings,
+            vec![DiscoveryWarning::EmptyField(
+                "registration_endpoint".to_string()
+            )]
+        );
+    }
 }
@@ -0,0 +1,338 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+
+use oauth2::AccessToken;
+
+use crate::core::{
+    CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+};
+use crate::{
+    AccessTokenClaims, AccessTokenVerifier, AdditionalClaims, ClaimsVerificationError, GenderClaim,
+    IdToken, IdTokenClaims, IdTokenVerifier, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, JwtAccessToken, Nonce, NonceVerifier,
+};
+
+///
+/// Type-erased [`IdTokenVerifier`].
+///
+/// Unlike [`IdTokenVerifier`], this type is generic only over the claims it returns, which makes
+/// it straightforward to store in a struct or to select at runtime (e.g., one verifier per tenant).
+/// Since the JOSE-specific type parameters are erased, ID tokens are passed to
+/// [`DynIdTokenVerifier::verify`] in their serialized (compact) form. Like [`IdTokenVerifier`], it
+/// is `Send` and `Sync` and may be shared by concurrent requests.
+///
+/// Verifiers that use the [`core`](crate::core) types may be converted using `From`.
+///
+#[derive(Clone)]
+pub struct DynIdTokenVerifier<'a, AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    verify_fn: Arc<
+        dyn Fn(&str) -> Result<IdTokenClaims<AC, GC>, ClaimsVerificationError> + Send + Sync + 'a,
+    >,
+}
+impl<'a, AC, GC> DynIdTokenVerifier<'a, AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Erases the type parameters of the specified ID token verifier.
+    ///
+    /// The JWE content encryption algorithm type `JE` can't be inferred from the verifier and must
+    /// be specified explicitly (e.g., `DynIdTokenVerifier::new::<MyJweAlgorithm, _, _, _, _>`).
+    ///
+    pub fn new<JE, JS, JT, JU, K>(verifier: IdTokenVerifier<'a, JS, JT, JU, K>) -> Self
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        IdTokenVerifier<'a, JS, JT, JU, K>: Send + Sync,
+    {
+        Self {
+            verify_fn: Arc::new(move |id_token: &str| {
+                id_token
+                    .parse::<IdToken<AC, GC, JE, JS, JT>>()
+                    .map_err(|err| {
+                        ClaimsVerificationError::Other(format!("failed to parse ID token: {}", err))
+                    })?
+                    .into_claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            }),
+        }
+    }
+
+    ///
+    /// Verifies the specified serialized ID token and returns its claims.
+    ///
+    /// The nonce is verified after all of the other claims have been verified.
+    ///
+    pub fn verify<N>(
+        &self,
+        id_token: &str,
+        nonce_verifier: N,
+    ) -> Result<IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        N: NonceVerifier,
+    {
+        let claims = (*self.verify_fn)(id_token)?;
+        nonce_verifier
+            .verify(claims.nonce())
+            .map_err(ClaimsVerificationError::InvalidNonce)?;
+        Ok(claims)
+    }
+}
+impl<'a, AC, GC>
+    From<
+        IdTokenVerifier<
+            'a,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+            CoreJsonWebKeyUse,
+            CoreJsonWebKey,
+        >,
+    > for DynIdTokenVerifier<'a, AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    fn from(
+        verifier: IdTokenVerifier<
+            'a,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+            CoreJsonWebKeyUse,
+            CoreJsonWebKey,
+        >,
+    ) -> Self {
+        Self::new::<CoreJweContentEncryptionAlgorithm, _, _, _, _>(verifier)
+    }
+}
+impl<'a, AC, GC> Debug for DynIdTokenVerifier<'a, AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("DynIdTokenVerifier").finish()
+    }
+}
+
+///
+/// Type-erased [`AccessTokenVerifier`].
+///
+/// Unlike [`AccessTokenVerifier`], this type is generic only over the additional claims it
+/// returns, which makes it straightforward to store in a struct or to select at runtime. Like
+/// [`AccessTokenVerifier`], it is `Send` and `Sync` and may be shared by concurrent requests.
+///
+#[derive(Clone)]
+pub struct DynAccessTokenVerifier<'a, AC>
+where
+    AC: AdditionalClaims,
+{
+    verify_fn: Arc<
+        dyn Fn(&AccessToken) -> Result<AccessTokenClaims<AC>, ClaimsVerificationError>
+            + Send
+            + Sync
+            + 'a,
+    >,
+}
+impl<'a, AC> DynAccessTokenVerifier<'a, AC>
+where
+    AC: AdditionalClaims,
+{
+    ///
+    /// Erases the type parameters of the specified access token verifier.
+    ///
+    pub fn new<JE, JS, JT, JU, K>(verifier: AccessTokenVerifier<'a, JE, JS, JT, JU, K>) -> Self
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        AccessTokenVerifier<'a, JE, JS, JT, JU, K>: Send + Sync,
+    {
+        Self {
+            verify_fn: Arc::new(move |access_token: &AccessToken| {
+                JwtAccessToken::<AC, JE, JS, JT>::from_access_token(access_token)
+                    .map_err(|err| {
+                        ClaimsVerificationError::Other(format!(
+                            "failed to parse access token: {}",
+                            err
+                        ))
+                    })?
+                    .claims(&verifier)
+            }),
+        }
+    }
+
+    ///
+    /// Verifies the specified bearer access token and returns its claims.
+    ///
+    pub fn verify(
+        &self,
+        access_token: &AccessToken,
+    ) -> Result<AccessTokenClaims<AC>, ClaimsVerificationError> {
+        (*self.verify_fn)(access_token)
+    }
+}
+impl<'a, AC, JE, JS, JT, JU, K> From<AccessTokenVerifier<'a, JE, JS, JT, JU, K>>
+    for DynAccessTokenVerifier<'a, AC>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    AccessTokenVerifier<'a, JE, JS, JT, JU, K>: Send + Sync,
+{
+    fn from(verifier: AccessTokenVerifier<'a, JE, JS, JT, JU, K>) -> Self {
+        Self::new(verifier)
+    }
+}
+impl<'a, AC> Debug for DynAccessTokenVerifier<'a, AC>
+where
+    AC: AdditionalClaims,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("DynAccessTokenVerifier").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use oauth2::{AccessToken, ClientId, ClientSecret};
+
+    use crate::core::{
+        CoreAccessTokenClaims, CoreAccessTokenVerifier, CoreDynAccessTokenVerifier,
+        CoreDynIdTokenVerifier, CoreHmacKey, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier,
+        CoreJsonWebKeySet, CoreJwsSigningAlgorithm, CoreJwtAccessToken, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::{
+        Audience, ClaimsVerificationError, DynAccessTokenVerifier, EmptyAdditionalClaims,
+        FixedClock, IssuerUrl, Nonce, PrivateSigningKey, StandardClaims, SubjectIdentifier,
+        VerificationTimeOptions,
+    };
+
+    #[test]
+    fn test_dyn_id_token_verifier() {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1311281970, 0),
+            Utc.timestamp(1311280970, 0),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        )
+        .set_nonce(Some(Nonce::new("Zm9vYmFy".to_string())));
+        let id_token = serde_json::to_value(
+            CoreIdToken::new(
+                claims.clone(),
+                &CoreHmacKey::new("secret"),
+                CoreJwsSigningAlgorithm::HmacSha256,
+                None,
+                None,
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string();
+
+        let verifier: CoreDynIdTokenVerifier = CoreIdTokenVerifier::new_confidential_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            ClientSecret::new("secret".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_options(
+            VerificationTimeOptions::new().set_clock(FixedClock::new(Utc.timestamp(1311280970, 0))),
+        )
+        .into();
+
+        assert_eq!(
+            verifier
+                .verify(&id_token, &Nonce::new("Zm9vYmFy".to_string()))
+                .unwrap(),
+            claims
+        );
+        // The verifier may be shared by concurrent requests.
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                verifier
+                    .verify(&id_token, &Nonce::new("Zm9vYmFy".to_string()))
+                    .unwrap()
+            });
+        });
+        match verifier.verify(&id_token, &Nonce::new("other".to_string())) {
+            Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match verifier.verify("not a JWT", |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::Other(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dyn_access_token_verifier() {
+        let claims = CoreAccessTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("https://api.example.com".to_string())],
+            Utc.timestamp(1311281970, 0),
+            EmptyAdditionalClaims {},
+        );
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let access_token = AccessToken::new(
+            serde_json::to_value(
+                CoreJwtAccessToken::new(
+                    claims.clone(),
+                    &signing_key,
+                    CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                )
+                .unwrap(),
+            )
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string(),
+        );
+
+        // Verifiers for different audiences have the same type once their type parameters are
+        // erased.
+        let verifiers: Vec<CoreDynAccessTokenVerifier> =
+            vec!["https://api.example.com", "https://other.example.com"]
+                .into_iter()
+                .map(|audience| {
+                    DynAccessTokenVerifier::from(
+                        CoreAccessTokenVerifier::new(
+                            Audience::new(audience.to_string()),
+                            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+                        )
+                        .set_clock(FixedClock::new(Utc.timestamp(1311280970, 0))),
+                    )
+                })
+                .collect();
+
+        assert_eq!(verifiers[0].verify(&access_token).unwrap(), claims);
+        match verifiers[1].verify(&access_token) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match verifiers[0].verify(&AccessToken::new("opaque".to_string())) {
+            Err(ClaimsVerificationError::Other(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
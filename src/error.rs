@@ -0,0 +1,239 @@
+use failure::Fail;
+use oauth2::{ErrorResponse, RequestTokenError};
+use serde::Serialize;
+
+#[cfg(feature = "net")]
+use crate::http_types::StatusCode;
+#[cfg(feature = "net")]
+use crate::TokenRequestError;
+
+///
+/// Broad category of an error returned while communicating with the OpenID Connect Provider.
+///
+/// The category determines whether retrying the same request may succeed (see
+/// [`ErrorKind::is_retryable`]).
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    ///
+    /// The request could not be sent or the response could not be received (e.g., due to a
+    /// connectivity failure or timeout).
+    ///
+    Request,
+    ///
+    /// The provider returned a `5xx` status code, a `429 Too Many Requests` response, or an OAuth2
+    /// error response with a `server_error` or `temporarily_unavailable` error code.
+    ///
+    ServerError,
+    ///
+    /// The provider rejected the request (e.g., with a `4xx` status code or an OAuth2 error
+    /// response such as `invalid_grant`).
+    ///
+    ClientError,
+    ///
+    /// The provider returned a malformed response (e.g., an unexpected `Content-Type` or invalid
+    /// JSON).
+    ///
+    InvalidResponse,
+    ///
+    /// The response failed verification (e.g., an invalid ID token signature or an unexpected
+    /// issuer).
+    ///
+    Verification,
+    ///
+    /// Any other error, such as invalid configuration or a failure to access local state.
+    ///
+    Other,
+}
+impl ErrorKind {
+    ///
+    /// Returns whether retrying the same request may succeed.
+    ///
+    /// Only [`ErrorKind::Request`] and [`ErrorKind::ServerError`] errors are retryable. By default,
+    /// [`RetryPolicy`](crate::RetryPolicy) uses this classification to decide which responses to
+    /// retry.
+    ///
+    pub fn is_retryable(self) -> bool {
+        match self {
+            ErrorKind::Request | ErrorKind::ServerError => true,
+            ErrorKind::ClientError
+            | ErrorKind::InvalidResponse
+            | ErrorKind::Verification
+            | ErrorKind::Other => false,
+        }
+    }
+
+    // Classifies an unexpected HTTP status code.
+    #[cfg(feature = "net")]
+    pub(crate) fn from_status(status: StatusCode) -> Self {
+        if status == StatusCode::TooManyRequests || status.is_server_error() {
+            ErrorKind::ServerError
+        } else if status.is_client_error() {
+            ErrorKind::ClientError
+        } else {
+            ErrorKind::InvalidResponse
+        }
+    }
+
+    // Classifies an OAuth2 error code. Providers return `server_error` and
+    // `temporarily_unavailable` when they're unable to handle the request due to an unexpected
+    // condition or a temporary overload (see
+    // https://tools.ietf.org/html/rfc6749#section-4.1.2.1).
+    pub(crate) fn from_error_code(error_code: &str) -> Self {
+        match error_code {
+            "server_error" | "temporarily_unavailable" => ErrorKind::ServerError,
+            _ => ErrorKind::ClientError,
+        }
+    }
+
+    // Classifies an OAuth2 error response by its error code.
+    pub(crate) fn from_error_response<T>(error_response: &T) -> Self
+    where
+        T: Serialize,
+    {
+        error_code(error_response).map_or(ErrorKind::ClientError, |error_code| {
+            ErrorKind::from_error_code(&error_code)
+        })
+    }
+}
+
+// The `ErrorResponse` trait doesn't expose the error code, so it's read from the serialized
+// response (see https://tools.ietf.org/html/rfc6749#section-5.2).
+pub(crate) fn error_code<T>(error_response: &T) -> Option<String>
+where
+    T: Serialize,
+{
+    serde_json::to_value(error_response)
+        .ok()
+        .and_then(|value| value.get("error")?.as_str().map(ToOwned::to_owned))
+}
+
+///
+/// Error that can be classified by [`ErrorKind`].
+///
+/// This trait is implemented by the errors returned when communicating with the OpenID Connect
+/// Provider, including token errors returned by the `oauth2` crate, so that callers can make
+/// consistent retry decisions.
+///
+pub trait ClassifiedError {
+    ///
+    /// Returns the category of this error.
+    ///
+    fn error_kind(&self) -> ErrorKind;
+
+    ///
+    /// Returns whether retrying the request that caused this error may succeed.
+    ///
+    fn is_retryable(&self) -> bool {
+        self.error_kind().is_retryable()
+    }
+}
+
+// The `oauth2` crate doesn't expose the HTTP status code of token error responses, which is why
+// the `Client` token request methods return `TokenRequestError` instead. OAuth2 error responses
+// are classified by their error code, while server errors that don't return an OAuth2 error
+// response typically return bodies that fail to parse and are therefore classified as invalid
+// responses.
+impl<RE, TE> ClassifiedError for RequestTokenError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            RequestTokenError::Request(_) => ErrorKind::Request,
+            RequestTokenError::ServerResponse(err) => ErrorKind::from_error_response(err),
+            _ => ErrorKind::InvalidResponse,
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+impl<RE, TE> ClassifiedError for TokenRequestError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match (self.error(), self.status()) {
+            (RequestTokenError::Request(_), _) => ErrorKind::Request,
+            (_, Some(status)) if ErrorKind::from_status(status) == ErrorKind::ServerError => {
+                ErrorKind::ServerError
+            }
+            (RequestTokenError::ServerResponse(err), _) => ErrorKind::from_error_response(err),
+            (_, Some(status)) if !status.is_success() => ErrorKind::from_status(status),
+            (_, Some(_)) => ErrorKind::InvalidResponse,
+            // The request wasn't sent (e.g., because a client assertion couldn't be signed).
+            (_, None) => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use oauth2::basic::{BasicErrorResponse, BasicErrorResponseType};
+    use oauth2::RequestTokenError;
+
+    #[cfg(feature = "net")]
+    use crate::http_types::StatusCode;
+
+    use super::{ClassifiedError, ErrorKind};
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_from_status() {
+        for (status, kind) in &[
+            (StatusCode::InternalServerError, ErrorKind::ServerError),
+            (StatusCode::ServiceUnavailable, ErrorKind::ServerError),
+            (StatusCode::TooManyRequests, ErrorKind::ServerError),
+            (StatusCode::Unauthorized, ErrorKind::ClientError),
+            (StatusCode::NotFound, ErrorKind::ClientError),
+            (StatusCode::Ok, ErrorKind::InvalidResponse),
+            (StatusCode::Found, ErrorKind::InvalidResponse),
+        ] {
+            assert_eq!(ErrorKind::from_status(*status), *kind, "{}", status);
+            assert_eq!(
+                kind.is_retryable(),
+                *kind == ErrorKind::ServerError,
+                "{}",
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_error_code() {
+        for (error_code, kind) in &[
+            ("server_error", ErrorKind::ServerError),
+            ("temporarily_unavailable", ErrorKind::ServerError),
+            ("invalid_grant", ErrorKind::ClientError),
+            ("invalid_client", ErrorKind::ClientError),
+            ("unknown_error", ErrorKind::ClientError),
+        ] {
+            assert_eq!(
+                ErrorKind::from_error_code(error_code),
+                *kind,
+                "{}",
+                error_code
+            );
+        }
+
+        let token_error = |error_type| {
+            RequestTokenError::<io::Error, _>::ServerResponse(BasicErrorResponse::new(
+                error_type, None, None,
+            ))
+        };
+        assert_eq!(
+            ErrorKind::ClientError,
+            token_error(BasicErrorResponseType::InvalidGrant).error_kind()
+        );
+        let err = token_error(BasicErrorResponseType::Extension(
+            "temporarily_unavailable".to_string(),
+        ));
+        assert_eq!(ErrorKind::ServerError, err.error_kind());
+        assert!(err.is_retryable());
+    }
+}
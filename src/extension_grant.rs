@@ -0,0 +1,432 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::marker::PhantomData;
+
+use failure::Fail;
+use futures::executor::block_on;
+use oauth2::{ClientId, ClientSecret, ErrorResponse, RequestTokenError, Scope, TokenUrl};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::http_client::{
+    AsyncHttpClient, BlockingHttpClient, HttpClient, InstrumentedHttpClient, Interceptors,
+    RequestKind, RequestOptions,
+};
+use crate::http_types::headers::{ACCEPT, CONTENT_TYPE};
+use crate::http_types::{Method, Request, Response, StatusCode};
+use crate::http_utils::{
+    auth_basic, check_content_type, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON,
+};
+use crate::url::form_urlencoded;
+use crate::{TokenEndpointAuthMethod, TokenRequestError};
+
+///
+/// A request to the token endpoint using an extension grant type (see
+/// [Section 4.5 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.5)).
+///
+/// Extension grants are identified by an absolute URI (e.g., Auth0's
+/// `http://auth0.com/oauth/grant-type/password-realm`) and accept grant-specific parameters, which
+/// may be added individually via [`ExtensionGrantTokenRequest::add_extra_param`] or from a
+/// serializable type via [`ExtensionGrantTokenRequest::add_extra_params`]. The client
+/// authenticates to the token endpoint using its
+/// [`TokenEndpointAuthMethod`](crate::TokenEndpointAuthMethod), including a client assertion for
+/// the JWT-based methods (see
+/// [`Client::set_client_assertion_signing_key`](crate::Client::set_client_assertion_signing_key)).
+///
+/// The response is deserialized as the client's token response type by default. Grants that
+/// return additional response fields may use a token response type with different extra fields
+/// (see [`ExtensionGrantTokenRequest::set_response_type`]).
+///
+/// Requires "net" feature.
+///
+pub struct ExtensionGrantTokenRequest<'a, TE, TR>
+where
+    TE: ErrorResponse,
+    TR: DeserializeOwned,
+{
+    pub(super) grant_type: Cow<'a, str>,
+    pub(super) token_url: Option<&'a TokenUrl>,
+    pub(super) client_id: &'a ClientId,
+    pub(super) client_secret: Option<&'a ClientSecret>,
+    pub(super) auth_method: TokenEndpointAuthMethod,
+    // Signed when the request is created, since `Client::client_assertion_params` depends on the
+    // client's type parameters.
+    pub(super) client_assertion_params: Result<Vec<(&'static str, String)>, String>,
+    pub(super) interceptors: Interceptors,
+    pub(super) request_options: RequestOptions,
+    pub(super) scopes: Vec<Scope>,
+    pub(super) extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub(super) _phantom: PhantomData<(TE, TR)>,
+}
+impl<'a, TE, TR> Debug for ExtensionGrantTokenRequest<'a, TE, TR>
+where
+    TE: ErrorResponse,
+    TR: DeserializeOwned,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // Extra params may carry credentials (e.g., a password or assertion), so only their
+        // names are included.
+        let extra_param_names = self
+            .extra_params
+            .iter()
+            .map(|(name, _)| name.as_ref())
+            .collect::<Vec<_>>();
+        f.debug_struct("ExtensionGrantTokenRequest")
+            .field("grant_type", &self.grant_type)
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret)
+            .field("auth_method", &self.auth_method)
+            .field(
+                "client_assertion_params",
+                &self
+                    .client_assertion_params
+                    .as_ref()
+                    .map(|params| params.iter().map(|(name, _)| *name).collect::<Vec<_>>()),
+            )
+            .field("interceptors", &self.interceptors)
+            .field("request_options", &self.request_options)
+            .field("scopes", &self.scopes)
+            .field("extra_params", &extra_param_names)
+            .finish()
+    }
+}
+impl<'a, TE, TR> ExtensionGrantTokenRequest<'a, TE, TR>
+where
+    TE: ErrorResponse + 'static,
+    TR: DeserializeOwned,
+{
+    ///
+    /// Appends a new scope to the token request.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Appends an extra param to the token request.
+    ///
+    /// If `name` conflicts with a parameter managed by this crate (e.g., `grant_type`, `scope`,
+    /// or the client credentials), the behavior is undefined.
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+
+    ///
+    /// Appends the fields of the specified value to the token request as extra params.
+    ///
+    /// The value must serialize to a JSON object whose fields are strings, numbers, booleans, or
+    /// `null`. Fields set to `null` (e.g., `None`) are omitted, and numbers and booleans are
+    /// converted to strings. Returns an error if the value can't be represented as form
+    /// parameters.
+    ///
+    pub fn add_extra_params<P>(mut self, params: &P) -> Result<Self, serde_json::Error>
+    where
+        P: Serialize + ?Sized,
+    {
+        let fields = match serde_json::to_value(params)? {
+            Value::Object(fields) => fields,
+            _ => {
+                return Err(serde::ser::Error::custom(
+                    "extra params must serialize to a JSON object",
+                ))
+            }
+        };
+        for (name, value) in fields {
+            let value = match value {
+                Value::Null => continue,
+                Value::String(value) => value,
+                Value::Bool(_) | Value::Number(_) => value.to_string(),
+                Value::Array(_) | Value::Object(_) => {
+                    return Err(serde::ser::Error::custom(format!(
+                        "extra param `{}` must be a string, number, or boolean",
+                        name
+                    )))
+                }
+            };
+            self.extra_params.push((name.into(), value.into()));
+        }
+        Ok(self)
+    }
+
+    ///
+    /// Specifies the options to apply to the HTTP request.
+    ///
+    pub fn set_request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
+    ///
+    /// Specifies the type as which the token response is deserialized (e.g., a
+    /// [`StandardTokenResponse`](crate::StandardTokenResponse) with grant-specific
+    /// [`ExtraTokenFields`](crate::ExtraTokenFields)).
+    ///
+    pub fn set_response_type<R>(self) -> ExtensionGrantTokenRequest<'a, TE, R>
+    where
+        R: DeserializeOwned,
+    {
+        ExtensionGrantTokenRequest {
+            grant_type: self.grant_type,
+            token_url: self.token_url,
+            client_id: self.client_id,
+            client_secret: self.client_secret,
+            auth_method: self.auth_method,
+            client_assertion_params: self.client_assertion_params,
+            interceptors: self.interceptors,
+            request_options: self.request_options,
+            scopes: self.scopes,
+            extra_params: self.extra_params,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Submits this request to the token endpoint using the specified asynchronous HTTP client.
+    ///
+    /// The client's interceptors (see [`Client::add_interceptor`](crate::Client::add_interceptor))
+    /// are applied to the request.
+    ///
+    pub async fn request<C>(self, http_client: &C) -> Result<TR, TokenRequestError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let http_request = self
+            .prepare_request()
+            .map_err(|err| TokenRequestError::new(RequestTokenError::Other(err), None))?;
+        let http_client = self.interceptors.wrap(http_client);
+        let http_response = InstrumentedHttpClient::new(RequestKind::Token, &http_client)
+            .call(http_request)
+            .await
+            .map_err(|err| TokenRequestError::new(RequestTokenError::Request(err), None))?;
+
+        let status = http_response.status();
+        token_response(http_response)
+            .await
+            .map_err(|err| TokenRequestError::new(err, Some(status)))
+    }
+
+    ///
+    /// Submits this request to the token endpoint using the specified synchronous HTTP client.
+    ///
+    pub fn request_blocking<C>(self, http_client: &C) -> Result<TR, TokenRequestError<C::Error, TE>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(self.request(&BlockingHttpClient(http_client)))
+    }
+
+    fn prepare_request(&self) -> Result<Request, String> {
+        let token_url = self
+            .token_url
+            .ok_or_else(|| "no token_url provided".to_string())?;
+
+        let mut request = Request::new(Method::Post, token_url.url().clone());
+        request.insert_header(ACCEPT, MIME_TYPE_JSON);
+        request.insert_header(CONTENT_TYPE, MIME_TYPE_FORM_URLENCODED);
+
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        body.append_pair("grant_type", &self.grant_type);
+        match (self.auth_method, self.client_secret) {
+            (TokenEndpointAuthMethod::ClientSecretBasic, Some(client_secret)) => {
+                let (auth_header, auth_value) = auth_basic(self.client_id, client_secret);
+                request.insert_header(auth_header, auth_value);
+            }
+            (TokenEndpointAuthMethod::ClientSecretPost, Some(client_secret)) => {
+                body.append_pair("client_id", self.client_id)
+                    .append_pair("client_secret", client_secret.secret());
+            }
+            // Public clients and clients authenticating via client assertions identify themselves
+            // using the `client_id` parameter.
+            _ => {
+                body.append_pair("client_id", self.client_id);
+            }
+        }
+        let client_assertion_params = self
+            .client_assertion_params
+            .as_ref()
+            .map_err(Clone::clone)?;
+        for (name, value) in client_assertion_params {
+            body.append_pair(name, value);
+        }
+        if !self.scopes.is_empty() {
+            let scopes = self
+                .scopes
+                .iter()
+                .map(|scope| scope.as_str())
+                .collect::<Vec<_>>();
+            body.append_pair("scope", &scopes.join(" "));
+        }
+        for (name, value) in &self.extra_params {
+            body.append_pair(name, value);
+        }
+        request.set_body(body.finish().into_bytes());
+
+        self.request_options.apply(&mut request);
+        Ok(request)
+    }
+}
+
+async fn token_response<RE, TE, TR>(
+    mut http_response: Response,
+) -> Result<TR, RequestTokenError<RE, TE>>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+    TR: DeserializeOwned,
+{
+    let status = http_response.status();
+    let content_type = check_content_type(&http_response, MIME_TYPE_JSON);
+    let body = http_response.body_bytes().await.map_err(|err| {
+        RequestTokenError::Other(format!("failed to read token response: {}", err))
+    })?;
+
+    if status != StatusCode::Ok {
+        // See https://tools.ietf.org/html/rfc6749#section-5.2.
+        return Err(match serde_json::from_slice(&body) {
+            Ok(error_response) => RequestTokenError::ServerResponse(error_response),
+            Err(_) => RequestTokenError::Other(format!("unexpected HTTP status code {}", status)),
+        });
+    }
+    content_type.map_err(RequestTokenError::Other)?;
+
+    serde_json::from_slice(&body)
+        .map_err(|err| RequestTokenError::Other(format!("failed to parse token response: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use futures::executor::block_on;
+    use futures::future::ready;
+    use oauth2::{RequestTokenError, Scope};
+    use url::form_urlencoded;
+
+    use crate::core::{CoreClient, CoreErrorResponseType, CoreTokenType};
+    use crate::http_types::headers::AUTHORIZATION;
+    use crate::http_types::{Request, Response, StatusCode};
+    use crate::{
+        AuthUrl, ClassifiedError, ClientId, ClientSecret, EmptyExtraTokenFields, ErrorKind,
+        IssuerUrl, JsonWebKeySet, OAuth2TokenResponse, StandardTokenResponse, TokenUrl,
+    };
+
+    const PASSWORD_REALM_GRANT: &str = "http://auth0.com/oauth/grant-type/password-realm";
+
+    fn new_client() -> CoreClient {
+        CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        )
+    }
+
+    #[derive(Serialize)]
+    struct PasswordRealmParams<'a> {
+        username: &'a str,
+        password: &'a str,
+        realm: &'a str,
+        audience: Option<&'a str>,
+    }
+
+    #[test]
+    fn test_extension_grant() {
+        let http_client = |mut request: Request| async move {
+            assert_eq!("https://example/token", request.url().as_str());
+            assert_eq!(
+                "Basic YWFhOmJiYg==",
+                request.header(AUTHORIZATION).unwrap().as_str()
+            );
+            let body = request.body_bytes().await.unwrap();
+            let params = form_urlencoded::parse(&body)
+                .into_owned()
+                .collect::<Vec<_>>();
+            assert_eq!(
+                vec![
+                    ("grant_type".to_string(), PASSWORD_REALM_GRANT.to_string()),
+                    ("scope".to_string(), "openid email".to_string()),
+                    ("username".to_string(), "alice".to_string()),
+                    ("password".to_string(), "secret".to_string()),
+                    ("realm".to_string(), "employees".to_string()),
+                ],
+                params
+            );
+
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"access_token\": \"token\", \"token_type\": \"bearer\"}");
+            Ok::<_, io::Error>(response)
+        };
+
+        let client = new_client();
+        let request = client
+            .exchange_extension_grant(PASSWORD_REALM_GRANT)
+            .add_scope(Scope::new("openid".to_string()))
+            .add_scope(Scope::new("email".to_string()))
+            .add_extra_params(&PasswordRealmParams {
+                username: "alice",
+                password: "secret",
+                realm: "employees",
+                audience: None,
+            })
+            .unwrap()
+            .set_response_type::<StandardTokenResponse<EmptyExtraTokenFields, CoreTokenType>>();
+        let token_response = block_on(request.request(&http_client)).unwrap();
+        assert_eq!("token", token_response.access_token().secret());
+    }
+
+    #[test]
+    fn test_extension_grant_error() {
+        let http_client = |_: Request| {
+            let mut response = Response::new(StatusCode::BadRequest);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"error\": \"invalid_grant\"}");
+            ready(Ok::<_, io::Error>(response))
+        };
+
+        let err = block_on(
+            new_client()
+                .exchange_extension_grant(PASSWORD_REALM_GRANT)
+                .request(&http_client),
+        )
+        .unwrap_err();
+        assert_eq!(Some(StatusCode::BadRequest), err.status());
+        assert_eq!(ErrorKind::ClientError, err.error_kind());
+        match err.into_error() {
+            RequestTokenError::ServerResponse(err) => {
+                assert_eq!(CoreErrorResponseType::InvalidGrant, *err.error())
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // Server errors without an OAuth2 error response are classified by their status code.
+        let http_client =
+            |_: Request| ready(Ok::<_, io::Error>(Response::new(StatusCode::BadGateway)));
+        let err = block_on(
+            new_client()
+                .exchange_extension_grant(PASSWORD_REALM_GRANT)
+                .request(&http_client),
+        )
+        .unwrap_err();
+        assert_eq!(Some(StatusCode::BadGateway), err.status());
+        assert_eq!(ErrorKind::ServerError, err.error_kind());
+
+        // Array-valued params can't be represented as form parameters.
+        assert!(new_client()
+            .exchange_extension_grant(PASSWORD_REALM_GRANT)
+            .add_extra_params(&serde_json::json!({ "realm": ["a", "b"] }))
+            .is_err());
+    }
+}
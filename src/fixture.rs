@@ -0,0 +1,381 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use failure::Fail;
+use futures::future::{ready, Ready};
+use serde::{Deserialize, Serialize};
+
+use crate::http_client::AsyncHttpClient;
+use crate::http_types::{Request, Response, StatusCode};
+
+///
+/// HTTP request and the response returned for it, as recorded by a [`RecordingHttpClient`] and
+/// served by a [`ReplayHttpClient`].
+///
+/// Only the request method and URL are recorded, since request headers and bodies may contain
+/// client credentials. Response bodies are stored as UTF-8 text.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct HttpFixture {
+    method: String,
+    url: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(default)]
+    body: String,
+}
+impl HttpFixture {
+    ///
+    /// Returns the HTTP method of the request (e.g., `GET`).
+    ///
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    ///
+    /// Returns the URL of the request.
+    ///
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    ///
+    /// Returns the HTTP status code of the response.
+    ///
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    ///
+    /// Returns the response headers as name-value pairs, in the order they were received.
+    ///
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    ///
+    /// Returns the response body.
+    ///
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    fn matches(&self, request: &Request) -> bool {
+        self.method == request.method().to_string() && self.url == request.url().as_str()
+    }
+
+    fn to_response(&self) -> Result<Response, ReplayError> {
+        let status = StatusCode::try_from(self.status).map_err(|_| {
+            ReplayError::InvalidFixture(format!("invalid status code `{}`", self.status))
+        })?;
+        let mut response = Response::new(status);
+        for (name, value) in &self.headers {
+            response.append_header(name.as_str(), value.as_str());
+        }
+        response.set_body(self.body.clone());
+        Ok(response)
+    }
+}
+
+///
+/// Error returned by a [`RecordingHttpClient`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum RecordingError<RE>
+where
+    RE: Fail,
+{
+    ///
+    /// The wrapped HTTP client returned an error.
+    ///
+    #[fail(display = "Request failed")]
+    Request(#[cause] RE),
+    ///
+    /// The response body could not be read.
+    ///
+    #[fail(display = "Failed to read response body: {}", _0)]
+    Response(String),
+}
+
+///
+/// HTTP client that records each request and response sent through the wrapped client as an
+/// [`HttpFixture`].
+///
+/// The recorded fixtures may be saved as JSON via [`RecordingHttpClient::save`] and later served
+/// by a [`ReplayHttpClient`], which allows discovery, token, and user info flows to be tested
+/// deterministically without a network connection. Clones of a recording client share the same
+/// fixtures, so a clone may be passed to the code under test.
+///
+/// Response bodies are buffered in memory so that they can be recorded.
+///
+/// # Security Warning
+///
+/// Recorded responses include any tokens returned by the provider. Fixtures should only be
+/// recorded using test accounts and credentials.
+///
+#[derive(Clone, Debug)]
+pub struct RecordingHttpClient<C> {
+    fixtures: Arc<Mutex<Vec<HttpFixture>>>,
+    inner: C,
+}
+impl<C> RecordingHttpClient<C>
+where
+    C: AsyncHttpClient + Clone + Send + Sync + 'static,
+    C::Future: Send,
+{
+    ///
+    /// Wraps the specified HTTP client so that each request and response is recorded.
+    ///
+    pub fn new(inner: C) -> Self {
+        Self {
+            fixtures: Arc::new(Mutex::new(Vec::new())),
+            inner,
+        }
+    }
+
+    ///
+    /// Returns the fixtures recorded so far, in the order the requests were sent.
+    ///
+    pub fn fixtures(&self) -> Vec<HttpFixture> {
+        self.lock().clone()
+    }
+
+    ///
+    /// Serializes the fixtures recorded so far as JSON.
+    ///
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&*self.lock())
+    }
+
+    ///
+    /// Writes the fixtures recorded so far to the specified file as JSON.
+    ///
+    pub fn save<P>(&self, path: P) -> Result<(), io::Error>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, self.to_json()?)
+    }
+
+    fn lock(&self) -> MutexGuard<Vec<HttpFixture>> {
+        // The fixtures remain consistent even if a previous holder panicked.
+        self.fixtures.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    async fn send(self, request: Request) -> Result<Response, RecordingError<C::Error>> {
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        let mut response = self
+            .inner
+            .call(request)
+            .await
+            .map_err(RecordingError::Request)?;
+
+        let body = response
+            .body_bytes()
+            .await
+            .map_err(|err| RecordingError::Response(err.to_string()))?;
+        let headers = response
+            .iter()
+            .flat_map(|(name, values)| {
+                values
+                    .iter()
+                    .map(move |value| (name.as_str().to_string(), value.as_str().to_string()))
+            })
+            .collect();
+        self.lock().push(HttpFixture {
+            method,
+            url,
+            status: u16::from(response.status()),
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        });
+
+        response.set_body(body);
+        Ok(response)
+    }
+}
+impl<C> AsyncHttpClient for RecordingHttpClient<C>
+where
+    C: AsyncHttpClient + Clone + Send + Sync + 'static,
+    C::Future: Send,
+{
+    type Error = RecordingError<C::Error>;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Self::Error>> + Send>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
+
+///
+/// Error returned by a [`ReplayHttpClient`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum ReplayError {
+    ///
+    /// None of the remaining fixtures matches the request's method and URL.
+    ///
+    #[fail(display = "No remaining fixture matches request: {} {}", _0, _1)]
+    NoMatchingFixture(String, String),
+    ///
+    /// The matching fixture could not be converted into a response.
+    ///
+    #[fail(display = "Invalid fixture: {}", _0)]
+    InvalidFixture(String),
+}
+
+///
+/// HTTP client that serves responses from previously recorded [`HttpFixture`]s instead of
+/// sending requests over the network.
+///
+/// Each request is answered by the first remaining fixture with the same method and URL, which
+/// is then consumed. Requests for the same URL are therefore answered in the order they were
+/// recorded. Requests without a matching fixture result in a [`ReplayError::NoMatchingFixture`]
+/// error. Clones of a replay client share the same fixtures.
+///
+#[derive(Clone, Debug)]
+pub struct ReplayHttpClient {
+    fixtures: Arc<Mutex<VecDeque<HttpFixture>>>,
+}
+impl ReplayHttpClient {
+    ///
+    /// Instantiates a client that serves the specified fixtures.
+    ///
+    pub fn new(fixtures: Vec<HttpFixture>) -> Self {
+        Self {
+            fixtures: Arc::new(Mutex::new(fixtures.into())),
+        }
+    }
+
+    ///
+    /// Instantiates a client that serves the fixtures serialized in the specified JSON (e.g., as
+    /// returned by [`RecordingHttpClient::to_json`]).
+    ///
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json).map(Self::new)
+    }
+
+    ///
+    /// Instantiates a client that serves the fixtures read from the specified JSON file (e.g., as
+    /// written by [`RecordingHttpClient::save`]).
+    ///
+    pub fn load<P>(path: P) -> Result<Self, io::Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::from_json(&fs::read_to_string(path)?)?)
+    }
+
+    ///
+    /// Returns the number of fixtures that have not yet been served.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<VecDeque<HttpFixture>> {
+        // The fixtures remain consistent even if a previous holder panicked.
+        self.fixtures.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+impl AsyncHttpClient for ReplayHttpClient {
+    type Error = ReplayError;
+    type Future = Ready<Result<Response, ReplayError>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        let mut fixtures = self.lock();
+        let fixture = fixtures
+            .iter()
+            .position(|fixture| fixture.matches(&request))
+            .and_then(|index| fixtures.remove(index));
+        ready(match fixture {
+            Some(fixture) => fixture.to_response(),
+            None => Err(ReplayError::NoMatchingFixture(
+                request.method().to_string(),
+                request.url().to_string(),
+            )),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use futures::executor::block_on;
+    use futures::future::ready;
+
+    use crate::http_types::{Method, Request, Response, StatusCode};
+    use crate::AsyncHttpClient;
+
+    use super::{RecordingHttpClient, ReplayError, ReplayHttpClient};
+
+    fn test_request(method: Method, url: &str) -> Request {
+        Request::new(method, url.parse::<url::Url>().unwrap())
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let recorder = RecordingHttpClient::new(|request: Request| {
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(format!("{{\"path\":\"{}\"}}", request.url().path()));
+            ready(Ok::<_, io::Error>(response))
+        });
+
+        for url in &["https://example.com/jwks", "https://example.com/userinfo"] {
+            let mut response = block_on(recorder.call(test_request(Method::Get, url))).unwrap();
+            // The recorded response body is still returned to the caller.
+            assert!(block_on(response.body_string())
+                .unwrap()
+                .starts_with("{\"path\":"));
+        }
+        let fixtures = recorder.fixtures();
+        assert_eq!(fixtures.len(), 2);
+        assert_eq!(fixtures[0].method(), "GET");
+        assert_eq!(fixtures[0].url(), "https://example.com/jwks");
+        assert_eq!(fixtures[0].status(), 200);
+        assert_eq!(fixtures[0].body(), "{\"path\":\"/jwks\"}");
+
+        let replay = ReplayHttpClient::from_json(&recorder.to_json().unwrap()).unwrap();
+        let mut response =
+            block_on(replay.call(test_request(Method::Get, "https://example.com/userinfo")))
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert_eq!(
+            response.header("Content-Type").unwrap().last().as_str(),
+            "application/json"
+        );
+        assert_eq!(
+            block_on(response.body_string()).unwrap(),
+            "{\"path\":\"/userinfo\"}"
+        );
+        assert_eq!(replay.remaining(), 1);
+
+        // Each fixture is only served once, and the method must match.
+        for (method, url) in &[
+            (Method::Get, "https://example.com/userinfo"),
+            (Method::Post, "https://example.com/jwks"),
+        ] {
+            match block_on(replay.call(test_request(*method, url))) {
+                Err(ReplayError::NoMatchingFixture(ref err_method, ref err_url)) => {
+                    assert_eq!(*err_method, method.to_string());
+                    assert_eq!(err_url, url);
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        block_on(replay.call(test_request(Method::Get, "https://example.com/jwks"))).unwrap();
+        assert_eq!(replay.remaining(), 0);
+    }
+}
@@ -1,6 +1,7 @@
 use serde::de::value::MapDeserializer;
 use serde::de::{DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
 use serde_value::{Value, ValueDeserializer};
 
 use std::cmp::PartialEq;
@@ -182,3 +183,15 @@ where
         Debug::fmt(&self.inner, f)
     }
 }
+
+// Serializes a claims struct (e.g., ID token or user info claims) into a flat JSON object.
+pub(crate) fn serialize_claims<T>(claims: &T) -> Result<Map<String, JsonValue>, serde_json::Error>
+where
+    T: Serialize,
+{
+    match serde_json::to_value(claims)? {
+        JsonValue::Object(map) => Ok(map),
+        // Structs containing flattened fields always serialize as maps.
+        other => unreachable!("claims serialized as non-object: {:?}", other),
+    }
+}
@@ -1,9 +1,11 @@
-use oauth2::AccessToken;
+use oauth2::{AccessToken, ClientId, ClientSecret};
 use std::str::FromStr;
 
 use crate::http_types::headers::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use crate::http_types::Response;
+use crate::url::form_urlencoded;
 
+pub const MIME_TYPE_FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
 pub const MIME_TYPE_JSON: &str = "application/json";
 pub const MIME_TYPE_JWKS: &str = "application/jwk-set+json";
 pub const MIME_TYPE_JWT: &str = "application/jwt";
@@ -45,3 +47,17 @@ pub fn auth_bearer(access_token: &AccessToken) -> (HeaderName, HeaderValue) {
             .expect("invalid access token"),
     )
 }
+
+// See https://tools.ietf.org/html/rfc6749#section-2.3.1.
+pub fn auth_basic(client_id: &ClientId, client_secret: &ClientSecret) -> (HeaderName, HeaderValue) {
+    let credentials = format!(
+        "{}:{}",
+        form_urlencoded::byte_serialize(client_id.as_bytes()).collect::<String>(),
+        form_urlencoded::byte_serialize(client_secret.secret().as_bytes()).collect::<String>()
+    );
+    (
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Basic {}", base64::encode(&credentials)))
+            .expect("invalid client credentials"),
+    )
+}
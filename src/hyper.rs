@@ -0,0 +1,179 @@
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+
+use failure::Fail;
+use futures::future::{select, Either};
+use futures_timer::Delay;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::Bytes;
+use hyper_util::client::legacy::connect::{Connect, HttpConnector};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+
+use crate::http_client::{AsyncHttpClient, RequestOptions, RequestPolicy};
+use crate::http_types::{Request, Response, StatusCode};
+
+///
+/// Error type returned by failed hyper HTTP requests.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum Error {
+    ///
+    /// Failed to convert between `http_types` and hyper representations.
+    ///
+    #[fail(display = "HTTP conversion failed: {}", _0)]
+    Http(String),
+    ///
+    /// Error returned by hyper while reading the response body.
+    ///
+    #[fail(display = "failed to read response body")]
+    Hyper(#[cause] hyper::Error),
+    ///
+    /// Error returned by the hyper client while sending the request.
+    ///
+    #[fail(display = "request failed")]
+    Client(#[cause] hyper_util::client::legacy::Error),
+    ///
+    /// The request did not complete within the timeout specified by its [`RequestOptions`].
+    ///
+    #[fail(display = "request timed out")]
+    Timeout,
+    ///
+    /// The response body exceeded the maximum size specified by the request's [`RequestPolicy`].
+    ///
+    #[fail(display = "response body exceeds {} bytes", _0)]
+    ResponseTooLarge(usize),
+}
+
+///
+/// Asynchronous HTTP client backed by a shared hyper [`Client`].
+///
+/// Cloning a `HyperClient` is cheap and reuses the underlying connection pool. A reference to this
+/// type may be passed anywhere an [`AsyncHttpClient`](crate::AsyncHttpClient) is expected.
+///
+/// The default connector only supports plain HTTP. Applications should supply a TLS-capable
+/// connector (e.g., from `hyper-rustls` or `hyper-tls`) via [`HyperClient::from_client`].
+///
+#[derive(Clone, Debug)]
+pub struct HyperClient<C = HttpConnector>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    inner: Client<C, Full<Bytes>>,
+}
+impl HyperClient<HttpConnector> {
+    ///
+    /// Instantiates a new client using the Tokio executor and a plain HTTP connector.
+    ///
+    pub fn new() -> Self {
+        Self::from_client(Client::builder(TokioExecutor::new()).build_http())
+    }
+}
+impl Default for HyperClient<HttpConnector> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<C> HyperClient<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    ///
+    /// Instantiates a new client wrapping the specified hyper [`Client`].
+    ///
+    /// Note that hyper does not follow redirects, which prevents SSRF vulnerabilities.
+    ///
+    pub fn from_client(inner: Client<C, Full<Bytes>>) -> Self {
+        Self { inner }
+    }
+
+    ///
+    /// Sends the specified request.
+    ///
+    pub async fn send(&self, request: Request) -> Result<Response, Error> {
+        let timeout = RequestOptions::from_request(&request).and_then(|o| o.timeout());
+        let max_response_size =
+            RequestPolicy::from_request(&request).and_then(RequestPolicy::max_response_size);
+        let response = async {
+            let hyper_request = to_hyper_request(request).await?;
+            let hyper_response = self
+                .inner
+                .request(hyper_request)
+                .await
+                .map_err(Error::Client)?;
+            from_hyper_response(hyper_response, max_response_size).await
+        };
+
+        match timeout {
+            Some(timeout) => match select(Box::pin(response), Delay::new(timeout)).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(Error::Timeout),
+            },
+            None => response.await,
+        }
+    }
+}
+impl<C> AsyncHttpClient for HyperClient<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Error>> + Send>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move { client.send(request).await })
+    }
+}
+
+async fn to_hyper_request(mut request: Request) -> Result<http1::Request<Full<Bytes>>, Error> {
+    let mut builder = http1::Request::builder()
+        .method(request.method().to_string().as_str())
+        .uri(request.url().as_str());
+    for (name, values) in request.iter() {
+        for value in values.iter() {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+    }
+    let body = request
+        .body_bytes()
+        .await
+        .map_err(|err| Error::Http(format!("failed to read request body: {}", err)))?;
+    builder
+        .body(Full::new(Bytes::from(body)))
+        .map_err(|err| Error::Http(err.to_string()))
+}
+
+async fn from_hyper_response(
+    hyper_response: http1::Response<hyper::body::Incoming>,
+    max_response_size: Option<usize>,
+) -> Result<Response, Error> {
+    let (parts, body) = hyper_response.into_parts();
+
+    let status_code = StatusCode::try_from(parts.status.as_u16())
+        .map_err(|err| Error::Http(format!("invalid HTTP status code: {}", err)))?;
+    let mut http_response = Response::new(status_code);
+    for (name, value) in parts.headers.iter() {
+        let value = value
+            .to_str()
+            .map_err(|err| Error::Http(format!("invalid header value: {}", err)))?;
+        http_response.append_header(name.as_str(), value);
+    }
+
+    let body = match max_response_size {
+        Some(max_response_size) => Limited::new(body, max_response_size)
+            .collect()
+            .await
+            .map_err(|err| match err.downcast::<hyper::Error>() {
+                Ok(err) => Error::Hyper(*err),
+                // The only other error returned by `Limited` is `LengthLimitError`.
+                Err(_) => Error::ResponseTooLarge(max_response_size),
+            })?
+            .to_bytes(),
+        None => body.collect().await.map_err(Error::Hyper)?.to_bytes(),
+    };
+    http_response.set_body(body.to_vec());
+    Ok(http_response)
+}
@@ -1,26 +1,32 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use oauth2::helpers::variant_name;
 use oauth2::ClientId;
 use serde::Serialize;
 
-use crate::helpers::FilteredFlatten;
+use crate::claims::parse_claims_lenient;
+use crate::helpers::{serialize_claims, FilteredFlatten};
 use crate::jwt::JsonWebTokenAccess;
 use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
-use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt};
+use crate::types::helpers::{
+    deserialize_issuer_claim, deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt,
+};
 use crate::types::LocalizedClaim;
 use crate::{
     AccessToken, AccessTokenHash, AdditionalClaims, AddressClaim, Audience, AudiencesClaim,
-    AuthenticationContextClass, AuthenticationMethodReference, AuthorizationCode,
-    AuthorizationCodeHash, ClaimsVerificationError, EndUserBirthday, EndUserEmail,
-    EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
-    EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
-    EndUserWebsiteUrl, ExtraTokenFields, GenderClaim, IdTokenVerifier, IssuerClaim, IssuerUrl,
-    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JsonWebTokenAlgorithm,
+    AuthenticationContextClass, AuthenticationMethodReference, AuthenticationMethodReferenceValue,
+    AuthorizationCode, AuthorizationCodeHash, AuthorizationDetail, ClaimWarning,
+    ClaimsVerificationError, EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName,
+    EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl,
+    EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, ExtraTokenFields,
+    GenderClaim, IdTokenVerifier, IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType,
+    JsonWebKeyUse, JsonWebToken, JsonWebTokenAlgorithm, JsonWebTokenHeader,
     JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag, Nonce, NonceVerifier,
-    PrivateSigningKey, SigningError, StandardClaims, SubjectIdentifier,
+    PrivateSigningKey, SessionId, SigningError, StandardClaims, SubjectIdentifier, TrustmarkUrl,
+    VectorOfTrust,
 };
 
 // This wrapper layer exists instead of directly verifying the JWT and returning the claims so that
@@ -125,6 +131,34 @@ where
         verifier.verified_claims_owned(self.0, nonce_verifier)
     }
 
+    ///
+    /// Returns the ID token claims without verifying the token's signature or claims.
+    ///
+    /// This is intended for logging, debugging, and routing decisions that must be made before
+    /// the token can be verified (e.g., selecting a tenant's verifier based on the `iss` claim).
+    ///
+    /// # Security Warning
+    ///
+    /// The returned claims are untrusted and may have been forged or tampered with. They must not
+    /// be used for authentication or authorization decisions; use [`IdToken::claims`] or
+    /// [`IdToken::into_claims`] instead.
+    ///
+    pub fn unverified_claims(&self) -> &IdTokenClaims<AC, GC> {
+        self.0.unverified_payload_ref()
+    }
+
+    ///
+    /// Returns the JOSE header of the ID token without verifying the token's signature.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned header is untrusted and may have been forged or tampered with (see
+    /// [`IdToken::unverified_claims`]).
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+
     ///
     /// Returns the [`JwsSigningAlgorithm`] used to sign this ID token.
     ///
@@ -139,6 +173,43 @@ where
             JsonWebTokenAlgorithm::None => Err(SigningError::UnsupportedAlg("none".to_string())),
         }
     }
+    ///
+    /// Parses an ID token in the JWS Compact Serialization without verifying it, tolerating
+    /// common provider bugs in its claims.
+    ///
+    /// Returns the ID token along with a [`ClaimWarning`] describing each coercion applied to the
+    /// claims (see [`ClaimWarning`] for the bugs tolerated). The signature is still verified over
+    /// the token exactly as issued. Use [`IdToken::from_str`] to reject these bugs instead.
+    ///
+    pub fn from_str_lenient(s: &str) -> Result<(Self, Vec<ClaimWarning>), serde_json::Error> {
+        let mut warnings = Vec::new();
+        let jwt = JsonWebToken::from_compact_with(s, |payload| {
+            parse_claims_lenient(payload, &mut warnings).map_err(|err| {
+                <serde_json::Error as serde::de::Error>::custom(format!(
+                    "Failed to parse payload JSON: {:?}",
+                    err
+                ))
+            })
+        })?;
+        Ok((Self(jwt), warnings))
+    }
+}
+impl<AC, GC, JE, JS, JT> FromStr for IdToken<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+
+    ///
+    /// Parses an ID token in the JWS Compact Serialization without verifying it.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonWebToken::from_compact(s).map(Self)
+    }
 }
 impl<AC, GC, JE, JS, JT> ToString for IdToken<AC, GC, JE, JS, JT>
 where
@@ -169,7 +240,7 @@ where
     AC: AdditionalClaims,
     GC: GenderClaim,
 {
-    #[serde(rename = "iss")]
+    #[serde(rename = "iss", deserialize_with = "deserialize_issuer_claim")]
     issuer: IssuerUrl,
     // We always serialize as an array, which is valid according to the spec. This sets the
     // 'default' attribute to be compatible with non-spec compliant OIDC providers that omit this
@@ -202,6 +273,12 @@ where
     access_token_hash: Option<AccessTokenHash>,
     #[serde(rename = "c_hash", skip_serializing_if = "Option::is_none")]
     code_hash: Option<AuthorizationCodeHash>,
+    #[serde(rename = "sid", skip_serializing_if = "Option::is_none")]
+    session_id: Option<SessionId>,
+    #[serde(rename = "vot", skip_serializing_if = "Option::is_none")]
+    vector_of_trust: Option<VectorOfTrust>,
+    #[serde(rename = "vtm", skip_serializing_if = "Option::is_none")]
+    trustmark: Option<TrustmarkUrl>,
 
     #[serde(bound = "GC: GenderClaim")]
     #[serde(flatten)]
@@ -239,6 +316,9 @@ where
             authorized_party: None,
             access_token_hash: None,
             code_hash: None,
+            session_id: None,
+            vector_of_trust: None,
+            trustmark: None,
             standard_claims,
             additional_claims: additional_claims.into(),
         }
@@ -257,9 +337,25 @@ where
             set_authorized_party -> authorized_party[Option<ClientId>] ["azp"],
             set_access_token_hash -> access_token_hash[Option<AccessTokenHash>] ["at_hash"],
             set_code_hash -> code_hash[Option<AuthorizationCodeHash>] ["c_hash"],
+            set_session_id -> session_id[Option<SessionId>] ["sid"],
+            set_vector_of_trust -> vector_of_trust[Option<VectorOfTrust>] ["vot"],
+            set_trustmark -> trustmark[Option<TrustmarkUrl>] ["vtm"],
         }
     ];
 
+    ///
+    /// Returns the authentication methods identified by the `amr` claim, if present, as
+    /// [RFC 8176](https://tools.ietf.org/html/rfc8176) values.
+    ///
+    pub fn auth_method_ref_values(&self) -> Option<Vec<AuthenticationMethodReferenceValue>> {
+        self.auth_method_refs.as_ref().map(|auth_method_refs| {
+            auth_method_refs
+                .iter()
+                .map(AuthenticationMethodReference::value)
+                .collect()
+        })
+    }
+
     ///
     /// Returns the `sub` claim.
     ///
@@ -296,7 +392,7 @@ where
             set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
             set_phone_number_verified -> phone_number_verified[Option<bool>],
             set_address -> address[Option<AddressClaim>],
-            set_updated_at -> updated_at[Option<String>],
+            set_updated_at -> updated_at[Option<DateTime<Utc>>],
         }
     ];
 
@@ -312,6 +408,28 @@ where
     pub fn additional_claims_mut(&mut self) -> &mut AC {
         self.additional_claims.as_mut()
     }
+
+    ///
+    /// Returns all ID token claims as a JSON object.
+    ///
+    /// The returned map contains the standard claims along with any additional claims. It's
+    /// serialized from the parsed claims rather than copied from the ID token, so it differs from
+    /// the token's payload as follows:
+    ///  * Claims not recognized by `AC` are discarded during deserialization. To retain every
+    ///    claim, use `serde_json::Map<String, serde_json::Value>` as the additional claims type,
+    ///    which returns each additional claim unchanged (including non-string values).
+    ///  * Standard claims with `null` values are omitted.
+    ///  * A single `aud` string is returned as an array.
+    ///  * Timestamps (i.e., `exp`, `iat`, `auth_time`, and `updated_at`) are returned as whole
+    ///    seconds since the Unix epoch.
+    ///  * Localized claims are returned with their language tags (e.g., `name#es`), except
+    ///    that an empty language tag (e.g., `name#`) is removed.
+    ///
+    pub fn all_claims(
+        &self,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, serde_json::Error> {
+        serialize_claims(self)
+    }
 }
 impl<AC, GC> AudiencesClaim for IdTokenClaims<AC, GC>
 where
@@ -365,6 +483,8 @@ where
 {
     #[serde(bound = "AC: AdditionalClaims")]
     id_token: Option<IdToken<AC, GC, JE, JS, JT>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    authorization_details: Option<Vec<AuthorizationDetail>>,
     #[serde(bound = "EF: ExtraTokenFields", flatten)]
     extra_fields: EF,
     #[serde(skip)]
@@ -385,17 +505,38 @@ where
     pub fn new(id_token: Option<IdToken<AC, GC, JE, JS, JT>>, extra_fields: EF) -> Self {
         Self {
             id_token,
+            authorization_details: None,
             extra_fields,
             _phantom: PhantomData,
         }
     }
 
+    ///
+    /// Sets the authorization details granted by the authorization server.
+    ///
+    pub fn set_authorization_details(
+        mut self,
+        authorization_details: Option<Vec<AuthorizationDetail>>,
+    ) -> Self {
+        self.authorization_details = authorization_details;
+        self
+    }
+
     ///
     /// Returns the [`IdToken`] contained in the OAuth2 token response.
     ///
     pub fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>> {
         self.id_token.as_ref()
     }
+
+    ///
+    /// Returns the authorization details granted by the authorization server, as defined by
+    /// [Rich Authorization Requests](https://tools.ietf.org/html/rfc9396#section-7), if any.
+    ///
+    pub fn authorization_details(&self) -> Option<&Vec<AuthorizationDetail>> {
+        self.authorization_details.as_ref()
+    }
+
     ///
     /// Returns the extra fields contained in the OAuth2 token response.
     ///
@@ -417,22 +558,34 @@ where
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::marker::PhantomData;
+    use std::time::Duration;
 
     use chrono::{TimeZone, Utc};
     use oauth2::basic::BasicTokenType;
-    use oauth2::{ClientId, TokenResponse};
+    use oauth2::{ClientId, ClientSecret, TokenResponse};
     use url::Url;
 
     use crate::claims::{AdditionalClaims, EmptyAdditionalClaims, StandardClaims};
-    use crate::core::{CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreTokenResponse};
+    use crate::core::{
+        CoreGenderClaim, CoreHmacKey, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier,
+        CoreJsonWebKey, CoreJsonWebKeySet, CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey, CoreTokenResponse,
+    };
+    use crate::jwt::tests::{assert_allowed_algs, TEST_RSA_PRIV_KEY};
     use crate::jwt::JsonWebTokenAccess;
+    use crate::types::Base64UrlEncodedBytes;
     use crate::{
         AccessTokenHash, AddressClaim, AddressCountry, AddressLocality, AddressPostalCode,
         AddressRegion, Audience, AuthenticationContextClass, AuthenticationMethodReference,
-        AuthorizationCodeHash, EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName,
-        EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl,
-        EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress,
-        IssuerUrl, LanguageTag, Nonce, StreetAddress, SubjectIdentifier,
+        AuthenticationMethodReferenceValue, AuthorizationCodeHash, ClaimWarning,
+        ClaimsVerificationError, EndUserBirthday, EndUserEmail, EndUserFamilyName,
+        EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber,
+        EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl,
+        FixedClock, FormattedAddress, IssuerUrl, JsonWebTokenAlgorithm, JsonWebTokenType,
+        KeySelectionPolicy, LanguageTag, Nonce, PrivateSigningKey, SessionId,
+        SignatureVerificationError, StreetAddress, SubjectIdentifier, TrustmarkUrl, VectorOfTrust,
+        VerificationTimeOptions,
     };
 
     use super::{AudiencesClaim, IdTokenClaims, IssuerClaim};
@@ -463,6 +616,20 @@ mod tests {
             SubjectIdentifier::new("24400320".to_string())
         );
 
+        assert_eq!(
+            id_token.unverified_claims().issuer().url(),
+            claims.issuer().url()
+        );
+        let header = id_token.unverified_header();
+        assert_eq!(
+            header.alg,
+            JsonWebTokenAlgorithm::Signature(
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                PhantomData
+            )
+        );
+        assert_eq!(header.kid, None);
+
         assert_eq!(
             serde_json::to_string(&id_token).expect("failed to serialize"),
             id_token_str
@@ -506,6 +673,59 @@ mod tests {
             serde_json::to_string(&response).expect("failed to serialize"),
             response_str
         );
+
+        // Neither the access token nor the serialized ID token appear in the `Debug` output.
+        let debug = format!("{:?}", response);
+        assert!(!debug.contains("foobar"));
+        assert!(!debug.contains("eyJhbGciOiJSUzI1NiJ9"));
+    }
+
+    #[test]
+    fn test_oauth2_response_authorization_details() {
+        let response_str = "{\
+            \"access_token\":\"foobar\",\
+            \"token_type\":\"bearer\",\
+            \"authorization_details\":[{\
+                \"type\":\"account_information\",\
+                \"actions\":[\"read\"],\
+                \"accounts\":[\"DE40100100103307118608\"]\
+            }]\
+        }";
+        let response =
+            serde_json::from_str::<CoreTokenResponse>(response_str).expect("failed to deserialize");
+
+        let authorization_details = response.extra_fields().authorization_details().unwrap();
+        assert_eq!(authorization_details.len(), 1);
+        assert_eq!(
+            authorization_details[0].detail_type(),
+            "account_information"
+        );
+        assert_eq!(
+            authorization_details[0].actions(),
+            Some(&vec!["read".to_string()])
+        );
+        assert!(authorization_details[0].fields().contains_key("accounts"));
+    }
+
+    #[test]
+    fn test_standard_claims_builder() {
+        let subject = SubjectIdentifier::new("24400320".to_string());
+        let built = StandardClaims::<CoreGenderClaim>::builder(subject.clone())
+            .name(EndUserName::new("Homer Simpson".to_string()).into())
+            .email(EndUserEmail::new("homer@homersimpson.me".to_string()))
+            .email_verified(true)
+            .updated_at(Utc.timestamp(1311283970, 0))
+            .build();
+
+        assert_eq!(
+            built,
+            StandardClaims::new(subject)
+                .set_name(Some(EndUserName::new("Homer Simpson".to_string()).into()))
+                .set_email(Some(EndUserEmail::new("homer@homersimpson.me".to_string())))
+                .set_email_verified(Some(true))
+                .set_updated_at(Some(Utc.timestamp(1311283970, 0)))
+        );
+        assert_eq!(built.phone_number(), None);
     }
 
     #[test]
@@ -553,6 +773,9 @@ mod tests {
         assert_eq!(claims.authorized_party(), None);
         assert_eq!(claims.access_token_hash(), None);
         assert_eq!(claims.code_hash(), None);
+        assert_eq!(claims.session_id(), None);
+        assert_eq!(claims.vector_of_trust(), None);
+        assert_eq!(claims.trustmark(), None);
         assert_eq!(*claims.additional_claims(), EmptyAdditionalClaims {});
         assert_eq!(claims.subject(), new_claims.subject());
         assert_eq!(claims.name(), None);
@@ -597,6 +820,9 @@ mod tests {
                            \"azp\":\"dGhpc19jbGllbnQ\",\
                            \"at_hash\":\"_JPLB-GtkomFJxAOWKHPHQ\",\
                            \"c_hash\":\"VpTQii5T_8rgwxA-Wtb2Bw\",\
+                           \"sid\":\"08a5019c-17e1-4977-8f42-65a12843ea02\",\
+                           \"vot\":\"P1.Cc.Ac\",\
+                           \"vtm\":\"https://trustmark.example.org/trustmark/server.example.com\",\
                            \"sub\":\"24400320\",\
                            \"name\":\"Homer Simpson\",\
                            \"name#es\":\"Jomer Simpson\",\
@@ -765,7 +991,7 @@ mod tests {
                     postal_code: Some(AddressPostalCode::new("90210".to_string())),
                     country: Some(AddressCountry::new("US".to_string())),
                 }),
-                updated_at: Some(Utc.timestamp(1311283970, 0).to_string()),
+                updated_at: Some(Utc.timestamp(1311283970, 0)),
             },
             EmptyAdditionalClaims {},
         )
@@ -784,7 +1010,17 @@ mod tests {
         )))
         .set_code_hash(Some(AuthorizationCodeHash::new(
             "VpTQii5T_8rgwxA-Wtb2Bw".to_string(),
-        )));
+        )))
+        .set_session_id(Some(SessionId::new(
+            "08a5019c-17e1-4977-8f42-65a12843ea02".to_string(),
+        )))
+        .set_vector_of_trust(Some(VectorOfTrust::new("P1.Cc.Ac".to_string())))
+        .set_trustmark(Some(
+            TrustmarkUrl::new(
+                "https://trustmark.example.org/trustmark/server.example.com".to_string(),
+            )
+            .unwrap(),
+        ));
 
         let claims: CoreIdTokenClaims =
             serde_json::from_str(claims_json).expect("failed to deserialize");
@@ -803,6 +1039,9 @@ mod tests {
         assert_eq!(claims.authorized_party(), new_claims.authorized_party());
         assert_eq!(claims.access_token_hash(), new_claims.access_token_hash());
         assert_eq!(claims.code_hash(), new_claims.code_hash());
+        assert_eq!(claims.session_id(), new_claims.session_id());
+        assert_eq!(claims.vector_of_trust(), new_claims.vector_of_trust());
+        assert_eq!(claims.trustmark(), new_claims.trustmark());
         assert_eq!(*claims.additional_claims(), EmptyAdditionalClaims {});
         assert_eq!(claims.subject(), new_claims.subject());
         assert_eq!(claims.name(), new_claims.name());
@@ -1019,6 +1258,110 @@ mod tests {
         assert_eq!(claims.additional_claims().0["tfa_method"], "u2f");
     }
 
+    #[test]
+    fn test_all_claims() {
+        let claims = serde_json::from_str::<
+            IdTokenClaims<serde_json::Map<String, serde_json::Value>, CoreGenderClaim>,
+        >(
+            "{
+                \"iss\": \"https://server.example.com\",
+                \"sub\": \"24400320\",
+                \"aud\": \"s6BhdRkqt3\",
+                \"exp\": 1311281970,
+                \"iat\": 1311280970.5,
+                \"nonce\": null,
+                \"name#\": \"Jane Doe\",
+                \"name#es\": \"Juana Perez\",
+                \"email\": null,
+                \"tfa_method\": \"u2f\",
+                \"groups\": [\"admin\"],
+                \"login_count\": 42,
+                \"risk_score\": 0.25,
+                \"mfa\": true,
+                \"org\": {\"id\": 7, \"roles\": [\"owner\"]},
+                \"manager\": null
+            }",
+        )
+        .expect("failed to deserialize");
+
+        assert_eq!(claims.additional_claims().len(), 7);
+
+        assert_eq!(
+            claims.all_claims().expect("failed to serialize"),
+            *serde_json::json!({
+                "iss": "https://server.example.com",
+                "sub": "24400320",
+                // A single audience is returned as an array.
+                "aud": ["s6BhdRkqt3"],
+                "exp": 1311281970,
+                // Fractional seconds are truncated.
+                "iat": 1311280970,
+                // Empty language tags are removed, while other language tags are retained.
+                "name": "Jane Doe",
+                "name#es": "Juana Perez",
+                // Additional claims are returned unchanged. Standard claims with null values
+                // (`nonce` and `email`) are omitted.
+                "tfa_method": "u2f",
+                "groups": ["admin"],
+                "login_count": 42,
+                "risk_score": 0.25,
+                "mfa": true,
+                "org": {"id": 7, "roles": ["owner"]},
+                "manager": null
+            })
+            .as_object()
+            .unwrap()
+        );
+
+        // Claims not recognized by the additional claims type are discarded.
+        let claims = serde_json::from_str::<CoreIdTokenClaims>(
+            "{
+                \"iss\": \"https://server.example.com\",
+                \"sub\": \"24400320\",
+                \"aud\": [\"s6BhdRkqt3\"],
+                \"exp\": 1311281970,
+                \"iat\": 1311280970,
+                \"tfa_method\": \"u2f\"
+            }",
+        )
+        .expect("failed to deserialize");
+        let all_claims = claims.all_claims().expect("failed to serialize");
+        assert!(!all_claims.contains_key("tfa_method"));
+        assert_eq!(all_claims.len(), 5);
+    }
+
+    #[test]
+    fn test_updated_at_string() {
+        let claims = serde_json::from_str::<CoreIdTokenClaims>(
+            "{
+                \"iss\": \"https://server.example.com\",
+                \"sub\": \"24400320\",
+                \"aud\": [\"s6BhdRkqt3\"],
+                \"exp\": 1311281970,
+                \"iat\": 1311280970,
+                \"updated_at\": \"1311283970\"
+            }",
+        )
+        .expect("failed to deserialize");
+        assert_eq!(claims.updated_at(), Some(Utc.timestamp(1311283970, 0)));
+
+        // Timestamps are always serialized as numbers.
+        let serialized_claims = serde_json::to_string(&claims).expect("failed to serialize");
+        assert!(serialized_claims.contains("\"updated_at\":1311283970"));
+
+        serde_json::from_str::<CoreIdTokenClaims>(
+            "{
+                \"iss\": \"https://server.example.com\",
+                \"sub\": \"24400320\",
+                \"aud\": [\"s6BhdRkqt3\"],
+                \"exp\": 1311281970,
+                \"iat\": 1311280970,
+                \"updated_at\": \"yesterday\"
+            }",
+        )
+        .expect_err("non-numeric updated_at should fail to deserialize");
+    }
+
     #[test]
     fn test_audiences_claim() {
         let claims = serde_json::from_str::<CoreIdTokenClaims>(
@@ -1064,4 +1407,529 @@ mod tests {
         verify_issuer(&claims);
         verify_issuer(&&claims);
     }
+
+    #[test]
+    fn test_clock_skew() {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1311281970, 0),
+            Utc.timestamp(1311280970, 0),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        let id_token = CoreIdToken::new(
+            claims,
+            &CoreHmacKey::new("secret"),
+            CoreJwsSigningAlgorithm::HmacSha256,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let verifier = |time_options: VerificationTimeOptions<'static>| {
+            CoreIdTokenVerifier::new_confidential_client(
+                ClientId::new("s6BhdRkqt3".to_string()),
+                ClientSecret::new("secret".to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![]),
+            )
+            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+            .set_time_options(time_options)
+        };
+        let one_minute_late =
+            VerificationTimeOptions::new().set_clock(FixedClock::new(Utc.timestamp(1311282030, 0)));
+
+        match id_token.claims(&verifier(one_minute_late.clone()), |_: Option<&Nonce>| {
+            Ok(())
+        }) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        id_token
+            .claims(
+                &verifier(one_minute_late.set_clock_skew(Duration::from_secs(120))),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .expect("expiration should be within the allowed clock skew");
+    }
+
+    #[test]
+    fn test_key_selection_policy() {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1311281970, 0),
+            Utc.timestamp(1311280970, 0),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        // Neither the signing key nor the JWT has a key ID.
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let id_token = CoreIdToken::new(
+            claims,
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let key = signing_key.as_verification_key();
+        let mut other_key = key.clone();
+        other_key.n = Some(Base64UrlEncodedBytes::new(vec![0xc3; 256]));
+
+        let verify = |keys: Vec<CoreJsonWebKey>, policy: KeySelectionPolicy| {
+            let verifier = CoreIdTokenVerifier::new_public_client(
+                ClientId::new("s6BhdRkqt3".to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(keys),
+            )
+            .set_key_selection_policy(policy)
+            .set_time_options(
+                VerificationTimeOptions::new()
+                    .set_clock(FixedClock::new(Utc.timestamp(1311280970, 0))),
+            );
+            id_token
+                .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+                .map(|_| ())
+        };
+
+        // By default, more than one eligible key is ambiguous.
+        match verify(
+            vec![other_key.clone(), key.clone()],
+            KeySelectionPolicy::new(),
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::AmbiguousKeyId(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        verify(
+            vec![key.clone(), other_key.clone()],
+            KeySelectionPolicy::new().set_try_all_matching_keys(true),
+        )
+        .expect("one of the keys should verify the signature");
+        verify(
+            vec![other_key.clone(), key.clone()],
+            KeySelectionPolicy::new().set_newest_first(true),
+        )
+        .expect("the newest key should be selected");
+        match verify(
+            vec![key.clone(), other_key.clone()],
+            KeySelectionPolicy::new().set_newest_first(true),
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::CryptoError(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Keys that don't permit signing or that specify a different algorithm can be excluded.
+        let mut unspecified_use_key = other_key.clone();
+        unspecified_use_key.use_ = None;
+        verify(
+            vec![unspecified_use_key, key.clone()],
+            KeySelectionPolicy::new().set_require_signature_use(true),
+        )
+        .expect("the key without a usage should be excluded");
+        let mut other_alg_key = other_key;
+        other_alg_key.alg = Some(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512);
+        verify(
+            vec![other_alg_key.clone(), key.clone()],
+            KeySelectionPolicy::new().set_filter_by_alg(true),
+        )
+        .expect("the key for a different algorithm should be excluded");
+        match verify(vec![other_alg_key, key], KeySelectionPolicy::new()) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::AmbiguousKeyId(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_allowed_algs() {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1311281970, 0),
+            Utc.timestamp(1311280970, 0),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+        )
+        .set_time_options(
+            VerificationTimeOptions::new().set_clock(FixedClock::new(Utc.timestamp(1311280970, 0))),
+        );
+
+        assert_allowed_algs(
+            |alg| CoreIdToken::new(claims.clone(), &signing_key, alg, None, None).unwrap(),
+            verifier,
+            CoreIdTokenVerifier::set_allowed_algs,
+            CoreIdTokenVerifier::allow_any_alg,
+            |id_token: &CoreIdToken, verifier: &CoreIdTokenVerifier| {
+                id_token
+                    .claims(verifier, |_: Option<&Nonce>| Ok(()))
+                    .map(|_| ())
+            },
+        );
+    }
+
+    #[test]
+    fn test_allowed_jwt_types() {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1311281970, 0),
+            Utc.timestamp(1311280970, 0),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        let signed_id_token = CoreIdToken::new(
+            claims,
+            &CoreHmacKey::new("secret"),
+            CoreJwsSigningAlgorithm::HmacSha256,
+            None,
+            None,
+        )
+        .unwrap();
+        let serialized = serde_json::to_value(&signed_id_token).unwrap();
+        let payload = serialized.as_str().unwrap().split('.').nth(1).unwrap();
+
+        // Replaces the header and strips the signature.
+        let with_header = |header: &str| -> CoreIdToken {
+            serde_json::from_value(serde_json::Value::String(format!(
+                "{}.{}.",
+                base64::encode_config(header, base64::URL_SAFE_NO_PAD),
+                payload
+            )))
+            .unwrap()
+        };
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .insecure_disable_signature_check()
+        .set_time_fn(|| Utc.timestamp(1311280971, 0));
+        let verify = |id_token: &CoreIdToken, verifier: &CoreIdTokenVerifier| {
+            id_token
+                .claims(verifier, |_: Option<&Nonce>| Ok(()))
+                .map(|_| ())
+        };
+
+        // By default, the `typ` header must either be omitted or equivalent to `JWT`.
+        for header in &[
+            "{\"alg\":\"none\"}",
+            "{\"alg\":\"none\",\"typ\":\"JWT\"}",
+            "{\"alg\":\"none\",\"typ\":\"application/jwt\"}",
+        ] {
+            verify(&with_header(header), &verifier)
+                .unwrap_or_else(|err| panic!("{} should be accepted: {:?}", header, err));
+        }
+        let logout_token = with_header("{\"alg\":\"none\",\"typ\":\"logout+jwt\"}");
+        match verify(&logout_token, &verifier) {
+            Err(ClaimsVerificationError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let strict_verifier = verifier.clone().require_jwt_type(true);
+        match verify(&with_header("{\"alg\":\"none\"}"), &strict_verifier) {
+            Err(ClaimsVerificationError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let logout_verifier = verifier
+            .clone()
+            .set_allowed_jwt_types(vec![JsonWebTokenType::logout_token()]);
+        verify(&logout_token, &logout_verifier).expect("logout+jwt should be accepted");
+        match verify(
+            &with_header("{\"alg\":\"none\",\"typ\":\"JWT\"}"),
+            &logout_verifier,
+        ) {
+            Err(ClaimsVerificationError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Critical header extensions are rejected unless the application understands them.
+        let critical_token =
+            with_header("{\"alg\":\"none\",\"crit\":[\"exp\"],\"exp\":1363284000}");
+        match verify(&critical_token, &verifier) {
+            Err(ClaimsVerificationError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let critical_verifier = verifier.set_understood_critical_headers(vec!["exp".to_string()]);
+        verify(&critical_token, &critical_verifier).expect("understood crit should be accepted");
+        for header in &[
+            "{\"alg\":\"none\",\"crit\":[]}",
+            "{\"alg\":\"none\",\"crit\":[\"exp\"]}",
+            "{\"alg\":\"none\",\"crit\":[\"exp\",\"typ\"],\"exp\":1363284000,\"typ\":\"JWT\"}",
+            "{\"alg\":\"none\",\"crit\":[\"exp\",\"b64\"],\"exp\":1363284000,\"b64\":false}",
+        ] {
+            match verify(&with_header(header), &critical_verifier) {
+                Err(ClaimsVerificationError::Unsupported(_)) => {}
+                other => panic!("{} should be rejected: {:?}", header, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_refresh_consistency() {
+        let client_id = ClientId::new("s6BhdRkqt3".to_string());
+        let client_secret = ClientSecret::new("secret".to_string());
+        let issuer = IssuerUrl::new("https://server.example.com".to_string()).unwrap();
+        let signing_key = CoreHmacKey::new("secret");
+
+        let new_claims = |subject: &str, auth_time: i64| {
+            CoreIdTokenClaims::new(
+                issuer.clone(),
+                vec![Audience::new("s6BhdRkqt3".to_string())],
+                Utc.timestamp(1311281970, 0),
+                Utc.timestamp(1311280970, 0),
+                StandardClaims::new(SubjectIdentifier::new(subject.to_string())),
+                EmptyAdditionalClaims {},
+            )
+            .set_auth_time(Some(Utc.timestamp(auth_time, 0)))
+        };
+        let original_claims = new_claims("24400320", 1311280969);
+
+        let verifier = CoreIdTokenVerifier::new_confidential_client(
+            client_id.clone(),
+            client_secret,
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_fn(|| Utc.timestamp(1311280971, 0))
+        .for_refresh(&original_claims);
+
+        let sign = |claims: CoreIdTokenClaims| {
+            CoreIdToken::new(
+                claims,
+                &signing_key,
+                CoreJwsSigningAlgorithm::HmacSha256,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        sign(new_claims("24400320", 1311280969))
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("refreshed ID token should be consistent with the original");
+
+        match sign(new_claims("other", 1311280969)).claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::InvalidSubject(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match sign(new_claims("24400320", 1311280970)).claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match sign(
+            new_claims("24400320", 1311280969)
+                .set_nonce(Some(Nonce::new("unexpected".to_string()))),
+        )
+        .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_required_auth_methods() {
+        let issuer = IssuerUrl::new("https://server.example.com".to_string()).unwrap();
+        let signing_key = CoreHmacKey::new("secret");
+        let sign = |auth_method_refs: Option<Vec<&str>>| {
+            let claims = CoreIdTokenClaims::new(
+                issuer.clone(),
+                vec![Audience::new("s6BhdRkqt3".to_string())],
+                Utc.timestamp(1311281970, 0),
+                Utc.timestamp(1311280970, 0),
+                StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+                EmptyAdditionalClaims {},
+            )
+            .set_auth_method_refs(auth_method_refs.map(|auth_method_refs| {
+                auth_method_refs
+                    .into_iter()
+                    .map(|amr| AuthenticationMethodReference::new(amr.to_string()))
+                    .collect()
+            }));
+            CoreIdToken::new(
+                claims,
+                &signing_key,
+                CoreJwsSigningAlgorithm::HmacSha256,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        let verifier = CoreIdTokenVerifier::new_confidential_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            ClientSecret::new("secret".to_string()),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_fn(|| Utc.timestamp(1311280971, 0))
+        .require_auth_methods(vec![
+            AuthenticationMethodReferenceValue::Mfa,
+            AuthenticationMethodReferenceValue::Hwk,
+        ]);
+
+        let id_token = sign(Some(vec!["pwd", "hwk", "mfa"]));
+        let claims = id_token
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("required authentication methods should be present");
+        assert_eq!(
+            claims.auth_method_ref_values(),
+            Some(vec![
+                AuthenticationMethodReferenceValue::Pwd,
+                AuthenticationMethodReferenceValue::Hwk,
+                AuthenticationMethodReferenceValue::Mfa,
+            ])
+        );
+
+        for auth_method_refs in vec![None, Some(vec!["pwd", "mfa"])] {
+            match sign(auth_method_refs).claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+                Err(ClaimsVerificationError::InvalidAuthMethod(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+    #[test]
+    fn test_lenient_claims() {
+        let header_base64 = base64::encode_config("{\"alg\":\"HS256\"}", base64::URL_SAFE_NO_PAD);
+        let payload_base64 = base64::encode_config(
+            "{
+                \"iss\": \"https://server.example.com\",
+                \"aud\": \"s6BhdRkqt3\",
+                \"exp\": \"1311281970\",
+                \"iat\": 1311280970,
+                \"sub\": \"24400320\",
+                \"nonce\": null,
+                \"email_verified\": \"true\",
+                \"custom\": null
+            }",
+            base64::URL_SAFE_NO_PAD,
+        );
+        let signing_input = format!("{}.{}", header_base64, payload_base64);
+        let signature = CoreHmacKey::new("secret")
+            .sign(
+                &CoreJwsSigningAlgorithm::HmacSha256,
+                signing_input.as_bytes(),
+            )
+            .unwrap();
+        let id_token_str = format!(
+            "{}.{}",
+            signing_input,
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+        );
+
+        id_token_str
+            .parse::<CoreIdToken>()
+            .expect_err("string exp claim should fail to parse strictly");
+
+        let (id_token, warnings) = CoreIdToken::from_str_lenient(&id_token_str).unwrap();
+        // The original serialization is preserved.
+        assert_eq!(id_token.to_string(), id_token_str);
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        for expected_warning in &[
+            ClaimWarning::ConvertedClaim("aud".to_string(), serde_json::json!(["s6BhdRkqt3"])),
+            ClaimWarning::ConvertedClaim("exp".to_string(), serde_json::json!(1311281970)),
+            ClaimWarning::ConvertedClaim("email_verified".to_string(), serde_json::json!(true)),
+            ClaimWarning::NullClaim("nonce".to_string()),
+        ] {
+            assert!(warnings.contains(expected_warning), "{:?}", warnings);
+        }
+
+        let verifier = CoreIdTokenVerifier::new_confidential_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            ClientSecret::new("secret".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_fn(|| Utc.timestamp(1311280971, 0));
+        let (claims, verified_warnings) = verifier
+            .verify_lenient::<CoreJweContentEncryptionAlgorithm, _, _, _>(
+                &id_token_str,
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .unwrap();
+        assert_eq!(claims.expiration(), Utc.timestamp(1311281970, 0));
+        assert_eq!(claims.email_verified(), Some(true));
+        assert_eq!(claims.nonce(), None);
+        assert_eq!(verified_warnings, warnings);
+    }
+    #[test]
+    fn test_trusted_issuer_alias() {
+        IssuerUrl::new("accounts.google.com".to_string())
+            .expect_err("issuer URLs must include a scheme");
+
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new_scheme_optional("accounts.google.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1311281970, 0),
+            Utc.timestamp(1311280970, 0),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        let id_token = CoreIdToken::new(
+            claims,
+            &CoreHmacKey::new("secret"),
+            CoreJwsSigningAlgorithm::HmacSha256,
+            None,
+            None,
+        )
+        .unwrap()
+        .to_string()
+        .parse::<CoreIdToken>()
+        .expect("scheme-less issuer should be parsed");
+        assert_eq!(
+            id_token.unverified_claims().issuer().as_str(),
+            "accounts.google.com"
+        );
+
+        let verifier = CoreIdTokenVerifier::new_confidential_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            ClientSecret::new("secret".to_string()),
+            IssuerUrl::new("https://accounts.google.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_fn(|| Utc.timestamp(1311280971, 0));
+
+        match id_token.claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match id_token.claims(
+            &verifier
+                .clone()
+                .add_trusted_issuer_alias("evil.example.com".to_string()),
+            |_: Option<&Nonce>| Ok(()),
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        id_token
+            .claims(
+                &verifier.add_trusted_issuer_alias("accounts.google.com".to_string()),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .expect("trusted issuer alias should be accepted");
+    }
 }
@@ -0,0 +1,732 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use futures::lock::Mutex;
+
+use crate::clock::{Clock, SystemClock};
+use crate::core::{
+    CoreClaimName, CoreClaimType, CoreClientAuthMethod, CoreGrantType,
+    CoreJweKeyManagementAlgorithm, CoreResponseMode, CoreResponseType, CoreSubjectIdentifierType,
+};
+use crate::error::{ClassifiedError, ErrorKind};
+use crate::http_client::{AsyncHttpClient, RequestOptions};
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, Client, ClientId, ClientSecret, DiscoveryError,
+    EmptyAdditionalProviderMetadata, ErrorResponse, GenderClaim, IssuerUrl, JsonWebKey,
+    JsonWebKeyId, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, ProviderMetadata, RedirectUrl, TokenResponse, TokenType,
+};
+
+// By default, provider metadata and JSON Web Key Sets are rediscovered after this many seconds so
+// that rotated signing keys are eventually observed.
+const DEFAULT_METADATA_TTL_SECS: u64 = 3600;
+
+// By default, the JSON Web Key Set of each issuer is refreshed at most once per this many seconds
+// so that tokens referencing unknown keys can't trigger a request to the provider for each token.
+const DEFAULT_MIN_JWKS_REFRESH_INTERVAL_SECS: u64 = 30;
+
+// The registry discovers provider metadata using the standard `Core*` types for the metadata
+// fields that don't correspond to type parameters of `Client`.
+type RegistryProviderMetadata<AD, JE, JS, JT, JU, K> = ProviderMetadata<
+    EmptyAdditionalProviderMetadata,
+    AD,
+    CoreClientAuthMethod,
+    CoreClaimName,
+    CoreClaimType,
+    CoreGrantType,
+    JE,
+    CoreJweKeyManagementAlgorithm,
+    JS,
+    JT,
+    JU,
+    K,
+    CoreResponseMode,
+    CoreResponseType,
+    CoreSubjectIdentifierType,
+>;
+
+struct Tenant<PM, CL> {
+    client_id: ClientId,
+    client_secret: Option<ClientSecret>,
+    redirect_url: Option<RedirectUrl>,
+    // Holding this lock while discovering makes discovery single-flight for each issuer.
+    cached: Mutex<Option<CachedProvider<PM, CL>>>,
+}
+
+struct CachedProvider<PM, CL> {
+    provider_metadata: PM,
+    client: CL,
+    discovered_at: DateTime<Utc>,
+    jwks_fetched_at: DateTime<Utc>,
+}
+
+///
+/// Registry of the OpenID Connect Providers trusted by a multi-tenant application, keyed by
+/// issuer.
+///
+/// Each tenant is registered with [`IssuerRegistry::register`] along with the client credentials
+/// issued to the application by that tenant's provider. The first call to
+/// [`IssuerRegistry::client`] for an issuer performs OpenID Connect Discovery and fetches the
+/// provider's JSON Web Key Set, and subsequent calls return a [`Client`] configured from the
+/// cached metadata. Discovery is single-flight: concurrent callers requesting the same issuer wait
+/// for an in-progress discovery instead of each issuing their own requests, while discovery for
+/// other issuers proceeds independently.
+///
+/// Cached metadata is rediscovered once it is older than the metadata TTL (one hour by default).
+/// When an ID token references a signing key that isn't in the cached JSON Web Key Set (e.g.,
+/// because the provider rotated its keys), call [`IssuerRegistry::refresh_jwks`] to fetch the
+/// current keys without repeating discovery. Key sets are refreshed at most once per minimum
+/// refresh interval (30 seconds by default) for each issuer.
+///
+/// The provider metadata retained by each client (see [`Client::provider_metadata`]) uses
+/// [`EmptyAdditionalProviderMetadata`] and the `Core*` types for the metadata fields that don't
+/// correspond to type parameters of [`Client`].
+///
+/// Requires "net" feature.
+///
+pub struct IssuerRegistry<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    tenants: RwLock<
+        HashMap<
+            IssuerUrl,
+            Arc<
+                Tenant<
+                    RegistryProviderMetadata<AD, JE, JS, JT, JU, K>,
+                    Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+                >,
+            >,
+        >,
+    >,
+    metadata_ttl: Duration,
+    min_jwks_refresh_interval: Duration,
+    request_options: RequestOptions,
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+    IssuerRegistry<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    RegistryProviderMetadata<AD, JE, JS, JT, JU, K>: Send + Sync + 'static,
+{
+    ///
+    /// Instantiates an empty registry.
+    ///
+    pub fn new() -> Self {
+        Self {
+            tenants: RwLock::new(HashMap::new()),
+            metadata_ttl: Duration::from_secs(DEFAULT_METADATA_TTL_SECS),
+            min_jwks_refresh_interval: Duration::from_secs(DEFAULT_MIN_JWKS_REFRESH_INTERVAL_SECS),
+            request_options: RequestOptions::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    ///
+    /// Specifies how long discovered provider metadata and JSON Web Key Sets are cached before
+    /// being rediscovered.
+    ///
+    pub fn set_metadata_ttl(mut self, metadata_ttl: Duration) -> Self {
+        self.metadata_ttl = metadata_ttl;
+        self
+    }
+
+    ///
+    /// Specifies the minimum interval between JSON Web Key Set fetches for each issuer when
+    /// calling [`IssuerRegistry::refresh_jwks`].
+    ///
+    /// Within this interval, requests to refresh the key set fail with
+    /// [`IssuerRegistryError::KeyNotFound`] instead of fetching it again, which prevents tokens
+    /// referencing unknown keys from causing a request to the provider for every token.
+    ///
+    pub fn set_min_jwks_refresh_interval(mut self, min_jwks_refresh_interval: Duration) -> Self {
+        self.min_jwks_refresh_interval = min_jwks_refresh_interval;
+        self
+    }
+
+    ///
+    /// Specifies the options to apply to each discovery and JSON Web Key Set request.
+    ///
+    pub fn set_request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time, which is used for expiring cached provider
+    /// metadata and limiting the rate of JSON Web Key Set refreshes.
+    ///
+    pub fn set_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    ///
+    /// Registers a tenant's OpenID Connect Provider along with the client credentials issued to
+    /// the application by that provider.
+    ///
+    /// Discovery is deferred until a client is first requested for the issuer. Registering an
+    /// issuer that is already registered replaces its client credentials and discards any cached
+    /// provider metadata.
+    ///
+    pub fn register(
+        &self,
+        issuer_url: IssuerUrl,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+        redirect_url: Option<RedirectUrl>,
+    ) {
+        let tenant = Tenant {
+            client_id,
+            client_secret,
+            redirect_url,
+            cached: Mutex::new(None),
+        };
+        self.tenants
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(issuer_url, Arc::new(tenant));
+    }
+
+    ///
+    /// Removes a tenant's OpenID Connect Provider from the registry, returning `true` if the
+    /// issuer was registered.
+    ///
+    pub fn unregister(&self, issuer_url: &IssuerUrl) -> bool {
+        self.tenants
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(issuer_url)
+            .is_some()
+    }
+
+    ///
+    /// Returns the registered issuers, in no particular order.
+    ///
+    pub fn issuers(&self) -> Vec<IssuerUrl> {
+        self.tenants
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Returns a client for the specified issuer, first discovering the provider metadata and JSON
+    /// Web Key Set if they aren't cached or have expired.
+    ///
+    pub async fn client<C>(
+        &self,
+        issuer_url: &IssuerUrl,
+        http_client: &C,
+    ) -> Result<Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>, IssuerRegistryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let tenant = match self.tenant(issuer_url) {
+            Some(tenant) => tenant,
+            None => return Err(IssuerRegistryError::UnknownIssuer(issuer_url.clone())),
+        };
+        let mut cached = tenant.cached.lock().await;
+        if let Some(ref cached) = *cached {
+            if !self.is_expired(cached.discovered_at) {
+                return Ok(cached.client.clone());
+            }
+        }
+
+        let provider_metadata = RegistryProviderMetadata::discover_with_options(
+            issuer_url.clone(),
+            http_client,
+            &self.request_options,
+        )
+        .await
+        .map_err(IssuerRegistryError::Discovery)?;
+        let now = self.clock.now();
+        let client = Self::cache(&mut *cached, &tenant, provider_metadata, now, now);
+        Ok(client)
+    }
+
+    ///
+    /// Fetches the current JSON Web Key Set of the specified issuer and returns a client that uses
+    /// it, provided that the key set contains a key with the specified key ID.
+    ///
+    /// This should be called when an ID token references a signing key that isn't in the cached
+    /// JSON Web Key Set. The cached provider metadata is reused if available; otherwise, discovery
+    /// is performed as in [`IssuerRegistry::client`]. Concurrent refreshes for the same issuer are
+    /// serialized, and the cached client is returned without fetching the key set if it already
+    /// contains the key (e.g., because a concurrent refresh fetched it).
+    ///
+    /// The key set is fetched at most once per minimum refresh interval (see
+    /// [`IssuerRegistry::set_min_jwks_refresh_interval`]). Within this interval, or if the fetched
+    /// key set doesn't contain the key, this method returns [`IssuerRegistryError::KeyNotFound`].
+    ///
+    pub async fn refresh_jwks<C>(
+        &self,
+        issuer_url: &IssuerUrl,
+        key_id: &JsonWebKeyId,
+        http_client: &C,
+    ) -> Result<Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>, IssuerRegistryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let tenant = match self.tenant(issuer_url) {
+            Some(tenant) => tenant,
+            None => return Err(IssuerRegistryError::UnknownIssuer(issuer_url.clone())),
+        };
+        let mut cached = tenant.cached.lock().await;
+        let (provider_metadata, discovered_at) = match cached.take() {
+            Some(cached_provider) => {
+                if Self::has_key(&cached_provider.provider_metadata, key_id) {
+                    let client = cached_provider.client.clone();
+                    *cached = Some(cached_provider);
+                    return Ok(client);
+                } else if !self.may_refresh_jwks(cached_provider.jwks_fetched_at) {
+                    *cached = Some(cached_provider);
+                    return Err(IssuerRegistryError::KeyNotFound(key_id.clone()));
+                }
+
+                let jwks = JsonWebKeySet::fetch_with_options(
+                    cached_provider.provider_metadata.jwks_uri(),
+                    http_client,
+                    &self.request_options,
+                )
+                .await;
+                match jwks {
+                    Ok(jwks) => (
+                        cached_provider.provider_metadata.set_jwks(jwks),
+                        // Refreshing the keys doesn't extend the lifetime of the other metadata.
+                        cached_provider.discovered_at,
+                    ),
+                    Err(err) => {
+                        *cached = Some(cached_provider);
+                        return Err(IssuerRegistryError::Discovery(err));
+                    }
+                }
+            }
+            None => (
+                RegistryProviderMetadata::discover_with_options(
+                    issuer_url.clone(),
+                    http_client,
+                    &self.request_options,
+                )
+                .await
+                .map_err(IssuerRegistryError::Discovery)?,
+                self.clock.now(),
+            ),
+        };
+        let has_key = Self::has_key(&provider_metadata, key_id);
+        let client = Self::cache(
+            &mut *cached,
+            &tenant,
+            provider_metadata,
+            discovered_at,
+            self.clock.now(),
+        );
+        if has_key {
+            Ok(client)
+        } else {
+            Err(IssuerRegistryError::KeyNotFound(key_id.clone()))
+        }
+    }
+
+    ///
+    /// Discards the cached provider metadata and JSON Web Key Set for the specified issuer, if any,
+    /// so that the next call to [`IssuerRegistry::client`] repeats discovery.
+    ///
+    pub async fn invalidate(&self, issuer_url: &IssuerUrl) {
+        if let Some(tenant) = self.tenant(issuer_url) {
+            *tenant.cached.lock().await = None;
+        }
+    }
+
+    // The tenant is cloned out of the map so that the map isn't locked while awaiting discovery.
+    fn tenant(
+        &self,
+        issuer_url: &IssuerUrl,
+    ) -> Option<
+        Arc<
+            Tenant<
+                RegistryProviderMetadata<AD, JE, JS, JT, JU, K>,
+                Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+            >,
+        >,
+    > {
+        self.tenants
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(issuer_url)
+            .cloned()
+    }
+
+    fn is_expired(&self, discovered_at: DateTime<Utc>) -> bool {
+        chrono::Duration::from_std(self.metadata_ttl)
+            .ok()
+            .and_then(|ttl| discovered_at.checked_add_signed(ttl))
+            .map_or(false, |expires_at| self.clock.now() >= expires_at)
+    }
+
+    fn may_refresh_jwks(&self, jwks_fetched_at: DateTime<Utc>) -> bool {
+        chrono::Duration::from_std(self.min_jwks_refresh_interval)
+            .ok()
+            .and_then(|interval| jwks_fetched_at.checked_add_signed(interval))
+            .map_or(true, |refresh_at| self.clock.now() >= refresh_at)
+    }
+
+    fn has_key(
+        provider_metadata: &RegistryProviderMetadata<AD, JE, JS, JT, JU, K>,
+        key_id: &JsonWebKeyId,
+    ) -> bool {
+        provider_metadata
+            .jwks()
+            .keys()
+            .iter()
+            .any(|key| key.key_id() == Some(key_id))
+    }
+
+    fn cache(
+        cached: &mut Option<
+            CachedProvider<
+                RegistryProviderMetadata<AD, JE, JS, JT, JU, K>,
+                Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+            >,
+        >,
+        tenant: &Tenant<
+            RegistryProviderMetadata<AD, JE, JS, JT, JU, K>,
+            Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+        >,
+        provider_metadata: RegistryProviderMetadata<AD, JE, JS, JT, JU, K>,
+        discovered_at: DateTime<Utc>,
+        jwks_fetched_at: DateTime<Utc>,
+    ) -> Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> {
+        let mut client = Client::from_provider_metadata(
+            provider_metadata.clone(),
+            tenant.client_id.clone(),
+            tenant.client_secret.clone(),
+        );
+        if let Some(ref redirect_url) = tenant.redirect_url {
+            client = client.set_redirect_uri(redirect_url.clone());
+        }
+        *cached = Some(CachedProvider {
+            provider_metadata,
+            client: client.clone(),
+            discovered_at,
+            jwks_fetched_at,
+        });
+        client
+    }
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> Default
+    for IssuerRegistry<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    RegistryProviderMetadata<AD, JE, JS, JT, JU, K>: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> Debug
+    for IssuerRegistry<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // The cached clients are omitted to avoid blocking on their locks and leaking secrets.
+        let issuers = self
+            .tenants
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        f.debug_struct("IssuerRegistry")
+            .field("issuers", &issuers)
+            .field("metadata_ttl", &self.metadata_ttl)
+            .field("min_jwks_refresh_interval", &self.min_jwks_refresh_interval)
+            .field("request_options", &self.request_options)
+            .finish()
+    }
+}
+
+///
+/// Error obtaining a client from an [`IssuerRegistry`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum IssuerRegistryError<RE>
+where
+    RE: Fail,
+{
+    ///
+    /// Failed to discover the provider metadata or fetch the JSON Web Key Set.
+    ///
+    #[fail(display = "Failed to discover OpenID Connect Provider")]
+    Discovery(#[cause] DiscoveryError<RE>),
+    ///
+    /// The issuer hasn't been registered via [`IssuerRegistry::register`].
+    ///
+    #[fail(display = "Unknown issuer")]
+    UnknownIssuer(IssuerUrl),
+    ///
+    /// The issuer's JSON Web Key Set doesn't contain a key with the specified key ID, either after
+    /// refreshing it or because it was refreshed within the minimum refresh interval (see
+    /// [`IssuerRegistry::set_min_jwks_refresh_interval`]).
+    ///
+    #[fail(display = "Signing key not found")]
+    KeyNotFound(JsonWebKeyId),
+}
+impl<RE> ClassifiedError for IssuerRegistryError<RE>
+where
+    RE: Fail,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            IssuerRegistryError::Discovery(err) => err.error_kind(),
+            IssuerRegistryError::UnknownIssuer(_) => ErrorKind::ClientError,
+            IssuerRegistryError::KeyNotFound(_) => ErrorKind::Verification,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::{Duration, TimeZone, Utc};
+    use futures::executor::block_on;
+    use futures::future::{join, ready};
+
+    use crate::clock::FixedClock;
+    use crate::core::CoreIssuerRegistry;
+    use crate::http_types::{Request, Response, StatusCode};
+    use crate::jwt::tests::TEST_RSA_PUB_KEY;
+    use crate::{ClientId, ClientSecret, IssuerUrl, JsonWebKeyId, RedirectUrl};
+
+    use super::IssuerRegistryError;
+
+    fn provider_metadata(issuer: &str) -> String {
+        format!(
+            "{{\
+             \"issuer\": \"{issuer}\", \
+             \"authorization_endpoint\": \"{issuer}/authorize\", \
+             \"token_endpoint\": \"{issuer}/token\", \
+             \"jwks_uri\": \"{issuer}/jwks\", \
+             \"response_types_supported\": [\"code\"], \
+             \"subject_types_supported\": [\"public\"], \
+             \"id_token_signing_alg_values_supported\": [\"RS256\"]\
+             }}",
+            issuer = issuer
+        )
+    }
+
+    #[test]
+    fn test_issuer_registry() {
+        let discovery_count = AtomicUsize::new(0);
+        let jwks_count = AtomicUsize::new(0);
+        let http_client = |request: Request| {
+            let url = request.url().clone();
+            let issuer = format!("https://{}", url.host_str().unwrap());
+            let body = if url.path() == "/.well-known/openid-configuration" {
+                discovery_count.fetch_add(1, Ordering::SeqCst);
+                provider_metadata(&issuer)
+            } else {
+                assert_eq!("/jwks", url.path());
+                jwks_count.fetch_add(1, Ordering::SeqCst);
+                format!("{{\"keys\": [{}]}}", TEST_RSA_PUB_KEY)
+            };
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(body);
+            ready(Ok::<_, io::Error>(response))
+        };
+
+        let clock = FixedClock::new(Utc.timestamp(1544932149, 0));
+        let registry = CoreIssuerRegistry::new().set_clock(clock.clone());
+        let tenant_a = IssuerUrl::new("https://tenant-a.example".to_string()).unwrap();
+        let tenant_b = IssuerUrl::new("https://tenant-b.example".to_string()).unwrap();
+        registry.register(
+            tenant_a.clone(),
+            ClientId::new("client-a".to_string()),
+            Some(ClientSecret::new("secret-a".to_string())),
+            Some(RedirectUrl::new("https://app.example/cb".to_string()).unwrap()),
+        );
+        registry.register(
+            tenant_b.clone(),
+            ClientId::new("client-b".to_string()),
+            None,
+            None,
+        );
+
+        // Discovery is deferred until a client is requested.
+        assert_eq!(0, discovery_count.load(Ordering::SeqCst));
+
+        // Concurrent requests for the same issuer share a single discovery.
+        let (client_a1, client_a2) = block_on(join(
+            registry.client(&tenant_a, &http_client),
+            registry.client(&tenant_a, &http_client),
+        ));
+        let (client_a1, client_a2) = (client_a1.unwrap(), client_a2.unwrap());
+        assert_eq!(1, discovery_count.load(Ordering::SeqCst));
+        assert_eq!(1, jwks_count.load(Ordering::SeqCst));
+        assert_eq!("client-a", client_a1.client_id.as_str());
+        assert_eq!("client-a", client_a2.client_id.as_str());
+        assert_eq!(
+            "https://app.example/cb",
+            client_a1.redirect_url.as_ref().unwrap().as_str()
+        );
+        assert_eq!(1, client_a1.jwks.keys().len());
+
+        let client_b = block_on(registry.client(&tenant_b, &http_client)).unwrap();
+        assert_eq!("client-b", client_b.client_id.as_str());
+        assert_eq!(tenant_b, client_b.issuer);
+        assert_eq!(2, discovery_count.load(Ordering::SeqCst));
+
+        // Refreshing the key set doesn't repeat discovery, and keys that are already cached don't
+        // require fetching it.
+        let key_id = JsonWebKeyId::new("bilbo.baggins@hobbiton.example".to_string());
+        block_on(registry.refresh_jwks(&tenant_a, &key_id, &http_client)).unwrap();
+        assert_eq!(2, discovery_count.load(Ordering::SeqCst));
+        assert_eq!(2, jwks_count.load(Ordering::SeqCst));
+
+        // Cached metadata is rediscovered once it expires.
+        clock.advance(Duration::minutes(59));
+        block_on(registry.client(&tenant_a, &http_client)).unwrap();
+        assert_eq!(2, discovery_count.load(Ordering::SeqCst));
+        clock.advance(Duration::minutes(1));
+        block_on(registry.client(&tenant_a, &http_client)).unwrap();
+        assert_eq!(3, discovery_count.load(Ordering::SeqCst));
+
+        block_on(registry.invalidate(&tenant_b));
+        block_on(registry.client(&tenant_b, &http_client)).unwrap();
+        assert_eq!(4, discovery_count.load(Ordering::SeqCst));
+
+        assert!(registry.unregister(&tenant_b));
+        match block_on(registry.client(&tenant_b, &http_client)) {
+            Err(IssuerRegistryError::UnknownIssuer(issuer)) => assert_eq!(tenant_b, issuer),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(vec![tenant_a], registry.issuers());
+    }
+
+    #[test]
+    fn test_issuer_registry_refresh_jwks() {
+        let jwks_count = AtomicUsize::new(0);
+        let http_client = |request: Request| {
+            let url = request.url().clone();
+            let body = if url.path() == "/.well-known/openid-configuration" {
+                provider_metadata("https://tenant.example")
+            } else {
+                jwks_count.fetch_add(1, Ordering::SeqCst);
+                format!("{{\"keys\": [{}]}}", TEST_RSA_PUB_KEY)
+            };
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(body);
+            ready(Ok::<_, io::Error>(response))
+        };
+
+        let clock = FixedClock::new(Utc.timestamp(1544932149, 0));
+        let registry = CoreIssuerRegistry::new().set_clock(clock.clone());
+        let tenant = IssuerUrl::new("https://tenant.example".to_string()).unwrap();
+        registry.register(
+            tenant.clone(),
+            ClientId::new("client".to_string()),
+            None,
+            None,
+        );
+        block_on(registry.client(&tenant, &http_client)).unwrap();
+        assert_eq!(1, jwks_count.load(Ordering::SeqCst));
+
+        // The key set was just fetched during discovery, so it isn't fetched again.
+        let unknown_key_id = JsonWebKeyId::new("unknown".to_string());
+        match block_on(registry.refresh_jwks(&tenant, &unknown_key_id, &http_client)) {
+            Err(IssuerRegistryError::KeyNotFound(key_id)) => assert_eq!(unknown_key_id, key_id),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(1, jwks_count.load(Ordering::SeqCst));
+
+        // Back-to-back lookups of an unknown key fetch the key set only once.
+        clock.advance(Duration::seconds(30));
+        for _ in 0..2 {
+            match block_on(registry.refresh_jwks(&tenant, &unknown_key_id, &http_client)) {
+                Err(IssuerRegistryError::KeyNotFound(_)) => {}
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
+            }
+        }
+        assert_eq!(2, jwks_count.load(Ordering::SeqCst));
+
+        clock.advance(Duration::seconds(29));
+        match block_on(registry.refresh_jwks(&tenant, &unknown_key_id, &http_client)) {
+            Err(IssuerRegistryError::KeyNotFound(_)) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(2, jwks_count.load(Ordering::SeqCst));
+
+        clock.advance(Duration::seconds(1));
+        match block_on(registry.refresh_jwks(&tenant, &unknown_key_id, &http_client)) {
+            Err(IssuerRegistryError::KeyNotFound(_)) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(3, jwks_count.load(Ordering::SeqCst));
+
+        // Known keys don't require fetching the key set.
+        let key_id = JsonWebKeyId::new("bilbo.baggins@hobbiton.example".to_string());
+        let client = block_on(registry.refresh_jwks(&tenant, &key_id, &http_client)).unwrap();
+        assert_eq!(1, client.jwks.keys().len());
+        assert_eq!(3, jwks_count.load(Ordering::SeqCst));
+    }
+}
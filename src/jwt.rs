@@ -1,28 +1,107 @@
+use std::collections::HashSet;
 use std::fmt::{Debug, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::str;
 
 use base64;
+use oauth2::helpers::variant_name;
 use serde::de::{DeserializeOwned, Error as _, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json;
 
 use super::{
-    JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
-    JwsSigningAlgorithm, PrivateSigningKey, SignatureVerificationError, SigningError,
+    JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, PrivateSigningKey,
+    SignatureVerificationError, SigningError,
 };
+use crate::verification::{validate_critical_headers, validate_jwt_type};
 
 new_type![
+    ///
+    /// Content type (`cty`) of a JSON Web Token.
+    ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     JsonWebTokenContentType(String)
 ];
 
 new_type![
+    ///
+    /// Media type (`typ`) of a JSON Web Token.
+    ///
+    /// Verifiers may restrict the accepted types in order to prevent one kind of JWT from being
+    /// substituted for another (e.g., a logout token being presented as an ID token).
+    ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     JsonWebTokenType(String)
+    impl {
+        ///
+        /// Generic JSON Web Token (`JWT`), which is the type used by ID tokens.
+        ///
+        pub fn jwt() -> Self {
+            Self::new("JWT".to_string())
+        }
+
+        ///
+        /// JWT access token (`at+jwt`), as defined in
+        /// [RFC 9068](https://tools.ietf.org/html/rfc9068#section-2.1).
+        ///
+        pub fn access_token() -> Self {
+            Self::new("at+jwt".to_string())
+        }
+
+        ///
+        /// Back-channel logout token (`logout+jwt`), as defined in
+        /// [OpenID Connect Back-Channel Logout](
+        /// https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken).
+        ///
+        pub fn logout_token() -> Self {
+            Self::new("logout+jwt".to_string())
+        }
+
+        ///
+        /// Security event token (`secevent+jwt`), as defined in
+        /// [RFC 8417](https://tools.ietf.org/html/rfc8417#section-2.3).
+        ///
+        pub fn security_event_token() -> Self {
+            Self::new("secevent+jwt".to_string())
+        }
+
+        ///
+        /// Returns whether this type is equivalent to `other`.
+        ///
+        /// As described in [Section 4.1.9 of RFC 7515](
+        /// https://tools.ietf.org/html/rfc7515#section-4.1.9), types are compared
+        /// case-insensitively, and an `application/` prefix is ignored.
+        ///
+        pub fn matches(&self, other: &Self) -> bool {
+            fn without_prefix(jwt_type: &str) -> &str {
+                const PREFIX: &str = "application/";
+                match jwt_type.get(..PREFIX.len()) {
+                    Some(prefix) if prefix.eq_ignore_ascii_case(PREFIX) => {
+                        &jwt_type[PREFIX.len()..]
+                    }
+                    _ => jwt_type,
+                }
+            }
+
+            without_prefix(&self.0).eq_ignore_ascii_case(without_prefix(&other.0))
+        }
+    }
+];
+
+new_type![
+    ///
+    /// Base64url-encoded SHA-1 thumbprint (`x5t`) of the X.509 certificate corresponding to the key
+    /// used to secure a JSON Web Token.
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    X509CertificateThumbprint(String)
 ];
 
+///
+/// Algorithm (`alg`) used to secure a JSON Web Token.
+///
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub enum JsonWebTokenAlgorithm<JE, JS, JT>
@@ -31,8 +110,14 @@ where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
 {
+    ///
+    /// Content encryption algorithm used by an encrypted JWT (JWE).
+    ///
     Encryption(JE),
-    // This is ugly, but we don't expose this module via the public API, so it's fine.
+    ///
+    /// Digital signature or MAC algorithm used by a signed JWT (JWS).
+    ///
+    // The PhantomData is ugly, but it's needed to tie the JT parameter to this variant.
     Signature(JS, PhantomData<JT>),
     ///
     /// No digital signature or MAC performed.
@@ -96,6 +181,13 @@ where
     }
 }
 
+///
+/// JOSE header of a JSON Web Token.
+///
+/// Headers are available prior to verifying a JWT (e.g., via [`IdToken::unverified_header`](
+/// crate::IdToken::unverified_header)) so that applications can select a verifier or key by `kid`
+/// or log the algorithms in use. The header is untrusted until the JWT's signature is verified.
+///
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct JsonWebTokenHeader<JE, JS, JT>
 where
@@ -103,37 +195,93 @@ where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
 {
+    ///
+    /// Algorithm used to secure the JWT (`alg`).
+    ///
     #[serde(
         bound = "JE: JweContentEncryptionAlgorithm<JT>, JS: JwsSigningAlgorithm<JT>, JT: JsonWebKeyType"
     )]
     pub alg: JsonWebTokenAlgorithm<JE, JS, JT>,
-    // Additional critical header parameters that must be understood by this implementation. Since
-    // we don't understand any such extensions, we reject any JWT with this value present (the
-    // spec specifically prohibits including public (standard) headers in this field).
+    // Additional critical header parameters that must be understood by this implementation. The
+    // verifiers reject any JWT with this value present unless the application has declared that
+    // it understands each of the listed extensions (the spec specifically prohibits including
+    // public (standard) headers in this field).
     // See https://tools.ietf.org/html/rfc7515#section-4.1.11.
+    ///
+    /// Critical header parameters (`crit`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub crit: Option<Vec<String>>,
+    ///
+    /// Content type of the JWT payload (`cty`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cty: Option<JsonWebTokenContentType>,
+    ///
+    /// Content encryption algorithm used by an encrypted JWT (`enc`).
+    ///
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enc: Option<JE>,
+    ///
+    /// ID of the key used to secure the JWT (`kid`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kid: Option<JsonWebKeyId>,
+    ///
+    /// Media type of the JWT (`typ`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub typ: Option<JsonWebTokenType>,
-    // Other JOSE header fields are omitted since the OpenID Connect spec specifically says that
+    ///
+    /// SHA-1 thumbprint of the X.509 certificate corresponding to the key used to secure the JWT
+    /// (`x5t`).
+    ///
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<X509CertificateThumbprint>,
+    // Other JOSE header fields are not modeled since the OpenID Connect spec specifically says that
     // the "x5u", "x5c", "jku", "jwk" header parameter fields SHOULD NOT be used.
-    // See http://openid.net/specs/openid-connect-core-1_0-final.html#IDToken.
+    // See http://openid.net/specs/openid-connect-core-1_0-final.html#IDToken. They're retained
+    // here along with any extension parameters (e.g., those listed in 'crit').
+    #[serde(flatten)]
+    other_params: serde_json::Map<String, serde_json::Value>,
     #[serde(skip)]
     _phantom_jt: PhantomData<JT>,
 }
+impl<JE, JS, JT> JsonWebTokenHeader<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Returns the value of a header parameter not modeled by this struct (e.g., an extension
+    /// parameter listed in `crit`), if present.
+    ///
+    pub fn other_param(&self, name: &str) -> Option<&serde_json::Value> {
+        self.other_params.get(name)
+    }
+}
 
+///
+/// Encoding of a JSON Web Token or JSON Web Signature payload.
+///
 pub trait JsonWebTokenPayloadSerde<P>: Debug
 where
     P: Debug + DeserializeOwned + Serialize,
 {
+    ///
+    /// Decodes the payload from the specified (base64url-decoded) bytes.
+    ///
     fn deserialize<DE: serde::de::Error>(payload: &[u8]) -> Result<P, DE>;
+    ///
+    /// Encodes the payload prior to base64url encoding.
+    ///
     fn serialize(payload: &P) -> Result<String, serde_json::Error>;
 }
 
+///
+/// JSON payload encoding, which is used for JWT claims.
+///
 #[derive(Clone, Debug, PartialEq)]
 pub struct JsonWebTokenJsonPayloadSerde;
 impl<P> JsonWebTokenPayloadSerde<P> for JsonWebTokenJsonPayloadSerde
@@ -165,6 +313,17 @@ where
     fn unverified_payload(self) -> Self::ReturnType;
     fn unverified_payload_ref(&self) -> &P;
 
+    // Verifies the signature without consuming the JWT, which allows multiple candidate keys to be
+    // tried.
+    fn verify_signature<JU, JW>(
+        &self,
+        signature_alg: &JS,
+        key: &JW,
+    ) -> Result<(), SignatureVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>;
+
     fn payload<JU, JW>(
         self,
         signature_alg: &JS,
@@ -191,9 +350,21 @@ pub enum JsonWebTokenError {
     ///
     #[fail(display = "Failed to sign JWT")]
     SigningError(#[cause] SigningError),
+    ///
+    /// The requested JWT can't be created because it requires an unsupported feature (e.g., JWE
+    /// encryption).
+    ///
+    #[fail(display = "Unsupported: {}", _0)]
+    Unsupported(String),
+    ///
+    /// The claims don't satisfy the requirements of the requested JWT (e.g., a logout token
+    /// without a `sub` or `sid` claim).
+    ///
+    #[fail(display = "Invalid claims: {}", _0)]
+    InvalidClaims(String),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct JsonWebToken<JE, JS, JT, P, S>
 where
     JE: JweContentEncryptionAlgorithm<JT>,
@@ -208,6 +379,25 @@ where
     signing_input: String,
     _phantom: PhantomData<S>,
 }
+impl<JE, JS, JT, P, S> Debug for JsonWebToken<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // The signing input and signature together reproduce the serialized JWT, which may be
+        // usable as a bearer credential (e.g., an ID token passed as a token exchange subject
+        // token), so only the decoded header and payload are included.
+        f.debug_struct("JsonWebToken")
+            .field("header", &self.header)
+            .field("payload", &self.payload)
+            .field("signature", &"[redacted]")
+            .finish()
+    }
+}
 impl<JE, JS, JT, P, S> JsonWebToken<JE, JS, JT, P, S>
 where
     JE: JweContentEncryptionAlgorithm<JT>,
@@ -217,6 +407,24 @@ where
     S: JsonWebTokenPayloadSerde<P>,
 {
     pub fn new<JU, K, SK>(payload: P, signing_key: &SK, alg: &JS) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        Self::new_with_type(payload, signing_key, alg, None)
+    }
+
+    ///
+    /// Initializes a new JWT with the specified media type (`typ`) header, which lets verifiers
+    /// distinguish it from other kinds of JWTs signed by the same key.
+    ///
+    pub fn new_with_type<JU, K, SK>(
+        payload: P,
+        signing_key: &SK,
+        alg: &JS,
+        typ: Option<JsonWebTokenType>,
+    ) -> Result<Self, JsonWebTokenError>
     where
         JU: JsonWebKeyUse,
         K: JsonWebKey<JS, JT, JU>,
@@ -226,8 +434,11 @@ where
             alg: JsonWebTokenAlgorithm::Signature(alg.clone(), PhantomData),
             crit: None,
             cty: None,
+            enc: None,
             kid: signing_key.as_verification_key().key_id().cloned(),
-            typ: None,
+            typ,
+            x5t: None,
+            other_params: serde_json::Map::new(),
             _phantom_jt: PhantomData,
         };
 
@@ -253,6 +464,80 @@ where
             _phantom: PhantomData,
         })
     }
+
+    ///
+    /// Parses a JWT in the JWS Compact Serialization without verifying it.
+    ///
+    /// The header and payload are decoded directly into their typed representations, without
+    /// deserializing via an intermediate `serde_json::Value`. Only the signing input is copied
+    /// from `compact`, since the parsed JWT doesn't borrow from its serialization.
+    ///
+    pub(crate) fn from_compact<DE>(compact: &str) -> Result<Self, DE>
+    where
+        DE: serde::de::Error,
+    {
+        Self::from_compact_with(compact, |payload| S::deserialize::<DE>(payload))
+    }
+
+    ///
+    /// Parses a JWT in the JWS Compact Serialization without verifying it, decoding the
+    /// (base64url-decoded) payload using `deserialize_payload` instead of `S`.
+    ///
+    pub(crate) fn from_compact_with<DE, F>(
+        compact: &str,
+        deserialize_payload: F,
+    ) -> Result<Self, DE>
+    where
+        DE: serde::de::Error,
+        F: FnOnce(&[u8]) -> Result<P, DE>,
+    {
+        let mut parts = compact.split('.');
+        let (header_base64, payload_base64, signature_base64) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(header), Some(payload), Some(signature), None) => {
+                    (header, payload, signature)
+                }
+                // NB: We avoid including the full payload encoding in the error output to avoid
+                // clients potentially logging sensitive values.
+                _ => {
+                    return Err(DE::custom(format!(
+                        "Invalid JSON web token: found {} parts (expected 3)",
+                        compact.split('.').count()
+                    )))
+                }
+            };
+
+        let header_json =
+            base64::decode_config(header_base64, crate::core::base64_url_safe_no_pad()).map_err(
+                |err| DE::custom(format!("Invalid base64url header encoding: {:?}", err)),
+            )?;
+        let header = serde_json::from_slice(&header_json)
+            .map_err(|err| DE::custom(format!("Failed to parse header JSON: {:?}", err)))?;
+
+        let raw_payload =
+            base64::decode_config(payload_base64, crate::core::base64_url_safe_no_pad()).map_err(
+                |err| DE::custom(format!("Invalid base64url payload encoding: {:?}", err)),
+            )?;
+        let payload = deserialize_payload(&raw_payload)?;
+
+        let signature =
+            base64::decode_config(signature_base64, crate::core::base64_url_safe_no_pad())
+                .map_err(|err| {
+                    DE::custom(format!("Invalid base64url signature encoding: {:?}", err))
+                })?;
+
+        // The signing input is the prefix of the compact serialization preceding the final '.',
+        // which is copied in a single allocation rather than reassembled from its parts.
+        let signing_input = compact[..header_base64.len() + 1 + payload_base64.len()].to_string();
+
+        Ok(JsonWebToken {
+            header,
+            payload,
+            signature,
+            signing_input,
+            _phantom: PhantomData,
+        })
+    }
 }
 // Owned JWT.
 impl<JE, JS, JT, P, S> JsonWebTokenAccess<JE, JS, JT, P> for JsonWebToken<JE, JS, JT, P, S>
@@ -273,11 +558,11 @@ where
     fn unverified_payload_ref(&self) -> &P {
         &self.payload
     }
-    fn payload<JU, JW>(
-        self,
+    fn verify_signature<JU, JW>(
+        &self,
         signature_alg: &JS,
         key: &JW,
-    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    ) -> Result<(), SignatureVerificationError>
     where
         JU: JsonWebKeyUse,
         JW: JsonWebKey<JS, JT, JU>,
@@ -286,7 +571,18 @@ where
             signature_alg,
             self.signing_input.as_bytes(),
             &self.signature,
-        )?;
+        )
+    }
+    fn payload<JU, JW>(
+        self,
+        signature_alg: &JS,
+        key: &JW,
+    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>,
+    {
+        self.verify_signature::<JU, JW>(signature_alg, key)?;
         Ok(self.payload)
     }
 }
@@ -309,11 +605,11 @@ where
     fn unverified_payload_ref(&self) -> &P {
         &self.payload
     }
-    fn payload<JU, JW>(
-        self,
+    fn verify_signature<JU, JW>(
+        &self,
         signature_alg: &JS,
         key: &JW,
-    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    ) -> Result<(), SignatureVerificationError>
     where
         JU: JsonWebKeyUse,
         JW: JsonWebKey<JS, JT, JU>,
@@ -322,7 +618,18 @@ where
             signature_alg,
             self.signing_input.as_bytes(),
             &self.signature,
-        )?;
+        )
+    }
+    fn payload<JU, JW>(
+        self,
+        signature_alg: &JS,
+        key: &JW,
+    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>,
+    {
+        self.verify_signature::<JU, JW>(signature_alg, key)?;
         Ok(&self.payload)
     }
 }
@@ -369,59 +676,7 @@ where
             where
                 DE: serde::de::Error,
             {
-                let raw_token = v.to_string();
-                let header: JsonWebTokenHeader<JE, JS, JT>;
-                let payload: P;
-                let signature;
-                let signing_input;
-
-                {
-                    let parts = raw_token.split('.').collect::<Vec<_>>();
-
-                    // NB: We avoid including the full payload encoding in the error output to avoid
-                    // clients potentially logging sensitive values.
-                    if parts.len() != 3 {
-                        return Err(DE::custom(format!(
-                            "Invalid JSON web token: found {} parts (expected 3)",
-                            parts.len()
-                        )));
-                    }
-
-                    let header_json =
-                        base64::decode_config(parts[0], crate::core::base64_url_safe_no_pad())
-                            .map_err(|err| {
-                                DE::custom(format!("Invalid base64url header encoding: {:?}", err))
-                            })?;
-                    header = serde_json::from_slice(&header_json).map_err(|err| {
-                        DE::custom(format!("Failed to parse header JSON: {:?}", err))
-                    })?;
-
-                    let raw_payload =
-                        base64::decode_config(parts[1], crate::core::base64_url_safe_no_pad())
-                            .map_err(|err| {
-                                DE::custom(format!("Invalid base64url payload encoding: {:?}", err))
-                            })?;
-                    payload = S::deserialize::<DE>(&raw_payload)?;
-
-                    signature =
-                        base64::decode_config(parts[2], crate::core::base64_url_safe_no_pad())
-                            .map_err(|err| {
-                                DE::custom(format!(
-                                    "Invalid base64url signature encoding: {:?}",
-                                    err
-                                ))
-                            })?;
-
-                    signing_input = format!("{}.{}", parts[0], parts[1]);
-                }
-
-                Ok(JsonWebToken {
-                    header,
-                    payload,
-                    signature,
-                    signing_input,
-                    _phantom: PhantomData,
-                })
+                JsonWebToken::from_compact(v)
             }
         }
         deserializer.deserialize_str(JsonWebTokenVisitor(
@@ -450,22 +705,473 @@ where
     }
 }
 
+///
+/// JSON Web Signature (JWS) in the general or flattened
+/// [JWS JSON Serialization](https://tools.ietf.org/html/rfc7515#section-7.2).
+///
+/// Unlike the compact serialization used by ID tokens, the JSON serializations may include
+/// multiple signatures over the same payload (e.g., OpenID Federation entity statements signed by
+/// more than one key), and headers may be split between an integrity-protected (`protected`) and
+/// an unprotected (`header`) part. Each signature's header is the union of these two parts. The
+/// JWS Compact Serialization is also accepted when deserializing, in which case the JWS has a
+/// single signature.
+///
+/// When serialized, a JWS with a single signature uses the flattened serialization, while one with
+/// multiple signatures uses the general serialization.
+///
+/// Before a signature is verified, its header is validated in the same way as the header of a
+/// compact JWT: the `typ` header must be one of the allowed types (see
+/// [`JsonWebSignature::set_allowed_jwt_types`]), and each header parameter listed in `crit` must be
+/// understood by the application (see [`JsonWebSignature::set_understood_critical_headers`]).
+/// Signatures with invalid headers are ignored.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    payload: P,
+    payload_base64: String,
+    signatures: Vec<JsonWebSignatureEntry<JE, JS, JT>>,
+    critical_headers: HashSet<String>,
+    jwt_type_required: bool,
+    jwt_types: Vec<JsonWebTokenType>,
+    _phantom: PhantomData<S>,
+}
+impl<JE, JS, JT, P, S> JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    ///
+    /// Specifies the allowed values of the `typ` header.
+    ///
+    /// By default, only signatures whose header omits `typ` or has the value `JWT` are verified.
+    ///
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_types = jwt_types.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Specifies whether each signature's header must include the `typ` header.
+    ///
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_type_required = jwt_type_required;
+        self
+    }
+
+    ///
+    /// Specifies the critical JOSE header extensions (i.e., values of the `crit` header) that the
+    /// application understands and processes. By default, signatures whose header includes a
+    /// `crit` header are never verified.
+    ///
+    pub fn set_understood_critical_headers<I>(mut self, critical_headers: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.critical_headers = critical_headers.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Returns the (combined protected and unprotected) header of each signature without
+    /// verifying any of the signatures.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned headers are untrusted and may have been forged or tampered with. In
+    /// particular, unprotected header parameters are not covered by any signature.
+    ///
+    pub fn unverified_headers(&self) -> impl Iterator<Item = &JsonWebTokenHeader<JE, JS, JT>> {
+        self.signatures.iter().map(|entry| &entry.header)
+    }
+
+    ///
+    /// Returns the payload without verifying any of the signatures.
+    ///
+    pub fn unverified_payload(&self) -> &P {
+        &self.payload
+    }
+
+    ///
+    /// Verifies the JWS using the specified key and signature algorithm, and returns the payload.
+    ///
+    /// Succeeds if any signature using `signature_alg` (and, if both specify a key ID, the same
+    /// key ID as `key`) is valid.
+    ///
+    pub fn payload<JU, JW>(
+        self,
+        signature_alg: &JS,
+        key: &JW,
+    ) -> Result<P, SignatureVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>,
+    {
+        let mut result = Err(SignatureVerificationError::NoMatchingKey);
+        for entry in self.signatures.iter().filter(|entry| {
+            entry.signature_alg() == Some(signature_alg) && entry.is_compatible_key::<JU, _>(key)
+        }) {
+            result = self.validate_header(entry).and_then(|()| {
+                key.verify_signature(
+                    signature_alg,
+                    entry.signing_input(&self.payload_base64).as_bytes(),
+                    &entry.signature,
+                )
+            });
+            if result.is_ok() {
+                break;
+            }
+        }
+        result.map(|()| self.payload)
+    }
+
+    ///
+    /// Verifies the JWS using the keys in the specified key set, and returns the payload.
+    ///
+    /// Each signature using one of the `allowed_algs` is checked against the keys of the
+    /// corresponding type (restricted to the header's key ID, if any), and the payload is returned
+    /// as soon as one of the signatures is valid.
+    ///
+    pub fn verified_payload<JU, K>(
+        self,
+        allowed_algs: &[JS],
+        key_set: &JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Result<P, SignatureVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        let mut result = Err(SignatureVerificationError::NoMatchingKey);
+        let mut disallowed_alg = None;
+        let mut found_allowed_alg = false;
+        for entry in &self.signatures {
+            let signature_alg = match entry.signature_alg() {
+                Some(signature_alg) if allowed_algs.contains(signature_alg) => signature_alg,
+                Some(signature_alg) => {
+                    disallowed_alg = Some(signature_alg);
+                    continue;
+                }
+                None => continue,
+            };
+            found_allowed_alg = true;
+            if let Err(err) = self.validate_header(entry) {
+                result = Err(err);
+                continue;
+            }
+            let signing_input = entry.signing_input(&self.payload_base64);
+            for key in key_set.keys().iter().filter(|key| {
+                Some(key.key_type()) == signature_alg.key_type().as_ref()
+                    && entry.is_compatible_key::<JU, _>(*key)
+            }) {
+                result =
+                    key.verify_signature(signature_alg, signing_input.as_bytes(), &entry.signature);
+                if result.is_ok() {
+                    return Ok(self.payload);
+                }
+            }
+        }
+        if let (Some(signature_alg), false) = (disallowed_alg, found_allowed_alg) {
+            return Err(SignatureVerificationError::DisallowedAlg(format!(
+                "algorithm `{}` is not one of: {}",
+                variant_name(signature_alg),
+                allowed_algs
+                    .iter()
+                    .map(variant_name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )));
+        }
+        result.map(|()| self.payload)
+    }
+
+    // Validates the header of a signature in the same way as the verifiers validate the header of
+    // a compact JWT (see https://tools.ietf.org/html/rfc7515#section-4.1.11).
+    fn validate_header(
+        &self,
+        entry: &JsonWebSignatureEntry<JE, JS, JT>,
+    ) -> Result<(), SignatureVerificationError> {
+        validate_jwt_type(&entry.header, &self.jwt_types, self.jwt_type_required)
+            .and_then(|()| validate_critical_headers(&entry.header, &self.critical_headers))
+            .map_err(SignatureVerificationError::InvalidHeader)
+    }
+}
+impl<'de, JE, JS, JT, P, S> Deserialize<'de> for JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (payload_base64, raw_signatures) = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(compact) => {
+                let parts = compact.split('.').collect::<Vec<_>>();
+                if parts.len() != 3 {
+                    return Err(D::Error::custom(format!(
+                        "Invalid JSON web signature: found {} parts (expected 3)",
+                        parts.len()
+                    )));
+                }
+                (
+                    parts[1].to_string(),
+                    vec![RawJsonWebSignatureEntry {
+                        protected: Some(parts[0].to_string()),
+                        header: None,
+                        signature: parts[2].to_string(),
+                    }],
+                )
+            }
+            serde_json::Value::Object(mut jws) => {
+                let payload_base64 = match jws.remove("payload") {
+                    Some(serde_json::Value::String(payload_base64)) => payload_base64,
+                    _ => return Err(D::Error::custom("Missing or invalid JWS payload")),
+                };
+                let raw_signatures = if let Some(signatures) = jws.remove("signatures") {
+                    // General JWS JSON Serialization.
+                    if jws.contains_key("signature") {
+                        return Err(D::Error::custom(
+                            "JWS must not contain both `signatures` and `signature`",
+                        ));
+                    }
+                    serde_json::from_value::<Vec<RawJsonWebSignatureEntry>>(signatures).map_err(
+                        |err| D::Error::custom(format!("Invalid JWS signatures: {:?}", err)),
+                    )?
+                } else {
+                    // Flattened JWS JSON Serialization.
+                    vec![serde_json::from_value::<RawJsonWebSignatureEntry>(
+                        serde_json::Value::Object(jws),
+                    )
+                    .map_err(|err| D::Error::custom(format!("Invalid JWS signature: {:?}", err)))?]
+                };
+                (payload_base64, raw_signatures)
+            }
+            _ => return Err(D::Error::custom("Expected a JSON web signature")),
+        };
+
+        if raw_signatures.is_empty() {
+            return Err(D::Error::custom("JWS must contain at least one signature"));
+        }
+
+        // NB: We avoid including the full payload encoding in the error output to avoid clients
+        // potentially logging sensitive values.
+        let raw_payload =
+            base64::decode_config(&payload_base64, crate::core::base64_url_safe_no_pad()).map_err(
+                |err| D::Error::custom(format!("Invalid base64url payload encoding: {:?}", err)),
+            )?;
+        let payload = S::deserialize::<D::Error>(&raw_payload)?;
+
+        let signatures = raw_signatures
+            .into_iter()
+            .map(JsonWebSignatureEntry::from_raw::<D::Error>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(JsonWebSignature {
+            payload,
+            payload_base64,
+            signatures,
+            critical_headers: HashSet::new(),
+            jwt_type_required: false,
+            jwt_types: vec![JsonWebTokenType::jwt()],
+            _phantom: PhantomData,
+        })
+    }
+}
+impl<JE, JS, JT, P, S> Serialize for JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        let mut raw_signatures = self
+            .signatures
+            .iter()
+            .map(JsonWebSignatureEntry::to_raw)
+            .collect::<Vec<_>>();
+        if raw_signatures.len() == 1 {
+            FlattenedJsonWebSignature {
+                payload: &self.payload_base64,
+                signature: raw_signatures.remove(0),
+            }
+            .serialize(serializer)
+        } else {
+            GeneralJsonWebSignature {
+                payload: &self.payload_base64,
+                signatures: raw_signatures,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+// A single signature of a JsonWebSignature.
+#[derive(Clone, Debug, PartialEq)]
+struct JsonWebSignatureEntry<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    // Union of the protected and unprotected headers.
+    header: JsonWebTokenHeader<JE, JS, JT>,
+    protected_base64: String,
+    unprotected: Option<serde_json::Map<String, serde_json::Value>>,
+    signature: Vec<u8>,
+}
+impl<JE, JS, JT> JsonWebSignatureEntry<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn from_raw<DE>(raw: RawJsonWebSignatureEntry) -> Result<Self, DE>
+    where
+        DE: serde::de::Error,
+    {
+        let protected_base64 = raw.protected.unwrap_or_default();
+        let mut header_params = if protected_base64.is_empty() {
+            serde_json::Map::new()
+        } else {
+            let protected_json =
+                base64::decode_config(&protected_base64, crate::core::base64_url_safe_no_pad())
+                    .map_err(|err| {
+                        DE::custom(format!("Invalid base64url header encoding: {:?}", err))
+                    })?;
+            serde_json::from_slice(&protected_json).map_err(|err| {
+                DE::custom(format!("Failed to parse protected header JSON: {:?}", err))
+            })?
+        };
+
+        // The protected and unprotected header parameter names must be disjoint, and 'crit' must
+        // be integrity protected. See https://tools.ietf.org/html/rfc7515#section-7.2.1.
+        if let Some(ref unprotected) = raw.header {
+            for (name, value) in unprotected {
+                if name == "crit" {
+                    return Err(DE::custom("JWS `crit` header must be integrity protected"));
+                } else if header_params.insert(name.clone(), value.clone()).is_some() {
+                    return Err(DE::custom(format!(
+                        "JWS header parameter `{}` is both protected and unprotected",
+                        name
+                    )));
+                }
+            }
+        }
+        let header = serde_json::from_value(serde_json::Value::Object(header_params))
+            .map_err(|err| DE::custom(format!("Failed to parse header JSON: {:?}", err)))?;
+
+        let signature =
+            base64::decode_config(&raw.signature, crate::core::base64_url_safe_no_pad()).map_err(
+                |err| DE::custom(format!("Invalid base64url signature encoding: {:?}", err)),
+            )?;
+
+        Ok(JsonWebSignatureEntry {
+            header,
+            protected_base64,
+            unprotected: raw.header,
+            signature,
+        })
+    }
+
+    fn to_raw(&self) -> RawJsonWebSignatureEntry {
+        RawJsonWebSignatureEntry {
+            protected: if self.protected_base64.is_empty() {
+                None
+            } else {
+                Some(self.protected_base64.clone())
+            },
+            header: self.unprotected.clone(),
+            signature: base64::encode_config(&self.signature, base64::URL_SAFE_NO_PAD),
+        }
+    }
+
+    fn signature_alg(&self) -> Option<&JS> {
+        match self.header.alg {
+            JsonWebTokenAlgorithm::Signature(ref signature_alg, _) => Some(signature_alg),
+            _ => None,
+        }
+    }
+
+    // Keys with a different key ID than the one specified in the header are never used.
+    fn is_compatible_key<JU, JW>(&self, key: &JW) -> bool
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>,
+    {
+        match (self.header.kid.as_ref(), key.key_id()) {
+            (Some(header_kid), Some(key_id)) => header_kid == key_id,
+            _ => true,
+        }
+    }
+
+    fn signing_input(&self, payload_base64: &str) -> String {
+        format!("{}.{}", self.protected_base64, payload_base64)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawJsonWebSignatureEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protected: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<serde_json::Map<String, serde_json::Value>>,
+    signature: String,
+}
+
+#[derive(Serialize)]
+struct GeneralJsonWebSignature<'a> {
+    payload: &'a str,
+    signatures: Vec<RawJsonWebSignatureEntry>,
+}
+
+#[derive(Serialize)]
+struct FlattenedJsonWebSignature<'a> {
+    payload: &'a str,
+    #[serde(flatten)]
+    signature: RawJsonWebSignatureEntry,
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::marker::PhantomData;
     use std::string::ToString;
 
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
     use serde_json;
 
     use crate::core::{
-        CoreJsonWebKey, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
+        CoreJsonWebKey, CoreJsonWebKeySet, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
         CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
     };
-    use crate::JsonWebKeyId;
+    use crate::{
+        ClaimsVerificationError, JsonWebKeyId, PrivateSigningKey, SignatureVerificationError,
+    };
 
     use super::{
-        JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenJsonPayloadSerde,
-        JsonWebTokenPayloadSerde,
+        JsonWebSignature, JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm,
+        JsonWebTokenHeader, JsonWebTokenJsonPayloadSerde, JsonWebTokenPayloadSerde,
+        JsonWebTokenType, X509CertificateThumbprint,
     };
 
     type CoreAlgorithm = JsonWebTokenAlgorithm<
@@ -534,6 +1240,65 @@ pub mod tests {
          1Vre3XB9HH8MYBVB6UIexaAq4xSeoemRKTBesZro7OKjKT8/GmiO\
          -----END RSA PRIVATE KEY-----";
 
+    // Checks that a verifier accepts only JWTs signed with one of its allowed algorithms (RS256 by
+    // default), and that unsigned JWTs are rejected regardless of the allowed algorithms.
+    // `new_jwt` signs the same claims with the specified algorithm using `TEST_RSA_PRIV_KEY`.
+    pub fn assert_allowed_algs<T, V, N, S, A, F>(
+        new_jwt: N,
+        verifier: V,
+        set_allowed_algs: S,
+        allow_any_alg: A,
+        verify: F,
+    ) where
+        T: DeserializeOwned + Serialize,
+        V: Clone,
+        N: Fn(CoreJwsSigningAlgorithm) -> T,
+        S: Fn(V, Vec<CoreJwsSigningAlgorithm>) -> V,
+        A: Fn(V) -> V,
+        F: Fn(&T, &V) -> Result<(), ClaimsVerificationError>,
+    {
+        let rs256_jwt = new_jwt(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256);
+        let rs512_jwt = new_jwt(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512);
+
+        // Strip the signature and replace the header with `{"alg":"none"}`.
+        let serialized = serde_json::to_value(&rs256_jwt).unwrap();
+        let payload = serialized.as_str().unwrap().split('.').nth(1).unwrap();
+        let unsigned_jwt: T = serde_json::from_value(serde_json::Value::String(format!(
+            "eyJhbGciOiJub25lIn0.{}.",
+            payload
+        )))
+        .unwrap();
+
+        // By default, only RS256 is allowed.
+        verify(&rs256_jwt, &verifier).expect("RS256 should be allowed by default");
+        match verify(&rs512_jwt, &verifier) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let rs512_verifier = set_allowed_algs(
+            verifier.clone(),
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512],
+        );
+        verify(&rs512_jwt, &rs512_verifier).expect("RS512 should be allowed");
+        match verify(&rs256_jwt, &rs512_verifier) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Unsigned JWTs are rejected regardless of the allowed algorithms.
+        for verifier in vec![verifier.clone(), rs512_verifier, allow_any_alg(verifier)] {
+            match verify(&unsigned_jwt, &verifier) {
+                Err(ClaimsVerificationError::NoSignature) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_jwt_algorithm_deserialization() {
         assert_eq!(
@@ -640,6 +1405,7 @@ pub mod tests {
                 );
                 assert_eq!(header.crit, None);
                 assert_eq!(header.cty, None);
+                assert_eq!(header.enc, None);
                 assert_eq!(
                     header.kid,
                     Some(JsonWebKeyId::new(
@@ -647,6 +1413,7 @@ pub mod tests {
                     ))
                 );
                 assert_eq!(header.typ, None);
+                assert_eq!(header.x5t, None);
             }
             assert_eq!(jwt_access.unverified_payload_ref(), expected_payload);
 
@@ -738,6 +1505,58 @@ pub mod tests {
             .expect_err("signature verification should have failed");
     }
 
+    #[test]
+    fn test_jwt_header() {
+        let header: JsonWebTokenHeader<
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+        > = serde_json::from_str(
+            "{\
+             \"alg\":\"ES256\",\
+             \"kid\":\"tenant-a\",\
+             \"typ\":\"at+jwt\",\
+             \"crit\":[\"exp\"],\
+             \"exp\":1363284000,\
+             \"x5t\":\"NjVBRjY5MDlCMUIwNzU4RTA2QzZFMDQ4QzQ2MDAyQjVDNjk1RTM2Qg\"\
+             }",
+        )
+        .expect("failed to deserialize");
+        assert_eq!(
+            header.alg,
+            JsonWebTokenAlgorithm::Signature(CoreJwsSigningAlgorithm::EcdsaP256Sha256, PhantomData)
+        );
+        assert_eq!(header.kid, Some(JsonWebKeyId::new("tenant-a".to_string())));
+        assert_eq!(
+            header.typ,
+            Some(JsonWebTokenType::new("at+jwt".to_string()))
+        );
+        assert_eq!(header.crit, Some(vec!["exp".to_string()]));
+        assert_eq!(
+            header.other_param("exp"),
+            Some(&serde_json::json!(1363284000))
+        );
+        assert_eq!(header.other_param("kid"), None);
+        assert_eq!(header.enc, None);
+        assert_eq!(
+            header.x5t,
+            Some(X509CertificateThumbprint::new(
+                "NjVBRjY5MDlCMUIwNzU4RTA2QzZFMDQ4QzQ2MDAyQjVDNjk1RTM2Qg".to_string()
+            ))
+        );
+
+        let encrypted_header: JsonWebTokenHeader<
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+        > = serde_json::from_str("{\"alg\":\"none\",\"enc\":\"A256GCM\"}")
+            .expect("failed to deserialize");
+        assert_eq!(
+            encrypted_header.enc,
+            Some(CoreJweContentEncryptionAlgorithm::Aes256Gcm)
+        );
+    }
+
     #[test]
     fn test_invalid_deserialization() {
         #[derive(Debug, Deserialize, Serialize)]
@@ -819,4 +1638,215 @@ pub mod tests {
         .expect("failed to deserialize");
         assert_eq!(deserialized.unverified_payload().foo, "bar");
     }
+
+    #[test]
+    fn test_json_web_signature() {
+        type TestJsonWebSignature = JsonWebSignature<
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+            String,
+            JsonWebTokenStringPayloadSerde,
+        >;
+
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        let key_set = CoreJsonWebKeySet::new(vec![key.clone()]);
+        let parts = TEST_JWT.split('.').collect::<Vec<_>>();
+        let rs256_header = base64::encode_config("{\"alg\":\"RS256\"}", base64::URL_SAFE_NO_PAD);
+
+        // Flattened JWS JSON Serialization, which round trips unchanged.
+        let flattened = serde_json::json!({
+            "payload": parts[1],
+            "protected": parts[0],
+            "header": {"x5t": "dGh1bWJwcmludA"},
+            "signature": parts[2],
+        });
+        let jws: TestJsonWebSignature = serde_json::from_value(flattened.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&jws).unwrap(), flattened);
+        assert_eq!(jws.unverified_payload(), TEST_JWT_PAYLOAD);
+        {
+            let headers = jws.unverified_headers().collect::<Vec<_>>();
+            assert_eq!(headers.len(), 1);
+            assert_eq!(
+                headers[0].kid,
+                Some(JsonWebKeyId::new(
+                    "bilbo.baggins@hobbiton.example".to_string()
+                ))
+            );
+            assert_eq!(
+                headers[0].x5t,
+                Some(X509CertificateThumbprint::new("dGh1bWJwcmludA".to_string()))
+            );
+        }
+        assert_eq!(
+            jws.payload(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, &key)
+                .unwrap(),
+            TEST_JWT_PAYLOAD
+        );
+
+        // General JWS JSON Serialization, in which only one of the signatures verifies.
+        let general = serde_json::json!({
+            "payload": parts[1],
+            "signatures": [
+                {"protected": rs256_header, "header": {"kid": "other"}, "signature": "AAAA"},
+                {"header": {"alg": "ES256"}, "signature": "AAAA"},
+                {"protected": rs256_header, "signature": "AAAA"},
+                {"protected": parts[0], "signature": parts[2]},
+            ],
+        });
+        let jws: TestJsonWebSignature = serde_json::from_value(general.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&jws).unwrap(), general);
+        assert_eq!(jws.unverified_headers().count(), 4);
+        assert_eq!(
+            jws.clone()
+                .payload(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, &key)
+                .unwrap(),
+            TEST_JWT_PAYLOAD
+        );
+        assert_eq!(
+            jws.clone()
+                .verified_payload(&[CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256], &key_set)
+                .unwrap(),
+            TEST_JWT_PAYLOAD
+        );
+        match jws.verified_payload(&[CoreJwsSigningAlgorithm::EcdsaP256Sha256], &key_set) {
+            Err(SignatureVerificationError::NoMatchingKey) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // None of the signatures verifies.
+        let jws: TestJsonWebSignature = serde_json::from_value(serde_json::json!({
+            "payload": parts[1],
+            "signatures": [{"protected": rs256_header, "signature": "AAAA"}],
+        }))
+        .unwrap();
+        match jws
+            .clone()
+            .payload(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, &key)
+        {
+            Err(SignatureVerificationError::CryptoError(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match jws.verified_payload(&[CoreJwsSigningAlgorithm::RsaSsaPssSha256], &key_set) {
+            Err(SignatureVerificationError::DisallowedAlg(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // JWS Compact Serialization.
+        let jws: TestJsonWebSignature =
+            serde_json::from_value(serde_json::Value::String(TEST_JWT.to_string())).unwrap();
+        assert_eq!(
+            jws.verified_payload(&[CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256], &key_set)
+                .unwrap(),
+            TEST_JWT_PAYLOAD
+        );
+
+        let expect_deserialization_err = |jws: serde_json::Value, pattern: &str| {
+            let err = serde_json::from_value::<TestJsonWebSignature>(jws)
+                .expect_err("deserialization should have failed");
+            assert!(
+                format!("{}", err).contains(pattern),
+                format!("Error `{}` must contain string `{}`", err, pattern),
+            );
+        };
+        expect_deserialization_err(
+            serde_json::json!({"signature": parts[2]}),
+            "Missing or invalid JWS payload",
+        );
+        expect_deserialization_err(
+            serde_json::json!({"payload": parts[1], "signatures": []}),
+            "JWS must contain at least one signature",
+        );
+        expect_deserialization_err(
+            serde_json::json!({
+                "payload": parts[1],
+                "protected": parts[0],
+                "header": {"kid": "other"},
+                "signature": parts[2],
+            }),
+            "JWS header parameter `kid` is both protected and unprotected",
+        );
+        expect_deserialization_err(
+            serde_json::json!({
+                "payload": parts[1],
+                "protected": parts[0],
+                "header": {"crit": ["exp"], "exp": 1363284000},
+                "signature": parts[2],
+            }),
+            "JWS `crit` header must be integrity protected",
+        );
+    }
+
+    #[test]
+    fn test_json_web_signature_header_validation() {
+        type TestJsonWebSignature = JsonWebSignature<
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+            String,
+            JsonWebTokenStringPayloadSerde,
+        >;
+
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        let key_set = CoreJsonWebKeySet::new(vec![key.clone()]);
+        let payload_base64 = TEST_JWT.split('.').nth(1).unwrap();
+        let signed_jws = |protected_header: &str| {
+            let protected_base64 = base64::encode_config(protected_header, base64::URL_SAFE_NO_PAD);
+            let signature = signing_key
+                .sign(
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    format!("{}.{}", protected_base64, payload_base64).as_bytes(),
+                )
+                .unwrap();
+            serde_json::from_value::<TestJsonWebSignature>(serde_json::json!({
+                "payload": payload_base64,
+                "protected": protected_base64,
+                "signature": base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+            }))
+            .unwrap()
+        };
+
+        // Validly signed JWS JSON Serializations are rejected if they list a critical header
+        // parameter that the application doesn't understand.
+        let jws = signed_jws("{\"alg\":\"RS256\",\"crit\":[\"exp\"],\"exp\":1363284000}");
+        match jws
+            .clone()
+            .payload(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, &key)
+        {
+            Err(SignatureVerificationError::InvalidHeader(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match jws
+            .clone()
+            .verified_payload(&[CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256], &key_set)
+        {
+            Err(SignatureVerificationError::InvalidHeader(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            jws.set_understood_critical_headers(vec!["exp".to_string()])
+                .verified_payload(&[CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256], &key_set)
+                .unwrap(),
+            TEST_JWT_PAYLOAD
+        );
+
+        // The `typ` header must be one of the allowed types.
+        let jws = signed_jws("{\"alg\":\"RS256\",\"typ\":\"logout+jwt\"}");
+        match jws
+            .clone()
+            .verified_payload(&[CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256], &key_set)
+        {
+            Err(SignatureVerificationError::InvalidHeader(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            jws.set_allowed_jwt_types(vec![JsonWebTokenType::logout_token()])
+                .payload(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, &key)
+                .unwrap(),
+            TEST_JWT_PAYLOAD
+        );
+    }
 }
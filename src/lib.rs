@@ -56,25 +56,36 @@
 //! used:
 //!  * **[`reqwest`]**
 //!
-//!    The `reqwest` HTTP client supports all three modes. By default, `reqwest` 0.9 is enabled,
-//!    which supports the synchronous and asynchronous `futures` 0.1 APIs.
+//!    The `reqwest` HTTP client (version 0.10) can be enabled in `Cargo.toml` via the `reqwest`
+//!    feature flag:
+//!    ```toml
+//!    openidconnect = { version = "2.0", features = ["reqwest"] }
+//!    ```
+//!
+//!    Async/await client: [`reqwest::async_http_client`], or [`reqwest::ReqwestClient`] to reuse a
+//!    single connection pool across requests.
+//!
+//!  * **[`hyper`]**
 //!
-//!    Synchronous client: [`reqwest::http_client`]
+//!    The `hyper` 1.x HTTP client can be enabled in `Cargo.toml` via the `hyper` feature flag.
 //!
-//!    Asynchronous `futures` 0.1 client: [`reqwest::future_http_client`]
+//!    Async/await client: [`hyper::HyperClient`], which may wrap an existing hyper client
+//!    (including one configured with a TLS connector).
 //!
-//!    Async/await `futures` 0.3 client: [`reqwest::async_http_client`]. This mode requires
-//!    `reqwest` 0.10, which can be enabled via the `reqwest-010` feature flag. Typically, the
-//!    default features are also disabled (`default-features = false` in `Cargo.toml`) to remove the
-//!    dependency on `reqwest` 0.9 when using `reqwest` 0.10. However, both can be used together if
-//!    both asynchronous interfaces are desired.
+//!  * **[`ureq`]**
 //!
-//!  * **[`curl`]**
+//!    The `ureq` HTTP client only supports the synchronous (blocking) API variants and can be
+//!    enabled in `Cargo.toml` via the `ureq` feature flag.
 //!
-//!    The `curl` HTTP client only supports the synchronous HTTP client mode and can be enabled in
-//!    `Cargo.toml` via the `curl` feature flag.
+//!    Synchronous client: [`ureq::http_client`], or [`ureq::UreqClient`] to reuse a single
+//!    connection pool across requests.
 //!
-//!    Synchronous client: [`curl::http_client`]
+//!  * **[`wasm`]**
+//!
+//!    When targeting `wasm32-unknown-unknown`, the `wasm` feature flag enables an HTTP client
+//!    backed by the browser's `fetch` API.
+//!
+//!    Async/await client: [`wasm::async_http_client`]
 //!
 //!  * **Custom**
 //!
@@ -108,6 +119,63 @@
 //!      RE: failure::Fail
 //!    ```
 //!
+//! ## Tracing
+//!
+//! When the `tracing` feature flag is enabled, each request sent to the OpenID Connect Provider
+//! is wrapped in a [`tracing`](https://crates.io/crates/tracing) span named
+//! `openidconnect_request`. This covers provider discovery, JSON Web Key Set fetches, dynamic
+//! client registration, user info requests, and the token requests sent by [`LoginFlow`],
+//! [`LoginManager`], and [`TokenManager`]. The span records the request kind, method, and URL.
+//! An event is emitted when the request completes, with the HTTP status (or error) and the elapsed
+//! time in milliseconds. URLs are recorded without their query strings, fragments, or user info.
+//! Headers and bodies are never recorded, since they may contain credentials or tokens.
+//!
+//! ## Zeroizing secrets
+//!
+//! When the `zeroize` feature flag is enabled, the secret types defined by this crate ([`Nonce`],
+//! [`LoginHint`], and [`RegistrationAccessToken`]) overwrite their contents with zeroes when
+//! they're dropped. The secret types re-exported from the `oauth2` crate (e.g., [`ClientSecret`],
+//! [`AccessToken`], [`AuthorizationCode`], and [`CsrfToken`]) aren't affected. All secret types
+//! are redacted from their `Debug` output regardless of this feature flag.
+//!
+//! ## Using `openidconnect` without an HTTP client
+//!
+//! The HTTP client interface and everything built on it (provider discovery, JSON Web Key Set
+//! fetching, user info requests, dynamic client registration, [`LoginFlow`], [`LoginManager`],
+//! [`TokenManager`], and the [`resource_server`] module) are enabled by the default `net` feature
+//! flag. Applications that only need the claims model, JWT verification, and JSON Web Key
+//! handling (e.g., a resource server that obtains the provider's keys out-of-band) may disable it
+//! to avoid the `futures` dependencies:
+//! ```toml
+//! openidconnect = { version = "1.0", default-features = false, features = ["ring"] }
+//! ```
+//!
+//! ## Selecting a cryptography backend
+//!
+//! Signatures and HMACs are computed using [`ring`](https://docs.rs/ring) by default. Environments
+//! that forbid `ring`'s C and assembly code (or that require FIPS builds of a particular
+//! cryptography library) may instead use the pure-Rust [RustCrypto](https://github.com/RustCrypto)
+//! crates by disabling the default `ring` feature flag and enabling `rustcrypto`:
+//! ```toml
+//! openidconnect = { version = "1.0", default-features = false, features = ["net", "rustcrypto"] }
+//! ```
+//!
+//! At least one of the two feature flags must be enabled. If both are, `ring` is used. The selected
+//! backend is exposed as [`DefaultCryptoBackend`], which implements the [`CryptoBackend`] trait.
+//!
+//! ## Testing
+//!
+//! When the `test-provider` feature flag is enabled, the `test_provider::TestProvider` struct
+//! simulates an OpenID Connect Provider in-process using a generated signing key. It serves
+//! authorization, discovery, JSON Web Key Set, token, and user info responses when used as the
+//! HTTP client, which allows relying parties to test complete login flows (as well as failures
+//! such as expired ID tokens or mismatched nonces) without a network connection. It also issues
+//! back-channel logout tokens (see [`LogoutToken`]) for testing the relying party's logout
+//! endpoint. It must never be used outside of tests.
+//!
+//! Alternatively, the [`RecordingHttpClient`] records the responses returned by a real provider as
+//! JSON fixtures, which the [`ReplayHttpClient`] serves back in subsequent test runs.
+//!
 //! # OpenID Connect Relying Party (Client) Interface
 //!
 //! The [`Client`] struct provides the OpenID Connect Relying Party interface. The most common
@@ -153,9 +221,9 @@
 //! # fn err_wrapper() -> Result<(), failure::Error> {
 //! // Use OpenID Connect Discovery to fetch the provider metadata.
 //! use openidconnect::{OAuth2TokenResponse, TokenResponse};
-//! let provider_metadata = CoreProviderMetadata::discover(
-//!     &IssuerUrl::new("https://accounts.example.com".to_string())?,
-//!     http_client,
+//! let provider_metadata = CoreProviderMetadata::discover_blocking(
+//!     IssuerUrl::new("https://accounts.example.com".to_string())?,
+//!     &http_client,
 //! )?;
 //!
 //! // Create an OpenID Connect client by specifying the client ID, client secret, authorization URL
@@ -510,9 +578,9 @@
 //! # async fn err_wrapper() -> Result<(), failure::Error> {
 //! // Use OpenID Connect Discovery to fetch the provider metadata.
 //! use openidconnect::{OAuth2TokenResponse, TokenResponse};
-//! let provider_metadata = CoreProviderMetadata::discover(
+//! let provider_metadata = CoreProviderMetadata::discover_async(
 //!     IssuerUrl::new("https://accounts.example.com".to_string())?,
-//!     async_http_client,
+//!     &async_http_client,
 //! )
 //! .await?;
 //!
@@ -603,6 +671,13 @@ extern crate pretty_assertions;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(not(any(feature = "ring", feature = "rustcrypto")))]
+compile_error!("at least one of the \"ring\" and \"rustcrypto\" features must be enabled");
+
+#[cfg(feature = "net")]
+use futures::executor::block_on;
+#[cfg(feature = "net")]
+use futures::Future;
 use oauth2::helpers::variant_name;
 use oauth2::ResponseType as OAuth2ResponseType;
 use url::Url;
@@ -610,6 +685,9 @@ use url::Url;
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::str;
+use std::sync::Arc;
+#[cfg(feature = "net")]
+use std::sync::Mutex;
 use std::time::Duration;
 
 pub use oauth2::{
@@ -622,46 +700,130 @@ pub use oauth2::{
 };
 
 ///
-/// Public re-exports of types used for HTTP client interfaces.
+/// Public re-exports of types used for HTTP client interfaces. Requires "net" feature.
 ///
+#[cfg(feature = "net")]
 pub use oauth2::http_types;
 pub use oauth2::url;
 
+pub use authorization::{AuthorizationResponse, AuthorizationResponseError};
+pub use authorization_details::{AuthorizationDetail, TokenRequestAuthorizationDetails};
+pub use authorization_server::AuthorizationServerMetadata;
 pub use claims::{
-    AdditionalClaims, AddressClaim, EmptyAdditionalClaims, GenderClaim, StandardClaims,
+    AdditionalClaims, AddressClaim, ClaimWarning, ClaimsRequest, EmptyAdditionalClaims,
+    GenderClaim, IndividualClaimRequest, StandardClaims, StandardClaimsBuilder,
 };
+///
+/// Derive macro implementing the [`AdditionalClaims`](trait@AdditionalClaims) trait. Requires
+/// "derive" feature.
+///
+#[cfg(feature = "derive")]
+pub use openidconnect_derive::AdditionalClaims;
+pub use crate::access_token::{AccessTokenClaims, JwtAccessToken};
+use crate::client_assertion::{ClientAssertionKey, CLIENT_ASSERTION_TYPE_JWT_BEARER};
+pub use crate::client_config::{ClientConfig, ClientConfigError, ClientSecretRef};
+pub use crate::clock::{Clock, FixedClock, ServerClock, SystemClock};
+#[cfg(feature = "ring")]
+pub use crate::crypto::RingCryptoBackend;
+#[cfg(feature = "rustcrypto")]
+pub use crate::crypto::RustCryptoBackend;
+pub use crate::crypto::{CryptoBackend, DefaultCryptoBackend, HashAlgorithm, RsaPadding};
+pub use crate::dyn_verifier::{DynAccessTokenVerifier, DynIdTokenVerifier};
+pub use crate::error::{ClassifiedError, ErrorKind};
 pub use discovery::{
-    AdditionalProviderMetadata, DiscoveryError, EmptyAdditionalProviderMetadata, ProviderMetadata,
+    AdditionalProviderMetadata, ComplianceIssue, ComplianceReport, ComplianceSeverity,
+    DiscoveryError, DiscoveryWarning, EmptyAdditionalProviderMetadata, EndpointSecurityPolicy,
+    ProviderMetadata,
+};
+#[cfg(feature = "net")]
+pub use discovery::DiscoveryOptions;
+#[cfg(feature = "net")]
+pub use crate::extension_grant::ExtensionGrantTokenRequest;
+#[cfg(feature = "net")]
+pub use crate::fixture::{
+    HttpFixture, RecordingError, RecordingHttpClient, ReplayError, ReplayHttpClient,
+};
+#[cfg(feature = "net")]
+use crate::http_client::{BlockingHttpClient, InstrumentedHttpClient};
+#[cfg(feature = "net")]
+pub use crate::http_client::{
+    AsyncHttpClient, HttpClient, InterceptedHttpClient, Interceptors, RequestKind, RequestOptions,
+    RequestPolicy, RequestUrl, UnexpectedResponse,
+};
+#[cfg(feature = "net")]
+pub use crate::issuer_registry::{IssuerRegistry, IssuerRegistryError};
+#[cfg(feature = "net")]
+pub use crate::login_flow::{LoginFlow, LoginFlowError};
+#[cfg(feature = "net")]
+pub use crate::login_manager::{LoginManager, LoginManagerError};
+pub use crate::login_state::{
+    ConsumingNonceVerifier, CookieStateStore, InMemoryNonceStore, InMemoryStateStore, NonceStore,
+    PendingLogin, StateStore, StateStoreError,
+};
+pub use crate::logout_token::{
+    process_backchannel_logout, BackchannelLogout, BackchannelLogoutError, InMemoryTokenIdCache,
+    LogoutToken, LogoutTokenClaims, TokenIdCache,
+};
+#[cfg(feature = "net")]
+pub use crate::metrics::{MetricsHttpClient, MetricsSink, RequestOutcome};
+pub use crate::multi_issuer::{IssuerTrustSet, MultiIssuerIdTokenVerifier};
+use crate::preflight::AuthorizationPreflight;
+pub use crate::preflight::{
+    AuthorizationPreflightIssue, AuthorizationPreflightReport, AuthorizationPreflightSeverity,
+};
+#[cfg(feature = "net")]
+pub use crate::retry::{RetryError, RetryPolicy, RetryingHttpClient};
+pub use crate::scope::{ScopeSet, StandardScopes};
+pub use crate::security_event::{
+    AccountDisabledReason, SecurityEvent, SecurityEventSubject, SecurityEventToken,
+    SecurityEventTokenClaims, SecurityEventType,
+};
+#[cfg(feature = "net")]
+pub use crate::token_manager::{TokenManager, TokenManagerError};
+pub use crate::token_response::TimestampedTokenResponse;
+pub use crate::token_store::{
+    FileTokenStore, InMemoryTokenStore, StoredToken, TokenStore, TokenStoreError,
 };
 pub use id_token::IdTokenFields;
 pub use id_token::{IdToken, IdTokenClaims};
-pub use jwt::JsonWebTokenError;
-use jwt::{JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader};
+pub use jwt::{
+    JsonWebSignature, JsonWebTokenAlgorithm, JsonWebTokenContentType, JsonWebTokenError,
+    JsonWebTokenHeader, JsonWebTokenJsonPayloadSerde, JsonWebTokenPayloadSerde, JsonWebTokenType,
+    X509CertificateThumbprint,
+};
+use jwt::{JsonWebToken, JsonWebTokenAccess};
 // Flatten the module hierarchy involving types. They're only separated to improve code
 // organization.
 pub use types::{
     AccessTokenHash, AddressCountry, AddressLocality, AddressPostalCode, AddressRegion,
     ApplicationType, Audience, AuthDisplay, AuthPrompt, AuthenticationContextClass,
-    AuthenticationMethodReference, AuthorizationCodeHash, ClaimName, ClaimType, ClientAuthMethod,
-    ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl, EndUserBirthday, EndUserEmail,
-    EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
-    EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
-    EndUserWebsiteUrl, FormattedAddress, GrantType, InitiateLoginUrl, IssuerUrl, JsonWebKey,
-    JsonWebKeyId, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse,
-    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
-    LocalizedClaim, LoginHint, LogoUrl, Nonce, OpPolicyUrl, OpTosUrl, PolicyUrl, PrivateSigningKey,
-    RegistrationAccessToken, RegistrationUrl, RequestUrl, ResponseMode, ResponseType,
-    ResponseTypes, SectorIdentifierUrl, ServiceDocUrl, SigningError, StreetAddress,
-    SubjectIdentifier, SubjectIdentifierType, ToSUrl,
+    AuthenticationMethodReference, AuthenticationMethodReferenceValue, AuthorizationCodeHash,
+    BackchannelLogoutUrl, Birthdate, BirthdateParseError, CheckSessionIframeUrl,
+    CheckSessionStatus, ClaimName, ClaimType, ClientAuthMethod, ClientConfigUrl,
+    ClientContactEmail, ClientName, ClientUrl, DeviceAuthorizationUrl, EndSessionUrl,
+    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
+    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
+    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, GrantType,
+    InitiateLoginUrl, IntrospectionUrl, IssuerUrl, JsonWebKey, JsonWebKeyId, JsonWebKeySet,
+    JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag, LocalizedClaim, LoginHint,
+    LogoUrl, Nonce, OpPolicyUrl, OpTosUrl, PolicyUrl, PrivateSigningKey, RegistrationAccessToken,
+    RegistrationUrl, RequestUrl, ResponseMode, ResponseType, ResponseTypes, RevocationUrl,
+    SectorIdentifierUrl, ServiceDocUrl, SessionId, SessionState, SigningError, StreetAddress,
+    SubjectIdentifier, SubjectIdentifierType, ToSUrl, TrustmarkUrl, VectorOfTrust,
+    VectorOfTrustCategory, VectorOfTrustComponent, VectorOfTrustParseError,
 };
+#[cfg(feature = "net")]
+pub use user_info::UserInfoRequest;
 pub use user_info::{
-    NoUserInfoEndpoint, UserInfoClaims, UserInfoError, UserInfoJsonWebToken, UserInfoRequest,
-    UserInfoUrl,
+    BearerTokenError, BearerTokenErrorType, NoUserInfoEndpoint, UserInfoClaims, UserInfoError,
+    UserInfoJsonWebToken, UserInfoUrl,
 };
 use verification::{AudiencesClaim, IssuerClaim};
 pub use verification::{
-    ClaimsVerificationError, IdTokenVerifier, NonceVerifier, SignatureVerificationError,
-    UserInfoVerifier,
+    AccessTokenVerifier, ClaimsVerificationError, IdTokenVerifier, KeySelectionPolicy,
+    LogoutTokenVerifier, NonceVerifier, SecurityEventTokenVerifier, SignatureVerificationError,
+    UserInfoVerifier, VerificationTimeOptions,
 };
 
 // Defined first since other modules need the macros, and definition order is significant for
@@ -675,8 +837,78 @@ pub mod core;
 /// OpenID Connect Dynamic Client Registration.
 pub mod registration;
 
+/// Claims and helpers for specific OpenID Connect Providers.
+pub mod providers;
+
+/// Helpers for native (e.g., desktop, mobile, and command-line) applications, as described in
+/// [RFC 8252](https://tools.ietf.org/html/rfc8252).
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
+
+/// Bearer access token validation for resource servers. Requires "net" feature.
+#[cfg(feature = "net")]
+pub mod resource_server;
+
+/// Login extractors and middleware for the [actix-web](https://crates.io/crates/actix-web) web
+/// framework. Requires "actix-web" feature.
+#[cfg(feature = "actix-web")]
+pub mod actix_web;
+
+/// Login extractors and handlers for the [axum](https://crates.io/crates/axum) web framework.
+/// Requires "axum" feature.
+#[cfg(feature = "axum")]
+pub mod axum;
+
+/// HTTP client backed by the [hyper](https://crates.io/crates/hyper) crate.
+/// Requires "hyper" feature.
+#[cfg(feature = "hyper")]
+pub mod hyper;
+
+/// HTTP client backed by the [reqwest](https://crates.io/crates/reqwest) crate.
+/// Requires "reqwest" feature.
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+
+/// HTTP client backed by the browser's `fetch` API for `wasm32-unknown-unknown` targets.
+/// Requires "wasm" feature.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+
+/// Synchronous HTTP client backed by the [ureq](https://crates.io/crates/ureq) crate.
+/// Requires "ureq" feature.
+#[cfg(feature = "ureq")]
+pub mod ureq;
+
+/// In-process mock OpenID Connect Provider for testing relying parties.
+/// Requires "test-provider" feature.
+#[cfg(feature = "test-provider")]
+pub mod test_provider;
+
+/// Bearer token validation middleware for [tower](https://crates.io/crates/tower) services.
+/// Requires "tower" feature.
+#[cfg(feature = "tower")]
+pub mod tower;
+
+// Not public API. Used by the code generated by the AdditionalClaims derive macro, which can't
+// assume that the calling crate depends on serde directly.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    pub use serde_derive::{Deserialize, Serialize};
+}
+
+// Private module for JWT access tokens; exported publicly via the pub use above.
+mod access_token;
+
+// Private module for Security Event Tokens; exported publicly via the pub use above.
+mod security_event;
+
 // Private modules since we may move types between different modules; these are exported publicly
 // via the pub use above.
+mod authorization;
+mod authorization_details;
+mod authorization_server;
 mod claims;
 mod discovery;
 mod helpers;
@@ -685,14 +917,96 @@ pub(crate) mod types;
 mod user_info;
 mod verification;
 
+// Private module for the names of standard claims; shared with the openidconnect-derive crate.
+mod standard_claims;
+
+// Private module for signing client assertions.
+mod client_assertion;
+
+// Private module for declarative client configuration; exported publicly via the pub use above.
+mod client_config;
+
+// Private module for time sources; exported publicly via the pub use above.
+mod clock;
+
+// Private module for cryptography backends; exported publicly via the pub use above.
+mod crypto;
+
+// Private module for type-erased verifiers; exported publicly via the pub use above.
+mod dyn_verifier;
+
+// Private module for classifying errors; exported publicly via the pub use above.
+mod error;
+
+// Private module for extension grant token requests; exported publicly via the pub use above.
+#[cfg(feature = "net")]
+mod extension_grant;
+
+// Private module for recording and replaying HTTP fixtures; exported publicly via the pub use
+// above.
+#[cfg(feature = "net")]
+mod fixture;
+
+// Private module for the HTTP client interface; exported publicly via the pub use above.
+#[cfg(feature = "net")]
+mod http_client;
+
 // Private module for HTTP(S) utilities.
+#[cfg(feature = "net")]
 mod http_utils;
 
+// Private module for caching the clients of multiple tenants' providers; exported publicly via the
+// pub use above.
+#[cfg(feature = "net")]
+mod issuer_registry;
+
+// Private modules for tracking pending logins; exported publicly via the pub use above.
+#[cfg(feature = "net")]
+mod login_flow;
+#[cfg(feature = "net")]
+mod login_manager;
+mod login_state;
+
+// Private module for back-channel logout tokens; exported publicly via the pub use above.
+mod logout_token;
+
+// Private module for reporting request metrics; exported publicly via the pub use above.
+#[cfg(feature = "net")]
+mod metrics;
+
+// Private module for verifying ID tokens from multiple issuers; exported publicly via the pub use
+// above.
+mod multi_issuer;
+
+// Private module for authorization request preflight validation; exported publicly via the pub
+// use above.
+mod preflight;
+
+// Private module for retrying failed HTTP requests; exported publicly via the pub use above.
+#[cfg(feature = "net")]
+mod retry;
+
+// Private module for scope utilities; exported publicly via the pub use above.
+mod scope;
+
 // Private module for JWT utilities.
 mod jwt;
 
+// Private modules for managing and persisting token lifecycles; exported publicly via the pub use
+// above.
+#[cfg(feature = "net")]
+mod token_manager;
+mod token_response;
+mod token_store;
+
+// Private module for the state shared by the web framework integrations; exported publicly via the
+// framework-specific modules above.
+#[cfg(any(feature = "actix-web", feature = "axum"))]
+mod web;
+
+#[cfg(feature = "net")]
 const CONFIG_URL_SUFFIX: &str = ".well-known/openid-configuration";
-const OPENID_SCOPE: &str = "openid";
+const PKCE_METHOD_S256: &str = "S256";
 
 ///
 /// Authentication flow, which determines how the Authorization Server returns the OpenID Connect
@@ -729,6 +1043,121 @@ pub enum AuthenticationFlow<RT: ResponseType> {
     Hybrid(Vec<RT>),
 }
 
+///
+/// Method used by a [`Client`] to authenticate to the token endpoint, as defined in
+/// [Section 9](https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication) of
+/// OpenID Connect Core.
+///
+/// Variants are declared from strongest to weakest.
+///
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TokenEndpointAuthMethod {
+    ///
+    /// JSON Web Token client assertion signed with a private key registered with the provider.
+    ///
+    PrivateKeyJwt,
+    ///
+    /// JSON Web Token client assertion signed with the client secret used as an HMAC key.
+    ///
+    ClientSecretJwt,
+    ///
+    /// Client secret passed via the HTTP Basic authentication scheme.
+    ///
+    ClientSecretBasic,
+    ///
+    /// Client secret passed via the POST request body.
+    ///
+    ClientSecretPost,
+    ///
+    /// No client authentication (i.e., a public client).
+    ///
+    None,
+}
+impl TokenEndpointAuthMethod {
+    // Candidates for negotiation, from strongest to weakest.
+    const RANKED: &'static [Self] = &[
+        TokenEndpointAuthMethod::PrivateKeyJwt,
+        TokenEndpointAuthMethod::ClientSecretJwt,
+        TokenEndpointAuthMethod::ClientSecretBasic,
+        TokenEndpointAuthMethod::ClientSecretPost,
+    ];
+
+    ///
+    /// Returns the name of this method as used in the `token_endpoint_auth_methods_supported`
+    /// provider metadata field.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TokenEndpointAuthMethod::PrivateKeyJwt => "private_key_jwt",
+            TokenEndpointAuthMethod::ClientSecretJwt => "client_secret_jwt",
+            TokenEndpointAuthMethod::ClientSecretBasic => "client_secret_basic",
+            TokenEndpointAuthMethod::ClientSecretPost => "client_secret_post",
+            TokenEndpointAuthMethod::None => "none",
+        }
+    }
+
+    ///
+    /// Selects the strongest method supported by both the provider and the client's credentials.
+    ///
+    /// `supported` contains the methods listed in the provider's
+    /// `token_endpoint_auth_methods_supported` metadata field, which defaults to
+    /// `client_secret_basic` if omitted. `available` contains the methods for which the client has
+    /// credentials: [`TokenEndpointAuthMethod::ClientSecretBasic`] and
+    /// [`TokenEndpointAuthMethod::ClientSecretPost`] for clients with a secret, and the method
+    /// corresponding to the client's assertion signing key, if any (see
+    /// [`Client::set_client_assertion_signing_key`]). Clients without any credentials use
+    /// [`TokenEndpointAuthMethod::None`].
+    ///
+    /// Returns an error if the provider supports none of the `available` methods.
+    ///
+    pub fn negotiate<CA>(
+        supported: Option<&[CA]>,
+        available: &[Self],
+    ) -> Result<Self, TokenEndpointAuthMethodError>
+    where
+        CA: ClientAuthMethod,
+    {
+        if available.is_empty() {
+            return Ok(TokenEndpointAuthMethod::None);
+        }
+        let supported = supported.map_or_else(
+            || vec![TokenEndpointAuthMethod::ClientSecretBasic.as_str()],
+            |supported| supported.iter().map(variant_name).collect(),
+        );
+        Self::RANKED
+            .iter()
+            .filter(|method| available.contains(method))
+            .find(|method| supported.contains(&method.as_str()))
+            .cloned()
+            .ok_or_else(|| {
+                TokenEndpointAuthMethodError(supported.into_iter().map(str::to_string).collect())
+            })
+    }
+
+    // Returns the method with the specified `token_endpoint_auth_methods_supported` name, if known.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::RANKED
+            .iter()
+            .chain(&[TokenEndpointAuthMethod::None])
+            .find(|method| method.as_str() == name)
+            .cloned()
+    }
+}
+
+///
+/// Error returned by [`TokenEndpointAuthMethod::negotiate`] when the provider supports none of
+/// the token endpoint authentication methods for which the client has credentials. This error
+/// holds the names of the methods supported by the provider.
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+#[fail(
+    display = "No mutually supported token endpoint auth method (provider: {:?})",
+    _0
+)]
+pub struct TokenEndpointAuthMethodError(pub Vec<String>);
+
 /// OpenID Connect client.
 #[derive(Clone, Debug)]
 pub struct Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
@@ -750,9 +1179,30 @@ where
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
     issuer: IssuerUrl,
+    auth_url: AuthUrl,
+    token_url: Option<TokenUrl>,
     userinfo_endpoint: Option<UserInfoUrl>,
     jwks: JsonWebKeySet<JS, JT, JU, K>,
+    token_endpoint_auth_method: TokenEndpointAuthMethod,
+    token_endpoint_auth_methods_supported: Option<Vec<TokenEndpointAuthMethod>>,
+    client_assertion_key: Option<ClientAssertionKey<JS, JT, JU, K>>,
     use_openid_scope: bool,
+    #[cfg(feature = "net")]
+    interceptors: Interceptors,
+    code_challenge_methods_supported: Option<Vec<PkceCodeChallengeMethod>>,
+    require_pkce: bool,
+    require_issuer_parameter: bool,
+    authorization_preflight: Option<Arc<AuthorizationPreflight>>,
+    require_authorization_preflight: bool,
+    oauth21_strict: bool,
+    redirect_url: Option<RedirectUrl>,
+    device_authorization_url: Option<DeviceAuthorizationUrl>,
+    introspection_url: Option<IntrospectionUrl>,
+    revocation_url: Option<RevocationUrl>,
+    // Serialized `ProviderMetadata` so that neither its type parameters nor any `Send`, `Sync`, or
+    // `'static` bounds need to be added to `Client`. Serialization errors are retained as strings
+    // and reported by `Client::provider_metadata`.
+    provider_metadata: Option<Arc<Result<serde_json::Value, String>>>,
     _phantom: PhantomData<(AC, AD, GC, JE, P)>,
 }
 impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
@@ -783,19 +1233,42 @@ where
         userinfo_endpoint: Option<UserInfoUrl>,
         jwks: JsonWebKeySet<JS, JT, JU, K>,
     ) -> Self {
+        let token_endpoint_auth_method = if client_secret.is_some() {
+            TokenEndpointAuthMethod::ClientSecretBasic
+        } else {
+            TokenEndpointAuthMethod::None
+        };
         Client {
             oauth2_client: oauth2::Client::new(
                 client_id.clone(),
                 client_secret.clone(),
-                auth_url,
-                token_url,
+                auth_url.clone(),
+                token_url.clone(),
             ),
             client_id,
             client_secret,
             issuer,
+            auth_url,
+            token_url,
             userinfo_endpoint,
             jwks,
+            token_endpoint_auth_method,
+            token_endpoint_auth_methods_supported: None,
+            client_assertion_key: None,
             use_openid_scope: true,
+            #[cfg(feature = "net")]
+            interceptors: Interceptors::default(),
+            code_challenge_methods_supported: None,
+            require_pkce: false,
+            require_issuer_parameter: false,
+            authorization_preflight: None,
+            require_authorization_preflight: false,
+            oauth21_strict: false,
+            redirect_url: None,
+            device_authorization_url: None,
+            introspection_url: None,
+            revocation_url: None,
+            provider_metadata: None,
             _phantom: PhantomData,
         }
     }
@@ -803,7 +1276,19 @@ where
     ///
     /// Initializes an OpenID Connect client from OpenID Connect Discovery provider metadata.
     ///
-    /// Use [`ProviderMetadata::discover`] to fetch the provider metadata.
+    /// Use [`ProviderMetadata::discover_async`] to fetch the provider metadata. The PKCE code
+    /// challenge methods supported by the provider (if advertised) are retained for use by
+    /// [`Client::require_pkce_if_supported`]. The provider's device authorization, token
+    /// introspection, and token revocation endpoints (if advertised) are used as the
+    /// [`Client::device_authorization_url`], [`Client::introspection_url`], and
+    /// [`Client::revocation_url`], respectively. If the provider advertises
+    /// `authorization_response_iss_parameter_supported`, the `iss` authorization response
+    /// parameter is required (see [`Client::set_require_issuer_parameter`]). The token endpoint
+    /// authentication method is negotiated from `token_endpoint_auth_methods_supported` (see
+    /// [`TokenEndpointAuthMethod::negotiate`]). If the provider doesn't support any method for
+    /// which the client has credentials, the client uses the same method as [`Client::new`]; use
+    /// [`Client::try_from_provider_metadata`] to reject such providers instead. The provider
+    /// metadata itself is retained and may be accessed via [`Client::provider_metadata`].
     ///
     pub fn from_provider_metadata<A, CA, CN, CT, G, JK, RM, RT, S>(
         provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
@@ -821,7 +1306,52 @@ where
         RT: ResponseType,
         S: SubjectIdentifierType,
     {
-        Self::new(
+        Self::from_provider_metadata_impl(provider_metadata, client_id, client_secret).0
+    }
+
+    ///
+    /// Initializes an OpenID Connect client from OpenID Connect Discovery provider metadata as in
+    /// [`Client::from_provider_metadata`], but returns an error if the provider doesn't support any
+    /// token endpoint authentication method for which the client has credentials.
+    ///
+    pub fn try_from_provider_metadata<A, CA, CN, CT, G, JK, RM, RT, S>(
+        provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> Result<Self, TokenEndpointAuthMethodError>
+    where
+        A: AdditionalProviderMetadata,
+        CA: ClientAuthMethod,
+        CN: ClaimName,
+        CT: ClaimType,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        RM: ResponseMode,
+        RT: ResponseType,
+        S: SubjectIdentifierType,
+    {
+        let (client, negotiated) =
+            Self::from_provider_metadata_impl(provider_metadata, client_id, client_secret);
+        negotiated.map(|()| client)
+    }
+
+    fn from_provider_metadata_impl<A, CA, CN, CT, G, JK, RM, RT, S>(
+        provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> (Self, Result<(), TokenEndpointAuthMethodError>)
+    where
+        A: AdditionalProviderMetadata,
+        CA: ClientAuthMethod,
+        CN: ClaimName,
+        CT: ClaimType,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        RM: ResponseMode,
+        RT: ResponseType,
+        S: SubjectIdentifierType,
+    {
+        let mut client = Self::new(
             client_id,
             client_secret,
             provider_metadata.issuer().clone(),
@@ -829,7 +1359,188 @@ where
             provider_metadata.token_endpoint().cloned(),
             provider_metadata.userinfo_endpoint().cloned(),
             provider_metadata.jwks().to_owned(),
-        )
+        );
+        client.code_challenge_methods_supported =
+            provider_metadata.code_challenge_methods_supported().cloned();
+        client.require_issuer_parameter =
+            provider_metadata.authorization_response_iss_parameter_supported() == Some(true);
+        client.authorization_preflight = Some(Arc::new(AuthorizationPreflight::new(
+            provider_metadata.response_types_supported(),
+            provider_metadata.scopes_supported(),
+            provider_metadata.prompt_values_supported(),
+        )));
+        let negotiated = client.negotiate_token_endpoint_auth_method(
+            provider_metadata
+                .token_endpoint_auth_methods_supported()
+                .map(Vec::as_slice),
+        );
+        client.device_authorization_url =
+            provider_metadata.device_authorization_endpoint().cloned();
+        client.introspection_url = provider_metadata.introspection_endpoint().cloned();
+        client.revocation_url = provider_metadata.revocation_endpoint().cloned();
+        client.provider_metadata = Some(Arc::new(
+            serde_json::to_value(&provider_metadata).map_err(|err| err.to_string()),
+        ));
+        (client, negotiated)
+    }
+
+    ///
+    /// Initializes an OpenID Connect client from a [`ClientConfig`] and the provider metadata of
+    /// the configured issuer (e.g., as returned by [`ProviderMetadata::discover_async`]).
+    ///
+    /// The client is initialized as in [`Client::from_provider_metadata`], after which the
+    /// configured redirect URI, token endpoint authentication method, and PKCE requirement are
+    /// applied. The client secret, if any, is resolved via [`ClientSecretRef::resolve`]. Returns
+    /// [`ClientConfigError::IssuerMismatch`] if the provider metadata is for a different issuer,
+    /// and [`ClientConfigError::TokenEndpointAuthMethod`] if no token endpoint authentication
+    /// method is configured and none can be negotiated.
+    ///
+    pub fn from_config<A, CA, CN, CT, G, JK, RM, RT, S>(
+        config: &ClientConfig,
+        provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+    ) -> Result<Self, ClientConfigError>
+    where
+        A: AdditionalProviderMetadata,
+        CA: ClientAuthMethod,
+        CN: ClaimName,
+        CT: ClaimType,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        RM: ResponseMode,
+        RT: ResponseType,
+        S: SubjectIdentifierType,
+    {
+        if *provider_metadata.issuer() != config.issuer {
+            return Err(ClientConfigError::IssuerMismatch(
+                provider_metadata.issuer().to_string(),
+            ));
+        }
+        let client_secret = config
+            .client_secret
+            .as_ref()
+            .map(ClientSecretRef::resolve)
+            .transpose()?;
+
+        // A configured authentication method overrides negotiation, so negotiation failures are
+        // only reported for clients that rely on it.
+        let mut client = if let Some(auth_method) = config.token_endpoint_auth_method {
+            Self::from_provider_metadata(provider_metadata, config.client_id.clone(), client_secret)
+                .set_token_endpoint_auth_method(auth_method)
+        } else {
+            Self::try_from_provider_metadata(
+                provider_metadata,
+                config.client_id.clone(),
+                client_secret,
+            )
+            .map_err(ClientConfigError::TokenEndpointAuthMethod)?
+        };
+        if let Some(redirect_url) = config.redirect_uris.first() {
+            client = client.set_redirect_uri(redirect_url.clone());
+        }
+        Ok(client.set_require_pkce(config.require_pkce))
+    }
+
+    ///
+    /// Returns the provider metadata from which this client was initialized via
+    /// [`Client::from_provider_metadata`].
+    ///
+    /// This allows applications to check the provider's capabilities (e.g., `scopes_supported`,
+    /// `claims_supported`, or its logout endpoints) without repeating OpenID Connect Discovery.
+    /// The metadata is retained in its serialized form, so it may be requested as any metadata
+    /// type that is able to deserialize it. Since [`ProviderMetadata::jwks`] isn't serialized, the
+    /// returned metadata contains the JSON Web Key Set used by this client instead.
+    ///
+    /// Returns `None` if the client wasn't initialized from provider metadata (e.g., via
+    /// [`Client::new`]), and an error describing the mismatch if the metadata can't be
+    /// represented by the requested metadata type.
+    ///
+    pub fn provider_metadata<A, CA, CN, CT, G, JK, RM, RT, S>(
+        &self,
+    ) -> Option<
+        Result<
+            ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+            serde_json::Error,
+        >,
+    >
+    where
+        A: AdditionalProviderMetadata,
+        CA: ClientAuthMethod,
+        CN: ClaimName,
+        CT: ClaimType,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        RM: ResponseMode,
+        RT: ResponseType,
+        S: SubjectIdentifierType,
+    {
+        let provider_metadata = match self.provider_metadata.as_ref()?.as_ref() {
+            Ok(provider_metadata) => {
+                serde_json::from_value(provider_metadata.clone()).map(|provider_metadata| {
+                    ProviderMetadata::set_jwks(provider_metadata, self.jwks.clone())
+                })
+            }
+            Err(err) => Err(serde::de::Error::custom(format!(
+                "failed to serialize provider metadata: {}",
+                err
+            ))),
+        };
+        Some(provider_metadata)
+    }
+
+    ///
+    /// Initializes an OAuth 2.0 client from
+    /// [RFC 8414](https://tools.ietf.org/html/rfc8414) authorization server metadata.
+    ///
+    /// Use [`AuthorizationServerMetadata::discover_async`] to fetch the metadata from authorization
+    /// servers that don't support OpenID Connect Discovery. Since such metadata doesn't include a
+    /// user info endpoint, the resulting client doesn't support user info requests. The JSON Web
+    /// Key Set is empty unless the authorization server advertises a `jwks_uri`. The PKCE code
+    /// challenge methods, the device authorization, token introspection, and token revocation
+    /// endpoints, and the `iss` authorization response parameter requirement are retained, and the
+    /// token endpoint authentication method is negotiated, as in
+    /// [`Client::from_provider_metadata`].
+    ///
+    pub fn from_authorization_server_metadata<A, CA, G, RM, RT>(
+        metadata: AuthorizationServerMetadata<A, CA, G, JS, JT, JU, K, RM, RT>,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> Self
+    where
+        A: AdditionalProviderMetadata,
+        CA: ClientAuthMethod,
+        G: GrantType,
+        RM: ResponseMode,
+        RT: ResponseType,
+    {
+        let mut client = Self::new(
+            client_id,
+            client_secret,
+            metadata.issuer().clone(),
+            metadata.authorization_endpoint().clone(),
+            metadata.token_endpoint().cloned(),
+            None,
+            metadata.jwks().to_owned(),
+        );
+        client.code_challenge_methods_supported =
+            metadata.code_challenge_methods_supported().cloned();
+        client.require_issuer_parameter =
+            metadata.authorization_response_iss_parameter_supported() == Some(true);
+        client.authorization_preflight = Some(Arc::new(AuthorizationPreflight::new(
+            metadata.response_types_supported(),
+            metadata.scopes_supported(),
+            None,
+        )));
+        // As in `from_provider_metadata`, clients without a mutually supported method keep the
+        // default method.
+        let _ = client.negotiate_token_endpoint_auth_method(
+            metadata
+                .token_endpoint_auth_methods_supported()
+                .map(Vec::as_slice),
+        );
+        client.device_authorization_url = metadata.device_authorization_endpoint().cloned();
+        client.introspection_url = metadata.introspection_endpoint().cloned();
+        client.revocation_url = metadata.revocation_endpoint().cloned();
+        client
     }
 
     ///
@@ -839,16 +1550,167 @@ where
     /// The default is to use HTTP Basic authentication, as recommended in
     /// [Section 2.3.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-2.3.1).
     ///
-    pub fn set_auth_type(mut self, auth_type: AuthType) -> Self {
-        self.oauth2_client = self.oauth2_client.set_auth_type(auth_type);
+    pub fn set_auth_type(self, auth_type: AuthType) -> Self {
+        let method = if self.client_secret.is_none() {
+            TokenEndpointAuthMethod::None
+        } else if let AuthType::RequestBody = auth_type {
+            TokenEndpointAuthMethod::ClientSecretPost
+        } else {
+            TokenEndpointAuthMethod::ClientSecretBasic
+        };
+        self.set_token_endpoint_auth_method(method)
+    }
+
+    ///
+    /// Overrides the method used to authenticate to the token endpoint, which is otherwise
+    /// negotiated by [`Client::from_provider_metadata`].
+    ///
+    /// The client secret is only sent to the provider by
+    /// [`TokenEndpointAuthMethod::ClientSecretBasic`] and
+    /// [`TokenEndpointAuthMethod::ClientSecretPost`]. The JWT-based methods require a client
+    /// assertion signing key (see [`Client::set_client_assertion_signing_key`]).
+    ///
+    pub fn set_token_endpoint_auth_method(mut self, method: TokenEndpointAuthMethod) -> Self {
+        self.token_endpoint_auth_method = method;
+        self.update_oauth2_client();
+        self
+    }
+
+    ///
+    /// Configures the key used to sign client assertions for the
+    /// [`TokenEndpointAuthMethod::PrivateKeyJwt`] (asymmetric signing algorithms) or
+    /// [`TokenEndpointAuthMethod::ClientSecretJwt`] (HMAC signing algorithms, using the client
+    /// secret as the key) token endpoint authentication methods.
+    ///
+    /// The corresponding method is selected unless the client was initialized from provider
+    /// metadata that doesn't list it in `token_endpoint_auth_methods_supported`. Client assertions
+    /// are signed when submitting token requests via [`Client::request_code_exchange`],
+    /// [`Client::request_refresh_token`], or [`Client::exchange_extension_grant`]. Other token
+    /// requests must add the parameters returned by [`Client::client_assertion_params`].
+    ///
+    pub fn set_client_assertion_signing_key<S>(mut self, signing_key: S, alg: JS) -> Self
+    where
+        S: PrivateSigningKey<JS, JT, JU, K> + Send + Sync + 'static,
+    {
+        let key = ClientAssertionKey::new(signing_key, alg);
+        let auth_method = key.auth_method();
+        self.client_assertion_key = Some(key);
+        let supported = self
+            .token_endpoint_auth_methods_supported
+            .as_ref()
+            .map_or(true, |supported| supported.contains(&auth_method));
+        if supported {
+            self = self.set_token_endpoint_auth_method(auth_method);
+        }
         self
     }
 
+    ///
+    /// Signs a new client assertion and returns the `client_assertion_type` and `client_assertion`
+    /// token request parameters, or no parameters if the client doesn't use a JWT-based token
+    /// endpoint authentication method.
+    ///
+    /// Returns an error if no client assertion signing key is configured (see
+    /// [`Client::set_client_assertion_signing_key`]), if the client has no token endpoint, or if
+    /// signing fails.
+    ///
+    pub fn client_assertion_params(
+        &self,
+    ) -> Result<Vec<(&'static str, String)>, JsonWebTokenError> {
+        match self.token_endpoint_auth_method {
+            TokenEndpointAuthMethod::PrivateKeyJwt | TokenEndpointAuthMethod::ClientSecretJwt => {}
+            _ => return Ok(Vec::new()),
+        }
+        let key = self.client_assertion_key.as_ref().ok_or_else(|| {
+            JsonWebTokenError::Unsupported("no client assertion signing key configured".to_string())
+        })?;
+        let token_url = self.token_url.as_ref().ok_or_else(|| {
+            JsonWebTokenError::InvalidClaims("no token endpoint configured".to_string())
+        })?;
+        let assertion = key.sign_assertion::<JE>(&self.client_id, token_url)?;
+        Ok(vec![
+            (
+                "client_assertion_type",
+                CLIENT_ASSERTION_TYPE_JWT_BEARER.to_string(),
+            ),
+            ("client_assertion", assertion),
+        ])
+    }
+
+    // Records the provider's supported token endpoint authentication methods and selects the
+    // strongest one for which the client has credentials. The current method is retained if
+    // negotiation fails.
+    fn negotiate_token_endpoint_auth_method<CA>(
+        &mut self,
+        supported: Option<&[CA]>,
+    ) -> Result<(), TokenEndpointAuthMethodError>
+    where
+        CA: ClientAuthMethod,
+    {
+        self.token_endpoint_auth_methods_supported = Some(supported.map_or_else(
+            || vec![TokenEndpointAuthMethod::ClientSecretBasic],
+            |supported| {
+                supported
+                    .iter()
+                    .filter_map(|method| TokenEndpointAuthMethod::from_name(variant_name(method)))
+                    .collect()
+            },
+        ));
+
+        let mut available = Vec::new();
+        if let Some(ref key) = self.client_assertion_key {
+            available.push(key.auth_method());
+        }
+        if self.client_secret.is_some() {
+            available.push(TokenEndpointAuthMethod::ClientSecretBasic);
+            available.push(TokenEndpointAuthMethod::ClientSecretPost);
+        }
+        self.token_endpoint_auth_method =
+            TokenEndpointAuthMethod::negotiate(supported, &available)?;
+        self.update_oauth2_client();
+        Ok(())
+    }
+
+    // Rebuilds the underlying OAuth2 client so that the client secret is only sent by the methods
+    // that transmit it directly. Other clients identify themselves via the `client_id` parameter.
+    fn update_oauth2_client(&mut self) {
+        let (client_secret, auth_type) = match self.token_endpoint_auth_method {
+            TokenEndpointAuthMethod::ClientSecretBasic => {
+                (self.client_secret.clone(), AuthType::BasicAuth)
+            }
+            TokenEndpointAuthMethod::ClientSecretPost => {
+                (self.client_secret.clone(), AuthType::RequestBody)
+            }
+            TokenEndpointAuthMethod::PrivateKeyJwt
+            | TokenEndpointAuthMethod::ClientSecretJwt
+            | TokenEndpointAuthMethod::None => (None, AuthType::RequestBody),
+        };
+        let mut oauth2_client = oauth2::Client::new(
+            self.client_id.clone(),
+            client_secret,
+            self.auth_url.clone(),
+            self.token_url.clone(),
+        )
+        .set_auth_type(auth_type);
+        if let Some(ref redirect_url) = self.redirect_url {
+            oauth2_client = oauth2_client.set_redirect_url(redirect_url.clone());
+        }
+        self.oauth2_client = oauth2_client;
+    }
+
+    ///
+    /// Returns the method used to authenticate to the token endpoint.
+    ///
+    pub fn token_endpoint_auth_method(&self) -> TokenEndpointAuthMethod {
+        self.token_endpoint_auth_method
+    }
+
     ///
     /// Sets the the redirect URL used by the authorization endpoint.
     ///
     pub fn set_redirect_uri(mut self, redirect_uri: RedirectUrl) -> Self {
-        self.oauth2_client = self.oauth2_client.set_redirect_url(redirect_uri);
+        self.oauth2_client = self.oauth2_client.set_redirect_url(redirect_uri.clone());
+        self.redirect_url = Some(redirect_uri);
         self
     }
 
@@ -866,11 +1728,252 @@ where
     ///
     /// Disables the `openid` scope from being requested automatically.
     ///
+    /// OpenID Connect authentication requests must still include the `openid` scope, which may be
+    /// added explicitly via [`AuthorizationRequest::add_scope`]. Otherwise,
+    /// [`AuthorizationRequest::try_url`] returns an error, while [`AuthorizationRequest::url`]
+    /// returns a URL without the scope.
+    ///
     pub fn disable_openid_scope(mut self) -> Self {
-        self.use_openid_scope = true;
+        self.use_openid_scope = false;
+        self
+    }
+
+    ///
+    /// Requires every authorization request to use
+    /// [Proof Key for Code Exchange](https://tools.ietf.org/html/rfc7636) (PKCE) with the `S256`
+    /// code challenge method, as recommended by OAuth 2.1.
+    ///
+    /// When enabled, [`AuthorizationRequest::try_url`] returns an error (and
+    /// [`AuthorizationRequest::url`] panics) if [`AuthorizationRequest::set_pkce_challenge`] was
+    /// not called with an `S256` challenge, or if the provider metadata passed to
+    /// [`Client::from_provider_metadata`] lists `code_challenge_methods_supported` without `S256`.
+    /// PKCE is not required by default.
+    ///
+    pub fn set_require_pkce(mut self, require_pkce: bool) -> Self {
+        self.require_pkce = require_pkce;
+        self
+    }
+
+    ///
+    /// Requires every authorization request to pass preflight validation against the provider
+    /// metadata (see [`AuthorizationRequest::preflight`]).
+    ///
+    /// When enabled, [`AuthorizationRequest::try_url`] returns an error (and
+    /// [`AuthorizationRequest::url`] panics) if the preflight report contains any errors, such as
+    /// a response type or prompt value the provider doesn't support. Warnings don't cause
+    /// requests to fail. This has no effect unless the client was initialized via
+    /// [`Client::from_provider_metadata`] or the `from_authorization_server_metadata`
+    /// constructor. Preflight validation is not required by default.
+    ///
+    pub fn set_require_authorization_preflight(mut self, require_preflight: bool) -> Self {
+        self.require_authorization_preflight = require_preflight;
+        self
+    }
+
+    ///
+    /// Requires PKCE with the `S256` code challenge method (see [`Client::set_require_pkce`]) if
+    /// the provider advertises support for it via `code_challenge_methods_supported`.
+    ///
+    /// This has no effect unless the client was initialized via
+    /// [`Client::from_provider_metadata`].
+    ///
+    pub fn require_pkce_if_supported(self) -> Self {
+        let supports_s256 = self
+            .code_challenge_methods_supported
+            .as_ref()
+            .map_or(false, |methods| {
+                methods.iter().any(|method| method.as_str() == PKCE_METHOD_S256)
+            });
+        if supports_s256 {
+            self.set_require_pkce(true)
+        } else {
+            self
+        }
+    }
+
+    ///
+    /// Enforces the [OAuth 2.1](https://tools.ietf.org/html/draft-ietf-oauth-v2-1) security
+    /// profile.
+    ///
+    /// This single switch configures the client to:
+    ///  * require PKCE with the `S256` code challenge method (see [`Client::set_require_pkce`]);
+    ///  * reject authorization requests using the Implicit or Hybrid Flows, which return tokens
+    ///    from the Authorization Endpoint;
+    ///  * reject per-request redirect URLs (see [`AuthorizationRequest::set_redirect_url`]) that
+    ///    don't exactly match the string passed to [`Client::set_redirect_uri`]; and
+    ///  * reject authorization responses returned in the URL fragment (see
+    ///    [`Client::authorization_response`]).
+    ///
+    /// Violations are reported by [`AuthorizationRequest::try_url`] (and cause
+    /// [`AuthorizationRequest::url`] to panic) and by [`Client::authorization_response`].
+    ///
+    pub fn oauth21_strict(self) -> Self {
+        let mut client = self.set_require_pkce(true);
+        client.oauth21_strict = true;
+        client
+    }
+
+    ///
+    /// Returns whether the client enforces the OAuth 2.1 security profile (see
+    /// [`Client::oauth21_strict`]).
+    ///
+    pub fn is_oauth21_strict(&self) -> bool {
+        self.oauth21_strict
+    }
+
+    ///
+    /// Requires authorization responses to include an `iss` parameter, as defined by
+    /// [RFC 9207](https://tools.ietf.org/html/rfc9207).
+    ///
+    /// Regardless of this setting, [`LoginManager::complete`] rejects authorization responses whose
+    /// `iss` parameter doesn't match the client's issuer, which mitigates mix-up attacks when a
+    /// client interacts with multiple providers. Requiring the parameter additionally rejects
+    /// responses that omit it. This is enabled by default for clients initialized via
+    /// [`Client::from_provider_metadata`] if the provider advertises
+    /// `authorization_response_iss_parameter_supported`.
+    ///
+    pub fn set_require_issuer_parameter(mut self, require_issuer_parameter: bool) -> Self {
+        self.require_issuer_parameter = require_issuer_parameter;
+        self
+    }
+
+    ///
+    /// Returns whether authorization responses must include an `iss` parameter (see
+    /// [`Client::set_require_issuer_parameter`]).
+    ///
+    pub fn require_issuer_parameter(&self) -> bool {
+        self.require_issuer_parameter
+    }
+
+    ///
+    /// Sets the device authorization endpoint used by the
+    /// [Device Authorization Grant](https://tools.ietf.org/html/rfc8628).
+    ///
+    /// Clients initialized via [`Client::from_provider_metadata`] use the provider's
+    /// `device_authorization_endpoint` by default, so this is only necessary if the provider
+    /// doesn't advertise one via OpenID Connect Discovery.
+    ///
+    pub fn set_device_authorization_url(
+        mut self,
+        device_authorization_url: Option<DeviceAuthorizationUrl>,
+    ) -> Self {
+        self.device_authorization_url = device_authorization_url;
+        self
+    }
+
+    ///
+    /// Returns the device authorization endpoint used by the
+    /// [Device Authorization Grant](https://tools.ietf.org/html/rfc8628), if any.
+    ///
+    pub fn device_authorization_url(&self) -> Option<&DeviceAuthorizationUrl> {
+        self.device_authorization_url.as_ref()
+    }
+
+    ///
+    /// Sets the [token introspection](https://tools.ietf.org/html/rfc7662) endpoint.
+    ///
+    /// Clients initialized via [`Client::from_provider_metadata`] use the provider's
+    /// `introspection_endpoint` by default.
+    ///
+    pub fn set_introspection_url(mut self, introspection_url: Option<IntrospectionUrl>) -> Self {
+        self.introspection_url = introspection_url;
+        self
+    }
+
+    ///
+    /// Returns the [token introspection](https://tools.ietf.org/html/rfc7662) endpoint, if any.
+    ///
+    pub fn introspection_url(&self) -> Option<&IntrospectionUrl> {
+        self.introspection_url.as_ref()
+    }
+
+    ///
+    /// Sets the [token revocation](https://tools.ietf.org/html/rfc7009) endpoint.
+    ///
+    /// Clients initialized via [`Client::from_provider_metadata`] use the provider's
+    /// `revocation_endpoint` by default.
+    ///
+    pub fn set_revocation_url(mut self, revocation_url: Option<RevocationUrl>) -> Self {
+        self.revocation_url = revocation_url;
+        self
+    }
+
+    ///
+    /// Returns the [token revocation](https://tools.ietf.org/html/rfc7009) endpoint, if any.
+    ///
+    pub fn revocation_url(&self) -> Option<&RevocationUrl> {
+        self.revocation_url.as_ref()
+    }
+
+    ///
+    /// Adds a hook invoked on each outgoing HTTP request issued on behalf of this client (e.g., to
+    /// inject tracing headers or sign the request).
+    ///
+    /// Interceptors are applied automatically to user info requests, extension grant token
+    /// requests, and token requests sent via [`Client::request_code_exchange`] or
+    /// [`Client::request_refresh_token`]. Other requests apply them when sent using the client
+    /// returned by [`Client::http_client`].
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn add_interceptor<T>(mut self, interceptor: T) -> Self
+    where
+        T: Fn(&mut http_types::Request) + Send + Sync + 'static,
+    {
+        self.interceptors = self.interceptors.add_request_interceptor(interceptor);
+        self
+    }
+
+    ///
+    /// Adds a hook invoked on each incoming HTTP response received on behalf of this client (e.g.,
+    /// to log wire traffic).
+    ///
+    /// See [`Client::add_interceptor`] for the requests to which interceptors apply.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn add_response_interceptor<T>(mut self, interceptor: T) -> Self
+    where
+        T: Fn(&mut http_types::Response) + Send + Sync + 'static,
+    {
+        self.interceptors = self.interceptors.add_response_interceptor(interceptor);
         self
     }
 
+    ///
+    /// Returns the interceptors associated with this client. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn interceptors(&self) -> &Interceptors {
+        &self.interceptors
+    }
+
+    ///
+    /// Wraps the specified HTTP client so that requests sent through it pass through this client's
+    /// interceptors.
+    ///
+    /// Token requests may be sent through the wrapped client by passing a closure that forwards to
+    /// [`AsyncHttpClient::call`]:
+    ///
+    /// ```ignore
+    /// let http_client = client.http_client(&async_http_client);
+    /// client
+    ///     .exchange_code(code)
+    ///     .request(|request| http_client.call(request))
+    /// ```
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn http_client<'c, C>(&self, http_client: &'c C) -> InterceptedHttpClient<'c, C>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        self.interceptors.wrap(http_client)
+    }
+
     ///
     /// Returns an ID token verifier for use with the [`IdToken::claims`] method.
     ///
@@ -898,8 +2001,7 @@ where
     /// ](https://openid.net/specs/openid-connect-core-1_0.html#JWTRequests)
     /// instead of URL query parameters is not currently supported. The
     /// [`claims` parameter](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter)
-    /// is also not directly supported, although the [`AuthorizationRequest::add_extra_param`]
-    /// method can be used to add custom parameters, including `claims`.
+    /// is supported via [`AuthorizationRequest::set_claims`].
     ///
     /// # Arguments
     ///
@@ -936,22 +2038,69 @@ where
             inner: self.oauth2_client.authorize_url(state_fn),
             acr_values: Vec::new(),
             authentication_flow,
+            authorization_details: Vec::new(),
+            claims: None,
             claims_locales: Vec::new(),
             display: None,
             id_token_hint: None,
             login_hint: None,
             max_age: None,
             nonce: nonce_fn(),
+            pkce_challenge_method: None,
+            pkce_requirement: if self.require_pkce {
+                Some(self.code_challenge_methods_supported.clone())
+            } else {
+                None
+            },
+            oauth21_redirect_url: if self.oauth21_strict {
+                Some(self.redirect_url.clone())
+            } else {
+                None
+            },
             prompts: Vec::new(),
+            purpose: None,
+            preflight: self.authorization_preflight.clone(),
+            redirect_url: None,
+            require_preflight: self.require_authorization_preflight,
+            scopes: ScopeSet::new(),
             ui_locales: Vec::new(),
+            vectors_of_trust: Vec::new(),
         };
         if self.use_openid_scope {
-            request.add_scope(Scope::new(OPENID_SCOPE.to_string()))
+            request.add_scope(Scope::new(Scope::OPENID.to_string()))
         } else {
             request
         }
     }
 
+    ///
+    /// Parses the URL to which the Authorization Endpoint redirected the user agent, applying this
+    /// client's configuration.
+    ///
+    /// The `state` parameter must match `expected_state`, and the `iss` parameter (if any) must
+    /// match the client's issuer (see [`AuthorizationResponse::from_redirect_url_with_issuer`] and
+    /// [`Client::set_require_issuer_parameter`]). If the client enforces OAuth 2.1 (see
+    /// [`Client::oauth21_strict`]), responses returned in the URL fragment are rejected.
+    ///
+    pub fn authorization_response<T>(
+        &self,
+        redirect_url: &Url,
+        expected_state: &CsrfToken,
+    ) -> Result<AuthorizationResponse, AuthorizationResponseError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        if self.oauth21_strict && redirect_url.fragment().map_or(false, |f| !f.is_empty()) {
+            return Err(AuthorizationResponseError::FragmentNotAllowed);
+        }
+        AuthorizationResponse::from_redirect_url_with_issuer(
+            redirect_url,
+            expected_state,
+            &self.issuer,
+            self.require_issuer_parameter,
+        )
+    }
+
     ///
     /// Creates a request builder for exchanging an authorization code for an access token.
     ///
@@ -979,6 +2128,112 @@ where
         self.oauth2_client.exchange_refresh_token(refresh_token)
     }
 
+    ///
+    /// Submits an authorization code exchange request (see [`Client::exchange_code`]) using the
+    /// specified asynchronous HTTP client.
+    ///
+    /// Unlike calling [`CodeTokenRequest::request`] directly, the request passes through this
+    /// client's interceptors (see [`Client::add_interceptor`]), is reported as a
+    /// [`RequestKind::Token`] request, and includes a client assertion if the client uses a
+    /// JWT-based token endpoint authentication method (see [`Client::client_assertion_params`]).
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn request_code_exchange<C>(
+        &self,
+        mut token_request: CodeTokenRequest<'_, TE, TR, TT>,
+        http_client: &C,
+    ) -> Result<TR, TokenRequestError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        for (name, value) in self
+            .client_assertion_params()
+            .map_err(TokenRequestError::client_assertion)?
+        {
+            token_request = token_request.add_extra_param(name, value);
+        }
+        let http_client = self.http_client(http_client);
+        let http_client = InstrumentedHttpClient::new(RequestKind::Token, &http_client);
+        let status = Mutex::new(None);
+        token_request
+            .request(|request| record_status(http_client.call(request), &status))
+            .await
+            .map_err(|err| TokenRequestError::new(err, *status.lock().unwrap()))
+    }
+
+    ///
+    /// Submits an authorization code exchange request (see [`Client::exchange_code`]) using the
+    /// specified synchronous HTTP client.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn request_code_exchange_blocking<C>(
+        &self,
+        token_request: CodeTokenRequest<'_, TE, TR, TT>,
+        http_client: &C,
+    ) -> Result<TR, TokenRequestError<C::Error, TE>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(self.request_code_exchange(token_request, &BlockingHttpClient(http_client)))
+    }
+
+    ///
+    /// Submits a refresh token request (see [`Client::exchange_refresh_token`]) using the
+    /// specified asynchronous HTTP client.
+    ///
+    /// Unlike calling [`RefreshTokenRequest::request`] directly, the request passes through this
+    /// client's interceptors (see [`Client::add_interceptor`]), is reported as a
+    /// [`RequestKind::Token`] request, and includes a client assertion if the client uses a
+    /// JWT-based token endpoint authentication method (see [`Client::client_assertion_params`]).
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn request_refresh_token<C>(
+        &self,
+        mut token_request: RefreshTokenRequest<'_, TE, TR, TT>,
+        http_client: &C,
+    ) -> Result<TR, TokenRequestError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        for (name, value) in self
+            .client_assertion_params()
+            .map_err(TokenRequestError::client_assertion)?
+        {
+            token_request = token_request.add_extra_param(name, value);
+        }
+        let http_client = self.http_client(http_client);
+        let http_client = InstrumentedHttpClient::new(RequestKind::Token, &http_client);
+        let status = Mutex::new(None);
+        token_request
+            .request(|request| record_status(http_client.call(request), &status))
+            .await
+            .map_err(|err| TokenRequestError::new(err, *status.lock().unwrap()))
+    }
+
+    ///
+    /// Submits a refresh token request (see [`Client::exchange_refresh_token`]) using the
+    /// specified synchronous HTTP client.
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn request_refresh_token_blocking<C>(
+        &self,
+        token_request: RefreshTokenRequest<'_, TE, TR, TT>,
+        http_client: &C,
+    ) -> Result<TR, TokenRequestError<C::Error, TE>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(self.request_refresh_token(token_request, &BlockingHttpClient(http_client)))
+    }
+
     ///
     /// Creates a request builder for exchanging credentials for an access token.
     ///
@@ -995,6 +2250,41 @@ where
         self.oauth2_client.exchange_password(username, password)
     }
 
+    ///
+    /// Creates a request builder for exchanging an extension grant for an access token.
+    ///
+    /// Extension grants are identified by an absolute URI `grant_type` (e.g., vendor-specific
+    /// grants). See [`ExtensionGrantTokenRequest`] for details.
+    ///
+    /// See https://tools.ietf.org/html/rfc6749#section-4.5
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn exchange_extension_grant<'a, G>(
+        &'a self,
+        grant_type: G,
+    ) -> ExtensionGrantTokenRequest<'a, TE, TR>
+    where
+        G: Into<Cow<'a, str>>,
+    {
+        ExtensionGrantTokenRequest {
+            grant_type: grant_type.into(),
+            token_url: self.token_url.as_ref(),
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            auth_method: self.token_endpoint_auth_method,
+            client_assertion_params: self
+                .client_assertion_params()
+                .map_err(|err| format!("failed to sign client assertion: {}", err)),
+            interceptors: self.interceptors.clone(),
+            request_options: RequestOptions::default(),
+            scopes: Vec::new(),
+            extra_params: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
     ///
     /// Creates a request builder for info about the user associated with the given access token.
     ///
@@ -1009,6 +2299,9 @@ where
     /// [`UserInfoRequest::request`] function will return [`UserInfoError::ClaimsVerification`].
     /// If set to `None`, any subject is accepted.
     ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
     pub fn user_info(
         &self,
         access_token: AccessToken,
@@ -1021,6 +2314,9 @@ where
                 .ok_or(NoUserInfoEndpoint)?
                 .to_owned(),
             access_token,
+            accept: None,
+            interceptors: self.interceptors.clone(),
+            request_options: RequestOptions::default(),
             require_signed_response: false,
             signed_response_verifier: UserInfoVerifier::new(
                 self.client_id.clone(),
@@ -1028,6 +2324,8 @@ where
                 self.jwks.clone(),
                 expected_subject,
             ),
+            token_in_body: false,
+            use_post: false,
         })
     }
 }
@@ -1044,14 +2342,31 @@ where
     inner: oauth2::AuthorizationRequest<'a>,
     acr_values: Vec<AuthenticationContextClass>,
     authentication_flow: AuthenticationFlow<RT>,
+    authorization_details: Vec<AuthorizationDetail>,
+    claims: Option<String>,
     claims_locales: Vec<LanguageTag>,
     display: Option<AD>,
     id_token_hint: Option<String>,
     login_hint: Option<LoginHint>,
     max_age: Option<Duration>,
     nonce: Nonce,
+    pkce_challenge_method: Option<PkceCodeChallengeMethod>,
+    // Set if PKCE is required, holding the code challenge methods supported by the provider (if
+    // known).
+    pkce_requirement: Option<Option<Vec<PkceCodeChallengeMethod>>>,
+    // Set if the client enforces OAuth 2.1, holding the client's redirect URL (if any).
+    oauth21_redirect_url: Option<Option<RedirectUrl>>,
     prompts: Vec<P>,
+    purpose: Option<String>,
+    // Capabilities advertised by the provider metadata (if known).
+    preflight: Option<Arc<AuthorizationPreflight>>,
+    // Per-request redirect URL override, retained so that it can be compared to the client's.
+    redirect_url: Option<String>,
+    require_preflight: bool,
+    // Scopes requested so far, retained so that the presence of the `openid` scope can be checked.
+    scopes: ScopeSet,
     ui_locales: Vec<LanguageTag>,
+    vectors_of_trust: Vec<VectorOfTrust>,
 }
 impl<'a, AD, P, RT> AuthorizationRequest<'a, AD, P, RT>
 where
@@ -1062,11 +2377,22 @@ where
     ///
     /// Appends a new scope to the authorization URL.
     ///
+    /// Scopes that have already been added are ignored.
+    ///
     pub fn add_scope(mut self, scope: Scope) -> Self {
-        self.inner = self.inner.add_scope(scope);
+        if self.scopes.insert(scope.clone()) {
+            self.inner = self.inner.add_scope(scope);
+        }
         self
     }
 
+    ///
+    /// Appends each scope in the specified set to the authorization URL.
+    ///
+    pub fn add_scopes(self, scopes: ScopeSet) -> Self {
+        scopes.into_iter().fold(self, Self::add_scope)
+    }
+
     ///
     /// Appends an extra param to the authorization URL.
     ///
@@ -1100,6 +2426,7 @@ where
     /// such as in a native, mobile app, or browser app).
     ///
     pub fn set_pkce_challenge(mut self, pkce_code_challenge: PkceCodeChallenge) -> Self {
+        self.pkce_challenge_method = Some(pkce_code_challenge.method().clone());
         self.inner = self.inner.set_pkce_challenge(pkce_code_challenge);
         self
     }
@@ -1116,6 +2443,24 @@ where
         self
     }
 
+    ///
+    /// Requests an Authentication Context Class Reference value via the `acr_values` parameter.
+    ///
+    /// This is equivalent to [`AuthorizationRequest::add_auth_context_value`].
+    ///
+    pub fn add_acr_value(self, acr_value: AuthenticationContextClass) -> Self {
+        self.add_auth_context_value(acr_value)
+    }
+
+    ///
+    /// Requests fine-grained permissions using an authorization detail object, as defined by
+    /// [Rich Authorization Requests](https://tools.ietf.org/html/rfc9396).
+    ///
+    pub fn add_authorization_detail(mut self, authorization_detail: AuthorizationDetail) -> Self {
+        self.authorization_details.push(authorization_detail);
+        self
+    }
+
     ///
     /// Requests the preferred languages for claims returned by the OpenID Connect Provider.
     ///
@@ -1126,8 +2471,26 @@ where
         self
     }
 
-    // TODO: support 'claims' parameter
-    // https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter
+    ///
+    /// Requests specific claims to be returned in the ID token and/or from the user info endpoint
+    /// using the
+    /// [`claims` parameter](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the claim names do not serialize as JSON strings.
+    ///
+    pub fn set_claims<CN>(mut self, claims: &ClaimsRequest<CN>) -> Self
+    where
+        CN: ClaimName,
+    {
+        self.claims = if claims.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(claims).expect("failed to serialize claims request"))
+        };
+        self
+    }
 
     ///
     /// Specifies how the OpenID Connect Provider displays the authentication and consent user
@@ -1191,6 +2554,19 @@ where
         self
     }
 
+    ///
+    /// Describes the purpose for which the client is requesting the end user's claims, as defined
+    /// by [OpenID Connect for Identity Assurance](
+    /// https://openid.net/specs/openid-connect-4-identity-assurance-1_0.html).
+    ///
+    /// The OpenID Connect Provider may display the purpose to the end user when obtaining consent.
+    /// The specification requires the purpose to be between 3 and 300 characters long.
+    ///
+    pub fn set_purpose(mut self, purpose: String) -> Self {
+        self.purpose = Some(purpose);
+        self
+    }
+
     ///
     /// Requests the preferred languages for the user interface presented by the OpenID Connect
     /// Provider.
@@ -1202,20 +2578,53 @@ where
         self
     }
 
+    ///
+    /// Requests a [`VectorOfTrust`] via the `vtr` parameter defined by
+    /// [RFC 8485](https://tools.ietf.org/html/rfc8485#section-6.1).
+    ///
+    /// Vectors should be added in order of preference. The vector actually satisfied by the
+    /// authentication is returned in the ID token's `vot` claim (see
+    /// [`IdTokenClaims::vector_of_trust`]).
+    ///
+    pub fn add_vector_of_trust(mut self, vector_of_trust: VectorOfTrust) -> Self {
+        self.vectors_of_trust.push(vector_of_trust);
+        self
+    }
+
     ///
     /// Overrides the `redirect_url` to the one specified.
     ///
     pub fn set_redirect_url(mut self, redirect_url: Cow<'a, RedirectUrl>) -> Self {
+        self.redirect_url = Some(redirect_url.as_str().to_string());
         self.inner = self.inner.set_redirect_url(redirect_url);
         self
     }
 
     ///
-    /// Returns the full authorization URL and CSRF state for this authorization
-    /// request.
+    /// Checks the requested `response_type`, scopes, and `prompt` values against the
+    /// `response_types_supported`, `scopes_supported`, and `prompt_values_supported` provider
+    /// metadata fields before the user is redirected to the provider.
     ///
-    pub fn url(self) -> (Url, CsrfToken, Nonce) {
-        let response_type = match self.authentication_flow {
+    /// Unsupported response types and prompt values, as well as combining the `none` prompt with
+    /// other values, are reported as errors. Scopes the provider doesn't advertise are reported as
+    /// warnings, since providers needn't advertise every supported scope. Metadata fields the
+    /// provider omits aren't checked, and the report is empty unless the client was initialized
+    /// from provider or authorization server metadata.
+    ///
+    pub fn preflight(&self) -> AuthorizationPreflightReport {
+        let preflight = match self.preflight {
+            Some(ref preflight) => preflight,
+            None => return AuthorizationPreflightReport::default(),
+        };
+        preflight.check(
+            self.response_type().as_str(),
+            &self.scopes,
+            &self.prompts.iter().map(AsRef::as_ref).collect::<Vec<_>>(),
+        )
+    }
+
+    fn response_type(&self) -> OAuth2ResponseType {
+        match self.authentication_flow {
             AuthenticationFlow::AuthorizationCode => core::CoreResponseType::Code.to_oauth2(),
             AuthenticationFlow::Implicit(include_token) => {
                 if include_token {
@@ -1240,7 +2649,95 @@ where
                     .collect::<Vec<_>>()
                     .join(" "),
             ),
-        };
+        }
+    }
+
+    ///
+    /// Returns the full authorization URL and CSRF state for this authorization
+    /// request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    ///  * the client requires PKCE (see [`Client::set_require_pkce`]) and the request does not
+    ///    use an `S256` code challenge, or the provider doesn't support the `S256` method,
+    ///  * the client enforces OAuth 2.1 (see [`Client::oauth21_strict`]) and the request uses the
+    ///    Implicit or Hybrid Flow, or a redirect URL other than the client's, or
+    ///  * the client requires preflight validation (see
+    ///    [`Client::set_require_authorization_preflight`]) and the
+    ///    [`AuthorizationRequest::preflight`] report contains any errors.
+    ///
+    /// Use [`AuthorizationRequest::try_url`] to handle these cases without panicking.
+    ///
+    /// Unlike [`AuthorizationRequest::try_url`], this function does not require the `openid`
+    /// scope, which may be omitted after calling [`Client::disable_openid_scope`].
+    ///
+    pub fn url(self) -> (Url, CsrfToken, Nonce) {
+        match self.build_url() {
+            Ok(url) => url,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    ///
+    /// Returns the full authorization URL and CSRF state for this authorization
+    /// request, or an error if the client requires PKCE (see [`Client::set_require_pkce`]) and the
+    /// request does not use an `S256` code challenge, if the request violates the OAuth 2.1
+    /// profile enforced by [`Client::oauth21_strict`], or if the request does not include the
+    /// `openid` scope. If the client requires preflight validation (see
+    /// [`Client::set_require_authorization_preflight`]), an error is also returned if the
+    /// [`AuthorizationRequest::preflight`] report contains any errors.
+    ///
+    pub fn try_url(self) -> Result<(Url, CsrfToken, Nonce), AuthorizationRequestError> {
+        if !self.scopes.contains(Scope::OPENID) {
+            return Err(AuthorizationRequestError::MissingOpenIdScope);
+        }
+        self.build_url()
+    }
+
+    fn build_url(self) -> Result<(Url, CsrfToken, Nonce), AuthorizationRequestError> {
+        if let Some(ref client_redirect_url) = self.oauth21_redirect_url {
+            match self.authentication_flow {
+                AuthenticationFlow::AuthorizationCode => {}
+                _ => return Err(AuthorizationRequestError::UnsupportedFlow),
+            }
+            if let Some(ref redirect_url) = self.redirect_url {
+                match client_redirect_url {
+                    Some(client_redirect_url) if client_redirect_url.as_str() == redirect_url => {}
+                    _ => {
+                        return Err(AuthorizationRequestError::RedirectUrlMismatch(
+                            redirect_url.clone(),
+                        ))
+                    }
+                }
+            }
+        }
+        if let Some(ref methods_supported) = self.pkce_requirement {
+            let supports_s256 = methods_supported.as_ref().map_or(true, |methods| {
+                methods.iter().any(|method| method.as_str() == PKCE_METHOD_S256)
+            });
+            if !supports_s256 {
+                return Err(AuthorizationRequestError::PkceUnsupportedByProvider);
+            }
+            match self.pkce_challenge_method {
+                None => return Err(AuthorizationRequestError::PkceRequired),
+                Some(ref method) if method.as_str() != PKCE_METHOD_S256 => {
+                    return Err(AuthorizationRequestError::UnsupportedPkceMethod(
+                        method.as_str().to_string(),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.require_preflight {
+            let report = self.preflight();
+            if !report.is_ok() {
+                return Err(AuthorizationRequestError::Preflight(report));
+            }
+        }
+
+        let response_type = self.response_type();
         let (mut inner, nonce) = (
             self.inner
                 .set_response_type(&response_type)
@@ -1250,6 +2747,15 @@ where
         if !self.acr_values.is_empty() {
             inner = inner.add_extra_param("acr_values", join_vec(&self.acr_values));
         }
+        if !self.authorization_details.is_empty() {
+            inner = inner.add_extra_param(
+                "authorization_details",
+                AuthorizationDetail::to_request_param(&self.authorization_details),
+            );
+        }
+        if let Some(ref claims) = self.claims {
+            inner = inner.add_extra_param("claims", claims);
+        }
         if !self.claims_locales.is_empty() {
             inner = inner.add_extra_param("claims_locales", join_vec(&self.claims_locales));
         }
@@ -1268,25 +2774,172 @@ where
         if !self.prompts.is_empty() {
             inner = inner.add_extra_param("prompt", join_vec(&self.prompts));
         }
+        if let Some(ref purpose) = self.purpose {
+            inner = inner.add_extra_param("purpose", purpose);
+        }
         if !self.ui_locales.is_empty() {
             inner = inner.add_extra_param("ui_locales", join_vec(&self.ui_locales));
         }
+        if !self.vectors_of_trust.is_empty() {
+            inner = inner.add_extra_param(
+                "vtr",
+                serde_json::to_string(&self.vectors_of_trust)
+                    .expect("failed to serialize vectors of trust"),
+            );
+        }
 
         let (url, state) = inner.url();
-        (url, state, nonce)
+        Ok((url, state, nonce))
     }
 }
 
 ///
-/// Extends the base OAuth2 token response with an ID token.
+/// Error building an authorization URL via [`AuthorizationRequest::try_url`].
 ///
-pub trait TokenResponse<AC, GC, JE, JS, JT, TT>: OAuth2TokenResponse<TT>
-where
-    AC: AdditionalClaims,
-    GC: GenderClaim,
-    JE: JweContentEncryptionAlgorithm<JT>,
-    JS: JwsSigningAlgorithm<JT>,
-    JT: JsonWebKeyType,
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum AuthorizationRequestError {
+    ///
+    /// The client requires PKCE, but no code challenge was set via
+    /// [`AuthorizationRequest::set_pkce_challenge`].
+    ///
+    #[fail(display = "PKCE is required but no code challenge was set")]
+    PkceRequired,
+    ///
+    /// The client requires PKCE, but the provider metadata does not list the `S256` code challenge
+    /// method in `code_challenge_methods_supported`.
+    ///
+    #[fail(display = "Provider does not support the S256 PKCE code challenge method")]
+    PkceUnsupportedByProvider,
+    ///
+    /// The client requires PKCE with the `S256` code challenge method, but a different method was
+    /// used.
+    ///
+    #[fail(display = "Unsupported PKCE code challenge method: {}", _0)]
+    UnsupportedPkceMethod(String),
+    ///
+    /// The client enforces OAuth 2.1 (see [`Client::oauth21_strict`]), but the request uses the
+    /// Implicit or Hybrid Flow.
+    ///
+    #[fail(display = "Only the Authorization Code Flow is allowed by OAuth 2.1")]
+    UnsupportedFlow,
+    ///
+    /// The client enforces OAuth 2.1 (see [`Client::oauth21_strict`]), but the redirect URL set
+    /// via [`AuthorizationRequest::set_redirect_url`] doesn't exactly match the client's redirect
+    /// URL.
+    ///
+    #[fail(display = "Redirect URL does not match the client's redirect URL: {}", _0)]
+    RedirectUrlMismatch(String),
+    ///
+    /// The request does not include the `openid` scope, which is required by all OpenID Connect
+    /// authentication requests. This happens if the scope was disabled via
+    /// [`Client::disable_openid_scope`] and not added explicitly.
+    ///
+    #[fail(display = "The openid scope is required but was not requested")]
+    MissingOpenIdScope,
+    ///
+    /// The client requires preflight validation (see
+    /// [`Client::set_require_authorization_preflight`]), and the request is expected to be
+    /// rejected by the provider.
+    ///
+    #[fail(display = "Authorization request failed preflight validation: {}", _0)]
+    Preflight(AuthorizationPreflightReport),
+}
+
+///
+/// Error returned by token requests sent via [`Client::request_code_exchange`],
+/// [`Client::request_refresh_token`], or [`ExtensionGrantTokenRequest::request`].
+///
+/// The [`RequestTokenError`] returned by the `oauth2` crate doesn't include the HTTP status code
+/// of the token endpoint's response, so this error records it alongside. The status code
+/// determines the error's [`ErrorKind`] (see [`ClassifiedError`]) in the same way as for
+/// discovery, user info, and registration errors.
+///
+/// Requires "net" feature.
+///
+#[cfg(feature = "net")]
+#[derive(Debug, Fail)]
+#[fail(display = "{}", error)]
+pub struct TokenRequestError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    #[cause]
+    error: RequestTokenError<RE, TE>,
+    status: Option<http_types::StatusCode>,
+}
+#[cfg(feature = "net")]
+impl<RE, TE> TokenRequestError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    pub(crate) fn new(
+        error: RequestTokenError<RE, TE>,
+        status: Option<http_types::StatusCode>,
+    ) -> Self {
+        Self { error, status }
+    }
+
+    pub(crate) fn client_assertion(err: JsonWebTokenError) -> Self {
+        Self::new(
+            RequestTokenError::Other(format!("failed to sign client assertion: {}", err)),
+            None,
+        )
+    }
+
+    ///
+    /// Returns the error returned by the `oauth2` crate.
+    ///
+    pub fn error(&self) -> &RequestTokenError<RE, TE> {
+        &self.error
+    }
+
+    ///
+    /// Returns the error returned by the `oauth2` crate, consuming this error.
+    ///
+    pub fn into_error(self) -> RequestTokenError<RE, TE> {
+        self.error
+    }
+
+    ///
+    /// Returns the HTTP status code of the token endpoint's response, or `None` if no response
+    /// was received (e.g., because of a connectivity failure or because the request couldn't be
+    /// prepared).
+    ///
+    pub fn status(&self) -> Option<http_types::StatusCode> {
+        self.status
+    }
+}
+
+// Records the HTTP status code of a token endpoint response, which `RequestTokenError` doesn't
+// expose.
+#[cfg(feature = "net")]
+async fn record_status<F, RE>(
+    response: F,
+    status: &Mutex<Option<http_types::StatusCode>>,
+) -> Result<http_types::Response, RE>
+where
+    F: Future<Output = Result<http_types::Response, RE>>,
+{
+    let result = response.await;
+    if let Ok(ref response) = result {
+        *status.lock().unwrap() = Some(response.status());
+    }
+    result
+}
+
+///
+/// Extends the base OAuth2 token response with an ID token.
+///
+pub trait TokenResponse<AC, GC, JE, JS, JT, TT>: OAuth2TokenResponse<TT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
     TT: TokenType,
 {
     ///
@@ -1328,18 +2981,86 @@ where
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
+    #[cfg(feature = "net")]
+    use std::collections::HashMap;
+    #[cfg(feature = "net")]
+    use std::io;
+    use std::marker::PhantomData;
+    #[cfg(feature = "net")]
+    use std::sync::Mutex;
     use std::time::Duration;
 
-    use oauth2::{AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenUrl};
+    #[cfg(feature = "net")]
+    use futures::executor::block_on;
+    #[cfg(feature = "net")]
+    use futures::future::ready;
+    #[cfg(feature = "net")]
+    use oauth2::{
+        AccessToken, AuthorizationCode, RefreshToken, TokenResponse as OAuth2TokenResponse,
+    };
+    use oauth2::{
+        AuthType, AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
+        PkceCodeChallengeMethod, RedirectUrl, Scope, TokenUrl,
+    };
+    #[cfg(feature = "net")]
+    use url::form_urlencoded;
+    use url::Url;
 
     use crate::core::CoreAuthenticationFlow;
-    use crate::core::{CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreIdToken, CoreResponseType};
+    #[cfg(feature = "net")]
+    use crate::core::CoreGenderClaim;
+    use crate::core::{
+        CoreAuthDisplay, CoreAuthErrorResponseType, CoreAuthPrompt, CoreClaimName, CoreClaimType,
+        CoreClaimsRequest, CoreClient, CoreClientAuthMethod, CoreGrantType, CoreHmacKey,
+        CoreIdToken, CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse,
+        CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
+        CoreProviderMetadata, CoreResponseMode, CoreResponseType, CoreSubjectIdentifierType,
+    };
+    #[cfg(feature = "net")]
+    use crate::http_types;
     use crate::IssuerUrl;
     use crate::{
-        AuthenticationContextClass, AuthenticationFlow, JsonWebKeySet, LanguageTag, LoginHint,
-        Nonce,
+        AdditionalProviderMetadata, AuthenticationContextClass, AuthenticationFlow,
+        AuthorizationDetail, AuthorizationPreflightSeverity, AuthorizationRequest,
+        AuthorizationRequestError, AuthorizationResponseError, DeviceAuthorizationUrl,
+        EmptyAdditionalProviderMetadata, IndividualClaimRequest, IntrospectionUrl, JsonWebKeySet,
+        JsonWebKeySetUrl, LanguageTag, LoginHint, Nonce, ProviderMetadata, ResponseTypes,
+        RevocationUrl, ScopeSet, StandardScopes, TokenEndpointAuthMethod,
+        TokenEndpointAuthMethodError, VectorOfTrust,
+    };
+    #[cfg(feature = "net")]
+    use crate::{
+        AsyncHttpClient, BearerTokenErrorType, ClassifiedError, EmptyAdditionalClaims, ErrorKind,
+        RequestKind, RequestOptions, TokenRequestAuthorizationDetails, UserInfoError, UserInfoUrl,
     };
 
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    struct SessionProviderMetadata {
+        check_session_iframe: String,
+        // Makes the metadata neither `Send` nor `Sync`.
+        #[serde(skip)]
+        _phantom: PhantomData<*const ()>,
+    }
+    impl AdditionalProviderMetadata for SessionProviderMetadata {}
+
+    type SessionCoreProviderMetadata = ProviderMetadata<
+        SessionProviderMetadata,
+        CoreAuthDisplay,
+        CoreClientAuthMethod,
+        CoreClaimName,
+        CoreClaimType,
+        CoreGrantType,
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreJsonWebKeyUse,
+        CoreJsonWebKey,
+        CoreResponseMode,
+        CoreResponseType,
+        CoreSubjectIdentifierType,
+    >;
+
     fn new_client() -> CoreClient {
         color_backtrace::install();
         CoreClient::new(
@@ -1372,6 +3093,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_authorize_url_openid_scope() {
+        let client = new_client().disable_openid_scope();
+        let new_request = || {
+            client.authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+
+        match new_request()
+            .add_scope(Scope::new(Scope::EMAIL.to_string()))
+            .try_url()
+        {
+            Err(AuthorizationRequestError::MissingOpenIdScope) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The infallible url() preserves the behavior of disable_openid_scope().
+        let (authorize_url, _, _) = new_request()
+            .add_scope(Scope::new(Scope::EMAIL.to_string()))
+            .url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=email&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+
+        let (authorize_url, _, _) = new_request()
+            .add_scopes(ScopeSet::from_space_delimited("openid email openid"))
+            .add_scope(Scope::new(Scope::EMAIL.to_string()))
+            .try_url()
+            .unwrap();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid+email&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+    }
+
     #[test]
     fn test_authorize_url_full() {
         let client = new_client()
@@ -1451,6 +3213,759 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_authorize_url_authorization_details() {
+        let (authorize_url, _, _) = new_client()
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_authorization_detail(
+                AuthorizationDetail::new("account_information".to_string())
+                    .set_actions(Some(vec!["read".to_string()])),
+            )
+            .url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&authorization_details=%5B%7B%22type%22%3A%22\
+             account_information%22%2C%22actions%22%3A%5B%22read%22%5D%7D%5D",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_require_pkce() {
+        fn try_url(
+            client: &CoreClient,
+            pkce_challenge: Option<PkceCodeChallenge>,
+        ) -> Result<(Url, CsrfToken, Nonce), AuthorizationRequestError> {
+            let request = client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            );
+            match pkce_challenge {
+                Some(pkce_challenge) => request.set_pkce_challenge(pkce_challenge),
+                None => request,
+            }
+            .try_url()
+        }
+        let s256_challenge = || PkceCodeChallenge::new_random_sha256().0;
+
+        // PKCE is not required by default.
+        assert!(try_url(&new_client(), None).is_ok());
+
+        let client = new_client().set_require_pkce(true);
+        match try_url(&client, None) {
+            Err(AuthorizationRequestError::PkceRequired) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let (authorize_url, _, _) = try_url(&client, Some(s256_challenge())).unwrap();
+        assert!(authorize_url
+            .query_pairs()
+            .any(|(name, value)| name == "code_challenge_method" && value == "S256"));
+
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        );
+        let client_from_metadata = |code_challenge_methods_supported: Option<Vec<&str>>| {
+            CoreClient::from_provider_metadata(
+                provider_metadata
+                    .clone()
+                    .set_code_challenge_methods_supported(code_challenge_methods_supported.map(
+                        |methods| {
+                            methods
+                                .into_iter()
+                                .map(|method| PkceCodeChallengeMethod::new(method.to_string()))
+                                .collect()
+                        },
+                    )),
+                ClientId::new("aaa".to_string()),
+                None,
+            )
+        };
+
+        // PKCE is only required automatically if the provider advertises S256.
+        let client = client_from_metadata(None).require_pkce_if_supported();
+        assert!(try_url(&client, None).is_ok());
+        let client = client_from_metadata(Some(vec!["plain"])).require_pkce_if_supported();
+        assert!(try_url(&client, None).is_ok());
+        let client = client_from_metadata(Some(vec!["plain", "S256"])).require_pkce_if_supported();
+        match try_url(&client, None) {
+            Err(AuthorizationRequestError::PkceRequired) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(try_url(&client, Some(s256_challenge())).is_ok());
+
+        let client = client_from_metadata(Some(vec!["plain"])).set_require_pkce(true);
+        match try_url(&client, Some(s256_challenge())) {
+            Err(AuthorizationRequestError::PkceUnsupportedByProvider) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_provider_metadata() {
+        let provider_metadata: Option<Result<CoreProviderMetadata, _>> =
+            new_client().provider_metadata();
+        assert!(provider_metadata.is_none());
+
+        let jwks = JsonWebKeySet::new(vec![CoreJsonWebKey::new_symmetric(vec![1, 2, 3])]);
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_scopes_supported(Some(vec![Scope::new("openid".to_string())]))
+        .set_jwks(jwks);
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata.clone(),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        let retained_metadata: CoreProviderMetadata = client.provider_metadata().unwrap().unwrap();
+        assert_eq!(retained_metadata, provider_metadata);
+        assert_eq!(
+            retained_metadata.scopes_supported(),
+            Some(&vec![Scope::new("openid".to_string())])
+        );
+
+        // Requesting a metadata type that can't represent the metadata reports why.
+        let session_metadata: Option<Result<SessionCoreProviderMetadata, _>> =
+            client.provider_metadata();
+        match session_metadata {
+            Some(Err(err)) => assert!(
+                err.to_string()
+                    .contains("missing field `check_session_iframe`"),
+                "{}",
+                err
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Metadata that is neither `Send` nor `Sync` is retained too.
+        let session_metadata = SessionCoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            SessionProviderMetadata {
+                check_session_iframe: "https://example/session".to_string(),
+                _phantom: PhantomData,
+            },
+        );
+        let client = CoreClient::from_provider_metadata(
+            session_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        let retained_metadata: SessionCoreProviderMetadata =
+            client.provider_metadata().unwrap().unwrap();
+        assert_eq!(
+            "https://example/session",
+            retained_metadata.additional_metadata().check_session_iframe
+        );
+    }
+
+    #[test]
+    fn test_authorization_preflight() {
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![
+                ResponseTypes::new(vec![CoreResponseType::Code]),
+                ResponseTypes::new(vec![CoreResponseType::Token, CoreResponseType::IdToken]),
+            ],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_scopes_supported(Some(vec![
+            Scope::new("openid".to_string()),
+            Scope::new("email".to_string()),
+        ]))
+        .set_prompt_values_supported(Some(vec!["none".to_string(), "login".to_string()]));
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        fn new_request(
+            client: &CoreClient,
+            flow: CoreAuthenticationFlow,
+        ) -> AuthorizationRequest<CoreAuthDisplay, CoreAuthPrompt, CoreResponseType> {
+            client.authorize_url(
+                flow,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        }
+
+        // Preflight validation is opt-in.
+        assert!(
+            new_request(&client, CoreAuthenticationFlow::Implicit(false))
+                .try_url()
+                .is_ok()
+        );
+        assert!(
+            new_request(&new_client(), CoreAuthenticationFlow::AuthorizationCode)
+                .preflight()
+                .issues()
+                .is_empty()
+        );
+
+        let report = new_request(&client, CoreAuthenticationFlow::Implicit(true))
+            .add_scope(Scope::new("email".to_string()))
+            .add_prompt(CoreAuthPrompt::Login)
+            .preflight();
+        assert!(report.issues().is_empty());
+
+        let report = new_request(&client, CoreAuthenticationFlow::AuthorizationCode)
+            .add_prompt(CoreAuthPrompt::Create)
+            .preflight();
+        assert_eq!(
+            report
+                .errors()
+                .map(|issue| (issue.parameter(), issue.value()))
+                .collect::<Vec<_>>(),
+            vec![("prompt", "create")]
+        );
+
+        let report = new_request(&client, CoreAuthenticationFlow::AuthorizationCode)
+            .add_scope(Scope::new("phone".to_string()))
+            .preflight();
+        assert!(report.is_ok());
+        let warnings = report.warnings().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].parameter(), "scope");
+        assert_eq!(warnings[0].value(), "phone");
+
+        let report = new_request(
+            &client,
+            CoreAuthenticationFlow::Hybrid(vec![CoreResponseType::Code, CoreResponseType::IdToken]),
+        )
+        .add_prompt(CoreAuthPrompt::None)
+        .add_prompt(CoreAuthPrompt::Consent)
+        .preflight();
+        assert!(!report.is_ok());
+        assert_eq!(
+            report
+                .errors()
+                .map(|issue| (issue.parameter(), issue.value()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("response_type", "code id_token"),
+                ("prompt", "none"),
+                ("prompt", "consent"),
+            ]
+        );
+        assert_eq!(
+            report.issues()[0].severity(),
+            AuthorizationPreflightSeverity::Error
+        );
+
+        let client = client.set_require_authorization_preflight(true);
+        assert!(
+            new_request(&client, CoreAuthenticationFlow::AuthorizationCode)
+                .add_scope(Scope::new("phone".to_string()))
+                .try_url()
+                .is_ok()
+        );
+        match new_request(&client, CoreAuthenticationFlow::Implicit(false)).try_url() {
+            Err(AuthorizationRequestError::Preflight(report)) => {
+                assert_eq!(report.errors().count(), 1)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_urls_from_provider_metadata() {
+        assert_eq!(new_client().device_authorization_url(), None);
+        assert_eq!(new_client().introspection_url(), None);
+        assert_eq!(new_client().revocation_url(), None);
+        assert!(!new_client().require_issuer_parameter());
+
+        let device_authorization_url =
+            DeviceAuthorizationUrl::new("https://example/device".to_string()).unwrap();
+        let introspection_url =
+            IntrospectionUrl::new("https://example/introspect".to_string()).unwrap();
+        let revocation_url = RevocationUrl::new("https://example/revoke".to_string()).unwrap();
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_device_authorization_endpoint(Some(device_authorization_url.clone()))
+        .set_introspection_endpoint(Some(introspection_url.clone()))
+        .set_revocation_endpoint(Some(revocation_url.clone()))
+        .set_authorization_response_iss_parameter_supported(Some(true));
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert_eq!(
+            client.device_authorization_url(),
+            Some(&device_authorization_url)
+        );
+        assert_eq!(client.introspection_url(), Some(&introspection_url));
+        assert_eq!(client.revocation_url(), Some(&revocation_url));
+        assert!(client.require_issuer_parameter());
+
+        let client = client
+            .set_device_authorization_url(None)
+            .set_introspection_url(None)
+            .set_revocation_url(None);
+        assert_eq!(client.device_authorization_url(), None);
+        assert_eq!(client.introspection_url(), None);
+        assert_eq!(client.revocation_url(), None);
+    }
+
+    #[test]
+    fn test_token_endpoint_auth_method() {
+        let secret_methods = [
+            TokenEndpointAuthMethod::ClientSecretBasic,
+            TokenEndpointAuthMethod::ClientSecretPost,
+        ];
+        let all_methods = [
+            TokenEndpointAuthMethod::PrivateKeyJwt,
+            TokenEndpointAuthMethod::ClientSecretBasic,
+            TokenEndpointAuthMethod::ClientSecretPost,
+        ];
+        let supported = [
+            CoreClientAuthMethod::ClientSecretPost,
+            CoreClientAuthMethod::ClientSecretBasic,
+            CoreClientAuthMethod::PrivateKeyJwt,
+        ];
+        assert_eq!(
+            TokenEndpointAuthMethod::negotiate(Some(&supported), &secret_methods),
+            Ok(TokenEndpointAuthMethod::ClientSecretBasic)
+        );
+        assert_eq!(
+            TokenEndpointAuthMethod::negotiate(Some(&supported), &all_methods),
+            Ok(TokenEndpointAuthMethod::PrivateKeyJwt)
+        );
+        assert_eq!(
+            TokenEndpointAuthMethod::negotiate(
+                Some(&[CoreClientAuthMethod::ClientSecretPost]),
+                &all_methods
+            ),
+            Ok(TokenEndpointAuthMethod::ClientSecretPost)
+        );
+        assert_eq!(
+            TokenEndpointAuthMethod::negotiate(None::<&[CoreClientAuthMethod]>, &secret_methods),
+            Ok(TokenEndpointAuthMethod::ClientSecretBasic)
+        );
+        assert_eq!(
+            TokenEndpointAuthMethod::negotiate(
+                Some(&[CoreClientAuthMethod::ClientSecretPost]),
+                &[]
+            ),
+            Ok(TokenEndpointAuthMethod::None)
+        );
+        assert_eq!(
+            TokenEndpointAuthMethod::negotiate(
+                Some(&[
+                    CoreClientAuthMethod::PrivateKeyJwt,
+                    CoreClientAuthMethod::ClientSecretJwt
+                ]),
+                &secret_methods
+            ),
+            Err(TokenEndpointAuthMethodError(vec![
+                "private_key_jwt".to_string(),
+                "client_secret_jwt".to_string()
+            ]))
+        );
+
+        assert_eq!(
+            new_client().token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::ClientSecretBasic
+        );
+        assert_eq!(
+            new_client()
+                .set_auth_type(AuthType::RequestBody)
+                .token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::ClientSecretPost
+        );
+
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_token_endpoint_auth_methods_supported(Some(vec![
+            CoreClientAuthMethod::PrivateKeyJwt,
+            CoreClientAuthMethod::ClientSecretPost,
+        ]));
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata.clone(),
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+        );
+        assert_eq!(
+            client.token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::ClientSecretPost
+        );
+        assert!(client.client_assertion_params().unwrap().is_empty());
+        let client = client.set_token_endpoint_auth_method(TokenEndpointAuthMethod::PrivateKeyJwt);
+        assert_eq!(
+            client.token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::PrivateKeyJwt
+        );
+        // No signing key is configured.
+        client.client_assertion_params().unwrap_err();
+
+        // The provider doesn't list `client_secret_jwt`, so the HMAC key isn't used.
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata.clone(),
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+        )
+        .set_client_assertion_signing_key(
+            CoreHmacKey::new("bbb"),
+            CoreJwsSigningAlgorithm::HmacSha256,
+        );
+        assert_eq!(
+            client.token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::ClientSecretPost
+        );
+
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata.clone(),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert_eq!(
+            client.token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::None
+        );
+
+        let provider_metadata =
+            provider_metadata.set_token_endpoint_auth_methods_supported(Some(vec![
+                CoreClientAuthMethod::PrivateKeyJwt,
+            ]));
+        assert_eq!(
+            CoreClient::try_from_provider_metadata(
+                provider_metadata.clone(),
+                ClientId::new("aaa".to_string()),
+                Some(ClientSecret::new("bbb".to_string())),
+            )
+            .unwrap_err(),
+            TokenEndpointAuthMethodError(vec!["private_key_jwt".to_string()])
+        );
+        assert_eq!(
+            CoreClient::from_provider_metadata(
+                provider_metadata,
+                ClientId::new("aaa".to_string()),
+                Some(ClientSecret::new("bbb".to_string())),
+            )
+            .token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::ClientSecretBasic
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_token_request_client_assertion() {
+        let client = new_client().set_client_assertion_signing_key(
+            CoreHmacKey::new("bbb"),
+            CoreJwsSigningAlgorithm::HmacSha256,
+        );
+        assert_eq!(
+            client.token_endpoint_auth_method(),
+            TokenEndpointAuthMethod::ClientSecretJwt
+        );
+
+        let sent_request = Mutex::new(None);
+        let http_client = |mut request: http_types::Request| {
+            let body = block_on(request.body_string()).unwrap();
+            *sent_request.lock().unwrap() = Some((request, body));
+            let mut response = http_types::Response::new(http_types::StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"access_token\":\"12/34\",\"token_type\":\"bearer\"}");
+            Ok::<_, io::Error>(response)
+        };
+        client
+            .request_code_exchange_blocking(
+                client.exchange_code(AuthorizationCode::new("ccc".to_string())),
+                &http_client,
+            )
+            .unwrap();
+
+        let (request, body) = sent_request.lock().unwrap().take().unwrap();
+        // The client secret is only used as the signing key.
+        assert!(request.header("Authorization").is_none());
+        let params = form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect::<HashMap<_, _>>();
+        assert_eq!(None, params.get("client_secret"));
+        assert_eq!("aaa", params["client_id"]);
+        assert_eq!(
+            "urn:ietf:params:oauth:client-assertion-type:jwt-bearer",
+            params["client_assertion_type"]
+        );
+
+        let assertion_parts = params["client_assertion"].split('.').collect::<Vec<_>>();
+        assert_eq!(3, assertion_parts.len());
+        let claims: serde_json::Value = serde_json::from_slice(
+            &base64::decode_config(assertion_parts[1], base64::URL_SAFE_NO_PAD).unwrap(),
+        )
+        .unwrap();
+        assert_eq!("aaa", claims["iss"]);
+        assert_eq!("aaa", claims["sub"]);
+        assert_eq!("https://example/token", claims["aud"]);
+        assert!(claims["jti"].is_string());
+        assert_eq!(
+            300,
+            claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap()
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_token_request_kind() {
+        let client = new_client();
+        let request_kinds = Mutex::new(Vec::new());
+        let http_client = |request: http_types::Request| {
+            request_kinds
+                .lock()
+                .unwrap()
+                .push(RequestKind::from_request(&request));
+            let mut response = http_types::Response::new(http_types::StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"access_token\":\"12/34\",\"token_type\":\"bearer\"}");
+            Ok::<_, io::Error>(response)
+        };
+
+        client
+            .request_code_exchange_blocking(
+                client.exchange_code(AuthorizationCode::new("ccc".to_string())),
+                &http_client,
+            )
+            .unwrap();
+        let refresh_token = RefreshToken::new("ddd".to_string());
+        client
+            .request_refresh_token_blocking(
+                client.exchange_refresh_token(&refresh_token),
+                &http_client,
+            )
+            .unwrap();
+
+        assert_eq!(
+            vec![Some(RequestKind::Token), Some(RequestKind::Token)],
+            *request_kinds.lock().unwrap()
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_token_request_authorization_details() {
+        let client = new_client();
+        let bodies = Mutex::new(Vec::new());
+        let http_client = |mut request: http_types::Request| {
+            bodies
+                .lock()
+                .unwrap()
+                .push(block_on(request.body_string()).unwrap());
+            let mut response = http_types::Response::new(http_types::StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"access_token\":\"12/34\",\"token_type\":\"bearer\"}");
+            Ok::<_, io::Error>(response)
+        };
+
+        let authorization_details =
+            vec![AuthorizationDetail::new("account_information".to_string())
+                .set_actions(Some(vec!["list_accounts".to_string()]))];
+        client
+            .request_code_exchange_blocking(
+                client
+                    .exchange_code(AuthorizationCode::new("ccc".to_string()))
+                    .set_authorization_details(&authorization_details),
+                &http_client,
+            )
+            .unwrap();
+        let refresh_token = RefreshToken::new("ddd".to_string());
+        client
+            .request_refresh_token_blocking(
+                client
+                    .exchange_refresh_token(&refresh_token)
+                    .set_authorization_details(&authorization_details),
+                &http_client,
+            )
+            .unwrap();
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(2, bodies.len());
+        for body in bodies.iter() {
+            let params = form_urlencoded::parse(body.as_bytes())
+                .into_owned()
+                .collect::<HashMap<_, _>>();
+            assert_eq!(
+                "[{\"type\":\"account_information\",\"actions\":[\"list_accounts\"]}]",
+                params["authorization_details"]
+            );
+        }
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_token_request_error_kind() {
+        type TestHttpClient =
+            dyn Fn(http_types::Request) -> Result<http_types::Response, io::Error>;
+
+        let client = new_client();
+        let exchange_code = |http_client: &TestHttpClient| {
+            client
+                .request_code_exchange_blocking(
+                    client.exchange_code(AuthorizationCode::new("ccc".to_string())),
+                    &http_client,
+                )
+                .unwrap_err()
+        };
+
+        let err = exchange_code(&|_| {
+            let mut response = http_types::Response::new(http_types::StatusCode::BadRequest);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"error\":\"invalid_grant\"}");
+            Ok(response)
+        });
+        assert_eq!(Some(http_types::StatusCode::BadRequest), err.status());
+        assert_eq!(ErrorKind::ClientError, err.error_kind());
+
+        let err = exchange_code(&|_| {
+            let mut response = http_types::Response::new(http_types::StatusCode::BadRequest);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"error\":\"temporarily_unavailable\"}");
+            Ok(response)
+        });
+        assert_eq!(ErrorKind::ServerError, err.error_kind());
+        assert!(err.is_retryable());
+
+        let err = exchange_code(&|_| {
+            let mut response = http_types::Response::new(http_types::StatusCode::BadGateway);
+            response.insert_header("Content-Type", "text/html");
+            response.set_body("<html>Bad Gateway</html>");
+            Ok(response)
+        });
+        assert_eq!(Some(http_types::StatusCode::BadGateway), err.status());
+        assert_eq!(ErrorKind::ServerError, err.error_kind());
+        assert!(err.is_retryable());
+
+        let err = exchange_code(&|_| Err(io::Error::new(io::ErrorKind::Other, "not sent")));
+        assert_eq!(None, err.status());
+        assert_eq!(ErrorKind::Request, err.error_kind());
+    }
+
+    #[test]
+    fn test_oauth21_strict() {
+        let client = new_client()
+            .set_redirect_uri(RedirectUrl::new("http://localhost:8888/cb".to_string()).unwrap())
+            .oauth21_strict();
+        assert!(client.is_oauth21_strict());
+        assert!(!new_client().is_oauth21_strict());
+
+        let try_url = |flow: CoreAuthenticationFlow, redirect_url: Option<&str>| {
+            let request = client
+                .authorize_url(
+                    flow,
+                    || CsrfToken::new("CSRF123".to_string()),
+                    || Nonce::new("NONCE456".to_string()),
+                )
+                .set_pkce_challenge(PkceCodeChallenge::new_random_sha256().0);
+            match redirect_url {
+                Some(redirect_url) => request.set_redirect_url(Cow::Owned(
+                    RedirectUrl::new(redirect_url.to_string()).unwrap(),
+                )),
+                None => request,
+            }
+            .try_url()
+        };
+
+        assert!(try_url(CoreAuthenticationFlow::AuthorizationCode, None).is_ok());
+        assert!(try_url(
+            CoreAuthenticationFlow::AuthorizationCode,
+            Some("http://localhost:8888/cb")
+        )
+        .is_ok());
+        match try_url(CoreAuthenticationFlow::Implicit(false), None) {
+            Err(AuthorizationRequestError::UnsupportedFlow) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // Redirect URLs must match exactly, even if they're equivalent.
+        match try_url(
+            CoreAuthenticationFlow::AuthorizationCode,
+            Some("http://LOCALHOST:8888/cb"),
+        ) {
+            Err(AuthorizationRequestError::RedirectUrlMismatch(url)) => {
+                assert_eq!(url, "http://LOCALHOST:8888/cb")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // PKCE is required.
+        match client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .try_url()
+        {
+            Err(AuthorizationRequestError::PkceRequired) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let state = CsrfToken::new("af0ifjsldkj".to_string());
+        client
+            .authorization_response::<CoreAuthErrorResponseType>(
+                &Url::parse("http://localhost:8888/cb?code=foo&state=af0ifjsldkj").unwrap(),
+                &state,
+            )
+            .unwrap();
+        match client.authorization_response::<CoreAuthErrorResponseType>(
+            &Url::parse("http://localhost:8888/cb#access_token=foo&state=af0ifjsldkj").unwrap(),
+            &state,
+        ) {
+            Err(AuthorizationResponseError::FragmentNotAllowed) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "PKCE is required but no code challenge was set")]
+    fn test_authorize_url_require_pkce_panics() {
+        new_client()
+            .set_require_pkce(true)
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .url();
+    }
+
     #[test]
     fn test_authorize_url_redirect_url_override() {
         let client = new_client()
@@ -1490,4 +4005,438 @@ mod tests {
             authorize_url.to_string()
         );
     }
+
+    #[test]
+    fn test_authorize_url_locales() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_claims_locale(LanguageTag::new("de".to_string()))
+            .add_claims_locale(LanguageTag::new("en-US".to_string()))
+            .add_ui_locale(LanguageTag::new("fr-CA".to_string()))
+            .add_ui_locale(LanguageTag::new("fr".to_string()))
+            .url();
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid&nonce=NONCE456&claims_locales=de+en-US&\
+             ui_locales=fr-CA+fr",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_acr_values_and_purpose() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_acr_value(AuthenticationContextClass::new("urn:acr:gold".to_string()))
+            .add_acr_value(AuthenticationContextClass::new("urn:acr:silver".to_string()))
+            .set_purpose("Account verification".to_string())
+            .url();
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid&nonce=NONCE456&\
+             acr_values=urn%3Aacr%3Agold+urn%3Aacr%3Asilver&purpose=Account+verification",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_vectors_of_trust() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_vector_of_trust(VectorOfTrust::new("P1.Cc.Ac".to_string()))
+            .add_vector_of_trust(VectorOfTrust::new("Cb".to_string()))
+            .url();
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid&nonce=NONCE456&\
+             vtr=%5B%22P1.Cc.Ac%22%2C%22Cb%22%5D",
+            authorize_url.to_string()
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_token_request_blocking() {
+        let client = new_client().add_interceptor(|request| {
+            request.insert_header("X-Request-Id", "abc");
+        });
+
+        let sent_bodies = Mutex::new(Vec::new());
+        let http_client = |mut request: http_types::Request| {
+            assert_eq!("abc", request.header("X-Request-Id").unwrap().as_str());
+            sent_bodies
+                .lock()
+                .unwrap()
+                .push(block_on(request.body_string()).unwrap());
+            let mut response = http_types::Response::new(http_types::StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(
+                "{\"access_token\":\"12/34\",\"token_type\":\"bearer\",\
+                 \"refresh_token\":\"56/78\"}",
+            );
+            Ok::<_, io::Error>(response)
+        };
+
+        let token_response = client
+            .request_code_exchange_blocking(
+                client.exchange_code(AuthorizationCode::new("ccc".to_string())),
+                &http_client,
+            )
+            .unwrap();
+        assert_eq!("12/34", token_response.access_token().secret());
+
+        let refresh_token = token_response.refresh_token().unwrap();
+        let token_response = client
+            .request_refresh_token_blocking(
+                client.exchange_refresh_token(refresh_token),
+                &http_client,
+            )
+            .unwrap();
+        assert_eq!("12/34", token_response.access_token().secret());
+
+        let sent_bodies = sent_bodies.lock().unwrap();
+        assert_eq!(2, sent_bodies.len());
+        assert!(sent_bodies[0].contains("grant_type=authorization_code&code=ccc"));
+        assert!(sent_bodies[1].contains("grant_type=refresh_token&refresh_token=56%2F78"));
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_user_info_request_token_in_body() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        );
+
+        let sent_request = Mutex::new(None);
+        let http_client = |mut request: http_types::Request| {
+            let body = block_on(request.body_string()).unwrap();
+            *sent_request.lock().unwrap() = Some((request, body));
+            Err::<http_types::Response, _>(io::Error::new(io::ErrorKind::Other, "not sent"))
+        };
+
+        let _ = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .set_token_in_body(true)
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client);
+
+        let (request, body) = sent_request.lock().unwrap().take().unwrap();
+        assert_eq!(http_types::Method::Post, request.method());
+        assert!(request.header("Authorization").is_none());
+        assert_eq!(
+            "application/x-www-form-urlencoded",
+            request.header("Content-Type").unwrap().as_str()
+        );
+        assert_eq!("access_token=secret_token", body);
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_user_info_request_headers() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        );
+
+        let sent_request = Mutex::new(None);
+        let http_client = |request: http_types::Request| {
+            *sent_request.lock().unwrap() = Some(request);
+            Err::<http_types::Response, _>(io::Error::new(io::ErrorKind::Other, "not sent"))
+        };
+
+        let _ = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client);
+        let request = sent_request.lock().unwrap().take().unwrap();
+        assert_eq!(http_types::Method::Get, request.method());
+        assert_eq!(
+            "application/json",
+            request.header("Accept").unwrap().as_str()
+        );
+        assert!(request.header("X-Request-Id").is_none());
+
+        let _ = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .set_accept(Some("application/jwt, application/json;q=0.9".to_string()))
+            .add_header("X-Request-Id", "abc")
+            .add_header("X-Tenant", "example")
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client);
+        let request = sent_request.lock().unwrap().take().unwrap();
+        assert_eq!(
+            "application/jwt, application/json;q=0.9",
+            request.header("Accept").unwrap().as_str()
+        );
+        assert_eq!("abc", request.header("X-Request-Id").unwrap().as_str());
+        assert_eq!("example", request.header("X-Tenant").unwrap().as_str());
+        assert_eq!(
+            "Bearer secret_token",
+            request.header("Authorization").unwrap().as_str()
+        );
+
+        // Replacing the request options discards previously added headers.
+        let _ = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .add_header("X-Request-Id", "abc")
+            .set_request_options(RequestOptions::new().add_header("X-Tenant", "example"))
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client);
+        let request = sent_request.lock().unwrap().take().unwrap();
+        assert!(request.header("X-Request-Id").is_none());
+        assert_eq!("example", request.header("X-Tenant").unwrap().as_str());
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_user_info_interceptors() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        );
+        assert!(client.interceptors().is_empty());
+
+        let client = client
+            .add_interceptor(|request| {
+                request.insert_header("X-Request-Id", "abc");
+            })
+            .add_response_interceptor(|response| {
+                // Repairs a provider that mislabels its JSON responses.
+                response.insert_header("Content-Type", "application/json");
+            });
+        assert!(!client.interceptors().is_empty());
+
+        let http_client = |request: http_types::Request| {
+            assert_eq!("abc", request.header("X-Request-Id").unwrap().as_str());
+            let mut response = http_types::Response::new(http_types::StatusCode::Ok);
+            response.insert_header("Content-Type", "text/plain");
+            response.set_body("{\"sub\":\"24400320\"}");
+            Ok::<_, io::Error>(response)
+        };
+
+        let claims = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client)
+            .unwrap();
+        assert_eq!("24400320", claims.subject().as_str());
+
+        // Requests sent outside the client pass through its interceptors via `http_client`.
+        let async_http_client = |request: http_types::Request| {
+            assert_eq!("abc", request.header("X-Request-Id").unwrap().as_str());
+            ready(Ok::<_, io::Error>(http_types::Response::new(
+                http_types::StatusCode::Ok,
+            )))
+        };
+        let response = block_on(client.http_client(&async_http_client).call(
+            http_types::Request::new(
+                http_types::Method::Get,
+                Url::parse("https://example/jwks").unwrap(),
+            ),
+        ))
+        .unwrap();
+        assert_eq!(
+            "application/json",
+            response.header("Content-Type").unwrap().as_str()
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_user_info_request_options() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        );
+
+        let sent_request = Mutex::new(None);
+        let http_client = |request: http_types::Request| {
+            *sent_request.lock().unwrap() = Some(request);
+            Err::<http_types::Response, _>(io::Error::new(io::ErrorKind::Other, "not sent"))
+        };
+
+        let request_options = RequestOptions::new()
+            .add_header("X-Request-Id", "abc")
+            .set_timeout(Some(Duration::from_secs(5)))
+            .set_user_agent(Some("test-agent/1.0".to_string()));
+        let user_info_request = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .set_request_options(request_options.clone());
+        assert_eq!(&request_options, user_info_request.request_options());
+        let _ = user_info_request
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client);
+
+        let request = sent_request.lock().unwrap().take().unwrap();
+        assert_eq!("abc", request.header("X-Request-Id").unwrap().as_str());
+        assert_eq!(
+            "test-agent/1.0",
+            request.header("User-Agent").unwrap().as_str()
+        );
+        assert_eq!(
+            "Bearer secret_token",
+            request.header("Authorization").unwrap().as_str()
+        );
+        assert_eq!(
+            Some(&request_options),
+            RequestOptions::from_request(&request)
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_user_info_error_response() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        );
+
+        let http_client = |_: http_types::Request| {
+            let mut response = http_types::Response::new(http_types::StatusCode::Unauthorized);
+            response.insert_header("Content-Type", "text/plain");
+            response.insert_header("Set-Cookie", "session=secret");
+            response.insert_header("WWW-Authenticate", "Bearer realm=\"example\"");
+            response.set_body("token expired");
+            Ok::<_, io::Error>(response)
+        };
+
+        let err = client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client)
+            .unwrap_err();
+        let response = match err {
+            UserInfoError::Response(ref response, _) => response.clone(),
+            ref other => panic!("unexpected error: {:?}", other),
+        };
+        assert_eq!(http_types::StatusCode::Unauthorized, response.status());
+        assert_eq!(b"token expired", response.body());
+        assert_eq!(
+            Some("Bearer realm=\"example\""),
+            response.header("www-authenticate")
+        );
+        assert_eq!(None, response.header("Set-Cookie"));
+        assert_eq!(ErrorKind::ClientError, err.error_kind());
+        assert!(!err.is_retryable());
+        assert_eq!(
+            "Server returned invalid response: unexpected HTTP status code \
+             (HTTP 401 Unauthorized: token expired)",
+            err.to_string()
+        );
+
+        // RFC 6750 errors are parsed from the WWW-Authenticate header.
+        let http_client = |_: http_types::Request| {
+            let mut response = http_types::Response::new(http_types::StatusCode::Forbidden);
+            response.insert_header(
+                "WWW-Authenticate",
+                "Bearer error=\"insufficient_scope\", scope=\"openid email\"",
+            );
+            Ok::<_, io::Error>(response)
+        };
+        match client
+            .user_info(AccessToken::new("secret_token".to_string()), None)
+            .unwrap()
+            .request_blocking::<EmptyAdditionalClaims, _, CoreGenderClaim>(&http_client)
+        {
+            Err(UserInfoError::BearerToken(ref bearer_token_error)) => {
+                assert_eq!(
+                    BearerTokenErrorType::InsufficientScope,
+                    *bearer_token_error.error()
+                );
+                assert_eq!(
+                    Some(&vec![
+                        Scope::new("openid".to_string()),
+                        Scope::new("email".to_string())
+                    ]),
+                    bearer_token_error.scopes()
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_authorize_url_claims() {
+        let client = new_client();
+
+        let claims = CoreClaimsRequest::new()
+            .add_userinfo_claim(
+                CoreClaimName::new("given_name".to_string()),
+                Some(IndividualClaimRequest::new().set_essential(Some(true))),
+            )
+            .add_userinfo_claim(CoreClaimName::new("nickname".to_string()), None)
+            .add_id_token_claim(
+                CoreClaimName::new("acr".to_string()),
+                Some(IndividualClaimRequest::new().set_values(Some(vec![
+                    serde_json::Value::String("urn:mace:incommon:iap:silver".to_string()),
+                ]))),
+            );
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .set_claims(&claims)
+            .url();
+
+        let claims_param = authorize_url
+            .query_pairs()
+            .find(|(name, _)| name == "claims")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        assert_eq!(
+            "{\"userinfo\":{\"given_name\":{\"essential\":true},\"nickname\":null},\
+             \"id_token\":{\"acr\":{\"values\":[\"urn:mace:incommon:iap:silver\"]}}}",
+            claims_param
+        );
+    }
 }
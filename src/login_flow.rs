@@ -0,0 +1,423 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+
+use failure::Fail;
+use url::Url;
+
+use crate::clock::Clock;
+use crate::core::{CoreAuthErrorResponseType, CoreResponseType};
+use crate::error::{ClassifiedError, ErrorKind};
+use crate::http_client::AsyncHttpClient;
+use crate::{
+    AccessTokenHash, AdditionalClaims, AuthDisplay, AuthPrompt, AuthenticationFlow,
+    AuthorizationRequest, AuthorizationRequestError, AuthorizationResponseError,
+    ClaimsVerificationError, Client, CsrfToken, ErrorResponse, GenderClaim, IdTokenClaims,
+    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
+    Nonce, OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier, TokenRequestError,
+    TokenResponse, TokenType,
+};
+
+///
+/// Single login via the Authorization Code Flow, performed in one process.
+///
+/// [`LoginFlow::new`] generates a CSRF token, nonce, and PKCE challenge and builds the
+/// authorization URL to which the user agent should be redirected (see [`LoginFlow::url`]).
+/// [`LoginFlow::authenticate`] accepts the URL to which the Authorization Endpoint redirected the
+/// user agent and performs the remaining steps: it verifies the `state` and `iss` parameters,
+/// exchanges the authorization code along with the PKCE verifier, and verifies the ID token
+/// (including its nonce and access token hash).
+///
+/// The flow keeps its secrets in memory, which suits native and command-line applications that
+/// receive the redirect in the same process. Web applications that handle the redirect in a
+/// separate request should use [`LoginManager`](crate::LoginManager), which persists pending logins
+/// to a [`StateStore`](crate::StateStore).
+///
+pub struct LoginFlow<'a, AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    client: &'a Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+    nonce: Nonce,
+    pkce_verifier: PkceCodeVerifier,
+    state: CsrfToken,
+    url: Url,
+}
+impl<'a, AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+    LoginFlow<'a, AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    ///
+    /// Begins a login using the specified client, which must have a redirect URI.
+    ///
+    /// The `request_fn` may customize the authorization request (e.g., to add scopes). The CSRF
+    /// token, nonce, and PKCE challenge are generated by this method and should not be overridden.
+    ///
+    /// Returns an error if the authorization request violates the client's requirements or
+    /// omits the `openid` scope (see [`AuthorizationRequest::try_url`]).
+    ///
+    pub fn new<F>(
+        client: &'a Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+        request_fn: F,
+    ) -> Result<Self, AuthorizationRequestError>
+    where
+        F: FnOnce(
+            AuthorizationRequest<AD, P, CoreResponseType>,
+        ) -> AuthorizationRequest<AD, P, CoreResponseType>,
+    {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (url, state, nonce) = request_fn(client.authorize_url(
+            AuthenticationFlow::AuthorizationCode,
+            CsrfToken::new_random,
+            Nonce::new_random,
+        ))
+        .set_pkce_challenge(pkce_challenge)
+        .try_url()?;
+
+        Ok(Self {
+            client,
+            nonce,
+            pkce_verifier,
+            state,
+            url,
+        })
+    }
+
+    ///
+    /// Returns the authorization URL to which the user agent should be redirected.
+    ///
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    ///
+    /// Completes the login given the URL to which the Authorization Endpoint redirected the user
+    /// agent, returning the verified ID token claims and the token response.
+    ///
+    pub async fn authenticate<C>(
+        self,
+        redirect_url: &Url,
+        http_client: &C,
+    ) -> Result<(IdTokenClaims<AC, GC>, TR), LoginFlowError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let (token_response, id_token_claims) = complete_login(
+            self.client,
+            None,
+            redirect_url,
+            &self.state,
+            &self.nonce,
+            Some(self.pkce_verifier),
+            http_client,
+        )
+        .await?;
+        Ok((id_token_claims, token_response))
+    }
+}
+impl<'a, AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> Debug
+    for LoginFlow<'a, AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("LoginFlow").field("url", &self.url).finish()
+    }
+}
+
+// Completes an Authorization Code Flow login once the pending login matching the redirect's
+// `state` parameter has been found. Shared with `LoginManager`. The ID token verifier isn't
+// `Send`, so it's only constructed after the token exchange to keep the returned future `Send`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn complete_login<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, C>(
+    client: &Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+    clock: Option<Arc<dyn Clock + Send + Sync>>,
+    redirect_url: &Url,
+    state: &CsrfToken,
+    nonce: &Nonce,
+    pkce_verifier: Option<PkceCodeVerifier>,
+    http_client: &C,
+) -> Result<(TR, IdTokenClaims<AC, GC>), LoginFlowError<C::Error, TE>>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    C: AsyncHttpClient + ?Sized,
+{
+    let response = client
+        .authorization_response(redirect_url, state)
+        .map_err(LoginFlowError::Authorization)?;
+    let code = response
+        .code()
+        .cloned()
+        .ok_or(LoginFlowError::MissingCode)?;
+
+    let mut token_request = client.exchange_code(code);
+    if let Some(pkce_verifier) = pkce_verifier {
+        token_request = token_request.set_pkce_verifier(pkce_verifier);
+    }
+    let token_response = client
+        .request_code_exchange(token_request, http_client)
+        .await
+        .map_err(LoginFlowError::Exchange)?;
+
+    let id_token = token_response
+        .id_token()
+        .ok_or(LoginFlowError::MissingIdToken)?;
+    let verifier = match clock {
+        Some(clock) => client.id_token_verifier().set_time_fn(move || clock.now()),
+        None => client.id_token_verifier(),
+    };
+    let id_token_claims = id_token
+        .claims(&verifier, nonce)
+        .map_err(LoginFlowError::IdTokenVerification)?
+        .clone();
+
+    // Verify the access token hash to ensure that the access token hasn't been substituted for
+    // another user's.
+    if let Some(expected_access_token_hash) = id_token_claims.access_token_hash() {
+        let actual_access_token_hash = id_token
+            .signing_alg()
+            .and_then(|alg| AccessTokenHash::from_token(token_response.access_token(), &alg))
+            .map_err(|err| LoginFlowError::InvalidAccessTokenHash(err.to_string()))?;
+        if actual_access_token_hash != *expected_access_token_hash {
+            return Err(LoginFlowError::InvalidAccessTokenHash(
+                "access token hash mismatch".to_string(),
+            ));
+        }
+    }
+
+    Ok((token_response, id_token_claims))
+}
+
+///
+/// Error completing a login via a [`LoginFlow`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum LoginFlowError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// The Authorization Endpoint returned an error or a malformed response.
+    ///
+    #[fail(display = "Failed to parse authorization response")]
+    Authorization(#[cause] AuthorizationResponseError<CoreAuthErrorResponseType>),
+    ///
+    /// The authorization code exchange failed.
+    ///
+    #[fail(display = "Failed to exchange authorization code")]
+    Exchange(#[cause] TokenRequestError<RE, TE>),
+    ///
+    /// The ID token failed verification.
+    ///
+    #[fail(display = "Failed to verify ID token")]
+    IdTokenVerification(#[cause] ClaimsVerificationError),
+    ///
+    /// The access token hash in the ID token does not match the access token.
+    ///
+    #[fail(display = "Invalid access token hash: {}", _0)]
+    InvalidAccessTokenHash(String),
+    ///
+    /// The authorization response does not contain an authorization code.
+    ///
+    #[fail(display = "Authorization response is missing an authorization code")]
+    MissingCode,
+    ///
+    /// The token response does not contain an ID token.
+    ///
+    #[fail(display = "Server did not return an ID token")]
+    MissingIdToken,
+}
+impl<RE, TE> ClassifiedError for LoginFlowError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            // The authorization response is delivered via the user's browser, so retrying the
+            // token request can't resolve authorization errors.
+            LoginFlowError::Authorization(_) => ErrorKind::ClientError,
+            LoginFlowError::Exchange(err) => err.error_kind(),
+            LoginFlowError::IdTokenVerification(_) | LoginFlowError::InvalidAccessTokenHash(_) => {
+                ErrorKind::Verification
+            }
+            LoginFlowError::MissingCode | LoginFlowError::MissingIdToken => {
+                ErrorKind::InvalidResponse
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::executor::block_on;
+    use futures::future::ready;
+
+    use crate::core::{CoreClient, CoreLoginFlow};
+    use crate::http_types::{Request, Response, StatusCode};
+    #[cfg(feature = "test-provider")]
+    use crate::test_provider::TestProvider;
+    #[cfg(feature = "test-provider")]
+    use crate::{Audience, Nonce, OAuth2TokenResponse, SubjectIdentifier};
+    use crate::{
+        AuthUrl, AuthorizationRequestError, AuthorizationResponseError, ClientId, ClientSecret,
+        IssuerUrl, JsonWebKeySet, RedirectUrl, Scope, TokenUrl,
+    };
+
+    use super::LoginFlowError;
+
+    fn new_client() -> CoreClient {
+        CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        )
+        .set_redirect_uri(RedirectUrl::new("https://client/cb".to_string()).unwrap())
+    }
+
+    fn query_param(url: &url::Url, name: &str) -> Option<String> {
+        url.query_pairs()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    #[test]
+    fn test_login_flow() {
+        let token_request_count = AtomicUsize::new(0);
+        let http_client = |_: Request| {
+            token_request_count.fetch_add(1, Ordering::SeqCst);
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body("{\"access_token\": \"foo\", \"token_type\": \"bearer\"}");
+            ready(Ok::<_, io::Error>(response))
+        };
+
+        let client = new_client();
+        let flow = CoreLoginFlow::new(&client, |request| {
+            request.add_scope(Scope::new("email".to_string()))
+        })
+        .unwrap();
+        let url = flow.url().clone();
+        assert_eq!(Some("openid email".to_string()), query_param(&url, "scope"));
+        assert_eq!(
+            Some("S256".to_string()),
+            query_param(&url, "code_challenge_method")
+        );
+        assert!(query_param(&url, "nonce").is_some());
+
+        // The state parameter must match the one generated by the flow.
+        match block_on(flow.authenticate(
+            &url::Url::parse("https://client/cb?code=the_code&state=other").unwrap(),
+            &http_client,
+        )) {
+            Err(LoginFlowError::Authorization(AuthorizationResponseError::StateMismatch)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(0, token_request_count.load(Ordering::SeqCst));
+
+        let flow = CoreLoginFlow::new(&client, |request| request).unwrap();
+        let redirect_url = url::Url::parse(&format!(
+            "https://client/cb?code=the_code&state={}",
+            query_param(flow.url(), "state").unwrap()
+        ))
+        .unwrap();
+        match block_on(flow.authenticate(&redirect_url, &http_client)) {
+            Err(LoginFlowError::MissingIdToken) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(1, token_request_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_login_flow_invalid_request() {
+        let client = new_client().disable_openid_scope();
+        match CoreLoginFlow::new(&client, |request| request) {
+            Err(AuthorizationRequestError::MissingOpenIdScope) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "test-provider")]
+    #[test]
+    fn test_login_flow_test_provider() {
+        let provider = TestProvider::new(
+            IssuerUrl::new("https://provider.example".to_string()).unwrap(),
+            ClientId::new("aaa".to_string()),
+        );
+        let client = CoreClient::from_provider_metadata(
+            provider.provider_metadata(),
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+        )
+        .set_redirect_uri(RedirectUrl::new("https://client/cb".to_string()).unwrap());
+
+        let flow = CoreLoginFlow::new(&client, |request| request).unwrap();
+        let nonce = query_param(flow.url(), "nonce").map(Nonce::new);
+        let redirect_url = provider.authorize(flow.url()).unwrap();
+        let (id_token_claims, token_response) =
+            block_on(flow.authenticate(&redirect_url, &provider)).unwrap();
+        assert_eq!(
+            *id_token_claims.subject(),
+            SubjectIdentifier::new("test-subject".to_string())
+        );
+        assert_eq!(
+            *id_token_claims.audiences(),
+            vec![Audience::new("aaa".to_string())]
+        );
+        assert_eq!(id_token_claims.nonce(), nonce.as_ref());
+        assert!(token_response.refresh_token().is_some());
+    }
+}
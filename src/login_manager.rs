@@ -0,0 +1,418 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use url::Url;
+
+use crate::authorization::redirect_url_state;
+use crate::clock::{Clock, SystemClock};
+use crate::core::{CoreAuthErrorResponseType, CoreResponseType};
+use crate::error::{ClassifiedError, ErrorKind};
+use crate::http_client::AsyncHttpClient;
+use crate::login_flow::{complete_login, LoginFlowError};
+use crate::login_state::{PendingLogin, StateStore};
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, AuthenticationFlow, AuthorizationRequest,
+    AuthorizationResponseError, ClaimsVerificationError, Client, CsrfToken, ErrorResponse,
+    GenderClaim, IdTokenClaims, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, Nonce, PkceCodeChallenge,
+    TokenRequestError, TokenResponse, TokenType,
+};
+
+// By default, logins must be completed within this many seconds of being started.
+const DEFAULT_MAX_AGE_SECS: u64 = 600;
+
+///
+/// High-level helper for logging in end users via the Authorization Code Flow.
+///
+/// [`LoginManager::begin`] generates an authorization URL with a fresh CSRF token, nonce, and PKCE
+/// challenge, and saves the corresponding [`PendingLogin`] to a [`StateStore`].
+/// [`LoginManager::complete`] handles the redirect back to the client: it consumes the pending
+/// login matching the `state` parameter, verifies the `iss` parameter (if any), exchanges the
+/// authorization code, and verifies the ID token (including its nonce and access token hash).
+///
+/// Since the pending login is consumed before the response is processed, each redirect may be
+/// handled at most once.
+///
+pub struct LoginManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+    max_age: Duration,
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+    LoginManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    ///
+    /// Instantiates a login manager for the specified client, which must have a redirect URI.
+    ///
+    pub fn new(client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>) -> Self {
+        Self {
+            client,
+            max_age: Duration::from_secs(DEFAULT_MAX_AGE_SECS),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    ///
+    /// Sets the maximum time between beginning and completing a login (10 minutes by default).
+    ///
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    ///
+    /// Sets the clock used for checking the age of pending logins and verifying ID tokens.
+    ///
+    pub fn set_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    ///
+    /// Returns the underlying client.
+    ///
+    pub fn client(&self) -> &Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> {
+        &self.client
+    }
+
+    ///
+    /// Begins a login, returning the authorization URL to which the user agent should be
+    /// redirected.
+    ///
+    /// The `request_fn` may customize the authorization request (e.g., to add scopes). The CSRF
+    /// token, nonce, and PKCE challenge are generated by this method and should not be overridden.
+    ///
+    pub fn begin<F, S>(&self, store: &S, request_fn: F) -> Result<Url, S::Error>
+    where
+        F: FnOnce(
+            AuthorizationRequest<AD, P, CoreResponseType>,
+        ) -> AuthorizationRequest<AD, P, CoreResponseType>,
+        S: StateStore + ?Sized,
+    {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (url, state, nonce) = request_fn(self.client.authorize_url(
+            AuthenticationFlow::AuthorizationCode,
+            CsrfToken::new_random,
+            Nonce::new_random,
+        ))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+        store.save(
+            &state,
+            PendingLogin::new(nonce, self.clock.now()).set_pkce_verifier(Some(pkce_verifier)),
+        )?;
+        Ok(url)
+    }
+
+    ///
+    /// Completes a login given the URL to which the Authorization Endpoint redirected the user
+    /// agent, returning the token response and the verified ID token claims.
+    ///
+    pub async fn complete<C, S>(
+        &self,
+        store: &S,
+        redirect_url: &Url,
+        http_client: &C,
+    ) -> Result<(TR, IdTokenClaims<AC, GC>), LoginManagerError<C::Error, S::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+        S: StateStore + ?Sized,
+    {
+        let state = redirect_url_state(redirect_url).ok_or(LoginManagerError::UnknownState)?;
+        let pending_login = store
+            .take(&state)
+            .map_err(LoginManagerError::StateStore)?
+            .ok_or(LoginManagerError::UnknownState)?;
+        if self.is_expired(pending_login.created_at()) {
+            return Err(LoginManagerError::Expired);
+        }
+
+        let nonce = pending_login.nonce().clone();
+        complete_login(
+            &self.client,
+            Some(self.clock.clone()),
+            redirect_url,
+            &state,
+            &nonce,
+            pending_login.into_pkce_verifier(),
+            http_client,
+        )
+        .await
+        .map_err(LoginManagerError::from)
+    }
+
+    fn is_expired(&self, created_at: DateTime<Utc>) -> bool {
+        chrono::Duration::from_std(self.max_age)
+            .ok()
+            .and_then(|max_age| created_at.checked_add_signed(max_age))
+            .map_or(false, |expires_at| self.clock.now() > expires_at)
+    }
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> Debug
+    for LoginManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("LoginManager")
+            .field("max_age", &self.max_age)
+            .finish()
+    }
+}
+
+///
+/// Error completing a login via a [`LoginManager`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum LoginManagerError<RE, SE, TE>
+where
+    RE: Fail,
+    SE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// The Authorization Endpoint returned an error or a malformed response.
+    ///
+    #[fail(display = "Failed to parse authorization response")]
+    Authorization(#[cause] AuthorizationResponseError<CoreAuthErrorResponseType>),
+    ///
+    /// The authorization code exchange failed.
+    ///
+    #[fail(display = "Failed to exchange authorization code")]
+    Exchange(#[cause] TokenRequestError<RE, TE>),
+    ///
+    /// The login was not completed within the maximum age.
+    ///
+    #[fail(display = "Login expired")]
+    Expired,
+    ///
+    /// The ID token failed verification.
+    ///
+    #[fail(display = "Failed to verify ID token")]
+    IdTokenVerification(#[cause] ClaimsVerificationError),
+    ///
+    /// The access token hash in the ID token does not match the access token.
+    ///
+    #[fail(display = "Invalid access token hash: {}", _0)]
+    InvalidAccessTokenHash(String),
+    ///
+    /// The authorization response does not contain an authorization code.
+    ///
+    #[fail(display = "Authorization response is missing an authorization code")]
+    MissingCode,
+    ///
+    /// The token response does not contain an ID token.
+    ///
+    #[fail(display = "Server did not return an ID token")]
+    MissingIdToken,
+    ///
+    /// Failed to read the pending login from the state store.
+    ///
+    #[fail(display = "Failed to read login state")]
+    StateStore(#[cause] SE),
+    ///
+    /// The `state` parameter is missing or does not match a pending login, which may indicate that
+    /// the redirect was forged or replayed.
+    ///
+    #[fail(display = "Unknown or previously used state parameter")]
+    UnknownState,
+}
+impl<RE, SE, TE> From<LoginFlowError<RE, TE>> for LoginManagerError<RE, SE, TE>
+where
+    RE: Fail,
+    SE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    fn from(err: LoginFlowError<RE, TE>) -> Self {
+        match err {
+            LoginFlowError::Authorization(err) => LoginManagerError::Authorization(err),
+            LoginFlowError::Exchange(err) => LoginManagerError::Exchange(err),
+            LoginFlowError::IdTokenVerification(err) => LoginManagerError::IdTokenVerification(err),
+            LoginFlowError::InvalidAccessTokenHash(err) => {
+                LoginManagerError::InvalidAccessTokenHash(err)
+            }
+            LoginFlowError::MissingCode => LoginManagerError::MissingCode,
+            LoginFlowError::MissingIdToken => LoginManagerError::MissingIdToken,
+        }
+    }
+}
+impl<RE, SE, TE> ClassifiedError for LoginManagerError<RE, SE, TE>
+where
+    RE: Fail,
+    SE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            // The authorization response is delivered via the user's browser, so retrying the
+            // token request can't resolve authorization errors.
+            LoginManagerError::Authorization(_) => ErrorKind::ClientError,
+            LoginManagerError::Exchange(err) => err.error_kind(),
+            LoginManagerError::IdTokenVerification(_)
+            | LoginManagerError::InvalidAccessTokenHash(_) => ErrorKind::Verification,
+            LoginManagerError::MissingCode | LoginManagerError::MissingIdToken => {
+                ErrorKind::InvalidResponse
+            }
+            LoginManagerError::Expired
+            | LoginManagerError::StateStore(_)
+            | LoginManagerError::UnknownState => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use chrono::{Duration, TimeZone, Utc};
+    use futures::executor::block_on;
+    use futures::future::ready;
+
+    use crate::core::{CoreClient, CoreLoginManager};
+    use crate::http_types::{Request, Response};
+    use crate::login_state::{InMemoryStateStore, StateStore};
+    use crate::{
+        AuthUrl, ClientId, ClientSecret, CsrfToken, FixedClock, IssuerUrl, JsonWebKeySet,
+        RedirectUrl, Scope, TokenUrl,
+    };
+
+    use super::LoginManagerError;
+
+    fn new_login_manager(clock: &FixedClock) -> CoreLoginManager {
+        CoreLoginManager::new(
+            CoreClient::new(
+                ClientId::new("aaa".to_string()),
+                Some(ClientSecret::new("bbb".to_string())),
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+                Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+                None,
+                JsonWebKeySet::default(),
+            )
+            .set_redirect_uri(RedirectUrl::new("https://client/cb".to_string()).unwrap()),
+        )
+        .set_clock(clock.clone())
+    }
+
+    fn query_param(url: &url::Url, name: &str) -> Option<String> {
+        url.query_pairs()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    #[test]
+    fn test_begin() {
+        let clock = FixedClock::new(Utc.timestamp(1544932149, 0));
+        let store = InMemoryStateStore::new();
+        let url = new_login_manager(&clock)
+            .begin(&store, |request| {
+                request.add_scope(Scope::new("email".to_string()))
+            })
+            .unwrap();
+
+        assert_eq!(Some("openid email".to_string()), query_param(&url, "scope"));
+        assert_eq!(
+            Some("S256".to_string()),
+            query_param(&url, "code_challenge_method")
+        );
+
+        let pending_login = store
+            .take(&CsrfToken::new(query_param(&url, "state").unwrap()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            query_param(&url, "nonce").unwrap(),
+            *pending_login.nonce().secret()
+        );
+        assert!(pending_login.pkce_verifier().is_some());
+        assert_eq!(Utc.timestamp(1544932149, 0), pending_login.created_at());
+    }
+
+    #[test]
+    fn test_complete_unknown_or_expired_state() {
+        let http_client = |_: Request| {
+            ready(Err::<Response, _>(io::Error::new(
+                io::ErrorKind::Other,
+                "unexpected request",
+            )))
+        };
+
+        let clock = FixedClock::new(Utc.timestamp(1544932149, 0));
+        let store = InMemoryStateStore::new();
+        let login_manager = new_login_manager(&clock);
+        let url = login_manager.begin(&store, |request| request).unwrap();
+        let redirect_url = url::Url::parse(&format!(
+            "https://client/cb?code=the_code&state={}",
+            query_param(&url, "state").unwrap()
+        ))
+        .unwrap();
+
+        match block_on(login_manager.complete(
+            &store,
+            &url::Url::parse("https://client/cb?code=the_code&state=other").unwrap(),
+            &http_client,
+        )) {
+            Err(LoginManagerError::UnknownState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        clock.advance(Duration::seconds(601));
+        match block_on(login_manager.complete(&store, &redirect_url, &http_client)) {
+            Err(LoginManagerError::Expired) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The pending login was consumed by the previous attempt, so the redirect can't be
+        // replayed.
+        match block_on(login_manager.complete(&store, &redirect_url, &http_client)) {
+            Err(LoginManagerError::UnknownState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
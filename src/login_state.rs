@@ -0,0 +1,553 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use rand::{thread_rng, Rng};
+
+use crate::crypto::constant_time_eq;
+use crate::types::helpers::serde_utc_seconds;
+use crate::{
+    CryptoBackend, CsrfToken, DefaultCryptoBackend, HashAlgorithm, Nonce, NonceVerifier,
+    PkceCodeVerifier,
+};
+
+///
+/// Authorization request state persisted by a [`StateStore`] between redirecting the user agent to
+/// the Authorization Endpoint and handling the redirect back to the client.
+///
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PendingLogin {
+    #[serde(with = "serde_utc_seconds")]
+    created_at: DateTime<Utc>,
+    nonce: Nonce,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pkce_verifier: Option<PkceCodeVerifier>,
+}
+impl PendingLogin {
+    ///
+    /// Instantiates login state for an authorization request created at the specified time.
+    ///
+    pub fn new(nonce: Nonce, created_at: DateTime<Utc>) -> Self {
+        Self {
+            created_at,
+            nonce,
+            pkce_verifier: None,
+        }
+    }
+
+    ///
+    /// Sets the PKCE code verifier to send when exchanging the authorization code.
+    ///
+    pub fn set_pkce_verifier(mut self, pkce_verifier: Option<PkceCodeVerifier>) -> Self {
+        self.pkce_verifier = pkce_verifier;
+        self
+    }
+
+    ///
+    /// Returns the time at which the authorization request was created.
+    ///
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    ///
+    /// Returns the nonce expected in the ID token.
+    ///
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    ///
+    /// Returns the PKCE code verifier, if any.
+    ///
+    pub fn pkce_verifier(&self) -> Option<&PkceCodeVerifier> {
+        self.pkce_verifier.as_ref()
+    }
+
+    ///
+    /// Consumes the login state and returns the PKCE code verifier, if any.
+    ///
+    pub fn into_pkce_verifier(self) -> Option<PkceCodeVerifier> {
+        self.pkce_verifier
+    }
+}
+
+///
+/// Storage for pending authorization requests, keyed by the `state` parameter (CSRF token).
+///
+/// Each entry may be consumed only once via [`StateStore::take`], which prevents a redirect to the
+/// client from being replayed. Implementations are provided for in-memory
+/// ([`InMemoryStateStore`]) and cookie-based ([`CookieStateStore`]) storage.
+///
+pub trait StateStore {
+    ///
+    /// Error returned when the login state could not be read or written.
+    ///
+    type Error: Fail;
+
+    ///
+    /// Stores the login state for the authorization request with the specified `state`.
+    ///
+    fn save(&self, state: &CsrfToken, login: PendingLogin) -> Result<(), Self::Error>;
+
+    ///
+    /// Removes and returns the login state for the specified `state`, if any.
+    ///
+    /// Subsequent calls with the same `state` must return `Ok(None)`.
+    ///
+    fn take(&self, state: &CsrfToken) -> Result<Option<PendingLogin>, Self::Error>;
+}
+
+///
+/// Storage for nonces issued in authorization requests.
+///
+/// This is useful for flows in which the login state isn't otherwise persisted via a
+/// [`StateStore`] (e.g., the Implicit Flow). Each nonce may be consumed only once via
+/// [`NonceStore::consume`], which prevents ID tokens from being replayed. The
+/// [`ConsumingNonceVerifier`] verifies ID token nonces against a `NonceStore`.
+///
+pub trait NonceStore {
+    ///
+    /// Error returned when the nonce could not be read or written.
+    ///
+    type Error: Fail;
+
+    ///
+    /// Stores a nonce issued in an authorization request.
+    ///
+    fn save(&self, nonce: &Nonce) -> Result<(), Self::Error>;
+
+    ///
+    /// Removes the specified nonce, returning whether it was present.
+    ///
+    /// Subsequent calls with the same nonce must return `Ok(false)`.
+    ///
+    fn consume(&self, nonce: &Nonce) -> Result<bool, Self::Error>;
+}
+
+///
+/// [`NonceVerifier`] that accepts ID tokens whose nonce is present in a [`NonceStore`], consuming
+/// the nonce so that it can't be reused.
+///
+#[derive(Debug)]
+pub struct ConsumingNonceVerifier<'a, S>
+where
+    S: NonceStore,
+{
+    store: &'a S,
+}
+impl<'a, S> ConsumingNonceVerifier<'a, S>
+where
+    S: NonceStore,
+{
+    ///
+    /// Instantiates a nonce verifier backed by the specified store.
+    ///
+    pub fn new(store: &'a S) -> Self {
+        Self { store }
+    }
+}
+impl<'a, S> NonceVerifier for ConsumingNonceVerifier<'a, S>
+where
+    S: NonceStore,
+{
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
+        let nonce = nonce.ok_or_else(|| "missing nonce claim".to_string())?;
+        match self.store.consume(nonce) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("unknown or previously used nonce".to_string()),
+            Err(err) => Err(format!("failed to consume nonce: {}", err)),
+        }
+    }
+}
+
+///
+/// Error returned by the built-in [`StateStore`], [`NonceStore`], and
+/// [`TokenIdCache`](crate::TokenIdCache) implementations.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum StateStoreError {
+    ///
+    /// The cookie is malformed or could not be decrypted.
+    ///
+    #[fail(display = "Invalid login state cookie")]
+    InvalidCookie,
+    ///
+    /// Failed to serialize or deserialize login state.
+    ///
+    #[fail(display = "Failed to serialize login state")]
+    Serialize(#[cause] serde_json::Error),
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// [`StateStore`] that holds login state in memory.
+///
+/// Login state is lost when the process exits, and is not shared between processes. Entries for
+/// authorization requests that are never completed remain until [`InMemoryStateStore::purge`] is
+/// called.
+///
+#[derive(Default)]
+pub struct InMemoryStateStore {
+    logins: Mutex<HashMap<String, PendingLogin>>,
+}
+impl InMemoryStateStore {
+    ///
+    /// Instantiates an empty state store.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Removes login state for authorization requests created before the specified time.
+    ///
+    pub fn purge(&self, created_before: DateTime<Utc>) -> Result<(), StateStoreError> {
+        self.logins
+            .lock()
+            .map_err(|_| StateStoreError::Other("state store lock poisoned".to_string()))?
+            .retain(|_, login| login.created_at() >= created_before);
+        Ok(())
+    }
+}
+impl Debug for InMemoryStateStore {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // The keys are the `state` parameters of pending logins, which must remain secret.
+        let logins = self.logins.lock().unwrap_or_else(|err| err.into_inner());
+        f.debug_struct("InMemoryStateStore")
+            .field("logins", &logins.len())
+            .finish()
+    }
+}
+impl StateStore for InMemoryStateStore {
+    type Error = StateStoreError;
+
+    fn save(&self, state: &CsrfToken, login: PendingLogin) -> Result<(), Self::Error> {
+        self.logins
+            .lock()
+            .map_err(|_| StateStoreError::Other("state store lock poisoned".to_string()))?
+            .insert(state.secret().clone(), login);
+        Ok(())
+    }
+
+    fn take(&self, state: &CsrfToken) -> Result<Option<PendingLogin>, Self::Error> {
+        Ok(self
+            .logins
+            .lock()
+            .map_err(|_| StateStoreError::Other("state store lock poisoned".to_string()))?
+            .remove(state.secret()))
+    }
+}
+
+///
+/// [`NonceStore`] that holds nonces in memory.
+///
+/// Nonces are lost when the process exits, and are not shared between processes.
+///
+#[derive(Default)]
+pub struct InMemoryNonceStore {
+    nonces: Mutex<HashSet<String>>,
+}
+impl InMemoryNonceStore {
+    ///
+    /// Instantiates an empty nonce store.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl Debug for InMemoryNonceStore {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        let nonces = self.nonces.lock().unwrap_or_else(|err| err.into_inner());
+        f.debug_struct("InMemoryNonceStore")
+            .field("nonces", &nonces.len())
+            .finish()
+    }
+}
+impl NonceStore for InMemoryNonceStore {
+    type Error = StateStoreError;
+
+    fn save(&self, nonce: &Nonce) -> Result<(), Self::Error> {
+        self.nonces
+            .lock()
+            .map_err(|_| StateStoreError::Other("nonce store lock poisoned".to_string()))?
+            .insert(nonce.secret().clone());
+        Ok(())
+    }
+
+    fn consume(&self, nonce: &Nonce) -> Result<bool, Self::Error> {
+        Ok(self
+            .nonces
+            .lock()
+            .map_err(|_| StateStoreError::Other("nonce store lock poisoned".to_string()))?
+            .remove(nonce.secret()))
+    }
+}
+
+// Context in which the cookie encryption key is derived from the key provided by the application,
+// which may be of any length.
+const COOKIE_KEY_CONTEXT: &[u8] = b"openidconnect login state cookie";
+// Length of the random AES-GCM nonce that precedes the ciphertext in each cookie.
+const COOKIE_NONCE_LEN: usize = 12;
+
+#[derive(Deserialize, Serialize)]
+struct CookiePayload {
+    state: CsrfToken,
+    login: PendingLogin,
+}
+
+///
+/// [`StateStore`] that holds login state in an encrypted cookie on the user agent, which avoids
+/// server-side storage.
+///
+/// A `CookieStateStore` is instantiated for each HTTP request. The value of the incoming cookie
+/// (if any) is provided via [`CookieStateStore::set_cookie`], and after calling
+/// [`StateStore::save`] or [`StateStore::take`], the value returned by
+/// [`CookieStateStore::cookie`] should be sent to the user agent in a `Set-Cookie` header, or the
+/// cookie should be deleted if it returns `None`.
+///
+/// The cookie is encrypted using AES-256-GCM, so the user agent (or anyone who captures the
+/// cookie) can neither read the PKCE code verifier and nonce nor tamper with them. The cookie
+/// holds a single pending login, so starting a new login replaces any previous one.
+///
+/// # Security Warning
+///
+/// The key should consist of at least 32 random bytes and must be kept secret. The cookie
+/// should be marked `HttpOnly` and `Secure`, with `SameSite=Lax` (or `SameSite=None` when using the
+/// Form Post Response Mode, which sends a cross-site `POST` request to the redirect URI).
+///
+/// Since the login state is stored by the user agent, a captured cookie could be replayed
+/// alongside the corresponding redirect. Applications should reject login state older than a
+/// short maximum age (see [`LoginManager::set_max_age`](crate::LoginManager::set_max_age)).
+///
+pub struct CookieStateStore {
+    cookie: Mutex<Option<String>>,
+    key: Vec<u8>,
+}
+impl CookieStateStore {
+    ///
+    /// Instantiates a cookie state store that encrypts cookies using a key derived from the
+    /// specified key.
+    ///
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            cookie: Mutex::new(None),
+            key: DefaultCryptoBackend::hmac_sign(HashAlgorithm::Sha256, key, COOKIE_KEY_CONTEXT),
+        }
+    }
+
+    ///
+    /// Sets the value of the cookie sent by the user agent, if any.
+    ///
+    pub fn set_cookie(self, cookie: Option<String>) -> Self {
+        *self.lock() = cookie;
+        self
+    }
+
+    ///
+    /// Returns the value of the cookie to send to the user agent, or `None` if the cookie should be
+    /// deleted.
+    ///
+    pub fn cookie(&self) -> Option<String> {
+        self.lock().clone()
+    }
+
+    // The guarded value is a plain string, so a poisoned lock is safe to reuse.
+    fn lock(&self) -> std::sync::MutexGuard<Option<String>> {
+        self.cookie.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn encode(&self, payload: &CookiePayload) -> Result<String, StateStoreError> {
+        let serialized = serde_json::to_vec(payload).map_err(StateStoreError::Serialize)?;
+        let mut cookie = thread_rng().gen::<[u8; COOKIE_NONCE_LEN]>().to_vec();
+        let ciphertext = DefaultCryptoBackend::aes_gcm_seal(&self.key, &cookie, &serialized)
+            .map_err(StateStoreError::Other)?;
+        cookie.extend(ciphertext);
+        Ok(base64::encode_config(&cookie, base64::URL_SAFE_NO_PAD))
+    }
+
+    fn decode(&self, cookie: &str) -> Result<CookiePayload, StateStoreError> {
+        let cookie = base64::decode_config(cookie, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| StateStoreError::InvalidCookie)?;
+        if cookie.len() < COOKIE_NONCE_LEN {
+            return Err(StateStoreError::InvalidCookie);
+        }
+        let (nonce, ciphertext) = cookie.split_at(COOKIE_NONCE_LEN);
+        let serialized = DefaultCryptoBackend::aes_gcm_open(&self.key, nonce, ciphertext)
+            .map_err(|_| StateStoreError::InvalidCookie)?;
+        serde_json::from_slice(&serialized).map_err(StateStoreError::Serialize)
+    }
+}
+// Omits the encryption key.
+impl Debug for CookieStateStore {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("CookieStateStore")
+            .field("cookie", &self.cookie)
+            .finish()
+    }
+}
+impl StateStore for CookieStateStore {
+    type Error = StateStoreError;
+
+    fn save(&self, state: &CsrfToken, login: PendingLogin) -> Result<(), Self::Error> {
+        let cookie = self.encode(&CookiePayload {
+            state: state.clone(),
+            login,
+        })?;
+        *self.lock() = Some(cookie);
+        Ok(())
+    }
+
+    fn take(&self, state: &CsrfToken) -> Result<Option<PendingLogin>, Self::Error> {
+        let mut cookie = self.lock();
+        let payload = match *cookie {
+            Some(ref value) => self.decode(value)?,
+            None => return Ok(None),
+        };
+        if !constant_time_eq(payload.state.secret().as_bytes(), state.secret().as_bytes()) {
+            return Ok(None);
+        }
+        *cookie = None;
+        Ok(Some(payload.login))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{CsrfToken, Nonce, NonceVerifier, PkceCodeVerifier};
+
+    use super::{
+        ConsumingNonceVerifier, CookieStateStore, InMemoryNonceStore, InMemoryStateStore,
+        NonceStore, PendingLogin, StateStore, StateStoreError,
+    };
+
+    fn new_login() -> PendingLogin {
+        PendingLogin::new(
+            Nonce::new("the_nonce".to_string()),
+            Utc.timestamp(1544932149, 0),
+        )
+        .set_pkce_verifier(Some(PkceCodeVerifier::new("the_verifier".to_string())))
+    }
+
+    fn assert_take_once<S>(store: &S)
+    where
+        S: StateStore,
+    {
+        let state = CsrfToken::new("the_state".to_string());
+        let login = store.take(&state).unwrap().unwrap();
+        assert_eq!("the_nonce", login.nonce().secret());
+        assert_eq!("the_verifier", login.pkce_verifier().unwrap().secret());
+        assert_eq!(Utc.timestamp(1544932149, 0), login.created_at());
+        // Login state may only be consumed once.
+        assert!(store.take(&state).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_in_memory_state_store() {
+        let store = InMemoryStateStore::new();
+        let state = CsrfToken::new("the_state".to_string());
+        assert!(store.take(&state).unwrap().is_none());
+
+        store.save(&state, new_login()).unwrap();
+        assert!(!format!("{:?}", store).contains("the_state"));
+        assert!(store
+            .take(&CsrfToken::new("other_state".to_string()))
+            .unwrap()
+            .is_none());
+        assert_take_once(&store);
+
+        store.save(&state, new_login()).unwrap();
+        store.purge(Utc.timestamp(1544932150, 0)).unwrap();
+        assert!(store.take(&state).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cookie_state_store() {
+        let state = CsrfToken::new("the_state".to_string());
+        let store = CookieStateStore::new(b"secret_key_secret_key_secret_key");
+        store.save(&state, new_login()).unwrap();
+        let cookie = store.cookie().unwrap();
+
+        // A different request presenting the cookie consumes it.
+        let store = CookieStateStore::new(b"secret_key_secret_key_secret_key")
+            .set_cookie(Some(cookie.clone()));
+        assert!(store
+            .take(&CsrfToken::new("other_state".to_string()))
+            .unwrap()
+            .is_none());
+        assert_eq!(Some(&cookie), store.cookie().as_ref());
+        assert_take_once(&store);
+        assert!(store.cookie().is_none());
+
+        // The cookie's content is encrypted, and each cookie uses a different nonce.
+        let decoded = base64::decode_config(&cookie, base64::URL_SAFE_NO_PAD).unwrap();
+        for secret in &["the_state", "the_nonce", "the_verifier"] {
+            assert!(!String::from_utf8_lossy(&decoded).contains(secret));
+        }
+        let other_store = CookieStateStore::new(b"secret_key_secret_key_secret_key");
+        other_store.save(&state, new_login()).unwrap();
+        assert_ne!(Some(&cookie), other_store.cookie().as_ref());
+
+        // Tampered cookies are rejected.
+        let mut tampered = decoded.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        let store = CookieStateStore::new(b"secret_key_secret_key_secret_key").set_cookie(Some(
+            base64::encode_config(&tampered, base64::URL_SAFE_NO_PAD),
+        ));
+        match store.take(&state) {
+            Err(StateStoreError::InvalidCookie) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Cookies encrypted with a different key are rejected.
+        let store =
+            CookieStateStore::new(b"other_key_other_key_other_key").set_cookie(Some(cookie));
+        match store.take(&state) {
+            Err(StateStoreError::InvalidCookie) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let store = CookieStateStore::new(b"secret_key_secret_key_secret_key")
+            .set_cookie(Some("not_a_cookie".to_string()));
+        match store.take(&state) {
+            Err(StateStoreError::InvalidCookie) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_consuming_nonce_verifier() {
+        let store = InMemoryNonceStore::new();
+        let nonce = Nonce::new("the_nonce".to_string());
+        store.save(&nonce).unwrap();
+        assert!(!format!("{:?}", store).contains("the_nonce"));
+
+        assert_eq!(
+            Err("missing nonce claim".to_string()),
+            ConsumingNonceVerifier::new(&store).verify(None)
+        );
+        assert_eq!(
+            Err("unknown or previously used nonce".to_string()),
+            ConsumingNonceVerifier::new(&store)
+                .verify(Some(&Nonce::new("other_nonce".to_string())))
+        );
+        assert_eq!(
+            Ok(()),
+            ConsumingNonceVerifier::new(&store).verify(Some(&nonce))
+        );
+        // The nonce may only be consumed once.
+        assert_eq!(
+            Err("unknown or previously used nonce".to_string()),
+            ConsumingNonceVerifier::new(&store).verify(Some(&nonce))
+        );
+    }
+}
@@ -0,0 +1,701 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+
+#[cfg(feature = "net")]
+use crate::http_types::StatusCode;
+use crate::jwt::{
+    JsonWebToken, JsonWebTokenAccess, JsonWebTokenError, JsonWebTokenHeader,
+    JsonWebTokenJsonPayloadSerde, JsonWebTokenType,
+};
+use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds};
+use crate::url::form_urlencoded;
+use crate::verification::{AudiencesClaim, IssuerClaim};
+use crate::{
+    Audience, ClaimsVerificationError, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LogoutTokenVerifier, PrivateSigningKey,
+    SecurityEventType, SessionId, StateStoreError, SubjectIdentifier,
+};
+
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+///
+/// Back-channel logout token as defined in
+/// [OpenID Connect Back-Channel Logout](
+/// https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken).
+///
+/// Providers send logout tokens to each relying party's registered `backchannel_logout_uri` (as
+/// the `logout_token` form parameter) when a user's session ends. Logout tokens are signed with
+/// the `logout+jwt` type (`typ`) header, which prevents them from being accepted as ID tokens.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LogoutToken<
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(JsonWebToken<JE, JS, JT, LogoutTokenClaims, JsonWebTokenJsonPayloadSerde>);
+impl<JE, JS, JT> LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes a new logout token containing the specified claims, signed with the specified
+    /// key and signing algorithm.
+    ///
+    /// Returns [`JsonWebTokenError::InvalidClaims`] if the claims contain neither a subject (`sub`)
+    /// nor a session ID (`sid`), at least one of which is required.
+    ///
+    pub fn new<JU, K, S>(
+        claims: LogoutTokenClaims,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        if claims.subject.is_none() && claims.session_id.is_none() {
+            return Err(JsonWebTokenError::InvalidClaims(
+                "logout token must contain a `sub` or `sid` claim".to_string(),
+            ));
+        }
+
+        Ok(Self(JsonWebToken::new_with_type(
+            claims,
+            signing_key,
+            &alg,
+            Some(JsonWebTokenType::logout_token()),
+        )?))
+    }
+
+    ///
+    /// Returns the JOSE header of the logout token without verifying the token's signature.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned header is untrusted and may have been forged or tampered with.
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+
+    ///
+    /// Returns the claims of the logout token without verifying the token's signature.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned claims are untrusted and may have been forged or tampered with.
+    ///
+    pub fn unverified_claims(&self) -> &LogoutTokenClaims {
+        self.0.unverified_payload_ref()
+    }
+
+    ///
+    /// Verifies and returns the logout token claims.
+    ///
+    /// The `jti` claim isn't checked for replays; see [`process_backchannel_logout`].
+    ///
+    pub fn claims<JU, K>(
+        self,
+        verifier: &LogoutTokenVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<LogoutTokenClaims, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_claims(self.0)
+    }
+}
+impl<JE, JS, JT> FromStr for LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+
+    ///
+    /// Parses a logout token in the JWS Compact Serialization without verifying it.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonWebToken::from_compact(s).map(Self)
+    }
+}
+
+///
+/// Claims of a [`LogoutToken`].
+///
+/// The `events` claim always contains the back-channel logout event. Logout tokens must not
+/// contain a `nonce` claim, so none can be set.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LogoutTokenClaims {
+    #[serde(rename = "iss")]
+    issuer: IssuerUrl,
+    #[serde(rename = "aud", deserialize_with = "deserialize_string_or_vec")]
+    audiences: Vec<Audience>,
+    #[serde(rename = "iat", with = "serde_utc_seconds")]
+    issue_time: DateTime<Utc>,
+    #[serde(rename = "exp", with = "serde_utc_seconds")]
+    expiration: DateTime<Utc>,
+    #[serde(rename = "jti")]
+    token_id: String,
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(rename = "sid", skip_serializing_if = "Option::is_none")]
+    session_id: Option<SessionId>,
+    events: HashMap<SecurityEventType, serde_json::Value>,
+    // Only deserialized so that the verifier can reject logout tokens containing a nonce.
+    #[serde(default, skip_serializing)]
+    nonce: Option<serde_json::Value>,
+}
+impl LogoutTokenClaims {
+    ///
+    /// Initializes new logout token claims containing the back-channel logout event.
+    ///
+    /// Either the subject or the session ID must be set (via
+    /// [`set_subject`](Self::set_subject) or [`set_session_id`](Self::set_session_id)) before
+    /// signing the logout token. The token ID (`jti`) should be unique so that relying parties
+    /// can detect replayed logout tokens.
+    ///
+    pub fn new(
+        issuer: IssuerUrl,
+        audiences: Vec<Audience>,
+        issue_time: DateTime<Utc>,
+        expiration: DateTime<Utc>,
+        token_id: String,
+    ) -> Self {
+        Self {
+            issuer,
+            audiences,
+            issue_time,
+            expiration,
+            token_id,
+            subject: None,
+            session_id: None,
+            events: vec![(
+                SecurityEventType::new(BACKCHANNEL_LOGOUT_EVENT.to_string()),
+                serde_json::json!({}),
+            )]
+            .into_iter()
+            .collect(),
+            nonce: None,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[IssuerUrl] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_issue_time -> issue_time[DateTime<Utc>] ["iat"],
+            set_expiration -> expiration[DateTime<Utc>] ["exp"],
+            set_token_id -> token_id[String] ["jti"],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_session_id -> session_id[Option<SessionId>] ["sid"],
+        }
+    ];
+
+    ///
+    /// Returns the `events` claim.
+    ///
+    pub fn events(&self) -> &HashMap<SecurityEventType, serde_json::Value> {
+        &self.events
+    }
+
+    ///
+    /// Returns whether the `events` claim contains the back-channel logout event.
+    ///
+    pub fn is_backchannel_logout(&self) -> bool {
+        self.events.iter().any(|(event_type, payload)| {
+            event_type.as_str() == BACKCHANNEL_LOGOUT_EVENT && payload.is_object()
+        })
+    }
+
+    pub(crate) fn has_nonce(&self) -> bool {
+        self.nonce.is_some()
+    }
+}
+impl AudiencesClaim for LogoutTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(LogoutTokenClaims::audiences(self))
+    }
+}
+impl<'a> AudiencesClaim for &'a LogoutTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(LogoutTokenClaims::audiences(self))
+    }
+}
+impl IssuerClaim for LogoutTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(LogoutTokenClaims::issuer(self))
+    }
+}
+impl<'a> IssuerClaim for &'a LogoutTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(LogoutTokenClaims::issuer(self))
+    }
+}
+
+///
+/// Cache of the token IDs (`jti` claims) of previously accepted logout tokens, which prevents
+/// logout tokens from being replayed.
+///
+pub trait TokenIdCache {
+    ///
+    /// Error returned when the cache could not be read or written.
+    ///
+    type Error: Fail;
+
+    ///
+    /// Records the specified token ID from the specified issuer, returning whether it was not
+    /// already present.
+    ///
+    /// The entry only needs to be retained until the token's `expiration`, after which the token
+    /// is rejected as expired.
+    ///
+    fn insert(
+        &self,
+        issuer: &IssuerUrl,
+        token_id: &str,
+        expiration: DateTime<Utc>,
+    ) -> Result<bool, Self::Error>;
+}
+
+///
+/// [`TokenIdCache`] that holds token IDs in memory.
+///
+/// Token IDs are lost when the process exits, and are not shared between processes. Entries for
+/// expired tokens remain until [`InMemoryTokenIdCache::purge`] is called.
+///
+#[derive(Debug, Default)]
+pub struct InMemoryTokenIdCache {
+    token_ids: Mutex<HashMap<(IssuerUrl, String), DateTime<Utc>>>,
+}
+impl InMemoryTokenIdCache {
+    ///
+    /// Instantiates an empty cache.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Removes the token IDs of tokens that expired before the specified time.
+    ///
+    pub fn purge(&self, expired_before: DateTime<Utc>) -> Result<(), StateStoreError> {
+        self.token_ids
+            .lock()
+            .map_err(|_| StateStoreError::Other("token ID cache lock poisoned".to_string()))?
+            .retain(|_, expiration| *expiration >= expired_before);
+        Ok(())
+    }
+}
+impl TokenIdCache for InMemoryTokenIdCache {
+    type Error = StateStoreError;
+
+    fn insert(
+        &self,
+        issuer: &IssuerUrl,
+        token_id: &str,
+        expiration: DateTime<Utc>,
+    ) -> Result<bool, Self::Error> {
+        let mut token_ids = self
+            .token_ids
+            .lock()
+            .map_err(|_| StateStoreError::Other("token ID cache lock poisoned".to_string()))?;
+        let key = (issuer.clone(), token_id.to_string());
+        if token_ids.contains_key(&key) {
+            Ok(false)
+        } else {
+            token_ids.insert(key, expiration);
+            Ok(true)
+        }
+    }
+}
+
+///
+/// Logout token accepted by [`process_backchannel_logout`].
+///
+/// The relying party should terminate the sessions identified by [`BackchannelLogout::session_id`]
+/// (if present) or else all sessions of [`BackchannelLogout::subject`], and then respond with
+/// [`BackchannelLogout::status`] (`200 OK`). The response should include a
+/// `Cache-Control: no-store` header.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackchannelLogout {
+    claims: LogoutTokenClaims,
+}
+impl BackchannelLogout {
+    ///
+    /// Returns the subject whose sessions should be terminated, if specified.
+    ///
+    pub fn subject(&self) -> Option<&SubjectIdentifier> {
+        self.claims.subject()
+    }
+
+    ///
+    /// Returns the session ID (`sid`) of the session that should be terminated, if specified.
+    ///
+    pub fn session_id(&self) -> Option<&SessionId> {
+        self.claims.session_id()
+    }
+
+    ///
+    /// Returns the verified logout token claims.
+    ///
+    pub fn claims(&self) -> &LogoutTokenClaims {
+        &self.claims
+    }
+
+    ///
+    /// Returns the HTTP status code with which to respond to the provider (`200 OK`). Requires
+    /// "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn status(&self) -> StatusCode {
+        StatusCode::Ok
+    }
+}
+
+///
+/// Error processing a back-channel logout request.
+///
+/// The relying party must respond to the provider with [`BackchannelLogoutError::status`]
+/// (`400 Bad Request`), and may include the JSON body returned by
+/// [`BackchannelLogoutError::to_json`]. The response should include a `Cache-Control: no-store`
+/// header.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum BackchannelLogoutError<SE>
+where
+    SE: Fail,
+{
+    ///
+    /// The request body doesn't contain a `logout_token` parameter.
+    ///
+    #[fail(display = "Missing logout_token parameter")]
+    MissingLogoutToken,
+    ///
+    /// The logout token is malformed.
+    ///
+    #[fail(display = "Failed to parse logout token")]
+    Parse(#[cause] serde_json::Error),
+    ///
+    /// The logout token is invalid.
+    ///
+    #[fail(display = "Failed to verify logout token")]
+    Verification(#[cause] ClaimsVerificationError),
+    ///
+    /// A logout token with the same issuer and token ID was previously accepted.
+    ///
+    #[fail(display = "Logout token was replayed")]
+    Replayed,
+    ///
+    /// Failed to record the logout token's ID in the [`TokenIdCache`].
+    ///
+    #[fail(display = "Failed to record logout token ID")]
+    Cache(#[cause] SE),
+}
+impl<SE> BackchannelLogoutError<SE>
+where
+    SE: Fail,
+{
+    ///
+    /// Returns the HTTP status code with which to respond to the provider (`400 Bad Request`).
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn status(&self) -> StatusCode {
+        StatusCode::BadRequest
+    }
+
+    ///
+    /// Returns a JSON response body containing the `error` and `error_description` attributes.
+    ///
+    /// Cache errors aren't described, since they're internal to the relying party.
+    ///
+    pub fn to_json(&self) -> String {
+        let error_description = match *self {
+            BackchannelLogoutError::Verification(ref err) => format!("{}: {}", self, err),
+            BackchannelLogoutError::Cache(_) => "Failed to process logout token".to_string(),
+            _ => self.to_string(),
+        };
+        serde_json::json!({
+            "error": "invalid_request",
+            "error_description": error_description,
+        })
+        .to_string()
+    }
+}
+impl<SE> From<ClaimsVerificationError> for BackchannelLogoutError<SE>
+where
+    SE: Fail,
+{
+    fn from(err: ClaimsVerificationError) -> Self {
+        BackchannelLogoutError::Verification(err)
+    }
+}
+
+///
+/// Processes a request to the relying party's back-channel logout endpoint.
+///
+/// Parses the `logout_token` parameter from the `application/x-www-form-urlencoded` request
+/// `body`, verifies the logout token using `verifier`, and records its token ID (`jti`) in
+/// `jti_cache` to reject replayed logout tokens. On success, returns the subject and/or session ID
+/// whose sessions should be terminated.
+///
+/// Both the result and the error describe the HTTP response to return to the provider (see
+/// [`BackchannelLogout::status`] and [`BackchannelLogoutError::status`]).
+///
+pub fn process_backchannel_logout<JE, JS, JT, JU, K, C>(
+    body: &[u8],
+    verifier: &LogoutTokenVerifier<JE, JS, JT, JU, K>,
+    jti_cache: &C,
+) -> Result<BackchannelLogout, BackchannelLogoutError<C::Error>>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    C: TokenIdCache,
+{
+    let logout_token = form_urlencoded::parse(body)
+        .find(|(name, _)| name == "logout_token")
+        .map(|(_, value)| value.into_owned())
+        .ok_or(BackchannelLogoutError::MissingLogoutToken)?;
+    let claims = logout_token
+        .parse::<LogoutToken<JE, JS, JT>>()
+        .map_err(BackchannelLogoutError::Parse)?
+        .claims(verifier)?;
+
+    if !jti_cache
+        .insert(claims.issuer(), claims.token_id(), claims.expiration())
+        .map_err(BackchannelLogoutError::Cache)?
+    {
+        return Err(BackchannelLogoutError::Replayed);
+    }
+
+    Ok(BackchannelLogout { claims })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::{
+        CoreJsonWebKeySet, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm, CoreLogoutToken, CoreLogoutTokenVerifier,
+        CoreRsaPrivateSigningKey,
+    };
+    #[cfg(feature = "net")]
+    use crate::http_types::StatusCode;
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
+    use crate::{
+        process_backchannel_logout, Audience, BackchannelLogoutError, ClaimsVerificationError,
+        ClientId, FixedClock, InMemoryTokenIdCache, IssuerUrl, JsonWebTokenError, JsonWebTokenType,
+        LogoutTokenClaims, PrivateSigningKey, SessionId, SubjectIdentifier,
+    };
+
+    #[test]
+    fn test_logout_token() {
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let claims = LogoutTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1471566154, 0),
+            Utc.timestamp(1471566274, 0),
+            "bWJq".to_string(),
+        );
+        assert!(claims.is_backchannel_logout());
+
+        // Either `sub` or `sid` is required.
+        match CoreLogoutToken::new(
+            claims.clone(),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ) {
+            Err(JsonWebTokenError::InvalidClaims(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let claims = claims
+            .set_subject(Some(SubjectIdentifier::new("248289761001".to_string())))
+            .set_session_id(Some(SessionId::new(
+                "08a5019c-17e1-4977-8f42-65a12843ea02".to_string(),
+            )));
+        assert_eq!(
+            serde_json::to_value(&claims).unwrap(),
+            serde_json::json!({
+                "iss": "https://server.example.com",
+                "aud": ["s6BhdRkqt3"],
+                "iat": 1471566154,
+                "exp": 1471566274,
+                "jti": "bWJq",
+                "sub": "248289761001",
+                "sid": "08a5019c-17e1-4977-8f42-65a12843ea02",
+                "events": {
+                    "http://schemas.openid.net/event/backchannel-logout": {}
+                }
+            })
+        );
+
+        // Round trip through a signed logout token.
+        let serialized = serde_json::to_value(
+            CoreLogoutToken::new(
+                claims.clone(),
+                &signing_key,
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let logout_token: CoreLogoutToken = serialized.as_str().unwrap().parse().unwrap();
+        assert_eq!(
+            logout_token.unverified_header().typ,
+            Some(JsonWebTokenType::logout_token())
+        );
+        assert_eq!(*logout_token.unverified_claims(), claims);
+    }
+    #[test]
+    fn test_process_backchannel_logout() {
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let issuer = IssuerUrl::new("https://server.example.com".to_string()).unwrap();
+        let claims = LogoutTokenClaims::new(
+            issuer.clone(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1471566154, 0),
+            Utc.timestamp(1471566274, 0),
+            "bWJq".to_string(),
+        )
+        .set_subject(Some(SubjectIdentifier::new("248289761001".to_string())));
+        let sign = |claims: &LogoutTokenClaims| {
+            let logout_token = CoreLogoutToken::new(
+                claims.clone(),
+                &signing_key,
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap();
+            format!(
+                "logout_token={}",
+                serde_json::to_value(&logout_token)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+            )
+        };
+
+        let clock = FixedClock::new(Utc.timestamp(1471566200, 0));
+        let verifier = CoreLogoutTokenVerifier::new(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+        )
+        .set_clock(clock.clone());
+        let cache = InMemoryTokenIdCache::new();
+
+        let logout = process_backchannel_logout(sign(&claims).as_bytes(), &verifier, &cache)
+            .expect("logout token should be accepted");
+        #[cfg(feature = "net")]
+        assert_eq!(logout.status(), StatusCode::Ok);
+        assert_eq!(
+            logout.subject(),
+            Some(&SubjectIdentifier::new("248289761001".to_string()))
+        );
+        assert_eq!(logout.session_id(), None);
+        assert_eq!(*logout.claims(), claims);
+
+        // Each logout token may only be used once.
+        match process_backchannel_logout(sign(&claims).as_bytes(), &verifier, &cache) {
+            Err(err @ BackchannelLogoutError::Replayed) => {
+                #[cfg(feature = "net")]
+                assert_eq!(err.status(), StatusCode::BadRequest);
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(&err.to_json()).unwrap()["error"],
+                    "invalid_request"
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        cache.purge(Utc.timestamp(1471566275, 0)).unwrap();
+        process_backchannel_logout(sign(&claims).as_bytes(), &verifier, &cache).unwrap();
+
+        match process_backchannel_logout(b"state=foo", &verifier, &cache) {
+            Err(BackchannelLogoutError::MissingLogoutToken) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match process_backchannel_logout(b"logout_token=foo", &verifier, &cache) {
+            Err(BackchannelLogoutError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Sessions must be identified if the client requires them.
+        let session_verifier = verifier.clone().require_session_id(true);
+        let claims = claims.set_token_id("bWJr".to_string());
+        match process_backchannel_logout(sign(&claims).as_bytes(), &session_verifier, &cache) {
+            Err(BackchannelLogoutError::Verification(ClaimsVerificationError::InvalidSubject(
+                _,
+            ))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let claims = claims.set_session_id(Some(SessionId::new(
+            "08a5019c-17e1-4977-8f42-65a12843ea02".to_string(),
+        )));
+        process_backchannel_logout(sign(&claims).as_bytes(), &session_verifier, &cache).unwrap();
+
+        // Expired logout tokens are rejected.
+        clock.set(Utc.timestamp(1471566274, 0));
+        let claims = claims.set_token_id("bWJs".to_string());
+        match process_backchannel_logout(sign(&claims).as_bytes(), &verifier, &cache) {
+            Err(BackchannelLogoutError::Verification(ClaimsVerificationError::Expired(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Logout tokens containing a nonce or lacking the logout event (e.g., ID tokens) are
+        // rejected.
+        clock.set(Utc.timestamp(1471566200, 0));
+        let mut payload = serde_json::to_value(&claims).unwrap();
+        payload["nonce"] = "n-0S6_WzA2Mj".into();
+        let sign_payload = |payload: serde_json::Value| {
+            let jwt = JsonWebToken::<
+                CoreJweContentEncryptionAlgorithm,
+                CoreJwsSigningAlgorithm,
+                CoreJsonWebKeyType,
+                serde_json::Value,
+                JsonWebTokenJsonPayloadSerde,
+            >::new(
+                payload,
+                &signing_key,
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap();
+            format!(
+                "logout_token={}",
+                serde_json::to_value(&jwt).unwrap().as_str().unwrap()
+            )
+        };
+        match process_backchannel_logout(
+            sign_payload(payload.clone()).as_bytes(),
+            &verifier,
+            &cache,
+        ) {
+            Err(BackchannelLogoutError::Verification(ClaimsVerificationError::InvalidNonce(_))) => {
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        payload.as_object_mut().unwrap().remove("nonce");
+        payload["events"] = serde_json::json!({});
+        match process_backchannel_logout(sign_payload(payload).as_bytes(), &verifier, &cache) {
+            Err(BackchannelLogoutError::Verification(ClaimsVerificationError::Other(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
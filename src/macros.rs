@@ -248,6 +248,14 @@ macro_rules! new_secret_type {
                 write!(f, concat!(stringify!($name), "([redacted])"))
             }
         }
+        #[cfg(feature = "zeroize")]
+        impl Drop for $name {
+            fn drop(&mut self) {
+                zeroize::Zeroize::zeroize(&mut self.0);
+            }
+        }
+        #[cfg(feature = "zeroize")]
+        impl zeroize::ZeroizeOnDrop for $name {}
     };
 }
 
@@ -438,7 +446,12 @@ macro_rules! deserialize_fields {
                 )
             );
         }
-        let seconds = $map.next_value::<Option<Seconds>>()?;
+        // Some providers serialize timestamps as strings, so accept both numbers and numeric
+        // strings.
+        let seconds = $map
+            .next_value::<Option<LenientSeconds>>()?
+            .map(|lenient| lenient.into_seconds().map_err(serde::de::Error::custom))
+            .transpose()?;
         $field = seconds
             .map(|sec| seconds_to_utc(&sec).map_err(|_| serde::de::Error::custom(
                 format!(
@@ -800,6 +813,38 @@ macro_rules! field_setters {
     };
 }
 
+// Generates consuming builder methods named after each field. Accepts the same field list as
+// `field_getters_setters!` (optional fields only). Each method takes the field's inner
+// (non-optional) value and wraps it in `Some`.
+macro_rules! field_builders {
+    (@case [$doc:expr] $vis:vis $self:ident [$zero:expr] $field:ident $type:ty) => {
+        #[doc = $doc]
+        $vis fn $field(
+            mut $self,
+            $field: $type
+        ) -> Self {
+            $zero.$field = Some($field);
+            $self
+        }
+    };
+    // Main entry point
+    (
+        $vis:vis $self:ident [$zero:expr] [$doc:expr] {
+            $(
+                $setter:ident -> $field:ident[Option<$type:ty>],
+            )+
+        }
+    ) => {
+        $(
+            field_builders![
+                @case
+                [concat!("Sets the `", stringify!($field), "` ", $doc, ".")]
+                $vis $self [$zero] $field $type
+            ];
+        )+
+    };
+}
+
 macro_rules! field_getters_setters {
     (
         @single $vis:vis $self:ident [$zero:expr] [$doc:expr]
@@ -0,0 +1,248 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::clock::{Clock, SystemClock};
+use crate::http_client::{AsyncHttpClient, RequestKind};
+use crate::http_types::{Request, Response, StatusCode};
+
+///
+/// Outcome of a request sent to the OpenID Connect Provider.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RequestOutcome {
+    ///
+    /// The provider returned a response with the specified HTTP status code, which may indicate
+    /// an error.
+    ///
+    Response(StatusCode),
+    ///
+    /// The request could not be sent or the response could not be received (e.g., due to a
+    /// connectivity failure or timeout).
+    ///
+    Error,
+}
+
+///
+/// Receives the kind, duration, and outcome of each request sent through a
+/// [`MetricsHttpClient`] (e.g., to record token endpoint latency or JSON Web Key Set fetch
+/// failures).
+///
+/// This trait is implemented for any `Fn(RequestKind, Duration, RequestOutcome)`.
+///
+pub trait MetricsSink {
+    ///
+    /// Invoked once each request completes or fails.
+    ///
+    fn on_request(&self, kind: RequestKind, duration: Duration, outcome: RequestOutcome);
+}
+impl<F> MetricsSink for F
+where
+    F: Fn(RequestKind, Duration, RequestOutcome),
+{
+    fn on_request(&self, kind: RequestKind, duration: Duration, outcome: RequestOutcome) {
+        (self)(kind, duration, outcome)
+    }
+}
+
+///
+/// HTTP client that reports each request to a [`MetricsSink`].
+///
+/// Since discovery, JSON Web Key Set, user info, and registration requests all accept any
+/// [`AsyncHttpClient`], wrapping a client in a `MetricsHttpClient` reports each of them. The kind
+/// of each request is determined by [`RequestKind::from_request`]. Token requests sent via
+/// [`Client::request_code_exchange`](crate::Client::request_code_exchange),
+/// [`Client::request_refresh_token`](crate::Client::request_refresh_token), or an
+/// [`ExtensionGrantTokenRequest`](crate::ExtensionGrantTokenRequest) (including those sent by a
+/// [`LoginManager`](crate::LoginManager) or [`TokenManager`](crate::TokenManager)) are reported
+/// as [`RequestKind::Token`], while requests not sent by this crate (e.g., token requests sent
+/// by calling [`CodeTokenRequest::request`](crate::CodeTokenRequest::request) directly) are
+/// reported as [`RequestKind::Other`].
+///
+/// When combined with a [`RetryingHttpClient`](crate::RetryingHttpClient), wrapping the retrying
+/// client reports the total duration of all attempts, while wrapping the inner client reports
+/// each attempt separately.
+///
+#[derive(Clone)]
+pub struct MetricsHttpClient<C> {
+    clock: Arc<dyn Clock + Send + Sync>,
+    inner: C,
+    sink: Arc<dyn MetricsSink + Send + Sync>,
+}
+impl<C> MetricsHttpClient<C>
+where
+    C: AsyncHttpClient,
+{
+    ///
+    /// Wraps the specified HTTP client so that each request is reported to the given sink.
+    ///
+    pub fn new<S>(inner: C, sink: S) -> Self
+    where
+        S: MetricsSink + Send + Sync + 'static,
+    {
+        Self {
+            clock: Arc::new(SystemClock),
+            inner,
+            sink: Arc::new(sink),
+        }
+    }
+
+    ///
+    /// Specifies the source of the current time used for measuring request durations. By default,
+    /// the [`SystemClock`] is used.
+    ///
+    pub fn set_clock<T>(mut self, clock: T) -> Self
+    where
+        T: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+impl<C> Debug for MetricsHttpClient<C>
+where
+    C: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("MetricsHttpClient")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+impl<C> AsyncHttpClient for MetricsHttpClient<C>
+where
+    C: AsyncHttpClient,
+{
+    type Error = C::Error;
+    type Future = MetricsFuture<C::Future>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        let kind = RequestKind::from_request(&request).unwrap_or(RequestKind::Other);
+        let started_at = self.clock.now();
+        MetricsFuture {
+            inner: Box::pin(self.inner.call(request)),
+            clock: self.clock.clone(),
+            kind,
+            sink: self.sink.clone(),
+            started_at,
+        }
+    }
+}
+
+///
+/// Future returned by [`MetricsHttpClient`].
+///
+pub struct MetricsFuture<F> {
+    inner: Pin<Box<F>>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    kind: RequestKind,
+    sink: Arc<dyn MetricsSink + Send + Sync>,
+    started_at: DateTime<Utc>,
+}
+impl<F, RE> Future for MetricsFuture<F>
+where
+    F: Future<Output = Result<Response, RE>>,
+{
+    type Output = Result<Response, RE>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let result = match self.inner.as_mut().poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let duration = (self.clock.now() - self.started_at)
+            .to_std()
+            .unwrap_or_default();
+        let outcome = match result {
+            Ok(ref response) => RequestOutcome::Response(response.status()),
+            Err(_) => RequestOutcome::Error,
+        };
+        self.sink.on_request(self.kind, duration, outcome);
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use futures::executor::block_on;
+    use futures::future::ready;
+
+    use crate::http_client::InstrumentedHttpClient;
+    use crate::http_types::{Method, Request, Response, StatusCode};
+    use crate::{AsyncHttpClient, FixedClock, RequestKind};
+
+    use super::{MetricsHttpClient, RequestOutcome};
+
+    fn test_request() -> Request {
+        Request::new(
+            Method::Get,
+            "https://example.com/".parse::<url::Url>().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_metrics() {
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let reported = Arc::new(Mutex::new(Vec::new()));
+
+        let request_clock = clock.clone();
+        let fail = Arc::new(Mutex::new(false));
+        let request_fail = fail.clone();
+        let sink_reported = reported.clone();
+        let client = MetricsHttpClient::new(
+            move |_: Request| {
+                request_clock.advance(chrono::Duration::milliseconds(250));
+                if *request_fail.lock().unwrap() {
+                    ready(Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "connection failed",
+                    )))
+                } else {
+                    ready(Ok(Response::new(StatusCode::Ok)))
+                }
+            },
+            move |kind: RequestKind, duration: Duration, outcome: RequestOutcome| {
+                sink_reported
+                    .lock()
+                    .unwrap()
+                    .push((kind, duration, outcome))
+            },
+        )
+        .set_clock(clock);
+
+        block_on(
+            InstrumentedHttpClient::new(RequestKind::JsonWebKeySet, &client).call(test_request()),
+        )
+        .unwrap();
+        *fail.lock().unwrap() = true;
+        block_on(client.call(test_request())).unwrap_err();
+
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![
+                (
+                    RequestKind::JsonWebKeySet,
+                    Duration::from_millis(250),
+                    RequestOutcome::Response(StatusCode::Ok),
+                ),
+                (
+                    RequestKind::Other,
+                    Duration::from_millis(250),
+                    RequestOutcome::Error,
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+
+use crate::{
+    AdditionalClaims, ClaimsVerificationError, GenderClaim, IdToken, IdTokenClaims,
+    IdTokenVerifier, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, NonceVerifier,
+};
+
+///
+/// Set of trusted OpenID Connect Providers, each with its own [`IdTokenVerifier`].
+///
+/// Each verifier determines the issuer it trusts (along with any trusted issuer aliases), the
+/// JSON Web Key Set used to verify signatures, and the expected audience (i.e., client ID). This
+/// allows, for example, an application to accept ID tokens from both a workforce and a customer
+/// identity provider. See [`MultiIssuerIdTokenVerifier`].
+///
+#[derive(Clone)]
+pub struct IssuerTrustSet<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    verifiers: Vec<IdTokenVerifier<'a, JS, JT, JU, K>>,
+}
+impl<'a, JS, JT, JU, K> IssuerTrustSet<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Initializes an empty trust set, which doesn't trust any issuers.
+    ///
+    pub fn new() -> Self {
+        Self {
+            verifiers: Vec::new(),
+        }
+    }
+
+    ///
+    /// Trusts ID tokens issued by the issuer of the specified verifier, which is used to verify
+    /// them.
+    ///
+    /// This replaces any verifier previously added for the same issuer.
+    ///
+    pub fn add_issuer(mut self, verifier: IdTokenVerifier<'a, JS, JT, JU, K>) -> Self {
+        self.verifiers
+            .retain(|existing| existing.issuer() != verifier.issuer());
+        self.verifiers.push(verifier);
+        self
+    }
+
+    ///
+    /// Returns the verifier for the specified issuer, if the issuer (or one of its trusted
+    /// aliases) is trusted.
+    ///
+    pub fn verifier(&self, issuer: &IssuerUrl) -> Option<&IdTokenVerifier<'a, JS, JT, JU, K>> {
+        self.verifiers
+            .iter()
+            .find(|verifier| verifier.trusts_issuer(issuer))
+    }
+
+    ///
+    /// Returns the trusted issuers.
+    ///
+    pub fn issuers(&self) -> Vec<&IssuerUrl> {
+        self.verifiers.iter().map(IdTokenVerifier::issuer).collect()
+    }
+}
+impl<'a, JS, JT, JU, K> Default for IssuerTrustSet<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<'a, JS, JT, JU, K> Debug for IssuerTrustSet<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("IssuerTrustSet")
+            .field("issuers", &self.issuers())
+            .finish()
+    }
+}
+
+///
+/// ID token verifier that accepts ID tokens from any of the issuers in an [`IssuerTrustSet`].
+///
+/// The verifier for each ID token is selected using the token's (unverified) `iss` claim, and
+/// then verifies the token's signature, issuer, audience, and other claims exactly as a
+/// single-issuer [`IdTokenVerifier`] would. ID tokens from issuers that aren't in the trust set
+/// are rejected with [`ClaimsVerificationError::InvalidIssuer`].
+///
+#[derive(Clone, Debug)]
+pub struct MultiIssuerIdTokenVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    trust_set: IssuerTrustSet<'a, JS, JT, JU, K>,
+}
+impl<'a, JS, JT, JU, K> MultiIssuerIdTokenVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Initializes a verifier that consults the specified trust set.
+    ///
+    pub fn new(trust_set: IssuerTrustSet<'a, JS, JT, JU, K>) -> Self {
+        Self { trust_set }
+    }
+
+    ///
+    /// Returns the trust set consulted by this verifier.
+    ///
+    pub fn trust_set(&self) -> &IssuerTrustSet<'a, JS, JT, JU, K> {
+        &self.trust_set
+    }
+
+    ///
+    /// Verifies and returns a reference to the claims of the specified ID token.
+    ///
+    pub fn claims<'b, AC, GC, JE, N>(
+        &self,
+        id_token: &'b IdToken<AC, GC, JE, JS, JT>,
+        nonce_verifier: N,
+    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        N: NonceVerifier,
+    {
+        id_token.claims(self.select_verifier(id_token)?, nonce_verifier)
+    }
+
+    ///
+    /// Verifies and returns the claims of the specified ID token.
+    ///
+    pub fn into_claims<AC, GC, JE, N>(
+        &self,
+        id_token: IdToken<AC, GC, JE, JS, JT>,
+        nonce_verifier: N,
+    ) -> Result<IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        N: NonceVerifier,
+    {
+        let verifier = self.select_verifier(&id_token)?;
+        id_token.into_claims(verifier, nonce_verifier)
+    }
+
+    fn select_verifier<AC, GC, JE>(
+        &self,
+        id_token: &IdToken<AC, GC, JE, JS, JT>,
+    ) -> Result<&IdTokenVerifier<'a, JS, JT, JU, K>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        // The issuer is untrusted at this point, but it's only used to select the verifier, which
+        // then verifies the signature using the selected issuer's keys.
+        let issuer = id_token.unverified_claims().issuer();
+        self.trust_set.verifier(issuer).ok_or_else(|| {
+            ClaimsVerificationError::InvalidIssuer(format!("untrusted issuer `{}`", **issuer))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use oauth2::{ClientId, ClientSecret};
+
+    use crate::core::{
+        CoreHmacKey, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm,
+    };
+    use crate::{
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, IssuerUrl, Nonce, StandardClaims,
+        SubjectIdentifier,
+    };
+
+    use super::{IssuerTrustSet, MultiIssuerIdTokenVerifier};
+
+    #[test]
+    fn test_multi_issuer_verifier() {
+        let sign = |issuer: &str, client_id: &str, secret: &str| {
+            CoreIdToken::new(
+                CoreIdTokenClaims::new(
+                    IssuerUrl::new(issuer.to_string()).unwrap(),
+                    vec![Audience::new(client_id.to_string())],
+                    Utc.timestamp(1311281970, 0),
+                    Utc.timestamp(1311280970, 0),
+                    StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+                    EmptyAdditionalClaims {},
+                ),
+                &CoreHmacKey::new(secret),
+                CoreJwsSigningAlgorithm::HmacSha256,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let new_verifier = |issuer: &str, client_id: &str, secret: &str| {
+            CoreIdTokenVerifier::new_confidential_client(
+                ClientId::new(client_id.to_string()),
+                ClientSecret::new(secret.to_string()),
+                IssuerUrl::new(issuer.to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![]),
+            )
+            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+            .set_time_fn(|| Utc.timestamp(1311280971, 0))
+        };
+
+        let verifier = MultiIssuerIdTokenVerifier::new(
+            IssuerTrustSet::new()
+                .add_issuer(new_verifier(
+                    "https://workforce.example.com",
+                    "workforce-client",
+                    "workforce-secret",
+                ))
+                .add_issuer(new_verifier(
+                    "https://customers.example.com",
+                    "customer-client",
+                    "customer-secret",
+                )),
+        );
+        assert_eq!(verifier.trust_set().issuers().len(), 2);
+
+        let no_nonce = |_: Option<&Nonce>| Ok(());
+        for &(issuer, client_id, secret) in &[
+            (
+                "https://workforce.example.com",
+                "workforce-client",
+                "workforce-secret",
+            ),
+            (
+                "https://customers.example.com",
+                "customer-client",
+                "customer-secret",
+            ),
+        ] {
+            let id_token = sign(issuer, client_id, secret);
+            let claims = verifier
+                .claims(&id_token, no_nonce)
+                .expect("trusted issuer should be accepted");
+            assert_eq!(claims.issuer().as_str(), issuer);
+            verifier
+                .into_claims(id_token, no_nonce)
+                .expect("trusted issuer should be accepted");
+        }
+
+        // Each issuer's token must be signed with that issuer's keys and list its client as an
+        // audience.
+        for id_token in &[
+            sign(
+                "https://workforce.example.com",
+                "workforce-client",
+                "customer-secret",
+            ),
+            sign(
+                "https://workforce.example.com",
+                "customer-client",
+                "workforce-secret",
+            ),
+        ] {
+            verifier
+                .claims(id_token, no_nonce)
+                .expect_err("token should fail verification");
+        }
+
+        match verifier.claims(
+            &sign(
+                "https://other.example.com",
+                "workforce-client",
+                "workforce-secret",
+            ),
+            no_nonce,
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,537 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use failure::Fail;
+use oauth2::{CsrfToken, ErrorResponseType, RedirectUrl};
+use url::Url;
+
+use crate::{AuthorizationResponse, AuthorizationResponseError, IssuerUrl};
+
+const DEFAULT_LANDING_PAGE: &str = "<!DOCTYPE html>\
+    <html><head><meta charset=\"utf-8\"><title>Login complete</title></head>\
+    <body><p>Login complete. You may close this window and return to the application.</p></body>\
+    </html>";
+const DEFAULT_ERROR_PAGE: &str = "<!DOCTYPE html>\
+    <html><head><meta charset=\"utf-8\"><title>Login failed</title></head>\
+    <body><p>Login failed. Please return to the application and try again.</p></body>\
+    </html>";
+
+// Interval at which a listener with a timeout polls for incoming connections.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// Maximum time to wait for a connected user agent to send its request, which prevents a stalled
+// connection from blocking the listener indefinitely.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+///
+/// Loopback HTTP listener that receives the authorization response for native (e.g., desktop or
+/// command-line) applications.
+///
+/// As recommended by [RFC 8252](https://tools.ietf.org/html/rfc8252#section-7.3), the listener
+/// binds to the loopback interface using an IP literal (rather than `localhost`), and by default
+/// uses an ephemeral port chosen by the operating system. The resulting
+/// [`RedirectListener::redirect_url`] should be passed to [`Client::set_redirect_uri`] before
+/// generating the authorization URL. Most providers allow any port for loopback redirect URIs.
+///
+/// Only the Authorization Code Flow is supported, since user agents don't send the URL fragment
+/// (in which the Implicit and Hybrid Flows return their responses) to the server.
+///
+/// [`Client::set_redirect_uri`]: crate::Client::set_redirect_uri
+///
+#[derive(Debug)]
+pub struct RedirectListener {
+    error_page: String,
+    expected_issuer: Option<IssuerUrl>,
+    landing_page: String,
+    listener: TcpListener,
+    redirect_url: RedirectUrl,
+    require_issuer: bool,
+    timeout: Option<Duration>,
+}
+impl RedirectListener {
+    ///
+    /// Binds a listener to an ephemeral port on the IPv4 loopback interface (`127.0.0.1`).
+    ///
+    pub fn bind() -> Result<Self, io::Error> {
+        Self::bind_to(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+    }
+
+    ///
+    /// Binds a listener to the specified address, which should be a loopback address.
+    ///
+    /// This is intended for providers that require the redirect URI (including its port) to
+    /// exactly match a registered value.
+    ///
+    pub fn bind_to(addr: SocketAddr) -> Result<Self, io::Error> {
+        let listener = TcpListener::bind(addr)?;
+        // IPv6 addresses are formatted in square brackets, as required within URLs.
+        let redirect_url = RedirectUrl::new(format!("http://{}/", listener.local_addr()?))
+            .expect("loopback redirect URL should be valid");
+        Ok(Self {
+            error_page: DEFAULT_ERROR_PAGE.to_string(),
+            expected_issuer: None,
+            landing_page: DEFAULT_LANDING_PAGE.to_string(),
+            listener,
+            redirect_url,
+            require_issuer: false,
+            timeout: None,
+        })
+    }
+
+    ///
+    /// Returns the redirect URI at which the listener receives the authorization response.
+    ///
+    pub fn redirect_url(&self) -> &RedirectUrl {
+        &self.redirect_url
+    }
+
+    ///
+    /// Sets the HTML page displayed to the user once the authorization response is received.
+    ///
+    pub fn set_landing_page(mut self, landing_page: String) -> Self {
+        self.landing_page = landing_page;
+        self
+    }
+
+    ///
+    /// Sets the HTML page displayed to the user if the authorization response is invalid or
+    /// contains an error.
+    ///
+    pub fn set_error_page(mut self, error_page: String) -> Self {
+        self.error_page = error_page;
+        self
+    }
+
+    ///
+    /// Specifies the issuer expected in the `iss` authorization response parameter (see
+    /// [`AuthorizationResponse::from_redirect_url_with_issuer`]). By default, the `iss`
+    /// parameter is not validated.
+    ///
+    pub fn set_expected_issuer(
+        mut self,
+        expected_issuer: Option<IssuerUrl>,
+        require_issuer: bool,
+    ) -> Self {
+        self.expected_issuer = expected_issuer;
+        self.require_issuer = require_issuer;
+        self
+    }
+
+    ///
+    /// Sets the maximum time to wait for the authorization response. By default, the listener
+    /// waits indefinitely.
+    ///
+    pub fn set_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    ///
+    /// Blocks until the user agent is redirected to the listener, and returns the authorization
+    /// response after verifying its `state` parameter.
+    ///
+    /// Requests for other paths (e.g., `/favicon.ico`) receive a `404 Not Found` response and
+    /// don't end the wait.
+    ///
+    pub fn wait_for_response<T>(
+        self,
+        expected_state: &CsrfToken,
+    ) -> Result<AuthorizationResponse, RedirectListenerError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        self.listener
+            .set_nonblocking(deadline.is_some())
+            .map_err(RedirectListenerError::Io)?;
+
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                        return Err(RedirectListenerError::Timeout);
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+                Err(err) => return Err(RedirectListenerError::Io(err)),
+            };
+            if let Some(result) = self.handle_connection(stream, expected_state) {
+                return result;
+            }
+        }
+    }
+
+    // Returns `None` if the connection didn't contain the authorization response (e.g., because
+    // the user agent closed a speculative connection without sending a request).
+    fn handle_connection<T>(
+        &self,
+        mut stream: TcpStream,
+        expected_state: &CsrfToken,
+    ) -> Option<Result<AuthorizationResponse, RedirectListenerError<T>>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        // Accepted sockets inherit the listener's non-blocking mode on some platforms.
+        stream.set_nonblocking(false).ok()?;
+        stream.set_read_timeout(Some(READ_TIMEOUT)).ok()?;
+
+        let request_line = {
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).ok()?;
+            // Read the remaining request headers, since closing a socket with unread data may
+            // cause the user agent to display a connection error instead of the response.
+            loop {
+                let mut header = String::new();
+                match reader.read_line(&mut header) {
+                    Ok(0) => break,
+                    Ok(_) if header.trim().is_empty() => break,
+                    Ok(_) => continue,
+                    Err(_) => return None,
+                }
+            }
+            request_line
+        };
+
+        let url = match request_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|target| self.redirect_url.url().join(target).ok())
+        {
+            Some(url) if url.path() == self.redirect_url.url().path() => url,
+            Some(_) => {
+                write_response(&mut stream, "404 Not Found", "");
+                return None;
+            }
+            None => {
+                write_response(&mut stream, "400 Bad Request", "");
+                return None;
+            }
+        };
+
+        let result = match self.expected_issuer {
+            Some(ref expected_issuer) => AuthorizationResponse::from_redirect_url_with_issuer(
+                &url,
+                expected_state,
+                expected_issuer,
+                self.require_issuer,
+            ),
+            None => AuthorizationResponse::from_redirect_url(&url, expected_state),
+        };
+        let page = if result.is_ok() {
+            &self.landing_page
+        } else {
+            &self.error_page
+        };
+        // The authorization response has been received even if the page can't be displayed.
+        write_response(&mut stream, "200 OK", page);
+        Some(result.map_err(RedirectListenerError::Authorization))
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+    .and_then(|_| stream.flush());
+}
+
+///
+/// Error receiving the authorization response via a [`RedirectListener`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum RedirectListenerError<T>
+where
+    T: ErrorResponseType + 'static,
+{
+    ///
+    /// The authorization response is invalid or contains an error.
+    ///
+    #[fail(display = "Invalid authorization response")]
+    Authorization(#[cause] AuthorizationResponseError<T>),
+    ///
+    /// An I/O error occurred while accepting connections.
+    ///
+    #[fail(display = "I/O error")]
+    Io(#[cause] io::Error),
+    ///
+    /// The authorization response was not received before the timeout elapsed.
+    ///
+    #[fail(display = "Timed out waiting for authorization response")]
+    Timeout,
+}
+
+///
+/// Redirect URI using a private-use URI scheme (e.g., `com.example.app:/oauth2redirect`), as used
+/// by mobile apps to receive the authorization response from the operating system.
+///
+/// As required by [RFC 8252](https://tools.ietf.org/html/rfc8252#section-7.1), the scheme must be
+/// based on a reverse domain name under the app developer's control (i.e., it must contain a
+/// period), which reduces the risk of another app claiming the same scheme.
+///
+/// Since the callback URI is typically delivered to the app's platform code (e.g., via an iOS
+/// `ASWebAuthenticationSession` or an Android intent filter),
+/// [`PrivateUseRedirect::parse_callback`] accepts it as a string, which is convenient to pass
+/// across an FFI boundary.
+///
+#[derive(Clone, Debug)]
+pub struct PrivateUseRedirect {
+    expected_issuer: Option<IssuerUrl>,
+    redirect_url: RedirectUrl,
+    require_issuer: bool,
+}
+impl PrivateUseRedirect {
+    ///
+    /// Validates that `redirect_url` uses a private-use URI scheme.
+    ///
+    pub fn new(redirect_url: RedirectUrl) -> Result<Self, PrivateUseRedirectError> {
+        let url = redirect_url.url();
+        let scheme = url.scheme();
+        if !scheme.contains('.') || scheme.starts_with('.') || scheme.ends_with('.') {
+            return Err(PrivateUseRedirectError::InvalidScheme(scheme.to_string()));
+        }
+        // Section 3.1.2 of RFC 6749 prohibits fragments in redirect URIs.
+        if url.fragment().is_some() {
+            return Err(PrivateUseRedirectError::Fragment);
+        }
+        Ok(Self {
+            expected_issuer: None,
+            redirect_url,
+            require_issuer: false,
+        })
+    }
+
+    ///
+    /// Returns the redirect URI, which should be passed to [`Client::set_redirect_uri`] before
+    /// generating the authorization URL.
+    ///
+    /// [`Client::set_redirect_uri`]: crate::Client::set_redirect_uri
+    ///
+    pub fn redirect_url(&self) -> &RedirectUrl {
+        &self.redirect_url
+    }
+
+    ///
+    /// Specifies the issuer expected in the `iss` authorization response parameter (see
+    /// [`AuthorizationResponse::from_redirect_url_with_issuer`]). By default, the `iss`
+    /// parameter is not validated.
+    ///
+    pub fn set_expected_issuer(
+        mut self,
+        expected_issuer: Option<IssuerUrl>,
+        require_issuer: bool,
+    ) -> Self {
+        self.expected_issuer = expected_issuer;
+        self.require_issuer = require_issuer;
+        self
+    }
+
+    ///
+    /// Parses the callback URI delivered by the operating system, verifying that it was sent to
+    /// this redirect URI and that its `state` parameter matches `expected_state`.
+    ///
+    /// The scheme, host, and path of the callback URI must match the redirect URI, which prevents
+    /// a callback intended for another redirect URI registered by the same app from being
+    /// accepted.
+    ///
+    pub fn parse_callback<T>(
+        &self,
+        callback_uri: &str,
+        expected_state: &CsrfToken,
+    ) -> Result<AuthorizationResponse, PrivateUseCallbackError<T>>
+    where
+        T: ErrorResponseType + 'static,
+    {
+        let callback_url = Url::parse(callback_uri).map_err(PrivateUseCallbackError::Parse)?;
+        let redirect_url = self.redirect_url.url();
+        // Schemes are case-insensitive and are normalized to lowercase when parsed.
+        if callback_url.scheme() != redirect_url.scheme()
+            || callback_url.host_str() != redirect_url.host_str()
+            || callback_url.path() != redirect_url.path()
+        {
+            return Err(PrivateUseCallbackError::RedirectMismatch);
+        }
+
+        match self.expected_issuer {
+            Some(ref expected_issuer) => AuthorizationResponse::from_redirect_url_with_issuer(
+                &callback_url,
+                expected_state,
+                expected_issuer,
+                self.require_issuer,
+            ),
+            None => AuthorizationResponse::from_redirect_url(&callback_url, expected_state),
+        }
+        .map_err(PrivateUseCallbackError::Authorization)
+    }
+}
+
+///
+/// Error validating a [`PrivateUseRedirect`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum PrivateUseRedirectError {
+    ///
+    /// The redirect URI contains a fragment.
+    ///
+    #[fail(display = "Redirect URI must not contain a fragment")]
+    Fragment,
+    ///
+    /// The redirect URI's scheme is not a reverse domain name (e.g., `com.example.app`).
+    ///
+    #[fail(display = "Invalid private-use URI scheme: {}", _0)]
+    InvalidScheme(String),
+}
+
+///
+/// Error parsing the callback URI received by a [`PrivateUseRedirect`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum PrivateUseCallbackError<T>
+where
+    T: ErrorResponseType + 'static,
+{
+    ///
+    /// The authorization response is invalid or contains an error.
+    ///
+    #[fail(display = "Invalid authorization response")]
+    Authorization(#[cause] AuthorizationResponseError<T>),
+    ///
+    /// The callback URI could not be parsed.
+    ///
+    #[fail(display = "Failed to parse callback URI")]
+    Parse(#[cause] url::ParseError),
+    ///
+    /// The callback URI doesn't match the redirect URI.
+    ///
+    #[fail(display = "Callback URI does not match the redirect URI")]
+    RedirectMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    use oauth2::CsrfToken;
+
+    use crate::core::CoreAuthErrorResponseType;
+
+    use super::{
+        PrivateUseCallbackError, PrivateUseRedirect, PrivateUseRedirectError, RedirectListener,
+        RedirectListenerError,
+    };
+    use crate::{AuthorizationResponseError, RedirectUrl};
+
+    #[test]
+    fn test_redirect_listener() {
+        let listener = RedirectListener::bind()
+            .unwrap()
+            .set_landing_page("done".to_string());
+        let redirect_url = listener.redirect_url().url().clone();
+        assert_eq!(Some("127.0.0.1"), redirect_url.host_str());
+
+        let user_agent = thread::spawn(move || {
+            let send = |target: &str| {
+                let mut stream = TcpStream::connect(
+                    redirect_url.socket_addrs(|| None).unwrap().first().unwrap(),
+                )
+                .unwrap();
+                write!(stream, "GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n", target).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            };
+            (
+                send("/favicon.ico"),
+                send("/?code=abc&state=xyz&scope=openid"),
+            )
+        });
+
+        let response = listener
+            .wait_for_response::<CoreAuthErrorResponseType>(&CsrfToken::new("xyz".to_string()))
+            .unwrap();
+        assert_eq!("abc", response.code().unwrap().secret());
+
+        let (not_found, landing_page) = user_agent.join().unwrap();
+        assert!(not_found.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(landing_page.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(landing_page.ends_with("\r\n\r\ndone"));
+    }
+
+    #[test]
+    fn test_redirect_listener_timeout() {
+        match RedirectListener::bind()
+            .unwrap()
+            .set_timeout(Some(Duration::from_millis(10)))
+            .wait_for_response::<CoreAuthErrorResponseType>(&CsrfToken::new("xyz".to_string()))
+        {
+            Err(RedirectListenerError::Timeout) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_private_use_redirect() {
+        let new = |url: &str| PrivateUseRedirect::new(RedirectUrl::new(url.to_string()).unwrap());
+        for url in &[
+            "myapp:/callback",
+            "https://example.com/callback",
+            "com.example.:/callback",
+        ] {
+            match new(url) {
+                Err(PrivateUseRedirectError::InvalidScheme(_)) => {}
+                other => panic!("unexpected result for {}: {:?}", url, other),
+            }
+        }
+        match new("com.example.app:/callback#fragment") {
+            Err(PrivateUseRedirectError::Fragment) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let redirect = new("com.example.app:/oauth2redirect").unwrap();
+        let state = CsrfToken::new("xyz".to_string());
+        let response = redirect
+            .parse_callback::<CoreAuthErrorResponseType>(
+                "com.example.app:/oauth2redirect?code=abc&state=xyz",
+                &state,
+            )
+            .unwrap();
+        assert_eq!("abc", response.code().unwrap().secret());
+
+        for callback_uri in &[
+            "com.example.app:/other?code=abc&state=xyz",
+            "com.example.other:/oauth2redirect?code=abc&state=xyz",
+        ] {
+            match redirect.parse_callback::<CoreAuthErrorResponseType>(callback_uri, &state) {
+                Err(PrivateUseCallbackError::RedirectMismatch) => {}
+                other => panic!("unexpected result for {}: {:?}", callback_uri, other),
+            }
+        }
+        match redirect.parse_callback::<CoreAuthErrorResponseType>(
+            "com.example.app:/oauth2redirect?code=abc&state=forged",
+            &state,
+        ) {
+            Err(PrivateUseCallbackError::Authorization(
+                AuthorizationResponseError::StateMismatch,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match redirect.parse_callback::<CoreAuthErrorResponseType>("not a URI", &state) {
+            Err(PrivateUseCallbackError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
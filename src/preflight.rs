@@ -0,0 +1,243 @@
+use std::fmt::{Display, Formatter, Result as FormatterResult};
+
+use oauth2::Scope;
+
+use crate::scope::ScopeSet;
+use crate::types::{ResponseType, ResponseTypes};
+
+const PROMPT_NONE: &str = "none";
+
+///
+/// Severity of an [`AuthorizationPreflightIssue`].
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum AuthorizationPreflightSeverity {
+    ///
+    /// The provider is expected to reject the authorization request.
+    ///
+    Error,
+    ///
+    /// The provider may ignore or reject the parameter value, since it isn't advertised as
+    /// supported. Providers aren't required to advertise every supported value, so the
+    /// authorization request may still succeed.
+    ///
+    Warning,
+}
+
+///
+/// Issue found by [`AuthorizationRequest::preflight`](crate::AuthorizationRequest::preflight).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthorizationPreflightIssue {
+    description: &'static str,
+    parameter: &'static str,
+    severity: AuthorizationPreflightSeverity,
+    value: String,
+}
+impl AuthorizationPreflightIssue {
+    ///
+    /// Returns the severity of this issue.
+    ///
+    pub fn severity(&self) -> AuthorizationPreflightSeverity {
+        self.severity
+    }
+
+    ///
+    /// Returns the name of the authorization request parameter to which this issue applies.
+    ///
+    pub fn parameter(&self) -> &str {
+        self.parameter
+    }
+
+    ///
+    /// Returns the offending parameter value.
+    ///
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    ///
+    /// Returns a human-readable description of this issue.
+    ///
+    pub fn description(&self) -> &str {
+        self.description
+    }
+}
+impl Display for AuthorizationPreflightIssue {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        write!(f, "{}={}: {}", self.parameter, self.value, self.description)
+    }
+}
+
+///
+/// Report returned by [`AuthorizationRequest::preflight`](crate::AuthorizationRequest::preflight).
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AuthorizationPreflightReport {
+    issues: Vec<AuthorizationPreflightIssue>,
+}
+impl AuthorizationPreflightReport {
+    ///
+    /// Returns all issues found, in the order they were checked.
+    ///
+    pub fn issues(&self) -> &[AuthorizationPreflightIssue] {
+        &self.issues
+    }
+
+    ///
+    /// Returns the issues expected to cause the provider to reject the authorization request.
+    ///
+    pub fn errors(&self) -> impl Iterator<Item = &AuthorizationPreflightIssue> {
+        self.issues_with_severity(AuthorizationPreflightSeverity::Error)
+    }
+
+    ///
+    /// Returns the issues involving parameter values the provider doesn't advertise as supported.
+    ///
+    pub fn warnings(&self) -> impl Iterator<Item = &AuthorizationPreflightIssue> {
+        self.issues_with_severity(AuthorizationPreflightSeverity::Warning)
+    }
+
+    ///
+    /// Returns `true` if no errors were found. Warnings don't affect the result.
+    ///
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    fn issues_with_severity(
+        &self,
+        severity: AuthorizationPreflightSeverity,
+    ) -> impl Iterator<Item = &AuthorizationPreflightIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+
+    fn error(&mut self, parameter: &'static str, value: &str, description: &'static str) {
+        self.issues.push(AuthorizationPreflightIssue {
+            description,
+            parameter,
+            severity: AuthorizationPreflightSeverity::Error,
+            value: value.to_string(),
+        });
+    }
+
+    fn warning(&mut self, parameter: &'static str, value: &str, description: &'static str) {
+        self.issues.push(AuthorizationPreflightIssue {
+            description,
+            parameter,
+            severity: AuthorizationPreflightSeverity::Warning,
+            value: value.to_string(),
+        });
+    }
+}
+impl Display for AuthorizationPreflightReport {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+// Capabilities advertised by the provider metadata from which a `Client` was initialized, retained
+// in a type-erased form for checking authorization requests.
+#[derive(Clone, Debug)]
+pub(crate) struct AuthorizationPreflight {
+    // Each supported combination of response types, sorted since combinations are unordered.
+    response_types_supported: Vec<Vec<String>>,
+    scopes_supported: Option<ScopeSet>,
+    prompt_values_supported: Option<Vec<String>>,
+}
+impl AuthorizationPreflight {
+    pub(crate) fn new<RT>(
+        response_types_supported: &[ResponseTypes<RT>],
+        scopes_supported: Option<&Vec<Scope>>,
+        prompt_values_supported: Option<&Vec<String>>,
+    ) -> Self
+    where
+        RT: ResponseType,
+    {
+        Self {
+            response_types_supported: response_types_supported
+                .iter()
+                .map(|response_types| {
+                    let mut response_types = response_types
+                        .iter()
+                        .map(|response_type| response_type.as_ref().to_string())
+                        .collect::<Vec<_>>();
+                    response_types.sort();
+                    response_types
+                })
+                .collect(),
+            scopes_supported: scopes_supported.map(|scopes| scopes.iter().cloned().collect()),
+            prompt_values_supported: prompt_values_supported.cloned(),
+        }
+    }
+
+    pub(crate) fn check(
+        &self,
+        response_type: &str,
+        scopes: &ScopeSet,
+        prompts: &[&str],
+    ) -> AuthorizationPreflightReport {
+        let mut report = AuthorizationPreflightReport::default();
+
+        let mut response_types = response_type
+            .split(' ')
+            .filter(|response_type| !response_type.is_empty())
+            .collect::<Vec<_>>();
+        response_types.sort_unstable();
+        if !self.response_types_supported.iter().any(|supported| {
+            supported
+                .iter()
+                .map(String::as_str)
+                .eq(response_types.iter().cloned())
+        }) {
+            report.error(
+                "response_type",
+                response_type,
+                "response type is not supported by the provider",
+            );
+        }
+
+        if let Some(ref scopes_supported) = self.scopes_supported {
+            for scope in scopes.difference(scopes_supported).iter() {
+                report.warning(
+                    "scope",
+                    scope.as_str(),
+                    "scope is not advertised by the provider",
+                );
+            }
+        }
+
+        if prompts.len() > 1 && prompts.contains(&PROMPT_NONE) {
+            report.error(
+                "prompt",
+                PROMPT_NONE,
+                "prompt none must not be combined with other values",
+            );
+        }
+        if let Some(ref prompt_values_supported) = self.prompt_values_supported {
+            for prompt in prompts {
+                if !prompt_values_supported
+                    .iter()
+                    .any(|value| value.as_str() == *prompt)
+                {
+                    report.error(
+                        "prompt",
+                        prompt,
+                        "prompt value is not supported by the provider",
+                    );
+                }
+            }
+        }
+
+        report
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use oauth2::{ClientId, EmptyExtraTokenFields, StandardErrorResponse, StandardTokenResponse};
+
+use crate::core::{
+    CoreAuthDisplay, CoreAuthPrompt, CoreErrorResponseType, CoreGenderClaim, CoreJsonWebKey,
+    CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm, CoreTokenType,
+};
+use crate::{
+    AccessTokenClaims, AdditionalClaims, Client, IdToken, IdTokenClaims, IdTokenFields,
+    UserInfoClaims,
+};
+
+///
+/// Roles granted to the End-User, as included in the `realm_access` claim and in each entry of
+/// the `resource_access` claim.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct KeycloakRoles {
+    ///
+    /// Names of the granted roles.
+    ///
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+///
+/// Additional claims included by Keycloak in ID tokens, access tokens, and user info responses.
+///
+/// Keycloak only includes these claims if the corresponding protocol mappers (e.g., the default
+/// `realm roles` and `client roles` mappers of the `roles` client scope) are configured to add
+/// them to the token or user info response.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct KeycloakClaims {
+    ///
+    /// Realm-level roles granted to the End-User.
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realm_access: Option<KeycloakRoles>,
+    ///
+    /// Client-level roles granted to the End-User, keyed by client ID.
+    ///
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub resource_access: HashMap<String, KeycloakRoles>,
+}
+impl KeycloakClaims {
+    ///
+    /// Returns the realm-level roles granted to the End-User.
+    ///
+    pub fn realm_roles(&self) -> &[String] {
+        self.realm_access
+            .as_ref()
+            .map(|realm_access| realm_access.roles.as_slice())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Returns the roles granted to the End-User for the specified client.
+    ///
+    pub fn client_roles(&self, client_id: &ClientId) -> &[String] {
+        self.resource_access
+            .get(client_id.as_str())
+            .map(|resource_access| resource_access.roles.as_slice())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Returns whether the End-User has been granted the specified realm-level role.
+    ///
+    pub fn has_realm_role(&self, role: &str) -> bool {
+        self.realm_roles()
+            .iter()
+            .any(|realm_role| realm_role == role)
+    }
+
+    ///
+    /// Returns whether the End-User has been granted the specified role for the specified client.
+    ///
+    pub fn has_client_role(&self, client_id: &ClientId, role: &str) -> bool {
+        self.client_roles(client_id)
+            .iter()
+            .any(|client_role| client_role == role)
+    }
+}
+impl AdditionalClaims for KeycloakClaims {}
+
+///
+/// Keycloak JWT access token claims.
+///
+pub type KeycloakAccessTokenClaims = AccessTokenClaims<KeycloakClaims>;
+
+///
+/// OpenID Connect client for Keycloak, which uses [`KeycloakClaims`] as its additional claims.
+///
+pub type KeycloakClient = Client<
+    KeycloakClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    KeycloakTokenResponse,
+    CoreTokenType,
+>;
+
+///
+/// Keycloak ID token.
+///
+pub type KeycloakIdToken = IdToken<
+    KeycloakClaims,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// Keycloak ID token claims.
+///
+pub type KeycloakIdTokenClaims = IdTokenClaims<KeycloakClaims, CoreGenderClaim>;
+
+///
+/// Keycloak ID token fields.
+///
+pub type KeycloakIdTokenFields = IdTokenFields<
+    KeycloakClaims,
+    EmptyExtraTokenFields,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// Keycloak token response.
+///
+pub type KeycloakTokenResponse = StandardTokenResponse<KeycloakIdTokenFields, CoreTokenType>;
+
+///
+/// Keycloak user info claims.
+///
+pub type KeycloakUserInfoClaims = UserInfoClaims<KeycloakClaims, CoreGenderClaim>;
+
+#[cfg(test)]
+mod tests {
+    use oauth2::ClientId;
+
+    use super::{KeycloakClaims, KeycloakIdTokenClaims};
+
+    #[test]
+    fn test_keycloak_claims() {
+        let claims = serde_json::from_str::<KeycloakIdTokenClaims>(
+            "{
+                \"iss\": \"https://keycloak.example.com/realms/example\",
+                \"sub\": \"24400320\",
+                \"aud\": \"account\",
+                \"exp\": 1311281970,
+                \"iat\": 1311280970,
+                \"realm_access\": {
+                    \"roles\": [\"offline_access\", \"admin\"]
+                },
+                \"resource_access\": {
+                    \"account\": {
+                        \"roles\": [\"manage-account\", \"view-profile\"]
+                    },
+                    \"empty\": {}
+                }
+            }",
+        )
+        .expect("failed to deserialize");
+        let keycloak_claims = claims.additional_claims();
+        let account = ClientId::new("account".to_string());
+
+        assert_eq!(keycloak_claims.realm_roles(), ["offline_access", "admin"]);
+        assert!(keycloak_claims.has_realm_role("admin"));
+        assert!(!keycloak_claims.has_realm_role("manage-account"));
+        assert_eq!(
+            keycloak_claims.client_roles(&account),
+            ["manage-account", "view-profile"]
+        );
+        assert!(keycloak_claims.has_client_role(&account, "view-profile"));
+        assert!(!keycloak_claims.has_client_role(&account, "admin"));
+        assert!(keycloak_claims
+            .client_roles(&ClientId::new("empty".to_string()))
+            .is_empty());
+        assert!(keycloak_claims
+            .client_roles(&ClientId::new("other".to_string()))
+            .is_empty());
+
+        // Tokens without role mappers configured omit these claims entirely.
+        let claims = serde_json::from_str::<KeycloakIdTokenClaims>(
+            "{
+                \"iss\": \"https://keycloak.example.com/realms/example\",
+                \"sub\": \"24400320\",
+                \"aud\": \"account\",
+                \"exp\": 1311281970,
+                \"iat\": 1311280970
+            }",
+        )
+        .expect("failed to deserialize");
+        assert_eq!(*claims.additional_claims(), KeycloakClaims::default());
+        assert!(claims.additional_claims().realm_roles().is_empty());
+        assert_eq!(
+            serde_json::to_value(claims.additional_claims()).unwrap(),
+            serde_json::json!({})
+        );
+    }
+}
@@ -0,0 +1,5 @@
+///
+/// Claims and type aliases for [Keycloak](https://www.keycloak.org/). Requires "keycloak" feature.
+///
+#[cfg(feature = "keycloak")]
+pub mod keycloak;
@@ -4,30 +4,47 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use failure::Fail;
+#[cfg(feature = "net")]
+use futures::executor::block_on;
+#[cfg(feature = "net")]
 use futures::Future;
+#[cfg(feature = "net")]
+use oauth2::helpers::variant_name;
 use serde;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
 use serde_json;
 
+use crate::error::{ClassifiedError, ErrorKind};
+#[cfg(feature = "net")]
+use crate::http_client::{
+    AsyncHttpClient, BlockingHttpClient, FnOnceHttpClient, HttpClient, HttpClientError,
+    InstrumentedHttpClient, RequestKind, UnexpectedResponse,
+};
+#[cfg(feature = "net")]
 use crate::http_types::headers::{ACCEPT, CONTENT_TYPE};
-use crate::http_types::{Body, Method, Request, Response, StatusCode};
+#[cfg(feature = "net")]
+use crate::http_types::{Method, Request, Response, StatusCode};
+#[cfg(feature = "net")]
 use crate::http_utils::{auth_bearer, check_content_type, MIME_TYPE_JSON};
 use crate::types::helpers::{serde_utc_seconds_opt, split_language_tag_key};
 use crate::types::{
-    ApplicationType, AuthenticationContextClass, ClientAuthMethod, ClientConfigUrl,
-    ClientContactEmail, ClientName, ClientUrl, GrantType, InitiateLoginUrl, JsonWebKeySetUrl,
-    JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
-    JwsSigningAlgorithm, LocalizedClaim, LogoUrl, PolicyUrl, RegistrationAccessToken,
-    RegistrationUrl, RequestUrl, ResponseType, ResponseTypes, SectorIdentifierUrl,
-    SubjectIdentifierType, ToSUrl,
+    ApplicationType, AuthenticationContextClass, BackchannelLogoutUrl, ClientAuthMethod,
+    ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl, GrantType, InitiateLoginUrl,
+    JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LocalizedClaim, LogoUrl, PolicyUrl,
+    RegistrationAccessToken, RegistrationUrl, RequestUrl, ResponseType, ResponseTypes,
+    SectorIdentifierUrl, SubjectIdentifierType, ToSUrl,
 };
 use crate::{
     AccessToken, ClientId, ClientSecret, ErrorResponseType, JsonWebKey, JsonWebKeySet, RedirectUrl,
     StandardErrorResponse,
 };
 
+#[cfg(feature = "net")]
+const PAIRWISE_SUBJECT_TYPE: &str = "pairwise";
+
 ///
 /// Trait for adding extra fields to [`ClientMetadata`].
 ///
@@ -121,6 +138,8 @@ where
                 default_acr_values: None,
                 initiate_login_uri: None,
                 request_uris: None,
+                backchannel_logout_uri: None,
+                backchannel_logout_session_required: None,
             },
             additional_metadata,
         }
@@ -157,9 +176,40 @@ where
             set_default_acr_values -> default_acr_values[Option<Vec<AuthenticationContextClass>>],
             set_initiate_login_uri -> initiate_login_uri[Option<InitiateLoginUrl>],
             set_request_uris -> request_uris[Option<Vec<RequestUrl>>],
+            set_backchannel_logout_uri -> backchannel_logout_uri[Option<BackchannelLogoutUrl>],
+            set_backchannel_logout_session_required -> backchannel_logout_session_required[Option<bool>],
         }
     ];
 
+    ///
+    /// Returns the sector identifier used to compute pairwise subject identifiers for this client,
+    /// as described in
+    /// [Section 8.1 of OpenID Connect Core](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#PairwiseAlg).
+    ///
+    /// The sector identifier is the host component of the `sector_identifier_uri`, if any.
+    /// Otherwise, it's the host component of the redirect URIs, which must all share the same
+    /// host. Returns `None` if no sector identifier can be determined.
+    ///
+    /// See [`SubjectIdentifier::pairwise`](crate::SubjectIdentifier::pairwise).
+    ///
+    pub fn sector_identifier(&self) -> Option<&str> {
+        if let Some(sector_identifier_uri) = self.sector_identifier_uri() {
+            return sector_identifier_uri.url().host_str();
+        }
+
+        let mut hosts = self
+            .redirect_uris()
+            .iter()
+            .map(|redirect_uri| redirect_uri.url().host_str());
+        let host = hosts.next()??;
+        if hosts.all(|other_host| other_host == Some(host)) {
+            Some(host)
+        } else {
+            None
+        }
+    }
+
     ///
     /// Returns additional client metadata fields.
     ///
@@ -219,6 +269,8 @@ where
     default_acr_values: Option<Vec<AuthenticationContextClass>>,
     initiate_login_uri: Option<InitiateLoginUrl>,
     request_uris: Option<Vec<RequestUrl>>,
+    backchannel_logout_uri: Option<BackchannelLogoutUrl>,
+    backchannel_logout_session_required: Option<bool>,
 }
 impl<'de, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S> Deserialize<'de>
     for StandardClientMetadata<AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>
@@ -324,6 +376,8 @@ where
                         [Option(default_acr_values)]
                         [Option(initiate_login_uri)]
                         [Option(request_uris)]
+                        [Option(backchannel_logout_uri)]
+                        [Option(backchannel_logout_session_required)]
                     }
                 }
             }
@@ -395,6 +449,8 @@ where
                 [Option(default_acr_values)]
                 [Option(initiate_login_uri)]
                 [Option(request_uris)]
+                [Option(backchannel_logout_uri)]
+                [Option(backchannel_logout_session_required)]
             }
         }
     }
@@ -456,8 +512,37 @@ where
 
     ///
     /// Submits this request to the specified registration endpoint using the specified asynchronous
-    /// HTTP client.
+    /// HTTP client. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn register_async<C>(
+        &self,
+        registration_endpoint: &RegistrationUrl,
+        http_client: &C,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, C::Error>,
+    >
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let http_request = self.prepare_registration(registration_endpoint)?;
+        let http_response = InstrumentedHttpClient::new(RequestKind::Registration, http_client)
+            .call(http_request)
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::register_response(http_response).await
+    }
+
+    ///
+    /// Submits this request to the specified registration endpoint using the specified asynchronous
+    /// HTTP client, which is passed by value. Requires "net" feature.
     ///
+    #[cfg(feature = "net")]
+    #[deprecated(
+        since = "2.0.0",
+        note = "use `register_async`, which borrows an `AsyncHttpClient` so that it may be reused"
+    )]
     pub async fn register<F, HC, RE>(
         &self,
         registration_endpoint: &RegistrationUrl,
@@ -471,13 +556,146 @@ where
         HC: FnOnce(Request) -> F,
         RE: Fail,
     {
-        let http_request = self.prepare_registration(registration_endpoint)?;
-        let http_response = http_client(http_request)
+        self.register_async(registration_endpoint, &FnOnceHttpClient::new(http_client))
+            .await
+            .map_err(|err| err.into_by_value_error())
+    }
+
+    ///
+    /// Submits this request to the specified registration endpoint using the specified synchronous
+    /// HTTP client. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn register_blocking<C>(
+        &self,
+        registration_endpoint: &RegistrationUrl,
+        http_client: &C,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, C::Error>,
+    >
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(self.register_async(registration_endpoint, &BlockingHttpClient(http_client)))
+    }
+
+    ///
+    /// Validates the pairwise subject identifier configuration of this request before it's
+    /// submitted, using the specified asynchronous HTTP client.
+    ///
+    /// This verifies that:
+    ///  * the requested `subject_type` (if any) is listed in the OpenID Connect Provider's
+    ///    `subject_types_supported` metadata (see [`ProviderMetadata`](crate::ProviderMetadata));
+    ///  * the `sector_identifier_uri` (if any) uses the `https` scheme and references a JSON array
+    ///    that includes every redirect URI, as described in
+    ///    [Section 5 of OpenID Connect Dynamic Client Registration](
+    ///    https://openid.net/specs/openid-connect-registration-1_0.html#SectorIdentifierValidation);
+    ///    and
+    ///  * pairwise subject identifiers are only requested without a `sector_identifier_uri` if all
+    ///    redirect URIs share the same host (see [`ClientMetadata::sector_identifier`]).
+    ///
+    /// Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn validate<C>(
+        &self,
+        subject_types_supported: &[S],
+        http_client: &C,
+    ) -> Result<(), ClientRegistrationError<ET, C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let client_metadata = self.client_metadata();
+        if let Some(subject_type) = client_metadata.subject_type() {
+            let subject_type = variant_name(subject_type);
+            if !subject_types_supported
+                .iter()
+                .any(|supported| variant_name(supported) == subject_type)
+            {
+                return Err(ClientRegistrationError::Validation(format!(
+                    "subject type `{}` is not supported by the provider",
+                    subject_type
+                )));
+            }
+            if subject_type == PAIRWISE_SUBJECT_TYPE
+                && client_metadata.sector_identifier().is_none()
+            {
+                return Err(ClientRegistrationError::Validation(
+                    "a sector identifier URI is required for pairwise subject identifiers when the \
+                     redirect URIs use different hosts"
+                        .to_string(),
+                ));
+            }
+        }
+
+        let sector_identifier_uri = match client_metadata.sector_identifier_uri() {
+            Some(sector_identifier_uri) => sector_identifier_uri,
+            None => return Ok(()),
+        };
+        if sector_identifier_uri.url().scheme() != "https" {
+            return Err(ClientRegistrationError::Validation(format!(
+                "sector identifier URI `{}` must use the https scheme",
+                sector_identifier_uri.url()
+            )));
+        }
+
+        let mut http_request = Request::new(Method::Get, sector_identifier_uri.url().clone());
+        http_request.append_header(ACCEPT, MIME_TYPE_JSON);
+        let mut http_response = InstrumentedHttpClient::new(RequestKind::Registration, http_client)
+            .call(http_request)
             .await
             .map_err(ClientRegistrationError::Request)?;
-        Self::register_response(http_response).await
+        if http_response.status() != StatusCode::Ok {
+            return Err(ClientRegistrationError::Response(
+                UnexpectedResponse::from_response(&mut http_response).await,
+                "unexpected HTTP status code".to_string(),
+            ));
+        }
+        if let Err(err_msg) = check_content_type(&http_response, MIME_TYPE_JSON) {
+            return Err(ClientRegistrationError::Response(
+                UnexpectedResponse::from_response(&mut http_response).await,
+                err_msg,
+            ));
+        }
+        let response_body = http_response.body_bytes().await.map_err(|err| {
+            ClientRegistrationError::Other(format!("failed to read response body: {}", err))
+        })?;
+        let sector_redirect_uris = serde_json::from_slice::<Vec<RedirectUrl>>(&response_body)
+            .map_err(ClientRegistrationError::Parse)?;
+
+        if let Some(redirect_uri) = client_metadata.redirect_uris().iter().find(|redirect_uri| {
+            !sector_redirect_uris
+                .iter()
+                .any(|sector_redirect_uri| sector_redirect_uri.url() == redirect_uri.url())
+        }) {
+            return Err(ClientRegistrationError::Validation(format!(
+                "redirect URI `{}` is not included in the sector identifier URI's redirect URIs",
+                redirect_uri.url()
+            )));
+        }
+        Ok(())
+    }
+
+    ///
+    /// Validates the pairwise subject identifier configuration of this request before it's
+    /// submitted, using the specified synchronous HTTP client.
+    ///
+    /// See [`ClientRegistrationRequest::validate`]. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn validate_blocking<C>(
+        &self,
+        subject_types_supported: &[S],
+        http_client: &C,
+    ) -> Result<(), ClientRegistrationError<ET, C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(self.validate(subject_types_supported, &BlockingHttpClient(http_client)))
     }
 
+    #[cfg(feature = "net")]
     fn prepare_registration<RE>(
         &self,
         registration_endpoint: &RegistrationUrl,
@@ -507,6 +725,7 @@ where
         Ok(req)
     }
 
+    #[cfg(feature = "net")]
     async fn register_response<RE>(
         mut http_response: Response,
     ) -> Result<
@@ -527,19 +746,17 @@ where
             && http_response.status() != StatusCode::BadRequest
         {
             return Err(ClientRegistrationError::Response(
-                http_response.status(),
-                http_response.take_body(),
+                UnexpectedResponse::from_response(&mut http_response).await,
                 "unexpected HTTP status code".to_string(),
             ));
         }
 
-        check_content_type(&http_response, MIME_TYPE_JSON).map_err(|err_msg| {
-            ClientRegistrationError::Response(
-                http_response.status(),
-                http_response.take_body(),
+        if let Err(err_msg) = check_content_type(&http_response, MIME_TYPE_JSON) {
+            return Err(ClientRegistrationError::Response(
+                UnexpectedResponse::from_response(&mut http_response).await,
                 err_msg,
-            )
-        })?;
+            ));
+        }
 
         let response_body = http_response.body_string().await.map_err(|parse_error| {
             ClientRegistrationError::Other(format!(
@@ -610,6 +827,8 @@ where
             set_default_acr_values -> default_acr_values[Option<Vec<AuthenticationContextClass>>],
             set_initiate_login_uri -> initiate_login_uri[Option<InitiateLoginUrl>],
             set_request_uris -> request_uris[Option<Vec<RequestUrl>>],
+            set_backchannel_logout_uri -> backchannel_logout_uri[Option<BackchannelLogoutUrl>],
+            set_backchannel_logout_session_required -> backchannel_logout_session_required[Option<bool>],
         }
     ];
 
@@ -786,6 +1005,8 @@ where
             set_default_acr_values -> default_acr_values[Option<Vec<AuthenticationContextClass>>],
             set_initiate_login_uri -> initiate_login_uri[Option<InitiateLoginUrl>],
             set_request_uris -> request_uris[Option<Vec<RequestUrl>>],
+            set_backchannel_logout_uri -> backchannel_logout_uri[Option<BackchannelLogoutUrl>],
+            set_backchannel_logout_session_required -> backchannel_logout_session_required[Option<bool>],
         }
     ];
 
@@ -850,10 +1071,11 @@ where
     #[fail(display = "Request failed")]
     Request(#[cause] RE),
     ///
-    /// Server returned an invalid response.
+    /// Server returned an invalid response. Requires "net" feature.
     ///
-    #[fail(display = "Server returned invalid response: {}", _2)]
-    Response(StatusCode, Body, String),
+    #[cfg(feature = "net")]
+    #[fail(display = "Server returned invalid response: {} ({})", _1, _0)]
+    Response(UnexpectedResponse, String),
     ///
     /// Failed to serialize client metadata.
     ///
@@ -864,10 +1086,68 @@ where
     ///
     #[fail(display = "Server returned error")]
     ServerResponse(StandardErrorResponse<T>),
+    ///
+    /// The client metadata failed validation (see [`ClientRegistrationRequest::validate`]).
+    ///
+    #[fail(display = "Validation error: {}", _0)]
+    Validation(String),
+}
+impl<T, RE> ClassifiedError for ClientRegistrationError<T, RE>
+where
+    RE: Fail,
+    T: RegisterErrorResponseType,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            ClientRegistrationError::Other(_)
+            | ClientRegistrationError::Serialize(_)
+            | ClientRegistrationError::Validation(_) => ErrorKind::Other,
+            ClientRegistrationError::Parse(_) => ErrorKind::InvalidResponse,
+            ClientRegistrationError::Request(_) => ErrorKind::Request,
+            #[cfg(feature = "net")]
+            ClientRegistrationError::Response(response, _) => {
+                ErrorKind::from_status(response.status())
+            }
+            ClientRegistrationError::ServerResponse(err) => ErrorKind::from_error_response(err),
+        }
+    }
+}
+#[cfg(feature = "net")]
+impl<T, RE> ClientRegistrationError<T, HttpClientError<RE>>
+where
+    RE: Fail,
+    T: RegisterErrorResponseType,
+{
+    // Unwraps the errors of an HTTP client passed by value to a deprecated method (see
+    // `FnOnceHttpClient`).
+    pub(crate) fn into_by_value_error(self) -> ClientRegistrationError<T, RE> {
+        match self {
+            ClientRegistrationError::Other(message) => ClientRegistrationError::Other(message),
+            ClientRegistrationError::Parse(err) => ClientRegistrationError::Parse(err),
+            ClientRegistrationError::Request(HttpClientError::Request(err)) => {
+                ClientRegistrationError::Request(err)
+            }
+            ClientRegistrationError::Request(err) => {
+                ClientRegistrationError::Other(err.to_string())
+            }
+            ClientRegistrationError::Response(response, message) => {
+                ClientRegistrationError::Response(response, message)
+            }
+            ClientRegistrationError::Serialize(err) => ClientRegistrationError::Serialize(err),
+            ClientRegistrationError::ServerResponse(err) => {
+                ClientRegistrationError::ServerResponse(err)
+            }
+            ClientRegistrationError::Validation(message) => {
+                ClientRegistrationError::Validation(message)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "net")]
+    use std::io;
     use std::time::Duration;
 
     use chrono::{TimeZone, Utc};
@@ -876,17 +1156,22 @@ mod tests {
 
     use crate::core::{
         CoreApplicationType, CoreClientAuthMethod, CoreClientMetadata,
-        CoreClientRegistrationResponse, CoreGrantType, CoreJweContentEncryptionAlgorithm,
-        CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm, CoreResponseType,
-        CoreSubjectIdentifierType,
+        CoreClientRegistrationRequest, CoreClientRegistrationResponse, CoreGrantType,
+        CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
+        CoreResponseType, CoreSubjectIdentifierType,
     };
+    #[cfg(feature = "net")]
+    use crate::http_types::{Request, Response, StatusCode};
     use crate::jwt::tests::TEST_RSA_PUB_KEY;
     use crate::{
-        AuthenticationContextClass, ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl,
-        JsonWebKeySet, JsonWebKeySetUrl, LanguageTag, LogoUrl, PolicyUrl, RequestUrl,
-        ResponseTypes, SectorIdentifierUrl, ToSUrl,
+        AuthenticationContextClass, BackchannelLogoutUrl, ClientConfigUrl, ClientContactEmail,
+        ClientName, ClientUrl, JsonWebKeySet, JsonWebKeySetUrl, LanguageTag, LogoUrl, PolicyUrl,
+        RequestUrl, ResponseTypes, SectorIdentifierUrl, ToSUrl,
     };
 
+    #[cfg(feature = "net")]
+    use super::ClientRegistrationError;
+
     #[test]
     fn test_metadata_serialization() {
         // `jwks_uri` and `jwks` aren't supposed to be used together, but this test is just for
@@ -928,7 +1213,9 @@ mod tests {
             \"default_acr_values\": [\"0\", \"urn:mace:incommon:iap:silver\", \
                 \"urn:mace:incommon:iap:bronze\"],
             \"initiate_login_uri\": \"https://example.com/login\",
-            \"request_uris\": [\"https://example.com/request-1\", \"https://example.com/request-2\"]
+            \"request_uris\": [\"https://example.com/request-1\", \"https://example.com/request-2\"],
+            \"backchannel_logout_uri\": \"https://example.com/backchannel-logout\",
+            \"backchannel_logout_session_required\": true
         }}", TEST_RSA_PUB_KEY);
 
         let client_metadata: CoreClientMetadata = serde_json::from_str(&json_response).unwrap();
@@ -1126,6 +1413,15 @@ mod tests {
                 RequestUrl::new("https://example.com/request-2".to_string()).unwrap(),
             ]
         );
+        assert_eq!(
+            *client_metadata.backchannel_logout_uri().unwrap(),
+            BackchannelLogoutUrl::new("https://example.com/backchannel-logout".to_string())
+                .unwrap()
+        );
+        assert_eq!(
+            client_metadata.backchannel_logout_session_required(),
+            Some(true)
+        );
         let serialized_json = serde_json::to_string(&client_metadata).unwrap();
 
         assert_eq!(
@@ -1173,6 +1469,8 @@ mod tests {
         assert_eq!(client_metadata.default_acr_values(), None);
         assert_eq!(client_metadata.sector_identifier_uri(), None);
         assert_eq!(client_metadata.request_uris(), None);
+        assert_eq!(client_metadata.backchannel_logout_uri(), None);
+        assert_eq!(client_metadata.backchannel_logout_session_required(), None);
 
         let serialized_json = serde_json::to_string(&client_metadata).unwrap();
 
@@ -1508,4 +1806,64 @@ mod tests {
             deserialized.additional_response,
         );
     }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_validate_pairwise() {
+        let redirect_uris = vec![
+            RedirectUrl::new("https://client.example.org/callback".to_string()).unwrap(),
+            RedirectUrl::new("https://other.example.org/callback".to_string()).unwrap(),
+        ];
+        let http_client = |request: Request| {
+            assert_eq!(request.url().as_str(), "https://client.example.org/sector");
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(
+                "[\"https://client.example.org/callback\", \"https://other.example.org/callback\"]",
+            );
+            Ok::<_, io::Error>(response)
+        };
+        let supported = vec![
+            CoreSubjectIdentifierType::Public,
+            CoreSubjectIdentifierType::Pairwise,
+        ];
+
+        let request = CoreClientRegistrationRequest::new(redirect_uris, Default::default())
+            .set_subject_type(Some(CoreSubjectIdentifierType::Pairwise));
+        // The redirect URIs use different hosts, so a sector identifier URI is required.
+        assert_eq!(request.client_metadata().sector_identifier(), None);
+        match request.validate_blocking(&supported, &http_client) {
+            Err(ClientRegistrationError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match request.validate_blocking(&[CoreSubjectIdentifierType::Public], &http_client) {
+            Err(ClientRegistrationError::Validation(msg)) => assert_eq!(
+                msg,
+                "subject type `pairwise` is not supported by the provider"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let request = request.set_sector_identifier_uri(Some(
+            SectorIdentifierUrl::new("https://client.example.org/sector".to_string()).unwrap(),
+        ));
+        assert_eq!(
+            request.client_metadata().sector_identifier(),
+            Some("client.example.org")
+        );
+        request.validate_blocking(&supported, &http_client).unwrap();
+
+        let request = request.set_redirect_uris(vec![RedirectUrl::new(
+            "https://unlisted.example.org/callback".to_string(),
+        )
+        .unwrap()]);
+        match request.validate_blocking(&supported, &http_client) {
+            Err(ClientRegistrationError::Validation(msg)) => assert_eq!(
+                msg,
+                "redirect URI `https://unlisted.example.org/callback` is not included in the \
+                 sector identifier URI's redirect URIs"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
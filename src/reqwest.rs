@@ -0,0 +1,165 @@
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+
+use failure::Fail;
+
+use crate::http_client::{AsyncHttpClient, RequestOptions, RequestPolicy};
+use crate::http_types::{Request, Response, StatusCode};
+
+///
+/// Error type returned by failed reqwest HTTP requests.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum Error {
+    ///
+    /// Error returned by reqwest crate.
+    ///
+    #[fail(display = "request failed")]
+    Reqwest(#[cause] reqwest::Error),
+    ///
+    /// Failed to convert between `http_types` and reqwest representations.
+    ///
+    #[fail(display = "HTTP conversion failed: {}", _0)]
+    Http(String),
+    ///
+    /// The response body exceeded the maximum size specified by the request's [`RequestPolicy`].
+    ///
+    #[fail(display = "response body exceeds {} bytes", _0)]
+    ResponseTooLarge(usize),
+}
+
+///
+/// Asynchronous HTTP client backed by a shared [`reqwest::Client`].
+///
+/// Cloning a `ReqwestClient` is cheap and reuses the underlying connection pool. A reference to
+/// this type may be passed anywhere an [`AsyncHttpClient`](crate::AsyncHttpClient) is expected.
+///
+#[derive(Clone, Debug)]
+pub struct ReqwestClient {
+    inner: reqwest::Client,
+}
+impl ReqwestClient {
+    ///
+    /// Instantiates a new client wrapping a newly constructed [`reqwest::Client`] with redirects
+    /// disabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend can't be initialized, as with [`reqwest::Client::new`].
+    ///
+    pub fn new() -> Self {
+        let inner = reqwest::Client::builder()
+            // Following redirects opens the client up to SSRF vulnerabilities.
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("failed to initialize reqwest client");
+        Self { inner }
+    }
+
+    ///
+    /// Instantiates a new client wrapping the specified [`reqwest::Client`].
+    ///
+    /// Redirects should be disabled on the provided client to prevent SSRF vulnerabilities.
+    ///
+    pub fn from_client(inner: reqwest::Client) -> Self {
+        Self { inner }
+    }
+
+    ///
+    /// Sends the specified request.
+    ///
+    pub async fn send(&self, request: Request) -> Result<Response, Error> {
+        let max_response_size =
+            RequestPolicy::from_request(&request).and_then(RequestPolicy::max_response_size);
+        let request = to_reqwest_request(&self.inner, request).await?;
+        let response = self
+            .inner
+            .execute(request)
+            .await
+            .map_err(Error::Reqwest)?;
+        from_reqwest_response(response, max_response_size).await
+    }
+}
+
+impl Default for ReqwestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AsyncHttpClient for ReqwestClient {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Error>> + Send>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move { client.send(request).await })
+    }
+}
+
+///
+/// Asynchronous HTTP client using a newly constructed [`reqwest::Client`] with redirects
+/// disabled.
+///
+/// Prefer [`ReqwestClient`] when issuing several requests so that connections may be reused.
+///
+pub async fn async_http_client(request: Request) -> Result<Response, Error> {
+    let client = reqwest::Client::builder()
+        // Following redirects opens the client up to SSRF vulnerabilities.
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(Error::Reqwest)?;
+    ReqwestClient::from_client(client).send(request).await
+}
+
+async fn to_reqwest_request(
+    client: &reqwest::Client,
+    mut request: Request,
+) -> Result<reqwest::Request, Error> {
+    let method = reqwest::Method::from_bytes(request.method().to_string().as_bytes())
+        .map_err(|err| Error::Http(format!("invalid HTTP method: {}", err)))?;
+    let mut builder = client.request(method, request.url().as_str());
+    if let Some(timeout) = RequestOptions::from_request(&request).and_then(|o| o.timeout()) {
+        builder = builder.timeout(timeout);
+    }
+    for (name, values) in request.iter() {
+        for value in values.iter() {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+    }
+    let body = request
+        .body_bytes()
+        .await
+        .map_err(|err| Error::Http(format!("failed to read request body: {}", err)))?;
+    builder.body(body).build().map_err(Error::Reqwest)
+}
+
+async fn from_reqwest_response(
+    mut response: reqwest::Response,
+    max_response_size: Option<usize>,
+) -> Result<Response, Error> {
+    let status_code = StatusCode::try_from(response.status().as_u16())
+        .map_err(|err| Error::Http(format!("invalid HTTP status code: {}", err)))?;
+    let mut http_response = Response::new(status_code);
+    for (name, value) in response.headers().iter() {
+        let value = value
+            .to_str()
+            .map_err(|err| Error::Http(format!("invalid header value: {}", err)))?;
+        http_response.append_header(name.as_str(), value);
+    }
+
+    // The body is read incrementally so that oversized responses are rejected without buffering
+    // them in full.
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(Error::Reqwest)? {
+        if let Some(max_response_size) = max_response_size {
+            if body.len() + chunk.len() > max_response_size {
+                return Err(Error::ResponseTooLarge(max_response_size));
+            }
+        }
+        body.extend_from_slice(&chunk);
+    }
+    http_response.set_body(body);
+    Ok(http_response)
+}
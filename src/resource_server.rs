@@ -0,0 +1,1329 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use futures::future::{ready, Ready};
+use oauth2::{AccessToken, ClientId, ClientSecret, IntrospectionUrl};
+use url::form_urlencoded;
+
+use crate::crypto::sha256;
+use crate::error::{ClassifiedError, ErrorKind};
+use crate::http_client::{
+    AsyncHttpClient, InstrumentedHttpClient, RequestKind, UnexpectedResponse,
+};
+use crate::http_types::headers::{ACCEPT, CONTENT_TYPE};
+use crate::http_types::{Method, Request, Response, StatusCode};
+use crate::http_utils::{
+    auth_basic, check_content_type, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON,
+};
+use crate::types::helpers::{deserialize_string_or_vec_opt, serde_utc_seconds_opt};
+use crate::{
+    AccessTokenClaims, AccessTokenVerifier, AdditionalClaims, AdditionalProviderMetadata, Audience,
+    AuthDisplay, ClaimName, ClaimType, ClaimsVerificationError, ClientAuthMethod, Clock, GrantType,
+    IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, JwtAccessToken,
+    ProviderMetadata, ResponseMode, ResponseType, ScopeSet, SubjectIdentifier,
+    SubjectIdentifierType, SystemClock,
+};
+
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+///
+/// Validator for bearer access tokens presented to a resource server.
+///
+/// This trait is implemented for [`ResourceServerValidator`], [`JwtBearerValidator`], and any
+/// `Fn(AccessToken) -> F where F: Future<Output = Result<T, E>>`. The latter may be used to
+/// validate access tokens using custom logic.
+///
+pub trait BearerTokenValidator {
+    ///
+    /// Verified claims returned for valid access tokens.
+    ///
+    type Claims: Clone + Send + Sync + 'static;
+    ///
+    /// Error returned when the access token is invalid.
+    ///
+    type Error: Fail;
+    ///
+    /// Future resolving to the verified claims.
+    ///
+    type Future: Future<Output = Result<Self::Claims, Self::Error>> + Send;
+
+    ///
+    /// Validates the specified access token.
+    ///
+    fn validate(&self, access_token: AccessToken) -> Self::Future;
+
+    ///
+    /// Returns the challenge with which to reject a request whose access token failed validation.
+    ///
+    /// By default, all errors are reported as an `invalid_token` error.
+    ///
+    fn challenge(&self, _error: &Self::Error) -> BearerChallenge {
+        BearerChallenge::invalid_token(None)
+    }
+}
+impl<F, R, T, E> BearerTokenValidator for F
+where
+    F: Fn(AccessToken) -> R,
+    R: Future<Output = Result<T, E>> + Send,
+    T: Clone + Send + Sync + 'static,
+    E: Fail,
+{
+    type Claims = T;
+    type Error = E;
+    type Future = R;
+
+    fn validate(&self, access_token: AccessToken) -> Self::Future {
+        (self)(access_token)
+    }
+}
+
+///
+/// [`BearerTokenValidator`] that verifies [`JwtAccessToken`]s using an [`AccessTokenVerifier`].
+///
+/// Use a [`ResourceServerValidator`] to also accept opaque access tokens or to require specific
+/// scopes.
+///
+pub struct JwtBearerValidator<AC, JE, JS, JT, JU, K>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    verifier: AccessTokenVerifier<'static, JE, JS, JT, JU, K>,
+    _phantom: PhantomData<fn() -> AC>,
+}
+impl<AC, JE, JS, JT, JU, K> JwtBearerValidator<AC, JE, JS, JT, JU, K>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates a validator using the specified verifier.
+    ///
+    pub fn new(verifier: AccessTokenVerifier<'static, JE, JS, JT, JU, K>) -> Self {
+        Self {
+            verifier,
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<AC, JE, JS, JT, JU, K> BearerTokenValidator for JwtBearerValidator<AC, JE, JS, JT, JU, K>
+where
+    AC: AdditionalClaims + Clone + Send + Sync,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    type Claims = AccessTokenClaims<AC>;
+    type Error = ClaimsVerificationError;
+    type Future = Ready<Result<Self::Claims, Self::Error>>;
+
+    fn validate(&self, access_token: AccessToken) -> Self::Future {
+        ready(
+            JwtAccessToken::<AC, JE, JS, JT>::from_access_token(&access_token)
+                .map_err(|err| {
+                    ClaimsVerificationError::Other(format!("failed to parse access token: {}", err))
+                })
+                .and_then(|jwt| jwt.claims(&self.verifier)),
+        )
+    }
+}
+
+///
+/// [`BearerTokenValidator`] for resource servers protected by an OpenID Connect Provider.
+///
+/// JWT access tokens (see [RFC 9068](https://tools.ietf.org/html/rfc9068)) are verified locally
+/// against the provider's JSON Web Key Set. All other (i.e., opaque) access tokens are validated
+/// via the provider's [token introspection](https://tools.ietf.org/html/rfc7662) endpoint, if
+/// any. Introspection responses are cached (see
+/// [`ResourceServerValidator::set_introspection_cache`]) to avoid contacting the provider on every
+/// request. Introspection responses whose `iss` field doesn't match the provider's issuer URL, or
+/// whose `nbf` field is in the future (accounting for
+/// [`ResourceServerValidator::set_clock_skew`]), are rejected.
+///
+/// Once an access token is validated, its scopes must include the scopes specified via
+/// [`ResourceServerValidator::set_required_scopes`]. Validation errors may be converted into
+/// `401 Unauthorized` or `403 Forbidden` responses via [`ResourceServerError::challenge`].
+///
+pub struct ResourceServerValidator<AC, JE, JS, JT, JU, K, C>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    audience: Audience,
+    cache: Arc<IntrospectionCache>,
+    client_id: Option<ClientId>,
+    client_secret: Option<ClientSecret>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    clock_skew: Duration,
+    http_client: Arc<C>,
+    introspection_url: Option<IntrospectionUrl>,
+    issuer: IssuerUrl,
+    jwt_verifier: Option<AccessTokenVerifier<'static, JE, JS, JT, JU, K>>,
+    required_scopes: ScopeSet,
+    _phantom: PhantomData<fn() -> AC>,
+}
+impl<AC, JE, JS, JT, JU, K, C> ResourceServerValidator<AC, JE, JS, JT, JU, K, C>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates a validator for the resource server identified by `audience`, which verifies
+    /// JWT access tokens issued by `issuer` using the specified keys.
+    ///
+    /// Opaque access tokens are rejected unless an introspection endpoint is specified via
+    /// [`ResourceServerValidator::set_introspection_url`].
+    ///
+    pub fn new(
+        audience: Audience,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+        http_client: C,
+    ) -> Self {
+        Self {
+            jwt_verifier: Some(AccessTokenVerifier::new(
+                audience.clone(),
+                issuer.clone(),
+                signature_keys,
+            )),
+            audience,
+            cache: Arc::new(IntrospectionCache::new(
+                DEFAULT_CACHE_TTL,
+                DEFAULT_CACHE_CAPACITY,
+            )),
+            client_id: None,
+            client_secret: None,
+            clock: Arc::new(SystemClock),
+            clock_skew: Duration::from_secs(0),
+            http_client: Arc::new(http_client),
+            introspection_url: None,
+            issuer,
+            required_scopes: ScopeSet::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Instantiates a validator for the resource server identified by `audience` using the
+    /// issuer, JSON Web Key Set, and token introspection endpoint (if advertised) from the
+    /// specified provider metadata.
+    ///
+    /// Most providers require resource servers to authenticate to the introspection endpoint,
+    /// which may be configured via [`ResourceServerValidator::set_client_credentials`].
+    ///
+    pub fn from_provider_metadata<A, AD, CA, CN, CT, G, JK, RM, RT, S>(
+        metadata: &ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+        audience: Audience,
+        http_client: C,
+    ) -> Self
+    where
+        A: AdditionalProviderMetadata,
+        AD: AuthDisplay,
+        CA: ClientAuthMethod,
+        CN: ClaimName,
+        CT: ClaimType,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        RM: ResponseMode,
+        RT: ResponseType,
+        S: SubjectIdentifierType,
+    {
+        Self::new(
+            audience,
+            metadata.issuer().clone(),
+            metadata.jwks().to_owned(),
+            http_client,
+        )
+        .set_introspection_url(metadata.introspection_endpoint().cloned())
+    }
+
+    ///
+    /// Specifies the credentials with which to authenticate to the introspection endpoint.
+    ///
+    /// If a client secret is specified, the credentials are sent using the HTTP Basic
+    /// authentication scheme. Otherwise, the client ID is sent in the request body.
+    ///
+    pub fn set_client_credentials(
+        mut self,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> Self {
+        self.client_id = Some(client_id);
+        self.client_secret = client_secret;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time used for verifying access tokens and expiring
+    /// cached introspection responses.
+    ///
+    pub fn set_clock<CL>(mut self, clock: CL) -> Self
+    where
+        CL: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self.jwt_verifier = self
+            .jwt_verifier
+            .take()
+            .map(|verifier| self.with_time_options(verifier));
+        self
+    }
+
+    ///
+    /// Specifies the maximum tolerated difference between the local clock and the provider's
+    /// clock when verifying JWT access tokens and the `nbf` field of introspection responses. By
+    /// default, no clock skew is tolerated.
+    ///
+    pub fn set_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self.jwt_verifier = self
+            .jwt_verifier
+            .take()
+            .map(|verifier| self.with_time_options(verifier));
+        self
+    }
+
+    ///
+    /// Specifies how long introspection responses are cached, and the maximum number of cached
+    /// responses. By default, up to 1024 responses are cached for 60 seconds.
+    ///
+    /// Responses are never cached beyond the access token's expiration time. A `ttl` or
+    /// `capacity` of zero disables caching. Since cached responses don't reflect token revocation,
+    /// shorter durations limit how long a revoked access token continues to be accepted.
+    ///
+    pub fn set_introspection_cache(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.cache = Arc::new(IntrospectionCache::new(ttl, capacity));
+        self
+    }
+
+    ///
+    /// Specifies the [token introspection](https://tools.ietf.org/html/rfc7662) endpoint used to
+    /// validate opaque access tokens.
+    ///
+    pub fn set_introspection_url(mut self, introspection_url: Option<IntrospectionUrl>) -> Self {
+        self.introspection_url = introspection_url;
+        self
+    }
+
+    ///
+    /// Specifies the verifier used for JWT access tokens (e.g., to allow signature algorithms
+    /// other than `RS256`), or disables local verification if `None`, in which case all access
+    /// tokens are validated via the introspection endpoint.
+    ///
+    pub fn set_jwt_verifier(
+        mut self,
+        jwt_verifier: Option<AccessTokenVerifier<'static, JE, JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = jwt_verifier.map(|verifier| self.with_time_options(verifier));
+        self
+    }
+
+    ///
+    /// Specifies the scopes that every access token must have been granted.
+    ///
+    pub fn set_required_scopes(mut self, required_scopes: ScopeSet) -> Self {
+        self.required_scopes = required_scopes;
+        self
+    }
+
+    fn with_time_options(
+        &self,
+        verifier: AccessTokenVerifier<'static, JE, JS, JT, JU, K>,
+    ) -> AccessTokenVerifier<'static, JE, JS, JT, JU, K> {
+        let clock = self.clock.clone();
+        verifier
+            .set_clock(move || clock.now())
+            .set_clock_skew(self.clock_skew)
+    }
+
+    fn introspection_request(
+        &self,
+        introspection_url: &IntrospectionUrl,
+        access_token: &AccessToken,
+    ) -> Request {
+        let mut request = Request::new(Method::Post, introspection_url.url().clone());
+        request.insert_header(ACCEPT, MIME_TYPE_JSON);
+        request.insert_header(CONTENT_TYPE, MIME_TYPE_FORM_URLENCODED);
+
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        body.append_pair("token", access_token.secret())
+            .append_pair("token_type_hint", "access_token");
+        match (&self.client_id, &self.client_secret) {
+            (Some(client_id), Some(client_secret)) => {
+                let (auth_header, auth_value) = auth_basic(client_id, client_secret);
+                request.insert_header(auth_header, auth_value);
+            }
+            (Some(client_id), None) => {
+                body.append_pair("client_id", client_id);
+            }
+            (None, _) => {}
+        }
+        request.set_body(body.finish().into_bytes());
+        request
+    }
+}
+impl<AC, JE, JS, JT, JU, K, C> BearerTokenValidator
+    for ResourceServerValidator<AC, JE, JS, JT, JU, K, C>
+where
+    AC: AdditionalClaims + Clone + Send + Sync,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    C: AsyncHttpClient + Send + Sync + 'static,
+    C::Future: Send,
+{
+    type Claims = BearerTokenClaims<AC>;
+    type Error = ResourceServerError<C::Error>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Claims, Self::Error>> + Send>>;
+
+    fn validate(&self, access_token: AccessToken) -> Self::Future {
+        // JWTs consist of exactly three base64url-encoded segments, which never appear in opaque
+        // access tokens issued by most providers.
+        let is_jwt = access_token.secret().split('.').count() == 3;
+        match (&self.jwt_verifier, &self.introspection_url) {
+            (Some(verifier), _) if is_jwt => {
+                let result: Result<Self::Claims, Self::Error> =
+                    JwtAccessToken::<AC, JE, JS, JT>::from_access_token(&access_token)
+                        .map_err(|err| {
+                            ResourceServerError::InvalidToken(format!(
+                                "failed to parse access token: {}",
+                                err
+                            ))
+                        })
+                        .and_then(|jwt| {
+                            jwt.claims(verifier)
+                                .map_err(ResourceServerError::Verification)
+                        })
+                        .map(BearerTokenClaims::Jwt)
+                        .and_then(|claims| require_scopes(claims, &self.required_scopes));
+                Box::pin(ready(result))
+            }
+            (_, Some(introspection_url)) => {
+                let cache_key = sha256(access_token.secret().as_bytes());
+                let expected = ExpectedIntrospection {
+                    audience: self.audience.clone(),
+                    clock_skew: self.clock_skew,
+                    issuer: self.issuer.clone(),
+                    required_scopes: self.required_scopes.clone(),
+                };
+                let clock = self.clock.clone();
+                if let Some(response) = self.cache.get(&cache_key, clock.now()) {
+                    return Box::pin(ready(introspected_claims::<AC, C::Error>(
+                        response,
+                        &expected,
+                        clock.now(),
+                    )));
+                }
+
+                let request = self.introspection_request(introspection_url, &access_token);
+                let http_client = self.http_client.clone();
+                let cache = self.cache.clone();
+                Box::pin(async move {
+                    let http_response =
+                        InstrumentedHttpClient::new(RequestKind::Introspection, &*http_client)
+                            .call(request)
+                            .await
+                            .map_err(ResourceServerError::Request)?;
+                    let response = introspection_response::<C::Error>(http_response).await?;
+                    cache.insert(cache_key, response.clone(), clock.now());
+                    introspected_claims::<AC, C::Error>(response, &expected, clock.now())
+                })
+            }
+            (_, None) => Box::pin(ready(Err(ResourceServerError::<C::Error>::InvalidToken(
+                "access token is not a JWT and no introspection endpoint is configured".to_string(),
+            )))),
+        }
+    }
+
+    fn challenge(&self, error: &Self::Error) -> BearerChallenge {
+        error.challenge()
+    }
+}
+
+async fn introspection_response<RE>(
+    mut http_response: Response,
+) -> Result<IntrospectionResponse, ResourceServerError<RE>>
+where
+    RE: Fail,
+{
+    if http_response.status() != StatusCode::Ok {
+        return Err(ResourceServerError::Response(
+            UnexpectedResponse::from_response(&mut http_response).await,
+            format!("HTTP status code {}", http_response.status()),
+        ));
+    }
+
+    if let Err(err_msg) = check_content_type(&http_response, MIME_TYPE_JSON) {
+        return Err(ResourceServerError::Response(
+            UnexpectedResponse::from_response(&mut http_response).await,
+            err_msg,
+        ));
+    }
+
+    let body = http_response
+        .body_bytes()
+        .await
+        .map_err(|_| ResourceServerError::Other("Body Error".to_string()))?;
+    serde_json::from_slice(&body).map_err(ResourceServerError::Parse)
+}
+
+// Validator configuration against which introspection responses are checked.
+struct ExpectedIntrospection {
+    audience: Audience,
+    clock_skew: Duration,
+    issuer: IssuerUrl,
+    required_scopes: ScopeSet,
+}
+
+fn introspected_claims<AC, RE>(
+    response: IntrospectionResponse,
+    expected: &ExpectedIntrospection,
+    now: DateTime<Utc>,
+) -> Result<BearerTokenClaims<AC>, ResourceServerError<RE>>
+where
+    AC: AdditionalClaims,
+    RE: Fail,
+{
+    if !response.active() {
+        return Err(ResourceServerError::InvalidToken(
+            "access token is not active".to_string(),
+        ));
+    }
+    // Cached responses may outlive the access token itself.
+    if response.expiration().map_or(false, |exp| exp <= now) {
+        return Err(ResourceServerError::InvalidToken(
+            "access token has expired".to_string(),
+        ));
+    }
+    // A clock skew too large to represent never rejects the token.
+    let not_yet_valid = response.not_before().map_or(false, |nbf| {
+        chrono::Duration::from_std(expected.clock_skew)
+            .ok()
+            .and_then(|skew| now.checked_add_signed(skew))
+            .map_or(false, |latest| nbf > latest)
+    });
+    if not_yet_valid {
+        return Err(ResourceServerError::InvalidToken(
+            "access token is not yet valid".to_string(),
+        ));
+    }
+    if let Some(issuer) = response.issuer() {
+        if *issuer != expected.issuer {
+            return Err(ResourceServerError::InvalidToken(format!(
+                "access token was issued by `{}` instead of `{}`",
+                **issuer, *expected.issuer
+            )));
+        }
+    }
+    if let Some(audiences) = response.audiences() {
+        if !audiences.contains(&expected.audience) {
+            return Err(ResourceServerError::InvalidToken(format!(
+                "access token is not intended for audience `{}`",
+                *expected.audience
+            )));
+        }
+    }
+    require_scopes(
+        BearerTokenClaims::Introspection(response),
+        &expected.required_scopes,
+    )
+}
+
+fn require_scopes<AC, RE>(
+    claims: BearerTokenClaims<AC>,
+    required_scopes: &ScopeSet,
+) -> Result<BearerTokenClaims<AC>, ResourceServerError<RE>>
+where
+    AC: AdditionalClaims,
+    RE: Fail,
+{
+    if claims.has_scopes(required_scopes) {
+        Ok(claims)
+    } else {
+        Err(ResourceServerError::InsufficientScope(
+            required_scopes.clone(),
+        ))
+    }
+}
+
+// Introspection responses keyed by the SHA-256 hash of the access token, which avoids retaining
+// the access tokens themselves.
+struct IntrospectionCache {
+    capacity: usize,
+    entries: Mutex<HashMap<Vec<u8>, (DateTime<Utc>, IntrospectionResponse)>>,
+    ttl: Duration,
+}
+impl IntrospectionCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    fn get(&self, key: &[u8], now: DateTime<Utc>) -> Option<IntrospectionResponse> {
+        let mut entries = self.lock();
+        match entries.get(key) {
+            Some((expires_at, response)) if *expires_at > now => Some(response.clone()),
+            _ => {
+                entries.remove(key);
+                None
+            }
+        }
+    }
+
+    fn insert(&self, key: Vec<u8>, response: IntrospectionResponse, now: DateTime<Utc>) {
+        let expires_at = match chrono::Duration::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl))
+        {
+            Some(expires_at) if expires_at > now && self.capacity > 0 => response
+                .expiration()
+                .map_or(expires_at, |exp| exp.min(expires_at)),
+            _ => return,
+        };
+
+        let mut entries = self.lock();
+        if entries.len() >= self.capacity {
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+            if entries.len() >= self.capacity {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (expires_at, response));
+    }
+
+    // The cached responses remain consistent even if a previous holder panicked.
+    fn lock(&self) -> MutexGuard<HashMap<Vec<u8>, (DateTime<Utc>, IntrospectionResponse)>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+///
+/// Claims of an access token validated by a [`ResourceServerValidator`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum BearerTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    ///
+    /// Claims of a JWT access token verified locally.
+    ///
+    Jwt(AccessTokenClaims<AC>),
+    ///
+    /// Token introspection response for an opaque access token.
+    ///
+    Introspection(IntrospectionResponse),
+}
+impl<AC> BearerTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    ///
+    /// Returns the scopes granted to the access token.
+    ///
+    pub fn scopes(&self) -> ScopeSet {
+        match self {
+            BearerTokenClaims::Jwt(claims) => claims.scopes(),
+            BearerTokenClaims::Introspection(response) => response.scopes(),
+        }
+    }
+
+    ///
+    /// Returns true if the access token has been granted all of the specified scopes.
+    ///
+    pub fn has_scopes(&self, scopes: &ScopeSet) -> bool {
+        scopes.is_subset(&self.scopes())
+    }
+
+    ///
+    /// Returns the subject (typically, the end-user) of the access token, if any.
+    ///
+    pub fn subject(&self) -> Option<&SubjectIdentifier> {
+        match self {
+            BearerTokenClaims::Jwt(claims) => claims.subject(),
+            BearerTokenClaims::Introspection(response) => response.subject(),
+        }
+    }
+
+    ///
+    /// Returns the client to which the access token was issued, if known.
+    ///
+    pub fn client_id(&self) -> Option<&ClientId> {
+        match self {
+            BearerTokenClaims::Jwt(claims) => claims.client_id(),
+            BearerTokenClaims::Introspection(response) => response.client_id(),
+        }
+    }
+
+    ///
+    /// Returns the expiration time of the access token, if known.
+    ///
+    pub fn expiration(&self) -> Option<DateTime<Utc>> {
+        match self {
+            BearerTokenClaims::Jwt(claims) => Some(claims.expiration()),
+            BearerTokenClaims::Introspection(response) => response.expiration(),
+        }
+    }
+}
+
+///
+/// Token introspection response, as defined in
+/// [RFC 7662](https://tools.ietf.org/html/rfc7662#section-2.2).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IntrospectionResponse {
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<ClientId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_type: Option<String>,
+    #[serde(
+        default,
+        rename = "exp",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    expiration: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        rename = "iat",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    issue_time: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        rename = "nbf",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    not_before: Option<DateTime<Utc>>,
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(
+        default,
+        rename = "aud",
+        deserialize_with = "deserialize_string_or_vec_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    audiences: Option<Vec<Audience>>,
+    #[serde(rename = "iss", skip_serializing_if = "Option::is_none")]
+    issuer: Option<IssuerUrl>,
+    #[serde(rename = "jti", skip_serializing_if = "Option::is_none")]
+    token_id: Option<String>,
+}
+impl IntrospectionResponse {
+    ///
+    /// Initializes a new introspection response.
+    ///
+    pub fn new(active: bool) -> Self {
+        Self {
+            active,
+            scope: None,
+            client_id: None,
+            username: None,
+            token_type: None,
+            expiration: None,
+            issue_time: None,
+            not_before: None,
+            subject: None,
+            audiences: None,
+            issuer: None,
+            token_id: None,
+        }
+    }
+
+    ///
+    /// Returns whether the access token is currently active.
+    ///
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    ///
+    /// Sets whether the access token is currently active.
+    ///
+    pub fn set_active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    field_getters_setters![
+        pub self [self] ["field"] {
+            set_scope -> scope[Option<String>],
+            set_client_id -> client_id[Option<ClientId>],
+            set_username -> username[Option<String>],
+            set_token_type -> token_type[Option<String>],
+            set_expiration -> expiration[Option<DateTime<Utc>>] ["exp"],
+            set_issue_time -> issue_time[Option<DateTime<Utc>>] ["iat"],
+            set_not_before -> not_before[Option<DateTime<Utc>>] ["nbf"],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_audiences -> audiences[Option<Vec<Audience>>] ["aud"],
+            set_issuer -> issuer[Option<IssuerUrl>] ["iss"],
+            set_token_id -> token_id[Option<String>] ["jti"],
+        }
+    ];
+
+    ///
+    /// Returns the scopes granted to the access token, parsed from the `scope` field.
+    ///
+    pub fn scopes(&self) -> ScopeSet {
+        self.scope
+            .as_ref()
+            .map(|scope| ScopeSet::from_space_delimited(scope))
+            .unwrap_or_default()
+    }
+}
+
+///
+/// Error validating a bearer access token.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum ResourceServerError<RE>
+where
+    RE: Fail,
+{
+    ///
+    /// The access token has not been granted all of the required scopes, which are included.
+    ///
+    #[fail(display = "Insufficient scope (requires `{}`)", _0)]
+    InsufficientScope(ScopeSet),
+    ///
+    /// The access token is invalid (e.g., it is not active or was issued for another audience).
+    ///
+    #[fail(display = "Invalid access token: {}", _0)]
+    InvalidToken(String),
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+    ///
+    /// Failed to parse the introspection response.
+    ///
+    #[fail(display = "Failed to parse introspection response")]
+    Parse(#[cause] serde_json::Error),
+    ///
+    /// An error occurred while sending the introspection request or receiving the response
+    /// (e.g., network connectivity failed).
+    ///
+    #[fail(display = "Introspection request failed")]
+    Request(#[cause] RE),
+    ///
+    /// The introspection endpoint returned an invalid response.
+    ///
+    #[fail(display = "Server returned invalid response: {} ({})", _1, _0)]
+    Response(UnexpectedResponse, String),
+    ///
+    /// The JWT access token failed verification.
+    ///
+    #[fail(display = "Failed to verify access token")]
+    Verification(#[cause] ClaimsVerificationError),
+}
+impl<RE> ResourceServerError<RE>
+where
+    RE: Fail,
+{
+    ///
+    /// Returns the challenge with which to reject the request, as described in
+    /// [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3.1).
+    ///
+    /// Invalid access tokens result in a `401 Unauthorized` response, and insufficient scopes
+    /// result in a `403 Forbidden` response. Failures to reach the introspection endpoint result
+    /// in a `503 Service Unavailable` response, since the access token itself may be valid.
+    ///
+    pub fn challenge(&self) -> BearerChallenge {
+        match self {
+            ResourceServerError::InsufficientScope(scopes) => {
+                BearerChallenge::insufficient_scope(scopes.clone())
+            }
+            ResourceServerError::InvalidToken(reason) => {
+                BearerChallenge::invalid_token(Some(reason.clone()))
+            }
+            ResourceServerError::Verification(_) => {
+                BearerChallenge::invalid_token(Some("access token failed verification".to_string()))
+            }
+            ResourceServerError::Other(_)
+            | ResourceServerError::Parse(_)
+            | ResourceServerError::Request(_)
+            | ResourceServerError::Response(..) => BearerChallenge::service_unavailable(),
+        }
+    }
+}
+impl<RE> ClassifiedError for ResourceServerError<RE>
+where
+    RE: Fail,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            ResourceServerError::InsufficientScope(_)
+            | ResourceServerError::InvalidToken(_)
+            | ResourceServerError::Verification(_) => ErrorKind::Verification,
+            ResourceServerError::Other(_) => ErrorKind::Other,
+            ResourceServerError::Parse(_) => ErrorKind::InvalidResponse,
+            ResourceServerError::Request(_) => ErrorKind::Request,
+            ResourceServerError::Response(response, _) => ErrorKind::from_status(response.status()),
+        }
+    }
+}
+
+///
+/// Error code included in a [`BearerChallenge`], as defined in
+/// [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3.1).
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BearerErrorCode {
+    ///
+    /// The request is malformed (e.g., it includes multiple access tokens).
+    ///
+    InvalidRequest,
+    ///
+    /// The access token is expired, revoked, malformed, or otherwise invalid.
+    ///
+    InvalidToken,
+    ///
+    /// The access token has not been granted the scopes required by the request.
+    ///
+    InsufficientScope,
+}
+impl BearerErrorCode {
+    ///
+    /// Returns the error code as used in the `error` attribute.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            BearerErrorCode::InvalidRequest => "invalid_request",
+            BearerErrorCode::InvalidToken => "invalid_token",
+            BearerErrorCode::InsufficientScope => "insufficient_scope",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match *self {
+            BearerErrorCode::InvalidRequest => StatusCode::BadRequest,
+            BearerErrorCode::InvalidToken => StatusCode::Unauthorized,
+            BearerErrorCode::InsufficientScope => StatusCode::Forbidden,
+        }
+    }
+}
+
+///
+/// HTTP response with which a resource server rejects a request, as described in
+/// [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3).
+///
+/// The response consists of the status code, the `WWW-Authenticate` header (see
+/// [`BearerChallenge::www_authenticate`]), and an optional JSON body (see
+/// [`BearerChallenge::to_json`]).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BearerChallenge {
+    error: Option<BearerErrorCode>,
+    error_description: Option<String>,
+    realm: Option<String>,
+    scope: Option<ScopeSet>,
+    status: StatusCode,
+}
+impl BearerChallenge {
+    ///
+    /// Instantiates a challenge for a request that lacks an access token, which doesn't include
+    /// an error code.
+    ///
+    pub fn missing_token() -> Self {
+        Self {
+            error: None,
+            error_description: None,
+            realm: None,
+            scope: None,
+            status: StatusCode::Unauthorized,
+        }
+    }
+
+    ///
+    /// Instantiates a challenge with the specified error code.
+    ///
+    pub fn new(error: BearerErrorCode, error_description: Option<String>) -> Self {
+        Self {
+            error: Some(error),
+            error_description,
+            realm: None,
+            scope: None,
+            status: error.status(),
+        }
+    }
+
+    ///
+    /// Instantiates an `invalid_token` challenge.
+    ///
+    pub fn invalid_token(error_description: Option<String>) -> Self {
+        Self::new(BearerErrorCode::InvalidToken, error_description)
+    }
+
+    ///
+    /// Instantiates an `insufficient_scope` challenge listing the required scopes.
+    ///
+    pub fn insufficient_scope(required_scopes: ScopeSet) -> Self {
+        Self {
+            scope: Some(required_scopes),
+            ..Self::new(BearerErrorCode::InsufficientScope, None)
+        }
+    }
+
+    ///
+    /// Instantiates a `503 Service Unavailable` response for a request whose access token
+    /// couldn't be validated, which doesn't include a challenge.
+    ///
+    pub fn service_unavailable() -> Self {
+        Self {
+            status: StatusCode::ServiceUnavailable,
+            ..Self::missing_token()
+        }
+    }
+
+    ///
+    /// Sets the `realm` attribute of the challenge.
+    ///
+    pub fn set_realm(mut self, realm: Option<String>) -> Self {
+        self.realm = realm;
+        self
+    }
+
+    ///
+    /// Returns the HTTP status code of the response.
+    ///
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    ///
+    /// Returns the error code, if any.
+    ///
+    pub fn error(&self) -> Option<BearerErrorCode> {
+        self.error
+    }
+
+    ///
+    /// Returns the human-readable error description, if any.
+    ///
+    pub fn error_description(&self) -> Option<&String> {
+        self.error_description.as_ref()
+    }
+
+    ///
+    /// Returns the scopes required by the request, if any.
+    ///
+    pub fn scope(&self) -> Option<&ScopeSet> {
+        self.scope.as_ref()
+    }
+
+    ///
+    /// Returns the value of the `WWW-Authenticate` response header, or `None` if the response
+    /// isn't a `401 Unauthorized` or `403 Forbidden` response.
+    ///
+    pub fn www_authenticate(&self) -> Option<String> {
+        if self.status != StatusCode::Unauthorized && self.status != StatusCode::Forbidden {
+            return None;
+        }
+
+        let mut attributes = Vec::new();
+        if let Some(ref realm) = self.realm {
+            attributes.push(format!("realm=\"{}\"", quoted_string(realm)));
+        }
+        if let Some(error) = self.error {
+            attributes.push(format!("error=\"{}\"", error.as_str()));
+        }
+        if let Some(ref error_description) = self.error_description {
+            attributes.push(format!(
+                "error_description=\"{}\"",
+                quoted_string(error_description)
+            ));
+        }
+        if let Some(ref scope) = self.scope {
+            attributes.push(format!("scope=\"{}\"", quoted_string(&scope.to_string())));
+        }
+
+        if attributes.is_empty() {
+            Some("Bearer".to_string())
+        } else {
+            Some(format!("Bearer {}", attributes.join(", ")))
+        }
+    }
+
+    ///
+    /// Returns a JSON response body containing the `error`, `error_description`, and `scope`
+    /// attributes (where present).
+    ///
+    pub fn to_json(&self) -> String {
+        let mut body = serde_json::Map::new();
+        if let Some(error) = self.error {
+            body.insert("error".to_string(), error.as_str().into());
+        }
+        if let Some(ref error_description) = self.error_description {
+            body.insert(
+                "error_description".to_string(),
+                error_description.as_str().into(),
+            );
+        }
+        if let Some(ref scope) = self.scope {
+            body.insert("scope".to_string(), scope.to_string().into());
+        }
+        serde_json::Value::Object(body).to_string()
+    }
+}
+
+// Removes characters that RFC 6750 doesn't allow within attribute values (i.e., double quotes,
+// backslashes, and non-printable or non-ASCII characters).
+fn quoted_string(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| (' '..='~').contains(c) && *c != '"' && *c != '\\')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use failure::Fail;
+    use futures::executor::block_on;
+    use futures::future::{ready, Ready};
+    use oauth2::{AccessToken, ClientId, ClientSecret, IntrospectionUrl};
+
+    use crate::core::{CoreJsonWebKeySet, CoreResourceServerValidator};
+    use crate::http_types::headers::AUTHORIZATION;
+    use crate::http_types::{Request, Response, StatusCode};
+    use crate::{Audience, FixedClock, IssuerUrl, ScopeSet};
+
+    use super::{
+        BearerChallenge, BearerErrorCode, BearerTokenClaims, BearerTokenValidator,
+        IntrospectionResponse, ResourceServerError,
+    };
+
+    #[derive(Debug, Fail)]
+    #[fail(display = "connection failed")]
+    struct TestError;
+
+    // Responds to each introspection request with the specified response, counting the requests.
+    #[derive(Clone)]
+    struct IntrospectionClient {
+        calls: Arc<AtomicUsize>,
+        response: IntrospectionResponse,
+    }
+    impl crate::AsyncHttpClient for IntrospectionClient {
+        type Error = TestError;
+        type Future = Ready<Result<Response, TestError>>;
+
+        fn call(&self, request: Request) -> Self::Future {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(
+                request.header(AUTHORIZATION).unwrap().as_str(),
+                "Basic cmVzb3VyY2Utc2VydmVyOnNlY3JldA=="
+            );
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(serde_json::to_vec(&self.response).unwrap());
+            ready(Ok(response))
+        }
+    }
+
+    fn validator(
+        response: IntrospectionResponse,
+        calls: Arc<AtomicUsize>,
+        clock: FixedClock,
+    ) -> CoreResourceServerValidator<IntrospectionClient> {
+        CoreResourceServerValidator::new(
+            Audience::new("https://api.example.com".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+            IntrospectionClient { calls, response },
+        )
+        .set_introspection_url(Some(
+            IntrospectionUrl::new("https://server.example.com/introspect".to_string()).unwrap(),
+        ))
+        .set_client_credentials(
+            ClientId::new("resource-server".to_string()),
+            Some(ClientSecret::new("secret".to_string())),
+        )
+        .set_clock(clock)
+    }
+
+    #[test]
+    fn test_introspection() {
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let response = IntrospectionResponse::new(true)
+            .set_scope(Some("read write".to_string()))
+            .set_expiration(Some(Utc.timestamp(1311281970, 0)))
+            .set_audiences(Some(vec![Audience::new(
+                "https://api.example.com".to_string(),
+            )]));
+        let validator = validator(response.clone(), calls.clone(), clock.clone());
+        let access_token = AccessToken::new("opaque-token".to_string());
+
+        let claims = block_on(validator.validate(access_token.clone())).unwrap();
+        assert_eq!(claims, BearerTokenClaims::Introspection(response));
+        assert!(claims.has_scopes(&ScopeSet::from_space_delimited("read")));
+
+        // The cached response is used until the cache TTL elapses.
+        block_on(validator.validate(access_token.clone())).unwrap();
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+        clock.advance(chrono::Duration::seconds(61));
+        block_on(validator.validate(access_token.clone())).unwrap();
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+
+        // Cached responses aren't accepted after the access token expires.
+        clock.set(Utc.timestamp(1311281970, 0));
+        match block_on(validator.validate(access_token.clone())) {
+            Err(ResourceServerError::InvalidToken(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let validator = validator
+            .set_required_scopes(ScopeSet::from_space_delimited("read admin"))
+            .set_introspection_cache(Duration::from_secs(0), 0);
+        clock.set(Utc.timestamp(1311280970, 0));
+        let err = block_on(validator.validate(access_token)).unwrap_err();
+        let challenge = validator.challenge(&err);
+        assert_eq!(StatusCode::Forbidden, challenge.status());
+        assert_eq!(
+            Some("Bearer error=\"insufficient_scope\", scope=\"read admin\"".to_string()),
+            challenge.www_authenticate()
+        );
+        assert_eq!(
+            "{\"error\":\"insufficient_scope\",\"scope\":\"read admin\"}",
+            challenge.to_json()
+        );
+    }
+
+    #[test]
+    fn test_introspection_issuer() {
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let access_token = AccessToken::new("opaque-token".to_string());
+        let response = |issuer: &str| {
+            IntrospectionResponse::new(true)
+                .set_issuer(Some(IssuerUrl::new(issuer.to_string()).unwrap()))
+        };
+
+        let trusted_validator = validator(
+            response("https://server.example.com"),
+            Arc::new(AtomicUsize::new(0)),
+            clock.clone(),
+        );
+        block_on(trusted_validator.validate(access_token.clone())).unwrap();
+
+        let untrusted_validator = validator(
+            response("https://other.example.com"),
+            Arc::new(AtomicUsize::new(0)),
+            clock,
+        );
+        match block_on(untrusted_validator.validate(access_token)) {
+            Err(ResourceServerError::InvalidToken(reason)) => assert_eq!(
+                "access token was issued by `https://other.example.com` instead of \
+                 `https://server.example.com`",
+                reason
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_introspection_not_before() {
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let access_token = AccessToken::new("opaque-token".to_string());
+        let validator = validator(
+            IntrospectionResponse::new(true).set_not_before(Some(Utc.timestamp(1311281000, 0))),
+            Arc::new(AtomicUsize::new(0)),
+            clock.clone(),
+        )
+        .set_introspection_cache(Duration::from_secs(0), 0);
+
+        match block_on(validator.validate(access_token.clone())) {
+            Err(ResourceServerError::InvalidToken(reason)) => {
+                assert_eq!("access token is not yet valid", reason)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The `nbf` field is compared with the current time, accounting for clock skew.
+        let validator = validator.set_clock_skew(Duration::from_secs(30));
+        block_on(validator.validate(access_token.clone())).unwrap();
+        let validator = validator.set_clock_skew(Duration::from_secs(29));
+        match block_on(validator.validate(access_token.clone())) {
+            Err(ResourceServerError::InvalidToken(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        clock.advance(chrono::Duration::seconds(1));
+        block_on(validator.validate(access_token)).unwrap();
+    }
+
+    #[test]
+    fn test_inactive_token() {
+        let clock = FixedClock::new(Utc.timestamp(1311280970, 0));
+        let validator = validator(
+            IntrospectionResponse::new(false),
+            Arc::new(AtomicUsize::new(0)),
+            clock,
+        );
+        let err =
+            block_on(validator.validate(AccessToken::new("revoked".to_string()))).unwrap_err();
+        let challenge = err.challenge().set_realm(Some("api".to_string()));
+        assert_eq!(StatusCode::Unauthorized, challenge.status());
+        assert_eq!(Some(BearerErrorCode::InvalidToken), challenge.error());
+        assert_eq!(
+            Some(
+                "Bearer realm=\"api\", error=\"invalid_token\", \
+                 error_description=\"access token is not active\""
+                    .to_string()
+            ),
+            challenge.www_authenticate()
+        );
+
+        assert_eq!(
+            Some("Bearer".to_string()),
+            BearerChallenge::missing_token().www_authenticate()
+        );
+        assert_eq!(
+            None,
+            BearerChallenge::service_unavailable().www_authenticate()
+        );
+    }
+}
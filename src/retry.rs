@@ -0,0 +1,723 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use futures_timer::Delay;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::ErrorKind;
+use crate::http_client::{AsyncHttpClient, RequestKind};
+use crate::http_types::{Method, Request, Response, StatusCode};
+
+///
+/// Policy controlling whether and when failed HTTP requests are retried.
+///
+/// Each retry is delayed using exponential backoff, starting at the initial backoff and growing
+/// by the backoff multiplier up to the maximum backoff. When a `429 Too Many Requests` or
+/// `503 Service Unavailable` response includes a `Retry-After` header, the server-specified delay
+/// is used instead. If the server asks the client to wait longer than the maximum backoff, the
+/// response is returned to the caller without retrying.
+///
+/// Token requests and other non-`GET` requests aren't idempotent, so by default they're retried
+/// only after a `429 Too Many Requests` response (see
+/// [`RetryPolicy::set_retry_non_idempotent_requests`]).
+///
+/// The policy is applied by wrapping an HTTP client in a [`RetryingHttpClient`].
+///
+#[derive(Clone)]
+pub struct RetryPolicy {
+    backoff_multiplier: u32,
+    clock: Arc<dyn Clock + Send + Sync>,
+    initial_backoff: Duration,
+    max_attempts: u32,
+    max_backoff: Duration,
+    retry_non_idempotent_requests: bool,
+    retry_request_errors: bool,
+    retry_status_fn: Option<Arc<dyn Fn(StatusCode) -> bool + Send + Sync>>,
+}
+impl RetryPolicy {
+    ///
+    /// Instantiates a new retry policy.
+    ///
+    /// By default, requests are attempted up to 3 times, starting with a 100 millisecond backoff
+    /// that doubles after each attempt (up to 10 seconds). Request errors (e.g., network
+    /// connectivity failures) and responses classified as retryable by
+    /// [`ErrorKind::is_retryable`] are retried. These include `5xx` and `429` responses, as well as
+    /// OAuth2 error responses with a `server_error` or `temporarily_unavailable` error code.
+    /// Non-idempotent requests are retried only after `429` responses.
+    ///
+    pub fn new() -> Self {
+        Self {
+            backoff_multiplier: 2,
+            clock: Arc::new(SystemClock),
+            initial_backoff: Duration::from_millis(100),
+            max_attempts: 3,
+            max_backoff: Duration::from_secs(10),
+            retry_non_idempotent_requests: false,
+            retry_request_errors: true,
+            retry_status_fn: None,
+        }
+    }
+
+    ///
+    /// Returns a policy that never retries requests.
+    ///
+    pub fn never() -> Self {
+        Self::new().set_max_attempts(1)
+    }
+
+    ///
+    /// Sets the maximum number of attempts (including the initial request).
+    ///
+    /// A value of `0` is treated as `1`.
+    ///
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    ///
+    /// Sets the delay before the first retry.
+    ///
+    pub fn set_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    ///
+    /// Sets the maximum delay between attempts.
+    ///
+    pub fn set_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    ///
+    /// Sets the factor by which the delay grows after each retry.
+    ///
+    pub fn set_backoff_multiplier(mut self, backoff_multiplier: u32) -> Self {
+        self.backoff_multiplier = backoff_multiplier;
+        self
+    }
+
+    ///
+    /// Specifies whether errors returned by the underlying HTTP client (e.g., network
+    /// connectivity failures) are retried.
+    ///
+    pub fn set_retry_request_errors(mut self, retry_request_errors: bool) -> Self {
+        self.retry_request_errors = retry_request_errors;
+        self
+    }
+
+    ///
+    /// Specifies whether token requests and other non-`GET` requests are retried after request
+    /// errors and server errors. By default, they're retried only after `429 Too Many Requests`
+    /// responses, which indicate that the server didn't process the request.
+    ///
+    /// Enabling this option risks sending the same request to the server more than once, since a
+    /// request that times out or fails with a server error may still have been processed. For
+    /// example, replaying an authorization code exchange or a refresh token that the provider has
+    /// already rotated typically fails with `invalid_grant`, and providers that detect refresh
+    /// token reuse may revoke the entire grant (see
+    /// [`TokenManagerError::RefreshTokenReused`](crate::TokenManagerError::RefreshTokenReused)).
+    ///
+    pub fn set_retry_non_idempotent_requests(
+        mut self,
+        retry_non_idempotent_requests: bool,
+    ) -> Self {
+        self.retry_non_idempotent_requests = retry_non_idempotent_requests;
+        self
+    }
+
+    ///
+    /// Specifies a function for determining which HTTP response status codes are retried.
+    ///
+    /// The function replaces the default classification by [`ErrorKind`], including the
+    /// inspection of OAuth2 error codes in response bodies.
+    ///
+    pub fn set_retry_status_fn<T>(mut self, retry_status_fn: T) -> Self
+    where
+        T: Fn(StatusCode) -> bool + Send + Sync + 'static,
+    {
+        self.retry_status_fn = Some(Arc::new(retry_status_fn));
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time, which is used for interpreting `Retry-After`
+    /// headers containing an HTTP date.
+    ///
+    pub fn set_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    ///
+    /// Returns the maximum number of attempts (including the initial request).
+    ///
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    // Returns the exponential backoff delay before the given retry (starting at 1).
+    fn backoff(&self, retry: u32) -> Duration {
+        let mut backoff = self.initial_backoff;
+        for _ in 1..retry {
+            backoff = backoff
+                .checked_mul(self.backoff_multiplier)
+                .unwrap_or(self.max_backoff);
+            if backoff >= self.max_backoff {
+                break;
+            }
+        }
+        backoff.min(self.max_backoff)
+    }
+
+    // Returns whether the request may be resent after a failure that may have occurred after the
+    // server processed it.
+    fn may_replay(&self, request: &Request) -> bool {
+        self.retry_non_idempotent_requests
+            || (request.method() == Method::Get
+                && RequestKind::from_request(request) != Some(RequestKind::Token))
+    }
+
+    // Returns the delay before the given retry (starting at 1), or `None` if the response should
+    // not be retried. Only `429` responses are retried if the request may not be replayed.
+    async fn response_delay(
+        &self,
+        response: &mut Response,
+        retry: u32,
+        now: DateTime<Utc>,
+        may_replay: bool,
+    ) -> Option<Duration> {
+        if !may_replay && response.status() != StatusCode::TooManyRequests {
+            return None;
+        }
+        let retryable = match self.retry_status_fn {
+            Some(ref retry_status_fn) => retry_status_fn(response.status()),
+            None => response_error_kind(response).await.is_retryable(),
+        };
+        if !retryable {
+            return None;
+        }
+
+        match response.status() {
+            StatusCode::TooManyRequests | StatusCode::ServiceUnavailable => {
+                match response
+                    .header("Retry-After")
+                    .and_then(|values| parse_retry_after(values.last().as_str(), now))
+                {
+                    Some(retry_after) if retry_after > self.max_backoff => None,
+                    Some(retry_after) => Some(retry_after),
+                    None => Some(self.backoff(retry)),
+                }
+            }
+            _ => Some(self.backoff(retry)),
+        }
+    }
+}
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("RetryPolicy")
+            .field("backoff_multiplier", &self.backoff_multiplier)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_attempts", &self.max_attempts)
+            .field("max_backoff", &self.max_backoff)
+            .field(
+                "retry_non_idempotent_requests",
+                &self.retry_non_idempotent_requests,
+            )
+            .field("retry_request_errors", &self.retry_request_errors)
+            .finish()
+    }
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Classifies a response by its status code. OAuth2 error responses use `4xx` status codes even
+// when the provider is temporarily unable to handle the request, so those are classified by the
+// error code in the response body instead (see https://tools.ietf.org/html/rfc6749#section-5.2).
+// The body is buffered and restored so that it remains readable by the caller.
+async fn response_error_kind(response: &mut Response) -> ErrorKind {
+    let error_kind = ErrorKind::from_status(response.status());
+    if error_kind != ErrorKind::ClientError {
+        return error_kind;
+    }
+
+    let body = match response.body_bytes().await {
+        Ok(body) => body,
+        Err(_) => return error_kind,
+    };
+    let error_code = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|value| value.get("error")?.as_str().map(ToOwned::to_owned));
+    response.set_body(body);
+    error_code.map_or(error_kind, |error_code| {
+        ErrorKind::from_error_code(&error_code)
+    })
+}
+
+// Parses a `Retry-After` header value, which may be either a number of seconds or an HTTP date
+// (see https://tools.ietf.org/html/rfc7231#section-7.1.3).
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| (date.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+///
+/// Error returned by a [`RetryingHttpClient`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum RetryError<RE>
+where
+    RE: Fail,
+{
+    ///
+    /// The wrapped HTTP client returned an error on the final attempt.
+    ///
+    #[fail(display = "Request failed")]
+    Request(#[cause] RE),
+    ///
+    /// The request body could not be read into memory for resending.
+    ///
+    #[fail(display = "Failed to read request body: {}", _0)]
+    RequestBody(String),
+}
+
+///
+/// HTTP client that retries failed requests according to a [`RetryPolicy`].
+///
+/// Since discovery, JSON Web Key Set, user info, and registration requests all accept any
+/// [`AsyncHttpClient`], wrapping a client in a `RetryingHttpClient` applies the policy to each of
+/// them. Token requests may use the same policy by passing a closure that forwards to
+/// [`AsyncHttpClient::call`], although they're retried only in limited circumstances unless
+/// [`RetryPolicy::set_retry_non_idempotent_requests`] is enabled.
+///
+/// Request bodies are buffered in memory so that they can be resent. If the body can't be read,
+/// the request isn't sent and [`RetryError::RequestBody`] is returned.
+///
+#[derive(Clone, Debug)]
+pub struct RetryingHttpClient<C> {
+    inner: C,
+    policy: RetryPolicy,
+}
+impl<C> RetryingHttpClient<C>
+where
+    C: AsyncHttpClient + Clone + Send + Sync + 'static,
+    C::Future: Send,
+{
+    ///
+    /// Wraps the specified HTTP client with the given retry policy.
+    ///
+    pub fn new(inner: C, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    ///
+    /// Returns the retry policy.
+    ///
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    async fn send(
+        inner: C,
+        policy: RetryPolicy,
+        mut request: Request,
+    ) -> Result<Response, RetryError<C::Error>> {
+        let body = request
+            .body_bytes()
+            .await
+            .map_err(|err| RetryError::RequestBody(err.to_string()))?;
+
+        let may_replay = policy.may_replay(&request);
+        let mut attempt = 1;
+        loop {
+            let mut attempt_request = request.clone();
+            attempt_request.set_body(body.clone());
+            let result = inner.call(attempt_request).await;
+
+            if attempt >= policy.max_attempts {
+                return result.map_err(RetryError::Request);
+            }
+
+            let delay = match result {
+                Ok(mut response) => {
+                    match policy
+                        .response_delay(&mut response, attempt, policy.clock.now(), may_replay)
+                        .await
+                    {
+                        Some(delay) => delay,
+                        None => return Ok(response),
+                    }
+                }
+                Err(_) if policy.retry_request_errors && may_replay => policy.backoff(attempt),
+                Err(err) => return Err(RetryError::Request(err)),
+            };
+
+            if delay > Duration::from_secs(0) {
+                Delay::new(delay).await;
+            }
+            attempt += 1;
+        }
+    }
+}
+impl<C> AsyncHttpClient for RetryingHttpClient<C>
+where
+    C: AsyncHttpClient + Clone + Send + Sync + 'static,
+    C::Future: Send,
+{
+    type Error = RetryError<C::Error>;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Self::Error>> + Send>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        Box::pin(Self::send(self.inner.clone(), self.policy.clone(), request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use failure::Fail;
+    use futures::executor::block_on;
+    use futures::future::{ready, Ready};
+    use futures::io::{AsyncRead, BufReader};
+
+    use crate::http_types::{Body, Method, Request, Response, StatusCode};
+    use crate::{AsyncHttpClient, RequestKind};
+
+    use super::{parse_retry_after, RetryError, RetryPolicy, RetryingHttpClient};
+
+    #[derive(Debug, Fail)]
+    #[fail(display = "connection failed")]
+    struct TestError;
+
+    #[derive(Clone)]
+    struct SequenceClient {
+        calls: Arc<AtomicUsize>,
+        statuses: Vec<StatusCode>,
+    }
+    impl AsyncHttpClient for SequenceClient {
+        type Error = TestError;
+        type Future = Ready<Result<Response, TestError>>;
+
+        fn call(&self, _: Request) -> Self::Future {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            ready(Ok(Response::new(self.statuses[call.min(self.statuses.len() - 1)])))
+        }
+    }
+
+    #[derive(Clone)]
+    struct ErrorResponseClient {
+        calls: Arc<AtomicUsize>,
+        error_codes: Vec<&'static str>,
+    }
+    impl AsyncHttpClient for ErrorResponseClient {
+        type Error = TestError;
+        type Future = Ready<Result<Response, TestError>>;
+
+        fn call(&self, _: Request) -> Self::Future {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let error_code = self.error_codes[call.min(self.error_codes.len() - 1)];
+            let mut response = Response::new(StatusCode::BadRequest);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(format!("{{\"error\":\"{}\"}}", error_code));
+            ready(Ok(response))
+        }
+    }
+
+    fn test_request() -> Request {
+        Request::new(Method::Get, "https://example.com/".parse::<url::Url>().unwrap())
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::ServiceUnavailable, StatusCode::Ok],
+            },
+            RetryPolicy::new().set_initial_backoff(Duration::from_secs(0)),
+        );
+        let response = block_on(client.call(test_request())).unwrap();
+        assert_eq!(StatusCode::Ok, response.status());
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_retry_max_attempts() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::BadGateway],
+            },
+            RetryPolicy::new()
+                .set_initial_backoff(Duration::from_secs(0))
+                .set_max_attempts(4),
+        );
+        let response = block_on(client.call(test_request())).unwrap();
+        assert_eq!(StatusCode::BadGateway, response.status());
+        assert_eq!(4, calls.load(Ordering::SeqCst));
+
+        // Non-retryable status codes are returned immediately.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::BadRequest],
+            },
+            RetryPolicy::new(),
+        );
+        let response = block_on(client.call(test_request())).unwrap();
+        assert_eq!(StatusCode::BadRequest, response.status());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_retry_error_kind() {
+        // All server errors are retried, not just the most common ones.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::HttpVersionNotSupported, StatusCode::Ok],
+            },
+            RetryPolicy::new().set_initial_backoff(Duration::from_secs(0)),
+        );
+        let response = block_on(client.call(test_request())).unwrap();
+        assert_eq!(StatusCode::Ok, response.status());
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+
+        // OAuth2 error responses indicating a temporary server failure are retried, and the body of
+        // the final response remains readable.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            ErrorResponseClient {
+                calls: calls.clone(),
+                error_codes: vec!["temporarily_unavailable", "server_error", "invalid_grant"],
+            },
+            RetryPolicy::new()
+                .set_initial_backoff(Duration::from_secs(0))
+                .set_max_attempts(5),
+        );
+        let mut response = block_on(client.call(test_request())).unwrap();
+        assert_eq!(StatusCode::BadRequest, response.status());
+        assert_eq!(
+            Some("application/json"),
+            response
+                .header("Content-Type")
+                .map(|values| values.last().as_str())
+        );
+        assert_eq!(
+            "{\"error\":\"invalid_grant\"}",
+            block_on(response.body_string()).unwrap()
+        );
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+
+        // A custom status function replaces the default classification.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            ErrorResponseClient {
+                calls: calls.clone(),
+                error_codes: vec!["temporarily_unavailable"],
+            },
+            RetryPolicy::new()
+                .set_initial_backoff(Duration::from_secs(0))
+                .set_retry_status_fn(|status| status.is_server_error()),
+        );
+        let response = block_on(client.call(test_request())).unwrap();
+        assert_eq!(StatusCode::BadRequest, response.status());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_retry_non_idempotent_requests() {
+        #[derive(Clone)]
+        struct FailingClient {
+            calls: Arc<AtomicUsize>,
+        }
+        impl AsyncHttpClient for FailingClient {
+            type Error = TestError;
+            type Future = Ready<Result<Response, TestError>>;
+
+            fn call(&self, _: Request) -> Self::Future {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                ready(Err(TestError))
+            }
+        }
+
+        let policy = RetryPolicy::new().set_initial_backoff(Duration::from_secs(0));
+        let post_request = || {
+            Request::new(
+                Method::Post,
+                "https://example.com/token".parse::<url::Url>().unwrap(),
+            )
+        };
+        let token_request = || {
+            let mut request = test_request();
+            request.ext_mut().insert(RequestKind::Token);
+            request
+        };
+
+        // Requests that may already have been processed aren't replayed by default.
+        for request in [post_request(), token_request()] {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let client = RetryingHttpClient::new(
+                SequenceClient {
+                    calls: calls.clone(),
+                    statuses: vec![StatusCode::ServiceUnavailable, StatusCode::Ok],
+                },
+                policy.clone(),
+            );
+            let response = block_on(client.call(request)).unwrap();
+            assert_eq!(StatusCode::ServiceUnavailable, response.status());
+            assert_eq!(1, calls.load(Ordering::SeqCst));
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            FailingClient {
+                calls: calls.clone(),
+            },
+            policy.clone(),
+        );
+        match block_on(client.call(post_request())) {
+            Err(RetryError::Request(TestError)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+
+        // Rate-limited requests weren't processed, so they're always retried.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::TooManyRequests, StatusCode::Ok],
+            },
+            policy.clone(),
+        );
+        let response = block_on(client.call(post_request())).unwrap();
+        assert_eq!(StatusCode::Ok, response.status());
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+
+        // Opting in retries non-idempotent requests like any other.
+        let policy = policy.set_retry_non_idempotent_requests(true);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::ServiceUnavailable, StatusCode::Ok],
+            },
+            policy.clone(),
+        );
+        let response = block_on(client.call(token_request())).unwrap();
+        assert_eq!(StatusCode::Ok, response.status());
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            FailingClient {
+                calls: calls.clone(),
+            },
+            policy,
+        );
+        assert!(block_on(client.call(post_request())).is_err());
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_unreadable_request_body() {
+        struct FailingReader;
+        impl AsyncRead for FailingReader {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _: &mut Context,
+                _: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "stream failed")))
+            }
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = RetryingHttpClient::new(
+            SequenceClient {
+                calls: calls.clone(),
+                statuses: vec![StatusCode::Ok],
+            },
+            RetryPolicy::new(),
+        );
+        let mut request = test_request();
+        request.set_body(Body::from_reader(BufReader::new(FailingReader), None));
+        match block_on(client.call(request)) {
+            Err(RetryError::RequestBody(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(0, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new()
+            .set_initial_backoff(Duration::from_secs(1))
+            .set_max_backoff(Duration::from_secs(5));
+        assert_eq!(Duration::from_secs(1), policy.backoff(1));
+        assert_eq!(Duration::from_secs(2), policy.backoff(2));
+        assert_eq!(Duration::from_secs(4), policy.backoff(3));
+        assert_eq!(Duration::from_secs(5), policy.backoff(4));
+        assert_eq!(Duration::from_secs(5), policy.backoff(100));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let now = Utc.timestamp(1445412480, 0);
+        assert_eq!(Some(Duration::from_secs(120)), parse_retry_after("120", now));
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            parse_retry_after("Wed, 21 Oct 2015 07:29:00 GMT", now)
+        );
+        // Dates in the past mean the request may be retried immediately.
+        assert_eq!(
+            Some(Duration::from_secs(0)),
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now)
+        );
+        assert_eq!(None, parse_retry_after("soon", now));
+
+        let policy = RetryPolicy::new().set_max_backoff(Duration::from_secs(30));
+        let mut response = Response::new(StatusCode::TooManyRequests);
+        response.insert_header("Retry-After", "5");
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            block_on(policy.response_delay(&mut response, 1, now, true))
+        );
+        response.insert_header("Retry-After", "3600");
+        assert_eq!(
+            None,
+            block_on(policy.response_delay(&mut response, 1, now, true))
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::fmt::{Display, Error as FormatterError, Formatter};
+use std::iter::FromIterator;
+
+use oauth2::Scope;
+
+///
+/// Standard scope values defined by
+/// [OpenID Connect Core 1.0](https://openid.net/specs/openid-connect-core-1_0.html#ScopeClaims).
+///
+/// This trait is implemented for [`Scope`], which allows the constants to be referenced as
+/// `Scope::OPENID`, `Scope::EMAIL`, etc. when the trait is in scope:
+///
+/// ```
+/// use openidconnect::{Scope, StandardScopes};
+///
+/// let scope = Scope::new(Scope::EMAIL.to_string());
+/// assert_eq!(scope.as_str(), "email");
+/// ```
+///
+pub trait StandardScopes {
+    ///
+    /// Scope required by all OpenID Connect authentication requests.
+    ///
+    const OPENID: &'static str = "openid";
+    ///
+    /// Requests access to the End-User's default profile claims.
+    ///
+    const PROFILE: &'static str = "profile";
+    ///
+    /// Requests access to the `email` and `email_verified` claims.
+    ///
+    const EMAIL: &'static str = "email";
+    ///
+    /// Requests access to the `address` claim.
+    ///
+    const ADDRESS: &'static str = "address";
+    ///
+    /// Requests access to the `phone_number` and `phone_number_verified` claims.
+    ///
+    const PHONE: &'static str = "phone";
+    ///
+    /// Requests a refresh token for accessing the UserInfo endpoint while the End-User is not
+    /// logged in.
+    ///
+    const OFFLINE_ACCESS: &'static str = "offline_access";
+}
+impl StandardScopes for Scope {}
+
+///
+/// Set of distinct OAuth2 scopes.
+///
+/// Scopes retain the order in which they were first inserted, which determines the order in which
+/// they are serialized to the space-delimited wire format (see
+/// [RFC 6749](https://tools.ietf.org/html/rfc6749#section-3.3)) via the [`Display`]
+/// implementation. Equality ignores order.
+///
+#[derive(Clone, Debug, Default)]
+pub struct ScopeSet {
+    scopes: Vec<Scope>,
+}
+impl ScopeSet {
+    ///
+    /// Instantiates an empty scope set.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Parses a space-delimited list of scopes, ignoring duplicates and extra whitespace.
+    ///
+    pub fn from_space_delimited(scopes: &str) -> Self {
+        scopes
+            .split(' ')
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| Scope::new(scope.to_string()))
+            .collect()
+    }
+
+    ///
+    /// Adds a scope to the set, returning `true` if it wasn't already present.
+    ///
+    pub fn insert(&mut self, scope: Scope) -> bool {
+        if self.contains(scope.as_str()) {
+            false
+        } else {
+            self.scopes.push(scope);
+            true
+        }
+    }
+
+    ///
+    /// Removes a scope from the set, returning `true` if it was present.
+    ///
+    pub fn remove(&mut self, scope: &str) -> bool {
+        let len = self.scopes.len();
+        self.scopes.retain(|existing| existing.as_str() != scope);
+        self.scopes.len() != len
+    }
+
+    ///
+    /// Returns `true` if the set contains the specified scope.
+    ///
+    pub fn contains(&self, scope: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|existing| existing.as_str() == scope)
+    }
+
+    ///
+    /// Returns `true` if every scope in this set is also in `other`.
+    ///
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.scopes
+            .iter()
+            .all(|scope| other.contains(scope.as_str()))
+    }
+
+    ///
+    /// Returns the scopes in this set followed by any scopes in `other` not already present.
+    ///
+    pub fn union(&self, other: &Self) -> Self {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    ///
+    /// Returns the scopes in this set that are also in `other`.
+    ///
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|scope| other.contains(scope.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Returns the scopes in this set that are not in `other`.
+    ///
+    pub fn difference(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|scope| !other.contains(scope.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Returns an iterator over the scopes in insertion order.
+    ///
+    pub fn iter(&self) -> std::slice::Iter<Scope> {
+        self.scopes.iter()
+    }
+
+    ///
+    /// Returns the number of scopes in the set.
+    ///
+    pub fn len(&self) -> usize {
+        self.scopes.len()
+    }
+
+    ///
+    /// Returns `true` if the set contains no scopes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+}
+impl PartialEq for ScopeSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+impl Eq for ScopeSet {}
+impl Display for ScopeSet {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        for (i, scope) in self.scopes.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", scope.as_str())?;
+        }
+        Ok(())
+    }
+}
+impl Extend<Scope> for ScopeSet {
+    fn extend<I: IntoIterator<Item = Scope>>(&mut self, iter: I) {
+        for scope in iter {
+            self.insert(scope);
+        }
+    }
+}
+impl FromIterator<Scope> for ScopeSet {
+    fn from_iter<I: IntoIterator<Item = Scope>>(iter: I) -> Self {
+        let mut scopes = Self::new();
+        scopes.extend(iter);
+        scopes
+    }
+}
+impl IntoIterator for ScopeSet {
+    type Item = Scope;
+    type IntoIter = std::vec::IntoIter<Scope>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.scopes.into_iter()
+    }
+}
+impl<'a> IntoIterator for &'a ScopeSet {
+    type Item = &'a Scope;
+    type IntoIter = std::slice::Iter<'a, Scope>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.scopes.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::Scope;
+
+    use super::{ScopeSet, StandardScopes};
+
+    #[test]
+    fn test_scope_set() {
+        let scopes = ScopeSet::from_space_delimited(" openid  email openid profile");
+        assert_eq!(scopes.len(), 3);
+        assert!(scopes.contains(Scope::OPENID));
+        assert!(!scopes.contains(Scope::PHONE));
+        assert_eq!(scopes.to_string(), "openid email profile");
+
+        let other = ScopeSet::from_space_delimited("profile phone");
+        assert_eq!(
+            scopes.union(&other).to_string(),
+            "openid email profile phone"
+        );
+        assert_eq!(scopes.intersection(&other).to_string(), "profile");
+        assert_eq!(scopes.difference(&other).to_string(), "openid email");
+        assert!(ScopeSet::from_space_delimited("email openid").is_subset(&scopes));
+        assert!(!other.is_subset(&scopes));
+        assert_eq!(
+            ScopeSet::from_space_delimited("profile email openid"),
+            scopes
+        );
+
+        let mut scopes = scopes;
+        assert!(!scopes.insert(Scope::new(Scope::EMAIL.to_string())));
+        assert!(scopes.insert(Scope::new(Scope::OFFLINE_ACCESS.to_string())));
+        assert!(scopes.remove(Scope::PROFILE));
+        assert!(!scopes.remove(Scope::ADDRESS));
+        assert_eq!(scopes.to_string(), "openid email offline_access");
+        assert!(ScopeSet::from_space_delimited("").is_empty());
+    }
+}
@@ -0,0 +1,683 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::de::Error as _;
+
+use crate::jwt::{
+    JsonWebToken, JsonWebTokenAccess, JsonWebTokenError, JsonWebTokenHeader,
+    JsonWebTokenJsonPayloadSerde, JsonWebTokenType,
+};
+use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt};
+use crate::{
+    Audience, AudiencesClaim, ClaimsVerificationError, IssuerClaim, IssuerUrl, JsonWebKey,
+    JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
+    PrivateSigningKey, SecurityEventTokenVerifier, SubjectIdentifier,
+};
+
+const RISC_ACCOUNT_CREDENTIAL_CHANGE_REQUIRED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/account-credential-change-required";
+const RISC_ACCOUNT_DISABLED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/account-disabled";
+const RISC_ACCOUNT_ENABLED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/account-enabled";
+const RISC_ACCOUNT_PURGED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/account-purged";
+const RISC_CREDENTIAL_COMPROMISE: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/credential-compromise";
+const RISC_IDENTIFIER_CHANGED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/identifier-changed";
+const RISC_IDENTIFIER_RECYCLED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/identifier-recycled";
+const RISC_SESSIONS_REVOKED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/sessions-revoked";
+const RISC_VERIFICATION: &str = "https://schemas.openid.net/secevent/risc/event-type/verification";
+const OAUTH_TOKENS_REVOKED: &str =
+    "https://schemas.openid.net/secevent/oauth/event-type/tokens-revoked";
+const CAEP_CREDENTIAL_CHANGE: &str =
+    "https://schemas.openid.net/secevent/caep/event-type/credential-change";
+const CAEP_SESSION_REVOKED: &str =
+    "https://schemas.openid.net/secevent/caep/event-type/session-revoked";
+
+new_type![
+    ///
+    /// Security event type identifier (a key of the SET `events` claim), such as
+    /// `https://schemas.openid.net/secevent/risc/event-type/account-disabled`.
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    SecurityEventType(String)
+];
+
+///
+/// Security Event Token (SET) as defined in [RFC 8417](https://tools.ietf.org/html/rfc8417).
+///
+/// Identity providers use SETs to notify relying parties of security-relevant changes to user
+/// accounts, such as those defined by the
+/// [RISC](https://openid.net/specs/openid-risc-profile-specification-1_0.html) and
+/// [CAEP](https://openid.net/specs/openid-caep-specification-1_0.html) profiles (e.g., Google's
+/// Cross-Account Protection). Use [`SecurityEventToken::claims`] to verify the SET, and
+/// [`SecurityEventTokenClaims::security_events`] to interpret its events.
+///
+/// The SET's `jti` claim uniquely identifies it, and applications should use it to detect
+/// duplicate deliveries.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SecurityEventToken<
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(JsonWebToken<JE, JS, JT, SecurityEventTokenClaims, JsonWebTokenJsonPayloadSerde>);
+impl<JE, JS, JT> SecurityEventToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes a new SET containing the specified claims, signed with the specified key and
+    /// signing algorithm.
+    ///
+    /// The SET's `typ` header is set to `secevent+jwt`, as required by
+    /// [`SecurityEventTokenVerifier`].
+    ///
+    pub fn new<JU, K, S>(
+        claims: SecurityEventTokenClaims,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        Ok(Self(JsonWebToken::new_with_type(
+            claims,
+            signing_key,
+            &alg,
+            Some(JsonWebTokenType::security_event_token()),
+        )?))
+    }
+
+    ///
+    /// Returns the JOSE header of the SET without verifying the token's signature.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned header is untrusted and may have been forged or tampered with.
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+
+    ///
+    /// Verifies and returns the SET claims.
+    ///
+    pub fn claims<JU, K>(
+        self,
+        verifier: &SecurityEventTokenVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<SecurityEventTokenClaims, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_claims(self.0)
+    }
+}
+impl<JE, JS, JT> FromStr for SecurityEventToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+
+    ///
+    /// Parses a SET in the JWS Compact Serialization (e.g., the body of an
+    /// `application/secevent+jwt` push delivery) without verifying it.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonWebToken::from_compact(s).map(Self)
+    }
+}
+
+///
+/// Claims of a [`SecurityEventToken`].
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SecurityEventTokenClaims {
+    #[serde(rename = "iss")]
+    issuer: IssuerUrl,
+    #[serde(rename = "aud", deserialize_with = "deserialize_string_or_vec")]
+    audiences: Vec<Audience>,
+    #[serde(rename = "iat", with = "serde_utc_seconds")]
+    issue_time: DateTime<Utc>,
+    #[serde(rename = "jti")]
+    token_id: String,
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(rename = "sub_id", skip_serializing_if = "Option::is_none")]
+    subject_id: Option<SecurityEventSubject>,
+    #[serde(rename = "txn", skip_serializing_if = "Option::is_none")]
+    transaction_id: Option<String>,
+    #[serde(
+        default,
+        rename = "toe",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    event_time: Option<DateTime<Utc>>,
+    events: HashMap<SecurityEventType, serde_json::Value>,
+}
+impl SecurityEventTokenClaims {
+    ///
+    /// Initializes new SET claims without any events.
+    ///
+    pub fn new(
+        issuer: IssuerUrl,
+        audiences: Vec<Audience>,
+        issue_time: DateTime<Utc>,
+        token_id: String,
+    ) -> Self {
+        Self {
+            issuer,
+            audiences,
+            issue_time,
+            token_id,
+            subject: None,
+            subject_id: None,
+            transaction_id: None,
+            event_time: None,
+            events: HashMap::new(),
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[IssuerUrl] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_issue_time -> issue_time[DateTime<Utc>] ["iat"],
+            set_token_id -> token_id[String] ["jti"],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_subject_id -> subject_id[Option<SecurityEventSubject>] ["sub_id"],
+            set_transaction_id -> transaction_id[Option<String>] ["txn"],
+            set_event_time -> event_time[Option<DateTime<Utc>>] ["toe"],
+            set_events -> events[HashMap<SecurityEventType, serde_json::Value>],
+        }
+    ];
+
+    ///
+    /// Adds an event with the specified type and payload to the `events` claim.
+    ///
+    pub fn add_event(mut self, event_type: SecurityEventType, payload: serde_json::Value) -> Self {
+        self.events.insert(event_type, payload);
+        self
+    }
+
+    ///
+    /// Parses the events contained in the `events` claim.
+    ///
+    /// Events without a `subject` member use the SET's `sub_id` claim as their subject, if
+    /// present. Events of types not modeled by [`SecurityEvent`] are returned as
+    /// [`SecurityEvent::Other`]. The order of the returned events is unspecified.
+    ///
+    pub fn security_events(&self) -> Result<Vec<SecurityEvent>, serde_json::Error> {
+        self.events
+            .iter()
+            .map(|(event_type, payload)| {
+                SecurityEvent::from_payload(event_type, payload, self.subject_id.as_ref())
+            })
+            .collect()
+    }
+}
+impl AudiencesClaim for SecurityEventTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(SecurityEventTokenClaims::audiences(self))
+    }
+}
+impl<'a> AudiencesClaim for &'a SecurityEventTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(SecurityEventTokenClaims::audiences(self))
+    }
+}
+impl IssuerClaim for SecurityEventTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(SecurityEventTokenClaims::issuer(self))
+    }
+}
+impl<'a> IssuerClaim for &'a SecurityEventTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(SecurityEventTokenClaims::issuer(self))
+    }
+}
+
+///
+/// Subject of a security event, as defined in
+/// [RFC 9493](https://tools.ietf.org/html/rfc9493).
+///
+/// Only the members used by the common subject identifier formats (e.g., `iss_sub`, `email`, and
+/// `phone_number`) are modeled. The legacy `subject_type` member used by some RISC transmitters
+/// (e.g., `iss-sub`) is accepted in place of `format`.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SecurityEventSubject {
+    #[serde(alias = "subject_type")]
+    format: String,
+    #[serde(rename = "iss", skip_serializing_if = "Option::is_none")]
+    issuer: Option<IssuerUrl>,
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+}
+impl SecurityEventSubject {
+    ///
+    /// Initializes a new subject identifier with the specified format and no other members.
+    ///
+    pub fn new(format: String) -> Self {
+        Self {
+            format,
+            issuer: None,
+            subject: None,
+            email: None,
+            phone_number: None,
+            id: None,
+            uri: None,
+        }
+    }
+
+    ///
+    /// Initializes a new `iss_sub` subject identifier, which identifies the subject by the
+    /// provider's issuer and the subject's `sub` claim.
+    ///
+    pub fn from_issuer_and_subject(issuer: IssuerUrl, subject: SubjectIdentifier) -> Self {
+        Self::new("iss_sub".to_string())
+            .set_issuer(Some(issuer))
+            .set_subject(Some(subject))
+    }
+
+    field_getters_setters![
+        pub self [self] ["member"] {
+            set_format -> format[String],
+            set_issuer -> issuer[Option<IssuerUrl>] ["iss"],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_email -> email[Option<String>],
+            set_phone_number -> phone_number[Option<String>],
+            set_id -> id[Option<String>],
+            set_uri -> uri[Option<String>],
+        }
+    ];
+}
+
+///
+/// Reason an account was disabled, as reported by a RISC `account-disabled` event.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccountDisabledReason {
+    ///
+    /// The account was disabled because it was hijacked.
+    ///
+    Hijacking,
+    ///
+    /// The account was disabled because it was used for bulk (e.g., spam) activity.
+    ///
+    BulkAccount,
+    ///
+    /// Some other reason.
+    ///
+    Other(String),
+}
+impl AccountDisabledReason {
+    fn from_reason(reason: String) -> Self {
+        match reason.as_str() {
+            "hijacking" => AccountDisabledReason::Hijacking,
+            "bulk-account" => AccountDisabledReason::BulkAccount,
+            _ => AccountDisabledReason::Other(reason),
+        }
+    }
+}
+
+///
+/// Security event contained in a [`SecurityEventToken`].
+///
+/// The common events defined by the
+/// [RISC](https://openid.net/specs/openid-risc-profile-specification-1_0.html#event_types) and
+/// [CAEP](https://openid.net/specs/openid-caep-specification-1_0.html#event-types) profiles are
+/// parsed into their own variants. Each variant (other than [`SecurityEvent::Verification`] and
+/// [`SecurityEvent::Other`]) includes the affected subject.
+///
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SecurityEvent {
+    ///
+    /// RISC `account-credential-change-required`: the subject should be required to change their
+    /// credentials (e.g., password) at the next login.
+    ///
+    AccountCredentialChangeRequired(SecurityEventSubject),
+    ///
+    /// RISC `account-disabled`: the account was disabled for the specified reason, if any. Sessions
+    /// for the account should be terminated.
+    ///
+    AccountDisabled(SecurityEventSubject, Option<AccountDisabledReason>),
+    ///
+    /// RISC `account-enabled`: a previously disabled account was re-enabled.
+    ///
+    AccountEnabled(SecurityEventSubject),
+    ///
+    /// RISC `account-purged`: the account was permanently deleted.
+    ///
+    AccountPurged(SecurityEventSubject),
+    ///
+    /// RISC `credential-compromise`: a credential of the specified type (e.g., `password`) was
+    /// compromised.
+    ///
+    CredentialCompromise(SecurityEventSubject, Option<String>),
+    ///
+    /// RISC `identifier-changed`: the subject's identifier (e.g., email address) changed to the
+    /// specified value, if known.
+    ///
+    IdentifierChanged(SecurityEventSubject, Option<String>),
+    ///
+    /// RISC `identifier-recycled`: the subject's identifier now refers to a different account.
+    ///
+    IdentifierRecycled(SecurityEventSubject),
+    ///
+    /// RISC `sessions-revoked`: all of the subject's sessions were revoked.
+    ///
+    SessionsRevoked(SecurityEventSubject),
+    ///
+    /// `tokens-revoked`: all of the subject's OAuth 2.0 tokens were revoked.
+    ///
+    TokensRevoked(SecurityEventSubject),
+    ///
+    /// RISC `verification`: a verification event requested by the receiver, including the
+    /// `state` value supplied in the verification request, if any.
+    ///
+    Verification(Option<String>),
+    ///
+    /// CAEP `session-revoked`: one of the subject's sessions was revoked.
+    ///
+    SessionRevoked(SecurityEventSubject),
+    ///
+    /// CAEP `credential-change`: a credential of the specified type (e.g., `password`) was
+    /// changed as described by the specified change type (e.g., `create` or `revoke`).
+    ///
+    CredentialChange(SecurityEventSubject, Option<String>, Option<String>),
+    ///
+    /// Event of some other type, along with its raw payload.
+    ///
+    Other(SecurityEventType, serde_json::Value),
+}
+impl SecurityEvent {
+    fn from_payload(
+        event_type: &SecurityEventType,
+        payload: &serde_json::Value,
+        default_subject: Option<&SecurityEventSubject>,
+    ) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct RawSecurityEvent {
+            #[serde(default)]
+            subject: Option<SecurityEventSubject>,
+            #[serde(default)]
+            reason: Option<String>,
+            #[serde(default)]
+            credential_type: Option<String>,
+            #[serde(default)]
+            change_type: Option<String>,
+            #[serde(default, rename = "new-value")]
+            new_value: Option<String>,
+            #[serde(default)]
+            state: Option<String>,
+        }
+
+        let raw = || serde_json::from_value::<RawSecurityEvent>(payload.clone());
+        let subject = |raw: &RawSecurityEvent| {
+            raw.subject
+                .clone()
+                .or_else(|| default_subject.cloned())
+                .ok_or_else(|| {
+                    serde_json::Error::custom(format!(
+                        "missing subject for security event `{}`",
+                        event_type.as_str()
+                    ))
+                })
+        };
+        Ok(match event_type.as_str() {
+            RISC_ACCOUNT_CREDENTIAL_CHANGE_REQUIRED => {
+                SecurityEvent::AccountCredentialChangeRequired(subject(&raw()?)?)
+            }
+            RISC_ACCOUNT_DISABLED => {
+                let raw = raw()?;
+                SecurityEvent::AccountDisabled(
+                    subject(&raw)?,
+                    raw.reason.map(AccountDisabledReason::from_reason),
+                )
+            }
+            RISC_ACCOUNT_ENABLED => SecurityEvent::AccountEnabled(subject(&raw()?)?),
+            RISC_ACCOUNT_PURGED => SecurityEvent::AccountPurged(subject(&raw()?)?),
+            RISC_CREDENTIAL_COMPROMISE => {
+                let raw = raw()?;
+                SecurityEvent::CredentialCompromise(subject(&raw)?, raw.credential_type)
+            }
+            RISC_IDENTIFIER_CHANGED => {
+                let raw = raw()?;
+                SecurityEvent::IdentifierChanged(subject(&raw)?, raw.new_value)
+            }
+            RISC_IDENTIFIER_RECYCLED => SecurityEvent::IdentifierRecycled(subject(&raw()?)?),
+            RISC_SESSIONS_REVOKED => SecurityEvent::SessionsRevoked(subject(&raw()?)?),
+            RISC_VERIFICATION => SecurityEvent::Verification(raw()?.state),
+            OAUTH_TOKENS_REVOKED => SecurityEvent::TokensRevoked(subject(&raw()?)?),
+            CAEP_SESSION_REVOKED => SecurityEvent::SessionRevoked(subject(&raw()?)?),
+            CAEP_CREDENTIAL_CHANGE => {
+                let raw = raw()?;
+                SecurityEvent::CredentialChange(
+                    subject(&raw)?,
+                    raw.credential_type,
+                    raw.change_type,
+                )
+            }
+            _ => SecurityEvent::Other(event_type.clone(), payload.clone()),
+        })
+    }
+
+    ///
+    /// Returns the subject of the event, if any.
+    ///
+    pub fn subject(&self) -> Option<&SecurityEventSubject> {
+        match self {
+            SecurityEvent::AccountCredentialChangeRequired(subject)
+            | SecurityEvent::AccountDisabled(subject, _)
+            | SecurityEvent::AccountEnabled(subject)
+            | SecurityEvent::AccountPurged(subject)
+            | SecurityEvent::CredentialCompromise(subject, _)
+            | SecurityEvent::IdentifierChanged(subject, _)
+            | SecurityEvent::IdentifierRecycled(subject)
+            | SecurityEvent::SessionsRevoked(subject)
+            | SecurityEvent::TokensRevoked(subject)
+            | SecurityEvent::SessionRevoked(subject)
+            | SecurityEvent::CredentialChange(subject, _, _) => Some(subject),
+            SecurityEvent::Verification(_) | SecurityEvent::Other(_, _) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::{
+        CoreJsonWebKeySet, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
+        CoreSecurityEventToken, CoreSecurityEventTokenVerifier,
+    };
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::jwt::JsonWebTokenType;
+    use crate::{
+        AccountDisabledReason, Audience, ClaimsVerificationError, FixedClock, IssuerUrl,
+        PrivateSigningKey, SecurityEvent, SecurityEventSubject, SecurityEventTokenClaims,
+        SecurityEventType, SubjectIdentifier,
+    };
+
+    #[test]
+    fn test_security_event_token() {
+        // Example account-disabled event delivered by Google's RISC service.
+        let claims: SecurityEventTokenClaims = serde_json::from_str(
+            "{
+                \"iss\": \"https://accounts.google.com/\",
+                \"aud\": \"123456789-abcedfgh.apps.googleusercontent.com\",
+                \"iat\": 1508184845,
+                \"jti\": \"756E69717565206964656E746966696572\",
+                \"events\": {
+                    \"https://schemas.openid.net/secevent/risc/event-type/account-disabled\": {
+                        \"subject\": {
+                            \"subject_type\": \"iss-sub\",
+                            \"iss\": \"https://accounts.google.com/\",
+                            \"sub\": \"7375626A656374\"
+                        },
+                        \"reason\": \"hijacking\"
+                    }
+                }
+            }",
+        )
+        .expect("failed to deserialize");
+        let subject = SecurityEventSubject::new("iss-sub".to_string())
+            .set_issuer(Some(
+                IssuerUrl::new("https://accounts.google.com/".to_string()).unwrap(),
+            ))
+            .set_subject(Some(SubjectIdentifier::new("7375626A656374".to_string())));
+        assert_eq!(
+            claims.security_events().unwrap(),
+            vec![SecurityEvent::AccountDisabled(
+                subject.clone(),
+                Some(AccountDisabledReason::Hijacking)
+            )]
+        );
+
+        // Events without a subject use the SET's sub_id claim.
+        let other_type = SecurityEventType::new("https://example.com/event-type/other".to_string());
+        let claims = SecurityEventTokenClaims::new(
+            IssuerUrl::new("https://accounts.google.com/".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1508184845, 0),
+            "756E69717565206964656E746966696572".to_string(),
+        )
+        .set_subject_id(Some(subject.clone()))
+        .add_event(
+            SecurityEventType::new(
+                "https://schemas.openid.net/secevent/caep/event-type/session-revoked".to_string(),
+            ),
+            serde_json::json!({}),
+        );
+        assert_eq!(
+            claims.security_events().unwrap(),
+            vec![SecurityEvent::SessionRevoked(subject.clone())]
+        );
+        let claims = claims
+            .set_subject_id(None)
+            .add_event(other_type.clone(), serde_json::json!({"foo": "bar"}));
+        assert!(claims.security_events().is_err());
+        let claims = claims.set_events(
+            vec![(other_type.clone(), serde_json::json!({"foo": "bar"}))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(
+            claims.security_events().unwrap(),
+            vec![SecurityEvent::Other(
+                other_type,
+                serde_json::json!({"foo": "bar"})
+            )]
+        );
+        assert_eq!(claims.security_events().unwrap()[0].subject(), None);
+
+        // Round trip through a signed SET.
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let serialized = serde_json::to_value(
+            CoreSecurityEventToken::new(
+                claims.clone(),
+                &signing_key,
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let set: CoreSecurityEventToken = serialized.as_str().unwrap().parse().unwrap();
+
+        let clock = FixedClock::new(Utc.timestamp(1508184900, 0));
+        let verifier = CoreSecurityEventTokenVerifier::new(
+            Audience::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://accounts.google.com/".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+        )
+        .set_clock(clock.clone());
+        assert_eq!(set.clone().claims(&verifier).unwrap(), claims);
+
+        // SETs issued in the future are rejected.
+        clock.set(Utc.timestamp(1508184000, 0));
+        match set.clone().claims(&verifier) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // SETs without any events are rejected.
+        clock.set(Utc.timestamp(1508184900, 0));
+        let empty_set = CoreSecurityEventToken::new(
+            claims.set_events(Default::default()),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        match empty_set.claims(&verifier) {
+            Err(ClaimsVerificationError::Other(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_security_event_token_type() {
+        let claims = SecurityEventTokenClaims::new(
+            IssuerUrl::new("https://accounts.google.com/".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp(1508184845, 0),
+            "756E69717565206964656E746966696572".to_string(),
+        )
+        .add_event(
+            SecurityEventType::new(
+                "https://schemas.openid.net/secevent/risc/event-type/account-enabled".to_string(),
+            ),
+            serde_json::json!({
+                "subject": {
+                    "subject_type": "iss-sub",
+                    "iss": "https://accounts.google.com/",
+                    "sub": "7375626A656374"
+                }
+            }),
+        );
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let set = CoreSecurityEventToken::new(
+            claims.clone(),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        assert_eq!(
+            set.unverified_header().typ,
+            Some(JsonWebTokenType::security_event_token())
+        );
+
+        // Verifiers that require the `typ` header accept the signed SET.
+        let verifier = CoreSecurityEventTokenVerifier::new(
+            Audience::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://accounts.google.com/".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+        )
+        .require_jwt_type(true)
+        .set_clock(FixedClock::new(Utc.timestamp(1508184900, 0)));
+        assert_eq!(set.claims(&verifier).unwrap(), claims);
+    }
+}
@@ -0,0 +1,63 @@
+// This module is also compiled into the openidconnect-derive crate (via a symlink) so that the
+// AdditionalClaims derive macro rejects exactly the claims parsed by `IdTokenClaims` and
+// `UserInfoClaims`. It must not depend on anything else in this crate.
+
+// Claims defined by OpenID Connect Core (or RFC 7519) that don't support language tags.
+const STANDARD_CLAIMS: &[&str] = &[
+    "acr",
+    "address",
+    "amr",
+    "at_hash",
+    "aud",
+    "auth_time",
+    "azp",
+    "birthday",
+    "c_hash",
+    "email",
+    "email_verified",
+    "exp",
+    "gender",
+    "iat",
+    "iss",
+    "locale",
+    "nbf",
+    "nonce",
+    "phone_number",
+    "phone_number_verified",
+    "preferred_username",
+    "sid",
+    "sub",
+    "updated_at",
+    "vot",
+    "vtm",
+    "zoneinfo",
+];
+
+// Standard claims that may also be suffixed with a language tag (e.g., `name#ja-Kana-JP`).
+const LOCALIZED_CLAIMS: &[&str] = &[
+    "family_name",
+    "given_name",
+    "middle_name",
+    "name",
+    "nickname",
+    "picture",
+    "profile",
+    "website",
+];
+
+// Returns whether the claim (including any language tag) is defined by OpenID Connect Core (or
+// RFC 7519).
+pub(crate) fn is_standard_claim(claim: &str) -> bool {
+    let mut lang_tag_sep = claim.splitn(2, '#');
+
+    // String::splitn(2) always returns at least one element.
+    let claim_name = lang_tag_sep.next().unwrap();
+
+    let language_tag = lang_tag_sep
+        .next()
+        .filter(|language_tag| !language_tag.is_empty());
+    match language_tag {
+        Some(_) => LOCALIZED_CLAIMS.contains(&claim_name),
+        None => STANDARD_CLAIMS.contains(&claim_name) || LOCALIZED_CLAIMS.contains(&claim_name),
+    }
+}
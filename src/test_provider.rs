@@ -0,0 +1,1130 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use chrono::Duration;
+use failure::Fail;
+use futures::executor::block_on;
+use rand_core::OsRng;
+use rsa::pkcs1::{EncodeRsaPrivateKey, LineEnding};
+use rsa::RsaPrivateKey;
+use serde::Serialize;
+
+use crate::clock::{Clock, SystemClock};
+use crate::core::{
+    base64_url_safe_no_pad, CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreJsonWebKeySet,
+    CoreJwsSigningAlgorithm, CoreLogoutToken, CoreProviderMetadata, CoreResponseType,
+    CoreRsaPrivateSigningKey, CoreSubjectIdentifierType, CoreUserInfoClaims,
+    CoreUserInfoJsonWebToken,
+};
+use crate::crypto::sha256;
+use crate::http_client::{AsyncHttpClient, HttpClient};
+use crate::http_types::headers::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE};
+use crate::http_types::{Method, Request, Response, StatusCode};
+use crate::http_utils::{MIME_TYPE_JSON, MIME_TYPE_JWT};
+use crate::url::{form_urlencoded, Url};
+use crate::{
+    AccessToken, Audience, AuthUrl, ClientId, ClientSecret, CsrfToken, EmptyAdditionalClaims,
+    EmptyAdditionalProviderMetadata, IssuerUrl, JsonWebKeyId, JsonWebKeySetUrl, JsonWebTokenError,
+    LogoutTokenClaims, Nonce, PrivateSigningKey, ResponseTypes, SessionId, StandardClaims,
+    SubjectIdentifier, TokenUrl, UserInfoUrl, PKCE_METHOD_S256,
+};
+
+const AUTHORIZE_PATH: &str = "authorize";
+const DISCOVERY_PATH: &str = ".well-known/openid-configuration";
+const JWKS_PATH: &str = "jwks";
+const TOKEN_PATH: &str = "token";
+const USER_INFO_PATH: &str = "userinfo";
+
+// Signature substituted for the real one when simulating `TestProviderFault::InvalidSignature`
+// (the base64url encoding of "invalid_signature").
+const INVALID_SIGNATURE: &str = "aW52YWxpZF9zaWduYXR1cmU";
+
+// Size of the RSA key generated to sign the providers' tokens.
+const SIGNING_KEY_BITS: usize = 2048;
+
+// PKCE code challenge method used if an authorization request doesn't specify one (see
+// RFC 7636, Section 4.3).
+const PKCE_METHOD_PLAIN: &str = "plain";
+
+// RSA key material shared by all providers in the process, since generating it is slow. Each
+// provider still uses a distinct key ID (see `new_signing_key`).
+static SIGNING_KEY_PEM: OnceLock<String> = OnceLock::new();
+
+///
+/// Failure simulated by a [`TestProvider`].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum TestProviderFault {
+    ///
+    /// ID tokens are issued with an expiration time in the past.
+    ///
+    ExpiredIdToken,
+    ///
+    /// ID tokens contain a nonce other than the one sent in the authorization request.
+    ///
+    WrongNonce,
+    ///
+    /// ID tokens and signed user info responses have an invalid signature.
+    ///
+    InvalidSignature,
+    ///
+    /// Token requests fail with an `invalid_grant` error.
+    ///
+    InvalidGrant,
+    ///
+    /// User info requests fail with an `invalid_token` error.
+    ///
+    InvalidAccessToken,
+    ///
+    /// All requests fail with a `500 Internal Server Error` response.
+    ///
+    ServerError,
+}
+
+///
+/// Error returned by a [`TestProvider`] when used as an HTTP client.
+///
+/// Requests to endpoints that the provider doesn't serve result in a `404 Not Found` response
+/// rather than an error, so this error only occurs if the request body can't be read.
+///
+#[derive(Debug, Fail)]
+#[fail(display = "Failed to read request body: {}", _0)]
+pub struct TestProviderError(String);
+
+// PKCE code challenge sent in an authorization request.
+struct CodeChallenge {
+    challenge: String,
+    method: String,
+}
+
+// An authorization code that has been issued but not yet exchanged.
+struct PendingCode {
+    code_challenge: Option<CodeChallenge>,
+    nonce: Option<Nonce>,
+    redirect_uri: String,
+}
+
+#[derive(Default)]
+struct TestProviderState {
+    access_tokens: Vec<String>,
+    codes: HashMap<String, PendingCode>,
+    fault: Option<TestProviderFault>,
+    next_id: u64,
+    refresh_tokens: Vec<String>,
+}
+impl TestProviderState {
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", prefix, self.next_id)
+    }
+}
+
+///
+/// In-process mock OpenID Connect Provider for testing relying parties.
+///
+/// The provider is used as an HTTP client (it implements both [`AsyncHttpClient`] and
+/// [`HttpClient`]), which routes each request to the provider's simulated endpoints rather than
+/// sending it over the network. The following endpoints are served relative to the issuer URL:
+///  * `/authorize`: authorization endpoint, which authenticates the user immediately and redirects
+///    (`302 Found`) to the `redirect_uri` with an authorization code
+///  * `/.well-known/openid-configuration`: OpenID Connect Discovery
+///  * `/jwks`: JSON Web Key Set containing the provider's signing key
+///  * `/token`: authorization code and refresh token grants, which return signed ID tokens (see
+///    below for the checks performed)
+///  * `/userinfo`: user info, returned as a signed JWT if the request's `Accept` header includes
+///    `application/jwt`, or as JSON otherwise
+///
+/// Since no browser is involved, [`TestProvider::authorize`] follows the authorization URL
+/// generated by the relying party and returns the URL to which the provider redirects the user.
+///
+/// Failures such as expired ID tokens or mismatched nonces may be simulated via
+/// [`TestProvider::set_fault`].
+///
+/// The token endpoint rejects authorization code grants whose `redirect_uri` parameter isn't
+/// identical to the one in the authorization request, and enforces PKCE using either the `S256`
+/// or `plain` code challenge method (authorization requests with other methods are redirected
+/// back with an `invalid_request` error). Clients must authenticate to the token endpoint using
+/// the `client_secret_basic` or `client_secret_post` method, or the `none` method (i.e., by
+/// sending only the `client_id` parameter) if no secret has been configured via
+/// [`TestProvider::set_client_secret`]. The JWT-based `client_secret_jwt` and `private_key_jwt`
+/// methods aren't supported, and such requests fail with an `invalid_client` error.
+///
+/// The RSA key material used to sign tokens is generated once per process and shared by all
+/// providers, but each provider uses a distinct random key ID, so tokens issued by one provider
+/// don't match any key in another provider's JSON Web Key Set. Use
+/// [`TestProvider::set_signing_key`] to sign tokens using a different key.
+///
+/// # Security Warning
+///
+/// The provider authenticates any user without checking credentials. It must only be used for
+/// testing.
+///
+#[derive(Clone)]
+pub struct TestProvider {
+    client_id: ClientId,
+    client_secret: Option<ClientSecret>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    id_token_lifetime: Duration,
+    issuer: IssuerUrl,
+    signing_key: Arc<CoreRsaPrivateSigningKey>,
+    standard_claims: StandardClaims<CoreGenderClaim>,
+    state: Arc<Mutex<TestProviderState>>,
+}
+impl TestProvider {
+    ///
+    /// Instantiates a provider with the specified issuer URL that issues tokens to the specified
+    /// client.
+    ///
+    /// By default, ID tokens are valid for one hour and identify the subject `test-subject`, and
+    /// tokens are signed using a 2048-bit RSA key with a random key ID.
+    ///
+    pub fn new(issuer: IssuerUrl, client_id: ClientId) -> Self {
+        Self {
+            client_id,
+            client_secret: None,
+            clock: Arc::new(SystemClock),
+            id_token_lifetime: Duration::hours(1),
+            issuer,
+            signing_key: Arc::new(new_signing_key()),
+            standard_claims: StandardClaims::new(SubjectIdentifier::new(
+                "test-subject".to_string(),
+            )),
+            state: Arc::new(Mutex::new(TestProviderState::default())),
+        }
+    }
+
+    ///
+    /// Specifies the secret with which the client must authenticate to the token endpoint.
+    ///
+    /// If no secret is specified (the default), the token endpoint only checks the client ID and
+    /// accepts any client secret.
+    ///
+    pub fn set_client_secret(mut self, client_secret: Option<ClientSecret>) -> Self {
+        self.client_secret = client_secret;
+        self
+    }
+
+    ///
+    /// Specifies the key used to sign ID tokens and user info responses.
+    ///
+    pub fn set_signing_key(mut self, signing_key: CoreRsaPrivateSigningKey) -> Self {
+        self.signing_key = Arc::new(signing_key);
+        self
+    }
+
+    ///
+    /// Specifies the claims (including the subject) returned for the authenticated user.
+    ///
+    pub fn set_standard_claims(mut self, standard_claims: StandardClaims<CoreGenderClaim>) -> Self {
+        self.standard_claims = standard_claims;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time used for the `iat` and `exp` claims.
+    ///
+    pub fn set_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    ///
+    /// Specifies how long ID tokens and access tokens remain valid.
+    ///
+    pub fn set_id_token_lifetime(mut self, id_token_lifetime: Duration) -> Self {
+        self.id_token_lifetime = id_token_lifetime;
+        self
+    }
+
+    ///
+    /// Specifies the failure to simulate for subsequent requests, or `None` to stop simulating
+    /// failures.
+    ///
+    /// Clones of this provider share the same fault, so a fault may be injected after passing a
+    /// clone to the code under test.
+    ///
+    pub fn set_fault(&self, fault: Option<TestProviderFault>) {
+        self.lock().fault = fault;
+    }
+
+    ///
+    /// Returns the provider's issuer URL.
+    ///
+    pub fn issuer(&self) -> &IssuerUrl {
+        &self.issuer
+    }
+
+    ///
+    /// Returns the metadata served by the provider's discovery endpoint, including its JSON Web
+    /// Key Set.
+    ///
+    pub fn provider_metadata(&self) -> CoreProviderMetadata {
+        CoreProviderMetadata::new(
+            self.issuer.clone(),
+            AuthUrl::from_url(self.endpoint(AUTHORIZE_PATH)),
+            JsonWebKeySetUrl::from_url(self.endpoint(JWKS_PATH)),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_token_endpoint(Some(TokenUrl::from_url(self.endpoint(TOKEN_PATH))))
+        .set_userinfo_endpoint(Some(UserInfoUrl::from_url(self.endpoint(USER_INFO_PATH))))
+        .set_userinfo_signing_alg_values_supported(Some(vec![
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ]))
+        .set_jwks(self.jwks())
+    }
+
+    ///
+    /// Returns the JSON Web Key Set served by the provider.
+    ///
+    pub fn jwks(&self) -> CoreJsonWebKeySet {
+        CoreJsonWebKeySet::new(vec![self.signing_key.as_verification_key()])
+    }
+
+    ///
+    /// Simulates the user authenticating at the authorization endpoint.
+    ///
+    /// Sends a request for the authorization URL generated by the relying party (e.g., by
+    /// [`Client::authorize_url`](crate::Client::authorize_url)) to the provider's authorization
+    /// endpoint, and returns the URL to which the provider redirects the user, which includes an
+    /// authorization code and the `state` parameter. Returns `None` if the provider doesn't
+    /// redirect the user (e.g., because the URL doesn't include a `redirect_uri` parameter).
+    ///
+    pub fn authorize(&self, authorization_url: &Url) -> Option<Url> {
+        let response =
+            HttpClient::call(self, Request::new(Method::Get, authorization_url.clone())).ok()?;
+        if response.status() != StatusCode::Found {
+            return None;
+        }
+        Url::parse(response.header(LOCATION)?.last().as_str()).ok()
+    }
+
+    ///
+    /// Returns a serialized back-channel logout token for the specified subject and/or session
+    /// ID, which may be posted to the relying party's `backchannel_logout_uri` as the
+    /// `logout_token` form parameter.
+    ///
+    /// Each logout token has a unique `jti` claim and is valid for two minutes. The
+    /// [`TestProviderFault::InvalidSignature`] fault also applies to logout tokens.
+    ///
+    pub fn logout_token(
+        &self,
+        subject: Option<SubjectIdentifier>,
+        session_id: Option<SessionId>,
+    ) -> Result<String, JsonWebTokenError> {
+        let (token_id, fault) = {
+            let mut state = self.lock();
+            (state.next_id("logout"), state.fault)
+        };
+        let issue_time = self.clock.now();
+        let claims = LogoutTokenClaims::new(
+            self.issuer.clone(),
+            vec![Audience::new((*self.client_id).clone())],
+            issue_time,
+            issue_time + Duration::minutes(2),
+            token_id,
+        )
+        .set_subject(subject)
+        .set_session_id(session_id);
+        let logout_token = CoreLogoutToken::new(
+            claims,
+            &*self.signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )?;
+        Ok(serialize_jwt(&logout_token, fault))
+    }
+
+    fn lock(&self) -> MutexGuard<TestProviderState> {
+        // The state remains consistent even if a previous holder panicked.
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn endpoint(&self, path: &str) -> Url {
+        let mut url = self.issuer.url().clone();
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        url.join(path).expect("endpoint path should be valid")
+    }
+
+    async fn handle(self, mut request: Request) -> Result<Response, TestProviderError> {
+        let body = request
+            .body_string()
+            .await
+            .map_err(|err| TestProviderError(err.to_string()))?;
+
+        let fault = self.lock().fault;
+        if fault == Some(TestProviderFault::ServerError) {
+            return Ok(Response::new(StatusCode::InternalServerError));
+        }
+
+        let url = request.url();
+        let endpoint = |path: &str| url.path() == self.endpoint(path).path();
+        let response = if request.method() == Method::Get && endpoint(AUTHORIZE_PATH) {
+            self.authorization_response(url)
+        } else if request.method() == Method::Get && endpoint(DISCOVERY_PATH) {
+            json_response(StatusCode::Ok, &self.provider_metadata())
+        } else if request.method() == Method::Get && endpoint(JWKS_PATH) {
+            json_response(StatusCode::Ok, &self.jwks())
+        } else if request.method() == Method::Post && endpoint(TOKEN_PATH) {
+            self.token_response(&request, &body, fault)
+        } else if endpoint(USER_INFO_PATH) {
+            self.user_info_response(&request, &body, fault)
+        } else {
+            Response::new(StatusCode::NotFound)
+        };
+        Ok(response)
+    }
+
+    fn authorization_response(&self, authorization_url: &Url) -> Response {
+        let param = |name: &str| {
+            authorization_url
+                .query_pairs()
+                .find(|(param_name, _)| param_name == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        let (redirect_uri, redirect_url) = match param("redirect_uri").and_then(|uri| {
+            Url::parse(&uri)
+                .ok()
+                .map(|redirect_url| (uri, redirect_url))
+        }) {
+            Some(redirect_uri) => redirect_uri,
+            None => {
+                return json_response(
+                    StatusCode::BadRequest,
+                    &serde_json::json!({ "error": "invalid_request" }),
+                )
+            }
+        };
+        let code_challenge = match param("code_challenge") {
+            Some(challenge) => {
+                let method =
+                    param("code_challenge_method").unwrap_or_else(|| PKCE_METHOD_PLAIN.to_string());
+                if method != PKCE_METHOD_PLAIN && method != PKCE_METHOD_S256 {
+                    return redirect_response(
+                        redirect_url,
+                        ("error", "invalid_request"),
+                        param("state"),
+                    );
+                }
+                Some(CodeChallenge { challenge, method })
+            }
+            None => None,
+        };
+        let code = {
+            let mut state = self.lock();
+            let code = state.next_id("code");
+            state.codes.insert(
+                code.clone(),
+                PendingCode {
+                    code_challenge,
+                    nonce: param("nonce").map(Nonce::new),
+                    redirect_uri,
+                },
+            );
+            code
+        };
+
+        redirect_response(redirect_url, ("code", &code), param("state"))
+    }
+
+    fn token_response(
+        &self,
+        request: &Request,
+        body: &str,
+        fault: Option<TestProviderFault>,
+    ) -> Response {
+        let params = form_params(body);
+        let param = |name: &str| params.get(name).map(String::as_str);
+
+        if !self.is_authenticated_client(request, &params) {
+            let mut response = json_response(
+                StatusCode::Unauthorized,
+                &serde_json::json!({ "error": "invalid_client" }),
+            );
+            response.insert_header(WWW_AUTHENTICATE, "Basic");
+            return response;
+        }
+
+        let mut state = self.lock();
+        let nonce = match (param("grant_type"), fault) {
+            (_, Some(TestProviderFault::InvalidGrant)) => return invalid_grant(),
+            (Some("authorization_code"), _) => {
+                let pending_code = match param("code").and_then(|code| state.codes.remove(code)) {
+                    Some(pending_code) => pending_code,
+                    None => return invalid_grant(),
+                };
+                // The redirect URI must be identical to the one included in the authorization
+                // request (see RFC 6749, Section 4.1.3).
+                if param("redirect_uri") != Some(pending_code.redirect_uri.as_str()) {
+                    return invalid_grant();
+                }
+                if let Some(ref code_challenge) = pending_code.code_challenge {
+                    let code_verifier = param("code_verifier").unwrap_or_default();
+                    let expected_challenge = if code_challenge.method == PKCE_METHOD_S256 {
+                        base64::encode_config(
+                            sha256(code_verifier.as_bytes()),
+                            base64_url_safe_no_pad(),
+                        )
+                    } else {
+                        code_verifier.to_string()
+                    };
+                    if code_challenge.challenge != expected_challenge {
+                        return invalid_grant();
+                    }
+                }
+                pending_code.nonce
+            }
+            (Some("refresh_token"), _) => {
+                let refresh_token = param("refresh_token").unwrap_or_default();
+                match state
+                    .refresh_tokens
+                    .iter()
+                    .position(|token| token == refresh_token)
+                {
+                    Some(index) => {
+                        state.refresh_tokens.remove(index);
+                    }
+                    None => return invalid_grant(),
+                }
+                None
+            }
+            _ => {
+                return json_response(
+                    StatusCode::BadRequest,
+                    &serde_json::json!({ "error": "unsupported_grant_type" }),
+                )
+            }
+        };
+
+        let access_token = state.next_id("access");
+        let refresh_token = state.next_id("refresh");
+        state.access_tokens.push(access_token.clone());
+        state.refresh_tokens.push(refresh_token.clone());
+        drop(state);
+
+        let nonce = match fault {
+            Some(TestProviderFault::WrongNonce) => Some(Nonce::new("wrong-nonce".to_string())),
+            _ => nonce,
+        };
+        let id_token = match self.id_token(&AccessToken::new(access_token.clone()), nonce, fault) {
+            Ok(id_token) => id_token,
+            Err(response) => return response,
+        };
+
+        json_response(
+            StatusCode::Ok,
+            &serde_json::json!({
+                "access_token": access_token,
+                "token_type": "bearer",
+                "expires_in": self.id_token_lifetime.num_seconds(),
+                "refresh_token": refresh_token,
+                "id_token": id_token,
+            }),
+        )
+    }
+
+    // Authenticates the client using the `client_secret_basic`, `client_secret_post`, or `none`
+    // method. Requests using more than one method are rejected (see RFC 6749, Section 2.3).
+    fn is_authenticated_client(&self, request: &Request, params: &HashMap<String, String>) -> bool {
+        let param = |name: &str| params.get(name);
+        if param("client_assertion").is_some() {
+            return false;
+        }
+        let (client_id, client_secret) = match request.header(AUTHORIZATION) {
+            Some(values) if param("client_secret").is_none() => {
+                match basic_credentials(values.last().as_str()) {
+                    Some((client_id, client_secret)) => (Some(client_id), Some(client_secret)),
+                    None => return false,
+                }
+            }
+            Some(_) => return false,
+            None => (param("client_id").cloned(), param("client_secret").cloned()),
+        };
+
+        client_id.as_deref() == Some(self.client_id.as_str())
+            && self.client_secret.as_ref().map_or(true, |expected_secret| {
+                client_secret.as_deref() == Some(expected_secret.secret().as_str())
+            })
+    }
+
+    fn id_token(
+        &self,
+        access_token: &AccessToken,
+        nonce: Option<Nonce>,
+        fault: Option<TestProviderFault>,
+    ) -> Result<String, Response> {
+        // When simulating expired tokens, the ID token is issued such that it expired one minute
+        // ago.
+        let issue_time = match fault {
+            Some(TestProviderFault::ExpiredIdToken) => {
+                self.clock.now() - self.id_token_lifetime - Duration::minutes(1)
+            }
+            _ => self.clock.now(),
+        };
+        let claims = CoreIdTokenClaims::new(
+            self.issuer.clone(),
+            vec![Audience::new((*self.client_id).clone())],
+            issue_time + self.id_token_lifetime,
+            issue_time,
+            self.standard_claims.clone(),
+            EmptyAdditionalClaims {},
+        )
+        .set_nonce(nonce);
+        let id_token = CoreIdToken::new(
+            claims,
+            &*self.signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            Some(access_token),
+            None,
+        )
+        .map_err(|_| Response::new(StatusCode::InternalServerError))?;
+        Ok(serialize_jwt(&id_token, fault))
+    }
+
+    fn user_info_response(
+        &self,
+        request: &Request,
+        body: &str,
+        fault: Option<TestProviderFault>,
+    ) -> Response {
+        let bearer_token = request
+            .header(AUTHORIZATION)
+            .and_then(|values| {
+                let value = values.last().as_str();
+                if value.len() > 7 && value[..7].eq_ignore_ascii_case("bearer ") {
+                    Some(value[7..].to_string())
+                } else {
+                    None
+                }
+            })
+            .or_else(|| form_params(body).remove("access_token"));
+        let is_valid_token =
+            bearer_token.map_or(false, |token| self.lock().access_tokens.contains(&token));
+        if !is_valid_token || fault == Some(TestProviderFault::InvalidAccessToken) {
+            let mut response = Response::new(StatusCode::Unauthorized);
+            response.insert_header(WWW_AUTHENTICATE, "Bearer error=\"invalid_token\"");
+            return response;
+        }
+
+        let claims =
+            CoreUserInfoClaims::new(self.standard_claims.clone(), EmptyAdditionalClaims {});
+        let accepts_jwt = request.header(ACCEPT).map_or(false, |values| {
+            values
+                .iter()
+                .any(|value| value.as_str().contains(MIME_TYPE_JWT))
+        });
+        if !accepts_jwt {
+            return json_response(StatusCode::Ok, &claims);
+        }
+
+        let claims = claims
+            .set_issuer(Some(self.issuer.clone()))
+            .set_audiences(Some(vec![Audience::new((*self.client_id).clone())]));
+        match CoreUserInfoJsonWebToken::new(
+            claims,
+            &*self.signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ) {
+            Ok(jwt) => {
+                let mut response = Response::new(StatusCode::Ok);
+                response.insert_header(CONTENT_TYPE, MIME_TYPE_JWT);
+                response.set_body(serialize_jwt(&jwt, fault));
+                response
+            }
+            Err(_) => Response::new(StatusCode::InternalServerError),
+        }
+    }
+}
+impl Debug for TestProvider {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("TestProvider")
+            .field("client_id", &self.client_id)
+            .field("id_token_lifetime", &self.id_token_lifetime)
+            .field("issuer", &self.issuer)
+            .field("fault", &self.lock().fault)
+            .finish()
+    }
+}
+impl AsyncHttpClient for TestProvider {
+    type Error = TestProviderError;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, TestProviderError>>>>;
+
+    fn call(&self, request: Request) -> Self::Future {
+        Box::pin(self.clone().handle(request))
+    }
+}
+impl HttpClient for TestProvider {
+    type Error = TestProviderError;
+
+    fn call(&self, request: Request) -> Result<Response, Self::Error> {
+        block_on(self.clone().handle(request))
+    }
+}
+
+// Returns an RSA signing key with a random key ID. The key material is generated the first time
+// this is called and then reused.
+fn new_signing_key() -> CoreRsaPrivateSigningKey {
+    let pem = SIGNING_KEY_PEM.get_or_init(|| {
+        RsaPrivateKey::new(&mut OsRng, SIGNING_KEY_BITS)
+            .expect("failed to generate signing key")
+            .to_pkcs1_pem(LineEnding::LF)
+            .expect("failed to encode signing key")
+            .to_string()
+    });
+    CoreRsaPrivateSigningKey::from_pem(
+        pem,
+        Some(JsonWebKeyId::new(CsrfToken::new_random().secret().clone())),
+    )
+    .expect("generated signing key should be valid")
+}
+
+// Parses the client ID and secret from an `Authorization` header using the `Basic` scheme. Each
+// is form-urlencoded before being base64-encoded (see RFC 6749, Section 2.3.1).
+fn basic_credentials(authorization: &str) -> Option<(String, String)> {
+    if authorization.len() <= 6 || !authorization[..6].eq_ignore_ascii_case("basic ") {
+        return None;
+    }
+    let credentials = String::from_utf8(base64::decode(&authorization[6..]).ok()?).ok()?;
+    let decode = |value: &str| {
+        form_urlencoded::parse(value.as_bytes())
+            .next()
+            .map_or_else(String::new, |(decoded, _)| decoded.into_owned())
+    };
+    let mut credentials = credentials.splitn(2, ':');
+    Some((decode(credentials.next()?), decode(credentials.next()?)))
+}
+
+fn form_params(body: &str) -> HashMap<String, String> {
+    form_urlencoded::parse(body.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+// Redirects the user agent to the client's redirect URI with the specified parameter (i.e., an
+// authorization code or error) and the `state` parameter, if any.
+fn redirect_response(
+    mut redirect_url: Url,
+    param: (&str, &str),
+    state: Option<String>,
+) -> Response {
+    {
+        let mut query = redirect_url.query_pairs_mut();
+        query.append_pair(param.0, param.1);
+        if let Some(state) = state {
+            query.append_pair("state", &state);
+        }
+    }
+    let mut response = Response::new(StatusCode::Found);
+    response.insert_header(LOCATION, redirect_url.as_str());
+    response
+}
+
+fn invalid_grant() -> Response {
+    json_response(
+        StatusCode::BadRequest,
+        &serde_json::json!({ "error": "invalid_grant" }),
+    )
+}
+
+fn json_response<T>(status: StatusCode, body: &T) -> Response
+where
+    T: Serialize,
+{
+    let mut response = Response::new(status);
+    response.insert_header(CONTENT_TYPE, MIME_TYPE_JSON);
+    response.set_body(serde_json::to_vec(body).expect("failed to serialize response"));
+    response
+}
+
+// Serializes a signed JWT into its compact form, replacing its signature if simulating
+// `TestProviderFault::InvalidSignature`.
+fn serialize_jwt<T>(jwt: &T, fault: Option<TestProviderFault>) -> String
+where
+    T: Serialize,
+{
+    let serialized = serde_json::to_value(jwt)
+        .ok()
+        .and_then(|value| value.as_str().map(ToOwned::to_owned))
+        .expect("JWT should serialize to a string");
+    if fault == Some(TestProviderFault::InvalidSignature) {
+        let signing_input = &serialized[..serialized.rfind('.').expect("JWT should have 3 parts")];
+        format!("{}.{}", signing_input, INVALID_SIGNATURE)
+    } else {
+        serialized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use url::Url;
+
+    use crate::core::{
+        base64_url_safe_no_pad, CoreClient, CoreLoginManager, CoreLogoutToken,
+        CoreProviderMetadata, CoreUserInfoClaims,
+    };
+    use crate::crypto::sha256;
+    use crate::http_client::HttpClient;
+    use crate::http_types::{Method, Request, StatusCode};
+    use crate::login_state::InMemoryStateStore;
+    use crate::url::form_urlencoded;
+    use crate::{
+        Audience, ClaimsVerificationError, ClientId, ClientSecret, IssuerUrl, JsonWebTokenError,
+        JsonWebTokenType, LoginManagerError, OAuth2TokenResponse, RedirectUrl, SessionId,
+        SubjectIdentifier,
+    };
+
+    use super::{TestProvider, TestProviderFault};
+
+    fn new_provider() -> TestProvider {
+        TestProvider::new(
+            IssuerUrl::new("https://provider.example".to_string()).unwrap(),
+            ClientId::new("aaa".to_string()),
+        )
+    }
+
+    fn new_login_manager(provider: &TestProvider) -> CoreLoginManager {
+        let provider_metadata =
+            CoreProviderMetadata::discover_blocking(provider.issuer().clone(), provider).unwrap();
+        CoreLoginManager::new(
+            CoreClient::from_provider_metadata(
+                provider_metadata,
+                ClientId::new("aaa".to_string()),
+                Some(ClientSecret::new("bbb".to_string())),
+            )
+            .set_redirect_uri(RedirectUrl::new("https://client/cb".to_string()).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_login() {
+        let provider = new_provider();
+        let login_manager = new_login_manager(&provider);
+        let store = InMemoryStateStore::new();
+
+        let url = login_manager.begin(&store, |request| request).unwrap();
+        let redirect_url = provider.authorize(&url).unwrap();
+        let (token_response, id_token_claims) =
+            block_on(login_manager.complete(&store, &redirect_url, &provider)).unwrap();
+        assert_eq!(
+            *id_token_claims.subject(),
+            SubjectIdentifier::new("test-subject".to_string())
+        );
+
+        for accept in &[None, Some("application/jwt".to_string())] {
+            let user_info: CoreUserInfoClaims = block_on(
+                login_manager
+                    .client()
+                    .user_info(token_response.access_token().clone(), None)
+                    .unwrap()
+                    .set_accept(accept.clone())
+                    .require_signed_response(accept.is_some())
+                    .request_async(&provider),
+            )
+            .unwrap();
+            assert_eq!(
+                *user_info.subject(),
+                SubjectIdentifier::new("test-subject".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_authorization_endpoint() {
+        let provider = new_provider();
+        let mut url = Url::parse("https://provider.example/authorize?state=the_state").unwrap();
+        let response = HttpClient::call(&provider, Request::new(Method::Get, url.clone())).unwrap();
+        assert_eq!(response.status(), StatusCode::BadRequest);
+        assert_eq!(provider.authorize(&url), None);
+
+        url.query_pairs_mut()
+            .append_pair("redirect_uri", "https://client/cb");
+        let redirect_url = provider.authorize(&url).unwrap();
+        assert_eq!(
+            redirect_url.as_str(),
+            "https://client/cb?code=code-1&state=the_state"
+        );
+    }
+
+    #[test]
+    fn test_token_endpoint() {
+        let provider = new_provider().set_client_secret(Some(ClientSecret::new("bbb".to_string())));
+        // Returns an authorization code issued for the `https://client/cb` redirect URI.
+        let code = |query: &str| {
+            let url = Url::parse(&format!(
+                "https://provider.example/authorize?redirect_uri=https://client/cb&{}",
+                query
+            ))
+            .unwrap();
+            let redirect_url = provider.authorize(&url).unwrap();
+            redirect_url
+                .query_pairs()
+                .find(|(name, _)| name == "code")
+                .map(|(_, code)| code.into_owned())
+                .unwrap()
+        };
+        // Returns the error code of a token response, or `None` if the request succeeded.
+        let token_error = |authorization: Option<&str>, params: &[(&str, &str)]| {
+            let mut request = Request::new(
+                Method::Post,
+                Url::parse("https://provider.example/token").unwrap(),
+            );
+            if let Some(authorization) = authorization {
+                request.insert_header("Authorization", authorization);
+            }
+            request.set_body(
+                form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(params)
+                    .finish(),
+            );
+            let mut response = HttpClient::call(&provider, request).unwrap();
+            if response.status() == StatusCode::Ok {
+                return None;
+            }
+            let body: serde_json::Value =
+                serde_json::from_str(&block_on(response.body_string()).unwrap()).unwrap();
+            Some(body["error"].as_str().unwrap().to_string())
+        };
+        // Basic credentials for client ID `aaa` and secret `bbb`.
+        let basic_auth = Some("Basic YWFhOmJiYg==");
+        let grant = |code: &str, redirect_uri: &'static str, code_verifier: &'static str| {
+            vec![
+                ("grant_type", "authorization_code".to_string()),
+                ("code", code.to_string()),
+                ("redirect_uri", redirect_uri.to_string()),
+                ("code_verifier", code_verifier.to_string()),
+            ]
+        };
+        let as_params = |params: &[(&'static str, String)]| {
+            params
+                .iter()
+                .map(|(name, value)| (*name, value.as_str()))
+                .collect::<Vec<_>>()
+        };
+
+        // Code challenges without a method use the `plain` method.
+        for (query, code_verifier, expected_error) in &[
+            ("code_challenge=the_verifier", "the_verifier", None),
+            (
+                "code_challenge=the_verifier",
+                "other_verifier",
+                Some("invalid_grant"),
+            ),
+            (
+                "code_challenge=the_verifier&code_challenge_method=plain",
+                "the_verifier",
+                None,
+            ),
+        ] {
+            let params = grant(&code(query), "https://client/cb", *code_verifier);
+            assert_eq!(
+                token_error(basic_auth, &as_params(&params)).as_deref(),
+                *expected_error,
+                "{}",
+                query
+            );
+        }
+        let s256_challenge =
+            base64::encode_config(sha256(b"the_verifier"), base64_url_safe_no_pad());
+        let s256_query = format!(
+            "code_challenge={}&code_challenge_method=S256",
+            s256_challenge
+        );
+        let params = grant(&code(&s256_query), "https://client/cb", "the_verifier");
+        assert_eq!(token_error(basic_auth, &as_params(&params)), None);
+        let params = grant(&code(&s256_query), "https://client/cb", "other_verifier");
+        assert_eq!(
+            token_error(basic_auth, &as_params(&params)).as_deref(),
+            Some("invalid_grant")
+        );
+
+        // Unsupported code challenge methods are rejected by the authorization endpoint.
+        let url = Url::parse(
+            "https://provider.example/authorize?redirect_uri=https://client/cb&state=the_state\
+             &code_challenge=the_verifier&code_challenge_method=S512",
+        )
+        .unwrap();
+        assert_eq!(
+            provider.authorize(&url).unwrap().as_str(),
+            "https://client/cb?error=invalid_request&state=the_state"
+        );
+
+        // The redirect URI must match the one in the authorization request.
+        for redirect_uri in &["https://client/other", "https://client/cb/"] {
+            let params = grant(&code(""), *redirect_uri, "");
+            assert_eq!(
+                token_error(basic_auth, &as_params(&params)).as_deref(),
+                Some("invalid_grant")
+            );
+        }
+        let params = grant(&code(""), "https://client/cb", "");
+        assert_eq!(
+            token_error(basic_auth, &as_params(&params[..2])).as_deref(),
+            Some("invalid_grant")
+        );
+
+        // The client must authenticate using exactly one supported method.
+        let params = grant(&code(""), "https://client/cb", "");
+        let post_auth = |client_id: &'static str, client_secret: &'static str| {
+            let mut params = params.clone();
+            params.push(("client_id", client_id.to_string()));
+            params.push(("client_secret", client_secret.to_string()));
+            params
+        };
+        for (authorization, params) in &[
+            // Wrong client secret.
+            (Some("Basic YWFhOmNjYw=="), params.clone()),
+            (None, post_auth("aaa", "ccc")),
+            // Wrong client ID.
+            (Some("Basic Y2NjOmJiYg=="), params.clone()),
+            (None, post_auth("ccc", "bbb")),
+            // Missing or malformed credentials.
+            (None, params.clone()),
+            (Some("Bearer YWFhOmJiYg=="), params.clone()),
+            // Multiple authentication methods.
+            (basic_auth, post_auth("aaa", "bbb")),
+            // Unsupported authentication method.
+            (None, {
+                let mut params = params.clone();
+                params.push(("client_id", "aaa".to_string()));
+                params.push(("client_assertion", "eyJ...".to_string()));
+                params
+            }),
+        ] {
+            assert_eq!(
+                token_error(*authorization, &as_params(params)).as_deref(),
+                Some("invalid_client")
+            );
+        }
+        // The code isn't consumed by requests that fail client authentication.
+        assert_eq!(
+            token_error(None, &as_params(&post_auth("aaa", "bbb"))),
+            None
+        );
+
+        // Without a configured secret, only the client ID is checked.
+        let provider = new_provider();
+        let url = Url::parse("https://provider.example/authorize?redirect_uri=https://client/cb")
+            .unwrap();
+        let redirect_url = provider.authorize(&url).unwrap();
+        let code = redirect_url.query_pairs().next().unwrap().1.into_owned();
+        let mut request = Request::new(
+            Method::Post,
+            Url::parse("https://provider.example/token").unwrap(),
+        );
+        request.set_body(
+            form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(as_params(&grant(&code, "https://client/cb", "")))
+                .append_pair("client_id", "aaa")
+                .finish(),
+        );
+        let response = HttpClient::call(&provider, request).unwrap();
+        assert_eq!(response.status(), StatusCode::Ok);
+    }
+
+    #[test]
+    fn test_signing_key_per_provider() {
+        let provider = new_provider();
+        // The relying party trusts a different provider's keys.
+        let login_manager = new_login_manager(&new_provider());
+        let store = InMemoryStateStore::new();
+
+        let url = login_manager.begin(&store, |request| request).unwrap();
+        let redirect_url = provider.authorize(&url).unwrap();
+        match block_on(login_manager.complete(&store, &redirect_url, &provider)) {
+            Err(LoginManagerError::IdTokenVerification(
+                ClaimsVerificationError::SignatureVerification(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_login_faults() {
+        let provider = new_provider();
+        let login_manager = new_login_manager(&provider);
+        let store = InMemoryStateStore::new();
+
+        for fault in &[
+            TestProviderFault::ExpiredIdToken,
+            TestProviderFault::WrongNonce,
+            TestProviderFault::InvalidSignature,
+        ] {
+            provider.set_fault(Some(*fault));
+            let url = login_manager.begin(&store, |request| request).unwrap();
+            let redirect_url = provider.authorize(&url).unwrap();
+            match (
+                fault,
+                block_on(login_manager.complete(&store, &redirect_url, &provider)),
+            ) {
+                (
+                    TestProviderFault::ExpiredIdToken,
+                    Err(LoginManagerError::IdTokenVerification(ClaimsVerificationError::Expired(
+                        _,
+                    ))),
+                )
+                | (
+                    TestProviderFault::WrongNonce,
+                    Err(LoginManagerError::IdTokenVerification(
+                        ClaimsVerificationError::InvalidNonce(_),
+                    )),
+                )
+                | (
+                    TestProviderFault::InvalidSignature,
+                    Err(LoginManagerError::IdTokenVerification(
+                        ClaimsVerificationError::SignatureVerification(_),
+                    )),
+                ) => {}
+                (_, other) => panic!("unexpected result for {:?}: {:?}", fault, other),
+            }
+        }
+    }
+    #[test]
+    fn test_logout_token() {
+        let provider = new_provider();
+
+        match provider.logout_token(None, None) {
+            Err(JsonWebTokenError::InvalidClaims(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let session_id = SessionId::new("session".to_string());
+        let logout_token: CoreLogoutToken = provider
+            .logout_token(None, Some(session_id.clone()))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            logout_token.unverified_header().typ,
+            Some(JsonWebTokenType::logout_token())
+        );
+        let claims = logout_token.unverified_claims();
+        assert_eq!(*claims.issuer(), *provider.issuer());
+        assert_eq!(*claims.audiences(), vec![Audience::new("aaa".to_string())]);
+        assert_eq!(claims.subject(), None);
+        assert_eq!(claims.session_id(), Some(&session_id));
+        assert!(claims.is_backchannel_logout());
+
+        // Each logout token has a unique ID.
+        let other_logout_token: CoreLogoutToken = provider
+            .logout_token(None, Some(session_id))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_ne!(
+            other_logout_token.unverified_claims().token_id(),
+            claims.token_id()
+        );
+    }
+}
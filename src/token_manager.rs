@@ -0,0 +1,678 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use futures::lock::Mutex;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{error_code, ClassifiedError, ErrorKind};
+use crate::http_client::AsyncHttpClient;
+use crate::token_response::expiration_time;
+use crate::token_store::StoredToken;
+use crate::{
+    AccessToken, AdditionalClaims, AuthDisplay, AuthPrompt, ClaimsVerificationError, Client,
+    ErrorResponse, GenderClaim, IdTokenClaims, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, Nonce, OAuth2TokenResponse, RefreshToken,
+    RequestTokenError, ScopeSet, TokenRequestError, TokenResponse, TokenType,
+};
+
+// By default, tokens are refreshed when they expire within this many seconds, which allows for
+// clock skew and network latency between the caller and the resource server.
+const DEFAULT_REFRESH_MARGIN_SECS: u64 = 60;
+
+///
+/// Tracks an OAuth2 access token and refreshes it before it expires.
+///
+/// The expiration time of each access token is recorded as an absolute instant when the token
+/// response is received. [`TokenManager::access_token`] returns the current access token, first
+/// refreshing it if it expires within the refresh margin (60 seconds by default). Refreshes are
+/// single-flight: concurrent callers wait for an in-progress refresh instead of each issuing their
+/// own refresh request.
+///
+/// ID tokens included in refresh token responses are verified using
+/// [`IdTokenVerifier::for_refresh`](crate::IdTokenVerifier::for_refresh) against the claims of the
+/// original ID token.
+///
+/// Authorization servers that rotate refresh tokens return a new refresh token with each refresh
+/// response and invalidate the previous one. Rotated refresh tokens are passed to the handler
+/// registered with [`TokenManager::set_rotation_handler`] (e.g., to persist them immediately),
+/// and once rotation has been observed, an `invalid_grant` refresh error is returned as
+/// [`TokenManagerError::RefreshTokenReused`].
+///
+/// Refresh requests may narrow the scope of the refreshed access token (see
+/// [`TokenManager::set_refresh_scopes`]), in which case refresh responses granting any other scopes
+/// are rejected with [`TokenManagerError::UnrequestedScopes`].
+///
+pub struct TokenManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+    refresh_margin: Duration,
+    refresh_scopes: Option<ScopeSet>,
+    state: Mutex<StoredToken<AC, GC>>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    rotation_handler: Option<Arc<dyn Fn(&RefreshToken) + Send + Sync>>,
+    // Whether a refresh response has rotated the refresh token. Only modified while holding the
+    // state lock.
+    rotation_observed: AtomicBool,
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+    TokenManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    ///
+    /// Instantiates a token manager from a token response returned by the token endpoint.
+    ///
+    /// `id_token_claims` should contain the verified claims of the ID token returned with the
+    /// token response, if any. These claims are used to verify the consistency of ID tokens
+    /// returned by subsequent refreshes.
+    ///
+    pub fn new(
+        client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+        token_response: &TR,
+        id_token_claims: Option<IdTokenClaims<AC, GC>>,
+    ) -> Self {
+        Self::from_stored_token(
+            client,
+            StoredToken::new(token_response.access_token().clone())
+                .set_expires_at(expiration_time(
+                    token_response.expires_in(),
+                    SystemClock.now(),
+                ))
+                .set_id_token_claims(id_token_claims)
+                .set_refresh_token(token_response.refresh_token().cloned()),
+        )
+    }
+
+    ///
+    /// Instantiates a token manager from previously stored token state (e.g., loaded from a
+    /// [`TokenStore`](crate::TokenStore)).
+    ///
+    pub fn from_stored_token(
+        client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+        stored_token: StoredToken<AC, GC>,
+    ) -> Self {
+        Self {
+            client,
+            refresh_margin: Duration::from_secs(DEFAULT_REFRESH_MARGIN_SECS),
+            refresh_scopes: None,
+            state: Mutex::new(stored_token),
+            clock: Arc::new(SystemClock),
+            rotation_handler: None,
+            rotation_observed: AtomicBool::new(false),
+        }
+    }
+
+    ///
+    /// Specifies how long before an access token expires that it should be refreshed.
+    ///
+    pub fn set_refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+
+    ///
+    /// Specifies the scopes to request when refreshing the access token, or `None` (the default)
+    /// to omit the `scope` parameter so that the originally granted scopes are retained.
+    ///
+    /// The scopes must have been granted by the original authorization (see
+    /// [Section 6 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-6)). If a refresh
+    /// response indicates that other scopes were granted (e.g., because the authorization server
+    /// ignored the requested scopes), the refreshed access token is discarded and
+    /// [`TokenManagerError::UnrequestedScopes`] is returned.
+    ///
+    pub fn set_refresh_scopes(mut self, refresh_scopes: Option<ScopeSet>) -> Self {
+        self.refresh_scopes = refresh_scopes;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time.
+    ///
+    /// The clock is used for computing and checking access token expiration times and for
+    /// verifying refreshed ID tokens. The expiration time of the current access token is not
+    /// recomputed.
+    ///
+    pub fn set_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This is equivalent to calling [`TokenManager::set_clock`] with the function.
+    ///
+    pub fn set_time_fn<T>(self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.set_clock(time_fn)
+    }
+
+    ///
+    /// Specifies a function to call whenever a refresh response rotates the refresh token.
+    ///
+    /// The function receives the new refresh token and is called while the token state is locked,
+    /// before any concurrent caller can use the new token. Since the previous refresh token is no
+    /// longer valid, callers persisting the token state (see [`TokenManager::stored_token`])
+    /// should do so from this function to avoid losing the session if the process exits.
+    ///
+    pub fn set_rotation_handler<F>(mut self, rotation_handler: F) -> Self
+    where
+        F: Fn(&RefreshToken) + Send + Sync + 'static,
+    {
+        self.rotation_handler = Some(Arc::new(rotation_handler));
+        self
+    }
+
+    ///
+    /// Returns the client used for refreshing tokens.
+    ///
+    pub fn client(&self) -> &Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> {
+        &self.client
+    }
+
+    ///
+    /// Returns the claims of the most recently verified ID token, if any.
+    ///
+    pub async fn id_token_claims(&self) -> Option<IdTokenClaims<AC, GC>> {
+        self.state.lock().await.id_token_claims().cloned()
+    }
+
+    ///
+    /// Returns the time at which the current access token expires, if known.
+    ///
+    pub async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.state.lock().await.expires_at()
+    }
+
+    ///
+    /// Returns a snapshot of the current token state, which may be persisted using a
+    /// [`TokenStore`](crate::TokenStore).
+    ///
+    /// The token state changes whenever the access token is refreshed, so callers persisting it
+    /// should do so after each call to [`TokenManager::access_token`] or
+    /// [`TokenManager::refresh`].
+    ///
+    pub async fn stored_token(&self) -> StoredToken<AC, GC> {
+        self.state.lock().await.clone()
+    }
+
+    ///
+    /// Returns a valid access token, refreshing it first if it expires within the refresh margin.
+    ///
+    /// If the access token has expired and no refresh token is available, this method returns
+    /// [`TokenManagerError::NoRefreshToken`]. Access tokens with an unknown expiration time are
+    /// returned without being refreshed.
+    ///
+    pub async fn access_token<C>(
+        &self,
+        http_client: &C,
+    ) -> Result<AccessToken, TokenManagerError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let mut state = self.state.lock().await;
+        if !self.needs_refresh(&state) {
+            return Ok(state.access_token().clone());
+        }
+        if state.refresh_token().is_none() && !self.is_expired(&state) {
+            // The access token can't be refreshed, but it remains valid for now.
+            return Ok(state.access_token().clone());
+        }
+        self.refresh_locked(&mut *state, http_client).await?;
+        Ok(state.access_token().clone())
+    }
+
+    ///
+    /// Refreshes the access token regardless of its expiration time.
+    ///
+    pub async fn refresh<C>(&self, http_client: &C) -> Result<(), TokenManagerError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let mut state = self.state.lock().await;
+        self.refresh_locked(&mut *state, http_client).await
+    }
+
+    fn needs_refresh(&self, state: &StoredToken<AC, GC>) -> bool {
+        state.expires_at().map_or(false, |expires_at| {
+            chrono::Duration::from_std(self.refresh_margin)
+                .ok()
+                .and_then(|margin| expires_at.checked_sub_signed(margin))
+                .map_or(true, |refresh_at| self.clock.now() >= refresh_at)
+        })
+    }
+
+    fn is_expired(&self, state: &StoredToken<AC, GC>) -> bool {
+        state
+            .expires_at()
+            .map_or(false, |expires_at| self.clock.now() >= expires_at)
+    }
+
+    // Must be called while holding the state lock so that concurrent callers wait for the refresh
+    // to complete rather than issuing their own requests.
+    async fn refresh_locked<C>(
+        &self,
+        state: &mut StoredToken<AC, GC>,
+        http_client: &C,
+    ) -> Result<(), TokenManagerError<C::Error, TE>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let refresh_token = state
+            .refresh_token()
+            .cloned()
+            .ok_or(TokenManagerError::NoRefreshToken)?;
+
+        let mut refresh_request = self.client.exchange_refresh_token(&refresh_token);
+        if let Some(ref refresh_scopes) = self.refresh_scopes {
+            for scope in refresh_scopes {
+                refresh_request = refresh_request.add_scope(scope.clone());
+            }
+        }
+        let token_response = match self
+            .client
+            .request_refresh_token(refresh_request, http_client)
+            .await
+        {
+            Ok(token_response) => token_response,
+            Err(err) if self.rotation_observed.load(Ordering::SeqCst) && is_invalid_grant(&err) => {
+                // Authorization servers that rotate refresh tokens revoke the whole grant when a
+                // previously used refresh token is presented, so the session can't be recovered.
+                *state = state.clone().set_refresh_token(None);
+                return Err(TokenManagerError::RefreshTokenReused(err));
+            }
+            Err(err) => return Err(TokenManagerError::Refresh(err)),
+        };
+
+        if let (Some(requested_scopes), Some(granted_scopes)) =
+            (self.refresh_scopes.as_ref(), token_response.scopes())
+        {
+            // Omitting the `scope` parameter indicates that exactly the requested scopes were
+            // granted (see https://tools.ietf.org/html/rfc6749#section-5.1).
+            let unrequested_scopes = granted_scopes
+                .iter()
+                .cloned()
+                .collect::<ScopeSet>()
+                .difference(requested_scopes);
+            if !unrequested_scopes.is_empty() {
+                return Err(TokenManagerError::UnrequestedScopes(unrequested_scopes));
+            }
+        }
+
+        let id_token_claims = if let Some(id_token) = token_response.id_token() {
+            let clock = self.clock.clone();
+            let verifier = self
+                .client
+                .id_token_verifier()
+                .set_time_fn(move || clock.now());
+            let verifier = match state.id_token_claims() {
+                Some(original_claims) => verifier.for_refresh(original_claims),
+                None => verifier,
+            };
+            // The nonce is checked against the original ID token by the refresh verifier.
+            Some(
+                id_token
+                    .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+                    .map_err(TokenManagerError::IdTokenVerification)?
+                    .clone(),
+            )
+        } else {
+            None
+        };
+
+        let rotated_refresh_token = token_response
+            .refresh_token()
+            .filter(|new_refresh_token| new_refresh_token.secret() != refresh_token.secret())
+            .cloned();
+
+        *state = StoredToken::new(token_response.access_token().clone())
+            .set_expires_at(expiration_time(
+                token_response.expires_in(),
+                self.clock.now(),
+            ))
+            // Refresh token responses need not include a new ID token.
+            .set_id_token_claims(id_token_claims.or_else(|| state.take_id_token_claims()))
+            // Section 6 of RFC 6749 permits the authorization server to omit a new refresh token,
+            // in which case the existing one remains valid.
+            .set_refresh_token(Some(rotated_refresh_token.clone().unwrap_or(refresh_token)));
+
+        if let Some(ref rotated_refresh_token) = rotated_refresh_token {
+            self.rotation_observed.store(true, Ordering::SeqCst);
+            if let Some(ref rotation_handler) = self.rotation_handler {
+                rotation_handler(rotated_refresh_token);
+            }
+        }
+        Ok(())
+    }
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT> Debug
+    for TokenManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // The token state is omitted to avoid blocking on the lock and leaking secrets.
+        f.debug_struct("TokenManager")
+            .field("refresh_margin", &self.refresh_margin)
+            .field("refresh_scopes", &self.refresh_scopes)
+            .field(
+                "rotation_observed",
+                &self.rotation_observed.load(Ordering::SeqCst),
+            )
+            .finish()
+    }
+}
+
+// Returns whether the provider rejected the refresh token with an `invalid_grant` error (see
+// https://tools.ietf.org/html/rfc6749#section-5.2).
+fn is_invalid_grant<RE, TE>(err: &TokenRequestError<RE, TE>) -> bool
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    match err.error() {
+        RequestTokenError::ServerResponse(err) => {
+            error_code(err).map_or(false, |error_code| error_code == "invalid_grant")
+        }
+        _ => false,
+    }
+}
+
+///
+/// Error obtaining an access token from a [`TokenManager`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum TokenManagerError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// The ID token returned by the refresh token response failed verification.
+    ///
+    #[fail(display = "Failed to verify refreshed ID token")]
+    IdTokenVerification(#[cause] ClaimsVerificationError),
+    ///
+    /// The access token has expired and no refresh token is available.
+    ///
+    #[fail(display = "Access token expired and no refresh token is available")]
+    NoRefreshToken,
+    ///
+    /// The refresh token request failed.
+    ///
+    #[fail(display = "Failed to refresh access token")]
+    Refresh(#[cause] TokenRequestError<RE, TE>),
+    ///
+    /// The authorization server rejected the refresh token with an `invalid_grant` error after
+    /// previously rotating it, which indicates that the refresh token was reused (e.g., because it
+    /// was stolen or because another process refreshed the same session). The authorization
+    /// server has likely revoked the grant, so the session should be invalidated everywhere and
+    /// the user should log in again. The refresh token is discarded from the token state.
+    ///
+    #[fail(display = "Refresh token was reused after rotation")]
+    RefreshTokenReused(#[cause] TokenRequestError<RE, TE>),
+    ///
+    /// The refresh token response granted scopes other than those specified via
+    /// [`TokenManager::set_refresh_scopes`] (e.g., because the authorization server ignored the
+    /// requested scopes). This variant holds the unrequested scopes. The refreshed access token is
+    /// discarded, and the token state is unchanged.
+    ///
+    #[fail(display = "Refresh response granted unrequested scopes: {}", _0)]
+    UnrequestedScopes(ScopeSet),
+}
+impl<RE, TE> ClassifiedError for TokenManagerError<RE, TE>
+where
+    RE: Fail,
+    TE: ErrorResponse + 'static,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            TokenManagerError::IdTokenVerification(_) => ErrorKind::Verification,
+            TokenManagerError::NoRefreshToken => ErrorKind::Other,
+            TokenManagerError::Refresh(err) => err.error_kind(),
+            TokenManagerError::RefreshTokenReused(_) => ErrorKind::ClientError,
+            TokenManagerError::UnrequestedScopes(_) => ErrorKind::Verification,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use futures::executor::block_on;
+    use futures::future::ready;
+
+    use crate::core::{CoreClient, CoreTokenManager, CoreTokenResponse};
+    use crate::http_types::{Request, Response, StatusCode};
+    use crate::{
+        AuthUrl, ClientId, ClientSecret, IssuerUrl, JsonWebKeySet, OAuth2TokenResponse,
+        RefreshToken, ScopeSet, TokenUrl,
+    };
+
+    use super::TokenManagerError;
+
+    fn new_client() -> CoreClient {
+        CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        )
+    }
+
+    fn token_response(json: &str) -> CoreTokenResponse {
+        serde_json::from_str(json).expect("failed to deserialize")
+    }
+
+    #[test]
+    fn test_refresh_near_expiry() {
+        let refresh_count = AtomicUsize::new(0);
+        let http_client = |_: Request| {
+            refresh_count.fetch_add(1, Ordering::SeqCst);
+            let mut response = Response::new(StatusCode::Ok);
+            response.insert_header("Content-Type", "application/json");
+            response.set_body(
+                "{\"access_token\": \"refreshed\", \"token_type\": \"bearer\", \"expires_in\": 3600}",
+            );
+            ready(Ok::<_, io::Error>(response))
+        };
+
+        let initial = token_response(
+            "{\"access_token\": \"initial\", \"token_type\": \"bearer\", \"expires_in\": 30, \
+             \"refresh_token\": \"refresh\"}",
+        );
+        let manager = CoreTokenManager::new(new_client(), &initial, None);
+
+        // The initial token expires within the default 60-second refresh margin.
+        let access_token = block_on(manager.access_token(&http_client)).unwrap();
+        assert_eq!("refreshed", access_token.secret());
+        assert_eq!(1, refresh_count.load(Ordering::SeqCst));
+
+        // The refreshed token remains valid, so no further refresh is needed.
+        let access_token = block_on(manager.access_token(&http_client)).unwrap();
+        assert_eq!("refreshed", access_token.secret());
+        assert_eq!(1, refresh_count.load(Ordering::SeqCst));
+
+        // The original refresh token is retained since the response didn't include a new one.
+        block_on(manager.refresh(&http_client)).unwrap();
+        assert_eq!(2, refresh_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_expired_without_refresh_token() {
+        let http_client = |_: Request| {
+            ready(Err::<Response, _>(io::Error::new(
+                io::ErrorKind::Other,
+                "unexpected request",
+            )))
+        };
+
+        let initial = token_response(
+            "{\"access_token\": \"initial\", \"token_type\": \"bearer\", \"expires_in\": 3600}",
+        );
+        assert!(initial.refresh_token().is_none());
+
+        let manager = CoreTokenManager::new(new_client(), &initial, None)
+            .set_refresh_margin(Duration::from_secs(0));
+        assert_eq!(
+            "initial",
+            block_on(manager.access_token(&http_client))
+                .unwrap()
+                .secret()
+        );
+
+        let manager = manager.set_time_fn(|| Utc.timestamp(i32::max_value() as i64, 0));
+        match block_on(manager.access_token(&http_client)) {
+            Err(TokenManagerError::NoRefreshToken) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_refresh_token_rotation() {
+        let refresh_count = AtomicUsize::new(0);
+        let http_client = |_: Request| {
+            let (status, body) = match refresh_count.fetch_add(1, Ordering::SeqCst) {
+                0 => (
+                    StatusCode::Ok,
+                    "{\"access_token\": \"refreshed\", \"token_type\": \"bearer\", \
+                     \"expires_in\": 3600, \"refresh_token\": \"rotated\"}",
+                ),
+                _ => (StatusCode::BadRequest, "{\"error\": \"invalid_grant\"}"),
+            };
+            let mut response = Response::new(status);
+            response.set_body(body);
+            response.insert_header("Content-Type", "application/json");
+            ready(Ok::<_, io::Error>(response))
+        };
+
+        let initial = token_response(
+            "{\"access_token\": \"initial\", \"token_type\": \"bearer\", \"expires_in\": 3600, \
+             \"refresh_token\": \"refresh\"}",
+        );
+        let rotated = Arc::new(Mutex::new(Vec::new()));
+        let manager = CoreTokenManager::new(new_client(), &initial, None).set_rotation_handler({
+            let rotated = rotated.clone();
+            move |refresh_token: &RefreshToken| {
+                rotated.lock().unwrap().push(refresh_token.secret().clone())
+            }
+        });
+
+        block_on(manager.refresh(&http_client)).unwrap();
+        assert_eq!(vec!["rotated".to_string()], *rotated.lock().unwrap());
+        assert_eq!(
+            "rotated",
+            block_on(manager.stored_token())
+                .refresh_token()
+                .unwrap()
+                .secret()
+        );
+
+        // Once rotation has been observed, `invalid_grant` indicates that the token was reused.
+        match block_on(manager.refresh(&http_client)) {
+            Err(TokenManagerError::RefreshTokenReused(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(block_on(manager.stored_token()).refresh_token().is_none());
+        assert_eq!(1, rotated.lock().unwrap().len());
+    }
+
+    #[test]
+    fn test_refresh_scopes() {
+        let refresh_count = AtomicUsize::new(0);
+        let http_client = |mut request: Request| {
+            let scope = match refresh_count.fetch_add(1, Ordering::SeqCst) {
+                0 => "openid",
+                _ => "openid email",
+            };
+            async move {
+                let body = request.body_string().await.unwrap();
+                assert!(body.contains("scope=openid"), "{}", body);
+                assert!(!body.contains("email"), "{}", body);
+
+                let mut response = Response::new(StatusCode::Ok);
+                response.insert_header("Content-Type", "application/json");
+                response.set_body(format!(
+                    "{{\"access_token\": \"refreshed\", \"token_type\": \"bearer\", \
+                     \"expires_in\": 3600, \"scope\": \"{}\"}}",
+                    scope
+                ));
+                Ok::<_, io::Error>(response)
+            }
+        };
+
+        let initial = token_response(
+            "{\"access_token\": \"initial\", \"token_type\": \"bearer\", \"expires_in\": 3600, \
+             \"refresh_token\": \"refresh\", \"scope\": \"openid email\"}",
+        );
+        let manager = CoreTokenManager::new(new_client(), &initial, None)
+            .set_refresh_scopes(Some(ScopeSet::from_space_delimited("openid")));
+
+        block_on(manager.refresh(&http_client)).unwrap();
+        assert_eq!(
+            "refreshed",
+            block_on(manager.stored_token()).access_token().secret()
+        );
+
+        // The authorization server ignored the narrowed scope.
+        match block_on(manager.refresh(&http_client)) {
+            Err(TokenManagerError::UnrequestedScopes(scopes)) => {
+                assert_eq!(ScopeSet::from_space_delimited("email"), scopes)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::clock::{Clock, SystemClock};
+use crate::types::helpers::{serde_utc_seconds, serde_utc_seconds_opt};
+use crate::{OAuth2TokenResponse, TokenType};
+
+///
+/// Token response annotated with the time at which it was received.
+///
+/// The `expires_in` field of a token response is relative to when the response was received, so
+/// it's meaningless once the response has been stored. This wrapper records the receipt time and
+/// the resulting absolute expiration time, and may be serialized alongside the token response.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TimestampedTokenResponse<TR> {
+    token_response: TR,
+    #[serde(with = "serde_utc_seconds")]
+    received_at: DateTime<Utc>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    expires_at: Option<DateTime<Utc>>,
+}
+impl<TR> TimestampedTokenResponse<TR> {
+    ///
+    /// Records the specified token response as having been received now.
+    ///
+    pub fn new<TT>(token_response: TR) -> Self
+    where
+        TR: OAuth2TokenResponse<TT>,
+        TT: TokenType,
+    {
+        Self::with_received_at(token_response, SystemClock.now())
+    }
+
+    ///
+    /// Records the specified token response as having been received at `received_at`.
+    ///
+    pub fn with_received_at<TT>(token_response: TR, received_at: DateTime<Utc>) -> Self
+    where
+        TR: OAuth2TokenResponse<TT>,
+        TT: TokenType,
+    {
+        let expires_at = expiration_time(token_response.expires_in(), received_at);
+        Self {
+            token_response,
+            received_at,
+            expires_at,
+        }
+    }
+
+    ///
+    /// Returns the token response.
+    ///
+    pub fn token_response(&self) -> &TR {
+        &self.token_response
+    }
+
+    ///
+    /// Returns the token response, discarding the timestamps.
+    ///
+    pub fn into_token_response(self) -> TR {
+        self.token_response
+    }
+
+    ///
+    /// Returns the time at which the token response was received.
+    ///
+    pub fn received_at(&self) -> DateTime<Utc> {
+        self.received_at
+    }
+
+    ///
+    /// Returns the time at which the access token expires, or `None` if the token response did not
+    /// include an `expires_in` field.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    ///
+    /// Returns whether the access token has expired or will expire within `clock_skew`.
+    ///
+    /// Access tokens with an unknown expiration time are never considered expired.
+    ///
+    pub fn is_expired(&self, clock_skew: Duration) -> bool {
+        self.is_expired_at(SystemClock.now(), clock_skew)
+    }
+
+    ///
+    /// Returns whether the access token has expired or will expire within `clock_skew` of `now`.
+    ///
+    pub fn is_expired_at(&self, now: DateTime<Utc>, clock_skew: Duration) -> bool {
+        self.expires_at.map_or(false, |expires_at| {
+            chrono::Duration::from_std(clock_skew)
+                .ok()
+                .and_then(|clock_skew| now.checked_add_signed(clock_skew))
+                .map_or(true, |now| now >= expires_at)
+        })
+    }
+}
+
+// Records the expiration as an absolute instant so that it doesn't depend on when the token
+// response is inspected.
+pub(crate) fn expiration_time(
+    expires_in: Option<Duration>,
+    received_at: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    expires_in.and_then(|expires_in| {
+        chrono::Duration::from_std(expires_in)
+            .ok()
+            .and_then(|expires_in| received_at.checked_add_signed(expires_in))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::{CoreTimestampedTokenResponse, CoreTokenResponse};
+
+    #[test]
+    fn test_timestamped_token_response() {
+        let token_response: CoreTokenResponse = serde_json::from_str(
+            "{\"access_token\": \"secret\", \"token_type\": \"bearer\", \"expires_in\": 3600}",
+        )
+        .unwrap();
+        let received_at = Utc.timestamp(1544932149, 0);
+        let timestamped =
+            CoreTimestampedTokenResponse::with_received_at(token_response, received_at);
+        assert_eq!(received_at, timestamped.received_at());
+        assert_eq!(Some(Utc.timestamp(1544935749, 0)), timestamped.expires_at());
+
+        assert!(!timestamped.is_expired_at(Utc.timestamp(1544935000, 0), Duration::from_secs(0)));
+        assert!(timestamped.is_expired_at(Utc.timestamp(1544935000, 0), Duration::from_secs(900)));
+        assert!(timestamped.is_expired_at(Utc.timestamp(1544935749, 0), Duration::from_secs(0)));
+
+        // The absolute expiration time survives a round trip through storage.
+        let deserialized: CoreTimestampedTokenResponse =
+            serde_json::from_str(&serde_json::to_string(&timestamped).unwrap()).unwrap();
+        assert_eq!(timestamped, deserialized);
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+
+use crate::types::helpers::serde_utc_seconds_opt;
+use crate::{AccessToken, AdditionalClaims, GenderClaim, IdTokenClaims, RefreshToken};
+
+///
+/// Serializable token state persisted by a [`TokenStore`].
+///
+/// This type is also used by [`TokenManager`](crate::TokenManager) to track the current access
+/// token (see [`TokenManager::stored_token`](crate::TokenManager::stored_token) and
+/// [`TokenManager::from_stored_token`](crate::TokenManager::from_stored_token)).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoredToken<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    access_token: AccessToken,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(
+        bound = "AC: AdditionalClaims, GC: GenderClaim",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    id_token_claims: Option<IdTokenClaims<AC, GC>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<RefreshToken>,
+}
+impl<AC, GC> StoredToken<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Instantiates token state from the specified access token.
+    ///
+    pub fn new(access_token: AccessToken) -> Self {
+        Self {
+            access_token,
+            expires_at: None,
+            id_token_claims: None,
+            refresh_token: None,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["token state"] {
+            set_access_token -> access_token[AccessToken],
+            set_expires_at -> expires_at[Option<DateTime<Utc>>],
+            set_id_token_claims -> id_token_claims[Option<IdTokenClaims<AC, GC>>],
+            set_refresh_token -> refresh_token[Option<RefreshToken>],
+        }
+    ];
+
+    pub(crate) fn take_id_token_claims(&mut self) -> Option<IdTokenClaims<AC, GC>> {
+        self.id_token_claims.take()
+    }
+}
+
+///
+/// Storage for token state, keyed by an application-defined identifier such as a session ID or
+/// the subject identifier of the end user.
+///
+/// Persisting token state allows web applications to retain refresh tokens and ID token claims
+/// across restarts. Implementations are provided for in-memory ([`InMemoryTokenStore`]) and
+/// file-backed ([`FileTokenStore`]) storage.
+///
+/// # Security Warning
+///
+/// Stored token state includes access and refresh tokens, which must be protected from
+/// unauthorized access.
+///
+pub trait TokenStore<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Error returned when the token state could not be read or written.
+    ///
+    type Error: Fail;
+
+    ///
+    /// Returns the token state stored under the specified key, if any.
+    ///
+    fn get(&self, key: &str) -> Result<Option<StoredToken<AC, GC>>, Self::Error>;
+
+    ///
+    /// Stores token state under the specified key, replacing any existing state.
+    ///
+    fn put(&self, key: &str, token: &StoredToken<AC, GC>) -> Result<(), Self::Error>;
+
+    ///
+    /// Removes any token state stored under the specified key.
+    ///
+    fn delete(&self, key: &str) -> Result<(), Self::Error>;
+}
+
+///
+/// Error returned by the built-in [`TokenStore`] implementations.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum TokenStoreError {
+    ///
+    /// Failed to read or write token state.
+    ///
+    #[fail(display = "IO error")]
+    Io(#[cause] io::Error),
+    ///
+    /// Failed to serialize or deserialize token state.
+    ///
+    #[fail(display = "Failed to serialize token state")]
+    Serialize(#[cause] serde_json::Error),
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// [`TokenStore`] that holds token state in memory.
+///
+/// Token state is lost when the process exits.
+///
+pub struct InMemoryTokenStore<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    tokens: Mutex<HashMap<String, StoredToken<AC, GC>>>,
+}
+impl<AC, GC> InMemoryTokenStore<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Instantiates an empty token store.
+    ///
+    pub fn new() -> Self {
+        Self {
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+}
+impl<AC, GC> Default for InMemoryTokenStore<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<AC, GC> Debug for InMemoryTokenStore<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // The keys may be session IDs (e.g., those generated by `OidcState`), which must remain
+        // secret.
+        let tokens = self.tokens.lock().unwrap_or_else(|err| err.into_inner());
+        f.debug_struct("InMemoryTokenStore")
+            .field("tokens", &tokens.len())
+            .finish()
+    }
+}
+impl<AC, GC> TokenStore<AC, GC> for InMemoryTokenStore<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    type Error = TokenStoreError;
+
+    fn get(&self, key: &str) -> Result<Option<StoredToken<AC, GC>>, Self::Error> {
+        Ok(self
+            .tokens
+            .lock()
+            .map_err(|_| TokenStoreError::Other("token store lock poisoned".to_string()))?
+            .get(key)
+            .cloned())
+    }
+
+    fn put(&self, key: &str, token: &StoredToken<AC, GC>) -> Result<(), Self::Error> {
+        self.tokens
+            .lock()
+            .map_err(|_| TokenStoreError::Other("token store lock poisoned".to_string()))?
+            .insert(key.to_string(), token.clone());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Self::Error> {
+        self.tokens
+            .lock()
+            .map_err(|_| TokenStoreError::Other("token store lock poisoned".to_string()))?
+            .remove(key);
+        Ok(())
+    }
+}
+
+///
+/// [`TokenStore`] that persists token state as JSON files within a directory.
+///
+/// Each key is stored in a separate file whose name is derived from the key. Files are replaced
+/// atomically and, on Unix platforms, are only readable by the current user.
+///
+#[derive(Clone, Debug)]
+pub struct FileTokenStore {
+    directory: PathBuf,
+}
+impl FileTokenStore {
+    ///
+    /// Instantiates a token store that persists token state within the specified directory.
+    ///
+    /// The directory is created on the first write if it does not already exist.
+    ///
+    pub fn new<P>(directory: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    ///
+    /// Returns the directory in which token state is stored.
+    ///
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+
+    // Keys may contain characters that aren't valid in file names (e.g., `/`), so they're encoded
+    // as URL-safe base64.
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!(
+            "{}.json",
+            base64::encode_config(key, base64::URL_SAFE_NO_PAD)
+        ))
+    }
+}
+impl<AC, GC> TokenStore<AC, GC> for FileTokenStore
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    type Error = TokenStoreError;
+
+    fn get(&self, key: &str) -> Result<Option<StoredToken<AC, GC>>, Self::Error> {
+        match File::open(self.path(key)) {
+            Ok(file) => serde_json::from_reader(io::BufReader::new(file))
+                .map(Some)
+                .map_err(TokenStoreError::Serialize),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(TokenStoreError::Io(err)),
+        }
+    }
+
+    fn put(&self, key: &str, token: &StoredToken<AC, GC>) -> Result<(), Self::Error> {
+        let serialized = serde_json::to_vec(token).map_err(TokenStoreError::Serialize)?;
+
+        fs::create_dir_all(&self.directory).map_err(TokenStoreError::Io)?;
+        let path = self.path(key);
+        // Write to a temporary file and then rename it so that readers never observe a partially
+        // written file.
+        let tmp_path = path.with_extension("json.tmp");
+        {
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let mut file = options.open(&tmp_path).map_err(TokenStoreError::Io)?;
+            file.write_all(&serialized).map_err(TokenStoreError::Io)?;
+            file.sync_all().map_err(TokenStoreError::Io)?;
+        }
+        fs::rename(&tmp_path, &path).map_err(TokenStoreError::Io)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Self::Error> {
+        match fs::remove_file(self.path(key)) {
+            Ok(()) => Ok(()),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(TokenStoreError::Io(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::CoreGenderClaim;
+    use crate::{AccessToken, EmptyAdditionalClaims, RefreshToken};
+
+    use super::{FileTokenStore, InMemoryTokenStore, StoredToken, TokenStore};
+
+    type TestStoredToken = StoredToken<EmptyAdditionalClaims, CoreGenderClaim>;
+
+    fn new_token() -> TestStoredToken {
+        StoredToken::new(AccessToken::new("access".to_string()))
+            .set_expires_at(Some(Utc.timestamp(1544932149, 0)))
+            .set_refresh_token(Some(RefreshToken::new("refresh".to_string())))
+    }
+
+    fn assert_round_trip<S>(store: &S)
+    where
+        S: TokenStore<EmptyAdditionalClaims, CoreGenderClaim>,
+    {
+        assert!(store.get("session/1").unwrap().is_none());
+
+        store.put("session/1", &new_token()).unwrap();
+        let token = store.get("session/1").unwrap().unwrap();
+        assert_eq!("access", token.access_token().secret());
+        assert_eq!(Some(Utc.timestamp(1544932149, 0)), token.expires_at());
+        assert_eq!("refresh", token.refresh_token().unwrap().secret());
+        assert!(token.id_token_claims().is_none());
+        assert!(store.get("session/2").unwrap().is_none());
+
+        store.delete("session/1").unwrap();
+        assert!(store.get("session/1").unwrap().is_none());
+        // Deleting a missing key is not an error.
+        store.delete("session/1").unwrap();
+    }
+
+    #[test]
+    fn test_in_memory_store() {
+        assert_round_trip(&InMemoryTokenStore::new());
+    }
+
+    #[test]
+    fn test_file_store() {
+        let directory =
+            std::env::temp_dir().join(format!("openidconnect-token-store-{}", std::process::id()));
+        assert_round_trip(&FileTokenStore::new(&directory));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_stored_token_serde() {
+        let serialized = serde_json::to_string(&new_token()).unwrap();
+        assert_eq!(
+            "{\"access_token\":\"access\",\"expires_at\":1544932149,\"refresh_token\":\"refresh\"}",
+            serialized
+        );
+        let deserialized = serde_json::from_str::<TestStoredToken>(&serialized).unwrap();
+        assert_eq!("access", deserialized.access_token().secret());
+    }
+}
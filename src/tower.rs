@@ -0,0 +1,218 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http1::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use http1::{HeaderMap, HeaderValue, Request, Response, StatusCode};
+use oauth2::AccessToken;
+use tower_layer::Layer;
+use tower_service::Service;
+
+pub use crate::resource_server::{BearerChallenge, BearerTokenValidator, JwtBearerValidator};
+
+///
+/// [`Layer`] that authenticates requests using `Authorization: Bearer` access tokens.
+///
+/// Requests with a valid access token are passed to the inner service with the verified claims
+/// (see [`BearerTokenValidator::Claims`]) inserted into the request extensions. Requests without
+/// an access token are rejected with a `401 Unauthorized` response, and requests with an invalid
+/// access token are rejected with the challenge returned by [`BearerTokenValidator::challenge`]
+/// (e.g., `403 Forbidden` for insufficient scopes). Challenges are sent via the `WWW-Authenticate`
+/// header as described in [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3).
+///
+pub struct BearerAuthLayer<V> {
+    validator: Arc<V>,
+}
+impl<V> BearerAuthLayer<V> {
+    ///
+    /// Instantiates a layer that validates access tokens using the specified validator.
+    ///
+    pub fn new(validator: V) -> Self {
+        Self {
+            validator: Arc::new(validator),
+        }
+    }
+}
+impl<V> Clone for BearerAuthLayer<V> {
+    fn clone(&self) -> Self {
+        Self {
+            validator: self.validator.clone(),
+        }
+    }
+}
+impl<S, V> Layer<S> for BearerAuthLayer<V> {
+    type Service = BearerAuth<S, V>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BearerAuth {
+            inner,
+            validator: self.validator.clone(),
+        }
+    }
+}
+
+///
+/// Service created by [`BearerAuthLayer`].
+///
+pub struct BearerAuth<S, V> {
+    inner: S,
+    validator: Arc<V>,
+}
+impl<S, V> Clone for BearerAuth<S, V>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            validator: self.validator.clone(),
+        }
+    }
+}
+impl<S, V, ReqBody, ResBody> Service<Request<ReqBody>> for BearerAuth<S, V>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    V: BearerTokenValidator + Send + Sync + 'static,
+    V::Future: 'static,
+    ReqBody: Send + 'static,
+    ResBody: Default + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        // The service that was polled ready must handle this request, so a clone takes its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let validator = self.validator.clone();
+        let validation = bearer_token(request.headers()).map(|token| validator.validate(token));
+        Box::pin(async move {
+            let validation = match validation {
+                Some(validation) => validation,
+                None => return Ok(challenge_response(&BearerChallenge::missing_token())),
+            };
+            match validation.await {
+                Ok(claims) => {
+                    request.extensions_mut().insert(claims);
+                    inner.call(request).await
+                }
+                Err(err) => Ok(challenge_response(&validator.challenge(&err))),
+            }
+        })
+    }
+}
+
+// Returns the access token from the request's `Authorization` header, if any.
+fn bearer_token(headers: &HeaderMap) -> Option<AccessToken> {
+    let authorization = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let mut parts = authorization.splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+            let token = token.trim();
+            if token.is_empty() {
+                None
+            } else {
+                Some(AccessToken::new(token.to_string()))
+            }
+        }
+        _ => None,
+    }
+}
+
+fn challenge_response<B>(challenge: &BearerChallenge) -> Response<B>
+where
+    B: Default,
+{
+    let mut response = Response::new(B::default());
+    *response.status_mut() =
+        StatusCode::from_u16(u16::from(challenge.status())).unwrap_or(StatusCode::UNAUTHORIZED);
+    if let Some(www_authenticate) = challenge
+        .www_authenticate()
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, www_authenticate);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::task::{Context, Poll};
+
+    use futures::executor::block_on;
+    use futures::future::{ready, Ready};
+    use http1::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use http1::{Request, Response, StatusCode};
+    use oauth2::AccessToken;
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    use super::BearerAuthLayer;
+    use crate::ClaimsVerificationError;
+
+    // Echoes the verified subject inserted by the layer.
+    #[derive(Clone)]
+    struct EchoSubject;
+    impl Service<Request<()>> for EchoSubject {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            ready(Ok(Response::new(
+                request.extensions().get::<String>().cloned().unwrap(),
+            )))
+        }
+    }
+
+    #[test]
+    fn test_bearer_auth() {
+        let mut service = BearerAuthLayer::new(|token: AccessToken| {
+            ready(if token.secret() == "valid" {
+                Ok("alice".to_string())
+            } else {
+                Err(ClaimsVerificationError::Other("invalid".to_string()))
+            })
+        })
+        .layer(EchoSubject);
+        let mut call = |authorization: Option<&str>| {
+            let mut request = Request::builder();
+            if let Some(authorization) = authorization {
+                request = request.header(AUTHORIZATION, authorization);
+            }
+            block_on(service.call(request.body(()).unwrap())).unwrap()
+        };
+
+        let response = call(Some("Bearer valid"));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "alice");
+
+        let response = call(Some("bearer invalid"));
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer error=\"invalid_token\""
+        );
+
+        for authorization in &[None, Some("Basic dXNlcjpwYXNz"), Some("Bearer ")] {
+            let response = call(*authorization);
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+        }
+    }
+}
@@ -1,27 +1,42 @@
 use std::collections::HashMap;
-use std::fmt::{Debug, Error as FormatterError, Formatter};
+use std::fmt::{Debug, Display, Error as FormatterError, Formatter};
 use std::hash::Hash;
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::str::FromStr;
 
 use base64;
+use chrono::NaiveDate;
 use failure::Fail;
+#[cfg(feature = "net")]
+use futures::executor::block_on;
+#[cfg(feature = "net")]
 use futures::Future;
 use oauth2;
 use oauth2::helpers::deserialize_space_delimited_vec;
 use rand::{thread_rng, Rng};
-use ring::constant_time;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
 use url;
 use url::Url;
 
+use crate::crypto::{constant_time_eq, sha256};
+#[cfg(feature = "net")]
+use crate::http_client::{
+    AsyncHttpClient, BlockingHttpClient, FnOnceHttpClient, HttpClient, InstrumentedHttpClient,
+    RequestKind, RequestOptions, UnexpectedResponse,
+};
+#[cfg(feature = "net")]
 use crate::http_types::headers::ACCEPT;
+#[cfg(feature = "net")]
 use crate::http_types::{Method, Request, Response, StatusCode};
+#[cfg(feature = "net")]
 use crate::http_utils::{check_content_type, MIME_TYPE_JSON, MIME_TYPE_JWKS};
-use crate::{AccessToken, AuthorizationCode, DiscoveryError, SignatureVerificationError};
+#[cfg(feature = "net")]
+use crate::DiscoveryError;
+use crate::{AccessToken, AuthorizationCode, ClientId, SignatureVerificationError};
 
 ///
 /// A [locale-aware](https://openid.net/specs/openid-connect-core-1_0.html#IndividualClaimsLanguages)
@@ -62,6 +77,43 @@ impl<T> LocalizedClaim<T> {
         }
     }
 
+    ///
+    /// Returns the entry best matching the specified locales, in order of preference.
+    ///
+    /// Locales are matched using the [BCP 47 lookup](https://tools.ietf.org/html/rfc4647#section-3.4)
+    /// scheme: each preferred locale is first matched exactly (ignoring case) and then
+    /// progressively truncated (e.g., `de-CH-1996` to `de-CH` and then `de`) until an entry is
+    /// found. If no locale matches, the entry without a locale (if any) is returned.
+    ///
+    pub fn lookup(&self, locales: &[LanguageTag]) -> Option<&T> {
+        locales
+            .iter()
+            .find_map(|locale| {
+                let mut range = locale.as_str();
+                loop {
+                    if let Some(value) = self.get_ignore_case(range) {
+                        return Some(value);
+                    }
+                    range = &range[..range.rfind('-')?];
+                    // Single-character subtags (e.g., the `x` private use prefix) are removed
+                    // together with the subtag that follows them.
+                    if let Some(end) = range.rfind('-') {
+                        if end + 2 == range.len() {
+                            range = &range[..end];
+                        }
+                    }
+                }
+            })
+            .or_else(|| self.1.as_ref())
+    }
+
+    fn get_ignore_case(&self, locale: &str) -> Option<&T> {
+        self.0
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(locale))
+            .map(|(_, value)| value)
+    }
+
     ///
     /// Returns an iterator over the locales and claim value entries.
     ///
@@ -233,6 +285,14 @@ where
     ///
     fn key_use(&self) -> Option<&JU>;
 
+    ///
+    /// Returns the signature algorithm this key is intended to be used with (the `alg`
+    /// parameter), or `None` if no algorithm is specified.
+    ///
+    fn signing_alg(&self) -> Option<&JS> {
+        None
+    }
+
     ///
     /// Initializes a new symmetric key or shared signing secret from the specified raw bytes.
     ///
@@ -391,6 +451,20 @@ impl AsRef<str> for AuthenticationContextClass {
     }
 }
 
+new_type![
+    ///
+    /// Identifier for a session between the End-User's user agent and the OpenID Connect Provider
+    /// (`sid`), as defined in
+    /// [OpenID Connect Front-Channel Logout](
+    /// https://openid.net/specs/openid-connect-frontchannel-1_0.html#ClaimsContents).
+    ///
+    /// Relying Parties may use the session ID to correlate logout requests (e.g., back-channel
+    /// logout tokens) with local sessions.
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    SessionId(String)
+];
+
 new_type![
     ///
     /// Identifier for an authentication method (e.g., `password` or `totp`).
@@ -400,6 +474,177 @@ new_type![
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     AuthenticationMethodReference(String)
 ];
+impl AuthenticationMethodReference {
+    ///
+    /// Returns the registered authentication method identified by this reference, or
+    /// [`AuthenticationMethodReferenceValue::Other`] if the identifier isn't registered.
+    ///
+    pub fn value(&self) -> AuthenticationMethodReferenceValue {
+        AuthenticationMethodReferenceValue::from_str(self)
+    }
+}
+impl From<AuthenticationMethodReferenceValue> for AuthenticationMethodReference {
+    fn from(value: AuthenticationMethodReferenceValue) -> Self {
+        AuthenticationMethodReference::new(value.as_ref().to_string())
+    }
+}
+
+///
+/// Authentication method reference values registered by
+/// [RFC 8176](https://tools.ietf.org/html/rfc8176#section-2).
+///
+/// Unregistered identifiers are preserved losslessly as
+/// [`AuthenticationMethodReferenceValue::Other`].
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum AuthenticationMethodReferenceValue {
+    ///
+    /// Biometric authentication using facial recognition.
+    ///
+    Face,
+    ///
+    /// Biometric authentication using a fingerprint.
+    ///
+    Fpt,
+    ///
+    /// Use of geolocation information for authentication.
+    ///
+    Geo,
+    ///
+    /// Proof-of-possession of a hardware-secured key.
+    ///
+    Hwk,
+    ///
+    /// Biometric authentication using an iris scan.
+    ///
+    Iris,
+    ///
+    /// Knowledge-based authentication.
+    ///
+    Kba,
+    ///
+    /// Multiple-channel authentication.
+    ///
+    Mca,
+    ///
+    /// Multiple-factor authentication.
+    ///
+    Mfa,
+    ///
+    /// One-time password.
+    ///
+    Otp,
+    ///
+    /// Personal Identification Number or pattern.
+    ///
+    Pin,
+    ///
+    /// Password-based authentication.
+    ///
+    Pwd,
+    ///
+    /// Risk-based authentication.
+    ///
+    Rba,
+    ///
+    /// Biometric authentication using a retina scan.
+    ///
+    Retina,
+    ///
+    /// Smart card.
+    ///
+    Sc,
+    ///
+    /// Confirmation using SMS text message to the user at a registered number.
+    ///
+    Sms,
+    ///
+    /// Proof-of-possession of a software-secured key.
+    ///
+    Swk,
+    ///
+    /// Confirmation by telephone call to the user at a registered number.
+    ///
+    Tel,
+    ///
+    /// User presence test.
+    ///
+    User,
+    ///
+    /// Biometric authentication using a voiceprint.
+    ///
+    Vbm,
+    ///
+    /// Windows integrated authentication.
+    ///
+    Wia,
+    ///
+    /// An identifier not registered by RFC 8176.
+    ///
+    Other(String),
+}
+deserialize_from_str!(AuthenticationMethodReferenceValue);
+serialize_as_str!(AuthenticationMethodReferenceValue);
+impl AuthenticationMethodReferenceValue {
+    fn from_str(s: &str) -> Self {
+        match s {
+            "face" => AuthenticationMethodReferenceValue::Face,
+            "fpt" => AuthenticationMethodReferenceValue::Fpt,
+            "geo" => AuthenticationMethodReferenceValue::Geo,
+            "hwk" => AuthenticationMethodReferenceValue::Hwk,
+            "iris" => AuthenticationMethodReferenceValue::Iris,
+            "kba" => AuthenticationMethodReferenceValue::Kba,
+            "mca" => AuthenticationMethodReferenceValue::Mca,
+            "mfa" => AuthenticationMethodReferenceValue::Mfa,
+            "otp" => AuthenticationMethodReferenceValue::Otp,
+            "pin" => AuthenticationMethodReferenceValue::Pin,
+            "pwd" => AuthenticationMethodReferenceValue::Pwd,
+            "rba" => AuthenticationMethodReferenceValue::Rba,
+            "retina" => AuthenticationMethodReferenceValue::Retina,
+            "sc" => AuthenticationMethodReferenceValue::Sc,
+            "sms" => AuthenticationMethodReferenceValue::Sms,
+            "swk" => AuthenticationMethodReferenceValue::Swk,
+            "tel" => AuthenticationMethodReferenceValue::Tel,
+            "user" => AuthenticationMethodReferenceValue::User,
+            "vbm" => AuthenticationMethodReferenceValue::Vbm,
+            "wia" => AuthenticationMethodReferenceValue::Wia,
+            other => AuthenticationMethodReferenceValue::Other(other.to_string()),
+        }
+    }
+}
+impl AsRef<str> for AuthenticationMethodReferenceValue {
+    fn as_ref(&self) -> &str {
+        match *self {
+            AuthenticationMethodReferenceValue::Face => "face",
+            AuthenticationMethodReferenceValue::Fpt => "fpt",
+            AuthenticationMethodReferenceValue::Geo => "geo",
+            AuthenticationMethodReferenceValue::Hwk => "hwk",
+            AuthenticationMethodReferenceValue::Iris => "iris",
+            AuthenticationMethodReferenceValue::Kba => "kba",
+            AuthenticationMethodReferenceValue::Mca => "mca",
+            AuthenticationMethodReferenceValue::Mfa => "mfa",
+            AuthenticationMethodReferenceValue::Otp => "otp",
+            AuthenticationMethodReferenceValue::Pin => "pin",
+            AuthenticationMethodReferenceValue::Pwd => "pwd",
+            AuthenticationMethodReferenceValue::Rba => "rba",
+            AuthenticationMethodReferenceValue::Retina => "retina",
+            AuthenticationMethodReferenceValue::Sc => "sc",
+            AuthenticationMethodReferenceValue::Sms => "sms",
+            AuthenticationMethodReferenceValue::Swk => "swk",
+            AuthenticationMethodReferenceValue::Tel => "tel",
+            AuthenticationMethodReferenceValue::User => "user",
+            AuthenticationMethodReferenceValue::Vbm => "vbm",
+            AuthenticationMethodReferenceValue::Wia => "wia",
+            AuthenticationMethodReferenceValue::Other(ref other) => other.as_str(),
+        }
+    }
+}
+impl Display for AuthenticationMethodReferenceValue {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
 
 new_type![
     ///
@@ -508,6 +753,52 @@ new_type![
     )
 ];
 
+new_url_type![
+    ///
+    /// URL of the OpenID Connect Provider's `check_session_iframe`, as defined in
+    /// [OpenID Connect Session Management](
+    /// https://openid.net/specs/openid-connect-session-1_0.html#OPMetadata).
+    ///
+    CheckSessionIframeUrl
+];
+
+///
+/// Status posted by the OpenID Connect Provider's `check_session_iframe` in response to a
+/// [check session message](SessionState::check_session_message).
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CheckSessionStatus {
+    ///
+    /// The End-User's session at the OpenID Connect Provider has changed (e.g., the End-User has
+    /// logged out). The Relying Party should re-authenticate the End-User, typically via a silent
+    /// authentication request (i.e., `prompt=none`).
+    ///
+    Changed,
+    ///
+    /// The End-User's session at the OpenID Connect Provider has not changed.
+    ///
+    Unchanged,
+    ///
+    /// The OpenID Connect Provider could not process the check session message (e.g., because it
+    /// was malformed).
+    ///
+    Error,
+}
+impl CheckSessionStatus {
+    ///
+    /// Parses the `data` of a message posted by the `check_session_iframe`, returning `None` if the
+    /// message isn't a recognized status.
+    ///
+    pub fn from_message(message: &str) -> Option<Self> {
+        match message {
+            "changed" => Some(CheckSessionStatus::Changed),
+            "unchanged" => Some(CheckSessionStatus::Unchanged),
+            "error" => Some(CheckSessionStatus::Error),
+            _ => None,
+        }
+    }
+}
+
 new_type![
     ///
     /// OpenID Connect client name.
@@ -538,6 +829,23 @@ new_type![
     ClientContactEmail(String)
 ];
 
+new_url_type![
+    ///
+    /// Device authorization endpoint URL as defined in
+    /// [RFC 8628](https://tools.ietf.org/html/rfc8628#section-4).
+    ///
+    DeviceAuthorizationUrl
+];
+
+new_url_type![
+    ///
+    /// End session endpoint URL as defined in
+    /// [OpenID Connect RP-Initiated Logout](
+    /// https://openid.net/specs/openid-connect-rpinitiated-1_0.html#OPMetadata).
+    ///
+    EndSessionUrl
+];
+
 new_type![
     ///
     /// End user's birthday, represented as an
@@ -550,8 +858,150 @@ new_type![
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     EndUserBirthday(String)
+    impl {
+        ///
+        /// Parses the birthday into a structured [`Birthdate`].
+        ///
+        pub fn to_birthdate(&self) -> Result<Birthdate, BirthdateParseError> {
+            self.0.parse()
+        }
+    }
 ];
 
+///
+/// Structured representation of an end user's birthday (see [`EndUserBirthday`]).
+///
+/// Either the year or the month and day may be omitted, but not both. Birthdates are parsed from
+/// (via [`FromStr`]) and formatted as (via [`Display`]) `YYYY-MM-DD`, `YYYY`, or `0000-MM-DD` (year
+/// omitted).
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Birthdate {
+    year: Option<u16>,
+    month_day: Option<(u8, u8)>,
+}
+impl Birthdate {
+    ///
+    /// Initializes a birthdate from a full date, or returns `None` if the date is invalid.
+    ///
+    pub fn from_ymd(year: u16, month: u8, day: u8) -> Option<Self> {
+        Self::new(Some(year), Some((month, day)))
+    }
+
+    ///
+    /// Initializes a birthdate containing only the year, or returns `None` if the year is invalid.
+    ///
+    pub fn from_year(year: u16) -> Option<Self> {
+        Self::new(Some(year), None)
+    }
+
+    ///
+    /// Initializes a birthdate with the year omitted, or returns `None` if the month and day are
+    /// invalid.
+    ///
+    pub fn from_month_day(month: u8, day: u8) -> Option<Self> {
+        Self::new(None, Some((month, day)))
+    }
+
+    fn new(year: Option<u16>, month_day: Option<(u8, u8)>) -> Option<Self> {
+        // Year `0000` is reserved to indicate that the year is omitted.
+        if year.map_or(false, |year| year == 0 || year > 9999) {
+            return None;
+        }
+        if let Some((month, day)) = month_day {
+            // Leap years permit February 29 when the year is omitted.
+            NaiveDate::from_ymd_opt(
+                i32::from(year.unwrap_or(2000)),
+                u32::from(month),
+                u32::from(day),
+            )?;
+        } else if year.is_none() {
+            return None;
+        }
+        Some(Self { year, month_day })
+    }
+
+    ///
+    /// Returns the year, or `None` if the year is omitted.
+    ///
+    pub fn year(&self) -> Option<u16> {
+        self.year
+    }
+
+    ///
+    /// Returns the month (`1`-`12`), or `None` if only the year is specified.
+    ///
+    pub fn month(&self) -> Option<u8> {
+        self.month_day.map(|(month, _)| month)
+    }
+
+    ///
+    /// Returns the day of the month (`1`-`31`), or `None` if only the year is specified.
+    ///
+    pub fn day(&self) -> Option<u8> {
+        self.month_day.map(|(_, day)| day)
+    }
+
+    ///
+    /// Returns the full date, or `None` if either the year or the month and day are omitted.
+    ///
+    pub fn to_naive_date(&self) -> Option<NaiveDate> {
+        match (self.year, self.month_day) {
+            (Some(year), Some((month, day))) => {
+                NaiveDate::from_ymd_opt(i32::from(year), u32::from(month), u32::from(day))
+            }
+            _ => None,
+        }
+    }
+}
+impl Display for Birthdate {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{:04}", self.year.unwrap_or(0))?;
+        if let Some((month, day)) = self.month_day {
+            write!(f, "-{:02}-{:02}", month, day)?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for Birthdate {
+    type Err = BirthdateParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn digits(value: &str, len: usize) -> Option<u16> {
+            if value.len() == len && value.bytes().all(|b| b.is_ascii_digit()) {
+                value.parse().ok()
+            } else {
+                None
+            }
+        }
+
+        let mut parts = s.split('-');
+        let birthdate = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(year), None, None, None) => digits(year, 4).and_then(Self::from_year),
+            (Some(year), Some(month), Some(day), None) => {
+                match (digits(year, 4), digits(month, 2), digits(day, 2)) {
+                    (Some(0), Some(month), Some(day)) => {
+                        Self::from_month_day(month as u8, day as u8)
+                    }
+                    (Some(year), Some(month), Some(day)) => {
+                        Self::from_ymd(year, month as u8, day as u8)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        birthdate.ok_or_else(|| BirthdateParseError(s.to_string()))
+    }
+}
+
+///
+/// Error parsing a [`Birthdate`].
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+#[fail(display = "Invalid birthdate: {}", _0)]
+pub struct BirthdateParseError(String);
+
 new_type![
     ///
     /// End user's e-mail address.
@@ -669,6 +1119,23 @@ new_url_type![
     InitiateLoginUrl
 ];
 
+new_url_type![
+    ///
+    /// URI at which the Relying Party receives back-channel logout requests, as described in
+    /// [OpenID Connect Back-Channel Logout](
+    /// https://openid.net/specs/openid-connect-backchannel-1_0.html#BCRegistration).
+    ///
+    BackchannelLogoutUrl
+];
+
+new_url_type![
+    ///
+    /// Token introspection endpoint URL as defined in
+    /// [RFC 7662](https://tools.ietf.org/html/rfc7662#section-2).
+    ///
+    IntrospectionUrl
+];
+
 new_url_type![
     ///
     /// URL using the `https` scheme with no query or fragment component that the OP asserts as its
@@ -688,6 +1155,20 @@ new_url_type![
                 Url::parse(&(self.1.clone() + "/" + suffix))
             }
         }
+
+        // Google historically issues some ID tokens with an `iss` claim that omits the `https://`
+        // scheme (i.e., `accounts.google.com`). Such issuers are parsed as `https` URLs but retain
+        // their original string representation, so they only match issuers (or trusted issuer
+        // aliases) with the same string.
+        pub(crate) fn new_scheme_optional(issuer: String) -> Result<Self, url::ParseError> {
+            match Url::parse(&issuer) {
+                Ok(url) => Ok(Self(url, issuer)),
+                Err(url::ParseError::RelativeUrlWithoutBase) => {
+                    Ok(Self(Url::parse(&format!("https://{}", issuer))?, issuer))
+                }
+                Err(err) => Err(err),
+            }
+        }
     }
 ];
 
@@ -739,9 +1220,14 @@ where
     }
 
     ///
-    /// Fetch a remote JSON Web Key Set from the specified `url` using the given async `http_client`
-    /// (e.g., [`crate::reqwest::async_http_client`]).
+    /// Fetch a remote JSON Web Key Set from the specified `url` using the given async `http_client`,
+    /// which is passed by value. Requires "net" feature.
     ///
+    #[cfg(feature = "net")]
+    #[deprecated(
+        since = "2.0.0",
+        note = "use `fetch_async`, which borrows an `AsyncHttpClient` so that it may be reused"
+    )]
     pub async fn fetch<F, HC, RE>(
         url: &JsonWebKeySetUrl,
         http_client: HC,
@@ -751,12 +1237,66 @@ where
         HC: FnOnce(Request) -> F,
         RE: Fail,
     {
-        match http_client(Self::fetch_request(url)).await {
+        Self::fetch_async(url, &FnOnceHttpClient::new(http_client))
+            .await
+            .map_err(|err| err.into_by_value_error())
+    }
+
+    ///
+    /// Fetch a remote JSON Web Key Set from the specified `url` using the given async
+    /// `http_client`. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn fetch_async<C>(
+        url: &JsonWebKeySetUrl,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        Self::fetch_with_options(url, http_client, &RequestOptions::default()).await
+    }
+
+    ///
+    /// Fetch a remote JSON Web Key Set from the specified `url` using the given async `http_client`,
+    /// applying the specified options to the HTTP request. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn fetch_with_options<C>(
+        url: &JsonWebKeySetUrl,
+        http_client: &C,
+        request_options: &RequestOptions,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: AsyncHttpClient + ?Sized,
+    {
+        let mut fetch_request = Self::fetch_request(url);
+        request_options.apply(&mut fetch_request);
+        match InstrumentedHttpClient::new(RequestKind::JsonWebKeySet, http_client)
+            .call(fetch_request)
+            .await
+        {
             Err(err) => Err(DiscoveryError::Request(err)),
             Ok(res) => Self::fetch_response(res).await,
         }
     }
 
+    ///
+    /// Fetch a remote JSON Web Key Set from the specified `url` using the given synchronous
+    /// `http_client`. Requires "net" feature.
+    ///
+    #[cfg(feature = "net")]
+    pub fn fetch_blocking<C>(
+        url: &JsonWebKeySetUrl,
+        http_client: &C,
+    ) -> Result<Self, DiscoveryError<C::Error>>
+    where
+        C: HttpClient + ?Sized,
+    {
+        block_on(Self::fetch_async(url, &BlockingHttpClient(http_client)))
+    }
+
+    #[cfg(feature = "net")]
     fn fetch_request(url: &JsonWebKeySetUrl) -> Request {
         let mut req = Request::new(Method::Get, url.url().clone());
         req.set_body(Vec::new());
@@ -764,23 +1304,26 @@ where
         req
     }
 
+    #[cfg(feature = "net")]
     async fn fetch_response<RE>(mut http_response: Response) -> Result<Self, DiscoveryError<RE>>
     where
         RE: Fail,
     {
         if http_response.status() != StatusCode::Ok {
             return Err(DiscoveryError::Response(
-                http_response.status(),
-                http_response.take_body(),
+                UnexpectedResponse::from_response(&mut http_response).await,
                 format!("HTTP status code {}", http_response.status()),
             ));
         }
 
-        check_content_type(&http_response, MIME_TYPE_JSON)
+        if let Err(err_msg) = check_content_type(&http_response, MIME_TYPE_JSON)
             .or_else(|err| check_content_type(&http_response, MIME_TYPE_JWKS).map_err(|_| err))
-            .map_err(|err_msg| {
-                DiscoveryError::Response(http_response.status(), http_response.take_body(), err_msg)
-            })?;
+        {
+            return Err(DiscoveryError::Response(
+                UnexpectedResponse::from_response(&mut http_response).await,
+                err_msg,
+            ));
+        }
 
         let body = match http_response.body_bytes().await {
             Err(_) => return Err(DiscoveryError::Other("Body problem".into())),
@@ -886,8 +1429,7 @@ new_secret_type![
 ];
 impl PartialEq for Nonce {
     fn eq(&self, other: &Self) -> bool {
-        constant_time::verify_slices_are_equal(self.secret().as_bytes(), other.secret().as_bytes())
-            .is_ok()
+        constant_time_eq(self.secret().as_bytes(), other.secret().as_bytes())
     }
 }
 
@@ -934,6 +1476,14 @@ new_url_type![
     RequestUrl
 ];
 
+new_url_type![
+    ///
+    /// Token revocation endpoint URL as defined in
+    /// [RFC 7009](https://tools.ietf.org/html/rfc7009#section-2).
+    ///
+    RevocationUrl
+];
+
 ///
 /// Informs the Authorization Server of the desired authorization processing flow, including what
 /// parameters are returned from the endpoints used.
@@ -973,6 +1523,26 @@ new_type![
     pub(crate) Seconds(serde_json::Number)
 ];
 
+// Number of seconds represented either as a JSON number or as a string containing a number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum LenientSeconds {
+    Number(serde_json::Number),
+    String(String),
+}
+impl LenientSeconds {
+    pub(crate) fn into_seconds(self) -> Result<Seconds, String> {
+        match self {
+            LenientSeconds::Number(number) => Ok(Seconds::new(number)),
+            LenientSeconds::String(string) => string
+                .trim()
+                .parse::<serde_json::Number>()
+                .map(Seconds::new)
+                .map_err(|_| format!("failed to parse `{}` as a number of seconds", string)),
+        }
+    }
+}
+
 new_url_type![
     ///
     /// URL for retrieving redirect URIs that should receive identical pairwise subject identifiers.
@@ -987,6 +1557,92 @@ new_url_type![
     ServiceDocUrl
 ];
 
+new_type![
+    ///
+    /// Session state returned by the Authorization Endpoint (`session_state`), as defined in
+    /// [OpenID Connect Session Management](
+    /// https://openid.net/specs/openid-connect-session-1_0.html#CreatingUpdatingSessions).
+    ///
+    /// The session state represents the End-User's login status at the OpenID Connect Provider.
+    /// Relying Parties pass it to the provider's `check_session_iframe` (see
+    /// [`SessionState::check_session_message`]) to detect when that status changes.
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    SessionState(String)
+    impl {
+        ///
+        /// Computes the session state for the specified client and OpenID Connect Provider
+        /// browser state using the algorithm suggested in
+        /// [Section 4.2 of OpenID Connect Session Management](
+        /// https://openid.net/specs/openid-connect-session-1_0.html#OPiframe).
+        ///
+        /// The session state is the hex-encoded SHA-256 hash of the space-separated client ID,
+        /// Relying Party origin (e.g., `https://client.example.org`), browser state, and salt,
+        /// followed by a period and the salt.
+        ///
+        pub fn compute(
+            client_id: &ClientId,
+            origin: &str,
+            browser_state: &str,
+            salt: &str
+        ) -> Self {
+            let hash = sha256(
+                format!("{} {} {} {}", client_id.as_str(), origin, browser_state, salt).as_bytes(),
+            );
+            let hex_hash = hash
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            Self::new(format!("{}.{}", hex_hash, salt))
+        }
+
+        ///
+        /// Computes the session state for the specified client and OpenID Connect Provider
+        /// browser state using a random salt.
+        ///
+        pub fn new_random(client_id: &ClientId, origin: &str, browser_state: &str) -> Self {
+            let random_bytes: Vec<u8> = (0..16).map(|_| thread_rng().gen::<u8>()).collect();
+            let salt = base64::encode_config(&random_bytes, base64::URL_SAFE_NO_PAD);
+            Self::compute(client_id, origin, browser_state, &salt)
+        }
+
+        ///
+        /// Returns the salt used to compute the session state, if the session state was computed
+        /// using [`SessionState::compute`] or a compatible algorithm.
+        ///
+        pub fn salt(&self) -> Option<&str> {
+            self.0.rsplitn(2, '.').next().filter(|salt| salt.len() < self.0.len())
+        }
+
+        ///
+        /// Returns whether the session state corresponds to the specified client and OpenID Connect
+        /// Provider browser state.
+        ///
+        /// This is the check performed by the provider's `check_session_iframe` upon receiving a
+        /// check session message.
+        ///
+        pub fn verify(&self, client_id: &ClientId, origin: &str, browser_state: &str) -> bool {
+            self.salt().map_or(false, |salt| {
+                constant_time_eq(
+                    Self::compute(client_id, origin, browser_state, salt).as_bytes(),
+                    self.as_bytes(),
+                )
+            })
+        }
+
+        ///
+        /// Returns the message that the Relying Party's iframe posts to the OpenID Connect
+        /// Provider's `check_session_iframe` (i.e., the client ID and session state separated by a
+        /// space).
+        ///
+        /// The response is parsed by [`CheckSessionStatus::from_message`].
+        ///
+        pub fn check_session_message(&self, client_id: &ClientId) -> String {
+            format!("{} {}", client_id.as_str(), self.0)
+        }
+    }
+];
+
 new_type![
     ///
     /// A user's street address.
@@ -1007,6 +1663,26 @@ new_type![
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     SubjectIdentifier(String)
+    impl {
+        ///
+        /// Computes a pairwise subject identifier using the algorithm suggested in
+        /// [Section 8.1 of OpenID Connect Core](
+        /// https://openid.net/specs/openid-connect-core-1_0.html#PairwiseAlg).
+        ///
+        /// The identifier is the base64url-encoded SHA-256 hash of the concatenated sector
+        /// identifier (see the `sector_identifier` method of
+        /// [`ClientMetadata`](crate::registration::ClientMetadata)), local account ID, and salt.
+        /// The salt must be kept secret by the OpenID Connect Provider and must not change, since
+        /// doing so changes every pairwise subject identifier.
+        ///
+        pub fn pairwise(sector_identifier: &str, local_account_id: &str, salt: &[u8]) -> Self {
+            let mut input = Vec::new();
+            input.extend_from_slice(sector_identifier.as_bytes());
+            input.extend_from_slice(local_account_id.as_bytes());
+            input.extend_from_slice(salt);
+            Self::new(base64::encode_config(&sha256(&input), base64::URL_SAFE_NO_PAD))
+        }
+    }
 ];
 
 new_url_type![
@@ -1016,6 +1692,158 @@ new_url_type![
     ToSUrl
 ];
 
+new_url_type![
+    ///
+    /// URL of a trustmark (`vtm` claim), which asserts the [`VectorOfTrust`] components that an
+    /// OpenID Connect Provider is capable of issuing, as defined in
+    /// [RFC 8485](https://tools.ietf.org/html/rfc8485#section-5).
+    ///
+    TrustmarkUrl
+];
+
+new_type![
+    ///
+    /// Vector of Trust (e.g., `P1.Cc.Ac`), as defined in
+    /// [RFC 8485](https://tools.ietf.org/html/rfc8485).
+    ///
+    /// Vectors of Trust describe the identity proofing, credential, and assertion properties of an
+    /// authentication. They are returned in the `vot` claim and requested via the `vtr` parameter
+    /// (see [`AuthorizationRequest::add_vector_of_trust`](
+    /// crate::AuthorizationRequest::add_vector_of_trust)).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    VectorOfTrust(String)
+    impl {
+        ///
+        /// Parses the vector into its period-separated components.
+        ///
+        pub fn components(&self) -> Result<Vec<VectorOfTrustComponent>, VectorOfTrustParseError> {
+            self.0
+                .split('.')
+                .map(|component| {
+                    component
+                        .parse()
+                        .map_err(|_| VectorOfTrustParseError(self.0.clone()))
+                })
+                .collect()
+        }
+    }
+];
+
+///
+/// Component category of a [`VectorOfTrust`], as defined in
+/// [Section 3 of RFC 8485](https://tools.ietf.org/html/rfc8485#section-3).
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum VectorOfTrustCategory {
+    ///
+    /// Identity proofing (`P`).
+    ///
+    IdentityProofing,
+    ///
+    /// Primary credential usage (`C`).
+    ///
+    PrimaryCredentialUsage,
+    ///
+    /// Primary credential management (`M`).
+    ///
+    PrimaryCredentialManagement,
+    ///
+    /// Assertion presentation (`A`).
+    ///
+    AssertionPresentation,
+    ///
+    /// Category defined by a trust framework other than RFC 8485.
+    ///
+    Other(char),
+}
+impl VectorOfTrustCategory {
+    ///
+    /// Returns the category for the specified uppercase demarcator (e.g., `P`).
+    ///
+    pub fn from_char(demarcator: char) -> Self {
+        match demarcator {
+            'P' => VectorOfTrustCategory::IdentityProofing,
+            'C' => VectorOfTrustCategory::PrimaryCredentialUsage,
+            'M' => VectorOfTrustCategory::PrimaryCredentialManagement,
+            'A' => VectorOfTrustCategory::AssertionPresentation,
+            other => VectorOfTrustCategory::Other(other),
+        }
+    }
+
+    ///
+    /// Returns the uppercase demarcator identifying the category (e.g., `P`).
+    ///
+    pub fn as_char(&self) -> char {
+        match *self {
+            VectorOfTrustCategory::IdentityProofing => 'P',
+            VectorOfTrustCategory::PrimaryCredentialUsage => 'C',
+            VectorOfTrustCategory::PrimaryCredentialManagement => 'M',
+            VectorOfTrustCategory::AssertionPresentation => 'A',
+            VectorOfTrustCategory::Other(other) => other,
+        }
+    }
+}
+
+///
+/// Component of a [`VectorOfTrust`] (e.g., `Cc`), consisting of a category and a value.
+///
+/// Components are parsed from (via [`FromStr`]) and formatted as (via [`Display`]) an uppercase
+/// ASCII letter identifying the category followed by a lowercase ASCII letter or digit identifying
+/// the value.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VectorOfTrustComponent {
+    category: VectorOfTrustCategory,
+    value: char,
+}
+impl VectorOfTrustComponent {
+    ///
+    /// Returns the component's category.
+    ///
+    pub fn category(&self) -> VectorOfTrustCategory {
+        self.category
+    }
+
+    ///
+    /// Returns the component's value (e.g., `c` for the component `Cc`).
+    ///
+    pub fn value(&self) -> char {
+        self.value
+    }
+}
+impl Display for VectorOfTrustComponent {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}{}", self.category.as_char(), self.value)
+    }
+}
+impl FromStr for VectorOfTrustComponent {
+    type Err = VectorOfTrustParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(category), Some(value), None)
+                if category.is_ascii_uppercase()
+                    && (value.is_ascii_lowercase() || value.is_ascii_digit()) =>
+            {
+                Ok(Self {
+                    category: VectorOfTrustCategory::from_char(category),
+                    value,
+                })
+            }
+            _ => Err(VectorOfTrustParseError(s.to_string())),
+        }
+    }
+}
+
+///
+/// Error parsing a [`VectorOfTrust`] or [`VectorOfTrustComponent`].
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+#[fail(display = "Invalid vector of trust: {}", _0)]
+pub struct VectorOfTrustParseError(String);
+
 // FIXME: Add tests
 pub(crate) mod helpers {
     use chrono::{DateTime, TimeZone, Utc};
@@ -1023,7 +1851,17 @@ pub(crate) mod helpers {
     use serde::{Deserialize, Deserializer, Serializer};
     use serde_json::{from_value, Value};
 
-    use super::{LanguageTag, Seconds};
+    use super::{IssuerUrl, LanguageTag, Seconds};
+
+    pub fn deserialize_issuer_claim<'de, D>(deserializer: D) -> Result<IssuerUrl, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let issuer: String = Deserialize::deserialize(deserializer)?;
+        IssuerUrl::new_scheme_optional(issuer).map_err(Error::custom)
+    }
 
     pub fn deserialize_string_or_vec<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
     where
@@ -1228,7 +2066,232 @@ mod serde_base64url_byte_array {
 mod tests {
     use serde_json;
 
-    use super::IssuerUrl;
+    use crate::ClientId;
+
+    use super::{
+        AuthenticationMethodReference, AuthenticationMethodReferenceValue, Birthdate,
+        CheckSessionStatus, EndUserBirthday, IssuerUrl, LanguageTag, LocalizedClaim, SessionState,
+        SubjectIdentifier, VectorOfTrust, VectorOfTrustCategory, VectorOfTrustComponent,
+    };
+
+    #[test]
+    fn test_birthdate() {
+        let full = EndUserBirthday::new("1956-05-12".to_string())
+            .to_birthdate()
+            .unwrap();
+        assert_eq!(Some(1956), full.year());
+        assert_eq!(Some(5), full.month());
+        assert_eq!(Some(12), full.day());
+        assert_eq!(
+            chrono::NaiveDate::from_ymd_opt(1956, 5, 12),
+            full.to_naive_date()
+        );
+        assert_eq!("1956-05-12", full.to_string());
+
+        let year_only = "1956".parse::<Birthdate>().unwrap();
+        assert_eq!(Some(1956), year_only.year());
+        assert_eq!(None, year_only.month());
+        assert_eq!(None, year_only.to_naive_date());
+        assert_eq!("1956", year_only.to_string());
+
+        let month_day = "0000-02-29".parse::<Birthdate>().unwrap();
+        assert_eq!(None, month_day.year());
+        assert_eq!(Some(2), month_day.month());
+        assert_eq!(Some(29), month_day.day());
+        assert_eq!("0000-02-29", month_day.to_string());
+
+        for invalid in &[
+            "",
+            "0000",
+            "56",
+            "1956-5-12",
+            "1956-13-01",
+            "1957-02-29",
+            "1956-05",
+            "1956-05-12T00",
+            "+956-05-12",
+        ] {
+            invalid
+                .parse::<Birthdate>()
+                .expect_err(&format!("{} should be invalid", invalid));
+        }
+    }
+
+    #[test]
+    fn test_auth_method_reference_value() {
+        let values: Vec<AuthenticationMethodReferenceValue> =
+            serde_json::from_str("[\"pwd\", \"otp\", \"fpt\", \"password\"]").unwrap();
+        assert_eq!(
+            values,
+            vec![
+                AuthenticationMethodReferenceValue::Pwd,
+                AuthenticationMethodReferenceValue::Otp,
+                AuthenticationMethodReferenceValue::Fpt,
+                AuthenticationMethodReferenceValue::Other("password".to_string()),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&values).unwrap(),
+            "[\"pwd\",\"otp\",\"fpt\",\"password\"]"
+        );
+
+        assert_eq!(
+            AuthenticationMethodReference::new("mfa".to_string()).value(),
+            AuthenticationMethodReferenceValue::Mfa
+        );
+        assert_eq!(
+            AuthenticationMethodReference::from(AuthenticationMethodReferenceValue::Swk),
+            AuthenticationMethodReference::new("swk".to_string())
+        );
+    }
+
+    #[test]
+    fn test_localized_claim_lookup() {
+        let claim = vec![
+            (None, "default"),
+            (Some(LanguageTag::new("de".to_string())), "de"),
+            (Some(LanguageTag::new("en-US".to_string())), "en-US"),
+            (Some(LanguageTag::new("zh-Hant".to_string())), "zh-Hant"),
+        ]
+        .into_iter()
+        .collect::<LocalizedClaim<_>>();
+
+        let lookup = |locales: &[&str]| {
+            claim
+                .lookup(
+                    &locales
+                        .iter()
+                        .map(|locale| LanguageTag::new(locale.to_string()))
+                        .collect::<Vec<_>>(),
+                )
+                .cloned()
+        };
+
+        assert_eq!(Some("en-US"), lookup(&["en-us"]));
+        assert_eq!(Some("de"), lookup(&["de-CH-1996"]));
+        assert_eq!(Some("de"), lookup(&["fr", "de-AT"]));
+        assert_eq!(Some("zh-Hant"), lookup(&["zh-Hant-x-private"]));
+        // Lookup never broadens a match to a more specific tag.
+        assert_eq!(Some("default"), lookup(&["en"]));
+        assert_eq!(Some("default"), lookup(&[]));
+
+        let no_default = vec![(Some(LanguageTag::new("de".to_string())), "de")]
+            .into_iter()
+            .collect::<LocalizedClaim<_>>();
+        assert_eq!(
+            None,
+            no_default.lookup(&[LanguageTag::new("fr".to_string())])
+        );
+        assert_eq!(
+            vec![(Some(&LanguageTag::new("de".to_string())), &"de")],
+            no_default.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_pairwise_subject_identifier() {
+        let subject = SubjectIdentifier::pairwise("client.example.org", "248289761001", b"salt");
+        // The identifier is stable and URL-safe.
+        assert_eq!(
+            subject,
+            SubjectIdentifier::pairwise("client.example.org", "248289761001", b"salt")
+        );
+        assert_eq!(subject.len(), 43);
+        assert!(subject
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        // Different sectors, accounts, and salts produce different identifiers.
+        assert_ne!(
+            subject,
+            SubjectIdentifier::pairwise("other.example.org", "248289761001", b"salt")
+        );
+        assert_ne!(
+            subject,
+            SubjectIdentifier::pairwise("client.example.org", "248289761002", b"salt")
+        );
+        assert_ne!(
+            subject,
+            SubjectIdentifier::pairwise("client.example.org", "248289761001", b"pepper")
+        );
+    }
+
+    #[test]
+    fn test_session_state() {
+        let client_id = ClientId::new("s6BhdRkqt3".to_string());
+        let session_state =
+            SessionState::compute(&client_id, "https://client.example.org", "opbs", "salt");
+        assert_eq!(Some("salt"), session_state.salt());
+        assert_eq!(64 + ".salt".len(), session_state.len());
+        assert!(session_state.verify(&client_id, "https://client.example.org", "opbs"));
+        assert!(!session_state.verify(&client_id, "https://client.example.org", "other"));
+        assert!(!session_state.verify(&client_id, "https://other.example.org", "opbs"));
+        assert!(!session_state.verify(
+            &ClientId::new("other".to_string()),
+            "https://client.example.org",
+            "opbs"
+        ));
+        assert_eq!(
+            format!("s6BhdRkqt3 {}", session_state.as_str()),
+            session_state.check_session_message(&client_id)
+        );
+
+        let random = SessionState::new_random(&client_id, "https://client.example.org", "opbs");
+        assert!(random.verify(&client_id, "https://client.example.org", "opbs"));
+        assert_ne!(
+            random,
+            SessionState::new_random(&client_id, "https://client.example.org", "opbs")
+        );
+
+        assert_eq!(None, SessionState::new("unsalted".to_string()).salt());
+        assert_eq!(
+            Some(CheckSessionStatus::Changed),
+            CheckSessionStatus::from_message("changed")
+        );
+        assert_eq!(
+            Some(CheckSessionStatus::Unchanged),
+            CheckSessionStatus::from_message("unchanged")
+        );
+        assert_eq!(None, CheckSessionStatus::from_message("unknown"));
+    }
+
+    #[test]
+    fn test_vector_of_trust() {
+        let components = VectorOfTrust::new("P1.Cc.Cd.Aa.Xz".to_string())
+            .components()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (VectorOfTrustCategory::IdentityProofing, '1'),
+                (VectorOfTrustCategory::PrimaryCredentialUsage, 'c'),
+                (VectorOfTrustCategory::PrimaryCredentialUsage, 'd'),
+                (VectorOfTrustCategory::AssertionPresentation, 'a'),
+                (VectorOfTrustCategory::Other('X'), 'z'),
+            ],
+            components
+                .iter()
+                .map(|component| (component.category(), component.value()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "P1.Cc.Cd.Aa.Xz",
+            components
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(".")
+        );
+        assert_eq!(
+            VectorOfTrustCategory::PrimaryCredentialManagement,
+            "Ma".parse::<VectorOfTrustComponent>().unwrap().category()
+        );
+
+        for invalid in &["", "P1.", "P", "p1", "PA", "P1C", "P1.Cc.Aa!"] {
+            VectorOfTrust::new(invalid.to_string())
+                .components()
+                .expect_err(&format!("{} should be invalid", invalid));
+        }
+    }
 
     #[test]
     fn test_issuer_url_append() {
@@ -0,0 +1,150 @@
+use std::convert::TryFrom;
+use std::io::Read;
+
+use failure::Fail;
+use futures::executor::block_on;
+
+use crate::http_client::{HttpClient, RequestOptions, RequestPolicy};
+use crate::http_types::{Request, Response, StatusCode};
+
+///
+/// Error type returned by failed ureq HTTP requests.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum Error {
+    ///
+    /// Non-ureq HTTP error.
+    ///
+    #[fail(display = "HTTP error: {}", _0)]
+    Http(String),
+    ///
+    /// IO error.
+    ///
+    #[fail(display = "IO error")]
+    Io(#[cause] std::io::Error),
+    ///
+    /// The response body exceeded the maximum size specified by the request's [`RequestPolicy`].
+    ///
+    #[fail(display = "response body exceeds {} bytes", _0)]
+    ResponseTooLarge(usize),
+    ///
+    /// Error returned by ureq crate.
+    ///
+    #[fail(display = "ureq request failed: {}", _0)]
+    Ureq(String),
+}
+
+///
+/// Synchronous HTTP client backed by a shared [`ureq::Agent`].
+///
+/// Cloning a `UreqClient` is cheap and reuses the underlying connection pool. A reference to this
+/// type may be passed anywhere an [`HttpClient`](crate::HttpClient) is expected (e.g.,
+/// [`ProviderMetadata::discover_blocking`](crate::ProviderMetadata::discover_blocking)).
+///
+#[derive(Clone, Debug)]
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+impl UreqClient {
+    ///
+    /// Instantiates a new client with redirects disabled.
+    ///
+    pub fn new() -> Self {
+        Self::from_agent(
+            ureq::AgentBuilder::new()
+                // Following redirects opens the client up to SSRF vulnerabilities.
+                .redirects(0)
+                .build(),
+        )
+    }
+
+    ///
+    /// Instantiates a new client wrapping the specified [`ureq::Agent`].
+    ///
+    /// Redirects should be disabled on the provided agent to prevent SSRF vulnerabilities.
+    ///
+    pub fn from_agent(agent: ureq::Agent) -> Self {
+        Self { agent }
+    }
+
+    ///
+    /// Sends the specified request.
+    ///
+    pub fn send(&self, mut request: Request) -> Result<Response, Error> {
+        let mut ureq_request = self
+            .agent
+            .request(&request.method().to_string(), request.url().as_str());
+        if let Some(timeout) = RequestOptions::from_request(&request).and_then(|o| o.timeout()) {
+            ureq_request = ureq_request.timeout(timeout);
+        }
+        let max_response_size =
+            RequestPolicy::from_request(&request).and_then(RequestPolicy::max_response_size);
+        for (name, values) in request.iter() {
+            for value in values.iter() {
+                ureq_request = ureq_request.set(name.as_str(), value.as_str());
+            }
+        }
+        let body = block_on(request.body_bytes())
+            .map_err(|err| Error::Http(format!("failed to read request body: {}", err)))?;
+
+        let ureq_response = match ureq_request.send_bytes(&body) {
+            Ok(response) => response,
+            // Error responses are returned to the caller, which is responsible for interpreting
+            // the status code.
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(err) => return Err(Error::Ureq(err.to_string())),
+        };
+
+        let status_code = StatusCode::try_from(ureq_response.status())
+            .map_err(|err| Error::Http(format!("invalid HTTP status code: {}", err)))?;
+        let mut http_response = Response::new(status_code);
+        for name in ureq_response.headers_names() {
+            for value in ureq_response.all(&name) {
+                http_response.append_header(name.as_str(), value);
+            }
+        }
+
+        let mut response_body = Vec::new();
+        let mut reader = ureq_response.into_reader();
+        match max_response_size {
+            // Reading one byte past the limit distinguishes a body that's exactly at the limit
+            // from one that exceeds it.
+            Some(max_response_size) => {
+                reader
+                    .take(max_response_size as u64 + 1)
+                    .read_to_end(&mut response_body)
+                    .map_err(Error::Io)?;
+                if response_body.len() > max_response_size {
+                    return Err(Error::ResponseTooLarge(max_response_size));
+                }
+            }
+            None => {
+                reader.read_to_end(&mut response_body).map_err(Error::Io)?;
+            }
+        }
+        http_response.set_body(response_body);
+        Ok(http_response)
+    }
+}
+impl Default for UreqClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl HttpClient for UreqClient {
+    type Error = Error;
+
+    fn call(&self, request: Request) -> Result<Response, Error> {
+        self.send(request)
+    }
+}
+
+///
+/// Synchronous HTTP client using a newly constructed [`UreqClient`].
+///
+/// Prefer [`UreqClient`] when issuing several requests so that connections may be reused.
+///
+pub fn http_client(request: Request) -> Result<Response, Error> {
+    UreqClient::new().send(request)
+}
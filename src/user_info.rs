@@ -1,34 +1,62 @@
+use std::fmt::{Debug, Display, Formatter, Result as FormatterResult};
 use std::ops::Deref;
 use std::str;
 use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use failure::Fail;
+#[cfg(feature = "net")]
+use futures::executor::block_on;
+#[cfg(feature = "net")]
 use futures::Future;
+use oauth2::helpers::variant_name;
+#[cfg(feature = "net")]
 use oauth2::AccessToken;
+use oauth2::Scope;
 use serde_json;
+#[cfg(feature = "net")]
+use url::form_urlencoded;
 use url::Url;
 
-use crate::helpers::FilteredFlatten;
-use crate::http_types::headers::{HeaderValue, ACCEPT, CONTENT_TYPE};
-use crate::http_types::{Body, Method, Request, Response, StatusCode};
-use crate::http_utils::{auth_bearer, content_type_has_essence, MIME_TYPE_JSON, MIME_TYPE_JWT};
+use crate::claims::parse_claims_lenient;
+use crate::error::{ClassifiedError, ErrorKind};
+use crate::helpers::{serialize_claims, FilteredFlatten};
+#[cfg(feature = "net")]
+use crate::http_client::{
+    AsyncHttpClient, BlockingHttpClient, FnOnceHttpClient, HttpClient, HttpClientError,
+    InstrumentedHttpClient, Interceptors, RequestKind, RequestOptions, UnexpectedResponse,
+};
+#[cfg(feature = "net")]
+use crate::http_types::headers::{HeaderValue, ACCEPT, CONTENT_TYPE, WWW_AUTHENTICATE};
+#[cfg(feature = "net")]
+use crate::http_types::{Method, Request, Response, StatusCode};
+#[cfg(feature = "net")]
+use crate::http_utils::{
+    auth_bearer, content_type_has_essence, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON,
+    MIME_TYPE_JWT,
+};
+#[cfg(feature = "net")]
+use crate::jwt::JsonWebTokenAccess;
 use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
-use crate::types::helpers::deserialize_string_or_vec_opt;
+use crate::registration::{AdditionalClientMetadata, ClientMetadata};
+use crate::types::helpers::{deserialize_string_or_vec_opt, serde_utc_seconds_opt};
 use crate::types::LocalizedClaim;
 use crate::verification::UserInfoVerifier;
 use crate::{
-    AdditionalClaims, AddressClaim, Audience, AudiencesClaim, ClaimsVerificationError,
-    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
-    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
-    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, GenderClaim, IssuerClaim, IssuerUrl,
-    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JweContentEncryptionAlgorithm,
-    JwsSigningAlgorithm, LanguageTag, PrivateSigningKey, StandardClaims, SubjectIdentifier,
+    AdditionalClaims, AddressClaim, ApplicationType, Audience, AudiencesClaim, ClaimWarning,
+    ClaimsVerificationError, ClientAuthMethod, EndUserBirthday, EndUserEmail, EndUserFamilyName,
+    EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber,
+    EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl,
+    GenderClaim, GrantType, IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JsonWebToken, JsonWebTokenHeader, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
+    JwsSigningAlgorithm, LanguageTag, PrivateSigningKey, ResponseType, StandardClaims,
+    SubjectIdentifier, SubjectIdentifierType,
 };
 
 ///
-/// User info request.
+/// User info request. Requires "net" feature.
 ///
+#[cfg(feature = "net")]
 pub struct UserInfoRequest<JE, JS, JT, JU, K>
 where
     JE: JweContentEncryptionAlgorithm<JT>,
@@ -39,9 +67,38 @@ where
 {
     pub(super) url: UserInfoUrl,
     pub(super) access_token: AccessToken,
+    pub(super) accept: Option<String>,
+    pub(super) interceptors: Interceptors,
+    pub(super) request_options: RequestOptions,
     pub(super) require_signed_response: bool,
     pub(super) signed_response_verifier: UserInfoVerifier<'static, JE, JS, JT, JU, K>,
+    pub(super) token_in_body: bool,
+    pub(super) use_post: bool,
+}
+#[cfg(feature = "net")]
+impl<JE, JS, JT, JU, K> Debug for UserInfoRequest<JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // `AccessToken`'s `Debug` output is redacted.
+        f.debug_struct("UserInfoRequest")
+            .field("url", &self.url)
+            .field("access_token", &self.access_token)
+            .field("accept", &self.accept)
+            .field("interceptors", &self.interceptors)
+            .field("request_options", &self.request_options)
+            .field("require_signed_response", &self.require_signed_response)
+            .field("token_in_body", &self.token_in_body)
+            .field("use_post", &self.use_post)
+            .finish()
+    }
 }
+#[cfg(feature = "net")]
 impl<JE, JS, JT, JU, K> UserInfoRequest<JE, JS, JT, JU, K>
 where
     JE: JweContentEncryptionAlgorithm<JT>,
@@ -52,8 +109,12 @@ where
 {
     ///
     /// Submits this request to the associated user info endpoint using the specified asynchronous
-    /// HTTP client.
+    /// HTTP client, which is passed by value.
     ///
+    #[deprecated(
+        since = "2.0.0",
+        note = "use `request_async`, which borrows an `AsyncHttpClient` so that it may be reused"
+    )]
     pub async fn request<AC, C, F, GC, RE>(
         self,
         http_client: C,
@@ -64,22 +125,78 @@ where
         F: Future<Output = Result<Response, RE>>,
         GC: GenderClaim,
         RE: Fail,
+    {
+        self.request_async(&FnOnceHttpClient::new(http_client))
+            .await
+            .map_err(|err| err.into_by_value_error())
+    }
+
+    ///
+    /// Submits this request to the associated user info endpoint using the specified asynchronous
+    /// HTTP client.
+    ///
+    pub async fn request_async<AC, C, GC>(
+        self,
+        http_client: &C,
+    ) -> Result<UserInfoClaims<AC, GC>, UserInfoError<C::Error>>
+    where
+        AC: AdditionalClaims,
+        C: AsyncHttpClient + ?Sized,
+        GC: GenderClaim,
     {
         let http_request = self.prepare_request();
-        let http_response = http_client(http_request)
+        let http_client = self.interceptors.wrap(http_client);
+        let http_response = InstrumentedHttpClient::new(RequestKind::UserInfo, &http_client)
+            .call(http_request)
             .await
             .map_err(UserInfoError::Request)?;
 
         self.user_info_response(http_response).await
     }
 
+    ///
+    /// Submits this request to the associated user info endpoint using the specified synchronous
+    /// HTTP client.
+    ///
+    pub fn request_blocking<AC, C, GC>(
+        self,
+        http_client: &C,
+    ) -> Result<UserInfoClaims<AC, GC>, UserInfoError<C::Error>>
+    where
+        AC: AdditionalClaims,
+        C: HttpClient + ?Sized,
+        GC: GenderClaim,
+    {
+        block_on(self.request_async(&BlockingHttpClient(http_client)))
+    }
+
     fn prepare_request(&self) -> Request {
-        let (auth_header, auth_value) = auth_bearer(&self.access_token);
+        let method = if self.use_post || self.token_in_body {
+            Method::Post
+        } else {
+            Method::Get
+        };
 
-        let mut req = Request::new(Method::Get, self.url.url().clone());
-        req.insert_header(ACCEPT, MIME_TYPE_JSON);
-        req.insert_header(auth_header, auth_value);
-        req.set_body(Vec::new());
+        let mut req = Request::new(method, self.url.url().clone());
+        req.insert_header(
+            ACCEPT,
+            self.accept.as_ref().map_or(MIME_TYPE_JSON, String::as_str),
+        );
+        if self.token_in_body {
+            // See https://tools.ietf.org/html/rfc6750#section-2.2.
+            req.insert_header(CONTENT_TYPE, MIME_TYPE_FORM_URLENCODED);
+            req.set_body(
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair("access_token", self.access_token.secret())
+                    .finish()
+                    .into_bytes(),
+            );
+        } else {
+            let (auth_header, auth_value) = auth_bearer(&self.access_token);
+            req.insert_header(auth_header, auth_value);
+            req.set_body(Vec::new());
+        }
+        self.request_options.apply(&mut req);
         req
     }
 
@@ -93,9 +210,23 @@ where
         RE: Fail,
     {
         if http_response.status() != StatusCode::Ok {
+            // See https://tools.ietf.org/html/rfc6750#section-3.1.
+            if let StatusCode::BadRequest | StatusCode::Unauthorized | StatusCode::Forbidden =
+                http_response.status()
+            {
+                if let Some(bearer_token_error) =
+                    http_response.header(WWW_AUTHENTICATE).and_then(|values| {
+                        values.iter().find_map(|value| {
+                            BearerTokenError::from_www_authenticate(value.as_str())
+                        })
+                    })
+                {
+                    return Err(UserInfoError::BearerToken(bearer_token_error));
+                }
+            }
+
             return Err(UserInfoError::Response(
-                http_response.status(),
-                http_response.take_body(),
+                UnexpectedResponse::from_response(&mut http_response).await,
                 "unexpected HTTP status code".to_string(),
             ));
         }
@@ -121,21 +252,81 @@ where
                 let jwt_str = http_response.body_string().await.map_err(|_| {
                     UserInfoError::Other("response body has invalid UTF-8 encoding".to_string())
                 })?;
-                serde_json::from_value::<UserInfoJsonWebToken<AC, GC, JE, JS, JT>>(
-                    serde_json::Value::String(jwt_str),
-                )
-                .map_err(UserInfoError::Parse)?
-                .claims(&self.signed_response_verifier)
-                .map_err(UserInfoError::ClaimsVerification)
+                jwt_str
+                    .parse::<UserInfoJsonWebToken<AC, GC, JE, JS, JT>>()
+                    .map_err(UserInfoError::Parse)?
+                    .claims(&self.signed_response_verifier)
+                    .map_err(UserInfoError::ClaimsVerification)
             }
             ref content_type => Err(UserInfoError::Response(
-                http_response.status(),
-                http_response.take_body(),
+                UnexpectedResponse::from_response(&mut http_response).await,
                 format!("unexpected response Content-Type: `{:?}`", content_type),
             )),
         }
     }
 
+    ///
+    /// Sets the `Accept` header sent with this request.
+    ///
+    /// By default, `application/json` is requested. Providers that sign user info responses
+    /// return them as `application/jwt`, which may be preferred by passing
+    /// `"application/jwt, application/json;q=0.9"`.
+    ///
+    pub fn set_accept(mut self, accept: Option<String>) -> Self {
+        self.accept = accept;
+        self
+    }
+
+    ///
+    /// Adds an extra header to send with this request.
+    ///
+    pub fn add_header(mut self, name: &str, value: &str) -> Self {
+        self.request_options = self.request_options.add_header(name, value);
+        self
+    }
+
+    ///
+    /// Sends this request using the HTTP `POST` method instead of `GET`.
+    ///
+    /// [Section 5.3.1](https://openid.net/specs/openid-connect-core-1_0.html#UserInfoRequest)
+    /// permits either method.
+    ///
+    pub fn use_post(mut self) -> Self {
+        self.use_post = true;
+        self
+    }
+
+    ///
+    /// Specifies whether to send the access token as a form-encoded `access_token` parameter in
+    /// the request body instead of the `Authorization` header, as described in
+    /// [Section 2.2 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-2.2).
+    ///
+    /// This is useful when an intermediary strips the `Authorization` header. Sending the token in
+    /// the body requires the `POST` method, which is used automatically when this option is
+    /// enabled.
+    ///
+    pub fn set_token_in_body(mut self, token_in_body: bool) -> Self {
+        self.token_in_body = token_in_body;
+        self
+    }
+
+    ///
+    /// Returns the HTTP options (e.g., timeout and extra headers) applied to this request.
+    ///
+    pub fn request_options(&self) -> &RequestOptions {
+        &self.request_options
+    }
+
+    ///
+    /// Sets the HTTP options (e.g., timeout and extra headers) applied to this request.
+    ///
+    /// This replaces any headers previously added via [`UserInfoRequest::add_header`].
+    ///
+    pub fn set_request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
     ///
     /// Specifies whether to require the user info response to be a signed JSON Web Token (JWT).
     ///
@@ -144,6 +335,21 @@ where
         self
     }
 
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported for signed JWT responses. By
+    /// default, only `RS256` is supported.
+    ///
+    /// This option has no effect on unsigned JSON responses, which are accepted only if
+    /// [`UserInfoRequest::require_signed_response`] is not enabled.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.signed_response_verifier = self.signed_response_verifier.set_allowed_algs(algs);
+        self
+    }
+
     ///
     /// Specifies whether to require the issuer of the signed JWT response to match the expected
     /// issuer URL for this provider.
@@ -188,6 +394,8 @@ where
         Self(UserInfoClaimsImpl {
             issuer: None,
             audiences: None,
+            expiration: None,
+            issue_time: None,
             standard_claims,
             additional_claims: additional_claims.into(),
         })
@@ -208,7 +416,38 @@ where
     {
         let user_info = serde_json::from_slice::<UserInfoClaimsImpl<AC, GC>>(&user_info_json)
             .map_err(UserInfoError::Parse)?;
+        Self::verify_subject(user_info, expected_subject)
+    }
 
+    ///
+    /// Initializes user info claims from the provided raw JSON response, tolerating common
+    /// provider bugs.
+    ///
+    /// Returns the claims along with a [`ClaimWarning`] describing each coercion applied to them
+    /// (see [`ClaimWarning`] for the bugs tolerated). Otherwise, this function behaves like
+    /// [`UserInfoClaims::from_json`].
+    ///
+    pub fn from_json_lenient<RE>(
+        user_info_json: &[u8],
+        expected_subject: Option<&SubjectIdentifier>,
+    ) -> Result<(Self, Vec<ClaimWarning>), UserInfoError<RE>>
+    where
+        RE: Fail,
+    {
+        let mut warnings = Vec::new();
+        let user_info =
+            parse_claims_lenient::<UserInfoClaimsImpl<AC, GC>>(user_info_json, &mut warnings)
+                .map_err(UserInfoError::Parse)?;
+        Self::verify_subject(user_info, expected_subject).map(|claims| (claims, warnings))
+    }
+
+    fn verify_subject<RE>(
+        user_info: UserInfoClaimsImpl<AC, GC>,
+        expected_subject: Option<&SubjectIdentifier>,
+    ) -> Result<Self, UserInfoError<RE>>
+    where
+        RE: Fail,
+    {
         // This is the only verification we need to do for JSON-based user info claims, so don't
         // bother with the complexity of a separate verifier object.
         if expected_subject
@@ -232,6 +471,8 @@ where
         pub self [self.0] ["claim"] {
             set_issuer -> issuer[Option<IssuerUrl>],
             set_audiences -> audiences[Option<Vec<Audience>>] ["aud"],
+            set_expiration -> expiration[Option<DateTime<Utc>>] ["exp"],
+            set_issue_time -> issue_time[Option<DateTime<Utc>>] ["iat"],
         }
     ];
 
@@ -270,7 +511,7 @@ where
             set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
             set_phone_number_verified -> phone_number_verified[Option<bool>],
             set_address -> address[Option<AddressClaim>],
-            set_updated_at -> updated_at[Option<String>],
+            set_updated_at -> updated_at[Option<DateTime<Utc>>],
         }
     ];
 
@@ -293,6 +534,21 @@ where
     pub fn additional_claims_mut(&mut self) -> &mut AC {
         self.0.additional_claims.as_mut()
     }
+
+    ///
+    /// Returns all user info claims as a JSON object.
+    ///
+    /// The returned map contains the standard claims along with any additional claims. It's
+    /// serialized from the parsed claims, so it differs from the user info response in the same
+    /// ways as [`IdTokenClaims::all_claims`](crate::IdTokenClaims::all_claims). In particular,
+    /// claims not recognized by `AC` are discarded during deserialization; to retain every claim,
+    /// use `serde_json::Map<String, serde_json::Value>` as the additional claims type.
+    ///
+    pub fn all_claims(
+        &self,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, serde_json::Error> {
+        serialize_claims(&self.0)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -311,6 +567,20 @@ where
         skip_serializing_if = "Option::is_none"
     )]
     pub audiences: Option<Vec<Audience>>,
+    #[serde(
+        default,
+        rename = "exp",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    pub expiration: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        rename = "iat",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    pub issue_time: Option<DateTime<Utc>>,
 
     #[serde(bound = "GC: GenderClaim", flatten)]
     pub standard_claims: StandardClaims<GC>,
@@ -395,6 +665,47 @@ where
         Ok(Self(JsonWebToken::new(claims.0, signing_key, &alg)?))
     }
 
+    ///
+    /// Initializes a new signed JWT containing the specified claims for a User Info Response
+    /// matching the client's registered `userinfo_signed_response_alg` and
+    /// `userinfo_encrypted_response_alg` metadata. This is intended for use by OpenID Connect
+    /// Providers.
+    ///
+    /// Returns `Ok(None)` if the client didn't register a signing or encryption algorithm, in
+    /// which case the claims should be returned as a plain JSON object (see
+    /// [`UserInfoClaims`]). Encrypted responses aren't currently supported, so this function
+    /// returns [`JsonWebTokenError::Unsupported`] if the client registered an encryption
+    /// algorithm.
+    ///
+    pub fn new_for_client<A, AT, CA, G, JK, JU, K, RT, S, SK>(
+        claims: UserInfoClaims<AC, GC>,
+        client_metadata: &ClientMetadata<A, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        signing_key: &SK,
+    ) -> Result<Option<Self>, JsonWebTokenError>
+    where
+        A: AdditionalClientMetadata,
+        AT: ApplicationType,
+        CA: ClientAuthMethod,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        RT: ResponseType,
+        S: SubjectIdentifierType,
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        if let Some(encryption_alg) = client_metadata.userinfo_encrypted_response_alg() {
+            return Err(JsonWebTokenError::Unsupported(format!(
+                "JWE encryption is not currently supported (client registered algorithm `{}`)",
+                variant_name(encryption_alg),
+            )));
+        }
+        client_metadata
+            .userinfo_signed_response_alg()
+            .map(|alg| Self::new(claims, signing_key, alg.clone()))
+            .transpose()
+    }
+
     ///
     /// Verifies and returns the user info claims.
     ///
@@ -408,6 +719,50 @@ where
     {
         Ok(UserInfoClaims(verifier.verified_claims(self.0)?))
     }
+
+    ///
+    /// Returns a copy of the user info claims without verifying the JWT's signature or claims.
+    ///
+    /// This is intended for logging, debugging, and routing decisions that must be made before
+    /// the JWT can be verified.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned claims are untrusted and may have been forged or tampered with. They must not
+    /// be used for authentication or authorization decisions; use
+    /// [`UserInfoJsonWebToken::claims`] instead.
+    ///
+    pub fn unverified_claims(&self) -> UserInfoClaims<AC, GC> {
+        UserInfoClaims(self.0.unverified_payload_ref().clone())
+    }
+
+    ///
+    /// Returns the JOSE header of the JWT without verifying its signature.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned header is untrusted and may have been forged or tampered with.
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+}
+impl<AC, GC, JE, JS, JT> FromStr for UserInfoJsonWebToken<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+
+    ///
+    /// Parses a signed user info response in the JWS Compact Serialization without verifying it.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonWebToken::from_compact(s).map(Self)
+    }
 }
 
 new_url_type![
@@ -426,6 +781,16 @@ pub enum UserInfoError<RE>
 where
     RE: Fail,
 {
+    ///
+    /// The user info endpoint rejected the access token with an
+    /// [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3) `WWW-Authenticate` challenge.
+    ///
+    /// A [`BearerTokenErrorType::InvalidToken`] error typically indicates that the access token
+    /// should be refreshed, while a [`BearerTokenErrorType::InsufficientScope`] error indicates
+    /// that the user must consent to additional scopes.
+    ///
+    #[fail(display = "Access token rejected: {}", _0)]
+    BearerToken(BearerTokenError),
     ///
     /// Failed to verify user info claims.
     ///
@@ -443,10 +808,11 @@ where
     #[fail(display = "Request failed")]
     Request(#[cause] RE),
     ///
-    /// Server returned an invalid response.
+    /// Server returned an invalid response. Requires "net" feature.
     ///
-    #[fail(display = "Server returned invalid response: {}", _2)]
-    Response(StatusCode, Body, String),
+    #[cfg(feature = "net")]
+    #[fail(display = "Server returned invalid response: {} ({})", _1, _0)]
+    Response(UnexpectedResponse, String),
     ///
     /// An unexpected error occurred.
     ///
@@ -454,6 +820,44 @@ where
     Other(String),
 }
 
+impl<RE> ClassifiedError for UserInfoError<RE>
+where
+    RE: Fail,
+{
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            UserInfoError::BearerToken(_) => ErrorKind::ClientError,
+            UserInfoError::ClaimsVerification(_) => ErrorKind::Verification,
+            UserInfoError::Parse(_) => ErrorKind::InvalidResponse,
+            UserInfoError::Request(_) => ErrorKind::Request,
+            #[cfg(feature = "net")]
+            UserInfoError::Response(response, _) => ErrorKind::from_status(response.status()),
+            UserInfoError::Other(_) => ErrorKind::Other,
+        }
+    }
+}
+#[cfg(feature = "net")]
+impl<RE> UserInfoError<HttpClientError<RE>>
+where
+    RE: Fail,
+{
+    // Unwraps the errors of an HTTP client passed by value to a deprecated method (see
+    // `FnOnceHttpClient`).
+    pub(crate) fn into_by_value_error(self) -> UserInfoError<RE> {
+        match self {
+            UserInfoError::BearerToken(err) => UserInfoError::BearerToken(err),
+            UserInfoError::ClaimsVerification(err) => UserInfoError::ClaimsVerification(err),
+            UserInfoError::Parse(err) => UserInfoError::Parse(err),
+            UserInfoError::Request(HttpClientError::Request(err)) => UserInfoError::Request(err),
+            UserInfoError::Request(err) => UserInfoError::Other(err.to_string()),
+            UserInfoError::Response(response, message) => {
+                UserInfoError::Response(response, message)
+            }
+            UserInfoError::Other(message) => UserInfoError::Other(message),
+        }
+    }
+}
+
 ///
 /// The OpenID Connect Provider has no associated user info endpoint.
 ///
@@ -461,6 +865,530 @@ where
 #[fail(display = "No user info endpoint specified")]
 pub struct NoUserInfoEndpoint;
 
+///
+/// Error code returned by a protected resource in a `WWW-Authenticate` response header.
+///
+/// See [Section 3.1 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-3.1).
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BearerTokenErrorType {
+    ///
+    /// The request is missing a required parameter or is otherwise malformed.
+    ///
+    InvalidRequest,
+    ///
+    /// The access token is expired, revoked, malformed, or invalid for other reasons.
+    ///
+    InvalidToken,
+    ///
+    /// The request requires higher privileges than provided by the access token.
+    ///
+    InsufficientScope,
+    ///
+    /// An extension error code not defined by RFC 6750.
+    ///
+    Extension(String),
+}
+impl BearerTokenErrorType {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "invalid_request" => BearerTokenErrorType::InvalidRequest,
+            "invalid_token" => BearerTokenErrorType::InvalidToken,
+            "insufficient_scope" => BearerTokenErrorType::InsufficientScope,
+            extension => BearerTokenErrorType::Extension(extension.to_string()),
+        }
+    }
+
+    ///
+    /// Returns the error code as it appears in the `WWW-Authenticate` header.
+    ///
+    pub fn as_str(&self) -> &str {
+        match self {
+            BearerTokenErrorType::InvalidRequest => "invalid_request",
+            BearerTokenErrorType::InvalidToken => "invalid_token",
+            BearerTokenErrorType::InsufficientScope => "insufficient_scope",
+            BearerTokenErrorType::Extension(extension) => extension,
+        }
+    }
+}
+impl Display for BearerTokenErrorType {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+///
+/// Error returned by a protected resource (e.g., the user info endpoint) in the `Bearer`
+/// challenge of a `WWW-Authenticate` response header.
+///
+/// See [Section 3 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-3).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BearerTokenError {
+    error: BearerTokenErrorType,
+    error_description: Option<String>,
+    scopes: Option<Vec<Scope>>,
+}
+impl BearerTokenError {
+    ///
+    /// Parses the `Bearer` challenge from the specified `WWW-Authenticate` header value.
+    ///
+    /// Returns `None` if the header value doesn't contain a `Bearer` challenge with an `error`
+    /// parameter (e.g., if the request didn't include an access token).
+    ///
+    pub fn from_www_authenticate(header_value: &str) -> Option<Self> {
+        let params = parse_bearer_challenge(header_value)?;
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(param_name, _)| param_name.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        };
+
+        Some(Self {
+            error: BearerTokenErrorType::from_code(param("error")?),
+            error_description: param("error_description").map(ToString::to_string),
+            scopes: param("scope").map(|scope| {
+                scope
+                    .split_whitespace()
+                    .map(|scope| Scope::new(scope.to_string()))
+                    .collect()
+            }),
+        })
+    }
+
+    ///
+    /// Returns the error code.
+    ///
+    pub fn error(&self) -> &BearerTokenErrorType {
+        &self.error
+    }
+
+    ///
+    /// Returns the human-readable description of the error, if provided.
+    ///
+    pub fn error_description(&self) -> Option<&String> {
+        self.error_description.as_ref()
+    }
+
+    ///
+    /// Returns the scopes required to access the protected resource, if provided.
+    ///
+    pub fn scopes(&self) -> Option<&Vec<Scope>> {
+        self.scopes.as_ref()
+    }
+}
+impl Display for BearerTokenError {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        write!(f, "{}", self.error)?;
+        if let Some(ref error_description) = self.error_description {
+            write!(f, ": {}", error_description)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ChallengeToken {
+    Comma,
+    Equals,
+    Quoted(String),
+    Token(String),
+}
+
+// Splits a `WWW-Authenticate` header value into tokens, unescaping quoted strings. Returns `None`
+// if a quoted string is unterminated.
+fn tokenize_challenges(header_value: &str) -> Option<Vec<ChallengeToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = header_value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => {}
+            ',' => tokens.push(ChallengeToken::Comma),
+            '=' => tokens.push(ChallengeToken::Equals),
+            '"' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => quoted.push(chars.next()?),
+                        c => quoted.push(c),
+                    }
+                }
+                tokens.push(ChallengeToken::Quoted(quoted));
+            }
+            c => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if let ' ' | '\t' | ',' | '=' | '"' = c {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+                tokens.push(ChallengeToken::Token(token));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+// Returns the parameters of the `Bearer` challenge in a `WWW-Authenticate` header value, which may
+// contain multiple challenges (see https://tools.ietf.org/html/rfc7235#section-4.1).
+fn parse_bearer_challenge(header_value: &str) -> Option<Vec<(String, String)>> {
+    let tokens = tokenize_challenges(header_value)?;
+    // A challenge begins with an auth scheme, which isn't followed by `=` (unlike a parameter
+    // name).
+    let scheme_index = (0..tokens.len()).find(|&index| match tokens[index] {
+        ChallengeToken::Token(ref scheme) => {
+            scheme.eq_ignore_ascii_case("bearer")
+                && (index == 0 || tokens[index - 1] == ChallengeToken::Comma)
+                && tokens.get(index + 1) != Some(&ChallengeToken::Equals)
+        }
+        _ => false,
+    })?;
+
+    let mut params = Vec::new();
+    let mut rest = &tokens[scheme_index + 1..];
+    loop {
+        // Parameters end at the next challenge's auth scheme or at the end of the header value.
+        let (name, value, tail) = match rest {
+            [ChallengeToken::Token(name), ChallengeToken::Equals, value, tail @ ..] => {
+                match value {
+                    ChallengeToken::Token(value) | ChallengeToken::Quoted(value) => {
+                        (name, value, tail)
+                    }
+                    _ => break,
+                }
+            }
+            _ => break,
+        };
+        params.push((name.clone(), value.clone()));
+        rest = tail;
+
+        match rest {
+            [ChallengeToken::Comma, tail @ ..] => rest = tail,
+            _ => break,
+        }
+    }
+    Some(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::{ClientId, Scope};
+
+    use crate::core::{
+        CoreClientMetadata, CoreGenderClaim, CoreJsonWebKeySet, CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey, CoreUserInfoJsonWebToken,
+        CoreUserInfoVerifier,
+    };
+    use crate::jwt::tests::{assert_allowed_algs, TEST_RSA_PRIV_KEY};
+    use crate::jwt::JsonWebTokenError;
+    use crate::registration::EmptyAdditionalClientMetadata;
+    use crate::{
+        Audience, ClaimWarning, ClaimsVerificationError, EmptyAdditionalClaims, FixedClock,
+        IssuerUrl, PrivateSigningKey, StandardClaims, SubjectIdentifier, UserInfoClaims,
+        VerificationTimeOptions,
+    };
+
+    use super::{BearerTokenError, BearerTokenErrorType, UserInfoError};
+
+    #[test]
+    fn test_bearer_token_error() {
+        let error = BearerTokenError::from_www_authenticate(
+            "Bearer realm=\"example\", error=\"invalid_token\", \
+             error_description=\"The access token \\\"abc\\\" expired\"",
+        )
+        .unwrap();
+        assert_eq!(*error.error(), BearerTokenErrorType::InvalidToken);
+        assert_eq!(
+            error.error_description().unwrap(),
+            "The access token \"abc\" expired"
+        );
+        assert_eq!(error.scopes(), None);
+        assert_eq!(
+            error.to_string(),
+            "invalid_token: The access token \"abc\" expired"
+        );
+
+        // The Bearer challenge may follow other challenges, and parameter values may be tokens.
+        let error = BearerTokenError::from_www_authenticate(
+            "Basic realm=\"example\", bearer error=insufficient_scope, scope=\"openid email\"",
+        )
+        .unwrap();
+        assert_eq!(*error.error(), BearerTokenErrorType::InsufficientScope);
+        assert_eq!(error.error_description(), None);
+        assert_eq!(
+            error.scopes(),
+            Some(&vec![
+                Scope::new("openid".to_string()),
+                Scope::new("email".to_string())
+            ])
+        );
+
+        let error =
+            BearerTokenError::from_www_authenticate("Bearer error=\"use_dpop_nonce\"").unwrap();
+        assert_eq!(
+            *error.error(),
+            BearerTokenErrorType::Extension("use_dpop_nonce".to_string())
+        );
+
+        // Challenges without an error code (e.g., when no access token was sent) aren't errors.
+        assert_eq!(
+            BearerTokenError::from_www_authenticate("Bearer realm=\"example\""),
+            None
+        );
+        assert_eq!(
+            BearerTokenError::from_www_authenticate("Basic realm=\"error\", error=\"x\""),
+            None
+        );
+        assert_eq!(
+            BearerTokenError::from_www_authenticate("Bearer error=\"invalid_token"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_user_info_jwt_for_client() {
+        let claims = UserInfoClaims::new(
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let client_metadata = CoreClientMetadata::new(Vec::new(), EmptyAdditionalClientMetadata {});
+
+        // Clients that didn't register an algorithm expect a plain JSON response.
+        assert!(CoreUserInfoJsonWebToken::new_for_client(
+            claims.clone(),
+            &client_metadata,
+            &signing_key
+        )
+        .unwrap()
+        .is_none());
+
+        let client_metadata = client_metadata
+            .set_userinfo_signed_response_alg(Some(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512));
+        let jwt = CoreUserInfoJsonWebToken::new_for_client(
+            claims.clone(),
+            &client_metadata,
+            &signing_key,
+        )
+        .unwrap()
+        .unwrap();
+        let verifier = CoreUserInfoVerifier::new(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            None,
+        )
+        .require_issuer_match(false)
+        .require_audience_match(false)
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512]);
+        jwt.claims(&verifier).expect("RS512 should be used");
+
+        let client_metadata = client_metadata
+            .set_userinfo_encrypted_response_alg(Some(CoreJweKeyManagementAlgorithm::RsaOaep));
+        match CoreUserInfoJsonWebToken::new_for_client(claims, &client_metadata, &signing_key) {
+            Err(JsonWebTokenError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_user_info_allowed_algs() {
+        let claims = UserInfoClaims::new(
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        )
+        .set_issuer(Some(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+        ))
+        .set_audiences(Some(vec![Audience::new("s6BhdRkqt3".to_string())]));
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let verifier = CoreUserInfoVerifier::new(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            Some(SubjectIdentifier::new("24400320".to_string())),
+        );
+
+        assert_allowed_algs(
+            |alg| CoreUserInfoJsonWebToken::new(claims.clone(), &signing_key, alg).unwrap(),
+            verifier,
+            CoreUserInfoVerifier::set_allowed_algs,
+            CoreUserInfoVerifier::allow_any_alg,
+            |jwt: &CoreUserInfoJsonWebToken, verifier: &CoreUserInfoVerifier| {
+                jwt.clone().claims(verifier).map(|_| ())
+            },
+        );
+    }
+
+    #[test]
+    fn test_user_info_time_claims() {
+        let claims = UserInfoClaims::new(
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            EmptyAdditionalClaims {},
+        )
+        .set_issuer(Some(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+        ))
+        .set_audiences(Some(vec![Audience::new("s6BhdRkqt3".to_string())]));
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let new_jwt = |claims| {
+            CoreUserInfoJsonWebToken::new(
+                claims,
+                &signing_key,
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap()
+        };
+        let timed_jwt = new_jwt(
+            claims
+                .clone()
+                .set_issue_time(Some(Utc.timestamp(1311280970, 0)))
+                .set_expiration(Some(Utc.timestamp(1311281970, 0))),
+        );
+        let untimed_jwt = new_jwt(claims);
+
+        let verifier = CoreUserInfoVerifier::new(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            Some(SubjectIdentifier::new("24400320".to_string())),
+        );
+        let verify = |jwt: &CoreUserInfoJsonWebToken, now: i64, clock_skew_secs: u64| {
+            jwt.clone()
+                .claims(
+                    &verifier.clone().set_time_options(
+                        VerificationTimeOptions::new()
+                            .set_clock(FixedClock::new(Utc.timestamp(now, 0)))
+                            .set_clock_skew(Duration::from_secs(clock_skew_secs)),
+                    ),
+                )
+                .map(|_| ())
+        };
+
+        verify(&timed_jwt, 1311280970, 0).expect("user info should be valid when issued");
+        verify(&timed_jwt, 1311281969, 0).expect("user info should be valid until it expires");
+
+        // Expired.
+        match verify(&timed_jwt, 1311281970, 0) {
+            Err(ClaimsVerificationError::Expired(message)) => {
+                assert!(message.contains("user info expired"), "{}", message)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match verify(&timed_jwt, 1311282030, 60) {
+            Err(ClaimsVerificationError::Expired(message)) => {
+                assert!(message.contains("user info expired"), "{}", message)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Issued in the future.
+        match verify(&timed_jwt, 1311280969, 0) {
+            Err(ClaimsVerificationError::Expired(message)) => assert!(
+                message.contains("user info issued in the future"),
+                "{}",
+                message
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match verify(&timed_jwt, 1311280909, 60) {
+            Err(ClaimsVerificationError::Expired(message)) => assert!(
+                message.contains("user info issued in the future"),
+                "{}",
+                message
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Within the tolerated clock skew.
+        verify(&timed_jwt, 1311282029, 60).expect("expiration should tolerate clock skew");
+        verify(&timed_jwt, 1311280910, 60).expect("issue time should tolerate clock skew");
+
+        // The `exp` and `iat` claims are optional for user info responses.
+        verify(&untimed_jwt, 0, 0).expect("user info without time claims should be valid");
+        verify(&untimed_jwt, 4102444800, 0).expect("user info without time claims should be valid");
+    }
+
+    #[test]
+    fn test_from_json_lenient() {
+        let user_info_json = b"{
+            \"sub\": \"24400320\",
+            \"email\": \"janedoe@example.com\",
+            \"email_verified\": \"false\",
+            \"phone_number\": null,
+            \"updated_at\": \"1311283970\"
+        }";
+        let expected_subject = SubjectIdentifier::new("24400320".to_string());
+        type Claims = UserInfoClaims<EmptyAdditionalClaims, CoreGenderClaim>;
+
+        Claims::from_json::<io::Error>(user_info_json, Some(&expected_subject))
+            .expect_err("string email_verified claim should fail to parse strictly");
+
+        let (claims, warnings) =
+            Claims::from_json_lenient::<io::Error>(user_info_json, Some(&expected_subject))
+                .unwrap();
+        assert_eq!(claims.email_verified(), Some(false));
+        assert_eq!(claims.phone_number(), None);
+        assert_eq!(claims.updated_at(), Some(Utc.timestamp(1311283970, 0)));
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        for expected_warning in &[
+            ClaimWarning::ConvertedClaim("email_verified".to_string(), serde_json::json!(false)),
+            ClaimWarning::NullClaim("phone_number".to_string()),
+            ClaimWarning::ConvertedClaim("updated_at".to_string(), serde_json::json!(1311283970)),
+        ] {
+            assert!(warnings.contains(expected_warning), "{:?}", warnings);
+        }
+
+        let other_subject = SubjectIdentifier::new("other".to_string());
+        match Claims::from_json_lenient::<io::Error>(user_info_json, Some(&other_subject)) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::InvalidSubject(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_all_claims() {
+        type Claims = UserInfoClaims<serde_json::Map<String, serde_json::Value>, CoreGenderClaim>;
+
+        let claims = Claims::from_json::<io::Error>(
+            b"{
+                \"sub\": \"24400320\",
+                \"aud\": \"s6BhdRkqt3\",
+                \"email\": null,
+                \"updated_at\": 1311283970.5,
+                \"groups\": [\"admin\"],
+                \"login_count\": 42,
+                \"manager\": null
+            }",
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            claims.all_claims().unwrap(),
+            *serde_json::json!({
+                "sub": "24400320",
+                "aud": ["s6BhdRkqt3"],
+                "updated_at": 1311283970,
+                "groups": ["admin"],
+                "login_count": 42,
+                "manager": null
+            })
+            .as_object()
+            .unwrap()
+        );
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use crate::core::CoreGenderClaim;
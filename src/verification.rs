@@ -1,23 +1,27 @@
 use std::collections::HashSet;
-use std::fmt::Debug;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use oauth2::helpers::variant_name;
 use oauth2::{ClientId, ClientSecret};
-use ring::constant_time::verify_slices_are_equal;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::constant_time_eq;
 use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
 use crate::user_info::UserInfoClaimsImpl;
 use crate::{
-    AdditionalClaims, Audience, AuthenticationContextClass, GenderClaim, IdTokenClaims, IssuerUrl,
-    JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JsonWebTokenAccess,
-    JsonWebTokenAlgorithm, JsonWebTokenHeader, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
-    Nonce, SubjectIdentifier,
+    AccessTokenClaims, AdditionalClaims, Audience, AuthenticationContextClass,
+    AuthenticationMethodReference, AuthenticationMethodReferenceValue, ClaimWarning, GenderClaim,
+    IdToken, IdTokenClaims, IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse,
+    JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader, JsonWebTokenType,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LogoutTokenClaims, Nonce,
+    SecurityEventTokenClaims, SubjectIdentifier,
 };
 
 pub(crate) trait AudiencesClaim {
@@ -43,6 +47,9 @@ pub enum ClaimsVerificationError {
     /// Authorization context class reference (`acr`) claim is invalid.
     #[fail(display = "Invalid authorization context class reference: {}", _0)]
     InvalidAuthContext(String),
+    /// Authentication method reference (`amr`) claim is invalid.
+    #[fail(display = "Invalid authentication method reference: {}", _0)]
+    InvalidAuthMethod(String),
     /// User authenticated too long ago.
     #[fail(display = "Invalid authentication time: {}", _0)]
     InvalidAuthTime(String),
@@ -84,6 +91,10 @@ pub enum SignatureVerificationError {
     /// The supplied signature algorithm is disallowed by the verifier.
     #[fail(display = "Disallowed signature algorithm: {}", _0)]
     DisallowedAlg(String),
+    /// The JOSE header of the signature is invalid or lists an unsupported critical header
+    /// parameter.
+    #[fail(display = "Invalid JOSE header: {}", _0)]
+    InvalidHeader(String),
     /// The supplied key cannot be used in this context. This may occur if the key type does not
     /// match the signature type (e.g., an RSA key used to validate an HMAC) or the JWK usage
     /// disallows signatures.
@@ -99,7 +110,7 @@ pub enum SignatureVerificationError {
     /// [rotation of asymmetric signing keys](
     /// http://openid.net/specs/openid-connect-core-1_0.html#RotateSigKeys), client applications
     /// should consider refreshing the JWKS document (via
-    /// [`JsonWebKeySet::fetch`][crate::JsonWebKeySet::fetch]).
+    /// [`JsonWebKeySet::fetch_async`][crate::JsonWebKeySet::fetch_async]).
     ///
     /// This error can also occur if the identified
     /// [JSON Web Key](https://tools.ietf.org/html/rfc7517) is of the wrong type (e.g., an RSA key
@@ -114,6 +125,86 @@ pub enum SignatureVerificationError {
     Other(String),
 }
 
+// JOSE header parameters registered by RFC 7515 and RFC 7516, which must not be listed in 'crit'.
+const REGISTERED_JOSE_HEADERS: &[&str] = &[
+    "alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit", "enc", "zip",
+];
+
+// Validates the 'typ' header field. Shared with `JsonWebSignature`.
+pub(crate) fn validate_jwt_type<JE, JS, JT>(
+    jose_header: &JsonWebTokenHeader<JE, JS, JT>,
+    jwt_types: &[JsonWebTokenType],
+    jwt_type_required: bool,
+) -> Result<(), String>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    // The 'typ' header field must match one of the allowed types, which prevents JWTs intended
+    // for other purposes (e.g., logout tokens) from being accepted by this verifier.
+    match jose_header.typ {
+        Some(ref jwt_type) => {
+            if !jwt_types
+                .iter()
+                .any(|allowed_type| allowed_type.matches(jwt_type))
+            {
+                return Err(format!(
+                    "unexpected or unsupported JWT type `{}`",
+                    **jwt_type
+                ));
+            }
+        }
+        None if jwt_type_required => {
+            return Err("missing JWT type (`typ`) header".to_string());
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+// Validates the 'crit' header field. Shared with `JsonWebSignature`.
+pub(crate) fn validate_critical_headers<JE, JS, JT>(
+    jose_header: &JsonWebTokenHeader<JE, JS, JT>,
+    understood_critical_headers: &HashSet<String>,
+) -> Result<(), String>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    // If 'crit' fields are specified, we must reject any we do not understand. This
+    // implementation doesn't understand any extensions itself, so only those declared by the
+    // application are accepted. Note that the spec prohibits this field from containing any of
+    // the standard headers or being empty, and each listed parameter must be present.
+    // See https://tools.ietf.org/html/rfc7515#section-4.1.11.
+    if let Some(ref critical_headers) = jose_header.crit {
+        if critical_headers.is_empty() {
+            return Err("critical JWT header field list must not be empty".to_string());
+        }
+        for critical_header in critical_headers {
+            if REGISTERED_JOSE_HEADERS.contains(&critical_header.as_str()) {
+                return Err(format!(
+                    "registered JWT header field `{}` must not be marked critical",
+                    critical_header
+                ));
+            } else if !understood_critical_headers.contains(critical_header) {
+                // https://tools.ietf.org/html/rfc7515#appendix-E
+                return Err(format!(
+                    "unsupported critical JWT header field `{}`",
+                    critical_header
+                ));
+            } else if jose_header.other_param(critical_header).is_none() {
+                return Err(format!(
+                    "missing critical JWT header field `{}`",
+                    critical_header
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 // This struct is intentionally private.
 #[derive(Clone)]
 struct JwtClaimsVerifier<'a, JS, JT, JU, K>
@@ -127,9 +218,14 @@ where
     aud_match_required: bool,
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
+    critical_headers: HashSet<String>,
     iss_required: bool,
     issuer: IssuerUrl,
+    issuer_aliases: Vec<String>,
     is_signature_check_enabled: bool,
+    jwt_type_required: bool,
+    jwt_types: Vec<JsonWebTokenType>,
+    key_selection_policy: KeySelectionPolicy,
     other_aud_verifier_fn: Arc<dyn Fn(&Audience) -> bool + 'a + Send + Sync>,
     signature_keys: JsonWebKeySet<JS, JT, JU, K>,
 }
@@ -150,9 +246,16 @@ where
             aud_match_required: true,
             client_id,
             client_secret: None,
+            // No critical header extensions are understood unless the application declares them.
+            critical_headers: HashSet::new(),
             iss_required: true,
             issuer,
+            issuer_aliases: Vec::new(),
             is_signature_check_enabled: true,
+            // By default, the 'typ' header field must either be omitted or have the value JWT.
+            jwt_type_required: false,
+            jwt_types: vec![JsonWebTokenType::jwt()],
+            key_selection_policy: KeySelectionPolicy::new(),
             // Secure default: reject all other audiences as untrusted, since any other audience
             // can potentially impersonate the user when by sending its copy of these claims
             // to this relying party.
@@ -171,6 +274,15 @@ where
         self
     }
 
+    pub fn add_trusted_issuer_alias(mut self, issuer_alias: String) -> Self {
+        self.issuer_aliases.push(issuer_alias);
+        self
+    }
+
+    fn trusts_issuer(&self, issuer: &IssuerUrl) -> bool {
+        *issuer == self.issuer || self.issuer_aliases.contains(&**issuer)
+    }
+
     pub fn require_signature_check(mut self, sig_required: bool) -> Self {
         self.is_signature_check_enabled = sig_required;
         self
@@ -193,6 +305,32 @@ where
         self
     }
 
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_types = jwt_types.into_iter().collect();
+        self
+    }
+
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_type_required = jwt_type_required;
+        self
+    }
+
+    pub fn set_understood_critical_headers<I>(mut self, critical_headers: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.critical_headers = critical_headers.into_iter().collect();
+        self
+    }
+
+    pub fn set_key_selection_policy(mut self, key_selection_policy: KeySelectionPolicy) -> Self {
+        self.key_selection_policy = key_selection_policy;
+        self
+    }
+
     pub fn set_other_audience_verifier_fn<T>(mut self, other_aud_verifier_fn: T) -> Self
     where
         T: Fn(&Audience) -> bool + 'a + Send + Sync,
@@ -202,20 +340,14 @@ where
     }
 
     fn validate_jose_header<JE>(
+        &self,
         jose_header: &JsonWebTokenHeader<JE, JS, JT>,
     ) -> Result<(), ClaimsVerificationError>
     where
         JE: JweContentEncryptionAlgorithm<JT>,
     {
-        // The 'typ' header field must either be omitted or have the canonicalized value JWT.
-        if let Some(ref jwt_type) = jose_header.typ {
-            if jwt_type.to_uppercase() != "JWT" {
-                return Err(ClaimsVerificationError::Unsupported(format!(
-                    "unexpected or unsupported JWT type `{}`",
-                    **jwt_type
-                )));
-            }
-        }
+        validate_jwt_type(jose_header, &self.jwt_types, self.jwt_type_required)
+            .map_err(ClaimsVerificationError::Unsupported)?;
         // The 'cty' header field must be omitted, since it's only used for JWTs that contain
         // content types other than JSON-encoded claims. This may include nested JWTs, such as if
         // JWE encryption is used. This is currently unsupported.
@@ -231,17 +363,8 @@ where
                 )));
             }
         }
-
-        // If 'crit' fields are specified, we must reject any we do not understand. Since this
-        // implementation doesn't understand any of them, unconditionally reject the JWT. Note that
-        // the spec prohibits this field from containing any of the standard headers or being empty.
-        if jose_header.crit.is_some() {
-            // https://tools.ietf.org/html/rfc7515#appendix-E
-            return Err(ClaimsVerificationError::Unsupported(
-                "critical JWT header fields are unsupported".to_string(),
-            ));
-        }
-        Ok(())
+        validate_critical_headers(jose_header, &self.critical_headers)
+            .map_err(ClaimsVerificationError::Unsupported)
     }
 
     pub fn verified_claims<A, C, JE, T>(&self, jwt: A) -> Result<T, ClaimsVerificationError>
@@ -253,7 +376,7 @@ where
     {
         {
             let jose_header = jwt.unverified_header();
-            Self::validate_jose_header(jose_header)?;
+            self.validate_jose_header(jose_header)?;
 
             // The code below roughly follows the validation steps described in
             // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
@@ -269,6 +392,15 @@ where
                     variant_name(encryption_alg),
                 )));
             }
+
+            // Unsigned JWTs are rejected before examining any claims. Since `none` is not a JWS
+            // signing algorithm, it can never be included in the allowed algorithms and is only
+            // accepted if signature verification is disabled entirely.
+            if let JsonWebTokenAlgorithm::None = jose_header.alg {
+                if self.is_signature_check_enabled {
+                    return Err(ClaimsVerificationError::NoSignature);
+                }
+            }
         }
 
         // TODO: Add encryption (JWE) support
@@ -278,7 +410,7 @@ where
             let unverified_claims = jwt.unverified_payload_ref();
             if self.iss_required {
                 if let Some(issuer) = unverified_claims.issuer() {
-                    if *issuer != self.issuer {
+                    if !self.trusts_issuer(issuer) {
                         return Err(ClaimsVerificationError::InvalidIssuer(format!(
                             "expected `{}` (found `{}`)",
                             *self.issuer, **issuer
@@ -409,7 +541,8 @@ where
         // if the JWK set contains more than one public key.
 
         // See if any key has a matching key ID (if supplied) and compatible type.
-        let public_keys = {
+        let policy = &self.key_selection_policy;
+        let mut public_keys = {
             let jose_header = jwt.unverified_header();
             self.signature_keys
                 .keys()
@@ -418,22 +551,42 @@ where
                     // The key must be of the type expected for this signature algorithm.
                     Some(key.key_type()) == signature_alg.key_type().as_ref() &&
                         // Either the key hasn't specified it's allowed usage (in which case
-                        // any usage is acceptable), or the key supports signing.
-                        (key.key_use().is_none() ||
-                            key.key_use().iter().any(
-                                |key_use| key_use.allows_signature()
-                            )) &&
+                        // any usage is acceptable unless the policy requires it), or the key
+                        // supports signing.
+                        key.key_use().map_or(
+                            !policy.require_signature_use,
+                            |key_use| key_use.allows_signature()
+                        ) &&
+                        // Either the key hasn't specified an algorithm (or the policy ignores
+                        // it), or the algorithm matches the JWT's.
+                        (!policy.filter_by_alg ||
+                            key.signing_alg().map_or(true, |alg| *alg == signature_alg)) &&
                         // Either the JWT doesn't include a 'kid' (in which case any 'kid'
                         // is acceptable), or the 'kid' matches the key's ID.
                         (jose_header.kid.is_none() ||
                             jose_header.kid.as_ref() == key.key_id()))
                 .collect::<Vec<&K>>()
         };
+        if policy.newest_first {
+            public_keys.reverse();
+        }
+
         if public_keys.is_empty() {
             return Err(ClaimsVerificationError::SignatureVerification(
                 SignatureVerificationError::NoMatchingKey,
             ));
-        } else if public_keys.len() != 1 {
+        } else if policy.try_all_matching_keys {
+            let mut last_err = None;
+            for key in public_keys {
+                match jwt.verify_signature(&signature_alg, key) {
+                    Ok(()) => return Ok(jwt.unverified_payload()),
+                    Err(err) => last_err = Some(err),
+                }
+            }
+            return Err(ClaimsVerificationError::SignatureVerification(
+                last_err.expect("unreachable"),
+            ));
+        } else if public_keys.len() != 1 && !policy.newest_first {
             return Err(ClaimsVerificationError::SignatureVerification(
                 SignatureVerificationError::AmbiguousKeyId(format!(
                     "JWK set must only contain one eligible public key \
@@ -479,9 +632,7 @@ pub trait NonceVerifier {
 impl NonceVerifier for &Nonce {
     fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
         if let Some(claims_nonce) = nonce {
-            if verify_slices_are_equal(claims_nonce.secret().as_bytes(), self.secret().as_bytes())
-                .is_err()
-            {
+            if !constant_time_eq(claims_nonce.secret().as_bytes(), self.secret().as_bytes()) {
                 return Err("nonce mismatch".to_string());
             }
         } else {
@@ -500,9 +651,208 @@ where
     }
 }
 
+///
+/// Policy for selecting which keys in the provider's JSON Web Key Set are used to verify a JWT
+/// signature.
+///
+/// By default, exactly one key in the JWK set must be compatible with the JWT's signature
+/// algorithm and match its key ID (`kid`), if present. Otherwise, verification fails with
+/// [`SignatureVerificationError::AmbiguousKeyId`]. Providers that rotate keys without assigning
+/// key IDs, or that reuse key IDs across keys, may require relaxing this policy.
+///
+/// This policy is shared by [`IdTokenVerifier`] and [`UserInfoVerifier`].
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeySelectionPolicy {
+    filter_by_alg: bool,
+    newest_first: bool,
+    require_signature_use: bool,
+    try_all_matching_keys: bool,
+}
+impl KeySelectionPolicy {
+    ///
+    /// Instantiates the default policy, which requires exactly one eligible key.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Specifies whether keys that specify a signature algorithm (`alg`) other than the JWT's are
+    /// excluded. Keys that don't specify an algorithm remain eligible.
+    ///
+    pub fn set_filter_by_alg(mut self, filter_by_alg: bool) -> Self {
+        self.filter_by_alg = filter_by_alg;
+        self
+    }
+
+    ///
+    /// Specifies whether keys must explicitly allow signature usage (i.e., `"use": "sig"`). By
+    /// default, keys that don't specify a usage are also eligible.
+    ///
+    pub fn set_require_signature_use(mut self, require_signature_use: bool) -> Self {
+        self.require_signature_use = require_signature_use;
+        self
+    }
+
+    ///
+    /// Specifies whether each eligible key is tried in turn until one verifies the signature,
+    /// rather than rejecting the JWT when more than one key is eligible.
+    ///
+    pub fn set_try_all_matching_keys(mut self, try_all_matching_keys: bool) -> Self {
+        self.try_all_matching_keys = try_all_matching_keys;
+        self
+    }
+
+    ///
+    /// Specifies whether the newest eligible key is preferred. Keys are assumed to be listed in the
+    /// JWK set from oldest to newest, as is typical of providers that append keys during rotation.
+    ///
+    /// When enabled, the last eligible key is used if more than one key is eligible, or is tried
+    /// first if [`KeySelectionPolicy::set_try_all_matching_keys`] is also enabled.
+    ///
+    pub fn set_newest_first(mut self, newest_first: bool) -> Self {
+        self.newest_first = newest_first;
+        self
+    }
+}
+
+///
+/// Time source and clock skew tolerance used when verifying time-based claims (e.g., `exp` and
+/// `iat`).
+///
+/// These options are shared by all of the verifiers in this crate (e.g., [`IdTokenVerifier`] and
+/// [`AccessTokenVerifier`]). The clock must be `Send` and `Sync` so that verifiers which are
+/// otherwise thread-safe may be shared by concurrent requests.
+///
+#[derive(Clone)]
+pub struct VerificationTimeOptions<'a> {
+    clock: Arc<dyn Clock + Send + Sync + 'a>,
+    clock_skew: Duration,
+}
+impl<'a> VerificationTimeOptions<'a> {
+    ///
+    /// Instantiates options that use the [`SystemClock`] and tolerate no clock skew.
+    ///
+    pub fn new() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            clock_skew: Duration::from_secs(0),
+        }
+    }
+
+    ///
+    /// Specifies the source of the current time.
+    ///
+    pub fn set_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'a,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    ///
+    /// Specifies the maximum tolerated difference between the local clock and the provider's
+    /// clock.
+    ///
+    /// Claims are treated as expired only once the current time is at least this long after the
+    /// expiration time, and issue times are rejected only if they are more than this far in the
+    /// future.
+    ///
+    pub fn set_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    ///
+    /// Returns the maximum tolerated clock skew.
+    ///
+    pub fn clock_skew(&self) -> Duration {
+        self.clock_skew
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This is equivalent to calling [`VerificationTimeOptions::set_clock`] with the function.
+    ///
+    pub fn set_time_fn<T>(self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'a,
+    {
+        self.set_clock(time_fn)
+    }
+
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    // Returns the current time if the specified expiration time has passed, accounting for clock
+    // skew.
+    pub(crate) fn check_expiration(&self, expiration: DateTime<Utc>) -> Result<(), DateTime<Utc>> {
+        let cur_time = self.now();
+        // A clock skew too large to represent never expires the claims.
+        let expired = chrono::Duration::from_std(self.clock_skew)
+            .ok()
+            .and_then(|skew| expiration.checked_add_signed(skew))
+            .map_or(false, |deadline| cur_time >= deadline);
+        if expired {
+            Err(cur_time)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Returns the current time if the specified issue time is in the future, accounting for clock
+    // skew.
+    pub(crate) fn check_issue_time(&self, issue_time: DateTime<Utc>) -> Result<(), DateTime<Utc>> {
+        let cur_time = self.now();
+        let future = chrono::Duration::from_std(self.clock_skew)
+            .ok()
+            .and_then(|skew| cur_time.checked_add_signed(skew))
+            .map_or(false, |latest| issue_time > latest);
+        if future {
+            Err(cur_time)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Rejects an issue time in the future (see `check_issue_time`). The token description (e.g.,
+    // "logout token") is included in the error message.
+    pub(crate) fn verify_issue_time(
+        &self,
+        issue_time: DateTime<Utc>,
+        token_description: &str,
+    ) -> Result<(), ClaimsVerificationError> {
+        self.check_issue_time(issue_time).map_err(|cur_time| {
+            ClaimsVerificationError::Expired(format!(
+                "{} issued in the future at {} (current time is {})",
+                token_description, issue_time, cur_time
+            ))
+        })
+    }
+}
+impl<'a> Default for VerificationTimeOptions<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<'a> Debug for VerificationTimeOptions<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("VerificationTimeOptions")
+            .field("clock_skew", &self.clock_skew)
+            .finish()
+    }
+}
+
 ///
 /// ID token verifier.
 ///
+/// The verifier is `Send` and `Sync` (provided its type parameters are), so the verifier functions
+/// specified via methods such as [`IdTokenVerifier::set_auth_time_verifier_fn`] must be as well.
+///
 #[derive(Clone)]
 pub struct IdTokenVerifier<'a, JS, JT, JU, K>
 where
@@ -511,12 +861,23 @@ where
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
 {
-    acr_verifier_fn: Arc<dyn Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a>,
     #[allow(clippy::type_complexity)]
-    auth_time_verifier_fn: Arc<dyn Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a>,
-    iat_verifier_fn: Arc<dyn Fn(DateTime<Utc>) -> Result<(), String> + 'a>,
+    acr_verifier_fn:
+        Arc<dyn Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + Send + Sync + 'a>,
+    #[allow(clippy::type_complexity)]
+    amr_verifier_fn: Arc<
+        dyn Fn(Option<&Vec<AuthenticationMethodReference>>) -> Result<(), String>
+            + Send
+            + Sync
+            + 'a,
+    >,
+    #[allow(clippy::type_complexity)]
+    auth_time_verifier_fn:
+        Arc<dyn Fn(Option<DateTime<Utc>>) -> Result<(), String> + Send + Sync + 'a>,
+    iat_verifier_fn: Arc<dyn Fn(DateTime<Utc>) -> Result<(), String> + Send + Sync + 'a>,
     jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
-    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a>,
+    original_claims: Option<OriginalIdTokenClaims>,
+    time_options: VerificationTimeOptions<'a>,
 }
 impl<'a, JS, JT, JU, K> IdTokenVerifier<'a, JS, JT, JU, K>
 where
@@ -529,12 +890,15 @@ where
         IdTokenVerifier {
             // By default, accept authorization context reference (acr claim).
             acr_verifier_fn: Arc::new(|_| Ok(())),
+            // By default, accept any authentication methods (amr claim).
+            amr_verifier_fn: Arc::new(|_| Ok(())),
             auth_time_verifier_fn: Arc::new(|_| Ok(())),
             // By default, accept any issued time (iat claim).
             iat_verifier_fn: Arc::new(|_| Ok(())),
             jwt_verifier,
-            // By default, use the current system time.
-            time_fn: Arc::new(Utc::now),
+            original_claims: None,
+            // By default, use the current system time and tolerate no clock skew.
+            time_options: VerificationTimeOptions::new(),
         }
     }
 
@@ -569,7 +933,10 @@ where
     }
 
     ///
-    /// Specifies which JSON Web Signature algorithms are supported.
+    /// Specifies which JSON Web Signature algorithms are supported. By default, only `RS256` is
+    /// supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are always rejected.
     ///
     pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
     where
@@ -579,9 +946,93 @@ where
         self
     }
 
+    ///
+    /// Specifies the policy for selecting which keys in the JWK set are used to verify the ID
+    /// token's signature.
+    ///
+    pub fn set_key_selection_policy(mut self, key_selection_policy: KeySelectionPolicy) -> Self {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_key_selection_policy(key_selection_policy);
+        self
+    }
+
+    ///
+    /// Specifies which JWT types (`typ` header values) are accepted. By default, only `JWT` is
+    /// accepted.
+    ///
+    /// Types are compared as described in [`JsonWebTokenType::matches`]. ID tokens without a
+    /// `typ` header are accepted unless [`IdTokenVerifier::require_jwt_type`] is enabled.
+    ///
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_jwt_types(jwt_types);
+        self
+    }
+
+    ///
+    /// Specifies whether ID tokens must include a `typ` header. By default, the header is
+    /// optional.
+    ///
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_jwt_type(jwt_type_required);
+        self
+    }
+
+    ///
+    /// Specifies the critical JOSE header extensions (i.e., values of the `crit` header) that the
+    /// application understands and processes. By default, JWTs with a `crit` header are
+    /// rejected.
+    ///
+    /// The application is responsible for processing these extensions, which are available via
+    /// [`JsonWebTokenHeader::other_param`].
+    ///
+    pub fn set_understood_critical_headers<I>(mut self, critical_headers: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_understood_critical_headers(critical_headers);
+        self
+    }
+
+    ///
+    /// Configures this verifier to verify an ID token returned in a refresh token response.
+    ///
+    /// In addition to the usual checks, [Section 12.2](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse) requires the
+    /// `iss`, `sub`, and `aud` claims of the new ID token to match those of the ID token issued at
+    /// the time of the original authentication (`original_claims`). If present, the `auth_time`,
+    /// `azp`, and `nonce` claims must also match the original values.
+    ///
+    /// Since the `nonce` claim is verified against the original ID token, callers may pass a
+    /// no-op [`NonceVerifier`] (e.g., `|_: Option<&Nonce>| Ok(())`) when verifying the refreshed
+    /// ID token.
+    ///
+    pub fn for_refresh<AC, GC>(mut self, original_claims: &IdTokenClaims<AC, GC>) -> Self
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+    {
+        self.original_claims = Some(OriginalIdTokenClaims {
+            audiences: original_claims.audiences().clone(),
+            auth_time: original_claims.auth_time(),
+            authorized_party: original_claims.authorized_party().cloned(),
+            issuer: original_claims.issuer().clone(),
+            nonce: original_claims.nonce().cloned(),
+            subject: original_claims.subject().clone(),
+        });
+        self
+    }
+
     ///
     /// Specifies that any signature algorithm is supported.
     ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are still rejected.
+    ///
     pub fn allow_any_alg(mut self) -> Self {
         self.jwt_verifier = self.jwt_verifier.allow_any_alg();
         self
@@ -595,12 +1046,61 @@ where
     ///
     pub fn set_auth_context_verifier_fn<T>(mut self, acr_verifier_fn: T) -> Self
     where
-        T: Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a,
+        T: Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + Send + Sync + 'a,
     {
         self.acr_verifier_fn = Arc::new(acr_verifier_fn);
         self
     }
 
+    ///
+    /// Specifies a function for verifying the `amr` claim.
+    ///
+    /// The function should return `Ok(())` if the claim is valid, or a string describing the error
+    /// otherwise.
+    ///
+    pub fn set_auth_method_verifier_fn<T>(mut self, amr_verifier_fn: T) -> Self
+    where
+        T: Fn(Option<&Vec<AuthenticationMethodReference>>) -> Result<(), String> + Send + Sync + 'a,
+    {
+        self.amr_verifier_fn = Arc::new(amr_verifier_fn);
+        self
+    }
+
+    ///
+    /// Requires the `amr` claim to include each of the specified authentication methods (e.g.,
+    /// [`AuthenticationMethodReferenceValue::Mfa`]).
+    ///
+    /// This replaces any function previously specified using
+    /// [`IdTokenVerifier::set_auth_method_verifier_fn`].
+    ///
+    pub fn require_auth_methods<I>(self, required_methods: I) -> Self
+    where
+        I: IntoIterator<Item = AuthenticationMethodReferenceValue>,
+    {
+        let required_methods = required_methods.into_iter().collect::<Vec<_>>();
+        self.set_auth_method_verifier_fn(move |auth_method_refs| {
+            let missing_methods = required_methods
+                .iter()
+                .filter(|required_method| {
+                    !auth_method_refs.map_or(false, |auth_method_refs| {
+                        auth_method_refs
+                            .iter()
+                            .any(|auth_method_ref| auth_method_ref.value() == **required_method)
+                    })
+                })
+                .map(|required_method| required_method.as_ref())
+                .collect::<Vec<_>>();
+            if missing_methods.is_empty() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "missing required authentication methods: {}",
+                    missing_methods.join(", ")
+                ))
+            }
+        })
+    }
+
     ///
     /// Specifies a function for verifying the `auth_time` claim.
     ///
@@ -609,7 +1109,7 @@ where
     ///
     pub fn set_auth_time_verifier_fn<T>(mut self, auth_time_verifier_fn: T) -> Self
     where
-        T: Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a,
+        T: Fn(Option<DateTime<Utc>>) -> Result<(), String> + Send + Sync + 'a,
     {
         self.auth_time_verifier_fn = Arc::new(auth_time_verifier_fn);
         self
@@ -648,6 +1148,28 @@ where
         self
     }
 
+    ///
+    /// Trusts the specified issuer identifier as equivalent to this verifier's issuer.
+    ///
+    /// ID tokens are accepted if their `iss` claim exactly matches either the issuer or one of the
+    /// trusted aliases. For example, Google historically issues some ID tokens with an `iss` claim
+    /// of `accounts.google.com` (without the `https://` scheme), which can be accepted alongside
+    /// `https://accounts.google.com` by adding it as an alias. Unlike disabling
+    /// [`IdTokenVerifier::require_issuer_match`], all other issuers are still rejected.
+    ///
+    pub fn add_trusted_issuer_alias(mut self, issuer_alias: String) -> Self {
+        self.jwt_verifier = self.jwt_verifier.add_trusted_issuer_alias(issuer_alias);
+        self
+    }
+
+    pub(crate) fn issuer(&self) -> &IssuerUrl {
+        &self.jwt_verifier.issuer
+    }
+
+    pub(crate) fn trusts_issuer(&self, issuer: &IssuerUrl) -> bool {
+        self.jwt_verifier.trusts_issuer(issuer)
+    }
+
     ///
     /// Specifies whether the audience claim must match this client's client ID.
     ///
@@ -661,11 +1183,40 @@ where
     ///
     /// This function is used for verifying the ID token expiration time.
     ///
-    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    pub fn set_time_fn<T>(self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'a,
+    {
+        self.set_clock(time_fn)
+    }
+
+    ///
+    /// Specifies the source of the current time (see [`IdTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock<C>(self, clock: C) -> Self
     where
-        T: Fn() -> DateTime<Utc> + 'a,
+        C: Clock + Send + Sync + 'a,
     {
-        self.time_fn = Arc::new(time_fn);
+        let time_options = self.time_options.clone().set_clock(clock);
+        self.set_time_options(time_options)
+    }
+
+    ///
+    /// Specifies the maximum tolerated clock skew (see [`IdTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock_skew(self, clock_skew: Duration) -> Self {
+        let time_options = self.time_options.clone().set_clock_skew(clock_skew);
+        self.set_time_options(time_options)
+    }
+
+    ///
+    /// Specifies the time source and clock skew tolerance used for verifying the ID token
+    /// expiration and issue times, replacing any previously set via
+    /// [`IdTokenVerifier::set_clock`], [`IdTokenVerifier::set_time_fn`], or
+    /// [`IdTokenVerifier::set_clock_skew`].
+    ///
+    pub fn set_time_options(mut self, time_options: VerificationTimeOptions<'a>) -> Self {
+        self.time_options = time_options;
         self
     }
 
@@ -677,7 +1228,7 @@ where
     ///
     pub fn set_issue_time_verifier_fn<T>(mut self, iat_verifier_fn: T) -> Self
     where
-        T: Fn(DateTime<Utc>) -> Result<(), String> + 'a,
+        T: Fn(DateTime<Utc>) -> Result<(), String> + Send + Sync + 'a,
     {
         self.iat_verifier_fn = Arc::new(iat_verifier_fn);
         self
@@ -703,18 +1254,46 @@ where
         self
     }
 
-    pub(super) fn verified_claims<'b, AC, GC, JE, N>(
+    ///
+    /// Parses and verifies the specified serialized ID token, tolerating common provider bugs in
+    /// its claims.
+    ///
+    /// Returns the verified claims along with a [`ClaimWarning`] describing each coercion applied
+    /// to them (see [`IdToken::from_str_lenient`]). The JWE content encryption algorithm type `JE`
+    /// can't be inferred and must be specified explicitly (e.g.,
+    /// `verifier.verify_lenient::<CoreJweContentEncryptionAlgorithm, _, _, _>(...)`).
+    ///
+    pub fn verify_lenient<JE, AC, GC, N>(
         &self,
-        jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
+        id_token: &str,
         nonce_verifier: N,
-    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    ) -> Result<(IdTokenClaims<AC, GC>, Vec<ClaimWarning>), ClaimsVerificationError>
     where
+        JE: JweContentEncryptionAlgorithm<JT>,
         AC: AdditionalClaims,
         GC: GenderClaim,
-        JE: JweContentEncryptionAlgorithm<JT>,
         N: NonceVerifier,
     {
-        // The code below roughly follows the validation steps described in
+        let (id_token, warnings) = IdToken::<AC, GC, JE, JS, JT>::from_str_lenient(id_token)
+            .map_err(|err| {
+                ClaimsVerificationError::Other(format!("failed to parse ID token: {}", err))
+            })?;
+        let claims = id_token.into_claims(self, nonce_verifier)?;
+        Ok((claims, warnings))
+    }
+
+    pub(super) fn verified_claims<'b, AC, GC, JE, N>(
+        &self,
+        jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
+        nonce_verifier: N,
+    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        N: NonceVerifier,
+    {
+        // The code below roughly follows the validation steps described in
         // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
 
         // Steps 1--3 are handled by the generic JwtClaimsVerifier.
@@ -787,15 +1366,17 @@ where
 
         // Steps 6--8 are handled by the generic JwtClaimsVerifier.
 
-        // 9. The current time MUST be before the time represented by the exp Claim.
-        let cur_time = (*self.time_fn)();
-        if cur_time >= partially_verified_claims.expiration() {
-            return Err(ClaimsVerificationError::Expired(format!(
-                "ID token expired at {} (current time is {})",
-                partially_verified_claims.expiration(),
-                cur_time
-            )));
-        }
+        // 9. The current time MUST be before the time represented by the exp Claim. Some small
+        //    leeway, usually no more than a few minutes, may be allowed to account for clock skew.
+        self.time_options
+            .check_expiration(partially_verified_claims.expiration())
+            .map_err(|cur_time| {
+                ClaimsVerificationError::Expired(format!(
+                    "ID token expired at {} (current time is {})",
+                    partially_verified_claims.expiration(),
+                    cur_time
+                ))
+            })?;
 
         // 10. The iat Claim can be used to reject tokens that were issued too far away from the
         //     current time, limiting the amount of time that nonces need to be stored to prevent
@@ -816,6 +1397,8 @@ where
         //     this specification.
         (*self.acr_verifier_fn)(partially_verified_claims.auth_context_ref())
             .map_err(ClaimsVerificationError::InvalidAuthContext)?;
+        (*self.amr_verifier_fn)(partially_verified_claims.auth_method_refs())
+            .map_err(ClaimsVerificationError::InvalidAuthMethod)?;
 
         // 13. If the auth_time Claim was requested, either through a specific request for this
         //     Claim or by using the max_age parameter, the Client SHOULD check the auth_time Claim
@@ -824,6 +1407,101 @@ where
         (*self.auth_time_verifier_fn)(partially_verified_claims.auth_time())
             .map_err(ClaimsVerificationError::InvalidAuthTime)?;
 
+        if let Some(ref original_claims) = self.original_claims {
+            original_claims.verify_refreshed(partially_verified_claims)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Claims from the ID token issued at the time of the original authentication, which must be
+// consistent with any ID token returned in a refresh token response.
+#[derive(Clone)]
+struct OriginalIdTokenClaims {
+    audiences: Vec<Audience>,
+    auth_time: Option<DateTime<Utc>>,
+    authorized_party: Option<ClientId>,
+    issuer: IssuerUrl,
+    nonce: Option<Nonce>,
+    subject: SubjectIdentifier,
+}
+impl OriginalIdTokenClaims {
+    // See https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse.
+    fn verify_refreshed<AC, GC>(
+        &self,
+        claims: &IdTokenClaims<AC, GC>,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+    {
+        // Its iss Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred.
+        if *claims.issuer() != self.issuer {
+            return Err(ClaimsVerificationError::InvalidIssuer(format!(
+                "refreshed ID token issuer `{}` does not match original issuer `{}`",
+                **claims.issuer(),
+                *self.issuer
+            )));
+        }
+
+        // Its sub Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred.
+        if *claims.subject() != self.subject {
+            return Err(ClaimsVerificationError::InvalidSubject(format!(
+                "refreshed ID token subject `{}` does not match original subject `{}`",
+                **claims.subject(),
+                *self.subject
+            )));
+        }
+
+        // Its aud Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred.
+        let audiences = claims.audiences().iter().collect::<HashSet<_>>();
+        if audiences != self.audiences.iter().collect::<HashSet<_>>() {
+            return Err(ClaimsVerificationError::InvalidAudience(
+                "refreshed ID token audiences do not match original audiences".to_string(),
+            ));
+        }
+
+        // If the ID Token contains an auth_time Claim, its value MUST represent the time of the
+        // original authentication - not the time that the new ID token is issued.
+        if let Some(auth_time) = claims.auth_time() {
+            if Some(auth_time) != self.auth_time {
+                return Err(ClaimsVerificationError::InvalidAuthTime(format!(
+                    "refreshed ID token auth_time {} does not match original authentication time",
+                    auth_time
+                )));
+            }
+        }
+
+        // Its azp Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred; if no azp Claim was present in the original ID Token, one MUST
+        // NOT be present in the new ID Token.
+        if claims.authorized_party() != self.authorized_party.as_ref() {
+            return Err(ClaimsVerificationError::InvalidAudience(
+                "refreshed ID token authorized party does not match original authorized party"
+                    .to_string(),
+            ));
+        }
+
+        // If the ID Token contains a nonce Claim, its value MUST be the same as in the ID Token
+        // issued at the time of the original authentication.
+        if let Some(nonce) = claims.nonce() {
+            let nonce_matches = self.nonce.as_ref().map_or(false, |original_nonce| {
+                constant_time_eq(
+                    nonce.secret().as_bytes(),
+                    original_nonce.secret().as_bytes(),
+                )
+            });
+            if !nonce_matches {
+                return Err(ClaimsVerificationError::InvalidNonce(
+                    "refreshed ID token nonce does not match original nonce".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -842,6 +1520,7 @@ where
 {
     jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
     expected_subject: Option<SubjectIdentifier>,
+    time_options: VerificationTimeOptions<'a>,
     _phantom: PhantomData<JE>,
 }
 impl<'a, JE, JS, JT, JU, K> UserInfoVerifier<'a, JE, JS, JT, JU, K>
@@ -864,6 +1543,7 @@ where
         UserInfoVerifier {
             jwt_verifier: JwtClaimsVerifier::new(client_id, issuer, signature_keys),
             expected_subject,
+            time_options: VerificationTimeOptions::new(),
             _phantom: PhantomData,
         }
     }
@@ -888,6 +1568,110 @@ where
         self
     }
 
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported for signed user info
+    /// responses. By default, only `RS256` is supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are always rejected.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported for signed user info responses.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are still rejected.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies the policy for selecting which keys in the JWK set are used to verify the
+    /// signature of signed user info responses.
+    ///
+    pub fn set_key_selection_policy(mut self, key_selection_policy: KeySelectionPolicy) -> Self {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_key_selection_policy(key_selection_policy);
+        self
+    }
+
+    ///
+    /// Specifies which JWT types (`typ` header values) are accepted for signed user info
+    /// responses. By default, only `JWT` is accepted.
+    ///
+    /// Types are compared as described in [`JsonWebTokenType::matches`].
+    ///
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_jwt_types(jwt_types);
+        self
+    }
+
+    ///
+    /// Specifies whether signed user info responses must include a `typ` header. By default, the
+    /// header is optional.
+    ///
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_jwt_type(jwt_type_required);
+        self
+    }
+
+    ///
+    /// Specifies the critical JOSE header extensions (i.e., values of the `crit` header) that the
+    /// application understands and processes. By default, JWTs with a `crit` header are
+    /// rejected.
+    ///
+    /// The application is responsible for processing these extensions, which are available via
+    /// [`JsonWebTokenHeader::other_param`].
+    ///
+    pub fn set_understood_critical_headers<I>(mut self, critical_headers: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_understood_critical_headers(critical_headers);
+        self
+    }
+
+    ///
+    /// Specifies the time source and clock skew tolerance used for verifying the `exp` and `iat`
+    /// claims, when present in signed user info responses.
+    ///
+    pub fn set_time_options(mut self, time_options: VerificationTimeOptions<'a>) -> Self {
+        self.time_options = time_options;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time (see [`UserInfoVerifier::set_time_options`]).
+    ///
+    pub fn set_clock<C>(self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'a,
+    {
+        let time_options = self.time_options.clone().set_clock(clock);
+        self.set_time_options(time_options)
+    }
+
+    ///
+    /// Specifies the maximum tolerated clock skew (see [`UserInfoVerifier::set_time_options`]).
+    ///
+    pub fn set_clock_skew(self, clock_skew: Duration) -> Self {
+        let time_options = self.time_options.clone().set_clock_skew(clock_skew);
+        self.set_time_options(time_options)
+    }
+
     pub(crate) fn verified_claims<AC, GC>(
         &self,
         user_info_jwt: JsonWebToken<
@@ -902,7 +1686,24 @@ where
         AC: AdditionalClaims,
         GC: GenderClaim,
     {
-        let user_info = self.jwt_verifier.verified_claims(user_info_jwt)?;
+        let user_info: UserInfoClaimsImpl<AC, GC> =
+            self.jwt_verifier.verified_claims(user_info_jwt)?;
+
+        if let Some(expiration) = user_info.expiration {
+            self.time_options
+                .check_expiration(expiration)
+                .map_err(|cur_time| {
+                    ClaimsVerificationError::Expired(format!(
+                        "user info expired at {} (current time is {})",
+                        expiration, cur_time
+                    ))
+                })?;
+        }
+        if let Some(issue_time) = user_info.issue_time {
+            self.time_options
+                .verify_issue_time(issue_time, "user info")?;
+        }
+
         if self
             .expected_subject
             .iter()
@@ -920,6 +1721,620 @@ where
     }
 }
 
+///
+/// Verifier for [`JwtAccessToken`](crate::JwtAccessToken)s presented to a resource server.
+///
+/// The `aud` claim must contain the resource server's audience identifier (unless
+/// [`AccessTokenVerifier::require_audience_match`] is disabled), and the `iss` claim must match the
+/// provider's issuer URL.
+///
+/// Like [`IdTokenVerifier`], this verifier is `Send` and `Sync` (provided its type parameters are),
+/// which allows a single instance to be shared by concurrent requests.
+///
+#[derive(Clone)]
+pub struct AccessTokenVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    time_options: VerificationTimeOptions<'a>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> AccessTokenVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates an access token verifier for the resource server identified by `audience`.
+    ///
+    pub fn new(
+        audience: Audience,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        AccessTokenVerifier {
+            // The generic verifier compares the audiences against the client ID, which for access
+            // tokens is the resource server's audience identifier.
+            jwt_verifier: JwtClaimsVerifier::new(
+                ClientId::new((*audience).clone()),
+                issuer,
+                signature_keys,
+            )
+            .set_allowed_jwt_types(vec![
+                JsonWebTokenType::access_token(),
+                JsonWebTokenType::jwt(),
+            ]),
+            // By default, use the current system time and tolerate no clock skew.
+            time_options: VerificationTimeOptions::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
+    ///
+    pub fn require_issuer_match(mut self, iss_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_issuer_match(iss_required);
+        self
+    }
+
+    ///
+    /// Specifies whether the audience claim must contain the resource server's audience
+    /// identifier.
+    ///
+    pub fn require_audience_match(mut self, aud_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_audience_match(aud_required);
+        self
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported. By default, only `RS256` is
+    /// supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are always rejected.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are still rejected.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies the policy for selecting which keys in the JWK set are used to verify the
+    /// signature.
+    ///
+    pub fn set_key_selection_policy(mut self, key_selection_policy: KeySelectionPolicy) -> Self {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_key_selection_policy(key_selection_policy);
+        self
+    }
+
+    ///
+    /// Specifies which JWT types (`typ` header values) are accepted. By default, `at+jwt` and
+    /// `JWT` are accepted, since some providers issue access tokens with the generic type.
+    ///
+    /// Types are compared as described in [`JsonWebTokenType::matches`]. To enforce
+    /// [RFC 9068](https://tools.ietf.org/html/rfc9068#section-4) strictly, accept only
+    /// [`JsonWebTokenType::access_token`] and enable
+    /// [`AccessTokenVerifier::require_jwt_type`].
+    ///
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_jwt_types(jwt_types);
+        self
+    }
+
+    ///
+    /// Specifies whether access tokens must include a `typ` header. By default, the header is
+    /// optional.
+    ///
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_jwt_type(jwt_type_required);
+        self
+    }
+
+    ///
+    /// Specifies the critical JOSE header extensions (i.e., values of the `crit` header) that the
+    /// application understands and processes. By default, JWTs with a `crit` header are
+    /// rejected.
+    ///
+    /// The application is responsible for processing these extensions, which are available via
+    /// [`JsonWebTokenHeader::other_param`].
+    ///
+    pub fn set_understood_critical_headers<I>(mut self, critical_headers: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_understood_critical_headers(critical_headers);
+        self
+    }
+
+    ///
+    /// Specifies the time source and clock skew tolerance used for verifying the
+    /// `exp` and `iat` claims.
+    ///
+    pub fn set_time_options(mut self, time_options: VerificationTimeOptions<'a>) -> Self {
+        self.time_options = time_options;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time (see [`AccessTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock<C>(self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'a,
+    {
+        let time_options = self.time_options.clone().set_clock(clock);
+        self.set_time_options(time_options)
+    }
+
+    ///
+    /// Specifies the maximum tolerated clock skew (see [`AccessTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock_skew(self, clock_skew: Duration) -> Self {
+        let time_options = self.time_options.clone().set_clock_skew(clock_skew);
+        self.set_time_options(time_options)
+    }
+
+    pub(crate) fn verified_claims<AC>(
+        &self,
+        jwt: JsonWebToken<JE, JS, JT, AccessTokenClaims<AC>, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<AccessTokenClaims<AC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+    {
+        let claims: AccessTokenClaims<AC> = self.jwt_verifier.verified_claims(jwt)?;
+
+        self.time_options
+            .check_expiration(claims.expiration())
+            .map_err(|cur_time| {
+                ClaimsVerificationError::Expired(format!(
+                    "access token expired at {} (current time is {})",
+                    claims.expiration(),
+                    cur_time
+                ))
+            })?;
+        if let Some(issue_time) = claims.issue_time() {
+            self.time_options
+                .verify_issue_time(issue_time, "access token")?;
+        }
+
+        Ok(claims)
+    }
+}
+
+///
+/// Verifier for [`SecurityEventToken`](crate::SecurityEventToken)s received from a provider.
+///
+/// The `aud` claim must contain the receiver's audience identifier (typically its client ID), the
+/// `iss` claim must match the provider's issuer URL, the `iat` claim must not be in the future,
+/// and the `events` claim must contain at least one event. Like [`AccessTokenVerifier`], this
+/// verifier is `Send` and `Sync` (provided its type parameters are).
+///
+#[derive(Clone)]
+pub struct SecurityEventTokenVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    time_options: VerificationTimeOptions<'a>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> SecurityEventTokenVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates a SET verifier for the receiver identified by `audience`.
+    ///
+    pub fn new(
+        audience: Audience,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        SecurityEventTokenVerifier {
+            // The generic verifier compares the audiences against the client ID, which for SETs is
+            // the receiver's audience identifier.
+            jwt_verifier: JwtClaimsVerifier::new(
+                ClientId::new((*audience).clone()),
+                issuer,
+                signature_keys,
+            )
+            .set_allowed_jwt_types(vec![JsonWebTokenType::security_event_token()]),
+            // By default, use the current system time and tolerate no clock skew.
+            time_options: VerificationTimeOptions::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
+    ///
+    pub fn require_issuer_match(mut self, iss_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_issuer_match(iss_required);
+        self
+    }
+
+    ///
+    /// Specifies whether the audience claim must contain the receiver's audience identifier.
+    ///
+    pub fn require_audience_match(mut self, aud_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_audience_match(aud_required);
+        self
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported. By default, only `RS256` is
+    /// supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are always rejected.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are still rejected.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies the policy for selecting which keys in the JWK set are used to verify the
+    /// signature.
+    ///
+    pub fn set_key_selection_policy(mut self, key_selection_policy: KeySelectionPolicy) -> Self {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_key_selection_policy(key_selection_policy);
+        self
+    }
+
+    ///
+    /// Specifies which JWT types (`typ` header values) are accepted. By default, only
+    /// `secevent+jwt` is accepted, which prevents other JWTs (e.g., ID tokens) from being
+    /// misinterpreted as SETs as described in
+    /// [RFC 8417](https://tools.ietf.org/html/rfc8417#section-2.3).
+    ///
+    /// Types are compared as described in [`JsonWebTokenType::matches`].
+    ///
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_jwt_types(jwt_types);
+        self
+    }
+
+    ///
+    /// Specifies whether SETs must include a `typ` header. By default, the header is optional,
+    /// since some transmitters omit it.
+    ///
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_jwt_type(jwt_type_required);
+        self
+    }
+
+    ///
+    /// Specifies the critical JOSE header extensions (i.e., values of the `crit` header) that the
+    /// application understands and processes. By default, JWTs with a `crit` header are
+    /// rejected.
+    ///
+    /// The application is responsible for processing these extensions, which are available via
+    /// [`JsonWebTokenHeader::other_param`].
+    ///
+    pub fn set_understood_critical_headers<I>(mut self, critical_headers: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_understood_critical_headers(critical_headers);
+        self
+    }
+
+    ///
+    /// Specifies the time source and clock skew tolerance used for verifying the `iat` claim.
+    ///
+    pub fn set_time_options(mut self, time_options: VerificationTimeOptions<'a>) -> Self {
+        self.time_options = time_options;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time (see
+    /// [`SecurityEventTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock<C>(self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'a,
+    {
+        let time_options = self.time_options.clone().set_clock(clock);
+        self.set_time_options(time_options)
+    }
+
+    ///
+    /// Specifies the maximum tolerated clock skew (see
+    /// [`SecurityEventTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock_skew(self, clock_skew: Duration) -> Self {
+        let time_options = self.time_options.clone().set_clock_skew(clock_skew);
+        self.set_time_options(time_options)
+    }
+
+    pub(crate) fn verified_claims(
+        &self,
+        jwt: JsonWebToken<JE, JS, JT, SecurityEventTokenClaims, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<SecurityEventTokenClaims, ClaimsVerificationError> {
+        let claims: SecurityEventTokenClaims = self.jwt_verifier.verified_claims(jwt)?;
+
+        self.time_options
+            .verify_issue_time(claims.issue_time(), "SET")?;
+
+        // https://tools.ietf.org/html/rfc8417#section-2.2
+        if claims.events().is_empty() {
+            return Err(ClaimsVerificationError::Other(
+                "SET must contain at least one event".to_string(),
+            ));
+        }
+
+        Ok(claims)
+    }
+}
+
+///
+/// Verifier for [`LogoutToken`](crate::LogoutToken)s received at the relying party's
+/// back-channel logout endpoint, as described in
+/// [OpenID Connect Back-Channel Logout](
+/// https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation).
+///
+/// In addition to the signature, issuer, and audience checks performed for ID tokens, the `iat`
+/// claim must not be in the future, the `exp` claim must not have passed, the `events` claim must
+/// contain the back-channel logout event, the token must contain a `sub` or `sid` claim (or a
+/// `sid` claim if [`LogoutTokenVerifier::require_session_id`] is set), and it must not contain a
+/// `nonce` claim. Replay detection using the `jti` claim is left to the caller (see
+/// [`process_backchannel_logout`](crate::process_backchannel_logout)).
+///
+#[derive(Clone)]
+pub struct LogoutTokenVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    session_id_required: bool,
+    time_options: VerificationTimeOptions<'a>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> LogoutTokenVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates a logout token verifier for the specified client.
+    ///
+    pub fn new(
+        client_id: ClientId,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        LogoutTokenVerifier {
+            jwt_verifier: JwtClaimsVerifier::new(client_id, issuer, signature_keys)
+                .set_allowed_jwt_types(vec![JsonWebTokenType::logout_token()]),
+            session_id_required: false,
+            // By default, use the current system time and tolerate no clock skew.
+            time_options: VerificationTimeOptions::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
+    ///
+    pub fn require_issuer_match(mut self, iss_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_issuer_match(iss_required);
+        self
+    }
+
+    ///
+    /// Specifies whether the audience claim must contain this client's client ID.
+    ///
+    pub fn require_audience_match(mut self, aud_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_audience_match(aud_required);
+        self
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported. By default, only `RS256` is
+    /// supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are always rejected.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    /// Unsigned JWTs (i.e., those with an `alg` of `none`) are still rejected.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies the policy for selecting which keys in the JWK set are used to verify the
+    /// signature.
+    ///
+    pub fn set_key_selection_policy(mut self, key_selection_policy: KeySelectionPolicy) -> Self {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_key_selection_policy(key_selection_policy);
+        self
+    }
+
+    ///
+    /// Specifies which JWT types (`typ` header values) are accepted. By default, only
+    /// `logout+jwt` is accepted, which prevents other JWTs (e.g., ID tokens) that include a `typ`
+    /// header from being accepted as logout tokens.
+    ///
+    /// Types are compared as described in [`JsonWebTokenType::matches`].
+    ///
+    pub fn set_allowed_jwt_types<I>(mut self, jwt_types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonWebTokenType>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_jwt_types(jwt_types);
+        self
+    }
+
+    ///
+    /// Specifies whether logout tokens must include a `typ` header. By default, the header is
+    /// optional, since explicit typing of logout tokens is only recommended.
+    ///
+    pub fn require_jwt_type(mut self, jwt_type_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_jwt_type(jwt_type_required);
+        self
+    }
+
+    ///
+    /// Specifies whether logout tokens must include a session ID (`sid`) claim. This should be
+    /// set if the client registered with `backchannel_logout_session_required`. By default, a
+    /// subject (`sub`) claim alone is sufficient.
+    ///
+    pub fn require_session_id(mut self, session_id_required: bool) -> Self {
+        self.session_id_required = session_id_required;
+        self
+    }
+
+    ///
+    /// Specifies the time source and clock skew tolerance used for verifying the
+    /// `iat` and `exp` claims.
+    ///
+    pub fn set_time_options(mut self, time_options: VerificationTimeOptions<'a>) -> Self {
+        self.time_options = time_options;
+        self
+    }
+
+    ///
+    /// Specifies the source of the current time (see [`LogoutTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock<C>(self, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'a,
+    {
+        let time_options = self.time_options.clone().set_clock(clock);
+        self.set_time_options(time_options)
+    }
+
+    ///
+    /// Specifies the maximum tolerated clock skew (see [`LogoutTokenVerifier::set_time_options`]).
+    ///
+    pub fn set_clock_skew(self, clock_skew: Duration) -> Self {
+        let time_options = self.time_options.clone().set_clock_skew(clock_skew);
+        self.set_time_options(time_options)
+    }
+
+    pub(crate) fn verified_claims(
+        &self,
+        jwt: JsonWebToken<JE, JS, JT, LogoutTokenClaims, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<LogoutTokenClaims, ClaimsVerificationError> {
+        let claims: LogoutTokenClaims = self.jwt_verifier.verified_claims(jwt)?;
+
+        self.time_options
+            .check_expiration(claims.expiration())
+            .map_err(|cur_time| {
+                ClaimsVerificationError::Expired(format!(
+                    "logout token expired at {} (current time is {})",
+                    claims.expiration(),
+                    cur_time
+                ))
+            })?;
+        self.time_options
+            .verify_issue_time(claims.issue_time(), "logout token")?;
+
+        // https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation
+        if !claims.is_backchannel_logout() {
+            return Err(ClaimsVerificationError::Other(
+                "logout token must contain the back-channel logout event".to_string(),
+            ));
+        }
+        if claims.has_nonce() {
+            return Err(ClaimsVerificationError::InvalidNonce(
+                "logout token must not contain a nonce claim".to_string(),
+            ));
+        }
+        if claims.session_id().is_none() {
+            if self.session_id_required {
+                return Err(ClaimsVerificationError::InvalidSubject(
+                    "missing session ID (sid) claim".to_string(),
+                ));
+            } else if claims.subject().is_none() {
+                return Err(ClaimsVerificationError::InvalidSubject(
+                    "logout token must contain a sub or sid claim".to_string(),
+                ));
+            }
+        }
+
+        Ok(claims)
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::cell::Cell;
@@ -0,0 +1,94 @@
+use std::convert::TryFrom;
+
+use failure::Fail;
+use js_sys::{Array, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit, RequestRedirect};
+
+use crate::http_types::{Request, Response, StatusCode};
+
+///
+/// Error type returned by failed `fetch` HTTP requests.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum Error {
+    ///
+    /// Failed to convert between `http_types` and `fetch` representations.
+    ///
+    #[fail(display = "HTTP conversion failed: {}", _0)]
+    Http(String),
+    ///
+    /// Error returned by the browser's `fetch` API. The JavaScript exception is converted to a
+    /// string, since `JsValue` is neither `Send` nor `Sync`.
+    ///
+    #[fail(display = "fetch failed: {}", _0)]
+    Fetch(String),
+}
+
+fn fetch_error(value: JsValue) -> Error {
+    Error::Fetch(value.as_string().unwrap_or_else(|| format!("{:?}", value)))
+}
+
+///
+/// Asynchronous HTTP client using the browser's
+/// [`fetch`](https://developer.mozilla.org/en-US/docs/Web/API/Fetch_API) API.
+///
+/// Redirects are not followed, which prevents SSRF vulnerabilities. Note that the returned future
+/// is not `Send`, as is the case for all futures that interact with JavaScript.
+///
+pub async fn async_http_client(mut request: Request) -> Result<Response, Error> {
+    let mut init = RequestInit::new();
+    init.method(&request.method().to_string());
+    init.redirect(RequestRedirect::Manual);
+
+    let headers = Headers::new().map_err(fetch_error)?;
+    for (name, values) in request.iter() {
+        for value in values.iter() {
+            headers
+                .append(name.as_str(), value.as_str())
+                .map_err(fetch_error)?;
+        }
+    }
+    init.headers(&headers);
+
+    let body = request
+        .body_bytes()
+        .await
+        .map_err(|err| Error::Http(format!("failed to read request body: {}", err)))?;
+    if !body.is_empty() {
+        init.body(Some(&Uint8Array::from(body.as_slice())));
+    }
+
+    let fetch_request = web_sys::Request::new_with_str_and_init(request.url().as_str(), &init)
+        .map_err(fetch_error)?;
+    let window =
+        web_sys::window().ok_or_else(|| Error::Fetch("no global `window` exists".to_string()))?;
+    let fetch_response: web_sys::Response =
+        JsFuture::from(window.fetch_with_request(&fetch_request))
+            .await
+            .map_err(fetch_error)?
+            .dyn_into()
+            .map_err(fetch_error)?;
+
+    let status_code = StatusCode::try_from(fetch_response.status())
+        .map_err(|err| Error::Http(format!("invalid HTTP status code: {}", err)))?;
+    let mut http_response = Response::new(status_code);
+
+    let header_iter = js_sys::try_iter(&fetch_response.headers())
+        .map_err(fetch_error)?
+        .ok_or_else(|| Error::Http("response headers are not iterable".to_string()))?;
+    for entry in header_iter {
+        let entry: Array = entry.map_err(fetch_error)?.dyn_into().map_err(fetch_error)?;
+        if let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string()) {
+            http_response.append_header(name.as_str(), value.as_str());
+        }
+    }
+
+    let array_buffer = JsFuture::from(fetch_response.array_buffer().map_err(fetch_error)?)
+        .await
+        .map_err(fetch_error)?;
+    http_response.set_body(Uint8Array::new(&array_buffer).to_vec());
+    Ok(http_response)
+}
@@ -0,0 +1,662 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use failure::Fail;
+use url::Url;
+
+use crate::authorization::redirect_url_state;
+use crate::clock::{Clock, SystemClock};
+use crate::http_client::AsyncHttpClient;
+use crate::login_state::StateStore;
+use crate::scope::ScopeSet;
+use crate::token_manager::expiration_time;
+use crate::token_store::{StoredToken, TokenStore};
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, CsrfToken, ErrorResponse, GenderClaim,
+    IdTokenClaims, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, LoginManager, OAuth2TokenResponse, TokenResponse, TokenType,
+};
+
+// Name of the cookie holding the session ID, unless overridden via
+// `OidcState::set_session_cookie_name`.
+const DEFAULT_SESSION_COOKIE_NAME: &str = "oidc_session";
+// Lifetime of the cookie binding a pending login to the user agent that started it. Matches the
+// default maximum age of logins started by `LoginManager`.
+const LOGIN_COOKIE_MAX_AGE_SECS: u64 = 600;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type BeginFn = dyn Fn(ScopeSet) -> Result<Url, Box<dyn Fail>> + Send + Sync;
+type CompleteResult<AC, GC> = Result<(IdTokenClaims<AC, GC>, StoredToken<AC, GC>), Box<dyn Fail>>;
+type CompleteFn<AC, GC> = dyn Fn(Url) -> BoxFuture<CompleteResult<AC, GC>> + Send + Sync;
+type LoadFn<AC, GC> = dyn Fn(&str) -> Result<Option<StoredToken<AC, GC>>, String> + Send + Sync;
+type SaveFn<AC, GC> = dyn Fn(&str, &StoredToken<AC, GC>) -> Result<(), String> + Send + Sync;
+
+///
+/// Error returned by the web framework integrations' extractors and handlers.
+///
+/// Each variant is converted into an HTTP response with an appropriate status code. Details of
+/// session store failures are omitted from the response body.
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum OidcRejection {
+    ///
+    /// The login could not be started or completed.
+    ///
+    #[fail(display = "Login failed: {}", _0)]
+    Login(#[cause] OidcLoginError),
+    ///
+    /// The request has no session, or the session has no verified ID token claims.
+    ///
+    #[fail(display = "Not authenticated")]
+    Unauthenticated,
+    ///
+    /// Failed to read or write the session store.
+    ///
+    #[fail(display = "Session store error: {}", _0)]
+    SessionStore(String),
+}
+
+///
+/// Error starting or completing a login via an [`OidcState`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum OidcLoginError {
+    ///
+    /// The [`LoginManager`] failed to start or complete the login (e.g., because the `state`
+    /// parameter is unknown or the ID token failed verification).
+    ///
+    #[fail(display = "{}", _0)]
+    LoginManager(#[cause] Box<dyn Fail>),
+    ///
+    /// The request to the redirect URI has no login cookie.
+    ///
+    #[fail(display = "Missing login cookie")]
+    MissingLoginCookie,
+    ///
+    /// The authorization URL has no `state` parameter.
+    ///
+    #[fail(display = "Missing state parameter")]
+    MissingState,
+    ///
+    /// The `state` parameter doesn't match the login started by the user agent (i.e., the one
+    /// identified by the login cookie).
+    ///
+    #[fail(display = "State parameter does not match login cookie")]
+    StateMismatch,
+}
+
+///
+/// Error instantiating an [`OidcState`].
+///
+#[derive(Debug, Fail)]
+#[non_exhaustive]
+pub enum OidcConfigError {
+    ///
+    /// The login manager's client has no redirect URI (see
+    /// [`Client::set_redirect_uri`](crate::Client::set_redirect_uri)).
+    ///
+    #[fail(display = "Client has no redirect URI")]
+    MissingRedirectUrl,
+}
+
+///
+/// Response redirecting the user agent (`303 See Other`), optionally setting cookies.
+///
+/// Returned by [`OidcState::login`] to redirect the user agent to the Authorization Endpoint, and
+/// by [`OidcCallback::redirect`] to redirect it back into the application once the login is
+/// complete.
+///
+#[derive(Clone)]
+pub struct OidcLoginRedirect {
+    cookies: Vec<String>,
+    location: String,
+}
+impl OidcLoginRedirect {
+    ///
+    /// Instantiates a redirect to the specified location.
+    ///
+    pub fn new(location: String) -> Self {
+        Self {
+            cookies: Vec::new(),
+            location,
+        }
+    }
+
+    ///
+    /// Adds a `Set-Cookie` header value to the response.
+    ///
+    pub fn add_cookie(mut self, cookie: String) -> Self {
+        self.cookies.push(cookie);
+        self
+    }
+
+    ///
+    /// Returns the location to which the user agent is redirected.
+    ///
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    ///
+    /// Returns the `Set-Cookie` header values added to the response.
+    ///
+    pub fn cookies(&self) -> &[String] {
+        &self.cookies
+    }
+}
+impl Debug for OidcLoginRedirect {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        // Cookies hold the login state and session ID, which must remain secret.
+        f.debug_struct("OidcLoginRedirect")
+            .field("cookies", &self.cookies.len())
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+///
+/// Shared state for the web framework integrations' extractors and handlers.
+///
+/// `OidcState` wraps a [`LoginManager`], a [`StateStore`] for pending logins, a [`TokenStore`]
+/// holding the token state of each session, and an HTTP client. Cloning an `OidcState` is cheap.
+///
+/// A typical application exposes two routes:
+///  * a login route whose handler returns [`OidcState::login`], which saves a pending login and
+///    redirects the user agent to the Authorization Endpoint, and
+///  * a callback route (the client's redirect URI) whose handler accepts an [`OidcCallback`],
+///    which completes the login and starts a session.
+///
+/// Other handlers accept [`VerifiedClaims`] to require an authenticated session.
+///
+/// Pending logins are bound to the user agent that started them via a short-lived cookie, which
+/// prevents an attacker from completing a login they started in a victim's browser.
+///
+pub struct OidcState<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    begin: Arc<BeginFn>,
+    complete: Arc<CompleteFn<AC, GC>>,
+    load: Arc<LoadFn<AC, GC>>,
+    save: Arc<SaveFn<AC, GC>>,
+    redirect_url: Url,
+    scopes: ScopeSet,
+    session_cookie_name: String,
+}
+impl<AC, GC> OidcState<AC, GC>
+where
+    AC: AdditionalClaims + Send + Sync + 'static,
+    GC: GenderClaim + Send + Sync + 'static,
+{
+    ///
+    /// Instantiates the state from the specified login manager and stores.
+    ///
+    /// Returns [`OidcConfigError::MissingRedirectUrl`] if the login manager's client has no
+    /// redirect URI.
+    ///
+    pub fn new<AD, JE, JS, JT, JU, K, P, TE, TR, TT, C, S, T>(
+        login_manager: LoginManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>,
+        state_store: S,
+        token_store: T,
+        http_client: C,
+    ) -> Result<Self, OidcConfigError>
+    where
+        AD: AuthDisplay,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        P: AuthPrompt,
+        TE: ErrorResponse + Send + 'static,
+        TR: TokenResponse<AC, GC, JE, JS, JT, TT> + Send + 'static,
+        TT: TokenType + 'static,
+        LoginManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT>: Send + Sync + 'static,
+        C: AsyncHttpClient + Send + Sync + 'static,
+        C::Future: Send,
+        S: StateStore + Send + Sync + 'static,
+        T: TokenStore<AC, GC> + Send + Sync + 'static,
+    {
+        let redirect_url = login_manager
+            .client()
+            .redirect_url
+            .as_ref()
+            .ok_or(OidcConfigError::MissingRedirectUrl)?
+            .url()
+            .clone();
+        let login_manager = Arc::new(login_manager);
+        let state_store = Arc::new(state_store);
+        let token_store = Arc::new(token_store);
+        let http_client = Arc::new(http_client);
+
+        let begin = {
+            let login_manager = login_manager.clone();
+            let state_store = state_store.clone();
+            move |scopes: ScopeSet| {
+                login_manager
+                    .begin(&*state_store, |request| request.add_scopes(scopes))
+                    .map_err(|err| Box::new(err) as Box<dyn Fail>)
+            }
+        };
+        let complete = move |redirect_url: Url| -> BoxFuture<CompleteResult<AC, GC>> {
+            let login_manager = login_manager.clone();
+            let state_store = state_store.clone();
+            let http_client = http_client.clone();
+            Box::pin(async move {
+                let (token_response, id_token_claims) = login_manager
+                    .complete(&*state_store, &redirect_url, &*http_client)
+                    .await
+                    .map_err(|err| Box::new(err) as Box<dyn Fail>)?;
+                let stored_token = StoredToken::new(token_response.access_token().clone())
+                    .set_expires_at(expiration_time(
+                        token_response.expires_in(),
+                        SystemClock.now(),
+                    ))
+                    .set_id_token_claims(Some(id_token_claims.clone()))
+                    .set_refresh_token(token_response.refresh_token().cloned());
+                Ok((id_token_claims, stored_token))
+            })
+        };
+        let load = {
+            let token_store = token_store.clone();
+            move |session_id: &str| token_store.get(session_id).map_err(|err| err.to_string())
+        };
+        let save = move |session_id: &str, token: &StoredToken<AC, GC>| {
+            token_store
+                .put(session_id, token)
+                .map_err(|err| err.to_string())
+        };
+
+        Ok(Self {
+            begin: Arc::new(begin),
+            complete: Arc::new(complete),
+            load: Arc::new(load),
+            save: Arc::new(save),
+            redirect_url,
+            scopes: ScopeSet::new(),
+            session_cookie_name: DEFAULT_SESSION_COOKIE_NAME.to_string(),
+        })
+    }
+
+    ///
+    /// Sets the scopes requested by [`OidcState::login`] in addition to `openid`.
+    ///
+    pub fn set_scopes(mut self, scopes: ScopeSet) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    ///
+    /// Sets the name of the cookie holding the session ID (`oidc_session` by default).
+    ///
+    pub fn set_session_cookie_name(mut self, session_cookie_name: String) -> Self {
+        self.session_cookie_name = session_cookie_name;
+        self
+    }
+
+    ///
+    /// Begins a login, returning a redirect to the Authorization Endpoint.
+    ///
+    pub fn login(&self) -> Result<OidcLoginRedirect, OidcRejection> {
+        let url = (self.begin)(self.scopes.clone())
+            .map_err(|err| OidcRejection::Login(OidcLoginError::LoginManager(err)))?;
+        let state =
+            redirect_url_state(&url).ok_or(OidcRejection::Login(OidcLoginError::MissingState))?;
+        Ok(OidcLoginRedirect::new(url.to_string()).add_cookie(cookie(
+            &self.login_cookie_name(),
+            state.secret(),
+            Some(LOGIN_COOKIE_MAX_AGE_SECS),
+        )))
+    }
+
+    // Completes a login given the query string of the request to the redirect URI and the value
+    // of the login cookie set by `login`.
+    pub(crate) async fn callback(
+        &self,
+        query: Option<&str>,
+        login_cookie: Option<String>,
+    ) -> Result<OidcCallback<AC, GC>, OidcRejection> {
+        let mut redirect_url = self.redirect_url.clone();
+        redirect_url.set_query(query);
+        let expected_state =
+            login_cookie.ok_or(OidcRejection::Login(OidcLoginError::MissingLoginCookie))?;
+        if redirect_url_state(&redirect_url).map(|state| state.secret().clone())
+            != Some(expected_state)
+        {
+            return Err(OidcRejection::Login(OidcLoginError::StateMismatch));
+        }
+
+        let (claims, stored_token) = (self.complete)(redirect_url)
+            .await
+            .map_err(|err| OidcRejection::Login(OidcLoginError::LoginManager(err)))?;
+        let session_id = CsrfToken::new_random().secret().clone();
+        (self.save)(&session_id, &stored_token).map_err(OidcRejection::SessionStore)?;
+
+        Ok(OidcCallback {
+            claims,
+            login_cookie_name: self.login_cookie_name(),
+            session_cookie_name: self.session_cookie_name.clone(),
+            session_id,
+        })
+    }
+
+    // Loads the verified ID token claims of the session identified by the session cookie.
+    pub(crate) fn verified_claims(
+        &self,
+        session_cookie: Option<String>,
+    ) -> Result<VerifiedClaims<AC, GC>, OidcRejection> {
+        let session_id = session_cookie.ok_or(OidcRejection::Unauthenticated)?;
+        (self.load)(&session_id)
+            .map_err(OidcRejection::SessionStore)?
+            .and_then(|mut stored_token| stored_token.take_id_token_claims())
+            .map(VerifiedClaims)
+            .ok_or(OidcRejection::Unauthenticated)
+    }
+
+    pub(crate) fn session_cookie_name(&self) -> &str {
+        &self.session_cookie_name
+    }
+
+    pub(crate) fn login_cookie_name(&self) -> String {
+        format!("{}_login", self.session_cookie_name)
+    }
+}
+impl<AC, GC> Clone for OidcState<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    fn clone(&self) -> Self {
+        Self {
+            begin: self.begin.clone(),
+            complete: self.complete.clone(),
+            load: self.load.clone(),
+            save: self.save.clone(),
+            redirect_url: self.redirect_url.clone(),
+            scopes: self.scopes.clone(),
+            session_cookie_name: self.session_cookie_name.clone(),
+        }
+    }
+}
+
+///
+/// Extractor that completes a login when the Authorization Endpoint redirects the user agent to
+/// the client's redirect URI.
+///
+/// The extractor verifies that the `state` parameter matches the login started by the same user
+/// agent, exchanges the authorization code, verifies the ID token, and stores the resulting token
+/// state in the [`TokenStore`] under a newly generated session ID. The handler should respond with
+/// [`OidcCallback::redirect`], which sets the session cookie.
+///
+#[derive(Clone)]
+pub struct OidcCallback<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    claims: IdTokenClaims<AC, GC>,
+    login_cookie_name: String,
+    session_cookie_name: String,
+    session_id: String,
+}
+impl<AC, GC> OidcCallback<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Returns the verified ID token claims.
+    ///
+    pub fn claims(&self) -> &IdTokenClaims<AC, GC> {
+        &self.claims
+    }
+
+    ///
+    /// Returns the ID of the newly created session.
+    ///
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    ///
+    /// Returns a redirect to the specified location that sets the session cookie and clears the
+    /// login cookie.
+    ///
+    pub fn redirect(&self, location: String) -> OidcLoginRedirect {
+        OidcLoginRedirect::new(location)
+            .add_cookie(cookie(&self.session_cookie_name, &self.session_id, None))
+            .add_cookie(cookie(&self.login_cookie_name, "", Some(0)))
+    }
+}
+impl<AC, GC> Debug for OidcCallback<AC, GC>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        f.debug_struct("OidcCallback")
+            .field("claims", &self.claims)
+            .field("login_cookie_name", &self.login_cookie_name)
+            .field("session_cookie_name", &self.session_cookie_name)
+            .field("session_id", &"[redacted]")
+            .finish()
+    }
+}
+
+///
+/// Extractor for the verified ID token claims of the current session.
+///
+/// The session ID is read from the session cookie set by [`OidcCallback::redirect`], and the
+/// claims are loaded from the [`TokenStore`]. Requests without a session are rejected with
+/// [`OidcRejection::Unauthenticated`].
+///
+#[derive(Clone, Debug)]
+pub struct VerifiedClaims<AC, GC>(pub IdTokenClaims<AC, GC>)
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim;
+
+fn cookie(name: &str, value: &str, max_age_secs: Option<u64>) -> String {
+    let mut cookie = format!("{}={}; HttpOnly; Secure; SameSite=Lax; Path=/", name, value);
+    if let Some(max_age_secs) = max_age_secs {
+        cookie.push_str(&format!("; Max-Age={}", max_age_secs));
+    }
+    cookie
+}
+
+// Returns the value of the named cookie given the values of each `Cookie` request header.
+pub(crate) fn cookie_value<'a, I>(headers: I, name: &str) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    headers
+        .into_iter()
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| {
+            let mut pair = pair.trim().splitn(2, '=');
+            match (pair.next(), pair.next()) {
+                (Some(cookie_name), Some(value)) if cookie_name == name => Some(value.to_string()),
+                _ => None,
+            }
+        })
+        .next()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{Duration, Utc};
+    use futures::future::ready;
+    use url::Url;
+
+    use crate::core::{
+        CoreClient, CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm, CoreLoginManager, CoreRsaPrivateSigningKey,
+    };
+    use crate::http_types::{Request, Response, StatusCode};
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::{
+        AccessToken, Audience, AuthUrl, ClientId, ClientSecret, EmptyAdditionalClaims,
+        InMemoryStateStore, InMemoryTokenStore, IssuerUrl, Nonce, PrivateSigningKey, RedirectUrl,
+        StandardClaims, SubjectIdentifier, TokenUrl,
+    };
+
+    use super::{cookie, cookie_value, OidcConfigError, OidcLoginRedirect, OidcState};
+
+    pub(crate) type TestOidcState = OidcState<EmptyAdditionalClaims, CoreGenderClaim>;
+
+    pub(crate) const TEST_SUBJECT: &str = "test-subject";
+
+    // `OidcState` whose HTTP client simulates the provider's token endpoint, for testing the web
+    // framework integrations' extractors. Each token response includes an ID token for
+    // `TEST_SUBJECT` bound to the nonce of the most recently started login.
+    pub(crate) struct TestLogin {
+        pub(crate) state: TestOidcState,
+        nonce: Arc<Mutex<Option<Nonce>>>,
+    }
+    impl TestLogin {
+        pub(crate) fn new() -> Self {
+            let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+            let client = CoreClient::new(
+                ClientId::new("aaa".to_string()),
+                Some(ClientSecret::new("bbb".to_string())),
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+                Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+                None,
+                CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            )
+            .set_redirect_uri(RedirectUrl::new("https://client/cb".to_string()).unwrap());
+
+            let nonce = Arc::new(Mutex::new(None));
+            let http_client = {
+                let nonce = nonce.clone();
+                move |_: Request| {
+                    let access_token = AccessToken::new("foo".to_string());
+                    let claims = CoreIdTokenClaims::new(
+                        IssuerUrl::new("https://example".to_string()).unwrap(),
+                        vec![Audience::new("aaa".to_string())],
+                        Utc::now() + Duration::hours(1),
+                        Utc::now(),
+                        StandardClaims::new(SubjectIdentifier::new(TEST_SUBJECT.to_string())),
+                        EmptyAdditionalClaims {},
+                    )
+                    .set_nonce(nonce.lock().unwrap().clone());
+                    let id_token = CoreIdToken::new(
+                        claims,
+                        &signing_key,
+                        CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                        Some(&access_token),
+                        None,
+                    )
+                    .unwrap();
+
+                    let mut response = Response::new(StatusCode::Ok);
+                    response.insert_header("Content-Type", "application/json");
+                    response.set_body(
+                        serde_json::json!({
+                            "access_token": access_token.secret(),
+                            "token_type": "bearer",
+                            "id_token": id_token,
+                        })
+                        .to_string(),
+                    );
+                    ready(Ok::<_, io::Error>(response))
+                }
+            };
+
+            Self {
+                state: OidcState::new(
+                    CoreLoginManager::new(client),
+                    InMemoryStateStore::new(),
+                    InMemoryTokenStore::new(),
+                    http_client,
+                )
+                .unwrap(),
+                nonce,
+            }
+        }
+
+        // Begins a login, returning the query string with which the Authorization Endpoint
+        // redirects the user agent back to the client, and the `Cookie` header value holding the
+        // login cookie.
+        pub(crate) fn begin(&self) -> (String, String) {
+            let redirect = self.state.login().unwrap();
+            let url = Url::parse(redirect.location()).unwrap();
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(param_name, _)| param_name == name)
+                    .map(|(_, value)| value.into_owned())
+                    .unwrap()
+            };
+            *self.nonce.lock().unwrap() = Some(Nonce::new(param("nonce")));
+            (
+                format!("code=the_code&state={}", param("state")),
+                request_cookie(&redirect.cookies()[0]),
+            )
+        }
+    }
+
+    // Converts a `Set-Cookie` header value into the corresponding `Cookie` request header value.
+    pub(crate) fn request_cookie(set_cookie: &str) -> String {
+        set_cookie.split(';').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_missing_redirect_url() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            CoreJsonWebKeySet::new(vec![]),
+        );
+        let http_client =
+            |_: Request| ready(Ok::<_, io::Error>(Response::new(StatusCode::NotFound)));
+
+        match TestOidcState::new(
+            CoreLoginManager::new(client),
+            InMemoryStateStore::new(),
+            InMemoryTokenStore::new(),
+            http_client,
+        ) {
+            Err(OidcConfigError::MissingRedirectUrl) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_cookies() {
+        let headers = vec!["a=1; oidc_session=abc", "oidc_session_login=xyz"];
+        assert_eq!(
+            cookie_value(headers.iter().cloned(), "oidc_session"),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            cookie_value(headers.iter().cloned(), "oidc_session_login"),
+            Some("xyz".to_string())
+        );
+        assert_eq!(cookie_value(headers.iter().cloned(), "oidc"), None);
+
+        assert_eq!(
+            cookie("oidc_session_login", "xyz", Some(600)),
+            "oidc_session_login=xyz; HttpOnly; Secure; SameSite=Lax; Path=/; Max-Age=600"
+        );
+
+        let redirect = OidcLoginRedirect::new("https://app/".to_string()).add_cookie(cookie(
+            "oidc_session",
+            "abc",
+            None,
+        ));
+        assert!(!format!("{:?}", redirect).contains("abc"));
+    }
+}
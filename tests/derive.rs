@@ -0,0 +1,71 @@
+#![cfg(feature = "derive")]
+
+use std::collections::HashMap;
+
+use openidconnect::core::{CoreGenderClaim, CoreIdTokenClaims};
+use openidconnect::{AdditionalClaims, IdTokenClaims};
+
+#[derive(AdditionalClaims, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TenantClaims {
+    tenant_id: String,
+    #[serde(rename = "https://example.com/roles", default)]
+    roles: Vec<String>,
+}
+
+#[derive(AdditionalClaims, Debug)]
+struct OtherClaims(HashMap<String, serde_json::Value>);
+
+#[derive(AdditionalClaims, Debug)]
+struct NoClaims {}
+
+const CLAIMS_JSON: &str = "{
+    \"iss\": \"https://server.example.com\",
+    \"sub\": \"24400320\",
+    \"aud\": [\"s6BhdRkqt3\"],
+    \"exp\": 1311281970,
+    \"iat\": 1311280970,
+    \"name\": \"Jane Doe\",
+    \"tenantId\": \"acme\",
+    \"https://example.com/roles\": [\"admin\"]
+}";
+
+#[test]
+fn test_derive_additional_claims() {
+    let claims = serde_json::from_str::<IdTokenClaims<TenantClaims, CoreGenderClaim>>(CLAIMS_JSON)
+        .expect("failed to deserialize");
+    assert_eq!(
+        *claims.additional_claims(),
+        TenantClaims {
+            tenant_id: "acme".to_string(),
+            roles: vec!["admin".to_string()],
+        }
+    );
+    let serialized = serde_json::to_value(&claims).expect("failed to serialize");
+    assert_eq!(serialized["tenantId"], "acme");
+    assert_eq!(serialized["https://example.com/roles"][0], "admin");
+    assert_eq!(serialized["name"], "Jane Doe");
+
+    assert_eq!(
+        serde_json::from_str::<TenantClaims>("{\"tenantId\": \"acme\"}")
+            .expect("failed to deserialize"),
+        TenantClaims {
+            tenant_id: "acme".to_string(),
+            roles: vec![],
+        }
+    );
+
+    let claims = serde_json::from_str::<IdTokenClaims<OtherClaims, CoreGenderClaim>>(CLAIMS_JSON)
+        .expect("failed to deserialize");
+    assert_eq!(claims.additional_claims().0.len(), 2);
+    assert_eq!(claims.additional_claims().0["tenantId"], "acme");
+
+    serde_json::from_str::<IdTokenClaims<NoClaims, CoreGenderClaim>>(CLAIMS_JSON)
+        .expect("failed to deserialize");
+    serde_json::from_str::<CoreIdTokenClaims>(CLAIMS_JSON).expect("failed to deserialize");
+}
+
+#[test]
+fn test_derive_additional_claims_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/derive/*.rs");
+}
@@ -0,0 +1,9 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+#[serde(deny_unknown_fields)]
+struct Claims {
+    tenant_id: String,
+}
+
+fn main() {}
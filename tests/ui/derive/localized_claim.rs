@@ -0,0 +1,8 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+struct Claims {
+    #[serde(rename = "name#ja-Kana-JP")] name_kana: String,
+}
+
+fn main() {}
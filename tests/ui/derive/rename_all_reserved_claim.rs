@@ -0,0 +1,10 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Claims {
+    tenant_id: String,
+    nonce: String,
+}
+
+fn main() {}
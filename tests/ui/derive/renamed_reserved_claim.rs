@@ -0,0 +1,8 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+struct Claims {
+    #[serde(rename = "iss")] issuer: String,
+}
+
+fn main() {}
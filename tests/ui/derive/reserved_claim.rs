@@ -0,0 +1,9 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+struct Claims {
+    tenant_id: String,
+    sub: String,
+}
+
+fn main() {}
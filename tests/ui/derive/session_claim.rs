@@ -0,0 +1,13 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+struct Claims {
+    #[serde(rename = "sid")] session_id: String,
+}
+
+#[derive(AdditionalClaims, Debug)]
+struct NotBeforeClaims {
+    nbf: i64,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+struct Claims;
+
+fn main() {}
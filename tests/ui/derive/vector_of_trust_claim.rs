@@ -0,0 +1,14 @@
+use openidconnect::AdditionalClaims;
+
+#[derive(AdditionalClaims, Debug)]
+struct Claims {
+    vot: String,
+}
+
+#[derive(AdditionalClaims, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TrustmarkClaims {
+    vtm: String,
+}
+
+fn main() {}